@@ -4,15 +4,31 @@ use std::{
     time::Duration,
 };
 
-use battler::{BattleResult, WorkerMessage, battling_worker_thread, write_results_to_csv};
+use battler::{
+    BINARY_DIRECTORY, BattleAssignment, BattleResult, Corpus, EngineSpec, GAME_LOG_DIRECTORY,
+    WorkerMessage, battling_worker_thread, corpus_assignments, init_logging, print_version_summary,
+    read_corpus,
+    render::{render_decisive_blunder, write_render_summary},
+    sanitize_file_name, write_move_stats_to_csv, write_results_to_csv,
+};
 use clap::Parser;
 use santorini_core::{
+    logging::LogArgs,
     matchup::{Matchup, MatchupArgs},
-    player::Player,
     utils::timestamp_string,
 };
 
 const DEFAULT_DURATION_SECS: f32 = 0.5;
+const RESULTS_CSV_FILE: &str = "tmp/engine_cmp.csv";
+const MOVE_STATS_CSV_FILE: &str = "tmp/engine_cmp_moves.csv";
+
+fn write_csvs(all_results: &[BattleResult]) -> std::io::Result<()> {
+    write_results_to_csv(all_results, &PathBuf::from(RESULTS_CSV_FILE))?;
+    write_move_stats_to_csv(
+        all_results.iter().flat_map(|result| &result.move_stats),
+        &PathBuf::from(MOVE_STATS_CSV_FILE),
+    )
+}
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -24,92 +40,230 @@ struct Args {
     #[arg(short = 's', long, default_value_t = DEFAULT_DURATION_SECS)]
     secs: f32,
 
+    /// Play every enabled corpus position with both colors, instead of every matchup
+    #[arg(long)]
+    corpus: bool,
+
+    /// With --corpus, play positions whose gods aren't in the requested matchups with their own
+    /// gods, instead of skipping them
+    #[arg(long, requires = "corpus")]
+    keep_corpus_gods: bool,
+
+    /// Play an empty board for every matchup with both colors, so that placement is part of the
+    /// game. Reported per position like the corpus, and can be combined with --corpus
+    #[arg(long)]
+    from_empty: bool,
+
+    /// Render the final position of every game next to its log, and write a summary report
+    /// linking them all
+    #[arg(long)]
+    render_finals: bool,
+
+    /// With --render-finals, also review every decided game with this many nodes per position,
+    /// and render the position before the loser's decisive blunder
+    #[arg(long, requires = "render_finals")]
+    review_nodes: Option<usize>,
+
     #[command(flatten)]
     matchups: MatchupArgs,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+/// Renders the decisive blunder of every decided game if asked to, then writes the summary
+/// report of `results` under GAME_LOG_DIRECTORY
+fn write_render_report(
+    results: &[BattleResult],
+    review_nodes: Option<usize>,
+) -> Result<(), String> {
+    if let Some(review_nodes) = review_nodes {
+        for (idx, result) in results.iter().enumerate() {
+            let (Some(game_id), Some(winner)) = (&result.game_id, result.winning_player) else {
+                continue;
+            };
+            log::info!("Reviewing {} ({}/{})", game_id, idx + 1, results.len());
+            if let Err(err) = render_decisive_blunder(game_id, !winner, review_nodes) {
+                log::error!("Failed to render the blunder of {}: {}", game_id, err);
+            }
+        }
+    }
+
+    let path = PathBuf::from(GAME_LOG_DIRECTORY).join(sanitize_file_name(&format!(
+        "engine_cmp-{}.md",
+        timestamp_string()
+    )));
+    write_render_summary(results, &path)?;
+    println!("Wrote report to {}", path.display());
+    Ok(())
+}
+
+/// Wins for each engine and draws, for every named position in the order they were first reported
+fn print_position_table(engine1: &str, engine2: &str, results: &[BattleResult]) {
+    let engine_name = |engine: &str| {
+        PathBuf::new()
+            .join(BINARY_DIRECTORY)
+            .join(engine)
+            .to_str()
+            .unwrap_or_default()
+            .to_owned()
+    };
+    let engine1_name = engine_name(engine1);
+
+    let mut position_wins: Vec<(&str, usize, usize, usize)> = Vec::new();
+    for result in results {
+        let Some(position_name) = &result.position_name else {
+            continue;
+        };
+        let idx = match position_wins
+            .iter()
+            .position(|(name, _, _, _)| name == position_name)
+        {
+            Some(idx) => idx,
+            None => {
+                position_wins.push((position_name, 0, 0, 0));
+                position_wins.len() - 1
+            }
+        };
+        match result.winning_engine() {
+            Some(winner) if winner == engine1_name => position_wins[idx].1 += 1,
+            Some(_) => position_wins[idx].2 += 1,
+            None => position_wins[idx].3 += 1,
+        }
+    }
+
+    println!(
+        "{:<28} {:>10} {:>10} {:>10}",
+        "Position", engine1, engine2, "Draws"
+    );
+    for (position_name, engine1_wins, engine2_wins, draws) in position_wins {
+        println!(
+            "{:<28} {:>10} {:>10} {:>10}",
+            position_name, engine1_wins, engine2_wins, draws
+        );
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    init_logging(&args.log);
 
-    let mut all_matchups = args.matchups.to_selector().get_all();
-    all_matchups.sort();
-    all_matchups.reverse();
-    let matchups_count = all_matchups.len();
+    let engine1 = EngineSpec::new(&args.engine1);
+    let engine2 = EngineSpec::new(&args.engine2);
+    let selector = args.matchups.to_selector();
+    // Named positions are played with both colors by their assignments, rather than by workers
+    let is_positions = args.corpus || args.from_empty;
+    let mut all_assignments: Vec<BattleAssignment> = if is_positions {
+        let mut assignments = Vec::new();
+        if args.corpus {
+            assignments.extend(corpus_assignments(
+                &read_corpus(),
+                &selector,
+                args.keep_corpus_gods,
+                &engine1,
+                &engine2,
+            ));
+        }
+        if args.from_empty {
+            assignments.extend(corpus_assignments(
+                &Corpus::empty_boards(&selector),
+                &selector,
+                false,
+                &engine1,
+                &engine2,
+            ));
+        }
+        assignments
+    } else {
+        let mut all_matchups = selector.get_all();
+        all_matchups.sort();
+        all_matchups
+            .into_iter()
+            .map(|matchup| BattleAssignment::for_matchup(matchup, engine1.clone(), engine2.clone()))
+            .collect()
+    };
+    // Workers pop from the back
+    all_assignments.reverse();
+    // Position assignments already cover both colors. Matchups are played from both sides
+    let total_games = if is_positions {
+        all_assignments.len()
+    } else {
+        all_assignments.len() * 2
+    };
 
     let mut all_results = Vec::<BattleResult>::new();
     let (tx, rx) = mpsc::channel::<WorkerMessage>();
 
     let num_cpus = num_cpus::get();
-    let num_workers = num_cpus / 2;
+    let num_workers = (num_cpus / 2).max(1);
 
-    eprintln!("Starting {} workers", num_workers);
+    log::info!("Starting {} workers", num_workers);
 
-    let all_matchups_queue = Arc::new(Mutex::new(all_matchups));
+    let all_assignments_queue = Arc::new(Mutex::new(all_assignments));
 
     let mut done_workers_count = 0;
     for worker_idx in 0..num_workers {
         let tx = tx.clone();
-        let matchups_queue = Arc::clone(&all_matchups_queue);
-        let engine1 = PathBuf::from(&args.engine1);
-        let engine2 = PathBuf::from(&args.engine2);
+        let assignments_queue = Arc::clone(&all_assignments_queue);
         let duration = Duration::from_secs_f32(args.secs);
+        let render_finals = args.render_finals;
         std::thread::spawn(move || {
-            battling_worker_thread::<true>(
-                worker_idx.to_string(),
-                matchups_queue,
-                &engine1,
-                &engine2,
-                duration,
-                tx.clone(),
-            );
+            let worker_name = format!("compare-{worker_idx}");
+            if is_positions {
+                battling_worker_thread::<false>(
+                    worker_name,
+                    assignments_queue,
+                    duration,
+                    render_finals,
+                    tx.clone(),
+                );
+            } else {
+                battling_worker_thread::<true>(
+                    worker_name,
+                    assignments_queue,
+                    duration,
+                    render_finals,
+                    tx.clone(),
+                );
+            }
             // Sleep a bit to make sure we don't miss anything
             std::thread::sleep(Duration::from_secs(1));
             tx.send(WorkerMessage::Done).unwrap();
         });
     }
 
-    eprintln!("starting {}", timestamp_string());
+    log::info!("starting");
 
     loop {
         let msg = rx.recv()?;
         match msg {
             WorkerMessage::BattleResult(result) => {
-                eprintln!("{}", result.get_pretty_description());
+                log::info!("{}", result.get_pretty_description());
                 all_results.push(result.clone());
-                write_results_to_csv(&all_results, &PathBuf::from("tmp/engine_cmp.csv"))?;
+                write_csvs(&all_results)?;
 
-                eprintln!(
-                    "{} reported: {}/{}",
-                    timestamp_string(),
-                    all_results.len(),
-                    matchups_count * 2
-                );
+                log::info!("reported: {}/{}", all_results.len(), total_games);
             }
             WorkerMessage::BattleResultPair((a, b)) => {
-                eprintln!("{}", a.get_pretty_description());
-                eprintln!("{}", b.get_pretty_description());
-                if a.winning_player != b.winning_player {
-                    let winning_engine = if a.winning_player == Player::One {
-                        &a.engine1
-                    } else {
-                        &a.engine2
-                    };
+                log::info!("{}", a.get_pretty_description());
+                log::info!("{}", b.get_pretty_description());
+                // The engines swap sides between the two games
+                if let (Some(a_winner), Some(b_winner)) = (a.winning_player, b.winning_player)
+                    && a_winner != b_winner
+                    && let Some(winning_engine) = a.winning_engine()
+                {
                     let matchup = Matchup::new(a.god1, a.god2);
-                    eprintln!(
+                    log::info!(
                         "!!! Matchup {} won on both sides by {}",
-                        matchup, winning_engine
+                        matchup,
+                        winning_engine
                     );
                 }
                 all_results.push(a.clone());
                 all_results.push(b.clone());
-                write_results_to_csv(&all_results, &PathBuf::from("tmp/engine_cmp.csv"))?;
+                write_csvs(&all_results)?;
 
-                eprintln!(
-                    "{} reported: {}/{}",
-                    timestamp_string(),
-                    all_results.len(),
-                    matchups_count * 2
-                );
+                log::info!("reported: {}/{}", all_results.len(), total_games);
             }
             WorkerMessage::Done => {
                 done_workers_count += 1;
@@ -120,8 +274,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    if is_positions {
+        print_position_table(&args.engine1, &args.engine2, &all_results);
+    }
+    print_version_summary(&all_results);
+    if args.render_finals {
+        write_render_report(&all_results, args.review_nodes)?;
+    }
+
     Ok(())
 }
+// cargo run -p battler --bin compare_engines -r -- -e v120 -E v121 --corpus
+// cargo run -p battler --bin compare_engines -r --features png -- -e v120 -E v121 --render-finals --review-nodes 20000
+// cargo run -p battler --bin compare_engines -r -- -e v120 -E v121 --from-empty --p1 bia
 // cargo run -p battler --bin compare_engines -r -- -e v119 -E v120 --exclude mortal --p1 stymphalians |& tee compare.txt
 // cargo run -p battler --bin compare_engines -r -- -e v111 -E v112 --p1 chronus
 // cargo run -p battler --bin compare_engines -r -- -e v111 -E v112 --gods chronus athena -s 2.0
@@ -0,0 +1,290 @@
+use std::{
+    collections::HashSet,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::mpsc::RecvTimeoutError,
+    time::{Duration, Instant},
+};
+
+use battler::{
+    BINARY_DIRECTORY, EngineSubprocess, SpawnConfig, init_logging, prepare_subprocess, read_corpus,
+};
+use clap::Parser;
+use santorini_core::{
+    board::FullGameState,
+    fen::game_state_to_fen,
+    logging::LogArgs,
+    search::{BestMoveTrigger, Heuristic},
+    uci_types::{BestMoveOutput, EngineOutput},
+    utils::timestamp_string,
+};
+
+const DEFAULT_NODES: usize = 200_000;
+const DEFAULT_MIN_GAP: Heuristic = 100;
+const DEFAULT_TIMEOUT_SECS: f32 = 60.0;
+const REPORT_FILE: &str = "tmp/disagreements.csv";
+
+#[derive(Parser, Debug)]
+struct Args {
+    #[arg(short = 'e', long)]
+    engine1: String,
+    #[arg(short = 'E', long)]
+    engine2: String,
+
+    /// Node budget given to both engines for every position
+    #[arg(short = 'n', long, default_value_t = DEFAULT_NODES)]
+    nodes: usize,
+
+    /// Only report disagreements where the scores differ by at least this much
+    #[arg(short = 'g', long, default_value_t = DEFAULT_MIN_GAP)]
+    min_gap: Heuristic,
+
+    /// Gamedata files, or files with one FEN per line. Directories are read file by file. Uses
+    /// the enabled corpus positions when empty
+    files: Vec<PathBuf>,
+
+    /// Only use every nth position from the files
+    #[arg(long, default_value_t = 1)]
+    every: usize,
+
+    /// Stop after this many positions
+    #[arg(long)]
+    max_positions: Option<usize>,
+
+    /// Skip a position if an engine hasn't finished its search by then
+    #[arg(long, default_value_t = DEFAULT_TIMEOUT_SECS)]
+    timeout_secs: f32,
+
+    #[arg(short = 'o', long, default_value = REPORT_FILE)]
+    output: PathBuf,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+/// Positions from the corpus, or the first field of every line in the given files
+fn fen_source(args: &Args) -> Box<dyn Iterator<Item = FullGameState>> {
+    if args.files.is_empty() {
+        let positions = read_corpus()
+            .positions
+            .into_iter()
+            .filter(|position| position.is_enabled)
+            .map(|position| position.state);
+        return Box::new(positions);
+    }
+
+    let mut paths = Vec::new();
+    for path in &args.files {
+        if path.is_dir() {
+            let mut dir_paths: Vec<PathBuf> = std::fs::read_dir(path)
+                .expect("Failed to read directory")
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file())
+                .collect();
+            dir_paths.sort();
+            paths.extend(dir_paths);
+        } else {
+            paths.push(path.clone());
+        }
+    }
+
+    let lines = paths.into_iter().flat_map(|path| {
+        let file = std::fs::File::open(&path)
+            .unwrap_or_else(|e| panic!("Failed to open {}: {}", path.display(), e));
+        BufReader::new(file).lines().map_while(Result::ok)
+    });
+    let states = lines
+        .filter_map(|line| {
+            let fen = line.split_whitespace().next()?;
+            match FullGameState::try_from(fen) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    log::warn!("Skipping unparseable FEN {}: {}", fen, e);
+                    None
+                }
+            }
+        })
+        .step_by(args.every.max(1));
+    Box::new(states)
+}
+
+/// Runs a fixed node search and waits for the engine's final answer
+fn search_position(
+    engine: &mut EngineSubprocess,
+    state: &FullGameState,
+    nodes: usize,
+    timeout: Duration,
+) -> Option<BestMoveOutput> {
+    let fen = game_state_to_fen(state);
+    writeln!(engine.stdin, "go {} nodes {}", fen, nodes).expect("Failed to write to stdin");
+
+    let end_at = Instant::now() + timeout;
+    loop {
+        let now = Instant::now();
+        if now >= end_at {
+            log::warn!("{} timed out on {}", engine.engine_name, fen);
+            writeln!(engine.stdin, "stop").expect("Failed to write to stdin");
+            return None;
+        }
+
+        let msg = match engine.receiver.recv_timeout(end_at - now) {
+            Ok(msg) => msg,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(e) => panic!("Error receiving message: {:?}", e),
+        };
+        let Ok(EngineOutput::BestMove(best_move)) = serde_json::from_str(&msg) else {
+            continue;
+        };
+        if best_move.start_state != *state {
+            continue;
+        }
+
+        if matches!(
+            best_move.trigger,
+            BestMoveTrigger::StopFlag
+                | BestMoveTrigger::EndOfLine
+                | BestMoveTrigger::OnlyMove
+                | BestMoveTrigger::Book
+        ) {
+            return Some(best_move);
+        }
+    }
+}
+
+struct Disagreement {
+    fen: String,
+    move1: String,
+    score1: Heuristic,
+    move2: String,
+    score2: Heuristic,
+}
+
+impl Disagreement {
+    fn gap(&self) -> i32 {
+        (self.score1 as i32 - self.score2 as i32).abs()
+    }
+}
+
+fn move_name(best_move: &BestMoveOutput) -> String {
+    best_move
+        .meta
+        .action_str
+        .clone()
+        .unwrap_or_else(|| format!("{:?}", best_move.meta.actions))
+}
+
+fn write_report(disagreements: &[Disagreement], path: &PathBuf) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(["fen", "move1", "score1", "move2", "score2", "gap"])?;
+    for disagreement in disagreements {
+        writer.write_record([
+            disagreement.fen.clone(),
+            disagreement.move1.clone(),
+            disagreement.score1.to_string(),
+            disagreement.move2.clone(),
+            disagreement.score2.to_string(),
+            disagreement.gap().to_string(),
+        ])?;
+    }
+    writer.flush()
+}
+
+fn start_engine(engine: &str, log_name: &str) -> EngineSubprocess {
+    let mut subprocess = prepare_subprocess(
+        &PathBuf::new().join(BINARY_DIRECTORY).join(engine),
+        &SpawnConfig::new(format!("{}-{}.log", log_name, timestamp_string())),
+    )
+    .unwrap_or_else(|err| panic!("Failed to start {}: {}", engine, err));
+    writeln!(subprocess.stdin, "setoption own_book false").expect("Failed to write to stdin");
+    writeln!(subprocess.stdin, "setoption quiet true").expect("Failed to write to stdin");
+    subprocess
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    init_logging(&args.log);
+    let timeout = Duration::from_secs_f32(args.timeout_secs);
+
+    let mut engine1 = start_engine(&args.engine1, "disagree-1");
+    let mut engine2 = start_engine(&args.engine2, "disagree-2");
+
+    let mut seen = HashSet::new();
+    let mut positions_checked = 0;
+    let mut disagreements = Vec::new();
+    for state in fen_source(&args) {
+        if args
+            .max_positions
+            .is_some_and(|max_positions| positions_checked >= max_positions)
+        {
+            break;
+        }
+        if state.board.get_winner().is_some() {
+            continue;
+        }
+        let fen = game_state_to_fen(&state);
+        if !seen.insert(fen.clone()) {
+            continue;
+        }
+
+        let Some(move1) = search_position(&mut engine1, &state, args.nodes, timeout) else {
+            continue;
+        };
+        let Some(move2) = search_position(&mut engine2, &state, args.nodes, timeout) else {
+            continue;
+        };
+        positions_checked += 1;
+
+        if move1.next_state == move2.next_state {
+            continue;
+        }
+        let disagreement = Disagreement {
+            fen,
+            move1: move_name(&move1),
+            score1: move1.meta.score,
+            move2: move_name(&move2),
+            score2: move2.meta.score,
+        };
+        if disagreement.gap() >= args.min_gap as i32 {
+            log::info!(
+                "{} | {}: {} ({}) | {}: {} ({})",
+                disagreement.fen,
+                args.engine1,
+                disagreement.move1,
+                disagreement.score1,
+                args.engine2,
+                disagreement.move2,
+                disagreement.score2
+            );
+            disagreements.push(disagreement);
+        }
+    }
+
+    disagreements.sort_by_key(|disagreement| std::cmp::Reverse(disagreement.gap()));
+    write_report(&disagreements, &args.output)?;
+
+    println!(
+        "Checked {} positions: {} disagreements with a gap of at least {}. Report written to {}",
+        positions_checked,
+        disagreements.len(),
+        args.min_gap,
+        args.output.display()
+    );
+    for disagreement in disagreements.iter().take(10) {
+        println!(
+            "{:>6} {} | {} ({}) vs {} ({})",
+            disagreement.gap(),
+            disagreement.fen,
+            disagreement.move1,
+            disagreement.score1,
+            disagreement.move2,
+            disagreement.score2
+        );
+    }
+
+    Ok(())
+}
+// cargo run -p battler --bin disagree -r -- -e v120 -E v121
+// cargo run -p battler --bin disagree -r -- -e v120 -E v121 ./game_data --every 50 --max-positions 2000 -n 500000
@@ -2,10 +2,14 @@ use std::fmt::Debug;
 use std::path::PathBuf;
 use std::time::Duration;
 
-use battler::{BINARY_DIRECTORY, create_log_dir, do_battle, prepare_subprocess, read_corpus};
+use battler::{
+    BINARY_DIRECTORY, SpawnConfig, TurnTiming, create_log_dir, do_battle_with_timing, init_logging,
+    prepare_subprocess, read_corpus,
+};
 use chrono::Utc;
 use clap::Parser;
 use santorini_core::gods::GodName;
+use santorini_core::logging::LogArgs;
 use santorini_core::player::Player;
 
 const DEFAULT_DURATION_SECS: f32 = 1.0;
@@ -20,9 +24,24 @@ struct FaceoffArgs {
     #[arg(short = 's', long, default_value_t = DEFAULT_DURATION_SECS)]
     secs: f32,
 
+    /// Play with a clock of this many seconds per player instead of a fixed time per turn
+    #[arg(short = 'c', long)]
+    clock_secs: Option<f32>,
+
+    /// Seconds added to a player's clock after each of their moves
+    #[arg(short = 'i', long, default_value_t = 0.0)]
+    increment_secs: f32,
+
+    /// With a clock, forfeit the game of an engine that sends nothing for this many seconds
+    #[arg(short = 'f', long)]
+    forfeit_after_silence_secs: Option<f32>,
+
     #[arg(short = 'g', long)]
     #[arg(short, long)]
     god: Option<GodName>,
+
+    #[command(flatten)]
+    log: LogArgs,
 }
 
 struct SidedPosition {
@@ -40,30 +59,46 @@ fn main() {
     create_log_dir();
 
     let args = FaceoffArgs::parse();
+    init_logging(&args.log);
     let now = Utc::now().format("%Y-%m-%d_%H-%M-%S").to_string();
 
     println!("Game ts: {}", now);
+    let timing = match args.clock_secs {
+        Some(clock_secs) => TurnTiming::Clock {
+            initial: Duration::from_secs_f32(clock_secs),
+            increment: Duration::from_secs_f32(args.increment_secs),
+            forfeit_after_silence: args.forfeit_after_silence_secs.map(Duration::from_secs_f32),
+        },
+        None => TurnTiming::PerTurn(Duration::from_secs_f32(args.secs)),
+    };
+    let timing_name = match args.clock_secs {
+        Some(clock_secs) => format!("{}+{}s", clock_secs, args.increment_secs),
+        None => format!("{}s", args.secs),
+    };
     let game_name = format!(
-        "faceoff-{}-{}-{}s-{}",
-        now, args.engine1, args.engine2, args.secs
+        "faceoff-{}-{}-{}-{}",
+        now, args.engine1, args.engine2, timing_name
     );
 
     let c1_logs_name = format!("{}-{}", game_name, args.engine1);
     let c2_logs_name = format!("{}-{}", game_name, args.engine2);
 
     let mut c1 = prepare_subprocess(
-        &PathBuf::new().join(c1_logs_name),
-        &PathBuf::new().join(BINARY_DIRECTORY).join(args.engine1),
-    );
+        &PathBuf::new().join(BINARY_DIRECTORY).join(&args.engine1),
+        &SpawnConfig::new(c1_logs_name),
+    )
+    .unwrap_or_else(|err| panic!("Failed to start {}: {}", args.engine1, err));
     let mut c2 = prepare_subprocess(
-        &PathBuf::new().join(c2_logs_name),
-        &PathBuf::new().join(BINARY_DIRECTORY).join(args.engine2),
-    );
+        &PathBuf::new().join(BINARY_DIRECTORY).join(&args.engine2),
+        &SpawnConfig::new(c2_logs_name),
+    )
+    .unwrap_or_else(|err| panic!("Failed to start {}: {}", args.engine2, err));
 
     let corpus = read_corpus();
 
     let mut e1_wins: Vec<SidedPosition> = Vec::new();
     let mut e2_wins: Vec<SidedPosition> = Vec::new();
+    let mut draws: Vec<String> = Vec::new();
 
     for position in corpus.positions {
         dbg!(&position);
@@ -79,64 +114,56 @@ fn main() {
         }
 
         {
-            let battle_result_1 = do_battle(
-                &state,
-                &mut c1,
-                &mut c2,
-                Duration::from_secs_f32(args.secs),
-                true,
-            );
-            if battle_result_1.winning_player == Player::One {
-                e1_wins.push(SidedPosition {
+            let battle_result_1 = do_battle_with_timing(&state, &mut c1, &mut c2, timing, true);
+            match battle_result_1.winning_player {
+                Some(Player::One) => e1_wins.push(SidedPosition {
                     name: position.name.clone(),
                     player: Player::One,
-                });
-            } else {
-                e2_wins.push(SidedPosition {
+                }),
+                Some(Player::Two) => e2_wins.push(SidedPosition {
                     name: position.name.clone(),
                     player: Player::Two,
-                });
+                }),
+                None => draws.push(position.name.clone()),
             }
         }
         println!(
-            "Current score. E1: {}. E2: {}",
+            "Current score. E1: {}. E2: {}. Draws: {}",
             e1_wins.len(),
             e2_wins.len(),
+            draws.len(),
         );
 
         {
-            let battle_result_2 = do_battle(
-                &state,
-                &mut c2,
-                &mut c1,
-                Duration::from_secs_f32(args.secs),
-                true,
-            );
-            if battle_result_2.winning_player == Player::One {
-                e2_wins.push(SidedPosition {
+            let battle_result_2 = do_battle_with_timing(&state, &mut c2, &mut c1, timing, true);
+            match battle_result_2.winning_player {
+                Some(Player::One) => e2_wins.push(SidedPosition {
                     name: position.name.clone(),
                     player: Player::One,
-                });
-            } else {
-                e1_wins.push(SidedPosition {
+                }),
+                Some(Player::Two) => e1_wins.push(SidedPosition {
                     name: position.name.clone(),
                     player: Player::Two,
-                });
+                }),
+                None => draws.push(position.name.clone()),
             }
         }
         println!(
-            "Current score. E1: {}. E2: {}",
+            "Current score. E1: {}. E2: {}. Draws: {}",
             e1_wins.len(),
             e2_wins.len(),
+            draws.len(),
         );
     }
 
     println!("E1 wins: {:?}", e1_wins);
     println!("E2 wins: {:?}", e2_wins);
+    println!("Draws: {:?}", draws);
     println!(
-        "Overall, e1_wins: {} e2_wins: {}",
+        "Overall, e1_wins: {} e2_wins: {} draws: {}",
         e1_wins.len(),
-        e2_wins.len()
+        e2_wins.len(),
+        draws.len()
     );
 
     let _ = c1.child.kill();
@@ -144,3 +171,4 @@ fn main() {
 }
 
 // cargo run -p battler --bin faceoff -- -e v1 -E v18
+// cargo run -p battler --bin faceoff -- -e v1 -E v18 --clock-secs 60 --increment-secs 1
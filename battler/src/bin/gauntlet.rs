@@ -0,0 +1,222 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
+
+use battler::{
+    BattleAssignment, BattleResult, WorkerMessage, battling_worker_thread, create_tmp_dir,
+    elo_estimate, init_logging, write_move_stats_to_csv, write_results_to_csv,
+};
+use clap::Parser;
+use rand::{SeedableRng, rngs::StdRng};
+use santorini_core::{
+    logging::LogArgs,
+    matchup::{BalancedSampler, MatchupArgs},
+};
+
+const DEFAULT_DURATION_SECS: f32 = 0.5;
+const RESULTS_CSV_FILE: &str = "tmp/gauntlet.csv";
+const MOVE_STATS_CSV_FILE: &str = "tmp/gauntlet_moves.csv";
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The engine being tested
+    #[arg(short = 'c', long)]
+    candidate: String,
+
+    /// Engines to play the candidate against, comma separated
+    #[arg(short = 'o', long, value_delimiter = ',', num_args = 1..)]
+    opponents: Vec<String>,
+
+    /// Games against each opponent. Every matchup is played from both sides, so this is rounded
+    /// up to an even number
+    #[arg(short = 'n', long, default_value_t = 100)]
+    games_per_pair: usize,
+
+    #[arg(short = 's', long, default_value_t = DEFAULT_DURATION_SECS)]
+    secs: f32,
+
+    /// Seed for picking matchups. Every opponent plays the same matchups
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    #[command(flatten)]
+    matchups: MatchupArgs,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct GauntletRecord {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+}
+
+impl GauntletRecord {
+    fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games().max(1) as f64
+    }
+}
+
+fn print_table(candidate: &str, opponents: &[String], records: &HashMap<String, GauntletRecord>) {
+    println!("Candidate: {}", candidate);
+    println!(
+        "{:<24} {:>6} {:>6} {:>6} {:>6} {:>7} {:>7}",
+        "Opponent", "Games", "Wins", "Draws", "Losses", "Score", "Elo"
+    );
+
+    let mut total = GauntletRecord::default();
+    for opponent in opponents {
+        let record = records.get(opponent).copied().unwrap_or_default();
+        total.wins += record.wins;
+        total.draws += record.draws;
+        total.losses += record.losses;
+        print_row(opponent, &record);
+    }
+    print_row("All opponents", &total);
+}
+
+fn print_row(name: &str, record: &GauntletRecord) {
+    let (elo, margin) = elo_estimate(record.wins, record.draws, record.losses);
+    println!(
+        "{:<24} {:>6} {:>6} {:>6} {:>6} {:>6.1}% {:>+7.0} ± {:<4.0}",
+        name,
+        record.games(),
+        record.wins,
+        record.draws,
+        record.losses,
+        record.score() * 100.0,
+        elo,
+        margin
+    );
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    init_logging(&args.log);
+    if args.opponents.is_empty() {
+        return Err("At least one opponent is required".into());
+    }
+    if args.opponents.contains(&args.candidate) {
+        return Err("The candidate can't also be an opponent".into());
+    }
+    create_tmp_dir();
+
+    // Pick the matchups once, so that every opponent is measured on the same games
+    let mut sampler = BalancedSampler::new(&args.matchups.to_selector())?;
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut pair_matchups = Vec::new();
+    for _ in 0..args.games_per_pair.div_ceil(2) {
+        let matchup = sampler.sample(&mut rng);
+        sampler.record_game(matchup);
+        pair_matchups.push(matchup);
+    }
+
+    // Workers pop from the back. Grouping the queue by opponent keeps workers on the same
+    // opponent for as long as possible, since switching means restarting a subprocess
+    let candidate = PathBuf::from(&args.candidate);
+    let mut all_assignments = Vec::new();
+    for opponent in args.opponents.iter().rev() {
+        for matchup in &pair_matchups {
+            all_assignments.push(BattleAssignment::for_matchup(
+                *matchup,
+                candidate.clone(),
+                PathBuf::from(opponent),
+            ));
+        }
+    }
+    let total_games = all_assignments.len() * 2;
+    let all_assignments_queue = Arc::new(Mutex::new(all_assignments));
+
+    let (tx, rx) = mpsc::channel::<WorkerMessage>();
+    let num_workers = (num_cpus::get() / 2).max(1);
+    log::info!("Starting {} workers", num_workers);
+
+    for worker_idx in 0..num_workers {
+        let tx = tx.clone();
+        let assignments_queue = Arc::clone(&all_assignments_queue);
+        let duration = Duration::from_secs_f32(args.secs);
+        std::thread::spawn(move || {
+            battling_worker_thread::<true>(
+                format!("gauntlet-{worker_idx}"),
+                assignments_queue,
+                duration,
+                false,
+                tx.clone(),
+            );
+            tx.send(WorkerMessage::Done).unwrap();
+        });
+    }
+
+    log::info!("starting");
+
+    // Results name engines by their path, while opponents are given by binary name
+    let opponent_by_engine_name: HashMap<String, String> = args
+        .opponents
+        .iter()
+        .map(|opponent| {
+            let engine_name = PathBuf::new()
+                .join(battler::BINARY_DIRECTORY)
+                .join(opponent)
+                .to_str()
+                .unwrap_or_default()
+                .to_owned();
+            (engine_name, opponent.clone())
+        })
+        .collect();
+
+    let mut records: HashMap<String, GauntletRecord> = HashMap::new();
+    let mut all_results = Vec::<BattleResult>::new();
+    let mut done_workers_count = 0;
+    loop {
+        let results = match rx.recv()? {
+            WorkerMessage::BattleResult(result) => vec![result],
+            WorkerMessage::BattleResultPair((a, b)) => vec![a, b],
+            WorkerMessage::Done => {
+                done_workers_count += 1;
+                if done_workers_count >= num_workers {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        for result in results {
+            log::info!("{}", result.get_pretty_description());
+            let opponent_engine = if opponent_by_engine_name.contains_key(&result.engine1) {
+                &result.engine1
+            } else {
+                &result.engine2
+            };
+            let opponent = &opponent_by_engine_name[opponent_engine];
+            let record = records.entry(opponent.clone()).or_default();
+            match result.winning_engine() {
+                Some(winner) if winner == opponent_engine => record.losses += 1,
+                Some(_) => record.wins += 1,
+                None => record.draws += 1,
+            }
+            all_results.push(result);
+        }
+
+        write_results_to_csv(&all_results, &PathBuf::from(RESULTS_CSV_FILE))?;
+        write_move_stats_to_csv(
+            all_results.iter().flat_map(|result| &result.move_stats),
+            &PathBuf::from(MOVE_STATS_CSV_FILE),
+        )?;
+        log::info!("reported: {}/{}", all_results.len(), total_games);
+    }
+
+    print_table(&args.candidate, &args.opponents, &records);
+
+    Ok(())
+}
+
+// cargo run -p battler --bin gauntlet -r -- -c v121 -o v118,v119,v120 -n 100 -s 2.0
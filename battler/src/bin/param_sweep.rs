@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
+
+use battler::{
+    BattleAssignment, BattleResult, EngineSpec, WorkerMessage, battling_worker_thread,
+    create_tmp_dir, elo_estimate, init_logging, write_move_stats_to_csv, write_results_to_csv,
+};
+use clap::Parser;
+use rand::{SeedableRng, rngs::StdRng};
+use santorini_core::{
+    logging::LogArgs,
+    matchup::{BalancedSampler, MatchupArgs},
+    search::SearchParams,
+};
+
+const DEFAULT_DURATION_SECS: f32 = 0.5;
+const RESULTS_CSV_FILE: &str = "tmp/param_sweep.csv";
+const MOVE_STATS_CSV_FILE: &str = "tmp/param_sweep_moves.csv";
+
+/// Plays a round robin between copies of one engine binary, each with a different value of a
+/// search param
+#[derive(Parser, Debug)]
+struct Args {
+    /// The engine binary every virtual engine runs
+    #[arg(short = 'e', long)]
+    engine: String,
+
+    /// The search param to vary, as named by `setoption param`
+    #[arg(short = 'p', long)]
+    param: String,
+
+    /// Values to try, comma separated
+    #[arg(short = 'v', long, value_delimiter = ',', num_args = 2..)]
+    values: Vec<String>,
+
+    /// Games between each pair of values. Every matchup is played from both sides, so this is
+    /// rounded up to an even number
+    #[arg(short = 'n', long, default_value_t = 100)]
+    games_per_pair: usize,
+
+    #[arg(short = 's', long, default_value_t = DEFAULT_DURATION_SECS)]
+    secs: f32,
+
+    /// Seed for picking matchups. Every pair of values plays the same matchups
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    #[command(flatten)]
+    matchups: MatchupArgs,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct SweepRecord {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+}
+
+impl SweepRecord {
+    fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games().max(1) as f64
+    }
+}
+
+fn print_table(args: &Args, records: &HashMap<String, SweepRecord>) {
+    println!("Engine: {}, param: {}", args.engine, args.param);
+    println!(
+        "{:<16} {:>6} {:>6} {:>6} {:>6} {:>7} {:>7}",
+        "Value", "Games", "Wins", "Draws", "Losses", "Score", "Elo"
+    );
+    for value in &args.values {
+        let record = records.get(value).copied().unwrap_or_default();
+        let (elo, margin) = elo_estimate(record.wins, record.draws, record.losses);
+        println!(
+            "{:<16} {:>6} {:>6} {:>6} {:>6} {:>6.1}% {:>+7.0} ± {:<4.0}",
+            value,
+            record.games(),
+            record.wins,
+            record.draws,
+            record.losses,
+            record.score() * 100.0,
+            elo,
+            margin
+        );
+    }
+    println!("Elo is against the other values combined");
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    init_logging(&args.log);
+    // Engines only log bad options, so catch them here rather than playing identical engines
+    for value in &args.values {
+        SearchParams::default().set(&args.param, value)?;
+    }
+    for (idx, value) in args.values.iter().enumerate() {
+        if args.values[..idx].contains(value) {
+            return Err(format!("Value {} is listed more than once", value).into());
+        }
+    }
+    create_tmp_dir();
+
+    let engines: Vec<EngineSpec> = args
+        .values
+        .iter()
+        .map(|value| {
+            EngineSpec::new(&args.engine)
+                .with_init_command(format!("setoption param {}={}", args.param, value))
+                .with_label(format!("{}={}", args.param, value))
+        })
+        .collect();
+    let value_by_engine_name: HashMap<String, String> = engines
+        .iter()
+        .zip(&args.values)
+        .map(|(engine, value)| (engine.engine_name(), value.clone()))
+        .collect();
+
+    // Pick the matchups once, so that every pair is measured on the same games
+    let mut sampler = BalancedSampler::new(&args.matchups.to_selector())?;
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut pair_matchups = Vec::new();
+    for _ in 0..args.games_per_pair.div_ceil(2) {
+        let matchup = sampler.sample(&mut rng);
+        sampler.record_game(matchup);
+        pair_matchups.push(matchup);
+    }
+
+    // Workers pop from the back. Grouping the queue by pair keeps workers on the same engines for
+    // as long as possible, since switching means restarting a subprocess
+    let mut all_assignments = Vec::new();
+    for (idx, first) in engines.iter().enumerate() {
+        for second in &engines[idx + 1..] {
+            for matchup in &pair_matchups {
+                all_assignments.push(BattleAssignment::for_matchup(
+                    *matchup,
+                    first.clone(),
+                    second.clone(),
+                ));
+            }
+        }
+    }
+    let total_games = all_assignments.len() * 2;
+    let all_assignments_queue = Arc::new(Mutex::new(all_assignments));
+
+    let (tx, rx) = mpsc::channel::<WorkerMessage>();
+    let num_workers = (num_cpus::get() / 2).max(1);
+    log::info!("Starting {} workers", num_workers);
+
+    for worker_idx in 0..num_workers {
+        let tx = tx.clone();
+        let assignments_queue = Arc::clone(&all_assignments_queue);
+        let duration = Duration::from_secs_f32(args.secs);
+        std::thread::spawn(move || {
+            battling_worker_thread::<true>(
+                format!("sweep-{worker_idx}"),
+                assignments_queue,
+                duration,
+                false,
+                tx.clone(),
+            );
+            tx.send(WorkerMessage::Done).unwrap();
+        });
+    }
+
+    log::info!("starting");
+
+    let mut records: HashMap<String, SweepRecord> = HashMap::new();
+    let mut all_results = Vec::<BattleResult>::new();
+    let mut done_workers_count = 0;
+    loop {
+        let results = match rx.recv()? {
+            WorkerMessage::BattleResult(result) => vec![result],
+            WorkerMessage::BattleResultPair((a, b)) => vec![a, b],
+            WorkerMessage::Done => {
+                done_workers_count += 1;
+                if done_workers_count >= num_workers {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        for result in results {
+            log::info!("{}", result.get_pretty_description());
+            let winner = result.winning_engine();
+            for engine in [&result.engine1, &result.engine2] {
+                let Some(value) = value_by_engine_name.get(engine) else {
+                    log::error!("Result from unknown engine {}", engine);
+                    continue;
+                };
+                let record = records.entry(value.clone()).or_default();
+                match winner {
+                    Some(winner) if winner == engine => record.wins += 1,
+                    Some(_) => record.losses += 1,
+                    None => record.draws += 1,
+                }
+            }
+            all_results.push(result);
+        }
+
+        write_results_to_csv(&all_results, &PathBuf::from(RESULTS_CSV_FILE))?;
+        write_move_stats_to_csv(
+            all_results.iter().flat_map(|result| &result.move_stats),
+            &PathBuf::from(MOVE_STATS_CSV_FILE),
+        )?;
+        log::info!("reported: {}/{}", all_results.len(), total_games);
+    }
+
+    print_table(&args, &records);
+
+    Ok(())
+}
+
+// cargo run -p battler --bin param_sweep -r -- -e v121 -p stable_move_min_depths -v 4,6,8,10 -n 50
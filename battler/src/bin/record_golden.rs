@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use battler::{GameLogEntry, init_logging, read_game_log};
+use clap::Parser;
+use santorini_core::{board::FullGameState, golden_game::GoldenGame, logging::LogArgs};
+
+/// Converts battler game logs into golden games for santorini_core's regression suite
+#[derive(Parser, Debug)]
+struct Args {
+    /// Game logs to record, as written under logs/games by the battler
+    #[arg(required = true)]
+    logs: Vec<PathBuf>,
+
+    /// Where to write the golden games. Existing games are never overwritten
+    #[arg(short = 'o', long, default_value = "santorini_core/tests/golden_games")]
+    out_dir: PathBuf,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+/// The canonical notation of the move played from `state`. Engines don't always use the same
+/// notation as `describe_moves` (a stuck player reports `NULL`), so the move is found by the
+/// position it reached where possible
+fn resolve_move_str(
+    state: &FullGameState,
+    next_state: Option<&FullGameState>,
+    action_str: Option<&str>,
+) -> Option<String> {
+    let descriptions = state.describe_moves();
+    let description = descriptions
+        .iter()
+        .find(|description| Some(&description.next_state) == next_state)
+        .or_else(|| {
+            descriptions
+                .iter()
+                .find(|description| Some(description.action_str.as_str()) == action_str)
+        })
+        .or_else(|| match descriptions.as_slice() {
+            [forced] => Some(forced),
+            _ => None,
+        })?;
+    Some(description.action_str.clone())
+}
+
+fn golden_game_from_log(path: &Path) -> Result<GoldenGame, String> {
+    let entries = read_game_log(&path.to_path_buf())
+        .map_err(|err| format!("Failed to read game log: {}", err))?;
+
+    let mut header = None;
+    let mut played = Vec::new();
+    let mut winner = None;
+    for entry in entries {
+        match entry {
+            GameLogEntry::Header {
+                god1,
+                engine1,
+                god2,
+                engine2,
+                start_state,
+                ..
+            } => header = Some((god1, engine1, god2, engine2, start_state)),
+            GameLogEntry::Move {
+                state, action_str, ..
+            } => played.push((state, action_str)),
+            GameLogEntry::Footer { winning_player, .. } => winner = Some(winning_player),
+        }
+    }
+
+    let (god1, engine1, god2, engine2, start_state) = header.ok_or("Missing header")?;
+    let mut moves = Vec::new();
+    for (idx, (state, action_str)) in played.iter().enumerate() {
+        let next_state = played.get(idx + 1).map(|(next_state, _)| next_state);
+        let move_str = resolve_move_str(state, next_state, action_str.as_deref())
+            .ok_or_else(|| format!("Couldn't resolve move {} ({:?})", idx + 1, action_str))?;
+        moves.push(move_str);
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let game = GoldenGame {
+        comments: vec![
+            format!("{} vs {}", god1, god2),
+            format!("Recorded from {}: {} vs {}", file_name, engine1, engine2),
+        ],
+        start_state,
+        moves,
+        winner: winner
+            .ok_or("Missing footer, was the game finished?")?
+            .ok_or("Drawn games have no winner to replay to")?,
+    };
+    game.replay()?;
+    Ok(game)
+}
+
+/// The first `<god1>-vs-<god2>[-n].txt` path in `out_dir` that isn't taken yet
+fn free_golden_game_path(out_dir: &Path, game: &GoldenGame) -> PathBuf {
+    let [god1, god2] = game.start_state.gods.map(|god| god.god_name);
+    let base = format!("{}-vs-{}", god1, god2);
+    let mut path = out_dir.join(format!("{}.txt", base));
+    let mut idx = 2;
+    while path.exists() {
+        path = out_dir.join(format!("{}-{}.txt", base, idx));
+        idx += 1;
+    }
+    path
+}
+
+fn main() {
+    let args = Args::parse();
+    init_logging(&args.log);
+    if let Err(err) = std::fs::create_dir_all(&args.out_dir) {
+        log::error!("Failed to create {}: {}", args.out_dir.display(), err);
+        std::process::exit(1);
+    }
+
+    let mut failures = 0;
+    for log_path in &args.logs {
+        let game = match golden_game_from_log(log_path) {
+            Ok(game) => game,
+            Err(err) => {
+                log::warn!("Skipping {}: {}", log_path.display(), err);
+                failures += 1;
+                continue;
+            }
+        };
+
+        let out_path = free_golden_game_path(&args.out_dir, &game);
+        match std::fs::write(&out_path, game.to_string()) {
+            Ok(()) => println!("{} -> {}", log_path.display(), out_path.display()),
+            Err(err) => {
+                log::error!("Failed to write {}: {}", out_path.display(), err);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+// cargo run -p battler --bin record_golden -- logs/games/*.jsonl
@@ -6,14 +6,17 @@ use std::{
 };
 
 use battler::{
-    BattleResult, WorkerMessage, create_tmp_dir, read_battle_result_csv, write_results_to_csv,
+    BattleResult, WorkerMessage, create_tmp_dir, init_logging, read_battle_result_csv,
+    write_results_to_csv,
 };
 use clap::Parser;
 use santorini_core::{
     board::FullGameState,
     engine::EngineThreadWrapper,
+    logging::LogArgs,
     matchup::{Matchup, MatchupArgs},
-    utils::timestamp_string,
+    uci_types::engine_version,
+    win_reason::get_win_reason,
 };
 
 const DEFAULT_DURATION_SECS: f32 = 4.0;
@@ -29,6 +32,9 @@ struct Args {
 
     #[command(flatten)]
     matchups: MatchupArgs,
+
+    #[command(flatten)]
+    log: LogArgs,
 }
 
 fn _read_battle_results_csv() -> Vec<BattleResult> {
@@ -62,12 +68,7 @@ fn worker_thread(
             current_thread.name().unwrap_or("unknown").to_string()
         };
 
-        eprintln!(
-            "{}: starting matchup {} {}",
-            thread_name,
-            timestamp_string(),
-            next_matchup
-        );
+        log::info!("{}: starting matchup {}", thread_name, next_matchup);
 
         let root_state = FullGameState::new_for_matchup(&next_matchup);
         let battle_result = playout_game(&root_state, &mut engine, duration).unwrap();
@@ -83,6 +84,7 @@ fn playout_game(
     duration: Duration,
 ) -> Result<BattleResult, String> {
     let mut current_state = root_state.clone();
+    let mut prev_state: Option<FullGameState> = None;
 
     let mut moves_made = 0;
     loop {
@@ -92,28 +94,40 @@ fn playout_game(
                 god2: root_state.gods[1].god_name,
                 engine1: "latest".to_string(),
                 engine2: "latest".to_string(),
-                winning_player: winner,
+                engine1_version: engine_version(),
+                engine2_version: engine_version(),
+                winning_player: Some(winner),
                 moves_made,
+                win_reason: prev_state
+                    .as_ref()
+                    .and_then(|prev_state| get_win_reason(prev_state, &current_state)),
+                draw_reason: None,
+                position_name: None,
+                game_id: None,
+                move_stats: Vec::new(),
             });
         }
 
         let best_move = engine
             .search_for_duration(&current_state, duration.as_secs_f32())
             .map_err(|err| format!("Error in search on state: {:?}, {:?}", current_state, err))?;
-        current_state = best_move.child_state;
-        moves_made += 1;
+        if !current_state.is_placement_phase() {
+            moves_made += 1;
+        }
+        prev_state = Some(std::mem::replace(&mut current_state, best_move.child_state));
     }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     create_tmp_dir();
     let args = Args::parse();
+    init_logging(&args.log);
 
     let mut all_matchups = get_all_matchups(&args);
     let mut all_results = Vec::<BattleResult>::new();
 
     if args.cont {
-        eprintln!("Cont mode - continue from previous run");
+        log::info!("Cont mode - continue from previous run");
         let completed_results = read_battle_result_csv(&PathBuf::from(MATCHUPS_CSV_FILE))?;
         all_results = completed_results.clone();
 
@@ -129,7 +143,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     all_matchups.sort();
     all_matchups.reverse();
     for m in &all_matchups {
-        eprintln!("{m}");
+        log::debug!("{m}");
     }
     let matchups_count = all_matchups.len();
 
@@ -138,7 +152,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let num_cpus = num_cpus::get();
     let num_workers = num_cpus - 1;
 
-    eprintln!("Starting {} workers", num_workers);
+    log::info!("Starting {} workers", num_workers);
 
     let all_matchups_queue = Arc::new(Mutex::new(all_matchups));
 
@@ -159,19 +173,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .expect(format!("failed to spawn thread {}", i).as_str());
     }
 
-    eprintln!("starting {}", timestamp_string());
+    log::info!("starting");
 
     loop {
         let msg = rx.recv()?;
         match msg {
             WorkerMessage::BattleResult(result) => {
-                eprintln!("{}", result.get_pretty_description());
+                log::info!("{}", result.get_pretty_description());
                 all_results.push(result.clone());
                 write_results_to_csv(&all_results, &PathBuf::from(MATCHUPS_CSV_FILE))?;
 
-                eprintln!(
-                    "{} reported: {}/{}",
-                    timestamp_string(),
+                log::info!(
+                    "reported: {}/{}",
                     all_results.len(),
                     matchups_count + base_results,
                 );
@@ -5,11 +5,13 @@ use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::time::{Duration, Instant};
 use std::{fs, thread};
 
+use battler::{describe_spawn_error, resolve_engine_path};
 use chrono::Utc;
 use clap::Parser;
 use santorini_core::board::FullGameState;
 use santorini_core::fen::game_state_to_fen;
 use santorini_core::gods::GodName;
+use santorini_core::logging::LogArgs;
 use santorini_core::player::Player;
 use santorini_core::search::BestMoveTrigger;
 use santorini_core::uci_types::{BestMoveOutput, EngineOutput};
@@ -28,12 +30,13 @@ struct EngineSubprocess {
 fn prepare_subprocess(log_path: &Path, engine_path: &str) -> EngineSubprocess {
     let stderr_file = std::fs::File::create(log_path).expect("Failed to create error log file");
 
-    let mut child = Command::new(engine_path)
+    let binary_path = resolve_engine_path(Path::new(engine_path));
+    let mut child = Command::new(&binary_path)
         .stdin(Stdio::piped())
         .stderr(std::process::Stdio::from(stderr_file))
         .stdout(Stdio::piped())
         .spawn()
-        .expect("Failed to spawn process");
+        .unwrap_or_else(|err| panic!("{}", describe_spawn_error(&binary_path, &err)));
 
     let stdin = child.stdin.take().expect("Failed to open stdin");
     let stdout = child.stdout.take().expect("Failed to open stdout");
@@ -48,7 +51,7 @@ fn prepare_subprocess(log_path: &Path, engine_path: &str) -> EngineSubprocess {
                     child_msg_tx.send(line).unwrap();
                 }
                 Err(e) => {
-                    eprintln!("Error reading line: {}", e);
+                    log::error!("Error reading line: {}", e);
                     break;
                 }
             }
@@ -115,7 +118,8 @@ fn do_battle<'a>(
     println!();
 
     loop {
-        let (engine, other, conf) = match current_state.board.current_player {
+        let (engine, other, conf) = match current_state.get_current_player_consider_placement_mode()
+        {
             Player::One => (&mut *c1, &mut *c2, &conf1),
             Player::Two => (&mut *c2, &mut *c1, &conf2),
         };
@@ -124,7 +128,7 @@ fn do_battle<'a>(
         writeln!(other.stdin, "stop").expect("Failed to write to stdin");
 
         let state_string = game_state_to_fen(&current_state);
-        eprintln!("set_position {}", state_string);
+        log::debug!("set_position {}", state_string);
         writeln!(engine.stdin, "set_position {}", state_string).expect("Failed to write to stdin");
 
         let started_at = Instant::now();
@@ -147,15 +151,25 @@ fn do_battle<'a>(
                                 // println!("Message for wrong state");
                                 continue;
                             }
-                            let is_eol = best_move.trigger == BestMoveTrigger::EndOfLine;
+                            let trigger = best_move.trigger.clone();
                             saved_best_move = Some(best_move);
-                            if is_eol {
+                            if trigger == BestMoveTrigger::EndOfLine {
                                 println!("Mate found, ending early");
                                 break;
+                            } else if trigger == BestMoveTrigger::Stable {
+                                println!("Best move is stable, ending early");
+                                break;
+                            } else if trigger == BestMoveTrigger::OnlyMove {
+                                println!("Only one legal move, ending early");
+                                break;
+                            } else if trigger == BestMoveTrigger::Book {
+                                println!("Book placement, ending early");
+                                break;
                             }
                         }
+                        EngineOutput::Info(_) => (),
                         _ => {
-                            eprintln!("Unexpected message: {:?}", parsed_msg);
+                            log::warn!("Unexpected message: {:?}", parsed_msg);
                         }
                     }
                 }
@@ -258,6 +272,9 @@ struct BattlerCliArgs {
 
     #[arg(short = 'b', long)]
     board: Option<String>,
+
+    #[command(flatten)]
+    log: LogArgs,
 }
 
 #[derive(Debug, Clone)]
@@ -270,7 +287,10 @@ struct BattlingPlayerConfig {
 fn _resolve_engine(name: Option<&str>) -> String {
     match name.as_deref() {
         Some("latest") | None => _get_latest_updated_version(),
-        Some(path) => format!("all_versions/{}", path),
+        Some(path) => Path::new(BINARY_DIRECTORY)
+            .join(path)
+            .to_string_lossy()
+            .into_owned(),
     }
 }
 
@@ -313,6 +333,7 @@ fn massage_inputs(args: &BattlerCliArgs) -> (BattlingPlayerConfig, BattlingPlaye
 
 fn main() {
     let args = BattlerCliArgs::parse();
+    args.log.init();
     let (mut conf1, mut conf2) = massage_inputs(&args);
 
     let state = match args.board {
@@ -323,13 +344,11 @@ fn main() {
                 state
             }
             Err(e) => {
-                eprintln!("Error parsing FEN: {}", e);
+                log::error!("Error parsing FEN: {}", e);
                 return;
             }
         },
-        None => {
-            FullGameState::new_empty_state(conf1.god, conf2.god)
-        }
+        None => FullGameState::new_empty_state(conf1.god, conf2.god),
     };
 
     if state.board.get_winner().is_some() {
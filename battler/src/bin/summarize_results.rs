@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use battler::{BattleResult, print_version_summary, read_battle_result_csv};
+use clap::Parser;
+
+/// Wins for every engine version across one or more battler results CSVs
+#[derive(Parser, Debug)]
+struct Args {
+    /// Results CSVs, like tmp/engine_cmp.csv or tmp/gauntlet.csv
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let mut results = Vec::<BattleResult>::new();
+    for path in &args.paths {
+        let path_results = read_battle_result_csv(path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        results.extend(path_results);
+    }
+
+    print_version_summary(&results);
+    Ok(())
+}
+
+// cargo run -p battler --bin summarize_results -r -- tmp/engine_cmp.csv tmp/gauntlet.csv
@@ -1,27 +1,35 @@
-use std::process::{Child, ChildStdin, Command, Stdio};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 
 use csv::Writer;
-use santorini_core::board::FullGameState;
+use santorini_core::board::{ConsoleRenderOptions, FullGameState, TerminalResult};
 use santorini_core::fen::game_state_to_fen;
 use santorini_core::gods::GodName;
-use santorini_core::matchup::Matchup;
+use santorini_core::logging::LogArgs;
+use santorini_core::matchup::{Matchup, MatchupSelector};
 use santorini_core::player::Player;
-use santorini_core::search::BestMoveTrigger;
+use santorini_core::search::{BestMoveTrigger, Heuristic};
 use santorini_core::utils::timestamp_string;
+use santorini_core::win_reason::{WinReason, get_win_reason};
 use serde::{Deserialize, Serialize};
 
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use santorini_core::uci_types::{BestMoveOutput, EngineOutput};
+use santorini_core::uci_types::{BestMoveOutput, DrawReason, EngineOutput, GameResult};
+
+pub mod render;
 
 const CORPUS_FILE_PATH: &str = "data/corpus.yaml";
 pub const BINARY_DIRECTORY: &str = "all_versions";
+pub const GAME_LOG_DIRECTORY: &str = "logs/games";
 
 fn _true_value() -> bool {
     true
@@ -36,11 +44,36 @@ pub struct StartingPosition {
     pub is_enabled: bool,
 }
 
+impl StartingPosition {
+    /// An empty board for `matchup`, so that the engines play the placements too
+    pub fn empty_board(matchup: Matchup) -> Self {
+        StartingPosition {
+            name: format!("empty_{}", matchup),
+            state: FullGameState::new_for_matchup(&matchup),
+            notes: "Empty board, starting from placement".to_owned(),
+            is_enabled: true,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Corpus {
     pub positions: Vec<StartingPosition>,
 }
 
+impl Corpus {
+    /// An empty board start for every matchup allowed by `selector`
+    pub fn empty_boards(selector: &MatchupSelector) -> Self {
+        Corpus {
+            positions: selector
+                .get_all()
+                .into_iter()
+                .map(StartingPosition::empty_board)
+                .collect(),
+        }
+    }
+}
+
 pub fn write_corpus(corpus: &Corpus) {
     let toml_string = serde_yaml::to_string(corpus).expect("Failed to serialize corpus");
     std::fs::write(CORPUS_FILE_PATH, toml_string).expect("Failed to write corpus to file");
@@ -66,33 +99,227 @@ pub fn create_tmp_dir() {
     std::fs::create_dir_all(&path).expect("Failed to create logs directory");
 }
 
+thread_local! {
+    /// The log file of the battling worker running on this thread, if any
+    static WORKER_LOG_FILE: RefCell<Option<std::fs::File>> = const { RefCell::new(None) };
+}
+
+/// Writes log records to the current thread's worker log file, or to stderr from any thread that
+/// isn't a battling worker
+struct WorkerLogWriter;
+
+impl Write for WorkerLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        WORKER_LOG_FILE.with_borrow_mut(|file| match file {
+            Some(file) => file.write(buf),
+            None => std::io::stderr().write(buf),
+        })
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        WORKER_LOG_FILE.with_borrow_mut(|file| match file {
+            Some(file) => file.flush(),
+            None => std::io::stderr().flush(),
+        })
+    }
+}
+
+/// Installs the global logger for battler binaries. Records from battling workers go to each
+/// worker's own file in the logs directory, so that parallel games don't interleave
+pub fn init_logging(args: &LogArgs) {
+    args.builder()
+        .target(env_logger::Target::Pipe(Box::new(WorkerLogWriter)))
+        .init();
+}
+
+/// Sends this thread's log records to a new file named after `worker_name`
+fn start_worker_log(worker_name: &str) {
+    let path = PathBuf::from("logs").join(sanitize_file_name(&format!(
+        "{}-{}.log",
+        worker_name,
+        timestamp_string()
+    )));
+    let file = std::fs::create_dir_all("logs").and_then(|_| std::fs::File::create(&path));
+    match file {
+        Ok(file) => WORKER_LOG_FILE.set(Some(file)),
+        Err(err) => log::error!("Failed to create worker log {}: {}", path.display(), err),
+    }
+}
+
+/// How long an engine gets to report that it started, unless configured otherwise
+pub const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How to start an engine subprocess
+#[derive(Clone, Debug)]
+pub struct SpawnConfig {
+    /// Where the engine's stderr goes, relative to the logs directory. Sanitized with
+    /// `sanitize_file_name`, so it can't name a subdirectory
+    pub log_path: PathBuf,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub startup_timeout: Duration,
+}
+
+impl SpawnConfig {
+    pub fn new(log_path: impl Into<PathBuf>) -> Self {
+        Self {
+            log_path: log_path.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+        }
+    }
+
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn with_startup_timeout(mut self, startup_timeout: Duration) -> Self {
+        self.startup_timeout = startup_timeout;
+        self
+    }
+}
+
+/// Why an engine subprocess couldn't be started
+#[derive(Clone, Debug)]
+pub enum SpawnError {
+    /// Setting up the log file or the process itself failed
+    Io(String),
+    /// The engine didn't report that it started in time
+    StartupTimeout(Duration),
+    /// The engine exited before reporting that it started
+    ExitedDuringStartup(Option<ExitStatus>),
+    /// The engine wrote something that isn't an engine message
+    UnexpectedOutput(String),
+}
+
+impl std::fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnError::Io(err) => write!(f, "{}", err),
+            SpawnError::StartupTimeout(timeout) => write!(
+                f,
+                "Engine didn't start within {:.1}s",
+                timeout.as_secs_f32()
+            ),
+            SpawnError::ExitedDuringStartup(Some(status)) => {
+                write!(f, "Engine exited during startup with {}", status)
+            }
+            SpawnError::ExitedDuringStartup(None) => write!(f, "Engine exited during startup"),
+            SpawnError::UnexpectedOutput(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SpawnError {}
+
 pub struct EngineSubprocess {
     pub engine_name: String,
-    #[allow(dead_code)]
+    /// The version the engine reported when it started, or its path for engines too old to say
+    pub engine_version: String,
     pub child: Child,
     pub stdin: ChildStdin,
     pub receiver: Receiver<String>,
+    /// Set once the process is known to have exited
+    exit_status: Option<ExitStatus>,
 }
 
-pub fn prepare_subprocess(log_path: &PathBuf, engine_path: &PathBuf) -> EngineSubprocess {
+/// `name` with every character that some OS doesn't allow in file names replaced by `-`, so that
+/// timestamps and engine specs can go into log names. Trailing dots and spaces are dropped too,
+/// since Windows strips them. Names come out the same on every OS
+pub fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    sanitized.trim_end_matches(['.', ' ']).to_owned()
+}
+
+/// The engine binary at `engine_path`, adding the platform's executable extension (`.exe` on
+/// Windows) if the path doesn't name a file as given
+pub fn resolve_engine_path(engine_path: &Path) -> PathBuf {
+    let suffix = std::env::consts::EXE_SUFFIX;
+    if suffix.is_empty() || engine_path.is_file() {
+        return engine_path.to_path_buf();
+    }
+
+    let mut with_suffix = engine_path.as_os_str().to_owned();
+    with_suffix.push(suffix);
+    let with_suffix = PathBuf::from(with_suffix);
+    if with_suffix.is_file() {
+        with_suffix
+    } else {
+        engine_path.to_path_buf()
+    }
+}
+
+/// An explanation of why the engine at `engine_path` couldn't be spawned, with a hint at the fix
+/// for the common cases
+pub fn describe_spawn_error(engine_path: &Path, err: &std::io::Error) -> String {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => format!(
+            "Engine {} not found. Copy a build into {} or check the name",
+            engine_path.display(),
+            BINARY_DIRECTORY
+        ),
+        std::io::ErrorKind::PermissionDenied => format!(
+            "Engine {} is not executable: {}",
+            engine_path.display(),
+            err
+        ),
+        _ => format!("Failed to spawn process {}: {}", engine_path.display(), err),
+    }
+}
+
+/// Starts the engine at `engine_path` and waits for it to report that it's ready. The path is
+/// resolved with `resolve_engine_path`, and is never looked up on the PATH
+pub fn prepare_subprocess(
+    engine_path: &PathBuf,
+    config: &SpawnConfig,
+) -> Result<EngineSubprocess, SpawnError> {
+    let binary_path = resolve_engine_path(engine_path);
+    if !binary_path.is_file() {
+        let err = std::io::Error::from(std::io::ErrorKind::NotFound);
+        return Err(SpawnError::Io(describe_spawn_error(engine_path, &err)));
+    }
+
     let log_dir = PathBuf::from("logs");
-    std::fs::create_dir_all(&log_dir).unwrap();
-    let log_path = log_dir.join(log_path);
+    std::fs::create_dir_all(&log_dir)
+        .map_err(|e| SpawnError::Io(format!("Failed to create log dir: {}", e)))?;
+    let log_path = log_dir.join(sanitize_file_name(&config.log_path.to_string_lossy()));
 
-    let stderr_file = std::fs::File::create(log_path).expect("Failed to create error log file");
+    let stderr_file = std::fs::File::create(log_path)
+        .map_err(|e| SpawnError::Io(format!("Failed to create error log file: {}", e)))?;
 
-    eprintln!("Spawning: {}", engine_path.display());
+    log::debug!("Spawning: {}", binary_path.display());
 
-    let mut child = Command::new(engine_path)
-        // .env("RUST_BACKTRACE", "full")
+    let mut child = Command::new(&binary_path)
+        .args(&config.args)
+        .envs(config.env.iter().map(|(key, value)| (key, value)))
         .stdin(Stdio::piped())
         .stderr(std::process::Stdio::from(stderr_file))
         .stdout(Stdio::piped())
         .spawn()
-        .expect("Failed to spawn process");
+        .map_err(|e| SpawnError::Io(describe_spawn_error(&binary_path, &e)))?;
 
-    let stdin = child.stdin.take().expect("Failed to open stdin");
-    let stdout = child.stdout.take().expect("Failed to open stdout");
+    let stdin = child
+        .stdin
+        .take()
+        .ok_or(SpawnError::Io("Failed to open stdin".to_owned()))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or(SpawnError::Io("Failed to open stdout".to_owned()))?;
 
     let (child_msg_tx, child_msg_rx) = mpsc::channel::<String>();
 
@@ -102,53 +329,131 @@ pub fn prepare_subprocess(log_path: &PathBuf, engine_path: &PathBuf) -> EngineSu
             match line {
                 Ok(line) => {
                     if let Err(err) = child_msg_tx.send(line.clone()) {
-                        eprintln!("Error sending line: {} {}", line, err);
+                        log::error!("Error sending line: {} {}", line, err);
                         break;
                     }
                 }
                 Err(e) => {
-                    eprintln!("Error reading line: {}", e);
+                    log::error!("Error reading line: {}", e);
                     break;
                 }
             }
         }
     });
 
-    let end_at = Instant::now() + Duration::from_secs(10);
+    let engine_name = engine_path.to_str().unwrap_or_default().to_owned();
+    let mut engine = EngineSubprocess {
+        engine_version: engine_name.clone(),
+        engine_name,
+        child,
+        stdin,
+        receiver: child_msg_rx,
+        exit_status: None,
+    };
+
+    let end_at = Instant::now() + config.startup_timeout;
     loop {
         let now = Instant::now();
         if now >= end_at {
-            panic!("Waited too long to spin up child");
+            return Err(SpawnError::StartupTimeout(config.startup_timeout));
         }
 
         let timeout = end_at - now;
 
-        match child_msg_rx.recv_timeout(timeout) {
+        match engine.receiver.recv_timeout(timeout) {
             Ok(msg) => {
                 // println!("I got a message {}", msg);
-                let parsed_msg: EngineOutput = serde_json::from_str(&msg).unwrap();
+                let parsed_msg: EngineOutput = serde_json::from_str(&msg).map_err(|e| {
+                    SpawnError::UnexpectedOutput(format!(
+                        "Failed to parse engine message {}: {}",
+                        msg, e
+                    ))
+                })?;
                 match parsed_msg {
-                    EngineOutput::Started(_) => {
-                        // println!("Started!");
+                    EngineOutput::Started(started) => {
+                        if let Some(version) = started.version {
+                            engine.engine_version = version;
+                        }
                         break;
                     }
                     _ => {
-                        println!("received non-ready message. Still waiting...")
+                        log::debug!("received non-ready message. Still waiting...")
                     }
                 }
-                println!("parsed msg: {:?}", parsed_msg);
+                log::debug!("parsed msg: {:?}", parsed_msg);
             }
-            Err(e) => {
-                panic!("Error while waiting for child: {:?}", e);
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(SpawnError::StartupTimeout(config.startup_timeout));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                // Stdout closing means the process is exiting, so its status is on the way
+                let status = engine.child.wait().ok();
+                engine.exit_status = status;
+                return Err(SpawnError::ExitedDuringStartup(status));
             }
         }
     }
 
-    EngineSubprocess {
-        engine_name: engine_path.to_str().unwrap().to_owned(),
-        child,
-        stdin,
-        receiver: child_msg_rx,
+    Ok(engine)
+}
+
+/// Same as `prepare_subprocess`, trying again after a growing delay if the engine fails to start
+pub fn prepare_subprocess_with_retries(
+    engine_path: &PathBuf,
+    config: &SpawnConfig,
+    attempts: usize,
+) -> Result<EngineSubprocess, SpawnError> {
+    let mut backoff = Duration::from_secs(1);
+    let mut attempt = 1;
+    loop {
+        match prepare_subprocess(engine_path, config) {
+            Ok(engine) => return Ok(engine),
+            Err(err) if attempt < attempts => {
+                log::warn!(
+                    "failed to start {} (attempt {}/{}), retrying in {}s: {}",
+                    engine_path.display(),
+                    attempt,
+                    attempts,
+                    backoff.as_secs(),
+                    err
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+impl EngineSubprocess {
+    /// The exit status of the engine, if it has exited on its own. Logged the first time it's
+    /// seen, so that crashes show up as more than a closed channel
+    pub fn exit_status(&mut self) -> Option<ExitStatus> {
+        if self.exit_status.is_none()
+            && let Ok(Some(status)) = self.child.try_wait()
+        {
+            log::warn!("{} exited with {}", self.engine_name, status);
+            self.exit_status = Some(status);
+        }
+        self.exit_status
+    }
+
+    /// Asks the engine to quit, then kills it and waits for it to exit. Does nothing if it has
+    /// already exited
+    pub fn kill(&mut self) {
+        if self.exit_status().is_some() {
+            return;
+        }
+        let _ = writeln!(self.stdin, "quit");
+        let _ = self.child.kill();
+        self.exit_status = self.child.wait().ok();
+    }
+}
+
+impl Drop for EngineSubprocess {
+    fn drop(&mut self) {
+        self.kill();
     }
 }
 
@@ -159,30 +464,226 @@ pub struct BattleResult {
     pub god2: GodName,
     pub engine2: String,
 
-    pub winning_player: Player,
+    /// None for draws. See `game_result`
+    pub winning_player: Option<Player>,
+    /// Moves after placement, so that games from empty boards compare with pre-placed starts
     pub moves_made: usize,
+    #[serde(default)]
+    pub win_reason: Option<WinReason>,
+    #[serde(default)]
+    pub draw_reason: Option<DrawReason>,
+    /// The corpus position the game started from, if any
+    #[serde(default)]
+    pub position_name: Option<String>,
+    /// Name of the game's log under GAME_LOG_DIRECTORY, without its extension. Renders of the
+    /// game are named after it too, see `render`
+    #[serde(default)]
+    pub game_id: Option<String>,
+    /// `EngineSubprocess::engine_version` of each engine. Empty in results from before versions
+    /// were recorded, see `engine_version`
+    #[serde(default)]
+    pub engine1_version: String,
+    #[serde(default)]
+    pub engine2_version: String,
+    /// Search stats for every move of the game. Written to their own CSV, one row per move
+    #[serde(skip)]
+    pub move_stats: Vec<MoveStats>,
+}
+
+/// How an engine's search went for a move it committed to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MoveStats {
+    pub engine: String,
+    pub god: GodName,
+    pub player: Player,
+    /// Placements and moves are numbered separately
+    pub move_number: usize,
+    pub is_placement: bool,
+    pub score: Heuristic,
+    pub depth: usize,
+    pub seldepth: Option<usize>,
+    pub nodes_visited: Option<usize>,
+    pub elapsed_seconds: f32,
+    pub nodes_per_second: Option<f32>,
+    pub tt_hit_rate: Option<f32>,
+}
+
+impl MoveStats {
+    pub fn new(engine_name: &str, move_number: usize, best_move: &BestMoveOutput) -> Self {
+        let meta = &best_move.meta;
+        // Older engines don't report nps, but it can be recovered from their nodes and time
+        let nodes_per_second = meta.nodes_per_second.or_else(|| {
+            meta.nodes_visited
+                .filter(|_| meta.elapsed_seconds > 0.0)
+                .map(|nodes| nodes as f32 / meta.elapsed_seconds)
+        });
+        let player = best_move
+            .start_state
+            .get_current_player_consider_placement_mode();
+
+        MoveStats {
+            engine: engine_name.to_owned(),
+            god: best_move.start_state.gods[player as usize].god_name,
+            player,
+            move_number,
+            is_placement: best_move.start_state.is_placement_phase(),
+            score: meta.score,
+            depth: meta.calculated_depth,
+            seldepth: meta.seldepth,
+            nodes_visited: meta.nodes_visited,
+            elapsed_seconds: meta.elapsed_seconds,
+            nodes_per_second,
+            tt_hit_rate: meta.tt_hit_rate,
+        }
+    }
 }
 
 impl BattleResult {
+    /// How the game ended. A result with no winner is a draw, even if its reason is missing
+    pub fn game_result(&self) -> GameResult {
+        match self.winning_player {
+            Some(winner) => GameResult::Win(winner, self.win_reason),
+            None => GameResult::Draw(self.draw_reason.unwrap_or(DrawReason::Adjudicated)),
+        }
+    }
+
+    /// None for draws
+    pub fn winning_engine(&self) -> Option<&str> {
+        match self.winning_player? {
+            Player::One => Some(&self.engine1),
+            Player::Two => Some(&self.engine2),
+        }
+    }
+
+    /// 1 for a win by the engine playing `player`, 0.5 for a draw and 0 for a loss
+    pub fn score_for(&self, player: Player) -> f64 {
+        self.game_result().score_for(player)
+    }
+
+    /// The version of the engine playing `player`, or its name for results that don't have one
+    pub fn engine_version(&self, player: Player) -> &str {
+        let (version, name) = match player {
+            Player::One => (&self.engine1_version, &self.engine1),
+            Player::Two => (&self.engine2_version, &self.engine2),
+        };
+        if version.is_empty() { name } else { version }
+    }
+
     pub fn get_pretty_description(&self) -> String {
         let winner_str = match self.winning_player {
-            Player::One => format!(
+            Some(Player::One) => format!(
                 "Won by player 1 ({} {}) after {} moves",
                 self.god1, self.engine1, self.moves_made
             ),
-            Player::Two => format!(
+            Some(Player::Two) => format!(
                 "Won by player 2 ({} {}) after {} moves",
                 self.god2, self.engine2, self.moves_made
             ),
+            None => format!("Drawn after {} moves", self.moves_made),
+        };
+
+        let reason_str = match self.game_result() {
+            GameResult::Win(_, Some(reason)) => format!(" ({reason})"),
+            GameResult::Win(_, None) => "".to_owned(),
+            GameResult::Draw(reason) => format!(" ({reason})"),
         };
 
         format!(
-            "{:?} ({}) v {:?} ({}) - {winner_str}",
+            "{:?} ({}) v {:?} ({}) - {winner_str}{reason_str}",
             self.god1, self.engine1, self.god2, self.engine2
         )
     }
 }
 
+/// Games played, won and drawn by one engine version, across every result it appears in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRecord {
+    pub version: String,
+    pub wins: usize,
+    pub draws: usize,
+    pub games: usize,
+}
+
+impl VersionRecord {
+    /// Wins plus half of draws, over games played
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games.max(1) as f64
+    }
+}
+
+/// A record for every engine version in `results`, keyed by `BattleResult::engine_version`, most
+/// games first. Games between two builds of the same version count once for that version
+pub fn summarize_by_version(results: &[BattleResult]) -> Vec<VersionRecord> {
+    let mut records: Vec<VersionRecord> = Vec::new();
+    for result in results {
+        let mut versions = vec![
+            result.engine_version(Player::One),
+            result.engine_version(Player::Two),
+        ];
+        versions.dedup();
+        for version in versions {
+            let idx = match records.iter().position(|record| record.version == version) {
+                Some(idx) => idx,
+                None => {
+                    records.push(VersionRecord {
+                        version: version.to_owned(),
+                        wins: 0,
+                        draws: 0,
+                        games: 0,
+                    });
+                    records.len() - 1
+                }
+            };
+            records[idx].games += 1;
+            match result.winning_player {
+                Some(winner) if result.engine_version(winner) == version => records[idx].wins += 1,
+                Some(_) => (),
+                None => records[idx].draws += 1,
+            }
+        }
+    }
+    records.sort_by(|a, b| b.games.cmp(&a.games));
+    records
+}
+
+/// Prints the `summarize_by_version` table for `results`
+pub fn print_version_summary(results: &[BattleResult]) {
+    println!(
+        "{:<40} {:>8} {:>8} {:>8} {:>8}",
+        "Version", "Games", "Wins", "Draws", "Score %"
+    );
+    for record in summarize_by_version(results) {
+        println!(
+            "{:<40} {:>8} {:>8} {:>8} {:>7.1}%",
+            record.version,
+            record.games,
+            record.wins,
+            record.draws,
+            100.0 * record.score()
+        );
+    }
+}
+
+/// Elo difference implied by an expected score. Clamped so that sweeps stay finite
+pub fn elo_from_score(score: f64) -> f64 {
+    let score = score.clamp(0.001, 0.999);
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+/// The Elo difference implied by a win/draw/loss record, and the half width of its 95%
+/// confidence interval. Draws count as half a win
+pub fn elo_estimate(wins: usize, draws: usize, losses: usize) -> (f64, f64) {
+    let games = (wins + draws + losses).max(1) as f64;
+    let score = (wins as f64 + 0.5 * draws as f64) / games;
+    // A sweep has no variance to measure, so treat it as if half a game had gone the other way
+    let variance_score = score.clamp(0.5 / games, 1.0 - 0.5 / games);
+    let margin = 1.96 * (variance_score * (1.0 - variance_score) / games).sqrt();
+    let elo = elo_from_score(score);
+    let lower = elo_from_score(score - margin);
+    let upper = elo_from_score(score + margin);
+    (elo, (upper - lower) / 2.0)
+}
+
 pub fn write_results_to_csv(results: &[BattleResult], path: &PathBuf) -> std::io::Result<()> {
     let mut wtr = Writer::from_path(path)?;
     for result in results {
@@ -192,6 +693,18 @@ pub fn write_results_to_csv(results: &[BattleResult], path: &PathBuf) -> std::io
     Ok(())
 }
 
+pub fn write_move_stats_to_csv<'a>(
+    move_stats: impl IntoIterator<Item = &'a MoveStats>,
+    path: &PathBuf,
+) -> std::io::Result<()> {
+    let mut wtr = Writer::from_path(path)?;
+    for stats in move_stats {
+        wtr.serialize(stats)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
 pub fn read_battle_result_csv(path: &PathBuf) -> std::io::Result<Vec<BattleResult>> {
     let mut results = Vec::new();
     let mut rdr = csv::Reader::from_path(path)?;
@@ -202,6 +715,221 @@ pub fn read_battle_result_csv(path: &PathBuf) -> std::io::Result<Vec<BattleResul
     Ok(results)
 }
 
+/// How long each engine gets for its moves
+#[derive(Clone, Copy, Debug)]
+pub enum TurnTiming {
+    /// The same think time for every move
+    PerTurn(Duration),
+    /// A clock per player, topped up by the increment after each of their moves. Engines are
+    /// sent their clocks with the `go` command and budget their own time. An engine that runs
+    /// out of time plays its latest best move, and carries on with only the increment
+    Clock {
+        initial: Duration,
+        increment: Duration,
+        /// Forfeits the game of an engine that sends nothing for this long during its turn
+        forfeit_after_silence: Option<Duration>,
+    },
+}
+
+/// Bumped whenever a game log field changes meaning or is removed
+pub const GAME_LOG_VERSION: u32 = 1;
+
+/// How long each engine got for its moves, as written to game logs
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameLogTimeControl {
+    PerTurn {
+        seconds: f32,
+    },
+    Clock {
+        initial_seconds: f32,
+        increment_seconds: f32,
+    },
+}
+
+impl From<TurnTiming> for GameLogTimeControl {
+    fn from(timing: TurnTiming) -> Self {
+        match timing {
+            TurnTiming::PerTurn(duration) => GameLogTimeControl::PerTurn {
+                seconds: duration.as_secs_f32(),
+            },
+            TurnTiming::Clock {
+                initial, increment, ..
+            } => GameLogTimeControl::Clock {
+                initial_seconds: initial.as_secs_f32(),
+                increment_seconds: increment.as_secs_f32(),
+            },
+        }
+    }
+}
+
+/// One line of a game log. A log is a header, a move for every turn, then a footer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameLogEntry {
+    Header {
+        version: u32,
+        god1: GodName,
+        engine1: String,
+        god2: GodName,
+        engine2: String,
+        start_state: FullGameState,
+        time_control: GameLogTimeControl,
+    },
+    Move {
+        move_number: usize,
+        engine: String,
+        player: Player,
+        /// The position before the move
+        state: FullGameState,
+        /// Missing for engines that don't report it
+        action_str: Option<String>,
+        score: Heuristic,
+        depth: usize,
+        nodes_visited: Option<usize>,
+        /// Wall time of the whole turn, as seen by the battler
+        elapsed_seconds: f32,
+    },
+    Footer {
+        /// None for draws
+        winning_player: Option<Player>,
+        winning_engine: Option<String>,
+        moves_made: usize,
+        win_reason: Option<WinReason>,
+        #[serde(default)]
+        draw_reason: Option<DrawReason>,
+        /// The position after the last move. Missing in logs from before it was recorded
+        #[serde(default)]
+        final_state: Option<FullGameState>,
+    },
+}
+
+fn write_game_log_entry(writer: &mut impl Write, entry: &GameLogEntry) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, entry)?;
+    writeln!(writer)
+}
+
+pub fn read_game_log(path: &PathBuf) -> std::io::Result<Vec<GameLogEntry>> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(&line)?);
+    }
+    Ok(entries)
+}
+
+/// A fresh file under GAME_LOG_DIRECTORY for the next game of a worker, and the game id it's
+/// named after
+pub fn create_game_log_file(log_prefix: &str) -> std::io::Result<(String, std::fs::File)> {
+    std::fs::create_dir_all(GAME_LOG_DIRECTORY)?;
+    let mut game_idx = 0;
+    loop {
+        let game_id = sanitize_file_name(&format!(
+            "{}-{}-{}",
+            log_prefix,
+            timestamp_string(),
+            game_idx
+        ));
+        match std::fs::File::create_new(render::game_log_path(&game_id)) {
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => game_idx += 1,
+            result => return result.map(|file| (game_id, file)),
+        }
+    }
+}
+
+/// Same as `do_battle_with_timing`, writing every move to `log_writer` as a JSONL game log.
+/// Failing to write the log is reported, but doesn't stop the game
+pub fn do_battle_logged<'a>(
+    start_state: &FullGameState,
+    c1: &'a mut EngineSubprocess,
+    c2: &'a mut EngineSubprocess,
+    timing: TurnTiming,
+    is_printing: bool,
+    mut log_writer: impl Write,
+) -> BattleResult {
+    let engine_names = [c1.engine_name.clone(), c2.engine_name.clone()];
+    let mut log_result = write_game_log_entry(
+        &mut log_writer,
+        &GameLogEntry::Header {
+            version: GAME_LOG_VERSION,
+            god1: start_state.gods[0].god_name,
+            engine1: engine_names[0].clone(),
+            god2: start_state.gods[1].god_name,
+            engine2: engine_names[1].clone(),
+            start_state: start_state.clone(),
+            time_control: timing.into(),
+        },
+    );
+
+    let never_stop = AtomicBool::new(false);
+    let mut move_number = 0;
+    let mut final_state = start_state.clone();
+    let mut turn_started_at = Instant::now();
+    let result = do_battle_observed_with_timing(
+        start_state,
+        c1,
+        c2,
+        timing,
+        is_printing,
+        &never_stop,
+        |event| {
+            let BattleEvent::Moved(best_move) = event else {
+                return;
+            };
+            move_number += 1;
+            let player = best_move
+                .start_state
+                .get_current_player_consider_placement_mode();
+            let entry = GameLogEntry::Move {
+                move_number,
+                engine: engine_names[player as usize].clone(),
+                player,
+                state: best_move.start_state.clone(),
+                action_str: best_move.meta.action_str.clone(),
+                score: best_move.meta.score,
+                depth: best_move.meta.calculated_depth,
+                nodes_visited: best_move.meta.nodes_visited,
+                elapsed_seconds: turn_started_at.elapsed().as_secs_f32(),
+            };
+            turn_started_at = Instant::now();
+            final_state = best_move.next_state.clone();
+            if log_result.is_ok() {
+                log_result = write_game_log_entry(&mut log_writer, &entry);
+            }
+        },
+    );
+    let result = match result {
+        Ok(Some(result)) => result,
+        Ok(None) => unreachable!("Battle was stopped without a stop request"),
+        Err(err) => panic!("{}", err),
+    };
+
+    let log_result = log_result
+        .and_then(|_| {
+            write_game_log_entry(
+                &mut log_writer,
+                &GameLogEntry::Footer {
+                    winning_player: result.winning_player,
+                    winning_engine: result.winning_engine().map(str::to_owned),
+                    moves_made: result.moves_made,
+                    win_reason: result.win_reason,
+                    draw_reason: result.draw_reason,
+                    final_state: Some(final_state),
+                },
+            )
+        })
+        .and_then(|_| log_writer.flush());
+    if let Err(err) = log_result {
+        log::error!("Failed to write game log: {}", err);
+    }
+
+    result
+}
+
 pub fn do_battle<'a>(
     start_state: &FullGameState,
     c1: &'a mut EngineSubprocess,
@@ -209,51 +937,236 @@ pub fn do_battle<'a>(
     per_turn_duration: Duration,
     is_printing: bool,
 ) -> BattleResult {
+    do_battle_with_timing(
+        start_state,
+        c1,
+        c2,
+        TurnTiming::PerTurn(per_turn_duration),
+        is_printing,
+    )
+}
+
+pub fn do_battle_with_timing<'a>(
+    start_state: &FullGameState,
+    c1: &'a mut EngineSubprocess,
+    c2: &'a mut EngineSubprocess,
+    timing: TurnTiming,
+    is_printing: bool,
+) -> BattleResult {
+    let never_stop = AtomicBool::new(false);
+    let result = do_battle_observed_with_timing(
+        start_state,
+        c1,
+        c2,
+        timing,
+        is_printing,
+        &never_stop,
+        |_| {},
+    );
+    match result {
+        Ok(Some(result)) => result,
+        Ok(None) => unreachable!("Battle was stopped without a stop request"),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// Progress of a battle, reported to the `do_battle_observed` callback
+pub enum BattleEvent<'a> {
+    /// The engine to move reported a new best move
+    Thinking(&'a BestMoveOutput),
+    /// The engine to move committed to a move
+    Moved(&'a BestMoveOutput),
+}
+
+/// How often a battle checks its stop flag while waiting on an engine
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Engines send heartbeats while searching, so a silence this long during a turn is logged
+const ENGINE_SILENCE_WARNING: Duration = Duration::from_secs(5);
+
+/// Same as `do_battle`, reporting every engine message to `on_event`. Returns Ok(None) if
+/// `should_stop` was set before the game finished
+pub fn do_battle_observed<'a>(
+    start_state: &FullGameState,
+    c1: &'a mut EngineSubprocess,
+    c2: &'a mut EngineSubprocess,
+    per_turn_duration: Duration,
+    is_printing: bool,
+    should_stop: &AtomicBool,
+    on_event: impl FnMut(BattleEvent),
+) -> Result<Option<BattleResult>, String> {
+    do_battle_observed_with_timing(
+        start_state,
+        c1,
+        c2,
+        TurnTiming::PerTurn(per_turn_duration),
+        is_printing,
+        should_stop,
+        on_event,
+    )
+}
+
+pub fn do_battle_observed_with_timing<'a>(
+    start_state: &FullGameState,
+    c1: &'a mut EngineSubprocess,
+    c2: &'a mut EngineSubprocess,
+    timing: TurnTiming,
+    is_printing: bool,
+    should_stop: &AtomicBool,
+    mut on_event: impl FnMut(BattleEvent),
+) -> Result<Option<BattleResult>, String> {
+    let mut clocks = match timing {
+        TurnTiming::PerTurn(_) => [Duration::ZERO; 2],
+        TurnTiming::Clock { initial, .. } => [initial; 2],
+    };
     let mut moves_made = 0;
+    let mut placements_made = 0;
+    let mut move_stats = Vec::new();
     let mut current_state = start_state.clone();
 
     if is_printing {
-        start_state.print_to_console();
-        println!();
+        println!(
+            "{}",
+            start_state.to_console_string(ConsoleRenderOptions::default())
+        );
     }
 
+    let write_err = |e: std::io::Error| format!("Failed to write to stdin: {}", e);
+
     loop {
-        let (engine, other) = match current_state.board.current_player {
+        // Settled here rather than by the engine, which may have nothing to say about a position
+        // without moves
+        if let Some(TerminalResult::Smothered(loser)) = current_state.get_terminal_result() {
+            if is_printing {
+                println!("Player {:?} has no moves and loses", loser);
+            }
+            writeln!(c1.stdin, "stop").map_err(write_err)?;
+            writeln!(c2.stdin, "stop").map_err(write_err)?;
+
+            return Ok(Some(BattleResult {
+                god1: current_state.gods[0].god_name,
+                engine1: c1.engine_name.clone(),
+                god2: current_state.gods[1].god_name,
+                engine2: c2.engine_name.clone(),
+                engine1_version: c1.engine_version.clone(),
+                engine2_version: c2.engine_version.clone(),
+                winning_player: Some(!loser),
+                moves_made,
+                win_reason: Some(WinReason::OpponentSmothered),
+                draw_reason: None,
+                position_name: None,
+                game_id: None,
+                move_stats,
+            }));
+        }
+
+        // Player 2 places first in some matchups, so `current_player` can't pick the engine
+        let mover = current_state.get_current_player_consider_placement_mode();
+        let is_placement = current_state.is_placement_phase();
+        let (engine, other) = match mover {
             Player::One => (&mut *c1, &mut *c2),
             Player::Two => (&mut *c2, &mut *c1),
         };
 
         let state_string = game_state_to_fen(&current_state);
-        if is_printing {
-            eprintln!(
-                "{}: setting position {}",
-                timestamp_string(),
-                engine.engine_name
-            );
-        }
-        writeln!(engine.stdin, "set_position {}", state_string).expect("Failed to write to stdin");
-        writeln!(other.stdin, "set_position {}", state_string).expect("Failed to write to stdin");
+        log::debug!("setting position {}", engine.engine_name);
+        let turn_duration = match timing {
+            TurnTiming::PerTurn(per_turn_duration) => {
+                writeln!(engine.stdin, "set_position {}", state_string).map_err(write_err)?;
+                per_turn_duration
+            }
+            TurnTiming::Clock { increment, .. } => {
+                writeln!(
+                    engine.stdin,
+                    "go {} wtime {} btime {} winc {} binc {}",
+                    state_string,
+                    clocks[0].as_millis(),
+                    clocks[1].as_millis(),
+                    increment.as_millis(),
+                    increment.as_millis()
+                )
+                .map_err(write_err)?;
+                clocks[mover as usize]
+            }
+        };
+        writeln!(other.stdin, "set_position {}", state_string).map_err(write_err)?;
 
         let started_at = Instant::now();
-        let end_at = started_at + per_turn_duration;
+        let end_at = started_at + turn_duration;
         let mut saved_best_move: Option<BestMoveOutput> = None;
+        let mut last_heard_at = started_at;
+        let mut is_silence_logged = false;
 
         loop {
+            if should_stop.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+
+            let silence = last_heard_at.elapsed();
+            if silence >= ENGINE_SILENCE_WARNING && !is_silence_logged {
+                log::warn!(
+                    "{} has been silent for {:.02}s",
+                    engine.engine_name,
+                    silence.as_secs_f32()
+                );
+                is_silence_logged = true;
+            }
+            if let TurnTiming::Clock {
+                forfeit_after_silence: Some(forfeit_after_silence),
+                ..
+            } = timing
+                && silence >= forfeit_after_silence
+            {
+                if is_printing {
+                    println!(
+                        "{} went silent for {:.02}s and forfeits",
+                        engine.engine_name,
+                        silence.as_secs_f32()
+                    );
+                }
+                writeln!(c1.stdin, "stop").map_err(write_err)?;
+                writeln!(c2.stdin, "stop").map_err(write_err)?;
+
+                return Ok(Some(BattleResult {
+                    god1: current_state.gods[0].god_name,
+                    engine1: c1.engine_name.clone(),
+                    god2: current_state.gods[1].god_name,
+                    engine2: c2.engine_name.clone(),
+                    engine1_version: c1.engine_version.clone(),
+                    engine2_version: c2.engine_version.clone(),
+                    winning_player: Some(!mover),
+                    moves_made,
+                    win_reason: Some(WinReason::Forfeit),
+                    draw_reason: None,
+                    position_name: None,
+                    game_id: None,
+                    move_stats,
+                }));
+            }
+
             let now = Instant::now();
             if now >= end_at {
                 break;
             }
 
-            let timeout = end_at - now;
+            let timeout = (end_at - now).min(STOP_POLL_INTERVAL);
             match engine.receiver.recv_timeout(timeout) {
                 Ok(msg) => {
-                    let parsed_msg: EngineOutput = serde_json::from_str(&msg).unwrap();
+                    let parsed_msg: EngineOutput = serde_json::from_str(&msg)
+                        .map_err(|e| format!("Failed to parse engine message {}: {}", msg, e))?;
                     match parsed_msg {
+                        EngineOutput::Info(info) => {
+                            // Leftovers from earlier positions say nothing about liveness
+                            if info.start_state == current_state {
+                                last_heard_at = Instant::now();
+                            }
+                        }
                         EngineOutput::BestMove(best_move) => {
                             if best_move.start_state != current_state {
                                 // println!("Message for wrong state");
                                 continue;
                             }
+                            last_heard_at = Instant::now();
+                            on_event(BattleEvent::Thinking(&best_move));
                             saved_best_move = Some(best_move.clone());
                             match best_move.trigger {
                                 BestMoveTrigger::StopFlag => {
@@ -265,22 +1178,46 @@ pub fn do_battle<'a>(
                                     }
                                     break;
                                 }
+                                BestMoveTrigger::Stable => {
+                                    if is_printing {
+                                        println!("Best move is stable, ending early");
+                                    }
+                                    break;
+                                }
+                                BestMoveTrigger::OnlyMove => {
+                                    if is_printing {
+                                        println!("Only one legal move, ending early");
+                                    }
+                                    break;
+                                }
+                                BestMoveTrigger::Book => {
+                                    if is_printing {
+                                        println!("Book placement, ending early");
+                                    }
+                                    break;
+                                }
                                 BestMoveTrigger::Seed
                                 | BestMoveTrigger::Improvement
                                 | BestMoveTrigger::Saved => (),
                             }
                         }
                         _ => {
-                            eprintln!("Unexpected message: {:?}", parsed_msg);
+                            log::warn!("Unexpected message: {:?}", parsed_msg);
                         }
                     }
                 }
                 Err(RecvTimeoutError::Timeout) => {
-                    // eprintln!("timeout reached");
-                    break;
+                    // Keep waiting until the turn is over, checking the stop flag in between
+                    continue;
                 }
                 Err(e) => {
-                    panic!("Error receiving message: {:?}", e);
+                    let exit_description = engine
+                        .exit_status()
+                        .map_or(String::new(), |status| format!(" (exited with {})", status));
+                    return Err(format!(
+                        "{}: Error receiving message: {:?}{}",
+                        engine.engine_name, e, exit_description
+                    ));
                 }
             }
         }
@@ -288,25 +1225,77 @@ pub fn do_battle<'a>(
         // eprintln!("{}: stopping {}", timestamp_string(), engine.engine_name);
         // writeln!(engine.stdin, "stop").expect("Failed to write to stdin");
 
-        moves_made += 1;
+        if is_placement {
+            placements_made += 1;
+        } else {
+            moves_made += 1;
+        }
+        if let TurnTiming::Clock { increment, .. } = timing {
+            clocks[mover as usize] =
+                clocks[mover as usize].saturating_sub(started_at.elapsed()) + increment;
+        }
 
         let Some(saved_best_move) = saved_best_move else {
-            panic!(
+            return Err(format!(
                 "{:?}, {}: Expected engine to output at least 1 move",
-                current_state,
-                engine.engine_name
-            );
+                current_state, engine.engine_name
+            ));
         };
 
+        if let Err(err) = verify_engine_move(&current_state, &saved_best_move) {
+            log::warn!(
+                "{} played an illegal move and forfeits: {}",
+                engine.engine_name,
+                err
+            );
+            writeln!(c1.stdin, "stop").map_err(write_err)?;
+            writeln!(c2.stdin, "stop").map_err(write_err)?;
+
+            return Ok(Some(BattleResult {
+                god1: current_state.gods[0].god_name,
+                engine1: c1.engine_name.clone(),
+                god2: current_state.gods[1].god_name,
+                engine2: c2.engine_name.clone(),
+                engine1_version: c1.engine_version.clone(),
+                engine2_version: c2.engine_version.clone(),
+                winning_player: Some(!mover),
+                moves_made,
+                win_reason: Some(WinReason::IllegalMove),
+                draw_reason: None,
+                position_name: None,
+                game_id: None,
+                move_stats,
+            }));
+        }
+
         current_state = saved_best_move.next_state.clone();
+        move_stats.push(MoveStats::new(
+            &engine.engine_name,
+            if is_placement {
+                placements_made
+            } else {
+                moves_made
+            },
+            &saved_best_move,
+        ));
+        on_event(BattleEvent::Moved(&saved_best_move));
 
-        let current_god = saved_best_move.start_state.get_active_god();
+        let current_god = saved_best_move.start_state.get_god_for_player(mover);
+        log::debug!(
+            "{} played {} for Player {:?} [{:?}] | depth: {} score: {}",
+            engine.engine_name,
+            saved_best_move.meta.action_str.as_deref().unwrap_or("?"),
+            mover,
+            current_god.god_name,
+            saved_best_move.meta.calculated_depth,
+            saved_best_move.meta.score,
+        );
 
         if is_printing {
             println!(
                 "({}) Made move for Player {:?} [{:?}]: {:?} | depth: {} score: {}, visited: {:?} secs: {:.04}",
                 engine.engine_name,
-                saved_best_move.start_state.board.current_player,
+                mover,
                 current_god.god_name,
                 saved_best_move.meta.actions,
                 saved_best_move.meta.calculated_depth,
@@ -314,85 +1303,359 @@ pub fn do_battle<'a>(
                 saved_best_move.meta.nodes_visited,
                 started_at.elapsed().as_secs_f32()
             );
-            current_state.print_to_console();
-            println!();
+            if let TurnTiming::Clock { .. } = timing {
+                println!(
+                    "Clocks: {:.02}s | {:.02}s",
+                    clocks[0].as_secs_f32(),
+                    clocks[1].as_secs_f32()
+                );
+            }
+            println!(
+                "{}",
+                current_state.to_console_string(ConsoleRenderOptions::default())
+            );
         }
 
         let winner = current_state.board.get_winner();
         if let Some(winner) = winner {
-            writeln!(c1.stdin, "stop").expect("Failed to write to stdin");
-            writeln!(c2.stdin, "stop").expect("Failed to write to stdin");
+            writeln!(c1.stdin, "stop").map_err(write_err)?;
+            writeln!(c2.stdin, "stop").map_err(write_err)?;
 
-            return BattleResult {
+            return Ok(Some(BattleResult {
                 god1: current_state.gods[0].god_name,
                 engine1: c1.engine_name.clone(),
                 god2: current_state.gods[1].god_name,
                 engine2: c2.engine_name.clone(),
-                winning_player: winner,
+                engine1_version: c1.engine_version.clone(),
+                engine2_version: c2.engine_version.clone(),
+                winning_player: Some(winner),
                 moves_made,
-            };
+                win_reason: get_win_reason(&saved_best_move.start_state, &current_state),
+                draw_reason: None,
+                position_name: None,
+                game_id: None,
+                move_stats,
+            }));
+        }
+    }
+}
+
+/// Checks that the position an engine moved to can be reached from `current_state` with one
+/// move, so that a buggy engine can't carry on a game from a position it made up
+pub fn verify_engine_move(
+    current_state: &FullGameState,
+    best_move: &BestMoveOutput,
+) -> Result<(), String> {
+    let child_hashes: HashSet<u64> = current_state
+        .get_complete_next_states_with_actions()
+        .into_iter()
+        .map(|(child, _)| child.board.hash)
+        .collect();
+    if child_hashes.contains(&best_move.next_state.board.hash) {
+        return Ok(());
+    }
+
+    Err(format!(
+        "{} can't be reached from {} (claimed move: {})",
+        game_state_to_fen(&best_move.next_state),
+        game_state_to_fen(current_state),
+        best_move.meta.action_str.as_deref().unwrap_or("unknown")
+    ))
+}
+
+/// An engine for a worker to run: a binary name under BINARY_DIRECTORY, and commands to send it
+/// once it has started. The same binary with different commands counts as a different engine
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EngineSpec {
+    pub path: PathBuf,
+    pub init_commands: Vec<String>,
+    /// Tells engines with the same binary apart in results and logs
+    pub label: Option<String>,
+}
+
+impl EngineSpec {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            init_commands: Vec::new(),
+            label: None,
+        }
+    }
+
+    pub fn with_init_command(mut self, command: impl Into<String>) -> Self {
+        self.init_commands.push(command.into());
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// The name results are recorded under: the binary's path, followed by the label if any
+    pub fn engine_name(&self) -> String {
+        let path = PathBuf::new().join(BINARY_DIRECTORY).join(&self.path);
+        let path = path.to_str().unwrap_or_default();
+        match &self.label {
+            Some(label) => format!("{}[{}]", path, label),
+            None => path.to_owned(),
+        }
+    }
+}
+
+impl From<PathBuf> for EngineSpec {
+    fn from(path: PathBuf) -> Self {
+        Self::new(path)
+    }
+}
+
+impl std::fmt::Display for EngineSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{}[{}]", self.path.display(), label),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+/// A position for a worker to play, along with the engines to play it
+#[derive(Clone, Debug)]
+pub struct BattleAssignment {
+    pub start_state: FullGameState,
+    /// Set when the position comes from the corpus
+    pub position_name: Option<String>,
+    pub engine1: EngineSpec,
+    pub engine2: EngineSpec,
+}
+
+impl BattleAssignment {
+    pub fn for_matchup(
+        matchup: Matchup,
+        engine1: impl Into<EngineSpec>,
+        engine2: impl Into<EngineSpec>,
+    ) -> Self {
+        Self {
+            start_state: FullGameState::new_for_matchup(&matchup),
+            position_name: None,
+            engine1: engine1.into(),
+            engine2: engine2.into(),
+        }
+    }
+}
+
+/// Every enabled corpus position, once with each engine as player 1. Positions whose gods aren't
+/// allowed by `selector` are skipped with a warning, unless `keep_corpus_gods` is set, in which
+/// case they're played with their own gods anyway
+pub fn corpus_assignments(
+    corpus: &Corpus,
+    selector: &MatchupSelector,
+    keep_corpus_gods: bool,
+    engine1: &EngineSpec,
+    engine2: &EngineSpec,
+) -> Vec<BattleAssignment> {
+    let allowed_matchups = selector.get_all();
+    let mut assignments = Vec::new();
+    for position in &corpus.positions {
+        if !position.is_enabled {
+            continue;
+        }
+
+        let matchup = Matchup::new(
+            position.state.gods[0].god_name,
+            position.state.gods[1].god_name,
+        );
+        if !keep_corpus_gods && !allowed_matchups.contains(&matchup) {
+            log::warn!(
+                "Skipping corpus position {}: {} isn't in the requested matchups",
+                position.name,
+                matchup
+            );
+            continue;
         }
+
+        for (first, second) in [(engine1, engine2), (engine2, engine1)] {
+            assignments.push(BattleAssignment {
+                start_state: position.state.clone(),
+                position_name: Some(position.name.clone()),
+                engine1: first.clone(),
+                engine2: second.clone(),
+            });
+        }
+    }
+    assignments
+}
+
+/// How many times a worker tries to start an engine before skipping the assignment
+const WORKER_SPAWN_ATTEMPTS: usize = 3;
+
+/// An engine subprocess owned by a worker. Reused for as long as assignments keep asking for the
+/// same engine and it keeps running, and killed when it's replaced or the slot is dropped
+struct WorkerEngineSlot {
+    log_prefix: String,
+    engine: Option<(EngineSpec, EngineSubprocess)>,
+}
+
+impl WorkerEngineSlot {
+    fn new(log_prefix: String) -> Self {
+        Self {
+            log_prefix,
+            engine: None,
+        }
+    }
+
+    fn is_running(&self, engine_spec: &EngineSpec) -> bool {
+        self.engine
+            .as_ref()
+            .is_some_and(|(current_spec, _)| current_spec == engine_spec)
+    }
+
+    fn get(&mut self, engine_spec: &EngineSpec) -> Result<&mut EngineSubprocess, SpawnError> {
+        if let Some((current_spec, current_engine)) = &mut self.engine
+            && (current_spec != engine_spec || current_engine.exit_status().is_some())
+        {
+            // Dropping the engine kills it
+            self.engine = None;
+        }
+
+        if self.engine.is_none() {
+            let config = SpawnConfig::new(format!(
+                "{}-{}-{}.log",
+                self.log_prefix,
+                timestamp_string(),
+                engine_spec
+            ));
+            let mut subprocess = prepare_subprocess_with_retries(
+                &PathBuf::new()
+                    .join(BINARY_DIRECTORY)
+                    .join(&engine_spec.path),
+                &config,
+                WORKER_SPAWN_ATTEMPTS,
+            )?;
+            for command in &engine_spec.init_commands {
+                log::debug!("{} init: {}", engine_spec, command);
+                writeln!(subprocess.stdin, "{}", command).map_err(|e| {
+                    SpawnError::Io(format!("Failed to send init command {}: {}", command, e))
+                })?;
+            }
+            subprocess.engine_name = engine_spec.engine_name();
+            // Specs of the same binary differ only by their label
+            if let Some(label) = &engine_spec.label {
+                subprocess.engine_version = format!("{}[{}]", subprocess.engine_version, label);
+            }
+            self.engine = Some((engine_spec.clone(), subprocess));
+        }
+        Ok(&mut self.engine.as_mut().unwrap().1)
     }
 }
 
+/// Plays assignments from the queue until it's empty. With RUN_BOTH_SIDES, each assignment is
+/// played twice, with the engines swapping sides. With `render_finals`, the final position of
+/// every game is rendered next to its log
 pub fn battling_worker_thread<const RUN_BOTH_SIDES: bool>(
-    worker_idx: String,
-    matchups_queue: Arc<Mutex<Vec<Matchup>>>,
-    engine1: &PathBuf,
-    engine2: &PathBuf,
+    worker_name: String,
+    assignments_queue: Arc<Mutex<Vec<BattleAssignment>>>,
     duration: Duration,
+    render_finals: bool,
     result_channel: mpsc::Sender<WorkerMessage>,
 ) {
-    let now_str = timestamp_string();
-    let mut c1 = prepare_subprocess(
-        &PathBuf::from(format!(
-            "compare-{worker_idx}-{}-{}.log",
-            now_str,
-            engine1.display()
-        )),
-        &PathBuf::new().join(BINARY_DIRECTORY).join(engine1),
-    );
-    let mut c2 = prepare_subprocess(
-        &PathBuf::from(format!(
-            "compare-{worker_idx}-{}-{}.log",
-            now_str,
-            engine2.display()
-        )),
-        &PathBuf::new().join(BINARY_DIRECTORY).join(engine2),
-    );
+    start_worker_log(&worker_name);
+    let mut slot1 = WorkerEngineSlot::new(worker_name.clone());
+    let mut slot2 = WorkerEngineSlot::new(worker_name.clone());
 
     loop {
-        let matchup = {
-            let mut queue = matchups_queue.lock().unwrap();
+        let assignment = {
+            let mut queue = assignments_queue.lock().unwrap();
             queue.pop()
         };
 
-        match matchup {
-            Some(matchup) => {
-                let start_state = FullGameState::new_for_matchup(&matchup);
-
-                if RUN_BOTH_SIDES {
-                    let result1 = do_battle(&start_state, &mut c1, &mut c2, duration, false);
-                    let result2 = do_battle(&start_state, &mut c2, &mut c1, duration, false);
-
-                    result_channel
-                        .send(WorkerMessage::BattleResultPair((result1, result2)))
-                        .unwrap();
-                } else {
-                    let result = do_battle(&start_state, &mut c1, &mut c2, duration, false);
-                    result_channel
-                        .send(WorkerMessage::BattleResult(result))
-                        .unwrap();
+        let Some(assignment) = assignment else {
+            break;
+        };
+
+        // Assignments often alternate which engine plays first. Swap rather than respawn both
+        if assignment.engine1 != assignment.engine2
+            && slot1.is_running(&assignment.engine2)
+            && slot2.is_running(&assignment.engine1)
+        {
+            std::mem::swap(&mut slot1, &mut slot2);
+        }
+
+        let start_state = &assignment.start_state;
+        let (c1, c2) = match (
+            slot1.get(&assignment.engine1),
+            slot2.get(&assignment.engine2),
+        ) {
+            (Ok(c1), Ok(c2)) => (c1, c2),
+            (Err(err), _) | (_, Err(err)) => {
+                log::error!(
+                    "{} skipping {} v {}: {}",
+                    worker_name,
+                    assignment.engine1,
+                    assignment.engine2,
+                    err
+                );
+                continue;
+            }
+        };
+
+        let play = |c1: &mut EngineSubprocess, c2: &mut EngineSubprocess| {
+            let timing = TurnTiming::PerTurn(duration);
+            let (game_id, result) = match create_game_log_file(&worker_name) {
+                Ok((game_id, log_file)) => {
+                    let result = do_battle_logged(
+                        start_state,
+                        c1,
+                        c2,
+                        timing,
+                        false,
+                        BufWriter::new(log_file),
+                    );
+                    (Some(game_id), result)
                 }
+                Err(err) => {
+                    log::error!("Failed to create game log: {}", err);
+                    (
+                        None,
+                        do_battle_with_timing(start_state, c1, c2, timing, false),
+                    )
+                }
+            };
+            if render_finals
+                && let Some(game_id) = &game_id
+                && let Err(err) = render::render_final_position(game_id)
+            {
+                log::error!("Failed to render {}: {}", game_id, err);
             }
-            None => {
-                break;
+            log::info!(
+                "{} ({:?}) v {} ({:?}): {} after {} moves",
+                result.engine1,
+                result.god1,
+                result.engine2,
+                result.god2,
+                result.game_result(),
+                result.moves_made,
+            );
+            BattleResult {
+                position_name: assignment.position_name.clone(),
+                game_id,
+                ..result
             }
+        };
+
+        if RUN_BOTH_SIDES {
+            let result1 = play(c1, c2);
+            let result2 = play(c2, c1);
+
+            result_channel
+                .send(WorkerMessage::BattleResultPair((result1, result2)))
+                .unwrap();
+        } else {
+            let result = play(c1, c2);
+            result_channel
+                .send(WorkerMessage::BattleResult(result))
+                .unwrap();
         }
     }
-
-    writeln!(c1.stdin, "quit").expect("Failed to write to stdin");
-    writeln!(c2.stdin, "quit").expect("Failed to write to stdin");
 }
 
 pub enum WorkerMessage {
@@ -400,3 +1663,193 @@ pub enum WorkerMessage {
     BattleResultPair((BattleResult, BattleResult)),
     Done,
 }
+
+#[cfg(test)]
+mod tests {
+    use santorini_core::fen::parse_fen;
+    use santorini_core::uci_types::BestMoveMeta;
+
+    use super::*;
+
+    fn fake_best_move(start_state: &FullGameState, next_state: FullGameState) -> BestMoveOutput {
+        BestMoveOutput {
+            original_str: None,
+            start_state: start_state.clone(),
+            next_state,
+            trigger: BestMoveTrigger::StopFlag,
+            meta: BestMoveMeta {
+                score: 0,
+                calculated_depth: 1,
+                nodes_visited: None,
+                elapsed_seconds: 0.0,
+                actions: Vec::new(),
+                action_str: Some("fake".to_owned()),
+                win_reason: None,
+                nodes_per_second: None,
+                seldepth: None,
+                tt_hit_rate: None,
+                god_data: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_engine_spec_names() {
+        let plain = EngineSpec::new("v121");
+        assert_eq!(plain.engine_name(), "all_versions/v121");
+
+        // The same binary with a different param is a different engine
+        let tuned = EngineSpec::new("v121")
+            .with_init_command("setoption param stable_move_min_depths=6")
+            .with_label("stable_move_min_depths=6");
+        assert_eq!(
+            tuned.engine_name(),
+            "all_versions/v121[stable_move_min_depths=6]"
+        );
+        assert_ne!(plain, tuned);
+    }
+
+    #[test]
+    fn test_summarize_by_version() {
+        let result =
+            |engine1: &str, version1: &str, engine2: &str, winner: Option<Player>| BattleResult {
+                god1: GodName::Mortal,
+                engine1: engine1.to_owned(),
+                god2: GodName::Mortal,
+                engine2: engine2.to_owned(),
+                engine1_version: version1.to_owned(),
+                engine2_version: "abc-release".to_owned(),
+                winning_player: winner,
+                moves_made: 10,
+                win_reason: None,
+                draw_reason: winner.is_none().then_some(DrawReason::MoveLimit),
+                position_name: None,
+                game_id: None,
+                move_stats: Vec::new(),
+            };
+        let results = [
+            // The same build under two names
+            result(
+                "all_versions/new",
+                "abc-release",
+                "all_versions/old",
+                Some(Player::One),
+            ),
+            result(
+                "all_versions/renamed",
+                "abc-release",
+                "all_versions/old",
+                Some(Player::Two),
+            ),
+            // Results from before versions were recorded fall back to the engine name
+            result("all_versions/v1", "", "all_versions/old", Some(Player::One)),
+            result("all_versions/v1", "", "all_versions/old", None),
+        ];
+        assert_eq!(
+            results[3].game_result(),
+            GameResult::Draw(DrawReason::MoveLimit)
+        );
+        assert_eq!(results[3].winning_engine(), None);
+        assert_eq!(results[3].score_for(Player::Two), 0.5);
+
+        let records = summarize_by_version(&results);
+        assert_eq!(
+            records,
+            vec![
+                VersionRecord {
+                    version: "abc-release".to_owned(),
+                    wins: 2,
+                    draws: 1,
+                    games: 4,
+                },
+                VersionRecord {
+                    version: "all_versions/v1".to_owned(),
+                    wins: 1,
+                    draws: 1,
+                    games: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(
+            sanitize_file_name("worker-2026-01-02 03:04:05.678.log"),
+            "worker-2026-01-02 03-04-05.678.log"
+        );
+        assert_eq!(
+            sanitize_file_name("all_versions/v121[a=1]"),
+            "all_versions-v121[a=1]"
+        );
+        assert_eq!(sanitize_file_name("a\\b|c\"d?e*f<g>h"), "a-b-c-d-e-f-g-h");
+        assert_eq!(sanitize_file_name("tab\tname. ."), "tab-name");
+    }
+
+    #[test]
+    fn test_prepare_subprocess_missing_engine() {
+        let engine_path = PathBuf::from(BINARY_DIRECTORY).join("no_such_engine");
+        let result = prepare_subprocess(&engine_path, &SpawnConfig::new("missing.log"));
+        match result {
+            Err(SpawnError::Io(err)) => assert!(err.contains("not found"), "{}", err),
+            Err(err) => panic!("Expected a spawn error, got {}", err),
+            Ok(_) => panic!("Started an engine that doesn't exist"),
+        }
+    }
+
+    #[test]
+    fn test_empty_board_corpus_positions() {
+        let matchup = Matchup::new(GodName::Mortal, GodName::Bia);
+        let selector = MatchupSelector::default()
+            .with_exact_gods_for_player(Player::One, &[GodName::Mortal])
+            .with_exact_gods_for_player(Player::Two, &[GodName::Bia]);
+        let corpus = Corpus::empty_boards(&selector);
+        assert_eq!(corpus.positions.len(), 1);
+
+        // Empty boards round trip through the corpus file, and are played with both colors
+        let corpus: Corpus =
+            serde_yaml::from_str(&serde_yaml::to_string(&corpus).unwrap()).unwrap();
+        let state = &corpus.positions[0].state;
+        assert!(state.is_placement_phase());
+        assert_eq!(
+            state.get_current_player_consider_placement_mode(),
+            Player::Two
+        );
+
+        let engine1 = EngineSpec::new("v1");
+        let engine2 = EngineSpec::new("v2");
+        let assignments = corpus_assignments(
+            &corpus,
+            &MatchupSelector::default(),
+            false,
+            &engine1,
+            &engine2,
+        );
+        assert_eq!(assignments.len(), 2);
+        assert_eq!(
+            assignments[0].position_name,
+            Some(format!("empty_{}", matchup))
+        );
+    }
+
+    #[test]
+    fn test_verify_engine_move() {
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        for child in state.get_next_states() {
+            assert!(verify_engine_move(&state, &fake_best_move(&state, child)).is_ok());
+        }
+
+        // An engine that plays for both players at once
+        let quiet_child = state
+            .get_next_states()
+            .into_iter()
+            .find(|child| child.get_winner().is_none())
+            .unwrap();
+        let grandchild = quiet_child.get_next_states().pop().unwrap();
+        let err = verify_engine_move(&state, &fake_best_move(&state, grandchild)).unwrap_err();
+        assert!(err.contains("claimed move: fake"));
+
+        // An engine that doesn't move at all
+        assert!(verify_engine_move(&state, &fake_best_move(&state, state.clone())).is_err());
+    }
+}
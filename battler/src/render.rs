@@ -0,0 +1,238 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use santorini_core::board::FullGameState;
+use santorini_core::player::Player;
+use santorini_core::review::{MoveJudgment, MoveSeverity, review_game};
+use santorini_core::svg::state_to_svg;
+
+use crate::{BattleResult, GAME_LOG_DIRECTORY, GameLogEntry, read_game_log};
+
+/// Renders are named `<game id>-<kind>.<extension>`, next to the game's log
+pub const FINAL_RENDER: &str = "final";
+pub const BLUNDER_RENDER: &str = "blunder";
+
+/// The log of the game `game_id`
+pub fn game_log_path(game_id: &str) -> PathBuf {
+    PathBuf::from(GAME_LOG_DIRECTORY).join(format!("{}.jsonl", game_id))
+}
+
+/// Where the `kind` render of the game `game_id` goes
+pub fn render_path(game_id: &str, kind: &str, extension: &str) -> PathBuf {
+    PathBuf::from(GAME_LOG_DIRECTORY).join(format!("{}-{}.{}", game_id, kind, extension))
+}
+
+/// Every position of a logged game, from its start to its final position. Fails for unfinished
+/// games and for logs from before the final position was recorded
+pub fn game_log_states(entries: &[GameLogEntry]) -> Result<Vec<FullGameState>, String> {
+    let mut states = Vec::new();
+    for entry in entries {
+        match entry {
+            GameLogEntry::Header { .. } => {}
+            GameLogEntry::Move { state, .. } => states.push(state.clone()),
+            GameLogEntry::Footer { final_state, .. } => {
+                let final_state = final_state
+                    .as_ref()
+                    .ok_or("Game log doesn't record its final position")?;
+                states.push(final_state.clone());
+                return Ok(states);
+            }
+        }
+    }
+    Err("Missing footer, was the game finished?".to_owned())
+}
+
+fn read_game_states(game_id: &str) -> Result<Vec<FullGameState>, String> {
+    let path = game_log_path(game_id);
+    let entries = read_game_log(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    game_log_states(&entries)
+}
+
+#[cfg(feature = "png")]
+fn write_png(svg: &str, path: &Path) -> Result<(), String> {
+    use std::sync::{Arc, LazyLock};
+
+    use resvg::{tiny_skia, usvg};
+
+    // Loading the system fonts is slow, so it's only done once
+    static FONTS: LazyLock<Arc<usvg::fontdb::Database>> = LazyLock::new(|| {
+        let mut fonts = usvg::fontdb::Database::new();
+        fonts.load_system_fonts();
+        Arc::new(fonts)
+    });
+
+    let options = usvg::Options {
+        fontdb: Arc::clone(&FONTS),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|err| err.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("Image has no area")?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .save_png(path)
+        .map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Writes `state` as the `kind` render of `game_id`. Always as an SVG, and also as a PNG when
+/// built with the `png` feature. Returns the path of the SVG
+pub fn write_render(game_id: &str, kind: &str, state: &FullGameState) -> Result<PathBuf, String> {
+    let svg = state_to_svg(state);
+    let svg_path = render_path(game_id, kind, "svg");
+    std::fs::write(&svg_path, &svg).map_err(|err| format!("{}: {}", svg_path.display(), err))?;
+    #[cfg(feature = "png")]
+    write_png(&svg, &render_path(game_id, kind, "png"))?;
+    Ok(svg_path)
+}
+
+/// Renders the last position of the logged game `game_id`
+pub fn render_final_position(game_id: &str) -> Result<PathBuf, String> {
+    let states = read_game_states(game_id)?;
+    // game_log_states always ends with the final position
+    write_render(game_id, FINAL_RENDER, states.last().unwrap())
+}
+
+/// The last blunder by `loser`, taken to be the move that decided the game
+pub fn decisive_blunder(judgments: &[MoveJudgment], loser: Player) -> Option<&MoveJudgment> {
+    judgments.iter().rev().find(|judgment| {
+        judgment.player == loser && judgment.severity == Some(MoveSeverity::Blunder)
+    })
+}
+
+/// Reviews the logged game `game_id` with `nodes_per_position` nodes per position, and renders
+/// the position `loser` played their decisive blunder from. Returns None if they didn't blunder
+pub fn render_decisive_blunder(
+    game_id: &str,
+    loser: Player,
+    nodes_per_position: usize,
+) -> Result<Option<PathBuf>, String> {
+    let states = read_game_states(game_id)?;
+    let judgments = review_game(&states, nodes_per_position);
+    decisive_blunder(&judgments, loser)
+        .map(|judgment| write_render(game_id, BLUNDER_RENDER, &states[judgment.ply]))
+        .transpose()
+}
+
+/// Links to the renders of `kind` that exist for `game_id`, relative to GAME_LOG_DIRECTORY
+fn render_links(game_id: &str, kind: &str) -> String {
+    ["svg", "png"]
+        .into_iter()
+        .filter(|extension| render_path(game_id, kind, extension).exists())
+        .map(|extension| format!("[{}](<{}-{}.{}>)", extension, game_id, kind, extension))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A markdown table of `results`, linking each game to its log and renders. Links are relative,
+/// so the summary belongs in GAME_LOG_DIRECTORY
+pub fn write_render_summary(results: &[BattleResult], path: &Path) -> Result<(), String> {
+    let mut summary = String::from("# Games\n\n");
+    summary.push_str("| Game | Position | Player One | Player Two | Result | Moves | Final | Decisive blunder |\n");
+    summary.push_str("|---|---|---|---|---|---|---|---|\n");
+    for result in results {
+        let Some(game_id) = &result.game_id else {
+            continue;
+        };
+        let _ = writeln!(
+            summary,
+            "| [{}](<{}.jsonl>) | {} | {} ({}) | {} ({}) | {} | {} | {} | {} |",
+            game_id,
+            game_id,
+            result.position_name.as_deref().unwrap_or_default(),
+            result.engine1,
+            result.god1,
+            result.engine2,
+            result.god2,
+            result.game_result(),
+            result.moves_made,
+            render_links(game_id, FINAL_RENDER),
+            render_links(game_id, BLUNDER_RENDER),
+        );
+    }
+    std::fs::write(path, summary).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use santorini_core::fen::parse_fen;
+
+    use super::*;
+
+    #[test]
+    fn test_game_log_states() {
+        let start = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let winning_state = start
+            .get_next_states()
+            .into_iter()
+            .find(|child| child.get_winner() == Some(Player::One))
+            .unwrap();
+        let header = GameLogEntry::Header {
+            version: crate::GAME_LOG_VERSION,
+            god1: start.gods[0].god_name,
+            engine1: "a".to_owned(),
+            god2: start.gods[1].god_name,
+            engine2: "b".to_owned(),
+            start_state: start.clone(),
+            time_control: crate::GameLogTimeControl::PerTurn { seconds: 1.0 },
+        };
+        let played = GameLogEntry::Move {
+            move_number: 1,
+            engine: "a".to_owned(),
+            player: Player::One,
+            state: start.clone(),
+            action_str: None,
+            score: 0,
+            depth: 1,
+            nodes_visited: None,
+            elapsed_seconds: 0.0,
+        };
+        let footer = |final_state: Option<FullGameState>| GameLogEntry::Footer {
+            winning_player: Some(Player::One),
+            winning_engine: Some("a".to_owned()),
+            moves_made: 1,
+            win_reason: None,
+            draw_reason: None,
+            final_state,
+        };
+
+        let entries = [
+            header.clone(),
+            played.clone(),
+            footer(Some(winning_state.clone())),
+        ];
+        assert_eq!(
+            game_log_states(&entries).unwrap(),
+            vec![start.clone(), winning_state]
+        );
+
+        // Old logs don't say where the game ended, and unfinished ones haven't ended
+        assert!(game_log_states(&[header.clone(), played.clone(), footer(None)]).is_err());
+        assert!(game_log_states(&[header, played]).is_err());
+    }
+
+    #[test]
+    fn test_decisive_blunder() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let judgment = |ply: usize, player: Player, severity: Option<MoveSeverity>| MoveJudgment {
+            ply,
+            player,
+            played_state: state.clone(),
+            played_score: 0,
+            best_score: 0,
+            best_move: None,
+            severity,
+        };
+        let judgments = [
+            judgment(0, Player::One, Some(MoveSeverity::Blunder)),
+            judgment(1, Player::Two, Some(MoveSeverity::Blunder)),
+            judgment(2, Player::One, Some(MoveSeverity::Blunder)),
+            judgment(3, Player::Two, Some(MoveSeverity::Mistake)),
+            judgment(4, Player::One, None),
+        ];
+
+        assert_eq!(decisive_blunder(&judgments, Player::One).unwrap().ply, 2);
+        assert_eq!(decisive_blunder(&judgments, Player::Two).unwrap().ply, 1);
+        assert!(decisive_blunder(&judgments[3..], Player::Two).is_none());
+    }
+}
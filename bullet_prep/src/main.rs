@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File, OpenOptions, remove_file};
 use std::hash::Hash;
 use std::io::{BufRead, BufReader, BufWriter, Read, Write};
@@ -10,12 +10,15 @@ use rand::seq::SliceRandom;
 use rand::{Rng, rng};
 use santorini_core::bitboard::BitBoard;
 use santorini_core::board::{BoardState, FullGameState, GodData, GodPair};
+use santorini_core::explorer::ExplorerIndex;
+use santorini_core::fen::extract_matchup_from_fen;
 use santorini_core::gods::{
     GOD_FEATURE_OFFSETS, GodName, TOTAL_GOD_DATA_FEATURE_COUNT, god_name_to_nnue_size,
 };
 use santorini_core::matchup::Matchup;
-use santorini_core::nnue::emit_god_data_features;
+use santorini_core::nnue::{LabeledAccumulator, emit_god_data_features, has_own_model};
 use santorini_core::player::Player;
+use santorini_core::search::Heuristic;
 use santorini_core::utils::timestamp_string;
 
 // !!! BulletSantoriniBoard needs to match exactly with the definition in santorini-trainer rep
@@ -146,10 +149,18 @@ fn _extract_god_data_to_u32(god: GodName, data: GodData) -> u32 {
 /// Extracts just the matchup from a raw data line (space-separated fields starting with a FEN).
 fn extract_matchup_from_line(line: &str) -> Option<Matchup> {
     let fen = line.split_once(' ')?.0;
-    santorini_core::fen::extract_matchup_from_fen(fen)
+    extract_matchup_from_fen(fen)
 }
 
-fn convert_row_to_board_and_meta(row: &str) -> Option<(FullGameState, Player)> {
+/// The columns of a single raw data row, as written by datagen
+struct RawRow<'a> {
+    fen: &'a str,
+    winner: Player,
+    score: Heuristic,
+    ply: usize,
+}
+
+fn split_raw_row(row: &str) -> Option<RawRow<'_>> {
     let parts: Vec<_> = row.split(' ').collect();
     if parts.len() < 6 {
         eprintln!("skipping malformed row: {}", row);
@@ -157,13 +168,13 @@ fn convert_row_to_board_and_meta(row: &str) -> Option<(FullGameState, Player)> {
     }
     let fen_str = parts[0];
     let winner_str = parts[1];
-    let _score_str = parts[2];
-    let _ply_str = parts[3];
+    let score_str = parts[2];
+    let ply_str = parts[3];
     let _depth_str = parts[4];
     let _nodes_str = parts[5];
 
-    let full_state = FullGameState::try_from(fen_str).expect("Could not parse fen");
-    // let score: i16 = score_str.parse().expect("Could not parse score");
+    let score: Heuristic = score_str.parse().expect("Could not parse score");
+    let ply: usize = ply_str.parse().expect("Could not parse ply");
     let winner_idx: i32 = winner_str.parse().expect("Could not parse winner");
 
     let winner = match winner_idx {
@@ -172,7 +183,19 @@ fn convert_row_to_board_and_meta(row: &str) -> Option<(FullGameState, Player)> {
         _ => panic!("Winner string must be either 1 or 2"),
     };
 
-    Some((full_state, winner))
+    Some(RawRow {
+        fen: fen_str,
+        winner,
+        score,
+        ply,
+    })
+}
+
+fn convert_row_to_board_and_meta(row: &str) -> Option<(FullGameState, Player)> {
+    let raw_row = split_raw_row(row)?;
+    let full_state = FullGameState::try_from(raw_row.fen).expect("Could not parse fen");
+
+    Some((full_state, raw_row.winner))
 }
 
 fn write_data_file<T: Copy>(items: &[T], path: &PathBuf) -> std::io::Result<()> {
@@ -610,6 +633,67 @@ enum Command {
         #[arg(short, long, default_value = ",", help = "Output field delimiter")]
         delimiter: String,
     },
+    /// Print a gods x gods matrix of player 1 win rates and write it as csv
+    WinRates {
+        #[arg(help = "Input directory containing raw .txt data files")]
+        input_dir: PathBuf,
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Skip games whose last recorded ply is below this"
+        )]
+        min_ply: usize,
+        #[arg(
+            long,
+            help = "Skip games whose first recorded position has an absolute score above this"
+        )]
+        max_opening_score: Option<Heuristic>,
+        #[arg(long, default_value = "win_rates.csv", help = "Output csv path")]
+        csv: PathBuf,
+    },
+    /// Compare fallback NNUE models against each god's own model on sampled positions, per matchup
+    ModelAudit {
+        #[arg(help = "Input directory containing raw .txt data files")]
+        input_dir: PathBuf,
+        #[arg(
+            long,
+            default_value_t = 0.05,
+            help = "Fraction of positions to evaluate, between 0 and 1"
+        )]
+        sample_rate: f64,
+        #[arg(
+            long,
+            default_value_t = 150.0,
+            help = "Flag matchups whose mean absolute eval difference is above this"
+        )]
+        max_mean_diff: f32,
+        #[arg(
+            long,
+            default_value_t = 0.15,
+            help = "Flag matchups whose evals disagree on the winning side more often than this"
+        )]
+        max_sign_flip_rate: f32,
+        #[arg(long, default_value = "model_audit.csv", help = "Output csv path")]
+        csv: PathBuf,
+    },
+    /// Index the moves played from each opening position, for the UI's explorer
+    ExplorerIndex {
+        #[arg(help = "Input directory containing raw .txt data files")]
+        input_dir: PathBuf,
+        #[arg(
+            long,
+            default_value_t = 8,
+            help = "Only index moves played before this ply, counting placements"
+        )]
+        max_ply: usize,
+        #[arg(
+            short,
+            long,
+            default_value = "explorer.idx",
+            help = "Output index path"
+        )]
+        output: PathBuf,
+    },
 }
 
 fn run_prep(is_delete: bool) -> Result<(), Box<dyn std::error::Error>> {
@@ -795,14 +879,15 @@ fn custom_split(input_dir: PathBuf, output_dir: PathBuf) -> Result<(), Box<dyn s
     Ok(())
 }
 
-/// Parallelizes over all .txt files in `input_dir`, classifies each line's matchup
-/// via `classify_fn`, and returns merged counts per key.
-fn parallel_count_by<K>(
+/// Parallelizes over all .txt files in `input_dir`. Each worker folds the files it reads into its
+/// own accumulator with `fold_file`, and the accumulators are combined with `merge` at the end.
+fn parallel_fold_files<T>(
     input_dir: &PathBuf,
-    classify_fn: impl Fn(&Matchup) -> K + Send + Sync + 'static,
-) -> Result<HashMap<K, usize>, Box<dyn std::error::Error>>
+    fold_file: impl Fn(&mut T, &mut dyn BufRead) + Send + Sync + 'static,
+    merge: impl Fn(&mut T, T),
+) -> Result<T, Box<dyn std::error::Error>>
 where
-    K: Hash + Eq + Send + 'static,
+    T: Default + Send + 'static,
 {
     let all_data_files = all_filenames_in_dir(input_dir)?;
     let total_files = all_data_files.len();
@@ -818,51 +903,68 @@ where
 
     let queue = Arc::new(Mutex::new(all_data_files));
     let pb = Arc::new(pb);
-    let classify_fn = Arc::new(classify_fn);
+    let fold_file = Arc::new(fold_file);
 
     let mut handles = Vec::with_capacity(num_workers);
     for _ in 0..num_workers {
         let queue = Arc::clone(&queue);
         let pb = Arc::clone(&pb);
-        let classify_fn = Arc::clone(&classify_fn);
+        let fold_file = Arc::clone(&fold_file);
         handles.push(std::thread::spawn(move || {
-            let mut local_counts: HashMap<K, usize> = HashMap::new();
-            let mut line_buf = String::new();
+            let mut local = T::default();
             loop {
                 let next_file = queue.lock().unwrap().pop();
                 let Some(file_path) = next_file else { break };
 
                 let file_handle = File::open(&file_path).expect("Failed to open file");
                 let mut reader = BufReader::new(file_handle);
-
-                line_buf.clear();
-                while reader
-                    .read_line(&mut line_buf)
-                    .expect("Failed to read line")
-                    > 0
-                {
-                    if let Some(matchup) = extract_matchup_from_line(&line_buf) {
-                        *local_counts.entry(classify_fn(&matchup)).or_default() += 1;
-                    }
-                    line_buf.clear();
-                }
+                fold_file(&mut local, &mut reader);
 
                 pb.inc(1);
             }
-            local_counts
+            local
         }));
     }
 
-    let mut counts: HashMap<K, usize> = HashMap::new();
+    let mut result = T::default();
     for handle in handles {
-        let local = handle.join().expect("Thread panicked");
-        for (key, count) in local {
-            *counts.entry(key).or_default() += count;
-        }
+        merge(&mut result, handle.join().expect("Thread panicked"));
     }
 
     pb.finish_and_clear();
-    Ok(counts)
+    Ok(result)
+}
+
+/// Parallelizes over all .txt files in `input_dir`, classifies each line's matchup
+/// via `classify_fn`, and returns merged counts per key.
+fn parallel_count_by<K>(
+    input_dir: &PathBuf,
+    classify_fn: impl Fn(&Matchup) -> K + Send + Sync + 'static,
+) -> Result<HashMap<K, usize>, Box<dyn std::error::Error>>
+where
+    K: Hash + Eq + Send + 'static,
+{
+    parallel_fold_files(
+        input_dir,
+        move |counts: &mut HashMap<K, usize>, reader| {
+            let mut line_buf = String::new();
+            while reader
+                .read_line(&mut line_buf)
+                .expect("Failed to read line")
+                > 0
+            {
+                if let Some(matchup) = extract_matchup_from_line(&line_buf) {
+                    *counts.entry(classify_fn(&matchup)).or_default() += 1;
+                }
+                line_buf.clear();
+            }
+        },
+        |counts, local| {
+            for (key, count) in local {
+                *counts.entry(key).or_default() += count;
+            }
+        },
+    )
 }
 
 fn run_stats(
@@ -895,6 +997,495 @@ fn run_stats(
     Ok(())
 }
 
+#[derive(Clone, Copy, Debug, Default)]
+struct WinRateCell {
+    games: usize,
+    p1_wins: usize,
+}
+
+impl WinRateCell {
+    fn p1_win_rate(&self) -> f32 {
+        self.p1_wins as f32 / self.games as f32
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct WinRateFilter {
+    min_ply: usize,
+    max_opening_score: Option<Heuristic>,
+}
+
+/// The parts of a single game that are needed for win rates.
+/// Only the first and latest rows of a game are kept, so memory doesn't grow with game length
+struct GameSummary {
+    matchup: Matchup,
+    winner: Player,
+    opening_score: Heuristic,
+    last_ply: usize,
+}
+
+impl GameSummary {
+    fn new(matchup: Matchup, row: &RawRow) -> Self {
+        GameSummary {
+            matchup,
+            winner: row.winner,
+            opening_score: row.score,
+            last_ply: row.ply,
+        }
+    }
+
+    /// Datagen writes each game as a run of rows with consecutive plies. Subgames branch off an
+    /// earlier ply, so they show up as a new game
+    fn is_continued_by(&self, matchup: &Matchup, row: &RawRow) -> bool {
+        self.matchup == *matchup && self.winner == row.winner && self.last_ply + 1 == row.ply
+    }
+
+    fn is_included(&self, filter: &WinRateFilter) -> bool {
+        if self.last_ply < filter.min_ply {
+            return false;
+        }
+        match filter.max_opening_score {
+            Some(max_score) => self.opening_score.abs() <= max_score,
+            None => true,
+        }
+    }
+}
+
+fn tally_game(
+    cells: &mut HashMap<Matchup, WinRateCell>,
+    game: GameSummary,
+    filter: &WinRateFilter,
+) {
+    if !game.is_included(filter) {
+        return;
+    }
+    let cell = cells.entry(game.matchup).or_default();
+    cell.games += 1;
+    if game.winner == Player::One {
+        cell.p1_wins += 1;
+    }
+}
+
+fn fold_win_rates_file(
+    cells: &mut HashMap<Matchup, WinRateCell>,
+    reader: &mut dyn BufRead,
+    filter: &WinRateFilter,
+) {
+    let mut current_game: Option<GameSummary> = None;
+    for line in reader.lines() {
+        let line = line.expect("Failed to read line");
+        let Some(row) = split_raw_row(&line) else {
+            continue;
+        };
+        let Some(matchup) = extract_matchup_from_fen(row.fen) else {
+            continue;
+        };
+
+        match &mut current_game {
+            Some(game) if game.is_continued_by(&matchup, &row) => game.last_ply = row.ply,
+            _ => {
+                if let Some(finished) = current_game.replace(GameSummary::new(matchup, &row)) {
+                    tally_game(cells, finished, filter);
+                }
+            }
+        }
+    }
+
+    if let Some(finished) = current_game {
+        tally_game(cells, finished, filter);
+    }
+}
+
+fn print_win_rate_table(cells: &BTreeMap<Matchup, WinRateCell>) {
+    let p1_gods: BTreeSet<GodName> = cells.keys().map(|m| m.gods[0]).collect();
+    let p2_gods: BTreeSet<GodName> = cells.keys().map(|m| m.gods[1]).collect();
+    let name_width = p1_gods
+        .iter()
+        .chain(p2_gods.iter())
+        .map(|god| god.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let header = "P1 \\ P2";
+    let name_width = name_width.max(header.len());
+    let cell_width = name_width.max(11);
+
+    print!("{:<name_width$}", header);
+    for god in &p2_gods {
+        print!(" | {:>cell_width$}", god.to_string());
+    }
+    println!();
+
+    for p1_god in &p1_gods {
+        print!("{:<name_width$}", p1_god.to_string());
+        for p2_god in &p2_gods {
+            let text = match cells.get(&Matchup::new(*p1_god, *p2_god)) {
+                Some(cell) => format!("{:.1}% ({})", cell.p1_win_rate() * 100.0, cell.games),
+                None => "-".to_owned(),
+            };
+            print!(" | {:>cell_width$}", text);
+        }
+        println!();
+    }
+}
+
+fn write_win_rate_csv(
+    cells: &BTreeMap<Matchup, WinRateCell>,
+    path: &PathBuf,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "god1,god2,games,p1_wins,p1_win_rate")?;
+    for (matchup, cell) in cells {
+        writeln!(
+            writer,
+            "{},{},{},{},{:.4}",
+            matchup.gods[0],
+            matchup.gods[1],
+            cell.games,
+            cell.p1_wins,
+            cell.p1_win_rate()
+        )?;
+    }
+    writer.flush()
+}
+
+fn run_win_rates(
+    input_dir: PathBuf,
+    filter: WinRateFilter,
+    csv_path: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cells = parallel_fold_files(
+        &input_dir,
+        move |cells: &mut HashMap<Matchup, WinRateCell>, reader| {
+            fold_win_rates_file(cells, reader, &filter)
+        },
+        |cells, local| {
+            for (matchup, cell) in local {
+                let total = cells.entry(matchup).or_default();
+                total.games += cell.games;
+                total.p1_wins += cell.p1_wins;
+            }
+        },
+    )?;
+    let cells: BTreeMap<Matchup, WinRateCell> = cells.into_iter().collect();
+
+    print_win_rate_table(&cells);
+    write_win_rate_csv(&cells, &csv_path)?;
+    println!(
+        "{} Wrote {} matchups to {:?}",
+        timestamp_string(),
+        cells.len(),
+        csv_path
+    );
+
+    Ok(())
+}
+
+/// How a matchup's assigned models compare with its gods' own models, over sampled positions
+#[derive(Clone, Copy, Debug, Default)]
+struct ModelAuditCell {
+    positions: usize,
+    total_abs_diff: u64,
+    sign_flips: usize,
+}
+
+impl ModelAuditCell {
+    fn add(&mut self, assigned_eval: Heuristic, exact_eval: Heuristic) {
+        self.positions += 1;
+        self.total_abs_diff += (assigned_eval as i64 - exact_eval as i64).unsigned_abs();
+        if assigned_eval.signum() * exact_eval.signum() < 0 {
+            self.sign_flips += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &ModelAuditCell) {
+        self.positions += other.positions;
+        self.total_abs_diff += other.total_abs_diff;
+        self.sign_flips += other.sign_flips;
+    }
+
+    fn mean_abs_diff(&self) -> f32 {
+        self.total_abs_diff as f32 / self.positions as f32
+    }
+
+    fn sign_flip_rate(&self) -> f32 {
+        self.sign_flips as f32 / self.positions as f32
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ModelAuditThresholds {
+    max_mean_abs_diff: f32,
+    max_sign_flip_rate: f32,
+}
+
+impl ModelAuditThresholds {
+    fn is_flagged(&self, cell: &ModelAuditCell) -> bool {
+        cell.mean_abs_diff() > self.max_mean_abs_diff
+            || cell.sign_flip_rate() > self.max_sign_flip_rate
+    }
+}
+
+enum AuditModels {
+    /// Both gods are already evaluated with their own models
+    Exact,
+    /// A fallback god has no model of its own to compare against
+    Unavailable,
+    Compare {
+        assigned: [GodName; 2],
+        exact: [GodName; 2],
+    },
+}
+
+fn audit_models(matchup: &Matchup) -> AuditModels {
+    let assigned = matchup.gods.map(|god| god.to_power().model_god_name);
+    if assigned == matchup.gods {
+        AuditModels::Exact
+    } else if !matchup.gods.iter().all(|god| has_own_model(*god)) {
+        AuditModels::Unavailable
+    } else {
+        AuditModels::Compare {
+            assigned,
+            exact: matchup.gods,
+        }
+    }
+}
+
+fn is_fallback_god(god: GodName) -> bool {
+    god.to_power().model_god_name != god
+}
+
+#[derive(Debug, Default)]
+struct ModelAudit {
+    cells: HashMap<Matchup, ModelAuditCell>,
+    /// Rows skipped because a fallback god in the matchup has no model of its own
+    unavailable_rows: HashMap<Matchup, usize>,
+}
+
+fn fold_model_audit_file(audit: &mut ModelAudit, reader: &mut dyn BufRead, sample_rate: f64) {
+    let mut rng = rng();
+    for line in reader.lines() {
+        let line = line.expect("Failed to read line");
+        let Some(matchup) = extract_matchup_from_line(&line) else {
+            continue;
+        };
+        let (assigned, exact) = match audit_models(&matchup) {
+            AuditModels::Exact => continue,
+            AuditModels::Unavailable => {
+                *audit.unavailable_rows.entry(matchup).or_default() += 1;
+                continue;
+            }
+            AuditModels::Compare { assigned, exact } => (assigned, exact),
+        };
+        if !rng.random_bool(sample_rate) {
+            continue;
+        }
+        let Some((state, _)) = convert_row_to_board_and_meta(&line) else {
+            continue;
+        };
+        if state.get_winner().is_some() {
+            continue;
+        }
+
+        let assigned_eval = LabeledAccumulator::new_with_models(&state, assigned).evaluate();
+        let exact_eval = LabeledAccumulator::new_with_models(&state, exact).evaluate();
+        audit
+            .cells
+            .entry(matchup)
+            .or_default()
+            .add(assigned_eval, exact_eval);
+    }
+}
+
+fn print_model_audit_row(label: &str, cell: &ModelAuditCell, thresholds: &ModelAuditThresholds) {
+    println!(
+        "{:<32} {:>9} {:>11.1} {:>9.1}%{}",
+        label,
+        cell.positions,
+        cell.mean_abs_diff(),
+        cell.sign_flip_rate() * 100.0,
+        if thresholds.is_flagged(cell) {
+            "  <- badly calibrated"
+        } else {
+            ""
+        }
+    );
+}
+
+fn print_model_audit(audit: &ModelAudit, thresholds: &ModelAuditThresholds) {
+    let header = format!(
+        "{:<32} {:>9} {:>11} {:>10}",
+        "", "Positions", "Mean |diff|", "Sign flips"
+    );
+
+    let mut matchups: Vec<(&Matchup, &ModelAuditCell)> = audit.cells.iter().collect();
+    matchups.sort_by(|a, b| b.1.mean_abs_diff().total_cmp(&a.1.mean_abs_diff()));
+    println!("By matchup, worst first");
+    println!("{}", header);
+    for (matchup, cell) in &matchups {
+        print_model_audit_row(&matchup.to_string(), cell, thresholds);
+    }
+
+    // The gods that most need their own training data
+    let mut god_cells: BTreeMap<GodName, ModelAuditCell> = BTreeMap::new();
+    for (matchup, cell) in &matchups {
+        let fallback_gods: BTreeSet<GodName> = matchup
+            .gods
+            .into_iter()
+            .filter(|god| is_fallback_god(*god))
+            .collect();
+        for god in fallback_gods {
+            god_cells.entry(god).or_default().merge(cell);
+        }
+    }
+    let mut gods: Vec<(GodName, ModelAuditCell)> = god_cells.into_iter().collect();
+    gods.sort_by(|a, b| b.1.mean_abs_diff().total_cmp(&a.1.mean_abs_diff()));
+    println!();
+    println!("By fallback god, worst first");
+    println!("{}", header);
+    for (god, cell) in &gods {
+        let label = format!("{} (as {})", god, god.to_power().model_god_name);
+        print_model_audit_row(&label, cell, thresholds);
+    }
+
+    if !audit.unavailable_rows.is_empty() {
+        let unavailable_gods: BTreeSet<GodName> = audit
+            .unavailable_rows
+            .keys()
+            .flat_map(|matchup| matchup.gods)
+            .filter(|god| is_fallback_god(*god) && !has_own_model(*god))
+            .collect();
+        println!();
+        println!(
+            "Skipped {} rows in {} matchups with no model to compare against, for: {}",
+            audit.unavailable_rows.values().sum::<usize>(),
+            audit.unavailable_rows.len(),
+            unavailable_gods
+                .iter()
+                .map(GodName::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
+fn write_model_audit_csv(
+    audit: &ModelAudit,
+    thresholds: &ModelAuditThresholds,
+    path: &PathBuf,
+) -> std::io::Result<()> {
+    let cells: BTreeMap<&Matchup, &ModelAuditCell> = audit.cells.iter().collect();
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(
+        writer,
+        "god1,god2,model1,model2,positions,mean_abs_diff,sign_flip_rate,flagged"
+    )?;
+    for (matchup, cell) in cells {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{:.2},{:.4},{}",
+            matchup.gods[0],
+            matchup.gods[1],
+            matchup.gods[0].to_power().model_god_name,
+            matchup.gods[1].to_power().model_god_name,
+            cell.positions,
+            cell.mean_abs_diff(),
+            cell.sign_flip_rate(),
+            thresholds.is_flagged(cell)
+        )?;
+    }
+    writer.flush()
+}
+
+fn run_model_audit(
+    input_dir: PathBuf,
+    sample_rate: f64,
+    thresholds: ModelAuditThresholds,
+    csv_path: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(0.0..=1.0).contains(&sample_rate) {
+        return Err(format!("Sample rate must be between 0 and 1, got {}", sample_rate).into());
+    }
+
+    let audit = parallel_fold_files(
+        &input_dir,
+        move |audit: &mut ModelAudit, reader| fold_model_audit_file(audit, reader, sample_rate),
+        |audit, local| {
+            for (matchup, cell) in local.cells {
+                audit.cells.entry(matchup).or_default().merge(&cell);
+            }
+            for (matchup, rows) in local.unavailable_rows {
+                *audit.unavailable_rows.entry(matchup).or_default() += rows;
+            }
+        },
+    )?;
+
+    print_model_audit(&audit, &thresholds);
+    write_model_audit_csv(&audit, &thresholds, &csv_path)?;
+    println!(
+        "{} Wrote {} matchups to {:?}",
+        timestamp_string(),
+        audit.cells.len(),
+        csv_path
+    );
+
+    Ok(())
+}
+
+/// Records a transition for every pair of consecutive rows where the second position follows
+/// from the first. Datagen writes each game's main line in order, but not the random opening
+/// moves or the winning move, so only those transitions can be recovered
+fn fold_explorer_file(index: &mut ExplorerIndex, reader: &mut dyn BufRead, max_ply: usize) {
+    let mut prev: Option<(FullGameState, Player)> = None;
+    for line in reader.lines() {
+        let line = line.expect("Failed to read line");
+        let parent = prev.take();
+        let Some(row) = split_raw_row(&line) else {
+            continue;
+        };
+        // The row's move count doesn't include placements, so it's never ahead of the real ply
+        if row.ply > max_ply {
+            continue;
+        }
+        let Ok(state) = FullGameState::try_from(row.fen) else {
+            continue;
+        };
+
+        if let Some((parent, parent_winner)) = parent
+            && parent_winner == row.winner
+            && parent.ply < max_ply
+            && state.ply == parent.ply + 1
+            && parent.get_next_states().contains(&state)
+        {
+            index.record(&parent, &state, row.winner);
+        }
+        prev = Some((state, row.winner));
+    }
+}
+
+fn run_explorer_index(
+    input_dir: PathBuf,
+    max_ply: usize,
+    output: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let folded = parallel_fold_files(
+        &input_dir,
+        move |index: &mut ExplorerIndex, reader| fold_explorer_file(index, reader, max_ply),
+        |index, local| index.merge(local),
+    )?;
+    let mut index = ExplorerIndex::new(max_ply);
+    index.merge(folded);
+
+    index.save(&output)?;
+    println!(
+        "{} Wrote {} positions to {:?}",
+        timestamp_string(),
+        index.len(),
+        output
+    );
+
+    Ok(())
+}
+
 fn run_split_matchups_stats(input_dir: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -1001,6 +1592,39 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             gods,
             delimiter,
         } => run_stats(input_dir, gods, &delimiter)?,
+        Command::WinRates {
+            input_dir,
+            min_ply,
+            max_opening_score,
+            csv,
+        } => run_win_rates(
+            input_dir,
+            WinRateFilter {
+                min_ply,
+                max_opening_score,
+            },
+            csv,
+        )?,
+        Command::ModelAudit {
+            input_dir,
+            sample_rate,
+            max_mean_diff,
+            max_sign_flip_rate,
+            csv,
+        } => run_model_audit(
+            input_dir,
+            sample_rate,
+            ModelAuditThresholds {
+                max_mean_abs_diff: max_mean_diff,
+                max_sign_flip_rate,
+            },
+            csv,
+        )?,
+        Command::ExplorerIndex {
+            input_dir,
+            max_ply,
+            output,
+        } => run_explorer_index(input_dir, max_ply, output)?,
     }
     Ok(())
 }
@@ -1014,4 +1638,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 // cargo run -p bullet_prep -r -- custom-split ./game_data ./custom_output
 // cargo run -p bullet_prep -r -- split-matchups-stats ./game_data
 // cargo run -p bullet_prep -r -- stats ./game_data
+// cargo run -p bullet_prep -r -- win-rates ./game_data --min-ply 10 --max-opening-score 500
 // cargo run -p bullet_prep -r -- stats ./game_data --gods
+// cargo run -p bullet_prep -r -- explorer-index ./game_data --max-ply 12
+// cargo run -p bullet_prep -r -- model-audit ./game_data --sample-rate 0.1
@@ -1,37 +1,37 @@
 use clap::Parser;
+use progress::{ProgressDisplay, WorkerProgress};
 use rand::distr::Alphanumeric;
 use rand::seq::{IndexedRandom, IteratorRandom};
 use rand::{Rng, rng};
 use santorini_core::gods::{ALL_GODS_BY_ID, GodName};
-use santorini_core::matchup::{Matchup, MatchupArgs};
+use santorini_core::logging::LogArgs;
+use santorini_core::matchup::{BalancedSampler, Matchup, MatchupArgs};
 use santorini_core::placement::get_starting_placement_state;
 use santorini_core::player::Player;
 use santorini_core::search::{
-    Heuristic, SearchContext, WINNING_SCORE_BUFFER, get_win_reached_search_terminator,
+    Heuristic, SearchContext, SearchError, WINNING_SCORE_BUFFER, get_win_reached_search_terminator,
     negamax_search,
 };
-use santorini_core::search_terminators::{
-    AndSearchTerminator, OrSearchTerminator, StaticMaxDepthSearchTerminator,
-    StaticNodesVisitedSearchTerminator,
-};
+use santorini_core::search_terminators::{DynamicSearchTerminator, SearchLimits};
 use santorini_core::transposition_table::TranspositionTable;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
-use std::sync::Arc;
-use std::thread;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, Sender, channel};
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-use santorini_core::board::FullGameState;
+use santorini_core::board::{ConsoleRenderOptions, FullGameState};
 use santorini_core::random_utils::get_random_state_flattening_powers;
 
-type DatagenStaticSearchTerminator = OrSearchTerminator<
-    StaticNodesVisitedSearchTerminator<100_000>,
-    AndSearchTerminator<
-        StaticMaxDepthSearchTerminator<8>,
-        StaticNodesVisitedSearchTerminator<20_000>,
-    >,
->;
+mod progress;
+
+const DATAGEN_SEARCH_LIMITS: SearchLimits = SearchLimits::new()
+    .with_max_nodes(100_000)
+    .with_max_depth(8)
+    .with_min_nodes(20_000);
 
 #[derive(Debug)]
 struct SingleState {
@@ -65,18 +65,44 @@ fn _get_new_datafile_name(rng: &mut impl Rng) -> PathBuf {
 const GAMES_PER_FILE: usize = 1_000;
 const MIN_EXAMPLES_PER_MATCHUP: usize = 50;
 const MIN_GAME_LENGTH: usize = 5;
+/// How often the status table is redrawn
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What each worker thread needs to share with the main thread
+#[derive(Clone)]
+struct WorkerCtx {
+    sampler: Arc<Mutex<BalancedSampler>>,
+    is_balanced: bool,
+    progress_sender: Sender<WorkerProgress>,
+    /// Set once the target number of examples is reached. Workers finish their current game first
+    stop_flag: Arc<AtomicBool>,
+}
+
+fn spawn_worker(worker_id: usize, ctx: WorkerCtx) -> JoinHandle<()> {
+    thread::spawn(move || worker_thread(worker_id, ctx))
+}
 
-fn worker_thread(matchups: Arc<Vec<Matchup>>) {
-    let result = _inner_worker_thread(matchups);
+fn worker_thread(worker_id: usize, ctx: WorkerCtx) {
+    let result = _inner_worker_thread(worker_id, ctx);
     match result {
-        Ok(_) => eprintln!("Worker thread completed {} games. Exiting", GAMES_PER_FILE),
-        Err(e) => eprintln!("Worker thread encountered an error: {:?}", e),
+        Ok(_) => log::debug!("Worker thread {worker_id} finished its data file. Exiting"),
+        Err(e) => log::error!("Worker thread {worker_id} encountered an error: {:?}", e),
     }
 }
 
-fn _inner_worker_thread(matchups: Arc<Vec<Matchup>>) -> Result<(), Box<dyn std::error::Error>> {
+fn _inner_worker_thread(
+    worker_id: usize,
+    ctx: WorkerCtx,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let WorkerCtx {
+        sampler,
+        is_balanced,
+        progress_sender,
+        stop_flag,
+    } = ctx;
     let mut tt = TranspositionTable::new();
     let mut rng = rng();
+    let mut progress = WorkerProgress::new(worker_id);
 
     let file_path = _get_new_datafile_name(&mut rng);
     let mut data_file = std::fs::File::create(file_path).expect("Failed to create error log file");
@@ -87,7 +113,7 @@ fn _inner_worker_thread(matchups: Arc<Vec<Matchup>>) -> Result<(), Box<dyn std::
     for _ in 0..GAMES_PER_FILE {
         let mut matchup;
         loop {
-            matchup = matchups.choose(&mut rng).unwrap().clone();
+            matchup = sampler.lock().unwrap().sample(&mut rng);
             if matchup.gods.contains(&GodName::Mortal) && matchup.gods[0] != matchup.gods[1] {
                 if rng.random_bool(0.5) {
                     break;
@@ -99,21 +125,40 @@ fn _inner_worker_thread(matchups: Arc<Vec<Matchup>>) -> Result<(), Box<dyn std::
             }
         }
 
+        progress.current_matchup = Some(matchup);
+        let _ = progress_sender.send(progress);
+
         let mut total_examples = 0;
         while total_examples < MIN_EXAMPLES_PER_MATCHUP {
+            if stop_flag.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+
             let now = Instant::now();
-            let game_history = generate_one(matchup, &mut tt, &mut rng)?;
+            let game_history = match generate_one(matchup, &mut tt, &mut rng) {
+                Ok(game_history) => game_history,
+                // A search bug only spoils the game it happened in
+                Err(err) if err.is::<SearchError>() => {
+                    log::error!("Discarding game for {}: {}", matchup, err);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
             if game_history.len() <= MIN_GAME_LENGTH {
-                eprintln!(
+                log::debug!(
                     "Discarding game with only {} examples for {}",
                     game_history.len(),
                     matchup
                 );
                 continue;
             }
-            total_examples += game_history.len();
+            let game_history_len = game_history.len();
+            total_examples += game_history_len;
+            if is_balanced {
+                sampler.lock().unwrap().record_game(matchup);
+            }
 
-            eprintln!(
+            log::debug!(
                 "Done single gen. Created {} examples in {:.4}s for {} (total for matchup: {})",
                 game_history.len(),
                 now.elapsed().as_secs_f32(),
@@ -135,6 +180,8 @@ fn _inner_worker_thread(matchups: Arc<Vec<Matchup>>) -> Result<(), Box<dyn std::
             }
 
             data_file.flush()?;
+            progress.record_game(game_history_len);
+            let _ = progress_sender.send(progress);
             // tt.reset();
         }
     }
@@ -170,19 +217,23 @@ fn playout_subgame(
     let mut game_history: Vec<SingleState> = Vec::new();
 
     let winner = loop {
-        let mut search_context = SearchContext::new(tt, DatagenStaticSearchTerminator::default());
+        let mut search_context =
+            SearchContext::new(tt, DynamicSearchTerminator::new(DATAGEN_SEARCH_LIMITS));
 
-        let search_result = negamax_search::<DatagenStaticSearchTerminator>(
+        let search_result = negamax_search(
             &mut search_context,
             current_state.clone(),
             get_win_reached_search_terminator(),
-        );
+        )?;
 
         let Some(best_child) = search_result.best_move else {
-            eprint!("Search returned no results for state {:?}", current_state);
+            log::error!(
+                "Search returned no results for state:\n{}",
+                current_state.to_console_string(ConsoleRenderOptions::ascii())
+            );
 
             for game_turn in game_history {
-                eprintln!(
+                log::error!(
                     "{:?} {} {} {} {} {}",
                     game_turn.game_state,
                     game_turn.winner as usize + 1,
@@ -288,6 +339,7 @@ fn generate_one(
         let action = placement_actions.choose(rng).unwrap().clone();
 
         active_god.make_placement_move(action, &mut current_state.board, active_player, other_god);
+        current_state.ply += 1;
     }
 
     // eprintln!("Random starting state: {:?}", current_state);
@@ -319,45 +371,102 @@ struct DatagenArgs {
     #[arg(short = 'j', long)]
     pub threads: Option<usize>,
 
+    /// Sample matchups inversely to how many games they've produced in this run
+    #[arg(short = 'b', long)]
+    pub balanced: bool,
+
+    /// Stop once this many examples have been written, across all workers
+    #[arg(long)]
+    pub target_examples: Option<usize>,
+
     #[command(flatten)]
     pub matchups: MatchupArgs,
+
+    #[command(flatten)]
+    pub log: LogArgs,
 }
 
 pub fn main() {
     let args = DatagenArgs::parse();
+    args.log.init();
 
     while std::fs::create_dir_all(&_gamedata_directory()).is_err() {
-        eprintln!("Failed to create data logs directory... Trying again.");
+        log::warn!("Failed to create data logs directory... Trying again.");
         sleep(Duration::from_millis(500));
     }
 
-    let matchups = Arc::new(args.matchups.to_selector().get_all());
+    let sampler = match BalancedSampler::new(&args.matchups.to_selector()) {
+        Ok(sampler) => Arc::new(Mutex::new(sampler)),
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let (progress_sender, progress_receiver) = channel::<WorkerProgress>();
+    let ctx = WorkerCtx {
+        sampler,
+        is_balanced: args.balanced,
+        progress_sender,
+        stop_flag: Arc::new(AtomicBool::new(false)),
+    };
 
     let num_cpus = num_cpus::get();
     let num_worker_threads = args.threads.unwrap_or_else(|| std::cmp::max(1, num_cpus));
-    println!("Found {num_cpus} CPUs. Creating {num_worker_threads} threads",);
-
-    let mut worker_threads = Vec::new();
+    log::info!("Found {num_cpus} CPUs. Creating {num_worker_threads} threads");
 
-    for _ in 0..num_worker_threads {
-        let matchups = Arc::clone(&matchups);
-        let new_thread = thread::spawn(move || worker_thread(matchups));
-        worker_threads.push(new_thread);
-    }
+    let mut worker_threads: Vec<JoinHandle<()>> = (0..num_worker_threads)
+        .map(|worker_id| spawn_worker(worker_id, ctx.clone()))
+        .collect();
+    let mut display = ProgressDisplay::new(num_worker_threads, args.target_examples);
+    let is_terminal = std::io::stderr().is_terminal();
+    let mut last_render = Instant::now();
+
+    while !display.is_target_reached() {
+        match progress_receiver.recv_timeout(PROGRESS_INTERVAL) {
+            Ok(progress) => display.update(progress),
+            Err(RecvTimeoutError::Timeout) => (),
+            // The main thread holds a sender, so this can't happen
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
 
-    loop {
-        for i in 0..num_worker_threads {
-            if worker_threads[i].is_finished() {
-                eprintln!("Worker thread {i} has died. Recreating.");
-                let matchups = Arc::clone(&matchups);
-                let new_thread = thread::spawn(move || worker_thread(matchups));
-                worker_threads.push(new_thread);
-                worker_threads.remove(i);
+        for (worker_id, worker_thread) in worker_threads.iter_mut().enumerate() {
+            if worker_thread.is_finished() {
+                log::debug!("Worker thread {worker_id} has exited. Recreating.");
+                // Take any progress it sent before exiting, so it's counted before its row resets
+                for progress in progress_receiver.try_iter() {
+                    display.update(progress);
+                }
+                display.retire(worker_id);
+                let finished =
+                    std::mem::replace(worker_thread, spawn_worker(worker_id, ctx.clone()));
+                let _ = finished.join();
             }
         }
 
-        thread::sleep(Duration::from_millis(500));
+        if last_render.elapsed() >= PROGRESS_INTERVAL {
+            render_progress(&display, is_terminal);
+            last_render = Instant::now();
+        }
+    }
+
+    log::info!("Reached the target number of examples. Waiting for workers to finish their games");
+    ctx.stop_flag.store(true, Ordering::Relaxed);
+    drop(ctx);
+    for worker_thread in worker_threads {
+        let _ = worker_thread.join();
+    }
+    for progress in progress_receiver.try_iter() {
+        display.update(progress);
+    }
+    render_progress(&display, is_terminal);
+}
+
+/// Redraws the status table in place when writing to a terminal, and appends it otherwise
+fn render_progress(display: &ProgressDisplay, is_terminal: bool) {
+    if is_terminal {
+        eprint!("\x1b[2J\x1b[H");
     }
+    eprint!("{}", display.render());
 }
 
 // cargo run -p datagen -r
@@ -365,3 +474,5 @@ pub fn main() {
 // cargo run -p datagen -r -- --p1 chronus
 // cargo run -p datagen -r -- --gods medusa iris castor -j 4
 // cargo run -p datagen -r -- --p1 wip
+// cargo run -p datagen -r -- --balanced --weight triton:4 --exclude-pair mortal:mortal
+// cargo run -p datagen -r -- --target-examples 1000000
@@ -0,0 +1,150 @@
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use santorini_core::matchup::Matchup;
+
+/// Sent by a worker after every game it writes. Counts are for the worker's current thread, so
+/// they start over if the worker is restarted
+#[derive(Clone, Copy, Debug)]
+pub struct WorkerProgress {
+    pub worker_id: usize,
+    pub games_done: usize,
+    pub examples_written: usize,
+    pub avg_game_len: f32,
+    pub current_matchup: Option<Matchup>,
+}
+
+impl WorkerProgress {
+    pub fn new(worker_id: usize) -> Self {
+        WorkerProgress {
+            worker_id,
+            games_done: 0,
+            examples_written: 0,
+            avg_game_len: 0.0,
+            current_matchup: None,
+        }
+    }
+
+    pub fn record_game(&mut self, game_len: usize) {
+        self.games_done += 1;
+        self.examples_written += game_len;
+        self.avg_game_len = self.examples_written as f32 / self.games_done as f32;
+    }
+}
+
+/// Progress of every worker, as shown in the status table
+pub struct ProgressDisplay {
+    workers: Vec<WorkerProgress>,
+    /// Totals from worker threads that have since exited
+    retired_games: usize,
+    retired_examples: usize,
+    started_at: Instant,
+    target_examples: Option<usize>,
+}
+
+impl ProgressDisplay {
+    pub fn new(num_workers: usize, target_examples: Option<usize>) -> Self {
+        ProgressDisplay {
+            workers: (0..num_workers).map(WorkerProgress::new).collect(),
+            retired_games: 0,
+            retired_examples: 0,
+            started_at: Instant::now(),
+            target_examples,
+        }
+    }
+
+    pub fn update(&mut self, progress: WorkerProgress) {
+        self.workers[progress.worker_id] = progress;
+    }
+
+    /// Keeps the totals of a worker whose thread exited, and starts its row over
+    pub fn retire(&mut self, worker_id: usize) {
+        let worker = &mut self.workers[worker_id];
+        self.retired_games += worker.games_done;
+        self.retired_examples += worker.examples_written;
+        *worker = WorkerProgress::new(worker_id);
+    }
+
+    pub fn total_games(&self) -> usize {
+        self.retired_games + self.workers.iter().map(|w| w.games_done).sum::<usize>()
+    }
+
+    pub fn total_examples(&self) -> usize {
+        self.retired_examples
+            + self
+                .workers
+                .iter()
+                .map(|w| w.examples_written)
+                .sum::<usize>()
+    }
+
+    pub fn is_target_reached(&self) -> bool {
+        self.target_examples
+            .is_some_and(|target| self.total_examples() >= target)
+    }
+
+    fn examples_per_sec(&self) -> f32 {
+        self.total_examples() as f32 / self.started_at.elapsed().as_secs_f32().max(1e-3)
+    }
+
+    fn eta(&self) -> Option<Duration> {
+        let target = self.target_examples?;
+        let rate = self.examples_per_sec();
+        if rate <= 0.0 {
+            return None;
+        }
+        let remaining = target.saturating_sub(self.total_examples());
+        Some(Duration::from_secs_f32(remaining as f32 / rate))
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>6} {:>8} {:>10} {:>8}  matchup",
+            "worker", "games", "examples", "avg len"
+        );
+        for worker in &self.workers {
+            let matchup = worker
+                .current_matchup
+                .map_or_else(|| "-".to_owned(), |m| m.to_string());
+            let _ = writeln!(
+                out,
+                "{:>6} {:>8} {:>10} {:>8.1}  {}",
+                worker.worker_id,
+                worker.games_done,
+                worker.examples_written,
+                worker.avg_game_len,
+                matchup
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{:>6} {:>8} {:>10}",
+            "total",
+            self.total_games(),
+            self.total_examples()
+        );
+
+        let elapsed = self.started_at.elapsed();
+        let _ = write!(
+            out,
+            "{:.1} examples/s, running for {}",
+            self.examples_per_sec(),
+            format_duration(elapsed)
+        );
+        if let Some(target) = self.target_examples {
+            let eta = self
+                .eta()
+                .map_or_else(|| "unknown".to_owned(), format_duration);
+            let _ = write!(out, ", target {} examples, ETA {}", target, eta);
+        }
+        out.push('\n');
+        out
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
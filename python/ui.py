@@ -100,7 +100,8 @@ def parse_game_state(game_state_string):
     game_state_string = ''.join(game_state_string.split())
     parts = game_state_string.split('/')
 
-    if len(parts) != 4:
+    # An optional trailing ply count follows the player sections
+    if len(parts) not in (4, 5):
         print("Game state has wrong number of parts: ", len(parts))
         return None
 
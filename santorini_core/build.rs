@@ -0,0 +1,50 @@
+//! Stamps the engine with the commit and profile it was built from, for `engine_version`
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+fn main() {
+    let git_hash =
+        git(&["rev-parse", "--short=10", "HEAD"]).unwrap_or_else(|| "unknown".to_owned());
+    let profile = std::env::var("PROFILE").unwrap_or_else(|_| "unknown".to_owned());
+    println!("cargo:rustc-env=SANTORINI_GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=SANTORINI_BUILD_PROFILE={}", profile);
+
+    // Only rerun when the commit changes, not on every edit to the tree. Git resolves where each
+    // file lives, since worktrees keep HEAD apart from the refs they share
+    println!("cargo:rerun-if-changed=build.rs");
+    let git_path = |file: &str| git(&["rev-parse", "--git-path", file]).map(PathBuf::from);
+    if let Some(head) = git_path("HEAD") {
+        watch(&head);
+    }
+    if let Some(packed_refs) = git_path("packed-refs") {
+        watch(&packed_refs);
+    }
+    if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]).and_then(|r| git_path(&r)) {
+        // A packed ref has no file of its own until the next commit writes one
+        if !watch(&head_ref)
+            && let Some(ref_dir) = head_ref.parent()
+        {
+            watch(ref_dir);
+        }
+    }
+}
+
+/// Watches `path` if it exists, since Cargo reruns build scripts every time when a watched path
+/// doesn't. Returns whether it was watched
+fn watch(path: &Path) -> bool {
+    let exists = path.exists();
+    if exists {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    exists
+}
@@ -0,0 +1,270 @@
+use clap::Parser;
+use rand::{SeedableRng, rngs::StdRng};
+use serde::Serialize;
+use std::{
+    hint::black_box,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use santorini_core::{
+    board::FullGameState,
+    fen::game_state_to_fen,
+    gods::{GodName, generic::GenericMove},
+    logging::LogArgs,
+    matchup::Matchup,
+    random_utils::{get_random_move, get_random_starting_state},
+    search::{SearchContext, get_win_reached_search_terminator, negamax_search},
+    search_terminators::{
+        DynamicMaxDepthSearchTerminator, DynamicNodesVisitedSearchTerminator, SearchTerminator,
+    },
+    transposition_table::{SearchScoreType, TranspositionTable},
+};
+
+/// Midgame positions spanning most gods, searched for the nodes per second benchmark
+const BENCH_POSITIONS: [&str; 20] = [
+    "0010001302101000000000001/1/apollo:B4,C2/hermes:A5,D4",
+    "0100000000004000022001000/1/ares:D4,C1/eros:D3,C2",
+    "1000022010100100001010200/1/artemis:C4,C2/hephaestus:A4,B3/12",
+    "0004011044000100110000200/1/atlas:C3,B2/athena[^]:C4,C2/12",
+    "0100011000011300000100001/1/bia:A4,E2/clio[0|B4,B3,C3]:C4,D2",
+    "0120000040141400000000100/1/charonv2:B4,B2/polyphemus[x]:C4,A1/12",
+    "0001200001020002110000000/1/chronus:E5,B2/theseus:B3,C2",
+    "0000100110000001102020100/1/graeae:C4,D3,C2/hera:D4,B2",
+    "3100010100001100010000001/1/harpies:B4,C3/aphrodite:A4,E2",
+    "0002000002000100042000210/1/hestia:C5,C1/europa[D2]:D3,B2/12",
+    "0000000000300003000001120/1/jason[x]:B3,D2,B1/achilles:B2,C1",
+    "0220000110000000020100010/1/maenads:B5,C3/zeus:D4,B3",
+    "0022000400120001001001010/1/minotaur:D5,C3/demeter:B4,D3/12",
+    "0000001000001000000001210/1/morpheus[4]:A3,D2/aeolus:C3,C2/12",
+    "0000000001300002010011100/1/mortal:B2,C2/pan:B3,B1",
+    "0000010230000300000001000/1/nike:D5,D2/nemesis:C5,C3",
+    "0103001101011001000000000/1/persephone:C4,D3/hades:C5,C3",
+    "0043122401000000031000000/1/poseidon:D4,C3/bellerophon[x]:B4,E4/12",
+    "1240211000001000010000000/1/prometheus:B5,B3/urania:A5,C4/12",
+    "2214003010000000000000000/1/selene[D4]:D4,B3/hippolyta[C5]:C5,C4/12",
+];
+
+/// Seed for the random games that move generation is benchmarked on, so that every run sees the
+/// same positions
+const MOVE_GEN_SEED: u64 = 0x5a7041;
+const MOVE_GEN_GAMES_PER_GOD: usize = 10;
+/// How long each microbenchmark runs for, at least
+const MICRO_BENCH_DURATION: Duration = Duration::from_millis(500);
+
+#[derive(Parser, Debug)]
+struct BenchArgs {
+    /// Node budget for each search position
+    #[arg(short = 'n', long, default_value_t = 500_000)]
+    nodes: usize,
+
+    /// Search each position to this depth instead of a node budget, for comparing how many nodes
+    /// a search change takes to reach the same depth
+    #[arg(short = 'd', long)]
+    depth: Option<usize>,
+
+    /// Also write the results as JSON, for tracking trends over time
+    #[arg(short = 'j', long)]
+    json: Option<PathBuf>,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+#[derive(Serialize, Debug)]
+struct SearchResult {
+    fen: String,
+    nodes: usize,
+    seconds: f32,
+    nodes_per_second: f32,
+}
+
+#[derive(Serialize, Debug)]
+struct ThroughputResult {
+    name: String,
+    ops: usize,
+    seconds: f32,
+    ops_per_second: f32,
+}
+
+#[derive(Serialize, Debug)]
+struct BenchReport {
+    search: Vec<SearchResult>,
+    total_nodes_per_second: f32,
+    move_gen: Vec<ThroughputResult>,
+    micro: Vec<ThroughputResult>,
+}
+
+/// Repeats `f` over `inputs` until MICRO_BENCH_DURATION has passed. `f` returns how many
+/// operations it did
+fn throughput<T>(name: &str, inputs: &[T], mut f: impl FnMut(&T) -> usize) -> ThroughputResult {
+    let start = Instant::now();
+    let mut ops = 0;
+    while start.elapsed() < MICRO_BENCH_DURATION {
+        for input in inputs {
+            ops += f(input);
+        }
+    }
+    let seconds = start.elapsed().as_secs_f32();
+    ThroughputResult {
+        name: name.to_owned(),
+        ops,
+        seconds,
+        ops_per_second: ops as f32 / seconds,
+    }
+}
+
+/// Searches every bench position, stopping each search with a terminator from `new_terminator`
+fn bench_search<T: SearchTerminator>(new_terminator: impl Fn() -> T) -> Vec<SearchResult> {
+    let mut tt = TranspositionTable::new();
+    BENCH_POSITIONS
+        .iter()
+        .map(|fen| {
+            let state = FullGameState::try_from(*fen).unwrap();
+            tt.reset();
+            let mut search_context = SearchContext::new(&mut tt, new_terminator());
+
+            let start = Instant::now();
+            let search_state = negamax_search(
+                &mut search_context,
+                state,
+                get_win_reached_search_terminator(),
+            )
+            .unwrap();
+            let seconds = start.elapsed().as_secs_f32();
+
+            SearchResult {
+                fen: fen.to_string(),
+                nodes: search_state.nodes_visited,
+                seconds,
+                nodes_per_second: search_state.nodes_visited as f32 / seconds,
+            }
+        })
+        .collect()
+}
+
+/// Every position of a few random games of `god` against Mortal
+fn random_game_positions(god: GodName, rng: &mut StdRng) -> Vec<FullGameState> {
+    let mut positions = Vec::new();
+    for _ in 0..MOVE_GEN_GAMES_PER_GOD {
+        let mut state = get_random_starting_state(&Matchup::new(god, GodName::Mortal), rng);
+        while let Some(next_state) = get_random_move(&state, rng) {
+            positions.push(state);
+            state = next_state;
+        }
+    }
+    positions
+}
+
+fn bench_move_gen() -> Vec<ThroughputResult> {
+    let mut rng = StdRng::seed_from_u64(MOVE_GEN_SEED);
+    GodName::all_non_wip()
+        .map(|god| {
+            let positions = random_game_positions(god, &mut rng);
+            throughput(&god.to_string(), &positions, |state| {
+                let player = state.board.current_player;
+                black_box(state.get_active_god().get_moves_for_search(state, player)).len()
+            })
+        })
+        .collect()
+}
+
+fn bench_micro() -> Vec<ThroughputResult> {
+    let states: Vec<FullGameState> = BENCH_POSITIONS
+        .iter()
+        .flat_map(|fen| FullGameState::try_from(*fen).unwrap().get_next_states())
+        .collect();
+    let fens: Vec<String> = states.iter().map(game_state_to_fen).collect();
+
+    let mut tt = TranspositionTable::new();
+    let mut results = vec![
+        throughput("fen_parse", &fens, |fen| {
+            black_box(FullGameState::try_from(fen.as_str()).unwrap());
+            1
+        }),
+        throughput("fen_serialize", &states, |state| {
+            black_box(game_state_to_fen(state));
+            1
+        }),
+        throughput("tt_insert", &states, |state| {
+            tt.insert(
+                state,
+                GenericMove::NULL_MOVE,
+                1,
+                SearchScoreType::Exact,
+                0,
+                0,
+                0,
+            );
+            1
+        }),
+    ];
+    results.push(throughput("tt_probe", &states, |state| {
+        black_box(tt.fetch(state, 0));
+        1
+    }));
+    results
+}
+
+fn print_throughput_table(title: &str, results: &[ThroughputResult]) {
+    println!("{:<24} {:>14} {:>16}", title, "ops", "ops/s");
+    for result in results {
+        println!(
+            "{:<24} {:>14} {:>16.0}",
+            result.name, result.ops, result.ops_per_second
+        );
+    }
+    println!();
+}
+
+fn main() {
+    let args = BenchArgs::parse();
+    args.log.init();
+
+    let search = match args.depth {
+        Some(depth) => bench_search(|| DynamicMaxDepthSearchTerminator::new(depth)),
+        None => bench_search(|| DynamicNodesVisitedSearchTerminator::new(args.nodes)),
+    };
+    println!(
+        "{:<72} {:>10} {:>8} {:>12}",
+        "search", "nodes", "secs", "nps"
+    );
+    for result in &search {
+        println!(
+            "{:<72} {:>10} {:>8.3} {:>12.0}",
+            result.fen, result.nodes, result.seconds, result.nodes_per_second
+        );
+    }
+    let total_nodes: usize = search.iter().map(|r| r.nodes).sum();
+    let total_seconds: f32 = search.iter().map(|r| r.seconds).sum();
+    let total_nodes_per_second = total_nodes as f32 / total_seconds;
+    println!(
+        "{:<72} {:>10} {:>8.3} {:>12.0}",
+        "total", total_nodes, total_seconds, total_nodes_per_second
+    );
+    println!();
+
+    let move_gen = bench_move_gen();
+    print_throughput_table("move gen (moves)", &move_gen);
+
+    let micro = bench_micro();
+    print_throughput_table("micro", &micro);
+
+    if let Some(json_path) = args.json {
+        let report = BenchReport {
+            search,
+            total_nodes_per_second,
+            move_gen,
+            micro,
+        };
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        if let Err(err) = std::fs::write(&json_path, json) {
+            log::error!("Failed to write {}: {}", json_path.display(), err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// cargo run -p santorini_core --release --bin bench
+// cargo run -p santorini_core --release --bin bench -- -n 1000000 -j bench.json
+// cargo run -p santorini_core --release --bin bench -- -d 8
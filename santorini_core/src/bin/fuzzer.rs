@@ -5,14 +5,21 @@ use rand::{Rng, rng, seq::IndexedRandom};
 
 use santorini_core::{
     board::FullGameState,
-    consistency_checker::consistency_check,
+    consistency_checker::{
+        CoveragePoint, CoverageReport, consistency_check, consistency_check_with_coverage,
+    },
     gods::{ALL_GODS_BY_ID, GodName, StaticGod},
+    logging::LogArgs,
     matchup::MatchupSelector,
     player::Player,
     random_utils::{get_random_starting_state, get_random_state_flattening_powers},
 };
 
-fn run_match_return_should_continue(root_state: FullGameState, rng: &mut impl Rng) -> bool {
+fn run_match_return_should_continue(
+    root_state: FullGameState,
+    rng: &mut impl Rng,
+    mut coverage: Option<&mut CoverageReport>,
+) -> bool {
     let mut prev_state = root_state.clone();
     let mut current_state = root_state;
     loop {
@@ -20,13 +27,17 @@ fn run_match_return_should_continue(root_state: FullGameState, rng: &mut impl Rn
             return true;
         }
 
-        if let Err(err) = consistency_check(&current_state) {
-            eprintln!("Consistency check failed: {:?}", current_state);
-            eprintln!("Previous state: {:?}", prev_state);
+        let check_result = match coverage.as_deref_mut() {
+            Some(coverage) => consistency_check_with_coverage(&current_state, coverage),
+            None => consistency_check(&current_state),
+        };
+        if let Err(err) = check_result {
+            log::error!("Consistency check failed: {:?}", current_state);
+            log::error!("Previous state: {:?}", prev_state);
             current_state.print_to_console();
 
             for error_line in err {
-                eprintln!("{error_line}");
+                log::error!("{error_line}");
             }
             return false;
         }
@@ -95,6 +106,17 @@ struct FuzzerArgs {
     /// Stop running after this many seconds.
     #[arg(short = 't', long)]
     timeout_secs: Option<f64>,
+
+    /// Print which consistency checks and god exceptions were hit, once the fuzzer stops.
+    #[arg(short = 'c', long)]
+    coverage: bool,
+
+    /// Exit with an error if any of these coverage points were never hit. Implies --coverage.
+    #[arg(long, num_args=0.., value_delimiter=' ')]
+    require_coverage: Vec<CoveragePoint>,
+
+    #[command(flatten)]
+    log: LogArgs,
 }
 
 fn maybe_kill_random_worker<T: Rng>(state: &mut FullGameState, player: Player, rng: &mut T) {
@@ -113,11 +135,21 @@ fn maybe_kill_random_worker<T: Rng>(state: &mut FullGameState, player: Player, r
 fn main() {
     let mut rng = rng();
     let args = FuzzerArgs::parse();
+    args.log.init();
+
+    if cfg!(feature = "verify_hash") {
+        // Bad hash updates panic with the offending board, rather than being reported as
+        // consistency errors
+        log::info!("Hash verification enabled");
+    }
 
     let deadline = args
         .timeout_secs
         .map(|secs| Instant::now() + std::time::Duration::from_secs_f64(secs));
 
+    let mut coverage =
+        (args.coverage || !args.require_coverage.is_empty()).then(CoverageReport::default);
+
     let mut matchup_selector = MatchupSelector::default().with_can_swap();
     if args.p1_gods.len() > 0 {
         matchup_selector = matchup_selector.with_exact_gods_for_player(Player::One, &args.p1_gods);
@@ -128,7 +160,7 @@ fn main() {
 
     loop {
         if deadline.is_some_and(|d| Instant::now() >= d) {
-            eprintln!("Timeout reached, stopping fuzzer.");
+            log::info!("Timeout reached, stopping fuzzer.");
             break;
         }
 
@@ -146,13 +178,27 @@ fn main() {
             continue;
         }
 
-        let should_continue = run_match_return_should_continue(root_state, &mut rng);
+        let should_continue =
+            run_match_return_should_continue(root_state, &mut rng, coverage.as_mut());
         if args.stop_on_failure && !should_continue {
-            eprintln!("Failure detected, stopping fuzzer.");
+            log::info!("Failure detected, stopping fuzzer.");
             break;
         }
     }
+
+    if let Some(coverage) = coverage {
+        println!("Coverage:\n{coverage}");
+
+        let missing = coverage.missing(&args.require_coverage);
+        if !missing.is_empty() {
+            let missing: Vec<String> = missing.iter().map(|point| point.to_string()).collect();
+            log::error!("Required coverage points never hit: {}", missing.join(", "));
+            std::process::exit(1);
+        }
+    }
 }
 
 // cargo run -p santorini_core --bin fuzzer -r
 // cargo run -p santorini_core --bin fuzzer -r -- -g morpheus
+// cargo run -p santorini_core --bin fuzzer -r --features verify_hash
+// cargo run -p santorini_core --bin fuzzer -r -- -G triton -t 60 --require-coverage skip_blocker_vs_triton
@@ -0,0 +1,88 @@
+use clap::Parser;
+use rand::{SeedableRng, rngs::StdRng};
+use std::{hint::black_box, time::Instant};
+
+use santorini_core::{
+    board::FullGameState,
+    gods::{GodName, WIP_GODS},
+    matchup::MatchupSelector,
+    player::Player,
+    random_utils::{get_random_move, get_random_starting_state},
+};
+
+#[derive(Parser, Debug)]
+struct HarpiesMoveGenPerfCliArgs {
+    /// Random games to collect positions from, per matchup
+    #[arg(short = 'g', long, default_value_t = 20)]
+    games: usize,
+
+    /// Times to generate moves for every collected position
+    #[arg(short = 'r', long, default_value_t = 20)]
+    rounds: usize,
+
+    #[arg(short = 's', long, default_value_t = 0)]
+    seed: u64,
+}
+
+/// Positions from random games of every matchup against Harpies, where the player to move is the
+/// one whose moves slide
+fn collect_positions(games: usize, rng: &mut StdRng) -> Vec<FullGameState> {
+    let mut selector = MatchupSelector::default()
+        .with_exact_gods_for_player(Player::Two, &[GodName::Harpies])
+        .with_can_swap();
+    for god in WIP_GODS {
+        selector = selector.minus_god_for_both(god);
+    }
+
+    let mut positions = Vec::new();
+    for matchup in selector.get_all() {
+        for _ in 0..games {
+            let mut state = get_random_starting_state(&matchup, rng);
+            while state.board.get_winner().is_none() {
+                let player = state.board.current_player;
+                if state.gods[!player as usize].god_name == GodName::Harpies {
+                    positions.push(state.clone());
+                }
+
+                let Some(next_state) = get_random_move(&state, rng) else {
+                    break;
+                };
+                state = next_state;
+            }
+        }
+    }
+    positions
+}
+
+fn time_move_gen(positions: &[FullGameState], rounds: usize) {
+    let now = Instant::now();
+    let mut move_count = 0;
+    for _ in 0..rounds {
+        for state in positions {
+            let player = state.board.current_player;
+            let moves = state.gods[player as usize].get_moves_for_search(state, player);
+            move_count += black_box(moves).len();
+        }
+    }
+    let duration = now.elapsed();
+
+    println!(
+        "Move gen took {:.4}s, {:.0} moves/s",
+        duration.as_secs_f32(),
+        move_count as f32 / duration.as_secs_f32()
+    );
+}
+
+fn main() {
+    let args = HarpiesMoveGenPerfCliArgs::parse();
+    let mut rng = StdRng::seed_from_u64(args.seed);
+
+    let positions = collect_positions(args.games, &mut rng);
+    println!("Collected {} positions", positions.len());
+
+    for _ in 0..5 {
+        time_move_gen(&positions, args.rounds);
+    }
+}
+
+// cargo run -p santorini_core --release --bin harpies_move_gen_perf
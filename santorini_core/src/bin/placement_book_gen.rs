@@ -0,0 +1,113 @@
+use clap::Parser;
+use std::time::Instant;
+
+use santorini_core::{
+    board::FullGameState,
+    gods::WIP_GODS,
+    matchup::MatchupArgs,
+    placement::get_starting_placement_state,
+    placement_book::{BookEntry, PlacementBook},
+    search::{SearchContext, SearchParams, get_past_win_search_terminator, negamax_search},
+    search_terminators::{DynamicSearchTerminator, SearchLimits},
+    transposition_table::TranspositionTable,
+};
+
+#[derive(Parser, Debug)]
+struct PlacementBookGenCliArgs {
+    /// Nodes to search for each placement
+    #[arg(short = 'n', long, default_value_t = 50_000_000)]
+    nodes: usize,
+
+    #[arg(
+        short = 'o',
+        long,
+        default_value = "santorini_core/data/placement_book.yaml"
+    )]
+    output: String,
+
+    /// Extend an existing book, rather than starting from an empty one. Entries for positions
+    /// that are searched again are replaced
+    #[arg(short = 'l', long)]
+    load: Option<String>,
+
+    #[command(flatten)]
+    matchups: MatchupArgs,
+}
+
+/// Searches every placement of the book line for `state`, until the placement phase is over
+fn add_placement_line(
+    book: &mut PlacementBook,
+    tt: &mut TranspositionTable,
+    mut state: FullGameState,
+    nodes: usize,
+) {
+    while get_starting_placement_state(&state.board, state.gods)
+        .unwrap()
+        .is_some()
+    {
+        let mut search_context = SearchContext::new(
+            tt,
+            DynamicSearchTerminator::new(SearchLimits::new().with_max_nodes(nodes)),
+        );
+        search_context.params = SearchParams {
+            use_placement_book: false,
+            ..Default::default()
+        };
+        let search_state = negamax_search(
+            &mut search_context,
+            state.clone(),
+            get_past_win_search_terminator(),
+        )
+        .unwrap();
+        let best_move = search_state.best_move.unwrap();
+
+        println!(
+            "  {}: {} score: {} depth: {}",
+            state, best_move.action_str, best_move.score, best_move.depth
+        );
+        book.insert(BookEntry {
+            state,
+            next_state: best_move.child_state.clone(),
+            score: best_move.score,
+        })
+        .unwrap();
+        state = best_move.child_state;
+    }
+}
+
+fn main() {
+    let args = PlacementBookGenCliArgs::parse();
+
+    let mut book = match &args.load {
+        Some(path) => PlacementBook::load(path).unwrap(),
+        None => PlacementBook::default(),
+    };
+
+    let mut selector = args.matchups.to_selector();
+    for god in WIP_GODS {
+        selector = selector.minus_god_for_both(god);
+    }
+    let matchups = selector.get_all();
+
+    let mut tt = TranspositionTable::new();
+    let now = Instant::now();
+    for (idx, matchup) in matchups.iter().enumerate() {
+        println!(
+            "[{}/{}] {} ({:.2}s)",
+            idx + 1,
+            matchups.len(),
+            matchup,
+            now.elapsed().as_secs_f32()
+        );
+        tt.reset();
+        add_placement_line(
+            &mut book,
+            &mut tt,
+            FullGameState::new_for_matchup(matchup),
+            args.nodes,
+        );
+    }
+
+    book.save(&args.output).unwrap();
+    println!("Saved {} positions to {}", book.len(), args.output);
+}
@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use santorini_core::{
+    board::FullGameState,
+    fen::parse_fen,
+    search::{Heuristic, SearchContext, get_past_win_search_terminator, negamax_search},
+    search_observer::{
+        SearchTraceRecord, SearchTraceWriter, TracedNode, build_traced_trees, find_traced_move,
+        read_search_trace,
+    },
+    search_terminators::DynamicMaxDepthSearchTerminator,
+    transposition_table::TranspositionTable,
+};
+
+const DEFAULT_TRACE_PATH: &str = "tmp/search_trace.jsonl.gz";
+
+/// Records which nodes a search visits, and shows the tree it explored under a root move
+#[derive(Debug, Parser)]
+struct Args {
+    #[command(subcommand)]
+    command: TraceCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum TraceCommand {
+    /// Search a position to a fixed depth, tracing every node up to a ply
+    Record {
+        fen: String,
+        #[arg(short = 'd', long, default_value_t = 6)]
+        depth: usize,
+        /// Nodes deeper than this aren't traced. Every ply multiplies the size of the trace
+        #[arg(short = 'p', long, default_value_t = 3)]
+        max_ply: usize,
+        #[arg(short = 'o', long, default_value = DEFAULT_TRACE_PATH)]
+        out: PathBuf,
+    },
+    /// Show the root moves of a trace, or the tree searched under one of them
+    Show {
+        #[arg(default_value = DEFAULT_TRACE_PATH)]
+        path: PathBuf,
+        /// A root move, as the engine writes it. Shows every search of that move
+        #[arg(short = 'm', long)]
+        root_move: Option<String>,
+        /// The iteration to show. Defaults to the deepest one
+        #[arg(short = 'd', long)]
+        depth: Option<i32>,
+    },
+}
+
+fn record(
+    fen: &str,
+    depth: usize,
+    max_ply: usize,
+    out: &PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let state = parse_fen(fen)?;
+    if let Some(parent) = out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut tt = TranspositionTable::new();
+    let mut search_context =
+        SearchContext::new(&mut tt, DynamicMaxDepthSearchTerminator::new(depth))
+            .with_observer(SearchTraceWriter::create(out, &state, max_ply)?);
+    // The placement book would answer without searching
+    search_context.params.use_placement_book = false;
+    let search_state =
+        negamax_search(&mut search_context, state, get_past_win_search_terminator())?;
+    search_context.observer.finish()?;
+
+    if let Some(best_move) = search_state.best_move {
+        println!(
+            "Best move: {} score: {} depth: {}",
+            best_move.action_str, best_move.score, best_move.depth
+        );
+    }
+    println!("Wrote trace to {}", out.display());
+    Ok(())
+}
+
+fn describe_node(label: &str, node: &TracedNode) -> String {
+    let score = node
+        .score
+        .map_or_else(|| "unfinished".to_owned(), |score| score.to_string());
+    let cutoff = node
+        .cutoff_score
+        .map_or_else(String::new, |score| format!(" cutoff at {}", score));
+    format!(
+        "{} [{}, {}] depth {} -> {}{}",
+        label, node.alpha, node.beta, node.depth, score, cutoff
+    )
+}
+
+/// Prints `node` and everything searched under it. Moves are found by matching child hashes, so
+/// children that aren't moves, like null move searches, are shown as `?`
+fn print_subtree(label: &str, node: &TracedNode, state: Option<&FullGameState>, indent: usize) {
+    println!("{}{}", "  ".repeat(indent), describe_node(label, node));
+    for child in &node.children {
+        match state.and_then(|state| find_traced_move(state, child.hash)) {
+            Some((action_str, child_state)) => {
+                print_subtree(&action_str, child, Some(&child_state), indent + 1)
+            }
+            None => print_subtree("?", child, None, indent + 1),
+        }
+    }
+}
+
+fn show(
+    path: &PathBuf,
+    root_move: Option<&str>,
+    depth: Option<i32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let records = read_search_trace(path)?;
+    let Some(SearchTraceRecord::Header { fen, max_ply, .. }) = records.first() else {
+        return Err("Trace doesn't start with a header".into());
+    };
+    let root_state = parse_fen(fen)?;
+    println!("{} (traced to ply {})", fen, max_ply);
+
+    let trees = build_traced_trees(&records)?;
+    let Some(depth) = depth.or_else(|| trees.iter().map(|tree| tree.depth).max()) else {
+        return Err("Trace has no searches".into());
+    };
+    let Some(tree) = trees.iter().find(|tree| tree.depth == depth) else {
+        return Err(format!("Trace has no iteration at depth {}", depth).into());
+    };
+
+    let Some(root_move) = root_move else {
+        println!("Root moves at depth {}:", depth);
+        let root_moves: Vec<(u64, Heuristic)> = records
+            .iter()
+            .filter_map(|record| match record {
+                SearchTraceRecord::RootMove {
+                    hash,
+                    score,
+                    depth: move_depth,
+                } if *move_depth as i32 == depth => Some((*hash, *score)),
+                _ => None,
+            })
+            .collect();
+        for (hash, score) in root_moves {
+            let action_str = find_traced_move(&root_state, hash)
+                .map_or_else(|| "?".to_owned(), |(action_str, _)| action_str);
+            let searches = tree
+                .children
+                .iter()
+                .filter(|child| child.hash == hash)
+                .count();
+            println!("  {:<24} {:>6} ({} searches)", action_str, score, searches);
+        }
+        return Ok(());
+    };
+
+    let mut is_found = false;
+    for child in &tree.children {
+        if let Some((action_str, child_state)) = find_traced_move(&root_state, child.hash)
+            && action_str == root_move
+        {
+            is_found = true;
+            print_subtree(&action_str, child, Some(&child_state), 0);
+        }
+    }
+    if !is_found {
+        return Err(format!("{} wasn't searched at depth {}", root_move, depth).into());
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    match &args.command {
+        TraceCommand::Record {
+            fen,
+            depth,
+            max_ply,
+            out,
+        } => record(fen, *depth, *max_ply, out),
+        TraceCommand::Show {
+            path,
+            root_move,
+            depth,
+        } => show(path, root_move.as_deref(), *depth),
+    }
+}
+
+// cargo run -p santorini_core --bin search_trace -r -- record "0000000000000000000000000/1/mortal:B2,D4/mortal:B4,D2" -d 6 -p 2
+// cargo run -p santorini_core --bin search_trace -r -- show -m B2C3^B3
@@ -0,0 +1,32 @@
+use clap::Parser;
+use std::time::Instant;
+
+use santorini_core::tablebase::Tablebase;
+
+#[derive(Parser, Debug)]
+struct TablebaseGenCliArgs {
+    /// Solve Mortal vs Mortal positions with up to this many blocks on the board
+    #[arg(short = 'k', long, default_value_t = 2)]
+    max_builds: usize,
+
+    #[arg(short = 'o', long, default_value = "mortal.stb")]
+    output: String,
+}
+
+fn main() {
+    let args = TablebaseGenCliArgs::parse();
+
+    let now = Instant::now();
+    let tablebase = Tablebase::generate(args.max_builds, |builds, solved| {
+        println!(
+            "Solved {} positions with {} blocks ({:.2}s)",
+            solved,
+            builds,
+            now.elapsed().as_secs_f32()
+        );
+    });
+    tablebase.save(&args.output).unwrap();
+    println!("Saved {} positions to {}", tablebase.len(), args.output);
+}
+
+// cargo run -p santorini_core --release --bin tablebase_gen -- -k 2
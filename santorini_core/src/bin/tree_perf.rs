@@ -23,6 +23,7 @@ fn test(tt: &mut TranspositionTable, scenario: usize) -> SearchState {
         game_state,
         get_win_reached_search_terminator(),
     )
+    .unwrap()
 }
 
 #[derive(Parser, Debug)]
@@ -48,7 +48,8 @@ fn run_scenario(
         &mut search_state,
         game_state.clone(),
         get_win_reached_search_terminator(),
-    );
+    )
+    .unwrap();
     let duration = start_at.elapsed();
 
     (result, duration)
@@ -1,4 +1,5 @@
 use const_for::const_for;
+use serde::{Deserialize, Serialize};
 use std::{fmt, ops::Mul};
 
 use crate::{
@@ -142,33 +143,6 @@ pub const PUSH_MAPPING: [[Option<Square>; NUM_SQUARES]; NUM_SQUARES] = {
     result
 };
 
-// Push mapping for iris
-pub const JUMP_OVER_PUSH_MAPPING: [[Option<Square>; NUM_SQUARES]; NUM_SQUARES] = {
-    let mut result = [[None; NUM_SQUARES]; NUM_SQUARES];
-    const_for!(from in 0..25 => {
-        let from_square: Square = transmute_enum!(from as u8);
-        let from_coord = from_square.to_icoord();
-        const_for!(direction_idx in 0..8 => {
-            let direction = Direction::from_u8(direction_idx as u8);
-            let delta = direction.to_icoord();
-
-            let to1 = from_coord.add(delta);
-            let to2 = to1.add(delta);
-            let to3 = to2.add(delta);
-
-            if let Some(to1) = to1.to_square() && let Some(to2) = to2.to_square() {
-                result[from as usize][to1 as usize] = Some(to2);
-
-                if let Some(to3) = to3.to_square() {
-                    result[from as usize][to2 as usize] = Some(to3);
-                }
-            }
-        });
-    });
-
-    result
-};
-
 pub const BETWEEN_MAPPING: [[Option<Square>; NUM_SQUARES]; NUM_SQUARES] = {
     let mut result = [[None; NUM_SQUARES]; NUM_SQUARES];
     const_for!(from in 0..25 => {
@@ -225,6 +199,85 @@ pub const WRAPPING_DIRECTION_MAPPING: [[Square; NUM_SQUARES]; 8] = {
     result
 };
 
+/// STEP_DIRECTION_MAPPING[from][to] is the direction of the step from `from` to the neighboring
+/// `to`
+pub const STEP_DIRECTION_MAPPING: [[Option<Direction>; NUM_SQUARES]; NUM_SQUARES] = {
+    let mut result = [[None; NUM_SQUARES]; NUM_SQUARES];
+    const_for!(from in 0..25 => {
+        let from_square: Square = transmute_enum!(from as u8);
+        const_for!(to in 0..25 => {
+            result[from as usize][to as usize] = from_square.direction_to(transmute_enum!(to as u8));
+        });
+    });
+    result
+};
+
+/// The squares met by stepping repeatedly in one direction from a square, nearest first. Used for
+/// slides (Harpies) so that they don't have to walk PUSH_MAPPING one square at a time
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SlideRay {
+    pub squares: [Square; BOARD_WIDTH - 1],
+    pub len: usize,
+    pub mask: BitBoard,
+    /// For each square on the ray, the number of ray squares before it
+    steps_before: [u8; NUM_SQUARES],
+    /// Whether square indexes increase along the ray, so that the nearest blocker is the lsb
+    is_ascending: bool,
+}
+
+impl SlideRay {
+    const EMPTY: SlideRay = SlideRay {
+        squares: [Square::A5; BOARD_WIDTH - 1],
+        len: 0,
+        mask: BitBoard::EMPTY,
+        steps_before: [0; NUM_SQUARES],
+        is_ascending: true,
+    };
+
+    /// The nearest square of `blockers` on the ray
+    pub fn first_blocker(&self, blockers: BitBoard) -> Option<Square> {
+        let hits = self.mask & blockers;
+        if hits.is_empty() {
+            None
+        } else if self.is_ascending {
+            Some(hits.lsb())
+        } else {
+            Some(Square::from(31 - hits.0.leading_zeros() as u8))
+        }
+    }
+
+    /// The ray squares that can be reached before running into `blockers`
+    pub fn unblocked_squares(&self, blockers: BitBoard) -> &[Square] {
+        match self.first_blocker(blockers) {
+            Some(blocker) => &self.squares[..self.steps_before[blocker as usize] as usize],
+            None => &self.squares[..self.len],
+        }
+    }
+}
+
+/// SLIDE_RAYS[from][direction] is the ray leaving `from` in `direction`, not including `from`
+pub const SLIDE_RAYS: [[SlideRay; 8]; NUM_SQUARES] = {
+    let mut result = [[SlideRay::EMPTY; 8]; NUM_SQUARES];
+
+    const_for!(from in 0..25 => {
+        const_for!(direction_idx in 0..8 => {
+            let mut ray = SlideRay::EMPTY;
+            let mut current: Square = transmute_enum!(from as u8);
+            while let Some(next) = DIRECTION_MAPPING[direction_idx][current as usize] {
+                ray.squares[ray.len] = next;
+                ray.steps_before[next as usize] = ray.len as u8;
+                ray.mask = ray.mask.bit_or(BitBoard::as_mask(next));
+                ray.is_ascending = next as usize > from as usize;
+                ray.len += 1;
+                current = next;
+            }
+            result[from as usize][direction_idx] = ray;
+        });
+    });
+
+    result
+};
+
 pub const WIND_AWARE_NEIGHBOR_MAP: [BitboardMapping; 9] = {
     let mut result = [[BitBoard::EMPTY; NUM_SQUARES]; 9];
 
@@ -422,6 +475,28 @@ impl fmt::Debug for BitBoard {
     }
 }
 
+/// Serialized as the list of squares it contains, for clients that don't know the bit layout
+impl Serialize for BitBoard {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.all_squares().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BitBoard {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let squares = Vec::<Square>::deserialize(deserializer)?;
+        Ok(squares
+            .into_iter()
+            .fold(BitBoard::EMPTY, |acc, square| acc | BitBoard::as_mask(square)))
+    }
+}
+
 impl BitBoard {
     pub const EMPTY: Self = Self(0);
     pub const MAIN_SECTION_MASK: Self = Self((1 << 25) - 1);
@@ -647,6 +722,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_slide_rays_follow_push_mapping() {
+        for from in 0..25u8 {
+            let from = Square::from(from);
+            for dir_idx in 0..8u8 {
+                let ray = &SLIDE_RAYS[from as usize][dir_idx as usize];
+
+                let mut expected = Vec::new();
+                let mut previous = from;
+                let mut current = DIRECTION_MAPPING[dir_idx as usize][from as usize];
+                while let Some(square) = current {
+                    expected.push(square);
+                    current = PUSH_MAPPING[previous as usize][square as usize];
+                    previous = square;
+                }
+
+                assert_eq!(&ray.squares[..ray.len], expected.as_slice());
+                assert_eq!(
+                    ray.mask,
+                    expected
+                        .iter()
+                        .fold(BitBoard::EMPTY, |acc, square| acc | square.to_board())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_slide_ray_unblocked_squares() {
+        let north = Direction::N as usize;
+        let ray = &SLIDE_RAYS[Square::C1 as usize][north];
+        assert_eq!(
+            ray.unblocked_squares(BitBoard::EMPTY),
+            &[Square::C2, Square::C3, Square::C4, Square::C5]
+        );
+        let blockers = Square::C4.to_board() | Square::C5.to_board() | Square::A1.to_board();
+        assert_eq!(ray.first_blocker(blockers), Some(Square::C4));
+        assert_eq!(ray.unblocked_squares(blockers), &[Square::C2, Square::C3]);
+
+        let south = Direction::S as usize;
+        let ray = &SLIDE_RAYS[Square::C5 as usize][south];
+        assert_eq!(ray.first_blocker(Square::A1.to_board()), None);
+        let blockers = Square::C2.to_board() | Square::C3.to_board();
+        assert_eq!(ray.first_blocker(blockers), Some(Square::C3));
+        assert_eq!(ray.unblocked_squares(blockers), &[Square::C4]);
+        assert!(ray.unblocked_squares(Square::C4.to_board()).is_empty());
+
+        assert_eq!(
+            SLIDE_RAYS[Square::A1 as usize][Direction::W as usize].len,
+            0
+        );
+    }
+
     #[test]
     fn test_shift_multi_square_bitboard() {
         // A multi-square bitboard shifted should equal the OR of individually shifted squares
@@ -724,4 +852,13 @@ mod tests {
             // eprintln!("flipped: {flipped}");
         }
     }
+
+    #[test]
+    fn test_serde_bitboard() {
+        let board = BitBoard::as_mask(Square::A1) | BitBoard::as_mask(Square::C3);
+        let board_str = serde_json::to_string(&board).unwrap();
+        assert_eq!(board_str, r#"["C3","A1"]"#);
+        assert_eq!(serde_json::from_str::<BitBoard>(&board_str).unwrap(), board);
+        assert_eq!(serde_json::to_string(&BitBoard::EMPTY).unwrap(), "[]");
+    }
 }
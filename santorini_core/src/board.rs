@@ -1,17 +1,21 @@
 use core::panic;
 
 use colored::Colorize;
-use strum::IntoEnumIterator;
+use rand::{Rng, seq::IndexedRandom};
+use strum::{EnumIter, IntoEnumIterator};
 
 use crate::{
     bitboard::BitBoard,
     fen::{game_state_to_fen, parse_fen},
-    gods::{BoardStateWithAction, GameStateWithAction, GodName, StaticGod, generic::GenericMove},
+    gods::{
+        BoardStateWithAction, FullAction, GameStateWithAction, GodDataItem, GodName, PartialAction,
+        StaticGod, generic::GenericMove,
+    },
     hashing::{
         HashType, ZOBRIST_DATA_RANDOMS, ZOBRIST_HEIGHT_RANDOMS, ZOBRIST_PLAYER_TWO,
         ZOBRIST_WORKER_RANDOMS, compute_hash_from_scratch_for_board,
     },
-    matchup::{self, BANNED_MATCHUPS, Matchup},
+    matchup::{self, Matchup},
     placement::{PlacementType, get_starting_placement_state},
     player::Player,
     square::Square,
@@ -19,15 +23,32 @@ use crate::{
 
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "verify_hash")]
+use crate::hashing::{HashDeltaCheck, verify_full_hash};
+
 pub type GodData = u32;
 pub type GodPair = [StaticGod; 2];
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct FullGameState {
     pub board: BoardState,
     pub gods: GodPair,
+    /// Turns played since the start of the game, counting each placement turn. FENs that don't
+    /// record it fall back to `BoardState::inferred_ply`.
+    /// Not part of the hash or of equality: the same position reached on different turns shares
+    /// TT entries, and search scores stay relative to the searched position. Use
+    /// `search::forced_result_game_ply` to place a forced result on the game's timeline
+    pub ply: usize,
+}
+
+impl PartialEq for FullGameState {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board && self.gods == other.gods
+    }
 }
 
+impl Eq for FullGameState {}
+
 impl Serialize for FullGameState {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -76,14 +97,196 @@ impl TryFrom<&String> for FullGameState {
     }
 }
 
+/// A legal move from a position, with everything tooling needs to show it
+#[derive(Clone, Debug)]
+pub struct MoveDescription {
+    pub action: GenericMove,
+    /// The move in engine notation, as accepted by `refute`
+    pub action_str: String,
+    /// Every series of interactive actions that plays this move
+    pub action_paths: Vec<FullAction>,
+    pub is_win: bool,
+    /// Whether the mover could win next turn if the opponent passed
+    pub is_check: bool,
+    pub next_state: FullGameState,
+}
+
+/// A finished game, from `FullGameState::get_terminal_result`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminalResult {
+    /// A player reached a win condition
+    Won(Player),
+    /// This player is to move, but has no legal moves
+    Smothered(Player),
+}
+
+impl TerminalResult {
+    pub fn winner(self) -> Player {
+        match self {
+            TerminalResult::Won(winner) => winner,
+            TerminalResult::Smothered(loser) => !loser,
+        }
+    }
+}
+
+/// How far a position can be used. Representable positions can be shown and edited, and playable
+/// ones can also be searched and played from
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionValidation {
+    pub representable: bool,
+    pub playable: bool,
+    /// Why the position isn't representable or playable. Empty if it's both
+    pub issues: Vec<String>,
+}
+
+impl PositionValidation {
+    /// For positions that couldn't even be parsed
+    pub fn unrepresentable(issue: String) -> Self {
+        Self {
+            representable: false,
+            playable: false,
+            issues: vec![issue],
+        }
+    }
+}
+
+/// Built levels, domes included, at which the board counts as fully developed for `progress`.
+/// Few games get this far
+const PROGRESS_FULL_LEVELS: f32 = 40.0;
+/// Dome count at which the board counts as fully domed for `progress`
+const PROGRESS_FULL_DOMES: f32 = 6.0;
+/// Weights of built levels, domes and the average worker height in `progress`. They sum to 1
+const PROGRESS_LEVEL_WEIGHT: f32 = 0.6;
+const PROGRESS_DOME_WEIGHT: f32 = 0.2;
+const PROGRESS_WORKER_WEIGHT: f32 = 0.2;
+/// Progress at which the opening ends and the midgame begins
+const MIDGAME_PROGRESS: f32 = 0.2;
+/// Progress at which the midgame ends and the endgame begins
+const ENDGAME_PROGRESS: f32 = 0.55;
+
+/// A coarse stage of the game, from `FullGameState::phase`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GamePhase {
+    /// Workers are still being placed
+    Placement,
+    Opening,
+    Midgame,
+    Endgame,
+}
+
+impl std::fmt::Display for GamePhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            GamePhase::Placement => "Placement",
+            GamePhase::Opening => "Opening",
+            GamePhase::Midgame => "Midgame",
+            GamePhase::Endgame => "Endgame",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A symmetry of the board, for editing positions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+pub enum Symmetry {
+    /// Swaps the A and E files
+    MirrorHorizontal,
+    /// Swaps the 5th and 1st ranks
+    MirrorVertical,
+    /// A quarter turn clockwise
+    Rotate90,
+}
+
+/// A worker that the board editor can put on a square
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EditorWorker {
+    pub player: Player,
+    /// The player's female worker, for gods with `WorkerConstraints::has_female_worker`
+    pub is_female: bool,
+}
+
+/// Options for `FullGameState::random_position`
+#[derive(Clone, Copy, Debug)]
+pub struct RandomPositionConfig {
+    pub matchup: Matchup,
+    /// Each position gets a random number of builds, up to this many
+    pub build_budget: usize,
+}
+
+impl RandomPositionConfig {
+    pub fn new(matchup: Matchup) -> Self {
+        RandomPositionConfig {
+            matchup,
+            build_budget: 20,
+        }
+    }
+
+    pub fn with_build_budget(mut self, build_budget: usize) -> Self {
+        self.build_budget = build_budget;
+        self
+    }
+}
+
+/// Options for `to_console_string`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConsoleRenderOptions {
+    /// Colored blocks for terminals, or plain ascii without escape codes, for logs
+    pub unicode: bool,
+    /// Rank numbers and file letters around the board
+    pub show_coordinates: bool,
+    /// The FEN and each god's data, when rendering a full game state
+    pub show_god_data: bool,
+}
+
+impl Default for ConsoleRenderOptions {
+    fn default() -> Self {
+        ConsoleRenderOptions {
+            unicode: true,
+            show_coordinates: true,
+            show_god_data: true,
+        }
+    }
+}
+
+impl ConsoleRenderOptions {
+    /// Everything shown, without escape codes
+    pub fn ascii() -> Self {
+        ConsoleRenderOptions {
+            unicode: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_coordinates(mut self, show_coordinates: bool) -> Self {
+        self.show_coordinates = show_coordinates;
+        self
+    }
+
+    pub fn with_god_data(mut self, show_god_data: bool) -> Self {
+        self.show_god_data = show_god_data;
+        self
+    }
+}
+
+/// Attempts at a random position before `random_position` gives up
+const RANDOM_POSITION_ATTEMPTS: usize = 100;
+
 impl FullGameState {
     pub fn new(board: BoardState, gods: GodPair) -> Self {
-        let mut res = FullGameState { gods, board };
+        let ply = board.inferred_ply();
+        let mut res = FullGameState { gods, board, ply };
         res.recalculate_internals();
         res
     }
 
+    /// An empty board for `matchup`. Placement order follows the matchup policy's placement
+    /// advantage, if it sets one, since `get_starting_placement_state` checks the policy
     pub fn new_for_matchup(matchup: &Matchup) -> Self {
+        debug_assert!(
+            !matchup::is_matchup_banned(matchup),
+            "Creating state for banned matchup: {}",
+            matchup
+        );
         FullGameState::new(BoardState::default(), [matchup.god_1(), matchup.god_2()])
     }
 
@@ -123,12 +326,18 @@ impl FullGameState {
     ) -> FullGameState {
         let mut result = self.clone();
         god.make_move(&mut result.board, other_god, action);
+        result.ply += 1;
+        #[cfg(feature = "verify_hash")]
+        verify_full_hash(&result, "next_state");
         result
     }
 
     pub fn next_state_passing(&self, god: StaticGod) -> FullGameState {
         let mut result = self.clone();
         god.make_passing_move(&mut result.board);
+        result.ply += 1;
+        #[cfg(feature = "verify_hash")]
+        verify_full_hash(&result, "next_state_passing");
         result
     }
 
@@ -137,7 +346,11 @@ impl FullGameState {
         let board_states_with_action_list = active_god.get_all_next_states(&self);
         board_states_with_action_list
             .into_iter()
-            .map(|e| FullGameState::new(e, self.gods))
+            .map(|e| {
+                let mut state = FullGameState::new(e, self.gods);
+                state.ply = self.ply + 1;
+                state
+            })
             .collect()
     }
 
@@ -181,12 +394,31 @@ impl FullGameState {
                 .map(|a| {
                     let mut state_clone = self.clone();
                     active_god.make_move(&mut state_clone.board, other_god, a.action);
+                    state_clone.ply += 1;
                     (state_clone, a.action)
                 })
                 .collect()
         }
     }
 
+    /// Like `get_all_next_states_with_actions`, but keeps generating moves after the first win,
+    /// so that every legal move is included
+    pub fn get_complete_next_states_with_actions(&self) -> Vec<(FullGameState, GenericMove)> {
+        if get_starting_placement_state(&self.board, self.gods)
+            .unwrap()
+            .is_some()
+        {
+            return self.get_all_next_states_with_actions();
+        }
+
+        let (active_god, other_god) = self.get_active_non_active_gods();
+        active_god
+            .get_all_moves(self, self.board.current_player)
+            .into_iter()
+            .map(|a| (self.next_state(active_god, other_god, a.action), a.action))
+            .collect()
+    }
+
     pub fn get_next_states_interactive(&self) -> Vec<GameStateWithAction> {
         let placement_mode = get_starting_placement_state(&self.board, self.gods).unwrap();
 
@@ -216,6 +448,7 @@ impl FullGameState {
                         board_state_w_action,
                         self.gods[0].god_name,
                         self.gods[1].god_name,
+                        new_state.ply,
                     ));
                 }
             }
@@ -227,11 +460,105 @@ impl FullGameState {
 
             board_states_with_action_list
                 .into_iter()
-                .map(|e| GameStateWithAction::new(e, self.gods[0].god_name, self.gods[1].god_name))
+                .map(|e| {
+                    GameStateWithAction::new(
+                        e,
+                        self.gods[0].god_name,
+                        self.gods[1].god_name,
+                        self.ply + 1,
+                    )
+                })
                 .collect()
         }
     }
 
+    /// Every legal move, using the same generators as `get_next_states_interactive`, so that both
+    /// reach the same states. Empty once the game is over
+    pub fn describe_moves(&self) -> Vec<MoveDescription> {
+        self.describe_moves_with::<true>()
+    }
+
+    /// Same as `describe_moves`, but only computes `is_check` when `INCLUDE_CHECKS` is set, since
+    /// that needs a winning move search per move
+    pub(crate) fn describe_moves_with<const INCLUDE_CHECKS: bool>(&self) -> Vec<MoveDescription> {
+        if self.get_winner().is_some() {
+            return Vec::new();
+        }
+
+        let placement_mode = get_starting_placement_state(&self.board, self.gods).unwrap();
+        if let Some(placement_mode) = placement_mode {
+            let player = placement_mode.next_placement;
+            let active_god = self.gods[player as usize];
+            let other_god = self.gods[!player as usize];
+
+            return active_god
+                .get_all_placement_actions(self.gods, &self.board, player)
+                .into_iter()
+                .map(|action| MoveDescription {
+                    action,
+                    action_str: active_god.stringify_placement_move(action),
+                    action_paths: active_god.placement_move_to_actions(
+                        action,
+                        &self.board,
+                        player,
+                        other_god,
+                    ),
+                    is_win: false,
+                    is_check: false,
+                    next_state: active_god.make_placement_move_on_clone(action, self, player),
+                })
+                .collect();
+        }
+
+        let player = self.board.current_player;
+        let (active_god, other_god) = self.get_active_non_active_gods();
+        let all_moves = active_god.get_all_moves(self, player);
+
+        // Lose due to no moves
+        if all_moves.is_empty() {
+            let mut next_state = self.clone();
+            next_state.board.set_winner(!player);
+            return vec![MoveDescription {
+                action: GenericMove::NULL_MOVE,
+                action_str: "no moves".to_owned(),
+                action_paths: vec![vec![PartialAction::NoMoves]],
+                is_win: false,
+                is_check: false,
+                next_state,
+            }];
+        }
+
+        all_moves
+            .into_iter()
+            .map(|scored_move| {
+                let action = scored_move.action;
+                let next_state = self.next_state(active_god, other_god, action);
+                let is_win = next_state.get_winner() == Some(player);
+                let is_check = INCLUDE_CHECKS && !is_win && {
+                    let mut passed_state = next_state.clone();
+                    other_god.make_passing_move(&mut passed_state.board);
+                    !active_god
+                        .get_winning_moves(&passed_state, player)
+                        .is_empty()
+                };
+
+                MoveDescription {
+                    action,
+                    action_str: active_god.stringify_move(action),
+                    action_paths: active_god.get_actions_for_move(
+                        &self.board,
+                        action,
+                        player,
+                        other_god,
+                    ),
+                    is_win,
+                    is_check,
+                    next_state,
+                }
+            })
+            .collect()
+    }
+
     pub fn get_active_non_active_gods(&self) -> (StaticGod, StaticGod) {
         self.get_player_non_player_gods(self.board.current_player)
     }
@@ -248,13 +575,78 @@ impl FullGameState {
         self.get_god_for_player(self.board.current_player)
     }
 
+    /// Whether workers are still being placed. Positions with an invalid placement order count
+    /// as started
+    pub fn is_placement_phase(&self) -> bool {
+        matches!(
+            get_starting_placement_state(&self.board, self.gods),
+            Ok(Some(_))
+        )
+    }
+
+    /// The player to act. During placement that's the next player to place, which isn't
+    /// `current_player` when player 2 places first
+    pub fn get_current_player_consider_placement_mode(&self) -> Player {
+        match get_starting_placement_state(&self.board, self.gods) {
+            Ok(Some(placement_state)) => placement_state.next_placement,
+            _ => self.board.current_player,
+        }
+    }
+
     pub fn get_other_god(&self) -> StaticGod {
         self.get_god_for_player(!self.board.current_player)
     }
 
+    /// Squares the player to move can interact with to stop the opponent's immediate wins: the
+    /// union of the blocker boards of every opponent winning move, as q-search uses. For some gods
+    /// this is wider than needed, but it never leaves a blocking square out. None unless the
+    /// player to move is in check
+    pub fn defensive_key_squares(&self) -> Option<BitBoard> {
+        if self.get_winner().is_some() {
+            return None;
+        }
+        let Ok(None) = get_starting_placement_state(&self.board, self.gods) else {
+            return None;
+        };
+
+        let other_god = self.get_other_god();
+        let key_squares = other_god
+            .get_winning_moves(self, !self.board.current_player)
+            .iter()
+            .fold(BitBoard::EMPTY, |acc, win| {
+                acc | other_god.get_blocker_board(&self.board, win.action)
+            });
+        key_squares.is_not_empty().then_some(key_squares)
+    }
+
+    /// Each player's god specific state, for frontends to render
+    pub fn god_data_items(&self) -> [Vec<GodDataItem>; 2] {
+        [Player::One, Player::Two].map(|player| {
+            self.get_god_for_player(player)
+                .god_data_items(&self.board, player)
+        })
+    }
+
+    pub fn to_console_string(&self, options: ConsoleRenderOptions) -> String {
+        let mut result = String::new();
+        if options.show_god_data {
+            result += &format!("{:?}\n", self);
+            for player in [Player::One, Player::Two] {
+                let god = self.gods[player as usize];
+                if let Some(data) = god.pretty_stringify_god_data(&self.board, player) {
+                    result += &format!("{:?} ({}): {}\n", player, god.god_name, data);
+                }
+            }
+        }
+        result + &self.board.to_console_string(options)
+    }
+
+    /// Prints the board to stderr, which is never part of the engine protocol
     pub fn print_to_console(&self) {
-        eprintln!("{:?}", self);
-        self.board.print_to_console();
+        eprint!(
+            "{}",
+            self.to_console_string(ConsoleRenderOptions::default())
+        );
     }
 
     pub fn base_hash(&self) -> HashType {
@@ -277,6 +669,25 @@ impl FullGameState {
         self.board.playable_err(self.gods)
     }
 
+    /// Both tiers of validation at once. Playability is only checked for representable positions
+    pub fn position_validation(&self) -> PositionValidation {
+        if let Err(err) = self.representation_err() {
+            return PositionValidation::unrepresentable(err);
+        }
+        match self.playable_err() {
+            Ok(()) => PositionValidation {
+                representable: true,
+                playable: true,
+                issues: Vec::new(),
+            },
+            Err(err) => PositionValidation {
+                representable: true,
+                playable: false,
+                issues: vec![err],
+            },
+        }
+    }
+
     pub fn validate(&self) {
         self.validation_err().unwrap();
     }
@@ -285,10 +696,296 @@ impl FullGameState {
         self.board.get_winner()
     }
 
+    /// Whether `player` has no legal moves, and so loses on their turn. Gods that may pass
+    /// generate a passing move, so they're never smothered. Always false during placement or
+    /// once the game is won
+    pub fn is_smothered(&self, player: Player) -> bool {
+        if self.get_winner().is_some()
+            || get_starting_placement_state(&self.board, self.gods)
+                .unwrap()
+                .is_some()
+        {
+            return false;
+        }
+
+        self.gods[player as usize]
+            .get_all_moves(self, player)
+            .is_empty()
+    }
+
+    /// How the game has ended, including a smothered player to move, which `get_winner` doesn't
+    /// report until their losing turn is played out
+    pub fn get_terminal_result(&self) -> Option<TerminalResult> {
+        if let Some(winner) = self.get_winner() {
+            return Some(TerminalResult::Won(winner));
+        }
+
+        let player = self.board.current_player;
+        self.is_smothered(player)
+            .then_some(TerminalResult::Smothered(player))
+    }
+
     pub fn get_all_permutations<const INCLUDE_SELF: bool>(&self) -> Vec<BoardState> {
         self.board
             .get_all_permutations::<INCLUDE_SELF>(self.gods, self.base_hash())
     }
+
+    /// How far the game has developed, from 0 for an empty board to 1 for a crowded one. Blends
+    /// the built levels (up to `PROGRESS_FULL_LEVELS`), the domes (up to `PROGRESS_FULL_DOMES`)
+    /// and the average height of all workers out of 2. The same for every god
+    pub fn progress(&self) -> f32 {
+        let levels: u32 = self.board.height_lookup.iter().map(|&h| h as u32).sum();
+        let domes = (self.board.height_map[3] & BitBoard::MAIN_SECTION_MASK).count_ones();
+
+        let workers = self.board.workers[0] | self.board.workers[1];
+        let worker_count = workers.count_ones();
+        let average_worker_height = match worker_count {
+            0 => 0.0,
+            _ => {
+                let total: usize = workers
+                    .into_iter()
+                    .map(|sq| self.board.get_height(sq).min(3))
+                    .sum();
+                total as f32 / worker_count as f32
+            }
+        };
+
+        let progress = PROGRESS_LEVEL_WEIGHT * (levels as f32 / PROGRESS_FULL_LEVELS).min(1.0)
+            + PROGRESS_DOME_WEIGHT * (domes as f32 / PROGRESS_FULL_DOMES).min(1.0)
+            + PROGRESS_WORKER_WEIGHT * (average_worker_height / 2.0).min(1.0);
+        progress.clamp(0.0, 1.0)
+    }
+
+    /// Placement until every worker is down, then the opening until `progress` reaches
+    /// `MIDGAME_PROGRESS`, and the endgame from `ENDGAME_PROGRESS`
+    pub fn phase(&self) -> GamePhase {
+        let is_placing = get_starting_placement_state(&self.board, self.gods)
+            .is_ok_and(|placement| placement.is_some());
+        if is_placing {
+            return GamePhase::Placement;
+        }
+
+        match self.progress() {
+            p if p < MIDGAME_PROGRESS => GamePhase::Opening,
+            p if p < ENDGAME_PROGRESS => GamePhase::Midgame,
+            _ => GamePhase::Endgame,
+        }
+    }
+
+    /// Hash shared by every rotation and reflection of this position
+    pub fn canonical_hash(&self) -> HashType {
+        self.get_all_permutations::<true>()
+            .iter()
+            .map(|board| board.hash)
+            .min()
+            .unwrap()
+    }
+
+    /// The same position under `symmetry`. God data is flipped the same way as in
+    /// `get_all_permutations`, so orientations like Aeolus's wind turn with the board
+    pub fn transform(&self, symmetry: Symmetry) -> FullGameState {
+        let mut result = self.clone();
+        // The winner shares a bitboard with the heights, and mustn't be moved with them
+        let winner = self.get_winner();
+        if let Some(winner) = winner {
+            result.board.unset_winner(winner);
+        }
+
+        match symmetry {
+            Symmetry::MirrorHorizontal => result.board._flip_horizontal_mut(self.gods),
+            Symmetry::MirrorVertical => result.board._flip_vertical_mut(self.gods),
+            Symmetry::Rotate90 => {
+                result.board._transpose_mut(self.gods);
+                result.board._flip_horizontal_mut(self.gods);
+            }
+        }
+
+        if let Some(winner) = winner {
+            result.board.set_winner(winner);
+        }
+        result.recalculate_internals();
+        result
+    }
+
+    /// Repairs god data that an edit to the board made invalid, like a Europa talus token under a
+    /// dome or a Selene female marker without a worker. Returns whether anything changed
+    pub fn reconcile_god_data(&mut self) -> bool {
+        let mut is_changed = false;
+        for player in [Player::One, Player::Two] {
+            let data = self.gods[player as usize].reconcile_god_data(&self.board, player);
+            if data != self.board.god_data[player as usize] {
+                self.board.set_god_data(player, data);
+                is_changed = true;
+            }
+        }
+        is_changed
+    }
+
+    /// The worker on `square`, as the board editor sees it
+    pub fn editor_worker_at(&self, square: Square) -> Option<EditorWorker> {
+        let player = self.board.get_worker_at(square)?;
+        let is_female = self.gods[player as usize]
+            .worker_constraints()
+            .has_female_worker
+            && (self.board.god_data[player as usize] & BitBoard::as_mask(square).0) != 0;
+        Some(EditorWorker { player, is_female })
+    }
+
+    /// What the board editor can put on `square`, in the order it cycles through them: nothing,
+    /// then each player's worker, and their female worker if their god has one. Workers that would
+    /// take a player past their god's `max_workers`, or that can't be represented on this square,
+    /// are left out. Whatever is on the square already is always included
+    pub fn editor_worker_options(&self, square: Square) -> Vec<Option<EditorWorker>> {
+        let current = self.editor_worker_at(square);
+        let mut options = vec![None];
+        for player in [Player::One, Player::Two] {
+            let constraints = self.gods[player as usize].worker_constraints();
+            let other_workers = self.board.workers[player as usize] & !BitBoard::as_mask(square);
+            let is_full = other_workers.count_ones() as usize >= constraints.max_workers;
+
+            for is_female in [false, true] {
+                if is_female && !constraints.has_female_worker {
+                    continue;
+                }
+                let option = Some(EditorWorker { player, is_female });
+                let is_allowed = !is_full
+                    && self
+                        .with_editor_worker(square, option)
+                        .representation_err()
+                        .is_ok();
+                if option == current || is_allowed {
+                    options.push(option);
+                }
+            }
+        }
+        options
+    }
+
+    /// This position with `worker` on `square` in place of whatever was there. A female worker
+    /// takes the female marker from any other worker of the same player
+    pub fn with_editor_worker(
+        &self,
+        square: Square,
+        worker: Option<EditorWorker>,
+    ) -> FullGameState {
+        let mut result = self.clone();
+        let mask = BitBoard::as_mask(square);
+        for player in [Player::One, Player::Two] {
+            if (result.board.workers[player as usize] & mask).is_not_empty() {
+                result.board.worker_xor(player, mask);
+            }
+            let god_data = result.board.god_data[player as usize];
+            if self.gods[player as usize]
+                .worker_constraints()
+                .has_female_worker
+                && (god_data & mask.0) != 0
+            {
+                result.board.set_god_data(player, god_data & !mask.0);
+            }
+        }
+
+        if let Some(EditorWorker { player, is_female }) = worker {
+            result.board.worker_xor(player, mask);
+            if is_female {
+                result.board.set_god_data(player, mask.0);
+            }
+        }
+        result
+    }
+
+    /// Ways the workers on the board fall short of a finished setup, for the board editor to
+    /// point out. These positions can still be legal, since workers can be lost during a game
+    pub fn editor_worker_issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        for player in [Player::One, Player::Two] {
+            let god = self.gods[player as usize];
+            let constraints = god.worker_constraints();
+            let worker_count = self.board.workers[player as usize].count_ones() as usize;
+            if worker_count == 0 {
+                continue;
+            }
+
+            if worker_count < constraints.starting_workers {
+                issues.push(format!(
+                    "Player {:?} ({:?}) has {} of {} workers",
+                    player, god.god_name, worker_count, constraints.starting_workers
+                ));
+            }
+            if constraints.has_female_worker
+                && worker_count >= constraints.starting_workers
+                && self.board.god_data[player as usize] == 0
+            {
+                issues.push(format!(
+                    "Player {:?} ({:?}) has no female worker",
+                    player, god.god_name
+                ));
+            }
+        }
+        issues
+    }
+
+    /// A random legal position for `config.matchup`: workers placed by the gods' own placement
+    /// rules, then up to `config.build_budget` random builds. Workers never end up above level 2.
+    /// Retries until the position passes `validation_err`
+    pub fn random_position(
+        rng: &mut impl Rng,
+        config: &RandomPositionConfig,
+    ) -> Result<FullGameState, String> {
+        let mut last_err = String::new();
+        for _ in 0..RANDOM_POSITION_ATTEMPTS {
+            let mut state = FullGameState::new(BoardState::default(), config.matchup.get_gods());
+
+            // Placement rules only apply to an empty board, so workers go down first
+            while let Some(placement) = get_starting_placement_state(&state.board, state.gods)? {
+                let player = placement.next_placement;
+                let (active_god, other_god) = state.get_player_non_player_gods(player);
+                let actions =
+                    active_god.get_all_placement_actions(state.gods, &state.board, player);
+                let Some(action) = actions.choose(rng) else {
+                    break;
+                };
+                active_god.make_placement_move(*action, &mut state.board, player, other_god);
+            }
+
+            let workers = state.board.workers[0] | state.board.workers[1];
+            let mut heights = [0; 25];
+            for _ in 0..rng.random_range(0..=config.build_budget) {
+                let buildable: Vec<Square> = Square::iter()
+                    .filter(|square| {
+                        let max_height = if workers.contains_square(*square) {
+                            2
+                        } else {
+                            4
+                        };
+                        heights[*square as usize] < max_height
+                    })
+                    .collect();
+                let Some(square) = buildable.choose(rng) else {
+                    break;
+                };
+                state.board.height_map[heights[*square as usize]] |= BitBoard::as_mask(*square);
+                heights[*square as usize] += 1;
+            }
+
+            state.board.current_player = if rng.random_bool(0.5) {
+                Player::One
+            } else {
+                Player::Two
+            };
+            state.recalculate_internals();
+            state.ply = state.board.inferred_ply();
+
+            match state.validation_err() {
+                Ok(()) => return Ok(state),
+                Err(err) => last_err = err,
+            }
+        }
+
+        Err(format!(
+            "Failed to make a valid position for {} in {} attempts: {}",
+            config.matchup, RANDOM_POSITION_ATTEMPTS, last_err
+        ))
+    }
 }
 
 pub(crate) const WINNER_MASK_OFFSET: usize = 30;
@@ -361,8 +1058,13 @@ impl BoardState {
     }
 
     pub fn flip_current_player(&mut self) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         self.current_player = !self.current_player;
         self.hash ^= ZOBRIST_PLAYER_TWO;
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "flip_current_player");
     }
 
     pub fn get_height(&self, position: Square) -> usize {
@@ -382,12 +1084,19 @@ impl BoardState {
     }
 
     pub fn set_winner(&mut self, player: Player) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         debug_assert_eq!(self.height_map[0].0 as usize >> WINNER_MASK_OFFSET, 0);
         self.height_map[0] ^= PLAYER_TO_WINNER_LOOKUP[player as usize];
         self.hash ^= ZOBRIST_HEIGHT_RANDOMS[0][WINNER_MASK_OFFSET + player as usize];
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "set_winner");
     }
 
     pub fn unset_winner(&mut self, player: Player) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let player_bit = WINNER_MASK_OFFSET + player as usize;
         debug_assert_eq!(
             self.height_map[0].0 as usize & 1 << player_bit,
@@ -396,6 +1105,9 @@ impl BoardState {
 
         self.height_map[0] ^= PLAYER_TO_WINNER_LOOKUP[player as usize];
         self.hash ^= ZOBRIST_HEIGHT_RANDOMS[0][player_bit];
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "unset_winner");
     }
 
     pub fn exactly_level_0(&self) -> BitBoard {
@@ -447,10 +1159,15 @@ impl BoardState {
     }
 
     pub fn worker_xor(&mut self, player: Player, xor: BitBoard) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         self.workers[player as usize] ^= xor;
         for pos in xor {
             self.hash ^= ZOBRIST_WORKER_RANDOMS[player as usize][pos as usize];
         }
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "worker_xor");
     }
 
     pub fn oppo_worker_xor(&mut self, other_god: StaticGod, player: Player, xor: BitBoard) {
@@ -474,14 +1191,21 @@ impl BoardState {
     }
 
     pub fn build_up(&mut self, build_position: Square) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let build_mask = BitBoard::as_mask(build_position);
         let current_height = self.get_height(build_position);
         self.height_map[current_height] ^= build_mask;
         self.hash ^= ZOBRIST_HEIGHT_RANDOMS[current_height][build_position as usize];
         self.height_lookup[build_position as usize] += 1;
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "build_up");
     }
 
     pub fn double_build_up(&mut self, build_position: Square) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let build_mask = BitBoard::as_mask(build_position);
         let current_height = self.get_height(build_position);
         self.height_map[current_height] ^= build_mask;
@@ -490,9 +1214,14 @@ impl BoardState {
 
         self.hash ^= ZOBRIST_HEIGHT_RANDOMS[current_height][build_position as usize];
         self.hash ^= ZOBRIST_HEIGHT_RANDOMS[current_height + 1][build_position as usize];
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "double_build_up");
     }
 
     pub fn dome_up(&mut self, build_position: Square) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let build_mask = BitBoard::as_mask(build_position);
         let current_height = self.get_height(build_position);
         for h in current_height..4 {
@@ -500,17 +1229,27 @@ impl BoardState {
             self.hash ^= ZOBRIST_HEIGHT_RANDOMS[h][build_position as usize];
         }
         self.height_lookup[build_position as usize] = 4;
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "dome_up");
     }
 
     pub fn unbuild(&mut self, build_position: Square) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let build_mask = BitBoard::as_mask(build_position);
         let current_height = self.get_height(build_position) - 1;
         self.height_map[current_height] ^= build_mask;
         self.hash ^= ZOBRIST_HEIGHT_RANDOMS[current_height][build_position as usize];
         self.height_lookup[build_position as usize] -= 1;
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "unbuild");
     }
 
     pub fn double_unbuild(&mut self, build_position: Square) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let build_mask = BitBoard::as_mask(build_position);
         let current_height = self.get_height(build_position) - 1;
         self.height_map[current_height] ^= build_mask;
@@ -520,18 +1259,28 @@ impl BoardState {
         self.hash ^= ZOBRIST_HEIGHT_RANDOMS[current_height - 1][build_position as usize];
 
         self.height_lookup[build_position as usize] -= 2;
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "double_unbuild");
     }
 
     pub fn undome(&mut self, build_position: Square, final_height: usize) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let build_mask = BitBoard::as_mask(build_position);
         for h in final_height..4 {
             self.height_map[h] ^= build_mask;
             self.hash ^= ZOBRIST_HEIGHT_RANDOMS[h][build_position as usize];
         }
         self.height_lookup[build_position as usize] = final_height as u8;
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "undome");
     }
 
     pub fn set_god_data(&mut self, player: Player, data: GodData) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         let old_data = self.god_data[player as usize];
         self.god_data[player as usize] = data;
 
@@ -541,9 +1290,14 @@ impl BoardState {
             delta &= delta - 1;
             self.hash ^= ZOBRIST_DATA_RANDOMS[player as usize][lsb as usize];
         }
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "set_god_data");
     }
 
     pub fn xor_god_data(&mut self, player: Player, mut delta: GodData) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(self);
         self.god_data[player as usize] ^= delta;
 
         while delta > 0 {
@@ -551,6 +1305,9 @@ impl BoardState {
             delta &= delta - 1;
             self.hash ^= ZOBRIST_DATA_RANDOMS[player as usize][lsb as usize];
         }
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(self, "xor_god_data");
     }
 
     pub fn print_for_debugging(&self) {
@@ -602,7 +1359,7 @@ impl BoardState {
         }
 
         let matchup = Matchup::new(gods[0].god_name, gods[1].god_name);
-        if let Some(reason) = BANNED_MATCHUPS.get(&matchup) {
+        if let Some(reason) = matchup::matchup_banned_reason(&matchup) {
             let err_str = match reason {
                 matchup::BannedReason::Game => "This matchup is banned",
                 matchup::BannedReason::Engine => "This matchup is not yet implemented",
@@ -644,20 +1401,12 @@ impl BoardState {
             }
         }
 
-        if [
-            GodName::Hermes,
-            GodName::Eros,
-            GodName::Nemesis,
-            GodName::Poseidon,
-        ]
-        .contains(&own_god.god_name)
-        {
-            if worker_count > 2 {
-                return Err(format!(
-                    "Player {:?} as {:?} can't have more than 2 workers",
-                    player, own_god.god_name
-                ));
-            }
+        let max_workers = own_god.worker_constraints().max_workers;
+        if worker_count as usize > max_workers {
+            return Err(format!(
+                "Player {:?} as {:?} can't have more than {} workers ({})",
+                player, own_god.god_name, max_workers, worker_count
+            ));
         }
 
         if own_god.god_name == GodName::Hypnus
@@ -681,13 +1430,6 @@ impl BoardState {
         }
 
         if own_god.god_name == GodName::Castor {
-            if worker_count > 2 {
-                return Err(format!(
-                    "Player {:?} as Castor can't have more than 2 workers",
-                    player
-                ));
-            }
-
             if [GodName::Persephone, GodName::Harpies, GodName::Hypnus]
                 .contains(&other_god.god_name)
             {
@@ -700,20 +1442,6 @@ impl BoardState {
             }
         }
 
-        if own_god.god_name == GodName::Hydra {
-            if worker_count > 11 {
-                return Err(format!(
-                    "Player {:?} has too many workers as hydra ({})",
-                    player, worker_count
-                ));
-            }
-        } else if worker_count > 4 {
-            return Err(format!(
-                "Player {:?} has too many workers ({})",
-                player, worker_count
-            ));
-        }
-
         if own_god.is_hypnus() && oppo_count == 1 && other_god.god_name != GodName::Hydra {
             return Err("Can't play hypnus against a solo worker".to_owned());
         }
@@ -744,6 +1472,10 @@ impl BoardState {
             }
         }
 
+        if (self.workers[0] & self.workers[1]).is_not_empty() {
+            return Err("Both players have workers on the same square".to_owned());
+        }
+
         for h in 1..4 {
             let height = self.height_map[h] & BitBoard::MAIN_SECTION_MASK;
             let lower = self.height_map[h - 1] & BitBoard::MAIN_SECTION_MASK;
@@ -780,15 +1512,16 @@ impl BoardState {
         }
     }
 
-    pub fn print_to_console(&self) {
-        if let Some(winner) = self.get_winner() {
-            eprintln!("Player {:?} wins!", winner);
-        } else {
-            eprintln!("Player {:?} to play", self.current_player);
-        }
+    pub fn to_console_string(&self, options: ConsoleRenderOptions) -> String {
+        let mut result = match self.get_winner() {
+            Some(winner) => format!("Player {:?} wins!\n", winner),
+            None => format!("Player {:?} to play\n", self.current_player),
+        };
 
         for row in 0_usize..5 {
-            let mut row_str = format!("{}", 5 - row);
+            if options.show_coordinates {
+                result += &format!("{}", 5 - row);
+            }
             for col in 0_usize..5 {
                 let pos = col + row * 5;
                 let mask = 1 << pos;
@@ -802,29 +1535,74 @@ impl BoardState {
                     "A square cannot have both players' workers"
                 );
 
-                let char = if is_1 {
-                    "X"
-                } else if is_2 {
-                    "0"
+                if options.unicode {
+                    let char = if is_1 {
+                        "X"
+                    } else if is_2 {
+                        "0"
+                    } else {
+                        " "
+                    }
+                    .black();
+
+                    let elem = match height {
+                        0 => char.on_white(),
+                        1 => char.on_yellow(),
+                        2 => char.on_blue(),
+                        3 => char.on_green(),
+                        4 => char.on_black(),
+                        _ => panic!("Invalid Height: {}", height),
+                    };
+                    result += &elem.to_string();
                 } else {
-                    " "
+                    let worker = if is_1 {
+                        'X'
+                    } else if is_2 {
+                        'O'
+                    } else {
+                        '.'
+                    };
+                    result += &format!(" {}{}", height, worker);
                 }
-                .black();
-
-                let elem = match height {
-                    0 => char.on_white(),
-                    1 => char.on_yellow(),
-                    2 => char.on_blue(),
-                    3 => char.on_green(),
-                    4 => char.on_black(),
-                    _ => panic!("Invalid Height: {}", height),
-                };
-                row_str = format!("{row_str}{elem}");
             }
-            eprint!("{}", row_str);
-            eprintln!()
+            result += "\n";
+        }
+
+        if options.show_coordinates {
+            if options.unicode {
+                result += " ABCDE\n";
+            } else {
+                result += "  A  B  C  D  E\n";
+            }
         }
-        eprintln!(" ABCDE");
+        result
+    }
+
+    /// Prints the board to stderr, which is never part of the engine protocol
+    pub fn print_to_console(&self) {
+        eprint!(
+            "{}",
+            self.to_console_string(ConsoleRenderOptions::default())
+        );
+    }
+
+    /// Best guess at the turns played to reach this board, for positions that don't record it:
+    /// one per placement turn, plus one per built level. Exact when every turn builds once
+    pub fn inferred_ply(&self) -> usize {
+        let built_levels: u32 = self
+            .height_map
+            .iter()
+            .map(|level| (*level & BitBoard::MAIN_SECTION_MASK).count_ones())
+            .sum();
+        let placement_turns = if built_levels > 0 {
+            2
+        } else {
+            self.workers
+                .iter()
+                .filter(|workers| (**workers & BitBoard::MAIN_SECTION_MASK).is_not_empty())
+                .count()
+        };
+        placement_turns + built_levels as usize
     }
 
     pub fn get_positions_for_player(&self, player: Player) -> Vec<Square> {
@@ -984,7 +1762,256 @@ impl GameStateBuilder {
 
 #[cfg(test)]
 mod tests {
-    use crate::square::Square;
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    use crate::{gods::ALL_GODS_BY_ID, square::Square};
+
+    use super::*;
+
+    fn described_outcomes(state: &FullGameState) -> Vec<String> {
+        let mut outcomes: Vec<String> = state
+            .describe_moves()
+            .into_iter()
+            .flat_map(|description| {
+                description
+                    .action_paths
+                    .into_iter()
+                    .map(move |actions| format!("{} {:?}", description.next_state, actions))
+            })
+            .collect();
+        outcomes.sort();
+        outcomes
+    }
+
+    fn interactive_outcomes(state: &FullGameState) -> Vec<String> {
+        let mut outcomes: Vec<String> = state
+            .get_next_states_interactive()
+            .into_iter()
+            .map(|choice| format!("{} {:?}", choice.state, choice.actions))
+            .collect();
+        outcomes.sort();
+        outcomes
+    }
+
+    #[test]
+    fn test_describe_moves_matches_interactive() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for god in ALL_GODS_BY_ID.iter() {
+            for matchup in [
+                Matchup::new(god.god_name, GodName::Mortal),
+                Matchup::new(GodName::Mortal, god.god_name),
+            ] {
+                // Starts from an empty board, so placements are covered too
+                let mut state = FullGameState::new_for_matchup(&matchup);
+                for _ in 0..12 {
+                    if state.get_winner().is_some() {
+                        break;
+                    }
+                    assert_eq!(
+                        described_outcomes(&state),
+                        interactive_outcomes(&state),
+                        "{:?}",
+                        state
+                    );
+                    let descriptions = state.describe_moves();
+                    state = descriptions.choose(&mut rng).unwrap().next_state.clone();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_describe_moves_flags() {
+        // P1 can win from A5 by moving to B5, and threatens to as long as that worker stays put
+        let state =
+            FullGameState::try_from("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3")
+                .unwrap();
+        let descriptions = state.describe_moves();
+
+        let wins: Vec<&MoveDescription> = descriptions.iter().filter(|d| d.is_win).collect();
+        assert!(!wins.is_empty());
+        for win in wins {
+            assert_eq!(win.next_state.get_winner(), Some(Player::One));
+            assert!(!win.is_check);
+        }
+
+        let checks = descriptions.iter().filter(|d| d.is_check).count();
+        assert!(checks > 0);
+        assert!(checks < descriptions.len());
+        for description in &descriptions {
+            assert_eq!(
+                description.action_str,
+                state.get_active_god().stringify_move(description.action)
+            );
+        }
+    }
+
+    #[test]
+    fn test_describe_moves_when_stuck() {
+        let state =
+            FullGameState::try_from("0444444444444444444444440/1/mortal:E1/mortal:A5").unwrap();
+        let descriptions = state.describe_moves();
+        assert_eq!(descriptions.len(), 1);
+        assert_eq!(
+            descriptions[0].action_paths,
+            vec![vec![PartialAction::NoMoves]]
+        );
+        assert_eq!(descriptions[0].next_state.get_winner(), Some(Player::Two));
+    }
+
+    #[test]
+    fn test_game_phase() {
+        let placing = FullGameState::try_from("0000000000000000000000000/1/mortal/mortal").unwrap();
+        assert_eq!(placing.phase(), GamePhase::Placement);
+        assert_eq!(placing.progress(), 0.0);
+
+        let opening =
+            FullGameState::try_from("1100000000000000000000000/1/mortal:E1/mortal:A5").unwrap();
+        assert_eq!(opening.phase(), GamePhase::Opening);
+
+        // 14 levels, with workers on levels 2 and 1
+        let midgame =
+            FullGameState::try_from("3322211000000000000000000/1/mortal:C5,D5/mortal:A4,B4")
+                .unwrap();
+        assert_eq!(midgame.phase(), GamePhase::Midgame);
+        assert!((midgame.progress() - 0.36).abs() < 1e-4);
+
+        // 6 domes are enough on their own, even with every worker on the ground
+        let endgame =
+            FullGameState::try_from("4444440000000000000000000/1/mortal:E1,D1/mortal:A1,B1")
+                .unwrap();
+        assert_eq!(endgame.phase(), GamePhase::Endgame);
+
+        assert!(opening.progress() < midgame.progress());
+        assert!(midgame.progress() < endgame.progress());
+        let crowded =
+            FullGameState::try_from("4444444444444444444443322/1/mortal:D1,E1/mortal:C1").unwrap();
+        assert_eq!(crowded.progress(), 1.0);
+    }
+
+    #[test]
+    fn test_terminal_result_when_smothered() {
+        let state =
+            FullGameState::try_from("0444444444444444444444440/1/mortal:E1/mortal:A5").unwrap();
+        assert!(state.is_smothered(Player::One));
+        assert!(state.is_smothered(Player::Two));
+        assert_eq!(state.get_winner(), None);
+        assert_eq!(
+            state.get_terminal_result(),
+            Some(TerminalResult::Smothered(Player::One))
+        );
+        assert_eq!(state.get_terminal_result().unwrap().winner(), Player::Two);
+
+        let played_out = state.describe_moves()[0].next_state.clone();
+        assert_eq!(
+            played_out.get_terminal_result(),
+            Some(TerminalResult::Won(Player::Two))
+        );
+        assert!(!played_out.is_smothered(Player::Two));
+
+        let open =
+            FullGameState::try_from("0000000000000000000000000/1/mortal:E1/mortal:A5").unwrap();
+        assert!(!open.is_smothered(Player::One));
+        assert_eq!(open.get_terminal_result(), None);
+
+        let placing = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        assert_eq!(placing.get_terminal_result(), None);
+
+        // Hermes can't climb out, but may stay put and build
+        let hermes =
+            FullGameState::try_from("0222222222222222222222220/1/hermes:E1/mortal:A5").unwrap();
+        assert!(!hermes.is_smothered(Player::One));
+        assert!(hermes.is_smothered(Player::Two));
+        assert_eq!(hermes.get_terminal_result(), None);
+    }
+
+    #[test]
+    fn test_transform_matches_permutations() {
+        let state =
+            FullGameState::try_from("04040 04040 04440 00000 00000/1/mortal:C4/aeolus[n]:E1,E2")
+                .unwrap();
+        let permutations = state.get_all_permutations::<true>();
+
+        for symmetry in Symmetry::iter() {
+            let transformed = state.transform(symmetry);
+            assert!(
+                permutations.contains(&transformed.board),
+                "{:?} gave {:?}",
+                symmetry,
+                transformed
+            );
+            transformed.validation_err().unwrap();
+        }
+
+        // A north wind blows south once the board is upside down
+        let mirrored = state.transform(Symmetry::MirrorVertical);
+        assert_eq!(mirrored.transform(Symmetry::MirrorVertical), state);
+        assert_ne!(mirrored.board.god_data[1], state.board.god_data[1]);
+
+        let mut rotated = state.clone();
+        for _ in 0..4 {
+            rotated = rotated.transform(Symmetry::Rotate90);
+        }
+        assert_eq!(rotated, state);
+        assert_eq!(
+            state
+                .transform(Symmetry::Rotate90)
+                .board
+                .get_height(Square::E4),
+            state.board.get_height(Square::D5)
+        );
+    }
+
+    #[test]
+    fn test_transform_keeps_winner() {
+        let state =
+            FullGameState::try_from("0000000000000000000000000/2/#mortal:A5,B4/mortal:E1,D2")
+                .unwrap();
+        for symmetry in Symmetry::iter() {
+            let transformed = state.transform(symmetry);
+            assert_eq!(transformed.get_winner(), Some(Player::One));
+            transformed.validation_err().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_random_position() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for god in ALL_GODS_BY_ID.iter() {
+            let config = RandomPositionConfig::new(Matchup::new(god.god_name, GodName::Mortal))
+                .with_build_budget(30);
+            let state = FullGameState::random_position(&mut rng, &config).unwrap();
+
+            state.validation_err().unwrap();
+            assert_eq!(state.get_matchup(), config.matchup);
+            assert!(state.board.workers[0].is_not_empty());
+            assert!(state.board.workers[1].is_not_empty());
+            let built_levels: usize = Square::iter().map(|sq| state.board.get_height(sq)).sum();
+            assert!(built_levels <= 30);
+            for square in state.board.workers[0] | state.board.workers[1] {
+                assert!(state.board.get_height(square) <= 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_defensive_key_squares() {
+        // Player 2 can step from A5 up onto B5
+        let state =
+            FullGameState::try_from("2300000000000000000000000/1/mortal:C3,E3/mortal:A5,E1")
+                .unwrap();
+        assert_eq!(
+            state.defensive_key_squares(),
+            Some(BitBoard::as_mask(Square::A5) | BitBoard::as_mask(Square::B5))
+        );
+
+        let mut passed = state.clone();
+        passed.flip_current_player();
+        assert_eq!(passed.defensive_key_squares(), None);
+
+        let placing = FullGameState::try_from("0000000000000000000000000/1/mortal/mortal").unwrap();
+        assert_eq!(placing.defensive_key_squares(), None);
+    }
 
     #[test]
     fn test_serde_coord() {
@@ -996,4 +2023,123 @@ mod tests {
             assert_eq!(coord, parsed_coord);
         }
     }
+
+    #[test]
+    fn test_to_console_string_ascii() {
+        let state = parse_fen("0000000000000000000000004/2/mortal:B2,A1/mortal:D4,C3").unwrap();
+        let rendered = state.to_console_string(ConsoleRenderOptions::ascii());
+        assert!(!rendered.contains('\u{1b}'), "{}", rendered);
+        assert_eq!(
+            rendered.lines().collect::<Vec<_>>(),
+            vec![
+                "0000000000000000000000004/2/mortal:B2,A1/mortal:D4,C3",
+                "Player Two to play",
+                "5 0. 0. 0. 0. 0.",
+                "4 0. 0. 0. 0O 0.",
+                "3 0. 0. 0O 0. 0.",
+                "2 0. 0X 0. 0. 0.",
+                "1 0X 0. 0. 0. 4.",
+                "  A  B  C  D  E",
+            ]
+        );
+
+        let bare = state.to_console_string(
+            ConsoleRenderOptions::ascii()
+                .with_coordinates(false)
+                .with_god_data(false),
+        );
+        assert_eq!(bare.lines().nth(1), Some(" 0. 0. 0. 0. 0."));
+        assert_eq!(bare.lines().count(), 6);
+    }
+
+    #[test]
+    fn test_current_player_consider_placement_mode() {
+        // Bia places first, even as player 2
+        let mut state =
+            FullGameState::new_for_matchup(&Matchup::new(GodName::Mortal, GodName::Bia));
+        assert_eq!(state.board.current_player, Player::One);
+
+        let mut placers = Vec::new();
+        while state.is_placement_phase() {
+            placers.push(state.get_current_player_consider_placement_mode());
+            state = state.describe_moves()[0].next_state.clone();
+        }
+        assert_eq!(placers, vec![Player::Two, Player::One]);
+        assert_eq!(
+            state.get_current_player_consider_placement_mode(),
+            state.board.current_player
+        );
+        assert_eq!(state.board.current_player, Player::One);
+    }
+
+    #[test]
+    fn test_editor_sets_up_selene_and_hydra() {
+        let mut state =
+            FullGameState::new_for_matchup(&Matchup::new(GodName::Selene, GodName::Hydra));
+        let mut edit = |square: Square, player: Player, is_female: bool| {
+            let worker = Some(EditorWorker { player, is_female });
+            assert!(state.editor_worker_options(square).contains(&worker));
+            state = state.with_editor_worker(square, worker);
+            state.clone()
+        };
+
+        edit(Square::A1, Player::One, false);
+        let after_female = edit(Square::C3, Player::One, true);
+        assert!(after_female.editor_worker_issues().is_empty());
+        assert_eq!(
+            after_female.board.god_data[0],
+            BitBoard::as_mask(Square::C3).0
+        );
+
+        // Moving the female marker leaves a single female worker
+        let moved = edit(Square::A1, Player::One, true);
+        assert_eq!(moved.board.god_data[0], BitBoard::as_mask(Square::A1).0);
+        assert_eq!(
+            moved.editor_worker_at(Square::C3),
+            Some(EditorWorker {
+                player: Player::One,
+                is_female: false
+            })
+        );
+
+        // Hydra has no female worker to offer
+        let options = moved.editor_worker_options(Square::E5);
+        assert!(options.contains(&Some(EditorWorker {
+            player: Player::One,
+            is_female: true
+        })));
+        assert!(
+            options
+                .iter()
+                .flatten()
+                .all(|worker| worker.player == Player::One || !worker.is_female)
+        );
+
+        let one_hydra = edit(Square::E5, Player::Two, false);
+        assert_eq!(one_hydra.editor_worker_issues().len(), 1);
+        let ready = edit(Square::E1, Player::Two, false);
+        assert!(ready.editor_worker_issues().is_empty());
+        ready.validation_err().unwrap();
+
+        // Hydra can grow up to 11 workers, but no further
+        let mut squares = Square::iter().filter(|sq| ready.board.get_worker_at(*sq).is_none());
+        for square in squares.by_ref().take(9) {
+            edit(square, Player::Two, false);
+        }
+        let full = state.clone();
+        assert_eq!(full.board.workers[1].count_ones(), 11);
+        full.validation_err().unwrap();
+        let next_square = squares.next().unwrap();
+        assert!(
+            full.editor_worker_options(next_square)
+                .iter()
+                .flatten()
+                .all(|worker| worker.player == Player::One)
+        );
+
+        // Clearing a female worker clears the marker too
+        let cleared = full.with_editor_worker(Square::A1, None);
+        assert_eq!(cleared.board.god_data[0], 0);
+        cleared.validation_err().unwrap();
+    }
 }
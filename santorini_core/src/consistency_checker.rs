@@ -1,39 +1,437 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
 
 use crate::{
-    bitboard::{
-        BETWEEN_MAPPING, BitBoard, INCLUSIVE_NEIGHBOR_MAP, NEIGHBOR_MAP, apply_mapping_to_mask,
-    },
+    bitboard::{BitBoard, INCLUSIVE_NEIGHBOR_MAP, NEIGHBOR_MAP, apply_mapping_to_mask},
     board::{BoardState, FullGameState},
     fen::{game_state_to_fen, parse_fen},
     gods::{
-        GodName, StaticGod,
+        GodName,
         achilles::AchillesMove,
         ares::AresMove,
         athena::AthenaMove,
         bellerophon::BellerophonMove,
         castor::CastorMove,
         generic::{CHECK_SENTINEL_SCORE, GenericMove, MOVE_DATA_MAIN_SECTION, ScoredMove},
-        harpies::slide_position_with_custom_blockers,
         hydra::HydraMove,
         mortal::MortalMove,
         stymphalians::StymphaliansMove,
     },
     hashing::compute_hash_from_scratch,
-    player::Player,
+    placement::get_starting_placement_state,
+    pretty_board::render_diff,
+    square::Square,
+    utils::panic_message,
+    win_reason::explain_win,
 };
 
+/// A child state, followed by what changed to reach it from `state`
+fn describe_child(state: &FullGameState, child: &FullGameState) -> String {
+    format!("{:?}\n{}", child, render_diff(state, child))
+}
+
+/// Outweighs any total of worker distances, so that pairings are chosen by the rule being
+/// checked first, and only broken by distance
+const RULE_COST: usize = 100;
+
+/// Number of king steps between two squares
+fn worker_distance(a: Square, b: Square) -> usize {
+    let (a, b) = (a.to_icoord(), b.to_icoord());
+    (a.col - b.col).abs().max((a.row - b.row).abs()) as usize
+}
+
+/// Pairs the squares that workers left with the squares that workers arrived at, minimizing the
+/// total `cost` of the pairs. Brute force over every assignment, since players have at most 4
+/// workers. When the counts differ, the extra squares on the larger side are left unmatched
+fn match_workers(
+    old_only: BitBoard,
+    new_only: BitBoard,
+    cost: impl Fn(Square, Square) -> usize,
+) -> Vec<(Square, Square)> {
+    fn assign(
+        cost: &dyn Fn(usize, usize) -> usize,
+        short_len: usize,
+        long_len: usize,
+        picked: &mut Vec<usize>,
+        total: usize,
+        best: &mut Option<(usize, Vec<usize>)>,
+    ) {
+        if best
+            .as_ref()
+            .is_some_and(|(best_total, _)| total >= *best_total)
+        {
+            return;
+        }
+        let idx = picked.len();
+        if idx == short_len {
+            *best = Some((total, picked.clone()));
+            return;
+        }
+        for long_idx in 0..long_len {
+            if picked.contains(&long_idx) {
+                continue;
+            }
+            picked.push(long_idx);
+            assign(
+                cost,
+                short_len,
+                long_len,
+                picked,
+                total + cost(idx, long_idx),
+                best,
+            );
+            picked.pop();
+        }
+    }
+
+    let old: Vec<Square> = old_only.into_iter().collect();
+    let new: Vec<Square> = new_only.into_iter().collect();
+    let is_old_shorter = old.len() <= new.len();
+    let pair = |short_idx: usize, long_idx: usize| match is_old_shorter {
+        true => (old[short_idx], new[long_idx]),
+        false => (old[long_idx], new[short_idx]),
+    };
+    let pair_cost = |short_idx: usize, long_idx: usize| {
+        let (from, to) = pair(short_idx, long_idx);
+        cost(from, to)
+    };
+
+    let (short_len, long_len) = (old.len().min(new.len()), old.len().max(new.len()));
+    let mut best = None;
+    assign(
+        &pair_cost,
+        short_len,
+        long_len,
+        &mut Vec::new(),
+        0,
+        &mut best,
+    );
+    best.map_or(Vec::new(), |(_, picked)| {
+        picked
+            .into_iter()
+            .enumerate()
+            .map(|(short_idx, long_idx)| pair(short_idx, long_idx))
+            .collect()
+    })
+}
+
+/// A named validation that ran, or a god specific exception that skipped part of one
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Display, EnumIter, EnumString,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum CoveragePoint {
+    CheckFen,
+    CheckHash,
+    CheckSearchMovesSubsetAllMoves,
+    CheckNonDuplicates,
+    CheckWins,
+    CheckOpponentBlockers,
+    CheckMissedBlockers,
+    CheckSelfChecks,
+    CheckFrozenMoves,
+    CheckStymphaliansMoves,
+    CheckHadesMoves,
+    CheckPersephoneMoves,
+    CheckAphroditeMoves,
+    CheckHypnusMoves,
+    CheckLimusBuilds,
+    CheckLimusDomesVsMortal,
+
+    SkipProteusWinFlags,
+    SkipDuplicatesProteusVsHarpies,
+    SkipDuplicatesHydraTranspositions,
+    SkipDuplicatesCastorDoubleMoves,
+    SkipHadesWorkerChangers,
+    SkipPersephoneZeus,
+    SkipPersephoneWorkerChangers,
+    SkipPersephoneCastor,
+    SkipPersephoneBellerophonPower,
+    SkipPersephoneAchillesPowerClimb,
+    SkipAphroditeWorkerChangers,
+    SkipAphroditeScyllaWins,
+    SkipHypnusWorkerChangers,
+    SkipLimusMedusa,
+    SkipLimusPolyphemus,
+    SkipLimusAchilles,
+    SkipLimusDomesMultiBuilders,
+    SkipBlockerHydraSelfKill,
+    SkipBlockerVsChronus,
+    SkipBlockerHermesVsCharon,
+    SkipBlockerVsCharon,
+    SkipBlockerPersephoneVsPan,
+    SkipBlockerMultiBuildersVsPan,
+    SkipBlockerStopClimbingVsPanFall,
+    SkipBlockerVsPanBigFall,
+    SkipBlockerAphroditeAffinity,
+    SkipBlockerAphroditeVsScylla,
+    SkipBlockerAresUndoBuild,
+    SkipBlockerVsIrisJumps,
+    SkipBlockerVsArtemis,
+    SkipBlockerVsMinotaur,
+    SkipBlockerVsMaenads,
+    SkipBlockerVsEros,
+    SkipBlockerVsStymphalians,
+    SkipBlockerVsTriton,
+    SkipBlockerVsCastorKeySquares,
+    SkipMissedBlockerPersephoneVsPan,
+    SkipMissedBlockerVsChronus,
+    SkipSelfChecksStymphalians,
+    SkipSelfChecksMaenadsDance,
+    SkipSelfChecksChronusDomes,
+    SelfChecksArtemisVsHarpiesAsMortal,
+    SelfChecksVsAphroditeAsMortal,
+    SkipSelfChecksPanFallVsPersephone,
+}
+
+/// How often each `CoveragePoint` was hit, by active and opponent god
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    counts: BTreeMap<(CoveragePoint, GodName, GodName), usize>,
+}
+
+impl CoverageReport {
+    pub fn record(&mut self, point: CoveragePoint, active_god: GodName, oppo_god: GodName) {
+        *self
+            .counts
+            .entry((point, active_god, oppo_god))
+            .or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: &CoverageReport) {
+        for (key, count) in &other.counts {
+            *self.counts.entry(*key).or_default() += count;
+        }
+    }
+
+    /// Total hits of `point` across all god pairs
+    pub fn count(&self, point: CoveragePoint) -> usize {
+        self.counts
+            .iter()
+            .filter(|((p, _, _), _)| *p == point)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// The points from `required` that were never hit
+    pub fn missing(&self, required: &[CoveragePoint]) -> Vec<CoveragePoint> {
+        required
+            .iter()
+            .copied()
+            .filter(|point| self.count(*point) == 0)
+            .collect()
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((point, active_god, oppo_god), count) in &self.counts {
+            writeln!(f, "{point:<40} {active_god:>12} vs {oppo_god:<12} {count}")?;
+        }
+
+        let never_hit = self.missing(&CoveragePoint::iter().collect::<Vec<_>>());
+        if !never_hit.is_empty() {
+            writeln!(f, "Never hit:")?;
+            for point in never_hit {
+                writeln!(f, "  {point}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 pub fn consistency_check(state: &FullGameState) -> Result<(), Vec<String>> {
     let mut checker = ConsistencyChecker::new(state);
     checker.perform_all_validations()
 }
 
+/// Same as `consistency_check`, adding the checks and exceptions that were hit to `coverage`
+pub fn consistency_check_with_coverage(
+    state: &FullGameState,
+    coverage: &mut CoverageReport,
+) -> Result<(), Vec<String>> {
+    let mut checker = ConsistencyChecker::new(state).with_coverage();
+    let result = checker.perform_all_validations();
+    if let Some(checker_coverage) = &checker.coverage {
+        coverage.merge(checker_coverage);
+    }
+    result
+}
+
+/// Like `consistency_check`, but reports a panic in move generation as an error instead of
+/// unwinding past the caller
+pub fn consistency_check_catching_panics(state: &FullGameState) -> Result<(), Vec<String>> {
+    std::panic::catch_unwind(|| consistency_check(state))
+        .unwrap_or_else(|payload| Err(vec![format!("Panicked: {}", panic_message(&*payload))]))
+}
+
+/// Result of `consistency_check_deep`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeepCheckReport {
+    pub positions_checked: usize,
+    /// Whether `max_positions` was hit before every reachable position was checked
+    pub truncated: bool,
+    /// Each error, prefixed with the FEN of the position it was found in
+    pub errors: Vec<String>,
+}
+
+/// Checks `state` and every distinct position reachable from it within `plies` plies, breadth
+/// first, stopping after `max_positions` positions. Panics are reported as errors
+pub fn consistency_check_deep(
+    state: &FullGameState,
+    plies: usize,
+    max_positions: usize,
+) -> DeepCheckReport {
+    let mut report = DeepCheckReport::default();
+    let mut seen = HashSet::from([state.board.hash]);
+    let mut frontier = vec![state.clone()];
+
+    for ply in 0..=plies {
+        let mut next_frontier = Vec::new();
+        for state in frontier {
+            if report.positions_checked >= max_positions {
+                report.truncated = true;
+                return report;
+            }
+            report.positions_checked += 1;
+
+            if let Err(errors) = consistency_check_catching_panics(&state) {
+                let fen = game_state_to_fen(&state);
+                report
+                    .errors
+                    .extend(errors.into_iter().map(|err| format!("{}: {}", fen, err)));
+                // Children of a broken position aren't worth checking
+                continue;
+            }
+            if ply == plies || state.board.get_winner().is_some() {
+                continue;
+            }
+            for child in state.get_next_states() {
+                if seen.insert(child.board.hash) {
+                    next_frontier.push(child);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+    report
+}
+
+/// A child state that only one of the move generators reaches
+#[derive(Clone, Debug)]
+pub struct DivergentMove {
+    pub action_str: String,
+    pub state: FullGameState,
+}
+
+/// Result of `divergence_report`
+#[derive(Clone, Debug, Default)]
+pub struct DivergenceReport {
+    /// States the engine searches, but can't be played through `get_next_states_interactive`.
+    /// Always a bug, since `find_action_path` can't explain how to reach them
+    pub search_only: Vec<DivergentMove>,
+    /// States that can be played, but the engine never searches. Expected when the mover has a
+    /// winning move, since search moves then stop at the win
+    pub interactive_only: Vec<DivergentMove>,
+}
+
+impl DivergenceReport {
+    pub fn is_empty(&self) -> bool {
+        self.search_only.is_empty() && self.interactive_only.is_empty()
+    }
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (label, moves) in [
+            ("Search only", &self.search_only),
+            ("Interactive only", &self.interactive_only),
+        ] {
+            for divergent_move in moves {
+                writeln!(
+                    f,
+                    "{}: {} -> {:?}",
+                    label, divergent_move.action_str, divergent_move.state
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the states reached by the moves the engine searches with the states the UI can play.
+/// Empty during placement, where both use the same placement actions, and once the game is over
+pub fn divergence_report(state: &FullGameState) -> DivergenceReport {
+    let mut report = DivergenceReport::default();
+    let player = state.board.current_player;
+    let is_placing = get_starting_placement_state(&state.board, state.gods)
+        .is_ok_and(|placement_mode| placement_mode.is_some());
+    if is_placing || state.get_terminal_result().is_some() {
+        return report;
+    }
+
+    let (active_god, oppo_god) = state.get_active_non_active_gods();
+    let interactive_states: HashSet<BoardState> = state
+        .get_next_states_interactive()
+        .into_iter()
+        .map(|child| child.state.board)
+        .collect();
+
+    let mut search_states = HashSet::new();
+    for action in active_god.get_moves_for_search(state, player) {
+        let child = state.next_state(active_god, oppo_god, action.action);
+        if !search_states.insert(child.board.clone()) {
+            continue;
+        }
+        if !interactive_states.contains(&child.board) {
+            report.search_only.push(DivergentMove {
+                action_str: active_god.stringify_move(action.action),
+                state: child,
+            });
+        }
+    }
+
+    for description in state.describe_moves_with::<false>() {
+        let board = &description.next_state.board;
+        if interactive_states.contains(board) && search_states.insert(board.clone()) {
+            report.interactive_only.push(DivergentMove {
+                action_str: description.action_str,
+                state: description.next_state,
+            });
+        }
+    }
+
+    report
+}
+
+/// Panics unless `child`, reported by the engine as its best move from `state`, can be played
+/// through `get_next_states_interactive`
+pub fn verify_engine_move(state: &FullGameState, child: &FullGameState) {
+    let is_reachable = state
+        .get_next_states_interactive()
+        .iter()
+        .any(|option| &option.state == child);
+    if !is_reachable {
+        panic!(
+            "Engine move can't be played interactively: {:?} -> {:?}\n{}",
+            state,
+            child,
+            divergence_report(state)
+        );
+    }
+}
+
 /// Performs validations on god move generators,
 /// such as ensuring that win/check tags are correct
 /// And that opponent turn powers are correctly respected
 pub(crate) struct ConsistencyChecker {
     state: FullGameState,
     errors: Vec<String>,
+    coverage: Option<CoverageReport>,
 }
 
 impl ConsistencyChecker {
@@ -41,6 +439,48 @@ impl ConsistencyChecker {
         Self {
             state: state.clone(),
             errors: Default::default(),
+            coverage: None,
+        }
+    }
+
+    /// Records which checks and exceptions are hit, at some cost to speed
+    pub fn with_coverage(mut self) -> Self {
+        self.coverage = Some(Default::default());
+        self
+    }
+
+    fn record(&mut self, point: CoveragePoint) {
+        if let Some(coverage) = &mut self.coverage {
+            let (active_god, oppo_god) = self.state.get_active_non_active_gods();
+            coverage.record(point, active_god.god_name, oppo_god.god_name);
+        }
+    }
+
+    /// The active player's workers after `action` is played, but before any end of turn power
+    /// adds, removes or swaps workers. Compared with the workers before the move, only the
+    /// workers that actually moved differ
+    fn workers_before_power(&self, action: GenericMove, new_state: &FullGameState) -> BitBoard {
+        let current_player = self.state.board.current_player;
+        let new_workers = new_state.board.workers[current_player as usize];
+
+        match self.state.get_active_god().god_name {
+            GodName::Hydra => {
+                let hydra_move: HydraMove = action.into();
+                if hydra_move.get_is_winning() {
+                    new_workers
+                } else {
+                    // The special square is either a new worker, or a worker that was removed
+                    new_workers ^ hydra_move.special_worker_position().to_board()
+                }
+            }
+            GodName::Nemesis => {
+                // Swapped workers trade squares, so the opponent now stands where Nemesis
+                // workers were before the swap
+                let old_oppo_workers = self.state.board.workers[!current_player as usize];
+                let new_oppo_workers = new_state.board.workers[!current_player as usize];
+                (new_workers & !old_oppo_workers) | (new_oppo_workers & !old_oppo_workers)
+            }
+            _ => new_workers,
         }
     }
 
@@ -90,6 +530,7 @@ impl ConsistencyChecker {
     }
 
     fn validate_fen(&mut self) {
+        self.record(CoveragePoint::CheckFen);
         let fen = game_state_to_fen(&self.state);
         match parse_fen(&fen) {
             Ok(parsed_state) => {
@@ -98,6 +539,11 @@ impl ConsistencyChecker {
                         "FEN round trip mismatch. fen: {} Parsed: {:?} Original: {:?}",
                         fen, parsed_state, self.state
                     ));
+                } else if parsed_state.ply != self.state.ply {
+                    self.errors.push(format!(
+                        "FEN round trip ply mismatch. fen: {} Parsed: {} Original: {}",
+                        fen, parsed_state.ply, self.state.ply
+                    ));
                 }
             }
             Err(err) => {
@@ -108,6 +554,7 @@ impl ConsistencyChecker {
     }
 
     fn validate_hash(&mut self) {
+        self.record(CoveragePoint::CheckHash);
         let computed_hash = compute_hash_from_scratch(&self.state);
         if self.state.board.hash != computed_hash {
             self.errors.push(format!(
@@ -123,6 +570,7 @@ impl ConsistencyChecker {
         search_moves: &Vec<ScoredMove>,
         own_winning_moves: &Vec<ScoredMove>,
     ) {
+        self.record(CoveragePoint::CheckSearchMovesSubsetAllMoves);
         let mut all_move_map = HashMap::<u32, bool>::new();
 
         for action in all_moves {
@@ -136,13 +584,17 @@ impl ConsistencyChecker {
             let key = action.action.0 & MOVE_DATA_MAIN_SECTION;
             if !all_move_map.contains_key(&key) {
                 self.errors.push(format!(
-                    "Search move not in all moves: {} -> {:?}",
+                    "Search move not in all moves: {} -> {}",
                     active_god.stringify_move(action.action),
-                    self.state.next_state(active_god, oppo_god, action.action)
+                    describe_child(
+                        &self.state,
+                        &self.state.next_state(active_god, oppo_god, action.action)
+                    )
                 ));
             } else {
                 let was_winning = all_move_map[&key];
                 if active_god.god_name == GodName::Proteus {
+                    self.record(CoveragePoint::SkipProteusWinFlags);
                     continue;
                 }
                 if was_winning != action.get_is_winning() {
@@ -160,12 +612,16 @@ impl ConsistencyChecker {
             let key = action.action.0 & MOVE_DATA_MAIN_SECTION;
             if !all_move_map.contains_key(&key) {
                 self.errors.push(format!(
-                    "Winning move not in all moves: {} -> {:?}",
+                    "Winning move not in all moves: {} -> {}",
                     active_god.stringify_move(action.action),
-                    self.state.next_state(active_god, oppo_god, action.action)
+                    describe_child(
+                        &self.state,
+                        &self.state.next_state(active_god, oppo_god, action.action)
+                    )
                 ));
             } else {
                 if active_god.god_name == GodName::Proteus {
+                    self.record(CoveragePoint::SkipProteusWinFlags);
                     continue;
                 }
                 let was_winning = all_move_map[&key];
@@ -234,11 +690,10 @@ impl ConsistencyChecker {
 
         if active_god.god_name == GodName::Proteus && oppo_god.god_name == GodName::Harpies {
             // Workers can end up in their own spot. This results in duplicate moves
+            self.record(CoveragePoint::SkipDuplicatesProteusVsHarpies);
             return;
         }
-        if active_god.god_name == GodName::Hydra {
-            return;
-        }
+        self.record(CoveragePoint::CheckNonDuplicates);
 
         for action in actions {
             let action = action.action;
@@ -255,15 +710,22 @@ impl ConsistencyChecker {
                         && other_action.maybe_move_from_position_2().is_some();
 
                     if action_is_double_move && other_is_double_move {
+                        self.record(CoveragePoint::SkipDuplicatesCastorDoubleMoves);
                         continue;
                     }
+                } else if active_god.god_name == GodName::Hydra && action != *other_action {
+                    // Hydra only leaves a set of workers behind, so different moves transpose.
+                    // Moving to one square and gaining a worker on another ends the same way as
+                    // the reverse, and a removed worker leaves no trace of where it moved
+                    self.record(CoveragePoint::SkipDuplicatesHydraTranspositions);
+                    continue;
                 }
 
                 self.errors.push(format!(
-                    "Duplicate move found: {} / {} -> {:?}",
+                    "Duplicate move found: {} / {} -> {}",
                     active_god.stringify_move(action),
                     active_god.stringify_move(*other_action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                 ));
                 return;
             }
@@ -282,6 +744,7 @@ impl ConsistencyChecker {
         if oppo_frozens.is_empty() {
             return;
         }
+        self.record(CoveragePoint::CheckFrozenMoves);
         let other_frozen_workers = oppo_workers & oppo_frozens;
 
         for action in actions {
@@ -295,9 +758,9 @@ impl ConsistencyChecker {
 
             if new_oppo_frozen_workers != other_frozen_workers {
                 self.errors.push(format!(
-                    "oppo frozen workers changed: {} -> {:?}\n Frozen: {}",
+                    "oppo frozen workers changed: {} -> {}\n Frozen: {}",
                     active_god.stringify_move(action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                     oppo_frozens
                 ));
                 return;
@@ -305,9 +768,9 @@ impl ConsistencyChecker {
 
             if (new_workers & oppo_frozens).is_not_empty() {
                 self.errors.push(format!(
-                    "Moved a worker into a frozen space: {} -> {:?}\n Frozen: {}",
+                    "Moved a worker into a frozen space: {} -> {}\n Frozen: {}",
                     active_god.stringify_move(action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                     oppo_frozens
                 ));
                 return;
@@ -318,9 +781,9 @@ impl ConsistencyChecker {
                 let new_height = new_state.board.get_height(frozen_sq);
                 if new_height != old_height {
                     self.errors.push(format!(
-                        "Changed height of a frozen space: {} -> {:?}\n Frozen: {}",
+                        "Changed height of a frozen space: {} -> {}\n Frozen: {}",
                         active_god.stringify_move(action),
-                        new_state,
+                        describe_child(&self.state, &new_state),
                         oppo_frozens
                     ));
                     return;
@@ -335,6 +798,7 @@ impl ConsistencyChecker {
         if active_god.god_name != GodName::Stymphalians {
             return;
         }
+        self.record(CoveragePoint::CheckStymphaliansMoves);
 
         for action in actions {
             let stymp_move: StymphaliansMove = action.action.into();
@@ -347,11 +811,11 @@ impl ConsistencyChecker {
             if is_neighbor_of_start {
                 let new_state = self.state.next_state(active_god, oppo_god, action.action);
                 self.errors.push(format!(
-                    "Stymphalians ended turn neighboring start position: {} (from {} to {}) -> {:?}",
+                    "Stymphalians ended turn neighboring start position: {} (from {} to {}) -> {}",
                     active_god.stringify_move(action.action),
                     from_pos,
                     to_pos,
-                    new_state,
+                    describe_child(&self.state, &new_state),
                 ));
                 return;
             }
@@ -366,13 +830,11 @@ impl ConsistencyChecker {
             return;
         }
 
-        if active_god.god_name == GodName::Proteus
-            || active_god.god_name == GodName::Hydra
-            || active_god.god_name == GodName::Nemesis
-            || active_god.god_name == GodName::Jason
-        {
+        if active_god.god_name == GodName::Proteus || active_god.god_name == GodName::Jason {
+            self.record(CoveragePoint::SkipHadesWorkerChangers);
             return;
         }
+        self.record(CoveragePoint::CheckHadesMoves);
 
         let old_workers = self.state.board.workers[current_player as usize];
 
@@ -380,37 +842,34 @@ impl ConsistencyChecker {
             let action = action.action;
 
             let new_state = self.state.next_state(active_god, oppo_god, action);
-            let new_workers = new_state.board.workers[current_player as usize];
+            let new_workers = self.workers_before_power(action, &new_state);
 
             let old_only = old_workers & !new_workers;
             let new_only = new_workers & !old_workers;
 
-            let mut old_heights = Vec::new();
-            let mut new_heights = Vec::new();
-            for old_pos in old_only {
-                old_heights.push(self.state.board.get_height(old_pos));
-            }
-            for new_pos in new_only {
-                new_heights.push(new_state.board.get_height(new_pos));
-            }
-
-            if old_heights.len() != new_heights.len() {
+            if old_only.count_ones() != new_only.count_ones() {
                 self.errors.push(format!(
-                    "different number of workers in hades change that we don't know how to handle {} -> {:?}",
+                    "different number of workers in hades change that we don't know how to handle {} -> {}",
                     active_god.stringify_move(action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                 ));
                 continue;
             }
-            old_heights.sort();
-            new_heights.sort();
 
-            for (old_h, new_h) in old_heights.iter().zip(new_heights) {
-                if new_h < *old_h {
+            let old_height = |sq: Square| self.state.board.get_height(sq);
+            let new_height = |sq: Square| new_state.board.get_height(sq);
+            // Multi worker movers like Hermes can go anywhere, so only fail when no pairing
+            // keeps every worker from moving down
+            let pairs = match_workers(old_only, new_only, |from, to| {
+                RULE_COST * (new_height(to) < old_height(from)) as usize + worker_distance(from, to)
+            });
+
+            for (from, to) in pairs {
+                if new_height(to) < old_height(from) {
                     self.errors.push(format!(
-                        "Decreased height against hades: {} -> {:?}",
+                        "Decreased height against hades: {} -> {}",
                         active_god.stringify_move(action),
-                        new_state,
+                        describe_child(&self.state, &new_state),
                     ));
                     return;
                 }
@@ -430,17 +889,18 @@ impl ConsistencyChecker {
         // Ignore zeus, who can appear to move up by building under himself
         // TODO: scope this down
         if active_god_name == GodName::Zeus {
+            self.record(CoveragePoint::SkipPersephoneZeus);
             return;
         }
 
         if active_god_name == GodName::Proteus
-            || active_god_name == GodName::Hydra
-            || active_god_name == GodName::Nemesis
             || active_god_name == GodName::Jason
             || active_god_name == GodName::Stymphalians
         {
+            self.record(CoveragePoint::SkipPersephoneWorkerChangers);
             return;
         }
+        self.record(CoveragePoint::CheckPersephoneMoves);
 
         let old_workers = self.state.board.workers[current_player as usize];
 
@@ -451,7 +911,7 @@ impl ConsistencyChecker {
             let action = action.action;
 
             let new_state = self.state.next_state(active_god, oppo_god, action);
-            let new_workers = new_state.board.workers[current_player as usize];
+            let new_workers = self.workers_before_power(action, &new_state);
 
             let old_only = old_workers & !new_workers;
             let new_only = new_workers & !old_workers;
@@ -489,34 +949,28 @@ impl ConsistencyChecker {
                 }
             } else if active_god.god_name == GodName::Castor {
                 // TODO: detect this properly
+                self.record(CoveragePoint::SkipPersephoneCastor);
                 return;
             } else {
-                let mut old_heights = Vec::new();
-                let mut new_heights = Vec::new();
-                for old_pos in old_only {
-                    old_heights.push(self.state.board.get_height(old_pos));
-                }
-                for new_pos in new_only {
-                    new_heights.push(new_state.board.get_height(new_pos));
-                }
-
-                if old_heights.len() != new_heights.len() {
+                if old_only.count_ones() != new_only.count_ones() {
                     self.errors.push(format!(
-                    "different number of workers in persephone change that we don't know how to handle {} -> {:?}",
+                    "different number of workers in persephone change that we don't know how to handle {} -> {}",
                     active_god.stringify_move(action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                 ));
                     continue;
                 }
-                old_heights.sort();
-                new_heights.sort();
 
-                for (old_h, new_h) in old_heights.iter().zip(new_heights) {
-                    if new_h > *old_h {
-                        did_any_increase = true;
-                        break;
-                    }
-                }
+                let old_height = |sq: Square| self.state.board.get_height(sq);
+                let new_height = |sq: Square| new_state.board.get_height(sq);
+                // Only count a climb when every pairing of the moved workers has one
+                let pairs = match_workers(old_only, new_only, |from, to| {
+                    RULE_COST * (new_height(to) > old_height(from)) as usize
+                        + worker_distance(from, to)
+                });
+                did_any_increase = pairs
+                    .into_iter()
+                    .any(|(from, to)| new_height(to) > old_height(from));
             }
 
             if did_any_increase {
@@ -539,6 +993,7 @@ impl ConsistencyChecker {
                 let inc: BellerophonMove = inc.into();
                 let non_inc: BellerophonMove = non_inc.into();
                 if inc.is_use_power() && !non_inc.is_use_power() {
+                    self.record(CoveragePoint::SkipPersephoneBellerophonPower);
                     return;
                 }
             } else if active_god.god_name == GodName::Achilles {
@@ -550,6 +1005,7 @@ impl ConsistencyChecker {
                 };
 
                 if inc_did_climb_on_power {
+                    self.record(CoveragePoint::SkipPersephoneAchillesPowerClimb);
                     return;
                 }
             }
@@ -577,6 +1033,7 @@ impl ConsistencyChecker {
             || active_god_name == GodName::Hydra
             || active_god_name == GodName::Medusa
         {
+            self.record(CoveragePoint::SkipAphroditeWorkerChangers);
             return;
         }
 
@@ -587,6 +1044,7 @@ impl ConsistencyChecker {
         if (old_workers & old_affinity_area).is_empty() {
             return;
         }
+        self.record(CoveragePoint::CheckAphroditeMoves);
 
         for action in actions {
             let action = action.action;
@@ -594,6 +1052,7 @@ impl ConsistencyChecker {
             if active_god.god_name == GodName::Scylla && action.get_is_winning() {
                 // Scylla can always pull aphrodite into range, and we didn't bother to implement the drag on winning moves
                 // So just ignore this check
+                self.record(CoveragePoint::SkipAphroditeScyllaWins);
                 continue;
             }
 
@@ -618,9 +1077,9 @@ impl ConsistencyChecker {
 
             if old_only.count_ones() != new_only.count_ones() {
                 self.errors.push(format!(
-                    "Unexpected worker change? {} -> {:?}",
+                    "Unexpected worker change? {} -> {}",
                     active_god.stringify_move(action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                 ));
             }
 
@@ -628,9 +1087,9 @@ impl ConsistencyChecker {
                 > (new_only & new_affinity_area).count_ones()
             {
                 self.errors.push(format!(
-                    "Moved a worker out of aphrodite affinity area: {} -> {:?}",
+                    "Moved a worker out of aphrodite affinity area: {} -> {}",
                     active_god.stringify_move(action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                 ));
             }
         }
@@ -644,18 +1103,17 @@ impl ConsistencyChecker {
             return;
         }
 
-        if active_god.god_name == GodName::Proteus
-            || active_god.god_name == GodName::Hydra
-            || active_god.god_name == GodName::Nemesis
-        {
+        if active_god.god_name == GodName::Proteus {
+            self.record(CoveragePoint::SkipHypnusWorkerChangers);
             return;
         }
+        self.record(CoveragePoint::CheckHypnusMoves);
 
         for action in actions {
             let action = action.action;
 
             let new_state = self.state.next_state(active_god, oppo_god, action);
-            let new_workers = new_state.board.workers[current_player as usize];
+            let new_workers = self.workers_before_power(action, &new_state);
             let old_workers = self.state.board.workers[current_player as usize];
 
             let moved_workers = old_workers & !new_workers;
@@ -668,9 +1126,9 @@ impl ConsistencyChecker {
                 let height_at_worker = self.state.board.height_map[old_worker_height - 1];
                 if (old_workers & height_at_worker).count_ones() == 1 {
                     self.errors.push(format!(
-                        "Moved a highest worker against hypnus: {} -> {:?}",
+                        "Moved a highest worker against hypnus: {} -> {}",
                         active_god.stringify_move(action),
-                        new_state,
+                        describe_child(&self.state, &new_state),
                     ));
                     return;
                 }
@@ -687,16 +1145,20 @@ impl ConsistencyChecker {
         }
         if active_god.god_name == GodName::Medusa {
             // Medusa can "build" around limus, by converting limus workers in to stone
+            self.record(CoveragePoint::SkipLimusMedusa);
             return;
         }
         if active_god.god_name == GodName::Polyphemus {
+            self.record(CoveragePoint::SkipLimusPolyphemus);
             return;
         }
         if active_god.god_name == GodName::Achilles {
             // This detection fails, because Achilles has some checks against mortals that don't
             // work vs limus (so we can't properly check that lists are equal)
+            self.record(CoveragePoint::SkipLimusAchilles);
             return;
         }
+        self.record(CoveragePoint::CheckLimusBuilds);
 
         let mut dome_build_actions = Vec::new();
 
@@ -735,9 +1197,9 @@ impl ConsistencyChecker {
 
             if (new_builds & !build_mask).is_not_empty() {
                 let error_string = format!(
-                    "Built in a build masked area: {} -> {:?}. Build mask:\n{}. Builds:\n:{}",
+                    "Built in a build masked area: {} -> {}. Build mask:\n{}. Builds:\n:{}",
                     active_god.stringify_move(action),
-                    new_state,
+                    describe_child(&self.state, &new_state),
                     build_mask,
                     new_builds
                 );
@@ -749,8 +1211,10 @@ impl ConsistencyChecker {
         if active_god.god_name == GodName::Morpheus || active_god.god_name == GodName::Poseidon {
             // The check below only works for domers.
             // Morpheus "domes" over multiple builds, which limus does not allow
+            self.record(CoveragePoint::SkipLimusDomesMultiBuilders);
             return;
         }
+        self.record(CoveragePoint::CheckLimusDomesVsMortal);
         let mut against_mortal_state = self.state.clone();
         against_mortal_state.gods[!current_player as usize] = GodName::Mortal.to_power();
         let against_mortal_search_moves =
@@ -777,10 +1241,10 @@ impl ConsistencyChecker {
 
                 if !seen_dome_build {
                     let error_string = format!(
-                        "Was able to build valid dome against mortal, but not limus: {} ({}) -> {:?}",
+                        "Was able to build valid dome against mortal, but not limus: {} ({}) -> {}",
                         active_god.stringify_move(vs_mortal_action),
                         vs_mortal_action.0,
-                        new_state,
+                        describe_child(&self.state, &new_state),
                     );
                     self.errors.push(error_string);
                     return;
@@ -827,6 +1291,7 @@ impl ConsistencyChecker {
                 .push("Opponent had wins, with no blocker board".to_owned());
             return;
         }
+        self.record(CoveragePoint::CheckOpponentBlockers);
 
         let scored_blocker_actions =
             active_god.get_scored_blocker_moves(&self.state, current_player, key_moves);
@@ -863,23 +1328,27 @@ impl ConsistencyChecker {
                 // TODO: fix that in move gen
                 let block_action: HydraMove = block_action.into();
                 if block_action.move_to_position() == block_action.special_worker_position() {
+                    self.record(CoveragePoint::SkipBlockerHydraSelfKill);
                     continue;
                 }
             }
 
             if oppo_god.god_name == GodName::Chronus {
                 // Hard to detect & prevent dome wins
+                self.record(CoveragePoint::SkipBlockerVsChronus);
                 continue;
             }
 
             if oppo_god.god_name == GodName::Charon {
                 if active_god.god_name == GodName::Hermes {
                     // Hermes thinks that while standing still in Charon's winning square he's blocking a win
+                    self.record(CoveragePoint::SkipBlockerHermesVsCharon);
                     continue;
                 }
 
                 // If a character builds in the flip zone, it doesn't do anything.
                 // TODO: add check to confirm this is what's happening
+                self.record(CoveragePoint::SkipBlockerVsCharon);
                 continue;
             }
 
@@ -887,6 +1356,7 @@ impl ConsistencyChecker {
                 let any_pan_move: MortalMove = other_wins[0].action.into();
 
                 if active_god.god_name == GodName::Persephone {
+                    self.record(CoveragePoint::SkipBlockerPersephoneVsPan);
                     continue;
                 }
 
@@ -895,6 +1365,7 @@ impl ConsistencyChecker {
                 {
                     // morpheus can triple build into 0's, allowing pan to make the same move to
                     // win
+                    self.record(CoveragePoint::SkipBlockerMultiBuildersVsPan);
                     continue;
                 }
 
@@ -907,6 +1378,7 @@ impl ConsistencyChecker {
                         >= self.state.board.get_height(any_pan_move.move_to_position()) + 2;
 
                     if athena_move.get_is_stopping_climbing() && did_pan_fall {
+                        self.record(CoveragePoint::SkipBlockerStopClimbingVsPanFall);
                         continue;
                     }
                 }
@@ -922,6 +1394,7 @@ impl ConsistencyChecker {
                     }
                 }
                 if is_pan_big_fall {
+                    self.record(CoveragePoint::SkipBlockerVsPanBigFall);
                     continue;
                 }
             }
@@ -932,12 +1405,14 @@ impl ConsistencyChecker {
                 // But this can false positive if another worker can pull out a win anyway
                 if (key_moves & self.state.board.workers[!current_player as usize]).count_ones() > 1
                 {
+                    self.record(CoveragePoint::SkipBlockerAphroditeAffinity);
                     continue;
                 }
 
                 // Aphrodite can't block scylla with affinity restrictions, so ignore this check
                 // TODO: consider if we should add this check to Aphrodite's check response instead
                 if oppo_god.god_name == GodName::Scylla {
+                    self.record(CoveragePoint::SkipBlockerAphroditeVsScylla);
                     continue;
                 }
             }
@@ -949,6 +1424,7 @@ impl ConsistencyChecker {
                         && (key_moves & BitBoard::as_mask(remove_build)).is_not_empty()
                     {
                         // Ares can undo his own build, not worth checking for
+                        self.record(CoveragePoint::SkipBlockerAresUndoBuild);
                         continue;
                     }
                 }
@@ -957,6 +1433,7 @@ impl ConsistencyChecker {
             if oppo_god.god_name == GodName::Iris {
                 // Jumps
                 if key_moves == BitBoard::MAIN_SECTION_MASK {
+                    self.record(CoveragePoint::SkipBlockerVsIrisJumps);
                     continue;
                 }
             }
@@ -987,6 +1464,7 @@ impl ConsistencyChecker {
                 //     blocked_state.print_to_console();
                 //     return;
                 // }
+                self.record(CoveragePoint::SkipBlockerVsArtemis);
                 continue;
             }
 
@@ -994,6 +1472,7 @@ impl ConsistencyChecker {
                 // TODO: scope this down
                 // Minotaur puts spots that it pushes TO during a mate into the blocker board
                 // but this only works on dome builds / moves - not lower builds.
+                self.record(CoveragePoint::SkipBlockerVsMinotaur);
                 continue;
             }
 
@@ -1001,20 +1480,31 @@ impl ConsistencyChecker {
                 // TODO: scope this down.
                 // Maenads dancing wins have a huge blocker board, since so far we haven't included
                 // FROM positions as part of key square maps
+                self.record(CoveragePoint::SkipBlockerVsMaenads);
                 continue;
             }
 
             if oppo_god.god_name == GodName::Eros {
                 // TODO: scope this down.
+                self.record(CoveragePoint::SkipBlockerVsEros);
                 continue;
             }
 
             if oppo_god.god_name == GodName::Stymphalians {
                 // TODO: scope this down.
+                self.record(CoveragePoint::SkipBlockerVsStymphalians);
+                continue;
+            }
+
+            if oppo_god.god_name == GodName::Triton {
+                // Triton's blocker board includes the whole perimeter, since only the start and
+                // end of a chain are reflected in the winning move.
+                self.record(CoveragePoint::SkipBlockerVsTriton);
                 continue;
             }
 
             if _test_castor_bad_key_move_blockers(&self.state) {
+                self.record(CoveragePoint::SkipBlockerVsCastorKeySquares);
                 continue;
             }
 
@@ -1035,6 +1525,7 @@ impl ConsistencyChecker {
         }
 
         let mut did_output_key_moves = false;
+        self.record(CoveragePoint::CheckMissedBlockers);
 
         // Test that we didn't miss any blockers
         for action in search_moves {
@@ -1053,6 +1544,7 @@ impl ConsistencyChecker {
                     // But this build might not actually force him to move up, so doesn't chang
                     // his winning moves at all
                     if new_oppo_wins.len() > 0 {
+                        self.record(CoveragePoint::SkipMissedBlockerPersephoneVsPan);
                         continue;
                     }
                 }
@@ -1061,6 +1553,7 @@ impl ConsistencyChecker {
                     // If we won on chronus behalf, it removes his winning moves, but he just wins
                     // anyway...
                     if new_oppo_wins.len() > 0 {
+                        self.record(CoveragePoint::SkipMissedBlockerVsChronus);
                         continue;
                     }
                 }
@@ -1092,8 +1585,10 @@ impl ConsistencyChecker {
 
         if active_god.god_name == GodName::Stymphalians {
             // We don't bother with check detection on this guy... It's too hard
+            self.record(CoveragePoint::SkipSelfChecksStymphalians);
             return;
         }
+        self.record(CoveragePoint::CheckSelfChecks);
 
         for (i, action) in search_moves.iter().enumerate() {
             if action.get_is_winning() {
@@ -1132,12 +1627,15 @@ impl ConsistencyChecker {
                 if is_real_checker && active_god.god_name == GodName::Maenads {
                     // maenads dancing kills...
                     // TODO: include these
+                    self.record(CoveragePoint::SkipSelfChecksMaenadsDance);
                     continue;
                 }
 
                 if is_real_checker && active_god.god_name == GodName::Chronus {
-                    // We're not doing check detection on dome wins
+                    // Dome win check detection only accounts for Hypnus among the opponent
+                    // powers, so it can miss dome wins that other powers open up
                     if check_state.board.height_map[3].count_ones() >= 4 {
+                        self.record(CoveragePoint::SkipSelfChecksChronusDomes);
                         continue;
                     }
                 }
@@ -1146,6 +1644,7 @@ impl ConsistencyChecker {
                     && active_god.god_name == GodName::Artemis
                     && oppo_god.god_name == GodName::Harpies
                 {
+                    self.record(CoveragePoint::SelfChecksArtemisVsHarpiesAsMortal);
                     let wins_from_mortal_check_state = GodName::Mortal
                         .to_power()
                         .get_winning_moves(&check_state, current_player);
@@ -1160,6 +1659,7 @@ impl ConsistencyChecker {
                     // We don't consider aphrodite areas when doing check detection
                     // Replace aphrodite with a mortal and detect for checks again. If it is,
                     // ignore it
+                    self.record(CoveragePoint::SelfChecksVsAphroditeAsMortal);
                     let mut checks_vs_mortal_state = check_state.clone();
                     checks_vs_mortal_state.gods[!current_player as usize] =
                         GodName::Mortal.to_power();
@@ -1187,6 +1687,7 @@ impl ConsistencyChecker {
                 {
                     // Persephone can force pan to go up, preventing his downfall win con
                     // Doesn't seem worth trying to account for
+                    self.record(CoveragePoint::SkipSelfChecksPanFallVsPersephone);
                     continue;
                 } else {
                     let type_msg = match is_real_checker {
@@ -1210,9 +1711,6 @@ impl ConsistencyChecker {
                 self._validate_win(
                     &format!("FromCheckState > {} ({:?})", stringed_action, check_state),
                     &check_state,
-                    current_player,
-                    active_god,
-                    oppo_god,
                     winning_action.action,
                 );
             }
@@ -1220,193 +1718,22 @@ impl ConsistencyChecker {
     }
 
     fn validate_wins(&mut self, wins: &Vec<ScoredMove>) {
+        self.record(CoveragePoint::CheckWins);
         for winning_action in wins {
             self._validate_win_from_current_state(winning_action.action);
         }
     }
 
     fn _validate_win_from_current_state(&mut self, action: GenericMove) {
-        let current_player = self.state.board.current_player;
-        let (active_god, oppo_god) = self.state.get_active_non_active_gods();
-        self._validate_win(
-            &"FromRootState",
-            &self.state.clone(),
-            current_player,
-            active_god,
-            oppo_god,
-            action,
-        );
+        self._validate_win("FromRootState", &self.state.clone(), action);
     }
 
-    fn _validate_win(
-        &mut self,
-        label: &str,
-        state: &FullGameState,
-        current_player: Player,
-        active_god: StaticGod,
-        oppo_god: StaticGod,
-        action: GenericMove,
-    ) {
-        let stringed_action = active_god.stringify_move(action);
+    fn _validate_win(&mut self, label: &str, state: &FullGameState, action: GenericMove) {
+        let (active_god, oppo_god) = state.get_active_non_active_gods();
         let won_state = state.next_state(active_god, oppo_god, action);
-        if won_state.get_winner() != Some(current_player) {
-            self.errors.push(format!(
-                "{label}:Winning move did not result in win: {}. {:?} -> {:?} winner: {:?} current_player: {:?}",
-                stringed_action, state, won_state, won_state.get_winner(), current_player
-            ));
-            return;
-        }
-
-        if active_god.god_name == GodName::Chronus {
-            if won_state.board.height_map[3].count_ones() >= 5 {
-                // chronus dome win
-                return;
-            }
-        }
-
-        let old_workers = state.board.workers[current_player as usize];
-        let new_workers = won_state.board.workers[current_player as usize];
-        let old_only = old_workers & !new_workers;
-        let new_only = new_workers & !old_workers;
-        assert_eq!(
-            old_only.count_ones(),
-            1,
-            "Could not identify old moved worker from winning move check"
-        );
-        assert_eq!(
-            new_only.count_ones(),
-            1,
-            "Could not identify new moved worker from winning move check"
-        );
-        let old_pos = old_only.lsb();
-        let new_pos = new_only.lsb();
-        let old_height = state.board.get_height(old_pos) as i32;
-        let new_height = won_state.board.get_height(new_pos) as i32;
-
-        let is_pan_falling_win =
-            active_god.god_name == GodName::Pan && new_height <= old_height - 2;
-
-        if active_god.god_name == GodName::Maenads {
-            // Maenads wins by dancing
-            return;
-        }
-
-        if active_god.god_name == GodName::Eros {
-            return;
-        }
-
-        let can_climb = oppo_god.can_opponent_climb(&state.board, !current_player);
-        if !can_climb && !is_pan_falling_win {
-            self.errors.push(format!(
-                "Win when blocked by athena: {}. {:?} -> {:?}",
-                stringed_action, state, won_state,
-            ));
-            return;
-        }
-
-        if active_god.god_name == GodName::Bellerophon {
-            if old_height == 1 && new_height == 3 {
-                let action: BellerophonMove = action.into();
-                if action.is_use_power() {
-                    return;
-                }
-            }
-        }
-
-        if active_god.god_name == GodName::Stymphalians {
-            // This guy can move a lot...
-            if new_height == 3 {
-                return;
-            }
-        }
-
-        let win_mask = oppo_god.win_mask;
-        if (win_mask & new_only).is_empty() {
-            if active_god.god_name == GodName::Castor {
-                if _validate_castor_double_move_win(
-                    state,
-                    &won_state,
-                    current_player,
-                    action,
-                    win_mask,
-                ) {
-                    return;
-                }
-            }
-
-            self.errors.push(format!(
-                "Winning move did not move to win mask: {}. {:?} -> {:?}",
-                stringed_action, state, won_state,
-            ));
-            return;
-        }
-
-        if old_height == 2 && new_height == 3 {
-            return;
-        }
-
-        if is_pan_falling_win {
-            return;
+        if let Err(err) = explain_win(state, &won_state, action) {
+            self.errors.push(format!("{label}:{err}"));
         }
-
-        if active_god.god_name == GodName::Artemis {
-            let old_n = NEIGHBOR_MAP[old_pos as usize];
-            let new_n = NEIGHBOR_MAP[new_pos as usize];
-            let path = old_n & new_n;
-            let path = path & state.board.exactly_level_2();
-            let path = path & !(state.board.workers[0] | state.board.workers[1]);
-
-            if (old_height == 1 || old_height == 3) && new_height == 3 && path.is_not_empty() {
-                return;
-            }
-
-            if oppo_god.god_name == GodName::Harpies {
-                let mut matched = false;
-                for n in old_n {
-                    let slide_n = slide_position_with_custom_blockers(
-                        &state.board,
-                        old_pos,
-                        n,
-                        state.board.workers[0] | state.board.workers[1],
-                    );
-                    if state.board.get_height(slide_n) == 2 {
-                        let final_n = NEIGHBOR_MAP[slide_n as usize];
-
-                        if (new_only & final_n).is_not_empty() {
-                            matched = true;
-                            break;
-                        }
-                    }
-                }
-
-                if matched {
-                    return;
-                }
-            }
-        }
-
-        if active_god.god_name == GodName::Castor {
-            if _validate_castor_double_move_win(state, &won_state, current_player, action, win_mask)
-            {
-                return;
-            }
-        }
-
-        // Iris wins by jumping over a worker to level 2
-        if active_god.god_name == GodName::Iris {
-            if let Some(jumped_pos) = BETWEEN_MAPPING[old_pos as usize][new_pos as usize] {
-                let all_workers = won_state.board.workers[0] | won_state.board.workers[1];
-
-                if all_workers.contains_square(jumped_pos) && old_height < 3 && new_height == 3 {
-                    return;
-                }
-            }
-        }
-
-        self.errors.push(format!(
-            "Move won with unknown winning condition: {}. {:?} -> {:?}. Old pos: {old_pos} New pos: {new_pos}. Old height: {old_height} New height: {new_height}",
-            stringed_action, state, won_state,
-        ));
     }
 
     fn _test_blocker_moves_are_consistent(
@@ -1470,36 +1797,151 @@ fn _test_castor_bad_key_move_blockers(state: &FullGameState) -> bool {
     return true;
 }
 
-fn _validate_castor_double_move_win(
-    prev_state: &FullGameState,
-    won_state: &FullGameState,
-    _player: Player,
-    action: GenericMove,
-    win_mask: BitBoard,
-) -> bool {
-    let action: CastorMove = action.into();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let Some(f1) = action.maybe_move_from_position_1() else {
-        return false;
-    };
-    let Some(f2) = action.maybe_move_from_position_2() else {
-        return false;
-    };
+    #[test]
+    fn test_coverage_records_checks_and_exceptions() {
+        let state = parse_fen("0000000000000000000000000/1/proteus:A1,B2/hades:D4,E5").unwrap();
+        let mut coverage = CoverageReport::default();
+        assert!(consistency_check_with_coverage(&state, &mut coverage).is_ok());
+        assert!(consistency_check_with_coverage(&state, &mut coverage).is_ok());
 
-    let t1 = action.move_to_position_1();
-    let t2 = action.move_to_position_2();
+        assert_eq!(coverage.count(CoveragePoint::CheckFen), 2);
+        assert_eq!(coverage.count(CoveragePoint::SkipHadesWorkerChangers), 2);
+        assert_eq!(coverage.count(CoveragePoint::CheckHadesMoves), 0);
+        assert_eq!(
+            coverage.missing(&[
+                CoveragePoint::SkipHadesWorkerChangers,
+                CoveragePoint::CheckHadesMoves
+            ]),
+            vec![CoveragePoint::CheckHadesMoves]
+        );
 
-    let o1 = prev_state.board.get_height(f1) as i32;
-    let n1 = won_state.board.get_height(t1) as i32;
-    if o1 == 2 && n1 == 3 && (t1.to_board() & win_mask).is_not_empty() {
-        return true;
+        let report = coverage.to_string();
+        assert!(report.contains("skip_hades_worker_changers"));
+        assert!(report.contains("proteus"));
+        assert!(report.contains("Never hit:\n"));
+        assert_eq!(
+            "skip_blocker_vs_triton".parse::<CoveragePoint>(),
+            Ok(CoveragePoint::SkipBlockerVsTriton)
+        );
     }
 
-    let o2 = prev_state.board.get_height(f2) as i32;
-    let n2 = won_state.board.get_height(t2) as i32;
-    if o2 == 2 && n2 == 3 && (t2.to_board() & win_mask).is_not_empty() {
-        return true;
+    #[test]
+    fn test_worker_changers_vs_hades() {
+        // Every worker stands on level 2 with only level 2 squares to move to, so workers only
+        // end up lower through a power: a new Hydra worker, or a Nemesis swap
+        for fen in [
+            "2000020000200002000020000/1/hydra:A5,A1/hades:E5,E1",
+            "2000020000200002000020000/1/nemesis:A5,A1/hades:E5,E1",
+        ] {
+            let state = parse_fen(fen).unwrap();
+            let mut coverage = CoverageReport::default();
+            let result = consistency_check_with_coverage(&state, &mut coverage);
+            assert!(result.is_ok(), "{}: {:?}", fen, result);
+            assert_eq!(coverage.count(CoveragePoint::CheckHadesMoves), 1);
+
+            let has_lowered_worker = state.get_next_states().iter().any(|child| {
+                child.board.workers[0]
+                    .into_iter()
+                    .any(|sq| child.board.get_height(sq) == 0)
+            });
+            assert!(has_lowered_worker, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_worker_changers_vs_persephone_and_hypnus() {
+        for fen in [
+            "0100000000000000000000000/1/hydra:A5,E1/persephone:C3,E3",
+            "0100000000000000000000000/1/nemesis:A5,E1/persephone:C3,E3",
+            "1000000000000000000000000/1/hydra:A5,E1/hypnus:C3,E3",
+            "1000000000000000000000000/1/nemesis:A5,E1/hypnus:C3,E3",
+        ] {
+            let state = parse_fen(fen).unwrap();
+            let result = consistency_check(&state);
+            assert!(result.is_ok(), "{}: {:?}", fen, result);
+        }
+    }
+
+    #[test]
+    fn test_hydra_transpositions_are_not_duplicates() {
+        // Found by the fuzzer: E5>E4 gaining a worker on D5 ends the same as E5>D5 gaining one on
+        // E4
+        let state = parse_fen("0000000000000000000000000/1/hydra:E5,A4/nike:D4,B2").unwrap();
+        let mut coverage = CoverageReport::default();
+        let result = consistency_check_with_coverage(&state, &mut coverage);
+        assert!(result.is_ok(), "{:?}", result);
+        assert!(coverage.count(CoveragePoint::SkipDuplicatesHydraTranspositions) > 0);
     }
 
-    false
+    #[test]
+    fn test_match_workers() {
+        let board = |squares: &[Square]| {
+            squares
+                .iter()
+                .fold(BitBoard::EMPTY, |acc, sq| acc | sq.to_board())
+        };
+        let pairs = match_workers(
+            board(&[Square::A1, Square::E5]),
+            board(&[Square::D4, Square::B2]),
+            worker_distance,
+        );
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs.contains(&(Square::A1, Square::B2)));
+        assert!(pairs.contains(&(Square::E5, Square::D4)));
+
+        // An extra square is left unmatched
+        let pairs = match_workers(
+            board(&[Square::C3]),
+            board(&[Square::A1, Square::C4]),
+            worker_distance,
+        );
+        assert_eq!(pairs, vec![(Square::C3, Square::C4)]);
+        assert!(match_workers(BitBoard::EMPTY, board(&[Square::C3]), worker_distance).is_empty());
+    }
+
+    #[test]
+    fn test_divergence_report() {
+        let quiet = parse_fen("0000000000000000000000000/1/mortal:A1,B2/pan:D4,E5").unwrap();
+        assert!(divergence_report(&quiet).is_empty());
+
+        // Search stops at the win, so the other moves are only playable
+        let winning = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let report = divergence_report(&winning);
+        assert!(report.search_only.is_empty());
+        assert!(!report.interactive_only.is_empty());
+
+        for child in winning.get_next_states() {
+            verify_engine_move(&winning, &child);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Engine move can't be played interactively")]
+    fn test_verify_engine_move_rejects_unreachable_states() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B2/pan:D4,E5").unwrap();
+        let unreachable = parse_fen("0000000000000000000000000/2/mortal:A1,B3/pan:D4,E5").unwrap();
+        verify_engine_move(&state, &unreachable);
+    }
+
+    #[test]
+    fn test_consistency_check_deep() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B2/pan:D4,E5").unwrap();
+        let root_children = state.get_next_states().len();
+
+        let report = consistency_check_deep(&state, 0, 1000);
+        assert_eq!(report.positions_checked, 1);
+        assert!(!report.truncated);
+
+        let report = consistency_check_deep(&state, 1, 1000);
+        assert_eq!(report.positions_checked, 1 + root_children);
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+        let report = consistency_check_deep(&state, 3, 50);
+        assert_eq!(report.positions_checked, 50);
+        assert!(report.truncated);
+    }
 }
@@ -48,6 +48,11 @@ impl Direction {
         }
     }
 
+    /// The square one step from `square` in this direction, or None if it's off the board
+    pub const fn apply(self, square: Square) -> Option<Square> {
+        square.to_icoord().add(self.to_icoord()).to_square()
+    }
+
     pub const fn from_u8(val: u8) -> Self {
         transmute_enum!(val)
     }
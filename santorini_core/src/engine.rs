@@ -1,4 +1,8 @@
 use std::{
+    cell::RefCell,
+    collections::HashMap,
+    panic::{AssertUnwindSafe, catch_unwind},
+    rc::Rc,
     sync::{
         Arc, Mutex,
         atomic::{AtomicBool, Ordering},
@@ -10,15 +14,271 @@ use std::{
 
 use crate::{
     board::FullGameState,
-    search::{BestSearchResult, SearchContext, get_past_win_search_terminator, negamax_search},
+    fen::game_state_to_fen,
+    gods::generic::GenericMove,
+    hashing::HashType,
+    matchup::{MatchupPolicy, matchup_policy},
+    quick_verdict::score_root_moves_parallel,
+    search::{
+        BestMoveTrigger, BestSearchResult, Heuristic, RootMoveFilter, SearchContext, SearchParams,
+        get_past_win_search_terminator, negamax_search,
+    },
+    search_observer::NoopSearchObserver,
     search_terminators::{
-        AndSearchTerminator, OrSearchTerminator, StaticMaxDepthSearchTerminator,
-        StaticNodesVisitedSearchTerminator, StopFlagSearchTerminator,
+        AndSearchTerminator, DynamicSearchTerminator, HeartbeatSearchTerminator,
+        OrSearchTerminator, PollSearchTerminator, SearchHeartbeat, SearchLimits,
+        StaticMaxDepthSearchTerminator, StaticNodesVisitedSearchTerminator,
+        StopFlagSearchTerminator,
     },
+    time_manager::TimeManager,
     transposition_table::TranspositionTable,
+    utils::panic_message,
 };
 
-type EachMoveCallback = Arc<dyn Fn(BestSearchResult) + Send + Sync>;
+pub type EachMoveCallback = Arc<dyn Fn(BestSearchResult) + Send + Sync>;
+/// Called periodically with the progress of the search of the given position
+pub type HeartbeatCallback = Arc<dyn Fn(&FullGameState, SearchHeartbeat) + Send + Sync>;
+/// Called from the engine thread when a search fails, after the failure can be taken with
+/// `take_search_failure`
+pub type SearchFailureCallback = Arc<dyn Fn(&SearchFailure) + Send + Sync>;
+
+/// How often searches report heartbeats, if a heartbeat callback is set
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Default for `EngineThreadWrapper::set_best_move_min_interval`
+pub const DEFAULT_BEST_MOVE_MIN_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A search result, along with how long the position had been analyzed when it was found
+pub type TimedSearchResult = (BestSearchResult, Duration);
+
+/// How many recently searched positions keep their results
+const ANALYSIS_CACHE_SIZE: usize = 4096;
+
+struct AnalysisEntry {
+    state: FullGameState,
+    results: Vec<TimedSearchResult>,
+    /// `AnalysisCache::use_count` when the entry was last read or written
+    last_used: u64,
+}
+
+/// Results for recently searched positions, deepest last. Lets analysis pick up where it left
+/// off, and lets a search of a position that was already searched report a move straight away.
+/// The least recently used position is evicted once it's full, and positions that share a hash
+/// replace each other
+#[derive(Default)]
+pub struct AnalysisCache {
+    entries: HashMap<HashType, AnalysisEntry>,
+    use_count: u64,
+    /// The matchup policy the results were found under. Komi comes from it
+    matchup_policy: Option<Arc<MatchupPolicy>>,
+}
+
+impl AnalysisCache {
+    fn get(&mut self, state: &FullGameState) -> Option<&AnalysisEntry> {
+        self.use_count += 1;
+        let entry = self
+            .entries
+            .get_mut(&state.board.hash)
+            .filter(|entry| &entry.state == state)?;
+        entry.last_used = self.use_count;
+        Some(entry)
+    }
+
+    /// How long `state` has been analyzed for so far
+    pub fn analyzed_duration(&mut self, state: &FullGameState) -> Duration {
+        self.get(state)
+            .and_then(|entry| entry.results.last())
+            .map_or(Duration::ZERO, |(_, elapsed)| *elapsed)
+    }
+
+    /// Restarted searches start over from depth 1, so only results that are at least as deep
+    /// as what's already known are kept
+    pub fn record(&mut self, state: &FullGameState, result: BestSearchResult, elapsed: Duration) {
+        self.use_count += 1;
+        if !self.entries.contains_key(&state.board.hash)
+            && self.entries.len() >= ANALYSIS_CACHE_SIZE
+            && let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(hash, _)| *hash)
+        {
+            self.entries.remove(&oldest);
+        }
+
+        let entry = self
+            .entries
+            .entry(state.board.hash)
+            .or_insert_with(|| AnalysisEntry {
+                state: state.clone(),
+                results: Vec::new(),
+                last_used: 0,
+            });
+        if &entry.state != state {
+            entry.state = state.clone();
+            entry.results.clear();
+        }
+        entry.last_used = self.use_count;
+
+        if entry
+            .results
+            .last()
+            .is_some_and(|(last, _)| result.depth < last.depth)
+        {
+            return;
+        }
+        entry.results.push((result, elapsed));
+    }
+
+    /// Results recorded for `state`, deepest last
+    pub fn results(&mut self, state: &FullGameState) -> Vec<TimedSearchResult> {
+        self.get(state)
+            .map_or_else(Vec::new, |entry| entry.results.clone())
+    }
+
+    /// The deepest result recorded for `state`, as a Saved result for this visit to it
+    pub fn saved_result(&mut self, state: &FullGameState) -> Option<BestSearchResult> {
+        let (result, _) = self.get(state)?.results.last()?;
+        let mut result = result.clone();
+        // The position may have been reached on a different turn
+        result.child_state.ply = state.ply + 1;
+        result.nodes_visited = 0;
+        result.trigger = BestMoveTrigger::Saved;
+        Some(result)
+    }
+
+    /// Forgets everything if the results were found under a different matchup policy
+    pub fn check_matchup_policy(&mut self, matchup_policy: &Arc<MatchupPolicy>) {
+        let is_same = self
+            .matchup_policy
+            .as_ref()
+            .is_some_and(|cached| Arc::ptr_eq(cached, matchup_policy));
+        if !is_same {
+            self.clear();
+            self.matchup_policy = Some(matchup_policy.clone());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Rate limits the best moves reported by a search. Progress reports that come within
+/// `min_interval` of the last reported move are held back, and each one replaces the last, so that
+/// the latest is reported once the interval is up. Results that aren't coalescable are reported
+/// straight away, and replace any held back result
+pub struct BestMoveCoalescer {
+    min_interval: Duration,
+    last_reported_at: Option<Instant>,
+    pending: Option<BestSearchResult>,
+    suppressed_updates: usize,
+}
+
+impl BestMoveCoalescer {
+    pub fn new(min_interval: Duration) -> Self {
+        BestMoveCoalescer {
+            min_interval,
+            last_reported_at: None,
+            pending: None,
+            suppressed_updates: 0,
+        }
+    }
+
+    /// Returns `result` if it should be reported now, otherwise holds on to it
+    pub fn offer(&mut self, result: BestSearchResult, now: Instant) -> Option<BestSearchResult> {
+        if self.pending.take().is_some() {
+            self.suppressed_updates += 1;
+        }
+
+        let is_due = self
+            .last_reported_at
+            .is_none_or(|last_reported_at| now - last_reported_at >= self.min_interval);
+        if is_due || !result.trigger.is_coalescable() {
+            Some(self.report(result, now))
+        } else {
+            self.pending = Some(result);
+            None
+        }
+    }
+
+    /// The held back result, if its interval is up by `now`
+    pub fn take_due(&mut self, now: Instant) -> Option<BestSearchResult> {
+        let last_reported_at = self.last_reported_at?;
+        if now - last_reported_at < self.min_interval {
+            return None;
+        }
+        let result = self.pending.take()?;
+        Some(self.report(result, now))
+    }
+
+    /// The held back result, whether or not its interval is up. For once the search is over
+    pub fn flush(&mut self, now: Instant) -> Option<BestSearchResult> {
+        let result = self.pending.take()?;
+        Some(self.report(result, now))
+    }
+
+    /// How many results were replaced before they could be reported
+    pub fn suppressed_updates(&self) -> usize {
+        self.suppressed_updates
+    }
+
+    fn report(&mut self, mut result: BestSearchResult, now: Instant) -> BestSearchResult {
+        self.last_reported_at = Some(now);
+        result.suppressed_updates = self.suppressed_updates;
+        result
+    }
+}
+
+/// Failures of the engine thread itself, rather than of a particular search
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EngineError {
+    /// The engine thread panicked, and its results can't be trusted
+    ThreadPanicked,
+    /// The search panicked or returned an error, and was abandoned. The engine thread survived,
+    /// and takes new searches. Details are available from `take_search_failure`
+    SearchFailed,
+    /// The engine thread has shut down, and no longer takes requests
+    ChannelClosed,
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::ThreadPanicked => write!(f, "Engine thread panicked"),
+            EngineError::SearchFailed => write!(f, "Search failed"),
+            EngineError::ChannelClosed => write!(f, "Engine thread has shut down"),
+        }
+    }
+}
+
+/// A search that panicked or returned an error, caught by the engine thread so that it could
+/// keep running
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchFailure {
+    pub message: String,
+    /// The position being searched
+    pub state: FullGameState,
+}
+
+impl std::fmt::Display for SearchFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Search failed in {}: {}",
+            game_state_to_fen(&self.state),
+            self.message
+        )
+    }
+}
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum EngineThreadState {
@@ -29,22 +289,38 @@ pub enum EngineThreadState {
 
 #[derive(Clone)]
 pub enum EngineThreadMessage {
-    Compute(EngineThreadExecution),
+    Compute(Box<EngineThreadExecution>),
     End,
 }
 
 #[derive(Clone)]
 pub struct EngineThreadExecution {
     state: FullGameState,
+    started_at: Instant,
     stop_flag: Arc<AtomicBool>,
+    /// Set by the engine thread once it's done with this search, and won't report any more moves
+    finished_flag: Arc<AtomicBool>,
+    /// Set along with `finished_flag` if the search failed
+    failed_flag: Arc<AtomicBool>,
     best_move: Arc<Mutex<Option<BestSearchResult>>>,
     new_best_move_sender: Sender<BestSearchResult>,
     each_move_callback: Option<EachMoveCallback>,
+    heartbeat_callback: Option<HeartbeatCallback>,
+    failure_callback: Option<SearchFailureCallback>,
+    best_move_min_interval: Duration,
+    limits: SearchLimits,
+    params: SearchParams,
+    root_move_filter: Option<RootMoveFilter>,
+    /// None for filtered searches, whose best moves only hold among the allowed root moves
+    analysis_cache: Option<Arc<Mutex<AnalysisCache>>>,
+    /// How long `state` had already been analyzed when this search started
+    analyzed_duration: Duration,
 }
 
 pub struct EngineThreadCtx {
     worker_state: Arc<Mutex<EngineThreadState>>,
     receiver: Receiver<EngineThreadMessage>,
+    failure_sender: Sender<SearchFailure>,
 }
 
 pub struct EngineThreadWrapper {
@@ -53,6 +329,14 @@ pub struct EngineThreadWrapper {
     active_execution: Option<EngineThreadExecution>,
     request_sender: Sender<EngineThreadMessage>,
     worker_state: Arc<Mutex<EngineThreadState>>,
+    /// Kept to hand to the engine thread again if it has to be restarted
+    failure_sender: Sender<SearchFailure>,
+    failure_receiver: Receiver<SearchFailure>,
+    search_params: SearchParams,
+    heartbeat_callback: Option<HeartbeatCallback>,
+    failure_callback: Option<SearchFailureCallback>,
+    best_move_min_interval: Duration,
+    analysis_cache: Arc<Mutex<AnalysisCache>>,
 }
 
 #[allow(dead_code)]
@@ -68,23 +352,70 @@ type DatagenStaticSearchTerminator = OrSearchTerminator<
 
 impl EngineThreadWrapper {
     pub fn new() -> Self {
+        let (failure_sender, failure_receiver) = channel::<SearchFailure>();
+        let (request_sender, worker_state, thread) =
+            Self::spawn_worker_thread(failure_sender.clone());
+
+        EngineThreadWrapper {
+            is_ending: false,
+            request_sender,
+            active_execution: None,
+            worker_state,
+            failure_sender,
+            failure_receiver,
+            search_params: Default::default(),
+            heartbeat_callback: None,
+            failure_callback: None,
+            best_move_min_interval: DEFAULT_BEST_MOVE_MIN_INTERVAL,
+            analysis_cache: Default::default(),
+            thread: Some(thread),
+        }
+    }
+
+    fn spawn_worker_thread(
+        failure_sender: Sender<SearchFailure>,
+    ) -> (
+        Sender<EngineThreadMessage>,
+        Arc<Mutex<EngineThreadState>>,
+        JoinHandle<()>,
+    ) {
         let (sender, receiver) = channel::<EngineThreadMessage>();
         let worker_state = Arc::new(Mutex::new(EngineThreadState::Starting));
 
         let engine_thread_ctx = EngineThreadCtx {
             worker_state: worker_state.clone(),
             receiver,
+            failure_sender,
         };
+        let thread = thread::spawn(move || {
+            Self::worker_thread_loop(engine_thread_ctx);
+        });
+        (sender, worker_state, thread)
+    }
 
-        EngineThreadWrapper {
-            is_ending: false,
-            request_sender: sender,
-            active_execution: None,
-            worker_state: worker_state.clone(),
-            thread: Some(thread::spawn(move || {
-                Self::worker_thread_loop(engine_thread_ctx);
-            })),
+    /// Searches catch their own panics, but if the engine thread died anyway, start a new one
+    /// rather than leaving the wrapper unusable
+    fn restart_thread_if_dead(&mut self) {
+        if !self.thread.as_ref().is_none_or(JoinHandle::is_finished) {
+            return;
+        }
+
+        log::warn!("Engine thread died, restarting it");
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
         }
+        let (request_sender, worker_state, thread) =
+            Self::spawn_worker_thread(self.failure_sender.clone());
+        self.request_sender = request_sender;
+        self.worker_state = worker_state;
+        self.thread = Some(thread);
+        self.active_execution = None;
+    }
+
+    /// The oldest search failure that hasn't been taken yet. Searches that fail are abandoned, and
+    /// don't stop the engine from taking new ones
+    pub fn take_search_failure(&mut self) -> Option<SearchFailure> {
+        self.failure_receiver.try_recv().ok()
     }
 
     fn worker_thread_loop(engine_thread_ctx: EngineThreadCtx) {
@@ -97,15 +428,69 @@ impl EngineThreadWrapper {
             }
 
             let Ok(msg) = engine_thread_ctx.receiver.recv() else {
-                eprintln!("EngineThread receiver received error");
+                log::error!("EngineThread receiver received error");
                 thread::sleep(Duration::from_millis(100));
                 continue;
             };
 
             match msg {
                 EngineThreadMessage::Compute(request) => {
+                    let request = *request;
                     let best_move_mutex = request.best_move;
                     let best_move_sender = request.new_best_move_sender;
+                    let heartbeat_state = request.state.clone();
+                    #[cfg(feature = "verify_engine_moves")]
+                    let root_state = request.state.clone();
+                    let analysis_cache = request.analysis_cache.clone();
+                    let cache_state = request.state.clone();
+                    let analyzed_duration = request.analyzed_duration;
+                    let started_at = request.started_at;
+
+                    // Only the callers' view is rate limited. The best move kept for `stop` and
+                    // the analysis cache always have the latest result
+                    let coalescer = Rc::new(RefCell::new(BestMoveCoalescer::new(
+                        request.best_move_min_interval,
+                    )));
+                    let each_move_callback = request.each_move_callback.clone();
+                    let report_best_move = Rc::new(move |new_best_move: BestSearchResult| {
+                        if let Some(each_move_callback) = &each_move_callback {
+                            each_move_callback(new_best_move.clone());
+                        }
+                        let _ = best_move_sender.send(new_best_move);
+                    });
+
+                    let on_heartbeat = request.heartbeat_callback.map(|heartbeat_callback| {
+                        move |heartbeat: SearchHeartbeat| {
+                            heartbeat_callback(&heartbeat_state, heartbeat)
+                        }
+                    });
+                    // Held back moves are polled for while searching, so that they're reported
+                    // once their interval is up instead of waiting for the next best move, which
+                    // can be a whole iteration away
+                    let on_poll = {
+                        let coalescer = coalescer.clone();
+                        let report_best_move = report_best_move.clone();
+                        (!request.best_move_min_interval.is_zero()).then_some(move || {
+                            let due = coalescer.borrow_mut().take_due(Instant::now());
+                            if let Some(new_best_move) = due {
+                                report_best_move(new_best_move);
+                            }
+                        })
+                    };
+                    let publish_best_move = {
+                        let coalescer = coalescer.clone();
+                        let report_best_move = report_best_move.clone();
+                        Rc::new(move |new_best_move: BestSearchResult| {
+                            *best_move_mutex.lock().unwrap() = Some(new_best_move.clone());
+
+                            let offered =
+                                coalescer.borrow_mut().offer(new_best_move, Instant::now());
+                            if let Some(new_best_move) = offered {
+                                report_best_move(new_best_move);
+                            }
+                        })
+                    };
+                    let search_publish_best_move = publish_best_move.clone();
                     {
                         let mut worker_state = engine_thread_ctx.worker_state.lock().unwrap();
                         *worker_state = EngineThreadState::Running;
@@ -114,27 +499,111 @@ impl EngineThreadWrapper {
                     let mut search_state = SearchContext {
                         tt: &mut transposition_table,
                         new_best_move_callback: Box::new(move |new_best_move: BestSearchResult| {
-                            let mut best_move_handle = best_move_mutex.lock().unwrap();
-                            *best_move_handle = Some(new_best_move.clone());
+                            #[cfg(feature = "verify_engine_moves")]
+                            crate::consistency_checker::verify_engine_move(
+                                &root_state,
+                                &new_best_move.child_state,
+                            );
 
-                            if let Some(each_move_callback) = &request.each_move_callback {
-                                each_move_callback(new_best_move.clone());
+                            if let Some(analysis_cache) = &analysis_cache {
+                                analysis_cache.lock().unwrap().record(
+                                    &cache_state,
+                                    new_best_move.clone(),
+                                    analyzed_duration + started_at.elapsed(),
+                                );
                             }
 
-                            let _ = best_move_sender.send(new_best_move.clone());
+                            search_publish_best_move(new_best_move);
                         }),
-                        terminator: StopFlagSearchTerminator::new(request.stop_flag.clone()),
+                        terminator: PollSearchTerminator::new(
+                            HeartbeatSearchTerminator::new(
+                                TimeManager::new(
+                                    OrSearchTerminator::new(
+                                        StopFlagSearchTerminator::new(request.stop_flag.clone()),
+                                        DynamicSearchTerminator::new(request.limits),
+                                    ),
+                                    request.limits.time_control,
+                                )
+                                .with_phase(request.state.phase()),
+                                HEARTBEAT_INTERVAL,
+                                on_heartbeat,
+                            ),
+                            request.best_move_min_interval,
+                            on_poll,
+                        ),
+                        params: request.params,
+                        root_move_filter: request.root_move_filter,
+                        observer: NoopSearchObserver,
                     };
 
-                    negamax_search(
-                        &mut search_state,
-                        request.state.clone(),
-                        get_past_win_search_terminator(),
-                    );
+                    log::debug!("search started: {}", request.state);
+                    let search_result = catch_unwind(AssertUnwindSafe(|| {
+                        // Replayed rather than recorded again, since it's already in the cache
+                        let cached_result = request
+                            .analysis_cache
+                            .as_ref()
+                            .and_then(|cache| cache.lock().unwrap().saved_result(&request.state));
+                        if let Some(cached_result) = cached_result {
+                            publish_best_move(cached_result);
+                        }
+
+                        let search_result = negamax_search(
+                            &mut search_state,
+                            request.state.clone(),
+                            get_past_win_search_terminator(),
+                        );
+                        if search_result.is_ok() {
+                            let held_back = coalescer.borrow_mut().flush(Instant::now());
+                            if let Some(new_best_move) = held_back {
+                                report_best_move(new_best_move);
+                            }
+                        }
+                        search_result
+                    }));
+                    let failure_message = match search_result {
+                        Ok(Ok(finished_search)) => {
+                            log::debug!(
+                                "search finished: depth {} nodes {} in {:.3}s, {} updates suppressed",
+                                finished_search.last_fully_completed_depth,
+                                finished_search.nodes_visited,
+                                request.started_at.elapsed().as_secs_f32(),
+                                coalescer.borrow().suppressed_updates(),
+                            );
+                            None
+                        }
+                        Ok(Err(err)) => Some(err.to_string()),
+                        Err(payload) => {
+                            // The search may have been midway through writing an entry
+                            transposition_table = TranspositionTable::new();
+                            if let Some(analysis_cache) = &request.analysis_cache {
+                                analysis_cache
+                                    .lock()
+                                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                    .clear();
+                            }
+                            Some(panic_message(&*payload))
+                        }
+                    };
+                    if let Some(message) = failure_message {
+                        let search_failure = SearchFailure {
+                            message,
+                            state: request.state.clone(),
+                        };
+                        log::error!("{}", search_failure);
+                        request.failed_flag.store(true, Ordering::Relaxed);
+                        let _ = engine_thread_ctx
+                            .failure_sender
+                            .send(search_failure.clone());
+                        if let Some(failure_callback) = &request.failure_callback {
+                            failure_callback(&search_failure);
+                        }
+                    }
 
                     request.stop_flag.store(true, Ordering::Relaxed);
+                    request.finished_flag.store(true, Ordering::Relaxed);
                 }
                 EngineThreadMessage::End => {
+                    log::debug!("engine thread ending");
                     break;
                 }
             }
@@ -150,8 +619,40 @@ impl EngineThreadWrapper {
         }
     }
 
+    /// Params used by all future searches. Results cached under other params are forgotten
+    pub fn set_search_params(&mut self, search_params: SearchParams) {
+        if search_params != self.search_params {
+            self.clear_analysis_cache();
+        }
+        self.search_params = search_params;
+    }
+
+    pub fn search_params(&self) -> SearchParams {
+        self.search_params
+    }
+
+    /// Heartbeat callback used by all future searches, or None to stop sending heartbeats
+    pub fn set_heartbeat_callback(&mut self, heartbeat_callback: Option<HeartbeatCallback>) {
+        self.heartbeat_callback = heartbeat_callback;
+    }
+
+    /// Failure callback used by all future searches, so that a caller that isn't polling
+    /// `take_search_failure` can find out
+    pub fn set_failure_callback(&mut self, failure_callback: Option<SearchFailureCallback>) {
+        self.failure_callback = failure_callback;
+    }
+
+    /// The least time between best moves reported for future searches, other than moves that end
+    /// a search. Zero reports every move. See `BestMoveCoalescer`
+    pub fn set_best_move_min_interval(&mut self, min_interval: Duration) {
+        self.best_move_min_interval = min_interval;
+    }
+
     pub fn spin_for_pending_state(&self) {
         loop {
+            if self.thread.as_ref().is_none_or(JoinHandle::is_finished) {
+                break;
+            }
             {
                 let worker_state = self.worker_state.lock().unwrap().clone();
                 if worker_state == EngineThreadState::Pending {
@@ -162,10 +663,25 @@ impl EngineThreadWrapper {
         }
     }
 
+    /// Starts a background search, which runs until `stop` is called or any of `limits` is reached.
+    /// If the position was searched before this session, the deepest result found for it is
+    /// reported first, with trigger Saved
     pub fn start_search(
         &mut self,
         state: &FullGameState,
         each_move_callback: Option<EachMoveCallback>,
+        limits: Option<SearchLimits>,
+    ) -> Result<Receiver<BestSearchResult>, String> {
+        self.start_filtered_search(state, each_move_callback, limits, None)
+    }
+
+    /// Like `start_search`, but only searches the root moves allowed by `root_move_filter`
+    pub fn start_filtered_search(
+        &mut self,
+        state: &FullGameState,
+        each_move_callback: Option<EachMoveCallback>,
+        limits: Option<SearchLimits>,
+        root_move_filter: Option<RootMoveFilter>,
     ) -> Result<Receiver<BestSearchResult>, String> {
         if let Err(err) = state.validation_err() {
             return Err(err);
@@ -175,24 +691,47 @@ impl EngineThreadWrapper {
             panic!("Tried to start a search when engine thread is already ended");
         }
 
+        self.restart_thread_if_dead();
         self.clear_active_state_if_already_stopped();
 
         if self.active_execution.is_some() {
             return Err("A search is already in progress".to_owned());
         }
 
+        let (analysis_cache, analyzed_duration) = match root_move_filter {
+            Some(_) => (None, Duration::ZERO),
+            None => {
+                let mut analysis_cache = self.analysis_cache.lock().unwrap();
+                analysis_cache.check_matchup_policy(&matchup_policy());
+                let analyzed_duration = analysis_cache.analyzed_duration(state);
+                (Some(self.analysis_cache.clone()), analyzed_duration)
+            }
+        };
         let (sender, receiver) = channel();
 
         let compute_request = EngineThreadExecution {
             state: state.clone(),
+            started_at: Instant::now(),
             stop_flag: Arc::new(AtomicBool::new(false)),
+            finished_flag: Arc::new(AtomicBool::new(false)),
+            failed_flag: Arc::new(AtomicBool::new(false)),
             best_move: Arc::new(Mutex::new(None)),
             new_best_move_sender: sender,
             each_move_callback,
+            heartbeat_callback: self.heartbeat_callback.clone(),
+            failure_callback: self.failure_callback.clone(),
+            best_move_min_interval: self.best_move_min_interval,
+            limits: limits.unwrap_or_default(),
+            params: self.search_params,
+            root_move_filter,
+            analysis_cache,
+            analyzed_duration,
         };
 
         self.request_sender
-            .send(EngineThreadMessage::Compute(compute_request.clone()))
+            .send(EngineThreadMessage::Compute(Box::new(
+                compute_request.clone(),
+            )))
             .map_err(|err| format!("{}", err))?;
 
         self.active_execution = Some(compute_request.clone());
@@ -200,17 +739,76 @@ impl EngineThreadWrapper {
         Ok(receiver)
     }
 
-    pub fn stop(&mut self) -> Result<BestSearchResult, String> {
-        if let Some(active_execution) = &self.active_execution.take() {
-            active_execution.stop_flag.store(true, Ordering::Relaxed);
+    /// Analysis mode: keeps searching `state` across calls. This is a no-op if `state` is already
+    /// being searched with the same limits, otherwise any other search is stopped and a new one
+    /// is started.
+    /// Results are recorded against the position they were found for, like those of every
+    /// unfiltered search, and can be read back with `cached_analysis`
+    pub fn ensure_searching(
+        &mut self,
+        state: &FullGameState,
+        each_move_callback: Option<EachMoveCallback>,
+        limits: Option<SearchLimits>,
+    ) -> Result<(), String> {
+        self.clear_active_state_if_already_stopped();
 
-            let result_state = active_execution.best_move.lock().unwrap();
-            result_state
-                .clone()
-                .ok_or_else(|| format!("Search returned no results. {:?}", active_execution.state))
-        } else {
-            Err("Attempted to stop, but no active execution".to_owned())
+        if let Some(active_execution) = &self.active_execution {
+            if &active_execution.state == state
+                && active_execution.limits == limits.unwrap_or_default()
+            {
+                return Ok(());
+            }
+            let _ = self.stop();
+        }
+
+        self.start_search(state, each_move_callback, limits)?;
+        Ok(())
+    }
+
+    /// Results found so far for `state`, if it was searched recently
+    pub fn cached_analysis(&self, state: &FullGameState) -> Vec<TimedSearchResult> {
+        self.analysis_cache.lock().unwrap().results(state)
+    }
+
+    /// Forgets every cached result, so that no search reports a move found before now
+    pub fn clear_analysis_cache(&self) {
+        self.analysis_cache.lock().unwrap().clear();
+    }
+
+    /// The position being searched and how long it's been searched for, if a search was started
+    /// and hasn't been stopped yet
+    pub fn active_search(&self) -> Option<(&FullGameState, Duration)> {
+        self.active_execution
+            .as_ref()
+            .map(|execution| (&execution.state, execution.started_at.elapsed()))
+    }
+
+    /// Stops the current search, and waits for its final best move. Returns Ok(None) if no search
+    /// was running
+    pub fn stop(&mut self) -> Result<Option<BestSearchResult>, EngineError> {
+        let Some(active_execution) = self.active_execution.take() else {
+            return match self.thread {
+                Some(_) => Ok(None),
+                None => Err(EngineError::ChannelClosed),
+            };
+        };
+        active_execution.stop_flag.store(true, Ordering::Relaxed);
+
+        while !active_execution.finished_flag.load(Ordering::Relaxed) {
+            if self.thread.as_ref().is_none_or(JoinHandle::is_finished) {
+                return Err(EngineError::ThreadPanicked);
+            }
+            thread::sleep(Duration::from_millis(1));
         }
+        if active_execution.failed_flag.load(Ordering::Relaxed) {
+            return Err(EngineError::SearchFailed);
+        }
+
+        let best_move = active_execution
+            .best_move
+            .lock()
+            .map_err(|_| EngineError::ThreadPanicked)?;
+        Ok(best_move.clone())
     }
 
     pub fn end(&mut self) {
@@ -220,12 +818,36 @@ impl EngineThreadWrapper {
         self.thread.take().map(JoinHandle::join).unwrap().unwrap();
     }
 
+    /// Scores every root move of `state` with its own fixed size search, spread over `threads`
+    /// threads. Runs alongside any search already in progress, rather than on the engine thread
+    pub fn score_root_moves(
+        &self,
+        state: &FullGameState,
+        nodes_per_move: usize,
+        threads: usize,
+    ) -> Result<Vec<(GenericMove, Heuristic)>, String> {
+        state.validation_err()?;
+        if state.get_winner().is_some() {
+            return Err("Cannot score moves from a terminal state".to_owned());
+        }
+        Ok(score_root_moves_parallel(state, nodes_per_move, threads))
+    }
+
     pub fn search_for_duration(
         &mut self,
         state: &FullGameState,
         duration_secs: f32,
     ) -> Result<BestSearchResult, String> {
-        let _message_receiver = self.start_search(state, None)?;
+        self.search_for_duration_with_callback(state, duration_secs, None)
+    }
+
+    pub fn search_for_duration_with_callback(
+        &mut self,
+        state: &FullGameState,
+        duration_secs: f32,
+        each_move_callback: Option<EachMoveCallback>,
+    ) -> Result<BestSearchResult, String> {
+        let _message_receiver = self.start_search(state, each_move_callback, None)?;
 
         let start_time = Instant::now();
         let end_time = start_time + Duration::from_secs_f32(duration_secs);
@@ -239,11 +861,11 @@ impl EngineThreadWrapper {
             };
 
             if is_over_on_time || is_already_over {
-                let result = self.stop();
+                let result = self.stop().map_err(|err| err.to_string())?;
 
                 self.spin_for_pending_state();
 
-                return result;
+                return result.ok_or_else(|| format!("Search returned no results. {:?}", state));
             }
 
             let time_till_end = end_time - Instant::now();
@@ -254,3 +876,248 @@ impl EngineThreadWrapper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use crate::{fen::parse_fen, player::Player, square::Square};
+
+    use super::*;
+
+    fn result_at_depth(state: &FullGameState, depth: usize) -> BestSearchResult {
+        BestSearchResult::new(
+            state.clone(),
+            GenericMove::NULL_MOVE,
+            false,
+            0,
+            depth,
+            depth * 100,
+            BestMoveTrigger::StopFlag,
+        )
+    }
+
+    #[test]
+    fn test_analysis_cache_keeps_deepest_results() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut cache = AnalysisCache::default();
+
+        cache.record(&state, result_at_depth(&state, 5), Duration::from_secs(1));
+        // A restarted search reports shallow results first
+        cache.record(&state, result_at_depth(&state, 1), Duration::from_secs(2));
+        cache.record(&state, result_at_depth(&state, 6), Duration::from_secs(3));
+
+        let depths: Vec<usize> = cache
+            .get(&state)
+            .unwrap()
+            .results
+            .iter()
+            .map(|(r, _)| r.depth)
+            .collect();
+        assert_eq!(depths, vec![5, 6]);
+        assert_eq!(cache.analyzed_duration(&state), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_analysis_cache_evicts_least_recently_used() {
+        let first = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut cache = AnalysisCache::default();
+        cache.record(&first, result_at_depth(&first, 1), Duration::ZERO);
+
+        let mut states = vec![];
+        for i in 1..ANALYSIS_CACHE_SIZE {
+            let mut state = first.clone();
+            state.board.set_god_data(Player::One, i as u32);
+            cache.record(&state, result_at_depth(&state, 1), Duration::ZERO);
+            states.push(state);
+        }
+
+        // Touching the first state keeps it, so the second one is evicted instead
+        assert!(cache.get(&first).is_some());
+        let mut extra = first.clone();
+        extra.board.dome_up(Square::C3);
+        cache.record(&extra, result_at_depth(&extra, 1), Duration::ZERO);
+
+        assert!(cache.get(&first).is_some());
+        assert!(cache.get(&states[0]).is_none());
+        assert!(cache.get(&states[1]).is_some());
+        assert!(cache.get(&extra).is_some());
+    }
+
+    #[test]
+    fn test_analysis_cache_saved_result() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut cache = AnalysisCache::default();
+        cache.record(&state, result_at_depth(&state, 5), Duration::from_secs(1));
+        cache.record(&state, result_at_depth(&state, 3), Duration::from_secs(2));
+
+        let mut revisited = state.clone();
+        revisited.ply = 40;
+        let saved = cache.saved_result(&revisited).unwrap();
+        assert_eq!(saved.depth, 5);
+        assert_eq!(saved.trigger, BestMoveTrigger::Saved);
+        assert_eq!(saved.child_state.ply, 41);
+
+        // Komi comes from the matchup policy, so results found under another one are stale
+        let policy = Arc::new(MatchupPolicy::default());
+        cache.check_matchup_policy(&policy);
+        assert!(cache.is_empty());
+        cache.record(&state, result_at_depth(&state, 5), Duration::ZERO);
+        cache.check_matchup_policy(&policy);
+        assert_eq!(cache.len(), 1);
+        cache.check_matchup_policy(&Arc::new(MatchupPolicy::default()));
+        assert!(cache.saved_result(&state).is_none());
+    }
+
+    #[test]
+    fn test_search_params_change_clears_cached_results() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut engine = EngineThreadWrapper::new();
+        engine.search_for_duration(&state, 0.05).unwrap();
+        assert!(!engine.cached_analysis(&state).is_empty());
+
+        // Setting the same params keeps what's known
+        engine.set_search_params(engine.search_params());
+        assert!(!engine.cached_analysis(&state).is_empty());
+
+        let mut params = engine.search_params();
+        params.set("extend_double_threats", "false").unwrap();
+        engine.set_search_params(params);
+        assert!(engine.cached_analysis(&state).is_empty());
+        engine.end();
+    }
+
+    #[test]
+    fn test_best_move_coalescer_keeps_latest_result() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let improvement = |depth| {
+            let mut result = result_at_depth(&state, depth);
+            result.trigger = BestMoveTrigger::Improvement;
+            result
+        };
+        let start = Instant::now();
+        let at_millis = |millis| start + Duration::from_millis(millis);
+        let mut coalescer = BestMoveCoalescer::new(Duration::from_millis(50));
+
+        assert_eq!(
+            coalescer
+                .offer(improvement(1), at_millis(0))
+                .map(|r| r.depth),
+            Some(1)
+        );
+        assert!(coalescer.offer(improvement(2), at_millis(10)).is_none());
+        assert!(coalescer.offer(improvement(3), at_millis(20)).is_none());
+        assert!(coalescer.take_due(at_millis(40)).is_none());
+
+        // The latest held back result wins once the interval is up
+        let due = coalescer.take_due(at_millis(60)).unwrap();
+        assert_eq!((due.depth, due.suppressed_updates), (3, 1));
+        assert!(coalescer.take_due(at_millis(200)).is_none());
+
+        // Results that end the search are never held back
+        assert!(coalescer.offer(improvement(4), at_millis(70)).is_none());
+        let stopped = coalescer
+            .offer(result_at_depth(&state, 4), at_millis(75))
+            .unwrap();
+        assert_eq!(stopped.trigger, BestMoveTrigger::StopFlag);
+        assert_eq!(stopped.suppressed_updates, 2);
+        assert!(coalescer.flush(at_millis(80)).is_none());
+
+        assert!(coalescer.offer(improvement(5), at_millis(90)).is_none());
+        assert_eq!(coalescer.flush(at_millis(91)).map(|r| r.depth), Some(5));
+        assert_eq!(coalescer.suppressed_updates(), 2);
+    }
+
+    #[test]
+    fn test_revisited_position_reports_cached_result_first() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut engine = EngineThreadWrapper::new();
+        let first_result = engine.search_for_duration(&state, 0.05).unwrap();
+
+        let (sender, receiver) = channel();
+        let sender = Mutex::new(sender);
+        let callback: EachMoveCallback = Arc::new(move |result| {
+            let _ = sender.lock().unwrap().send(result);
+        });
+        engine.start_search(&state, Some(callback), None).unwrap();
+        let _ = engine.stop();
+
+        let cached = receiver.recv().unwrap();
+        assert_eq!(cached.trigger, BestMoveTrigger::Saved);
+        assert!(cached.depth >= first_result.depth);
+        assert!(
+            state
+                .get_next_states()
+                .iter()
+                .any(|child| child == &cached.child_state)
+        );
+        engine.end();
+    }
+
+    #[test]
+    fn test_stop_idle_engine() {
+        let mut engine = EngineThreadWrapper::new();
+        assert_eq!(engine.stop().map(|r| r.is_none()), Ok(true));
+        engine.end();
+        assert_eq!(engine.stop().err(), Some(EngineError::ChannelClosed));
+    }
+
+    #[test]
+    fn test_stop_mid_search() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut engine = EngineThreadWrapper::new();
+        engine.start_search(&state, None, None).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        assert!(engine.active_search().is_some());
+        let best_move = engine.stop().unwrap().unwrap();
+        assert!(
+            state
+                .get_next_states()
+                .iter()
+                .any(|child| child == &best_move.child_state)
+        );
+        assert!(engine.active_search().is_none());
+
+        // The search is already over, so there's nothing left to stop
+        assert_eq!(engine.stop().map(|r| r.is_none()), Ok(true));
+        engine.end();
+    }
+
+    #[test]
+    fn test_recovers_from_search_failure() {
+        let poisoned = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut engine = EngineThreadWrapper::new();
+        let failure_count = Arc::new(AtomicUsize::new(0));
+        let callback_count = failure_count.clone();
+        engine.set_failure_callback(Some(Arc::new(move |_| {
+            callback_count.fetch_add(1, Ordering::Relaxed);
+        })));
+
+        // Panics from inside the search, as the root search does on inconsistent states
+        let poison: EachMoveCallback = Arc::new(|_| panic!("poisoned search"));
+        engine.start_search(&poisoned, Some(poison), None).unwrap();
+
+        let search_failure = loop {
+            if let Some(search_failure) = engine.take_search_failure() {
+                break search_failure;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(search_failure.message, "poisoned search");
+        assert_eq!(search_failure.state, poisoned);
+        assert_eq!(engine.stop().err(), Some(EngineError::SearchFailed));
+        assert_eq!(failure_count.load(Ordering::Relaxed), 1);
+
+        let state = parse_fen("0000000000000000000000000/2/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let best_move = engine.search_for_duration(&state, 0.05).unwrap();
+        assert!(
+            state
+                .get_next_states()
+                .iter()
+                .any(|child| child == &best_move.child_state)
+        );
+        assert!(engine.take_search_failure().is_none());
+        engine.end();
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    bitboard::{BitBoard, NEIGHBOR_MAP},
+    board::FullGameState,
+    gods::EvalPolicy,
+    nnue::LabeledAccumulator,
+    player::Player,
+    search::Heuristic,
+};
+
+/// Bonus for a worker standing at each height
+const WORKER_HEIGHT_SCORES: [Heuristic; 4] = [0, 40, 120, 120];
+
+/// Bonus for a worker at each height with an open square one level up next to it. From level 2
+/// that's a threat to win
+const CLIMB_SCORES: [Heuristic; 3] = [10, 30, 100];
+
+/// Each worker is worth far more than any positional term, for gods with extra or removable
+/// workers
+const WORKER_SCORE: Heuristic = 150;
+
+/// Bonus for a worker on each square. Central squares have more neighbours to move and build on
+#[rustfmt::skip]
+const CENTER_SCORES: [Heuristic; 25] = [
+    0,  0,  0,  0,  0,
+    0, 10, 10, 10,  0,
+    0, 10, 30, 10,  0,
+    0, 10, 10, 10,  0,
+    0,  0,  0,  0,  0,
+];
+
+/// Move counts are capped so that gods with huge move lists, like Triton, aren't dominated by
+/// mobility
+const MOBILITY_CAP: usize = 40;
+const MOBILITY_SCORE: Heuristic = 3;
+
+fn player_score(state: &FullGameState, player: Player) -> Heuristic {
+    let board = &state.board;
+    let god = state.gods[player as usize];
+    let workers = board.workers[player as usize] & BitBoard::MAIN_SECTION_MASK;
+
+    let open_squares = !(board.workers[0] | board.workers[1]) & BitBoard::MAIN_SECTION_MASK;
+
+    let mut score = 0;
+    for square in workers {
+        let height = board.get_height(square).min(3);
+        score += WORKER_SCORE;
+        score += WORKER_HEIGHT_SCORES[height];
+        score += CENTER_SCORES[square as usize];
+
+        if height < 3 {
+            let one_up = board.height_map[height] & !board.height_map[height + 1];
+            if (NEIGHBOR_MAP[square as usize] & one_up & open_squares).is_not_empty() {
+                score += CLIMB_SCORES[height];
+            }
+        }
+    }
+
+    let mobility = god
+        .get_moves_for_search(state, player)
+        .len()
+        .min(MOBILITY_CAP);
+    score += mobility as Heuristic * MOBILITY_SCORE;
+    score += god.get_eval_modifier(board.god_data[player as usize]);
+
+    score
+}
+
+/// Handcrafted evaluation, from the perspective of the player to move. Used for gods whose
+/// `EvalPolicy` is `UseClassical`, where no NNUE model gives sensible scores
+pub fn classical_eval(state: &FullGameState) -> Heuristic {
+    let current_player = state.board.current_player;
+    player_score(state, current_player) - player_score(state, !current_player)
+}
+
+/// Whether either god's `EvalPolicy` asks for the classical evaluation
+pub fn uses_classical_eval(state: &FullGameState) -> bool {
+    state
+        .gods
+        .iter()
+        .any(|god| god.eval_policy == EvalPolicy::UseClassical)
+}
+
+/// Static evaluation of `state`, from the perspective of the player to move. `nnue_acc` must
+/// already be up to date with `state`
+pub fn evaluate_state(state: &FullGameState, nnue_acc: &LabeledAccumulator) -> Heuristic {
+    if uses_classical_eval(state) {
+        classical_eval(state)
+    } else {
+        nnue_acc.evaluate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fen::parse_fen, gods::GodName};
+
+    use super::*;
+
+    #[test]
+    fn test_classical_eval_is_symmetric() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:C3,B2/mortal:A5,E1").unwrap();
+        let mut flipped = state.clone();
+        flipped.flip_current_player();
+
+        assert!(classical_eval(&state) > 0);
+        assert_eq!(classical_eval(&state), -classical_eval(&flipped));
+    }
+
+    #[test]
+    fn test_classical_eval_prefers_height_and_workers() {
+        let low = parse_fen("0000000000000000000000000/1/mortal:A5,E1/mortal:A1,E5").unwrap();
+        let high = parse_fen("1000000000000000000000000/1/mortal:A5,E1/mortal:A1,E5").unwrap();
+        assert!(classical_eval(&high) > classical_eval(&low));
+
+        let fewer_workers =
+            parse_fen("0000000000000000000000000/1/mortal:A5/mortal:A1,E5").unwrap();
+        assert!(classical_eval(&fewer_workers) < classical_eval(&low));
+    }
+
+    #[test]
+    fn test_eval_policy_dispatch() {
+        let mortal = parse_fen("0000000000000000000000000/1/mortal:C3,B2/mortal:A5,E1").unwrap();
+        assert!(!uses_classical_eval(&mortal));
+
+        // Triton plays better on the Mortal model than on the classical eval
+        let triton = parse_fen("0000000000000000000000000/1/mortal:C3,B2/triton:A5,E1").unwrap();
+        assert_eq!(
+            GodName::Triton.to_power().eval_policy,
+            EvalPolicy::UseNnueAs(GodName::Mortal)
+        );
+        assert!(!uses_classical_eval(&triton));
+
+        let mut nnue_acc = LabeledAccumulator::new_from_scratch(
+            &triton.board,
+            triton.gods[0].model_god_name,
+            triton.gods[1].model_god_name,
+        );
+        nnue_acc.replace_from_state(&triton);
+        assert_eq!(evaluate_state(&triton, &nnue_acc), nnue_acc.evaluate());
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    board::FullGameState, hashing::HashType, player::Player, pretty_board::get_acting_player,
+};
+
+const FILE_MAGIC: &[u8; 4] = b"SXP1";
+
+/// How often a move was played, and how often the player who played it went on to win
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MoveTally {
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl MoveTally {
+    pub fn win_rate(&self) -> f32 {
+        self.wins as f32 / self.games.max(1) as f32
+    }
+
+    fn add(&mut self, other: MoveTally) {
+        self.games += other.games;
+        self.wins += other.wins;
+    }
+}
+
+/// A move from the queried position that appears in the index
+#[derive(Clone, Debug)]
+pub struct ExplorerMove {
+    pub action_str: String,
+    pub next_state: FullGameState,
+    pub tally: MoveTally,
+}
+
+/// Opening statistics from recorded games. Positions and moves are keyed by their canonical
+/// hash, so transpositions and symmetric positions share their counts
+#[derive(Clone, Debug, Default)]
+pub struct ExplorerIndex {
+    max_ply: usize,
+    positions: HashMap<HashType, HashMap<HashType, MoveTally>>,
+}
+
+impl ExplorerIndex {
+    pub fn new(max_ply: usize) -> Self {
+        Self {
+            max_ply,
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Positions from this ply onwards weren't indexed
+    pub fn max_ply(&self) -> usize {
+        self.max_ply
+    }
+
+    /// Number of indexed positions, not counting symmetric copies
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Records that `child` was played from `parent` in a game won by `winner`. Callers are
+    /// responsible for skipping positions past `max_ply`
+    pub fn record(&mut self, parent: &FullGameState, child: &FullGameState, winner: Player) {
+        let Ok(mover) = get_acting_player(parent) else {
+            return;
+        };
+        let tally = self
+            .positions
+            .entry(parent.canonical_hash())
+            .or_default()
+            .entry(child.canonical_hash())
+            .or_default();
+        tally.add(MoveTally {
+            games: 1,
+            wins: (mover == winner) as u32,
+        });
+    }
+
+    pub fn merge(&mut self, other: ExplorerIndex) {
+        self.max_ply = self.max_ply.max(other.max_ply);
+        for (parent, moves) in other.positions {
+            let position = self.positions.entry(parent).or_default();
+            for (child, tally) in moves {
+                position.entry(child).or_default().add(tally);
+            }
+        }
+    }
+
+    /// Every indexed move from `state`, most played first. Moves that reach the same position up
+    /// to symmetry are only listed once
+    pub fn query(&self, state: &FullGameState) -> Vec<ExplorerMove> {
+        let Some(moves) = self.positions.get(&state.canonical_hash()) else {
+            return Vec::new();
+        };
+
+        let mut seen = HashSet::new();
+        let mut result: Vec<ExplorerMove> = state
+            .describe_moves_with::<false>()
+            .into_iter()
+            .filter_map(|description| {
+                let key = description.next_state.canonical_hash();
+                let tally = *moves.get(&key)?;
+                seen.insert(key).then_some(ExplorerMove {
+                    action_str: description.action_str,
+                    next_state: description.next_state,
+                    tally,
+                })
+            })
+            .collect();
+        result.sort_by_key(|explorer_move| Reverse(explorer_move.tally.games));
+        result
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<(), String> {
+        let mut entries: Vec<(HashType, HashType, MoveTally)> = self
+            .positions
+            .iter()
+            .flat_map(|(parent, moves)| {
+                moves.iter().map(|(child, tally)| (*parent, *child, *tally))
+            })
+            .collect();
+        entries.sort_by_key(|(parent, child, _)| (*parent, *child));
+
+        let mut write = |bytes: &[u8]| writer.write_all(bytes).map_err(|e| e.to_string());
+        write(FILE_MAGIC)?;
+        write(&(self.max_ply as u32).to_le_bytes())?;
+        write(&(entries.len() as u64).to_le_bytes())?;
+        for (parent, child, tally) in entries {
+            write(&parent.to_le_bytes())?;
+            write(&child.to_le_bytes())?;
+            write(&tally.games.to_le_bytes())?;
+            write(&tally.wins.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, String> {
+        let mut read = |bytes: &mut [u8]| reader.read_exact(bytes).map_err(|e| e.to_string());
+
+        let mut magic = [0; 4];
+        read(&mut magic)?;
+        if &magic != FILE_MAGIC {
+            return Err("Not an explorer index file".to_owned());
+        }
+
+        let mut max_ply = [0; 4];
+        read(&mut max_ply)?;
+        let mut len = [0; 8];
+        read(&mut len)?;
+
+        let mut result = Self::new(u32::from_le_bytes(max_ply) as usize);
+        for _ in 0..u64::from_le_bytes(len) {
+            let mut parent = [0; 8];
+            read(&mut parent)?;
+            let mut child = [0; 8];
+            read(&mut child)?;
+            let mut games = [0; 4];
+            read(&mut games)?;
+            let mut wins = [0; 4];
+            read(&mut wins)?;
+
+            result
+                .positions
+                .entry(HashType::from_le_bytes(parent))
+                .or_default()
+                .insert(
+                    HashType::from_le_bytes(child),
+                    MoveTally {
+                        games: u32::from_le_bytes(games),
+                        wins: u32::from_le_bytes(wins),
+                    },
+                );
+        }
+        Ok(result)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let mut writer = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+        self.write_to(&mut writer)?;
+        writer.flush().map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::read_from(&mut BufReader::new(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Symmetry, fen::parse_fen};
+
+    use super::*;
+
+    // A height breaks the symmetry of the workers, so every move leads somewhere different
+    const START_STRING: &str = "0100000000000000000000000/1/mortal:B2,A1/mortal:D4,C3";
+
+    fn child_by_move(state: &FullGameState, move_idx: usize) -> FullGameState {
+        state.get_next_states()[move_idx].clone()
+    }
+
+    #[test]
+    fn test_explorer_query_merges_symmetric_positions() {
+        let state = parse_fen(START_STRING).unwrap();
+        let child = child_by_move(&state, 0);
+        let other_child = child_by_move(&state, 1);
+
+        let mut index = ExplorerIndex::new(8);
+        index.record(&state, &child, Player::One);
+        index.record(&state, &other_child, Player::Two);
+        // The same game, seen in a mirrored position
+        let mirrored = state.transform(Symmetry::MirrorHorizontal);
+        let mirrored_child = child.transform(Symmetry::MirrorHorizontal);
+        index.record(&mirrored, &mirrored_child, Player::Two);
+
+        assert_eq!(index.len(), 1);
+        let moves = index.query(&state);
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0].next_state, child);
+        assert_eq!(moves[0].tally, MoveTally { games: 2, wins: 1 });
+        assert_eq!(moves[1].next_state, other_child);
+        assert_eq!(moves[1].tally, MoveTally { games: 1, wins: 0 });
+
+        assert_eq!(index.query(&mirrored)[0].tally.games, 2);
+        assert!(index.query(&child).is_empty());
+    }
+
+    #[test]
+    fn test_explorer_round_trip() {
+        let state = parse_fen(START_STRING).unwrap();
+        let child = child_by_move(&state, 0);
+
+        let mut index = ExplorerIndex::new(6);
+        index.record(&state, &child, Player::One);
+        let mut other = ExplorerIndex::new(8);
+        other.record(&state, &child, Player::One);
+        index.merge(other);
+
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        let read = ExplorerIndex::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(read.max_ply(), 8);
+        assert_eq!(read.query(&state)[0].tally, MoveTally { games: 2, wins: 2 });
+        assert!(ExplorerIndex::read_from(&mut &b"nope"[..]).is_err());
+    }
+}
@@ -1,10 +1,10 @@
-use std::str::FromStr;
+use std::{str::FromStr, sync::LazyLock};
 
 use regex::Regex;
 
 use crate::{
     bitboard::{BitBoard, NUM_SQUARES},
-    board::{BoardState, FullGameState, GodData},
+    board::{BoardState, FullGameState, GodData, PositionValidation},
     gods::{ALL_GODS_BY_ID, GodName},
     matchup::Matchup,
     player::Player,
@@ -62,9 +62,28 @@ pub fn game_state_to_fen(state: &FullGameState) -> String {
     result += "/";
     result += &player_section_string(state, Player::Two);
 
+    // Only recorded when it can't be inferred, so that FENs from before the ply existed, and
+    // most positions from normal play, are unchanged
+    if state.ply != board.inferred_ply() {
+        result += "/";
+        result += &state.ply.to_string();
+    }
+
     result
 }
 
+/// Longer FENs are rejected before any parsing. The longest real FENs are a few hundred bytes, and
+/// callers like the web client pass on whatever they're given
+pub const MAX_FEN_LENGTH: usize = 1024;
+
+/// Largest ply count a FEN can record. Far longer than any game, and small enough that the plies
+/// after it can still be counted
+pub const MAX_FEN_PLY: usize = 100_000;
+
+/// A god name with optional markers, then optional god data in brackets
+static CHARACTER_SECTION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([^\[\]]*)(\[([^\[\]]*)\])?$").unwrap());
+
 struct CharacterFen {
     #[allow(dead_code)]
     god: GodName,
@@ -87,13 +106,14 @@ fn parse_character_section(s: &str) -> Result<CharacterFen, String> {
         return Err(CHARACTER_FEN_WARNING.to_owned());
     }
 
-    let re = Regex::new(r"([^\[]*)(\[(.*)\])?").map_err(|e| format!("{}", e))?;
-    let god_name_captures = re.captures(colon_splits[0]).ok_or_else(|| {
-        format!(
-            "Failed to parse god name from section: {}. {}",
-            colon_splits[0], CHARACTER_FEN_WARNING
-        )
-    })?;
+    let god_name_captures = CHARACTER_SECTION_RE
+        .captures(colon_splits[0])
+        .ok_or_else(|| {
+            format!(
+                "Failed to parse god name from section: {}. {}",
+                colon_splits[0], CHARACTER_FEN_WARNING
+            )
+        })?;
     let god_string = god_name_captures.get(1).unwrap().as_str().to_owned();
     let is_won = god_string.contains("#");
     let is_up_limited = god_string.contains("-");
@@ -121,6 +141,9 @@ fn parse_character_section(s: &str) -> Result<CharacterFen, String> {
             continue;
         }
         let pos: Square = worker_pos_string.parse()?;
+        if worker_locations.contains(&pos) {
+            return Err(format!("Worker square {} is listed twice", pos));
+        }
         worker_locations.push(pos);
     }
 
@@ -158,9 +181,28 @@ fn parse_heights(board: &mut BoardState, height_str: &str) -> Result<(), String>
 }
 
 pub fn parse_fen(s: &str) -> Result<FullGameState, String> {
+    let state = parse_fen_unvalidated(s)?;
+    state.validation_err()?;
+    Ok(state)
+}
+
+/// Like `parse_fen`, but returns any position the FEN describes, even ones that fail validation.
+/// Check `FullGameState::position_validation` before showing or searching it
+pub fn parse_fen_unvalidated(s: &str) -> Result<FullGameState, String> {
+    if s.len() > MAX_FEN_LENGTH {
+        return Err(format!(
+            "FEN is too long: {} bytes, where the limit is {}",
+            s.len(),
+            MAX_FEN_LENGTH
+        ));
+    }
+
     let sections: Vec<&str> = s.split('/').collect();
-    if sections.len() != 4 {
-        return Err("Input string must have exactly 4 sections separated by '/'".to_string());
+    if sections.len() != 4 && sections.len() != 5 {
+        return Err(
+            "Input string must have 4 sections separated by '/', plus an optional ply count"
+                .to_string(),
+        );
     }
 
     let mut result = BoardState::default();
@@ -214,24 +256,51 @@ pub fn parse_fen(s: &str) -> Result<FullGameState, String> {
     // result.flip_worker_can_climb(Player::One, p1_section.is_movement_blocked);
     // result.flip_worker_can_climb(Player::Two, p2_section.is_movement_blocked);
 
+    let ply = match sections.get(4) {
+        Some(ply_str) => ply_str
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|ply| *ply <= MAX_FEN_PLY)
+            .ok_or_else(|| {
+                format!(
+                    "Ply count must be a number from 0 to {}. Found: {}",
+                    MAX_FEN_PLY, ply_str
+                )
+            })?,
+        None => result.inferred_ply(),
+    };
+
     let mut full_result = FullGameState {
         board: result,
         gods: [
             &ALL_GODS_BY_ID[p1_section.god as usize],
             &ALL_GODS_BY_ID[p2_section.god as usize],
         ],
+        ply,
     };
 
     full_result.recalculate_internals();
-    full_result.validation_err()?;
 
     Ok(full_result)
 }
 
+/// Parses and validates a FEN, reporting every tier of validation instead of just the first
+/// failure. FENs that don't parse are unrepresentable
+pub fn validate_fen(s: &str) -> PositionValidation {
+    match parse_fen_unvalidated(s) {
+        Ok(state) => state.position_validation(),
+        Err(err) => PositionValidation::unrepresentable(err),
+    }
+}
+
 /// Extracts just the matchup (god names) from a FEN string without doing a full parse.
 /// Avoids all the expensive work (height bitboards, regex, zobrist hashing, validation).
 pub fn extract_matchup_from_fen(fen: &str) -> Option<Matchup> {
-    let mut slash_iter = fen.splitn(4, '/');
+    if fen.len() > MAX_FEN_LENGTH {
+        return None;
+    }
+    let mut slash_iter = fen.split('/');
     slash_iter.next()?; // heights
     slash_iter.next()?; // player
     let god1_section = slash_iter.next()?;
@@ -252,10 +321,125 @@ fn extract_god_name_from_fen_section(section: &str) -> Option<GodName> {
 
 #[cfg(test)]
 mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
     use crate::random_utils::GameStateFuzzer;
 
     use super::*;
 
+    /// Pieces of FEN syntax, and things that look like it, for mutating valid FENs
+    const FEN_FRAGMENTS: &[&str] = &[
+        "/", ":", ",", "[", "]", "#", "-", "^", "|", " ", "0", "4", "5", "9", "A1", "E5", "F6",
+        "Z9", "a1", "[]", "[[", "]]", "[^]", "::", ",,", "//", "-1", "é", "\u{0}", "mortal",
+        "athena", "🏛",
+    ];
+
+    /// A FEN with a few random edits, most of which make it invalid in some small way
+    fn mutated_fen(rng: &mut StdRng, fen: &str) -> String {
+        let mut chars: Vec<char> = fen.chars().collect();
+        for _ in 0..rng.random_range(1..=3) {
+            let idx = rng.random_range(0..=chars.len());
+            match rng.random_range(0..7) {
+                0 => {
+                    let end = rng.random_range(idx..=chars.len());
+                    chars.drain(idx..end);
+                }
+                1 => {
+                    let fragment = FEN_FRAGMENTS.choose(rng).unwrap();
+                    chars.splice(idx..idx, fragment.chars());
+                }
+                2 => chars.truncate(idx),
+                3 => {
+                    let god: &str = ALL_GODS_BY_ID.choose(rng).unwrap().god_name.into();
+                    let data: String = (0..rng.random_range(0..6))
+                        .map(|_| *b"0123456789ABCDEabcde,|^-x:#[]".choose(rng).unwrap() as char)
+                        .collect();
+                    chars.splice(idx..idx, format!("{}[{}]", god, data).chars());
+                }
+                4 => {
+                    let number = rng.random::<u64>() >> rng.random_range(0..64);
+                    chars.splice(idx..idx, number.to_string().chars());
+                }
+                5 => {
+                    let start = rng.random_range(0..=idx);
+                    let copied: Vec<char> = chars[start..idx].to_vec();
+                    chars.splice(idx..idx, copied);
+                }
+                _ => {
+                    if idx < chars.len() {
+                        chars[idx] = *FEN_FRAGMENTS
+                            .choose(rng)
+                            .unwrap()
+                            .chars()
+                            .collect::<Vec<_>>()
+                            .choose(rng)
+                            .unwrap();
+                    }
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn test_fuzz_parse_fen_never_panics() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for state in GameStateFuzzer::new(300) {
+            let fen = game_state_to_fen(&state);
+            for _ in 0..20 {
+                let fuzzed = mutated_fen(&mut rng, &fen);
+                let result = std::panic::catch_unwind(|| {
+                    let _ = extract_matchup_from_fen(&fuzzed);
+                    if let Ok(state) = parse_fen_unvalidated(&fuzzed) {
+                        let _ = state.position_validation();
+                        let _ = state.god_data_items();
+                    }
+                    if let Ok(state) = parse_fen(&fuzzed) {
+                        assert_eq!(state.representation_err(), Ok(()));
+                        assert_eq!(parse_fen(&game_state_to_fen(&state)), Ok(state.clone()));
+                        if state.get_winner().is_none() {
+                            let _ = state.get_next_states();
+                        }
+                    }
+                });
+                assert!(result.is_ok(), "Panicked on {:?}", fuzzed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fen_pathological_inputs() {
+        let cases = [
+            // Overflowed the ply count of the next move
+            "0000000000000000000000000/1/mortal:A1,B2/mortal:C3,D4/18446744073709551615",
+            // Overflowed the build token count on Morpheus' next move
+            "0000000000000000000000000/1/morpheus[4294967295]:A1,B2/mortal:C3,D4",
+            // Rendering asserts that squares only hold one worker
+            "0000000000000000000000000/1/mortal:A1,B2/mortal:A1,D4",
+            "0000000000000000000000000/1/mortal:A1,A1/mortal:C3,D4",
+            // Anything after an unclosed bracket used to be ignored
+            "0000000000000000000000000/1/mortal[:A1,B2/mortal:C3,D4",
+            "0000000000000000000000000/1/athena[^]]:A1,B2/mortal:C3,D4",
+        ];
+        for fen in cases {
+            assert!(parse_fen(fen).is_err(), "{}", fen);
+        }
+
+        let long_fen = format!(
+            "0000000000000000000000000/1/mortal:A1,B2/mortal:C3,D4{}",
+            ",A1".repeat(MAX_FEN_LENGTH)
+        );
+        assert!(parse_fen(&long_fen).unwrap_err().contains("too long"));
+        assert_eq!(extract_matchup_from_fen(&long_fen), None);
+        assert!(!validate_fen(&long_fen).representable);
+
+        let max_ply = format!(
+            "0000000000000000000000000/1/mortal:A1,B2/mortal:C3,D4/{}",
+            MAX_FEN_PLY
+        );
+        assert_eq!(parse_fen(&max_ply).unwrap().ply, MAX_FEN_PLY);
+    }
+
     #[test]
     fn test_fen_athena_backcompat() {
         let res = parse_fen("0000000000000000000000000/1/-mortal:B3,D3/athena:C2,C4");
@@ -296,6 +480,24 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn test_validate_fen() {
+        let valid = validate_fen("0000000000000000000000000/1/mortal:A1,B2/mortal:C3,D4");
+        assert!(valid.representable && valid.playable);
+        assert!(valid.issues.is_empty());
+
+        // Displayable, but placed out of order
+        let out_of_order = validate_fen("0000000000000000000000000/1/mortal/mortal:A1,B2");
+        assert!(out_of_order.representable);
+        assert!(!out_of_order.playable);
+        assert_eq!(out_of_order.issues.len(), 1);
+        assert!(parse_fen_unvalidated("0000000000000000000000000/1/mortal/mortal:A1,B2").is_ok());
+
+        let garbage = validate_fen("not a fen");
+        assert!(!garbage.representable && !garbage.playable);
+        assert_eq!(garbage.issues.len(), 1);
+    }
+
     #[test]
     fn test_fen_datas() {
         let res = parse_fen("0000000000000000000000000/1/athena[^]:B3,D3/mortal:C2,C4");
@@ -322,7 +524,64 @@ mod tests {
                 state, rebuilt_state,
                 "State mismatch after string conversion"
             );
+            assert_eq!(
+                state.ply, rebuilt_state.ply,
+                "Ply mismatch for {}",
+                state_string
+            );
+        }
+    }
+
+    #[test]
+    fn test_fen_ply_inferred_when_absent() {
+        let state = parse_fen("1000020000000000000000000/1/mortal:B3,D3/mortal:C4,C2").unwrap();
+        assert_eq!(state.ply, 5);
+        assert_eq!(
+            game_state_to_fen(&state),
+            "1000020000000000000000000/1/mortal:B3,D3/mortal:C4,C2"
+        );
+
+        let placing = parse_fen("0000000000000000000000000/2/mortal:B3,D3/mortal").unwrap();
+        assert_eq!(placing.ply, 1);
+    }
+
+    #[test]
+    fn test_fen_ply_round_trip() {
+        let fen = "1000020000000000000000000/1/mortal:B3,D3/mortal:C4,C2/12";
+        let state = parse_fen(fen).unwrap();
+        assert_eq!(state.ply, 12);
+        assert_eq!(game_state_to_fen(&state), fen);
+        assert_eq!(
+            state,
+            parse_fen("1000020000000000000000000/1/mortal:B3,D3/mortal:C4,C2").unwrap(),
+            "Ply isn't part of the position"
+        );
+        assert_eq!(
+            extract_matchup_from_fen("0000000000000000000000000/1/mortal/pan/3"),
+            Some(Matchup::new(GodName::Mortal, GodName::Pan))
+        );
+
+        assert!(parse_fen("0000000000000000000000000/1/mortal/mortal/x").is_err());
+        assert!(parse_fen("0000000000000000000000000/1/mortal/mortal/1/2").is_err());
+    }
+
+    #[test]
+    fn test_ply_follows_moves() {
+        let mut state = parse_fen("0000000000000000000000000/1/mortal/demeter").unwrap();
+        assert_eq!(state.ply, 0);
+
+        // Demeter's double builds make the inferred ply run ahead, so the FEN has to record it
+        for expected_ply in 1..=6 {
+            state = state
+                .get_all_next_states_with_actions()
+                .into_iter()
+                .map(|(child, _)| child)
+                .last()
+                .unwrap();
+            assert_eq!(state.ply, expected_ply);
         }
+        let rebuilt = parse_fen(&game_state_to_fen(&state)).unwrap();
+        assert_eq!(rebuilt.ply, 6, "{}", game_state_to_fen(&state));
     }
 
     #[test]
@@ -18,7 +18,12 @@ use crate::{
 use const_for::const_for;
 use counted_array::counted_array;
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumString, IntoStaticStr};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use strum::{Display, IntoStaticStr};
+
+#[cfg(feature = "verify_hash")]
+use crate::hashing::HashDeltaCheck;
 
 pub(crate) mod achilles;
 pub(crate) mod aeolus;
@@ -38,6 +43,7 @@ pub(crate) mod charon_v2;
 pub(crate) mod chronus;
 pub(crate) mod clio;
 pub(crate) mod demeter;
+pub(crate) mod descriptions;
 pub(crate) mod eros;
 pub(crate) mod europa;
 pub mod generic;
@@ -73,26 +79,15 @@ pub(crate) mod scylla;
 pub(crate) mod selene;
 pub(crate) mod stymphalians;
 pub(crate) mod theseus;
+pub(crate) mod triton;
 pub(crate) mod urania;
 pub(crate) mod zeus;
 
+pub use descriptions::{god_description, god_implementation_notes};
+
 pub type StaticGod = &'static GodPower;
 
-#[derive(
-    Clone,
-    Copy,
-    PartialEq,
-    Eq,
-    Hash,
-    Debug,
-    Display,
-    Serialize,
-    Deserialize,
-    EnumString,
-    IntoStaticStr,
-    PartialOrd,
-    Ord,
-)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, IntoStaticStr, PartialOrd, Ord)]
 #[strum(serialize_all = "lowercase")]
 pub enum GodName {
     Mortal = 0,
@@ -148,10 +143,24 @@ pub enum GodName {
     Jason = 50,
     Achilles = 51,
     Stymphalians = 52,
+    Triton = 53,
 }
 
-pub const WIP_GODS: [GodName; 0] = [];
-// counted_array!(pub const WIP_GODS: [GodName; _] = []);
+// pub const WIP_GODS: [GodName; 0] = [];
+counted_array!(pub const WIP_GODS: [GodName; _] = [GodName::Triton]);
+
+/// How well the engine plays a god
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+#[serde(rename_all = "snake_case")]
+pub enum GodSupport {
+    /// Move generation and a trained NNUE model
+    Full,
+    /// Positions are evaluated as if the god were another one, which has a model
+    EvalFallback(GodName),
+    /// Move generation works, but the engine doesn't know how to evaluate the god's positions
+    Wip,
+}
 
 impl GodName {
     pub const fn to_power(&self) -> StaticGod {
@@ -161,6 +170,31 @@ impl GodName {
     pub const fn is_equal(self, other: GodName) -> bool {
         self as usize == other as usize
     }
+
+    /// Every god, in id order
+    pub fn all() -> impl Iterator<Item = GodName> {
+        ALL_GODS_BY_ID.iter().map(|god| god.god_name)
+    }
+
+    /// Every god that isn't in WIP_GODS, in id order
+    pub fn all_non_wip() -> impl Iterator<Item = GodName> {
+        Self::all().filter(|god| !WIP_GODS.contains(god))
+    }
+
+    pub fn support_level(self) -> GodSupport {
+        if WIP_GODS.contains(&self) {
+            return GodSupport::Wip;
+        }
+        match self.to_power().model_god_name {
+            model_god_name if model_god_name == self => GodSupport::Full,
+            model_god_name => GodSupport::EvalFallback(model_god_name),
+        }
+    }
+}
+
+/// The support level of every god, for clients that need to warn about weak engine play
+pub fn god_support_levels() -> BTreeMap<GodName, GodSupport> {
+    GodName::all().map(|god| (god, god.support_level())).collect()
 }
 
 impl AsRef<str> for GodName {
@@ -169,6 +203,96 @@ impl AsRef<str> for GodName {
     }
 }
 
+/// Names this far from every god name are too different to be worth suggesting
+const MAX_GOD_NAME_SUGGESTION_DISTANCE: usize = 3;
+
+/// Returned when a string doesn't name any god
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GodNameParseError {
+    pub input: String,
+    /// The closest god name, when the input looks like a typo of it
+    pub suggestion: Option<GodName>,
+}
+
+impl std::fmt::Display for GodNameParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown god '{}'", self.input)?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ". Did you mean '{}'?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for GodNameParseError {}
+
+/// Lowercases the name and drops separators, so that "Apollo V2", "ApolloV2" and "apollo_v2" all
+/// match the FEN spelling "apollov2"
+fn normalize_god_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev_row[j] + (a_char != *b_char) as usize;
+            row[j + 1] = substitution.min(prev_row[j + 1] + 1).min(row[j] + 1);
+        }
+        prev_row = row;
+    }
+    prev_row[b.len()]
+}
+
+impl FromStr for GodName {
+    type Err = GodNameParseError;
+
+    /// Case insensitive, accepting both the FEN spelling and the enum spelling
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = normalize_god_name(s.trim());
+        if let Some(god) = Self::all().find(|god| god.as_ref() == normalized) {
+            return Ok(god);
+        }
+
+        let suggestion = Self::all()
+            .map(|god| (edit_distance(&normalized, god.as_ref()), god))
+            .min_by_key(|(distance, _)| *distance)
+            .filter(|(distance, _)| *distance <= MAX_GOD_NAME_SUGGESTION_DISTANCE)
+            .map(|(_, god)| god);
+
+        Err(GodNameParseError {
+            input: s.to_owned(),
+            suggestion,
+        })
+    }
+}
+
+/// Serialized with the same lowercase spelling as FEN
+impl Serialize for GodName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
+impl<'de> Deserialize<'de> for GodName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        GodName::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 pub trait ResultsMapper<T>: Clone {
     fn new() -> Self;
     fn add_action(&mut self, partial_action: PartialAction);
@@ -283,11 +407,17 @@ pub struct GameStateWithAction {
 }
 
 impl GameStateWithAction {
-    pub fn new(board_state_with_action: BoardStateWithAction, p1: GodName, p2: GodName) -> Self {
+    pub fn new(
+        board_state_with_action: BoardStateWithAction,
+        p1: GodName,
+        p2: GodName,
+        ply: usize,
+    ) -> Self {
         GameStateWithAction {
             state: FullGameState {
                 board: board_state_with_action.result_state,
                 gods: [p1.to_power(), p2.to_power()],
+                ply,
             },
             actions: board_state_with_action.actions,
         }
@@ -306,9 +436,110 @@ fn _default_parse_god_data(fen: &str) -> Result<GodData, String> {
 fn _default_stringify_god_data(_data: GodData) -> Option<String> {
     None
 }
-pub(super) type PrettyStringifyGodDataFn = fn(&BoardState, Player) -> Option<String>;
-fn _default_pretty_stringify_god_data(_board: &BoardState, _player: Player) -> Option<String> {
-    None
+/// One piece of god specific state, typed so that frontends can render it richly
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum GodDataItem {
+    Flag {
+        name: String,
+        value: bool,
+    },
+    /// A count of tokens or uses. `max` is None for counts without a fixed limit
+    Counter {
+        name: String,
+        value: u32,
+        max: Option<u32>,
+    },
+    Squares {
+        name: String,
+        bitboard: BitBoard,
+    },
+    /// State that doesn't fit the other shapes, such as a direction
+    Text {
+        name: String,
+        value: String,
+    },
+}
+
+impl GodDataItem {
+    pub fn flag(name: &str, value: bool) -> Self {
+        GodDataItem::Flag {
+            name: name.to_owned(),
+            value,
+        }
+    }
+
+    pub fn counter(name: &str, value: u32, max: Option<u32>) -> Self {
+        GodDataItem::Counter {
+            name: name.to_owned(),
+            value,
+            max,
+        }
+    }
+
+    pub fn squares(name: &str, bitboard: BitBoard) -> Self {
+        GodDataItem::Squares {
+            name: name.to_owned(),
+            bitboard,
+        }
+    }
+
+    pub fn text(name: &str, value: String) -> Self {
+        GodDataItem::Text {
+            name: name.to_owned(),
+            value,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            GodDataItem::Flag { name, .. }
+            | GodDataItem::Counter { name, .. }
+            | GodDataItem::Squares { name, .. }
+            | GodDataItem::Text { name, .. } => name,
+        }
+    }
+}
+
+impl std::fmt::Display for GodDataItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GodDataItem::Flag { name, value } => {
+                write!(f, "{}: {}", name, if *value { "yes" } else { "no" })
+            }
+            GodDataItem::Counter {
+                name,
+                value,
+                max: Some(max),
+            } => write!(f, "{}: {}/{}", name, value, max),
+            GodDataItem::Counter {
+                name,
+                value,
+                max: None,
+            } => write!(f, "{}: {}", name, value),
+            GodDataItem::Squares { name, bitboard } if bitboard.is_empty() => {
+                write!(f, "{}: none", name)
+            }
+            GodDataItem::Squares { name, bitboard } => write!(
+                f,
+                "{}: {}",
+                name,
+                bitboard
+                    .all_squares()
+                    .iter()
+                    .map(Square::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            GodDataItem::Text { name, value } => write!(f, "{}: {}", name, value),
+        }
+    }
+}
+
+pub(super) type GodDataItemsFn = fn(&BoardState, Player) -> Vec<GodDataItem>;
+fn _default_god_data_items(_board: &BoardState, _player: Player) -> Vec<GodDataItem> {
+    Vec::new()
 }
 
 pub(super) type GetWindIdxFn = fn(&BoardState, Player) -> usize;
@@ -428,6 +659,12 @@ fn _default_get_data_mask_fn(_board: &BoardState, _player: Player) -> BitBoard {
     BitBoard::EMPTY
 }
 
+/// The god data for `player`, repaired or cleared where an edit to the board made it invalid
+pub(super) type ReconcileGodDataFn = fn(&BoardState, Player) -> GodData;
+fn _default_reconcile_god_data(board: &BoardState, player: Player) -> GodData {
+    board.god_data[player as usize]
+}
+
 pub(super) type FlipGodDataFn = fn(GodData) -> GodData;
 fn _default_flip_god_data(god_data: GodData) -> GodData {
     god_data
@@ -435,9 +672,33 @@ fn _default_flip_god_data(god_data: GodData) -> GodData {
 
 pub(super) type EvalScoreModifierFn = fn(GodData) -> Heuristic;
 
+/// How positions involving a god are evaluated at the leaves of a search
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvalPolicy {
+    /// The god's own NNUE model
+    UseNnue,
+    /// The NNUE model of another god, for gods without a trained model that play similarly
+    UseNnueAs(GodName),
+    /// `eval::classical_eval`, for gods that no NNUE model describes well
+    UseClassical,
+}
+
+/// The workers a god can legally have on the board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorkerConstraints {
+    /// Workers placed at the start of the game. Fewer is still legal after workers are lost
+    pub starting_workers: usize,
+    /// The most workers the god can ever have
+    pub max_workers: usize,
+    /// Whether one of the god's workers is marked as female in its god data
+    pub has_female_worker: bool,
+}
+
 pub struct GodPower {
     pub god_name: GodName,
+    /// The god whose NNUE features this god uses. Follows `eval_policy`
     pub model_god_name: GodName,
+    pub eval_policy: EvalPolicy,
 
     // Move Fns
     pub _get_all_moves: MoveGeneratorFn,
@@ -458,6 +719,7 @@ pub struct GodPower {
     _flip_god_data_horizontal: FlipGodDataFn,
     _flip_god_data_vertical: FlipGodDataFn,
     _flip_god_data_transpose: FlipGodDataFn,
+    _reconcile_god_data: ReconcileGodDataFn,
 
     _eval_score_modifier_fn: Option<EvalScoreModifierFn>,
 
@@ -479,7 +741,7 @@ pub struct GodPower {
     _parse_god_data: ParseGodDataFn,
     _stringify_god_data: StringifyGodDataFn,
 
-    _pretty_stringify_god_data: PrettyStringifyGodDataFn,
+    _god_data_items: GodDataItemsFn,
 
     _placement_fns: GodPlacementFns,
 
@@ -489,6 +751,7 @@ pub struct GodPower {
     pub is_persephone: bool,
     pub is_preventing_down: bool,
     pub is_placement_priority: bool,
+    max_workers: usize,
 
     _get_wind_idx: GetWindIdxFn,
 
@@ -623,7 +886,13 @@ impl GodPower {
         player: Player,
         other_god: StaticGod,
     ) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(board);
+
         (self._placement_fns._make_placement_move)(action, board, player, other_god);
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(board, self.god_name.into());
     }
 
     pub fn make_placement_move_on_clone(
@@ -639,6 +908,7 @@ impl GodPower {
             player,
             new_state.gods[!player as usize],
         );
+        new_state.ply += 1;
         new_state
     }
 
@@ -671,18 +941,38 @@ impl GodPower {
         }
     }
 
+    pub fn worker_constraints(&self) -> WorkerConstraints {
+        WorkerConstraints {
+            starting_workers: self.get_num_workers(),
+            max_workers: self.max_workers,
+            has_female_worker: self.placement_type == PlacementType::FemaleWorker,
+        }
+    }
+
     pub fn get_blocker_board(&self, board: &BoardState, action: GenericMove) -> BitBoard {
         (self._get_blocker_board)(board, action)
     }
 
     pub fn make_move(&self, board: &mut BoardState, other_god: StaticGod, action: GenericMove) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(board);
+
         (self._make_move)(board, action, other_god);
         board.flip_current_player();
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(board, self.god_name.into());
     }
 
     pub fn make_passing_move(&self, board: &mut BoardState) {
+        #[cfg(feature = "verify_hash")]
+        let hash_check = HashDeltaCheck::new(board);
+
         (self._make_passing_move)(board);
         board.flip_current_player();
+
+        #[cfg(feature = "verify_hash")]
+        hash_check.verify(board, self.god_name.into());
     }
 
     pub fn stringify_move(&self, action: GenericMove) -> String {
@@ -703,7 +993,7 @@ impl GodPower {
         (self._get_history_hash)(board, action)
     }
 
-    pub(super) fn get_eval_modifier(&self, _god_data: GodData) -> Heuristic {
+    pub(crate) fn get_eval_modifier(&self, _god_data: GodData) -> Heuristic {
         0
         // if let Some(modifier_fn) = self._eval_score_modifier_fn {
         //     modifier_fn(god_data)
@@ -724,6 +1014,10 @@ impl GodPower {
         (self._get_female_worker_mask)(board, player)
     }
 
+    pub(super) fn reconcile_god_data(&self, board: &BoardState, player: Player) -> GodData {
+        (self._reconcile_god_data)(board, player)
+    }
+
     pub(super) fn parse_god_data(&self, fen: &str) -> Result<GodData, String> {
         (self._parse_god_data)(fen)
     }
@@ -732,8 +1026,20 @@ impl GodPower {
         (self._stringify_god_data)(god_data)
     }
 
+    /// God specific state for `player`, for frontends to render
+    pub fn god_data_items(&self, board: &BoardState, player: Player) -> Vec<GodDataItem> {
+        (self._god_data_items)(board, player)
+    }
+
+    /// God specific state for display, along with any implementation notes for this god
     pub fn pretty_stringify_god_data(&self, board: &BoardState, player: Player) -> Option<String> {
-        (self._pretty_stringify_god_data)(board, player)
+        let lines: Vec<String> = self
+            .god_data_items(board, player)
+            .iter()
+            .map(GodDataItem::to_string)
+            .chain(god_implementation_notes(self.god_name).map(str::to_owned))
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
     }
 
     pub fn get_flip_horizontal_god_data(&self, god_data: GodData) -> GodData {
@@ -823,6 +1129,7 @@ counted_array!(pub const ALL_GODS_BY_ID: [GodPower; _] = [
     jason::build_jason(),
     achilles::build_achilles(),
     stymphalians::build_stymphalians(),
+    triton::build_triton(),
 ]);
 
 pub const fn god_name_to_nnue_size(god_name: GodName) -> usize {
@@ -958,6 +1265,7 @@ const fn god_power(
     GodPower {
         god_name: name,
         model_god_name: name,
+        eval_policy: EvalPolicy::UseNnue,
         _get_all_moves: movers._get_all_moves,
         _get_moves_for_search: movers._get_moves_for_search,
         _get_wins: movers._get_wins,
@@ -973,6 +1281,7 @@ const fn god_power(
         _flip_god_data_horizontal: _default_flip_god_data,
         _flip_god_data_vertical: _default_flip_god_data,
         _flip_god_data_transpose: _default_flip_god_data,
+        _reconcile_god_data: _default_reconcile_god_data,
 
         _eval_score_modifier_fn: None,
         _get_wind_idx: _default_get_wind_idx,
@@ -988,7 +1297,7 @@ const fn god_power(
         _parse_god_data: _default_parse_god_data,
         _stringify_god_data: _default_stringify_god_data,
 
-        _pretty_stringify_god_data: _default_pretty_stringify_god_data,
+        _god_data_items: _default_god_data_items,
 
         placement_type: PlacementType::Normal,
         _placement_fns: placement_to_fns::<StandardWorkerPlacement>(),
@@ -999,6 +1308,7 @@ const fn god_power(
         is_persephone: false,
         is_preventing_down: false,
         is_placement_priority: false,
+        max_workers: 4,
 
         hash1,
         hash2,
@@ -1007,8 +1317,18 @@ const fn god_power(
 
 impl GodPower {
     #[allow(dead_code)]
-    pub(super) const fn with_nnue_god_name(mut self, name: GodName) -> Self {
-        self.model_god_name = name;
+    pub(super) const fn with_nnue_god_name(self, name: GodName) -> Self {
+        self.with_eval_policy(EvalPolicy::UseNnueAs(name))
+    }
+
+    pub(super) const fn with_eval_policy(mut self, eval_policy: EvalPolicy) -> Self {
+        self.eval_policy = eval_policy;
+        self.model_god_name = match eval_policy {
+            EvalPolicy::UseNnue => self.god_name,
+            EvalPolicy::UseNnueAs(name) => name,
+            // Search still keeps an accumulator up to date, so it needs a model that exists
+            EvalPolicy::UseClassical => GodName::Mortal,
+        };
         self
     }
 
@@ -1054,6 +1374,11 @@ impl GodPower {
         self
     }
 
+    pub(super) const fn with_max_workers(mut self, max_workers: usize) -> Self {
+        self.max_workers = max_workers;
+        self
+    }
+
     pub(super) const fn with_moveable_worker_filter(
         mut self,
         moveable_worker_filter_fn: MovableWorkerFilter,
@@ -1091,11 +1416,8 @@ impl GodPower {
         self
     }
 
-    pub(super) const fn with_pretty_stringify_god_data_fn(
-        mut self,
-        stringify_god_data: PrettyStringifyGodDataFn,
-    ) -> Self {
-        self._pretty_stringify_god_data = stringify_god_data;
+    pub(super) const fn with_god_data_items_fn(mut self, god_data_items: GodDataItemsFn) -> Self {
+        self._god_data_items = god_data_items;
         self
     }
 
@@ -1141,6 +1463,14 @@ impl GodPower {
         self
     }
 
+    pub(super) const fn with_reconcile_god_data_fn(
+        mut self,
+        reconcile_god_data_fn: ReconcileGodDataFn,
+    ) -> Self {
+        self._reconcile_god_data = reconcile_god_data_fn;
+        self
+    }
+
     #[allow(dead_code)]
     pub(super) const fn with_eval_score_modifier_fn(
         mut self,
@@ -1214,6 +1544,123 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_god_name_round_trips() {
+        for god in GodName::all() {
+            let name = god.to_string();
+            assert_eq!(GodName::from_str(&name), Ok(god));
+            assert_eq!(GodName::from_str(&name.to_uppercase()), Ok(god));
+            assert_eq!(GodName::from_str(&format!("{:?}", god)), Ok(god));
+
+            let json = serde_json::to_string(&god).unwrap();
+            assert_eq!(json, format!("\"{}\"", name));
+            assert_eq!(serde_json::from_str::<GodName>(&json).unwrap(), god);
+
+            let state = FullGameState::new_empty_state(god, GodName::Mortal);
+            let fen = crate::fen::game_state_to_fen(&state);
+            assert!(fen.contains(&name), "{} not in {}", name, fen);
+            assert_eq!(parse_fen(&fen).unwrap().gods[0].god_name, god);
+        }
+    }
+
+    #[test]
+    fn test_god_name_parsing() {
+        assert_eq!(GodName::from_str("Apollo V2"), Ok(GodName::ApolloV2));
+        assert_eq!(GodName::from_str(" charon_v2 "), Ok(GodName::CharonV2));
+        // Older serializations used the enum spelling
+        assert_eq!(
+            serde_json::from_str::<GodName>("\"ApolloV2\"").unwrap(),
+            GodName::ApolloV2
+        );
+
+        let err = GodName::from_str("athnea").unwrap_err();
+        assert_eq!(err.suggestion, Some(GodName::Athena));
+        assert_eq!(
+            err.to_string(),
+            "Unknown god 'athnea'. Did you mean 'athena'?"
+        );
+
+        let err = GodName::from_str("not a god").unwrap_err();
+        assert_eq!(err.suggestion, None);
+        assert_eq!(err.to_string(), "Unknown god 'not a god'");
+    }
+
+    #[test]
+    fn test_god_name_listing() {
+        let all: Vec<GodName> = GodName::all().collect();
+        assert_eq!(all.len(), ALL_GODS_BY_ID.len());
+        assert!(all.iter().enumerate().all(|(i, god)| *god as usize == i));
+
+        let non_wip: Vec<GodName> = GodName::all_non_wip().collect();
+        assert_eq!(non_wip.len(), all.len() - WIP_GODS.len());
+        assert!(WIP_GODS.iter().all(|god| !non_wip.contains(god)));
+    }
+
+    #[test]
+    fn test_god_support_levels() {
+        assert_eq!(GodName::Mortal.support_level(), GodSupport::Full);
+        for god in WIP_GODS {
+            assert_eq!(god.support_level(), GodSupport::Wip);
+        }
+
+        let levels = god_support_levels();
+        assert_eq!(levels.len(), ALL_GODS_BY_ID.len());
+        for (god, level) in &levels {
+            if let GodSupport::EvalFallback(model_god_name) = level {
+                assert_ne!(god, model_god_name);
+            }
+        }
+
+        let json = serde_json::to_string(&levels).unwrap();
+        assert!(json.contains(r#""mortal":{"type":"full"}"#));
+        let parsed: BTreeMap<GodName, GodSupport> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, levels);
+    }
+
+    #[test]
+    fn test_partial_action_serde() {
+        let cases = [
+            (
+                PartialAction::SelectWorker(Square::C3),
+                r#"{"type":"select_worker","value":"C3"}"#,
+            ),
+            (
+                PartialAction::MoveWorker(Square::A5.into()),
+                r#"{"type":"move_worker","value":{"dest":"A5","meta":null}}"#,
+            ),
+            (
+                PartialAction::new_move_with_displace(Square::B2, Square::B3, Square::B4),
+                r#"{"type":"move_worker","value":{"dest":"B2","meta":{"type":"move_enemy_worker","value":{"from":"B3","to":"B4"}}}}"#,
+            ),
+            (
+                PartialAction::new_move_with_kill(Square::D4, Square::E5),
+                r#"{"type":"move_worker","value":{"dest":"D4","meta":{"type":"kill_enemy_worker","value":{"square":"E5"}}}}"#,
+            ),
+            (
+                PartialAction::ForceOpponentWorker(Square::E1, Square::D1),
+                r#"{"type":"force_opponent_worker","value":["E1","D1"]}"#,
+            ),
+            (
+                PartialAction::SetWindDirection(Some(Direction::NE)),
+                r#"{"type":"set_wind_direction","value":"NE"}"#,
+            ),
+            (
+                PartialAction::SetWindDirection(None),
+                r#"{"type":"set_wind_direction","value":null}"#,
+            ),
+            (PartialAction::EndTurn, r#"{"type":"end_turn"}"#),
+        ];
+
+        for (action, expected) in cases {
+            let json = serde_json::to_string(&action).unwrap();
+            assert_eq!(json, expected);
+            assert_eq!(
+                serde_json::from_str::<PartialAction>(&json).unwrap(),
+                action
+            );
+        }
+    }
+
     #[test]
     fn test_god_alignment() {
         for (i, god_power) in ALL_GODS_BY_ID.iter().enumerate() {
@@ -1263,4 +1710,35 @@ mod tests {
             state.get_next_states_interactive();
         }
     }
+
+    #[test]
+    fn test_god_data_items() {
+        let state =
+            parse_fen("0100011000011300000100001/1/bia:A4,E2/clio[0|B4,B3,C3]:C4,D2").unwrap();
+        let [bia_items, clio_items] = state.god_data_items();
+        assert!(bia_items.is_empty());
+
+        let coins = BitBoard::as_mask(Square::B4)
+            | BitBoard::as_mask(Square::B3)
+            | BitBoard::as_mask(Square::C3);
+        assert_eq!(
+            clio_items,
+            vec![
+                GodDataItem::counter("Coins left", 0, Some(3)),
+                GodDataItem::squares("Coins", coins),
+            ]
+        );
+        assert_eq!(clio_items[0].to_string(), "Coins left: 0/3");
+        assert_eq!(clio_items[1].to_string(), "Coins: B4, B3, C3");
+
+        let json = serde_json::to_string(&clio_items[1]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"squares","name":"Coins","bitboard":["B4","B3","C3"]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<GodDataItem>(&json).unwrap(),
+            clio_items[1]
+        );
+    }
 }
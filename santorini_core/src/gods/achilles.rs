@@ -3,7 +3,8 @@ use crate::{
     board::{BoardState, FullGameState, GodData},
     build_god_power_movers,
     gods::{
-        FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod, build_god_power_actions,
+        FullAction, GodDataItem, GodName, GodPower, HistoryIdxHelper, StaticGod,
+        build_god_power_actions,
         generic::{
             ANY_MOVE_FILTER, GenericMove, GodMove, LOWER_POSITION_MASK, MOVE_IS_WINNING_MASK,
             MoveData, MoveGenFlags, NULL_MOVE_DATA, POSITION_WIDTH, ScoredMove,
@@ -795,11 +796,11 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     }
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
-    match board.god_data[player as usize] {
-        0 => Some(format!("Power available")),
-        _ => Some(format!("Power used")),
-    }
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
+    vec![GodDataItem::flag(
+        "Power available",
+        board.god_data[player as usize] == 0,
+    )]
 }
 
 pub const fn build_achilles() -> GodPower {
@@ -812,5 +813,5 @@ pub const fn build_achilles() -> GodPower {
     )
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
 }
@@ -4,7 +4,8 @@ use crate::{
     build_god_power_movers,
     direction::{Direction, direction_idx_to_reverse},
     gods::{
-        FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod, build_god_power_actions,
+        FullAction, GodDataItem, GodName, GodPower, HistoryIdxHelper, StaticGod,
+        build_god_power_actions,
         generic::{
             GenericMove, GodMove, LOWER_POSITION_MASK, MOVE_IS_WINNING_MASK, MoveData,
             MoveGenFlags, NULL_MOVE_DATA, POSITION_WIDTH, ScoredMove,
@@ -231,7 +232,7 @@ fn aeolus_move_gen_with_next_wind_direction<const F: MoveGenFlags, const MUST_CL
                     break;
                 }
             }
-        } else if other_god == GodName::Stymphalians {
+        } else if other_god == GodName::Stymphalians || other_god == GodName::Triton {
             // See if any key squares would be impacted by the wind. There will be false positives
             // here (ex: will always allow opposite wind directions)
             let next_direction = Direction::from_u8((next_wind_idx - 1) as u8);
@@ -407,11 +408,11 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     Some(Direction::from_u8(data as u8 - 1).to_string())
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
     let god_data = board.god_data[player as usize];
     let wind_direction_str =
         stringify_god_data(god_data).map_or("none".to_string(), |w| w.to_uppercase());
-    Some(format!("Preventing {}", wind_direction_str))
+    vec![GodDataItem::text("Preventing", wind_direction_str)]
 }
 
 fn get_wind_idx(board: &BoardState, player: Player) -> usize {
@@ -459,7 +460,7 @@ pub const fn build_aeolus() -> GodPower {
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
     .with_get_wind_idx_fn(get_wind_idx)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
     .with_flip_god_data_horizontal_fn(flip_horizontal)
     .with_flip_god_data_vertical_fn(flip_vertical)
     .with_flip_god_data_transpose_fn(flip_tranpose)
@@ -16,7 +16,7 @@ use crate::{
             is_mate_only, is_stop_on_mate, modify_prelude_for_checking_workers, push_winning_moves,
             restrict_moves_by_affinity_area, GeneratorPreludeState, WorkerStartMoveState,
         },
-        FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod,
+        FullAction, GodDataItem, GodName, GodPower, HistoryIdxHelper, StaticGod,
     },
     player::Player,
     square::Square,
@@ -681,11 +681,11 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     }
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
-    match board.god_data[player as usize] {
-        0 => Some(format!("Power available")),
-        _ => Some(format!("Power used")),
-    }
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
+    vec![GodDataItem::flag(
+        "Power available",
+        board.god_data[player as usize] == 0,
+    )]
 }
 
 pub const fn build_bellerophon() -> GodPower {
@@ -698,5 +698,5 @@ pub const fn build_bellerophon() -> GodPower {
     )
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
 }
@@ -365,6 +365,7 @@ mod tests {
         search::{
             SearchContext, WINNING_SCORE_BUFFER, get_win_reached_search_terminator, negamax_search,
         },
+        search_observer::NoopSearchObserver,
         search_terminators::DynamicMaxDepthSearchTerminator,
         square::Square,
         transposition_table::TranspositionTable,
@@ -379,12 +380,16 @@ mod tests {
             tt: &mut tt,
             new_best_move_callback: Box::new(move |_new_best_move| {}),
             terminator: DynamicMaxDepthSearchTerminator::new(4),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
         };
         let search_state = negamax_search(
             &mut search_context,
             state,
             get_win_reached_search_terminator(),
-        );
+        )
+        .unwrap();
         assert!(search_state.best_move.unwrap().score > WINNING_SCORE_BUFFER);
     }
 
@@ -429,12 +434,16 @@ mod tests {
             tt: &mut tt,
             new_best_move_callback: Box::new(move |_new_best_move| {}),
             terminator: DynamicMaxDepthSearchTerminator::new(1),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
         };
         let search_state = negamax_search(
             &mut search_context,
             state,
             get_win_reached_search_terminator(),
-        );
+        )
+        .unwrap();
         let child_board = search_state.best_move.unwrap().child_state.board;
         assert_eq!(child_board.current_player, Player::Two);
         assert!(child_board.workers[0].is_empty());
@@ -450,12 +459,16 @@ mod tests {
             tt: &mut tt,
             new_best_move_callback: Box::new(move |_new_best_move| {}),
             terminator: DynamicMaxDepthSearchTerminator::new(1),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
         };
         let search_state = negamax_search(
             &mut search_context,
             state,
             get_win_reached_search_terminator(),
-        );
+        )
+        .unwrap();
         let child_board = search_state.best_move.unwrap().child_state.board;
         assert_eq!(child_board.current_player, Player::Two);
         assert!(child_board.workers[0].is_not_empty());
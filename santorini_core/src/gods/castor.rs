@@ -1,7 +1,7 @@
 use arrayvec::ArrayVec;
 
 use crate::{
-    bitboard::{BitBoard, NEIGHBOR_MAP, PUSH_MAPPING},
+    bitboard::{BitBoard, NEIGHBOR_MAP},
     board::{BoardState, FullGameState},
     build_god_power_movers,
     gods::{
@@ -11,7 +11,7 @@ use crate::{
             MoveGenFlags, NULL_MOVE_DATA, POSITION_WIDTH, ScoredMove,
         },
         god_power,
-        harpies::basic_slide_from_unblocked,
+        harpies::{basic_slide_from_unblocked, slide_after_step},
         move_helpers::{
             GeneratorPreludeState, build_scored_move,
             get_basic_moves_from_raw_data_with_custom_blockers, get_generator_prelude_state,
@@ -113,7 +113,7 @@ impl GodMove for CastorMove {
                 board.worker_xor(player, move_mask);
 
                 if board.workers[player as usize].is_empty() {
-                    eprintln!("move made board empty: {:?}", self);
+                    log::error!("move made board empty: {:?}", self);
                 }
 
                 if self.get_is_winning() {
@@ -491,41 +491,18 @@ fn handle_must_climb_double_moves<const F: MoveGenFlags>(
 fn _harpy_slide_with_coverage(
     prelude: &GeneratorPreludeState,
     unblocked_squares: BitBoard,
-    mut from: Square,
-    mut to: Square,
+    from: Square,
+    to: Square,
 ) -> HarpyWorkerSlideState {
-    let dir = to;
-    let mut current_height = prelude.board.get_height(to);
-    let mut cover = from.to_board() | to.to_board();
-
-    loop {
-        let Some(next_spot) = PUSH_MAPPING[from as usize][to as usize] else {
-            break;
-        };
-
-        let next_mask = next_spot.to_board();
-        if (unblocked_squares & next_mask).is_empty() {
-            break;
-        }
-
-        let new_height = prelude.board.get_height(next_spot);
-        if new_height > current_height {
-            // Can't climb
-            break;
-        }
-
-        cover |= next_mask;
-
-        from = to;
-        to = next_spot;
-        current_height = new_height;
-    }
+    let to_height = prelude.board.get_height(to);
+    let (dest, height, slid_over) =
+        slide_after_step(prelude.board, from, to, to_height, !unblocked_squares);
 
     HarpyWorkerSlideState {
-        dir,
-        dest: to,
-        cover,
-        height: current_height,
+        dir: to,
+        dest,
+        cover: from.to_board() | to.to_board() | slid_over,
+        height,
     }
 }
 
@@ -1162,6 +1139,7 @@ pub const fn build_castor() -> GodPower {
         2979614850588903286,
         362356524330526493,
     )
+    .with_max_workers(2)
 }
 
 #[cfg(test)]
@@ -1,5 +1,5 @@
 use crate::{
-    bitboard::{BitBoard, NEIGHBOR_MAP},
+    bitboard::{BitBoard, NEIGHBOR_MAP, apply_mapping_to_mask},
     board::{BoardState, FullGameState},
     build_god_power_movers,
     gods::{
@@ -315,7 +315,78 @@ pub(super) fn chronus_move_gen<const F: MoveGenFlags, const MUST_CLIMB: bool>(
                         let final_level_3 = (prelude.exactly_level_2 & build_mask)
                             | (prelude.exactly_level_3 & !build_mask);
                         let check_board = reach_board & final_level_3;
-                        check_board.is_not_empty()
+
+                        // With 4 domes on the board, any level 3 square we can build on next
+                        // turn completes the fifth tower
+                        let new_dome = prelude.exactly_level_3 & build_mask;
+                        let final_dome_count = dome_count + new_dome.count_ones();
+                        // Harpies slide workers past the squares they move to, and Persephone can
+                        // force them up instead, so dome threats aren't tracked against either
+                        let dome_check_board = if final_dome_count == CHRONUS_DOME_COUNT_TO_WIN - 1
+                            && !prelude.is_against_harpies
+                            && prelude.other_god.god_name != GodName::Persephone
+                        {
+                            let final_levels = [
+                                prelude.exactly_level_0 & !build_mask,
+                                (prelude.exactly_level_0 & build_mask)
+                                    | (prelude.exactly_level_1 & !build_mask),
+                                (prelude.exactly_level_1 & build_mask)
+                                    | (prelude.exactly_level_2 & !build_mask),
+                                final_level_3,
+                            ];
+                            let next_turn_unblocked = unblocked_squares & !new_dome;
+
+                            let next_turn_workers = || {
+                                std::iter::once((
+                                    worker_end_move_state.worker_end_pos,
+                                    worker_end_move_state.worker_end_height,
+                                ))
+                                .chain(
+                                    worker_start_state
+                                        .other_own_workers
+                                        .into_iter()
+                                        .map(|worker| (worker, state.board.get_height(worker))),
+                                )
+                            };
+                            // Hypnus stops a worker that's higher than all the others from moving
+                            let hypnus_stopped_height = if prelude.is_against_hypnus {
+                                let highest = next_turn_workers()
+                                    .map(|(_, height)| height)
+                                    .max()
+                                    .unwrap_or_default();
+                                let highest_count = next_turn_workers()
+                                    .filter(|&(_, height)| height == highest)
+                                    .count();
+                                (highest_count == 1).then_some(highest)
+                            } else {
+                                None
+                            };
+
+                            // Every worker can dome level 3 squares next to where it moves next
+                            // turn, including the square it moves off of
+                            let mut dome_check_board = BitBoard::EMPTY;
+                            for (worker, height) in next_turn_workers() {
+                                if hypnus_stopped_height == Some(height) {
+                                    continue;
+                                }
+                                // Hades stops workers from moving down
+                                let lowest = if prelude.is_down_prevented { height } else { 0 };
+                                let climbable = final_levels[lowest..=(height + 1).min(3)]
+                                    .iter()
+                                    .fold(BitBoard::EMPTY, |acc, &level| acc | level);
+                                let moves = prelude.standard_neighbor_map[worker as usize]
+                                    & climbable
+                                    & next_turn_unblocked;
+                                dome_check_board |= apply_mapping_to_mask(moves, &NEIGHBOR_MAP)
+                                    & final_level_3
+                                    & (next_turn_unblocked | worker.to_board());
+                            }
+                            dome_check_board
+                        } else {
+                            BitBoard::EMPTY
+                        };
+
+                        (check_board | dome_check_board).is_not_empty()
                     };
 
                     result.push(build_scored_move::<F, _>(
@@ -340,3 +411,50 @@ pub(crate) const fn build_chronus() -> GodPower {
         3013502386383907053,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::fen::parse_fen;
+
+    use super::*;
+
+    #[test]
+    fn test_chronus_dome_threat_is_check() {
+        let fen = "4444000000000000000000003/1/chronus:A1,B1/mortal:C4,D4";
+        let state = parse_fen(fen).unwrap();
+        let chronus = GodName::Chronus.to_power();
+
+        let next_moves = chronus.get_moves_for_search(&state, Player::One);
+        let is_check = |move_str: &str| {
+            next_moves
+                .iter()
+                .find(|m| chronus.stringify_move(m.action) == move_str)
+                .map(|m| m.action.get_is_check())
+                .unwrap()
+        };
+
+        assert!(is_check("B1>C1^B1"));
+        assert!(!is_check("B1>A2^B1"));
+    }
+
+    #[test]
+    fn test_chronus_dome_threat_needs_a_reachable_square() {
+        // E1 is only reachable from D1 and D2, which are too high to climb onto from C2
+        let fen = "4444000000000000002000023/1/chronus:A1,B1/mortal:C4,D4";
+        let state = parse_fen(fen).unwrap();
+        let chronus = GodName::Chronus.to_power();
+
+        let next_moves = chronus.get_moves_for_search(&state, Player::One);
+        let is_check = |move_str: &str| {
+            next_moves
+                .iter()
+                .find(|m| chronus.stringify_move(m.action) == move_str)
+                .map(|m| m.action.get_is_check())
+                .unwrap()
+        };
+
+        assert!(!is_check("B1>C2^B1"));
+        // Building D2 up to level 3 makes a square that can be domed from C1, C3 or D3
+        assert!(is_check("B1>C2^D2"));
+    }
+}
@@ -10,7 +10,7 @@ use crate::{
             get_worker_end_move_state, get_worker_next_build_state, get_worker_next_move_state,
             get_worker_start_move_state, is_mate_only, modify_prelude_for_checking_workers,
             push_winning_moves,
-        }, FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod
+        }, FullAction, GodDataItem, GodName, GodPower, HistoryIdxHelper, StaticGod
     },
     persephone_check_result,
     player::Player,
@@ -314,31 +314,28 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     }
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
-    match board.god_data[player as usize] {
-        0 => None,
-        x => {
-            let used_coins = x >> 25;
-            let mask_section = BitBoard(x & BitBoard::MAIN_SECTION_MASK.0);
-
-            Some(format!(
-                "{} Coins left. Coins at {}.",
-                3 - used_coins,
-                mask_section
-                    .all_squares()
-                    .iter()
-                    .map(Square::to_string)
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            ))
-        }
-    }
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
+    let god_data = board.god_data[player as usize];
+    let used_coins = god_data >> 25;
+    vec![
+        GodDataItem::counter("Coins left", 3 - used_coins, Some(3)),
+        GodDataItem::squares("Coins", BitBoard(god_data & BitBoard::MAIN_SECTION_MASK.0)),
+    ]
 }
 
 fn get_frozen_mask(board: &BoardState, player: Player) -> BitBoard {
     BitBoard(board.god_data[player as usize] & BitBoard::MAIN_SECTION_MASK.0)
 }
 
+/// Coins sit on built blocks, and are taken off by building a dome. Used coins stay used
+fn reconcile_god_data(board: &BoardState, player: Player) -> GodData {
+    let god_data = board.god_data[player as usize];
+    let coin_mask = BitBoard(god_data & BitBoard::MAIN_SECTION_MASK.0)
+        & board.height_map[0]
+        & !board.height_map[3];
+    coin_mask.0 as GodData | (god_data & USED_COIN_MASK)
+}
+
 fn flip_horizontal(god_data: GodData) -> GodData {
     BitBoard(god_data & BitBoard::MAIN_SECTION_MASK.0)
         .flip_horizontal()
@@ -370,9 +367,33 @@ pub const fn build_clio() -> GodPower {
     )
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
     .with_get_frozen_mask_fn(get_frozen_mask)
     .with_flip_god_data_horizontal_fn(flip_horizontal)
     .with_flip_god_data_vertical_fn(flip_vertical)
     .with_flip_god_data_transpose_fn(flip_transpose)
+    .with_reconcile_god_data_fn(reconcile_god_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::parse_fen;
+
+    #[test]
+    fn test_clio_reconcile_god_data() {
+        let mut state =
+            parse_fen("0000000000001100000000000/1/clio[1|C3,D3]:A1,E1/mortal:A5,E5").unwrap();
+        assert!(!state.reconcile_god_data());
+
+        // A coin needs a block under it, and a dome takes it off
+        state.board.unbuild(Square::C3);
+        state.board.dome_up(Square::D3);
+        assert!(state.reconcile_god_data());
+
+        let god_data = state.board.god_data[0];
+        assert_eq!(god_data & BitBoard::MAIN_SECTION_MASK.0, 0);
+        assert_eq!(god_data >> GOD_DATA_COIN_COUNT_OFFSET, 2);
+        state.validation_err().unwrap();
+    }
 }
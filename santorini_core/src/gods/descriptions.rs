@@ -0,0 +1,181 @@
+use crate::gods::GodName;
+
+/// Power text for `god`, followed by any notes on how this implementation differs from the cards
+pub fn god_description(god: GodName) -> &'static str {
+    match god {
+        GodName::Mortal => "No god power. Move one of your Workers, then build with it.",
+        GodName::Pan => "Win Condition: You also win if your Worker moves down two or more levels.",
+        GodName::Artemis => {
+            "Your Move: Your Worker may move one additional time, but not back to its initial space."
+        }
+        GodName::Hephaestus => {
+            "Your Build: Your Worker may build one additional block (not dome) on top of your first block."
+        }
+        GodName::Atlas => "Your Build: Your Worker may build a dome at any level.",
+        GodName::Athena => {
+            "Opponent's Turn: If one of your Workers moved up on your last turn, opponent Workers cannot move up this turn."
+        }
+        GodName::Minotaur => {
+            "Your Move: Your Worker may move into an opponent Worker's space, if their Worker can be forced one space straight backwards to an unoccupied space at any level."
+        }
+        GodName::Demeter => {
+            "Your Build: Your Worker may build one additional time, but not on the same space."
+        }
+        GodName::Apollo => {
+            "Your Move: Your Worker may move into an opponent Worker's space by forcing their Worker to the space yours just vacated."
+        }
+        GodName::Hermes => {
+            "Your Turn: If your Workers do not move up or down, they may each move any number of times (even zero), and then either builds."
+        }
+        GodName::Prometheus => {
+            "Your Turn: If your Worker does not move up, it may build both before and after moving."
+        }
+        GodName::Urania => {
+            "Your Turn: When your Worker moves or builds, treat opposite edges and corners as if they are adjacent so that everything wraps around."
+        }
+        GodName::Graeae => {
+            "Setup: Place 3 Workers of your color.\nYour Build: Build with a Worker that did not move."
+        }
+        GodName::Hera => {
+            "Opponent's Turn: An opponent cannot win by moving into a perimeter space."
+        }
+        GodName::Limus => {
+            "Opponent's Turn: Opponent Workers cannot build on spaces neighboring your Workers, unless building a dome to create a Complete Tower."
+        }
+        GodName::Hypnus => {
+            "Start of Opponent's Turn: If one of your opponent's Workers is higher than all of their others, it cannot move."
+        }
+        GodName::Harpies => {
+            "Opponent's Turn: Each time an opponent's Worker moves, it is forced space by space in the same direction until the next space is at a higher level or it is obstructed."
+        }
+        GodName::Aphrodite => {
+            "Any Move: If an opponent Worker starts its turn neighboring one of your Workers, its last move must be to a space neighboring one of your Workers."
+        }
+        GodName::Persephone => {
+            "Opponent's Turn: If possible, at least one Worker must move up this turn."
+        }
+        GodName::Hades => "Opponent's Turn: Opponent Workers cannot move down.",
+        GodName::Morpheus => {
+            "Start of Your Turn: Place a block or dome on your God Power card.\nYour Build: Your Worker cannot build as normal. Instead, spend any number of blocks or domes from your God Power card and build that many times.\n* Multiple Morpheus builds must start in the NW corner and progress clockwise"
+        }
+        GodName::Aeolus => {
+            "End of Your Turn: Choose a direction for the Wind, or remove it.\nAny Move: Workers cannot move directly into the Wind."
+        }
+        GodName::Hestia => {
+            "Your Build: Your Worker may build one additional time, but this cannot be on a perimeter space."
+        }
+        GodName::Europa => {
+            "End of Your Turn: You may relocate your Talus Token to an unoccupied space neighboring the Worker that moved.\nAny Time: All players treat the space containing the Talus Token as if it contains only a dome."
+        }
+        GodName::Bia => {
+            "Setup: Place your Workers first. Your Workers must be placed in perimeter spaces.\nYour Move: If your Worker moves into a space and the next space in the same direction is occupied by an opponent Worker, the opponent's Worker is removed from the game."
+        }
+        GodName::Clio => {
+            "Your Build: Place a Coin Token on each of the first 3 blocks your Workers build.\nOpponent's Turn: Opponents treat spaces containing your Coin Tokens as if they contain only a dome."
+        }
+        GodName::Maenads => {
+            "End of Your Turn: If your Workers neighbor an opponent Worker on opposite sides, you win."
+        }
+        GodName::Zeus => "Your Build: Your Worker may build a block under itself.",
+        GodName::Ares => {
+            "End of Your Turn: You may remove an unoccupied block (not dome) neighboring your unmoved Worker."
+        }
+        GodName::Eros => {
+            "Setup: Place your Workers anywhere along opposite edges of the board.\nWin Condition: You also win if one of your Workers moves to a space neighboring your other Worker and both are on the first level."
+        }
+        GodName::Selene => {
+            "Setup: Place one of your Workers as a female Worker.\nYour Build: Instead of your normal build, your female Worker may build a dome at any level regardless of which Worker moved."
+        }
+        GodName::Hippolyta => {
+            "Setup: Place one of your Workers as a female Worker.\nYour Move: Your female Worker moves normally. Your other Workers may only move diagonally."
+        }
+        GodName::Scylla => {
+            "Your Move: If your Worker moves from a space that neighbors an opponent Worker, you may force their Worker into the space yours just vacated."
+        }
+        GodName::Charon => {
+            "Your Move: Before your Worker moves, you may force a neighboring opponent Worker to the space directly on the other side of your Worker, if that space is unoccupied."
+        }
+        GodName::Pegasus => {
+            "Your Move: Your Worker may move up more than one level, but cannot win the game by doing so."
+        }
+        GodName::Proteus => {
+            "Setup: Place 3 Workers of your color.\nYour Move: After your Worker moves, if possible, force one of your other Workers into the space your Worker just vacated."
+        }
+        GodName::Asteria => {
+            "End of Your Turn: If one of your Workers moved down this turn, you may build a dome on any unoccupied space."
+        }
+        GodName::Hydra => {
+            "End of Your Turn: If none of your Workers neighbor each other, gain a new Worker and place it in one of the lowest unoccupied spaces next to the Worker you moved. Otherwise, choose a Worker who has neighbors and remove it from the game. You cannot remove your last Worker."
+        }
+        GodName::ApolloV2 => {
+            "Your Move: Your Worker may move into an opponent Worker's space by forcing their Worker to the space yours just vacated.\n* Cannot swap with an opponent Worker that is higher than your Worker"
+        }
+        GodName::Medusa => {
+            "End of Your Turn: If possible, your Workers build in lower neighboring spaces that are occupied by opponent Workers, removing the opponent Workers from the game."
+        }
+        GodName::Iris => {
+            "Your Move: If there is a Worker neighboring your Worker and the space directly on the other side of it is unoccupied, your Worker may move to that space regardless of its level."
+        }
+        GodName::Castor => {
+            "Your Turn: Instead of your normal turn, you may move both your Workers without building, or build with both your Workers without moving."
+        }
+        GodName::CharonV2 => {
+            "Your Turn: Instead of moving, you may force a neighboring opponent Worker to the space directly on the other side of your Worker, if that space is unoccupied. Then build with your Worker that did not move."
+        }
+        GodName::Polyphemus => {
+            "End of Your Turn: Once per game, you may build up to 2 domes at any level on any unoccupied spaces."
+        }
+        GodName::Nike => {
+            "Opponent's Turn: If one of your Workers moved down on your last turn, opponent Workers cannot move up this turn."
+        }
+        GodName::Nemesis => {
+            "End of Your Turn: If none of an opponent's Workers neighbor yours, you may force as many of your opponent's Workers as possible to take the spaces occupied by your Workers, and vice versa."
+        }
+        GodName::Poseidon => {
+            "End of Your Turn: If your unmoved Worker is on the ground level, it may build up to three times."
+        }
+        GodName::Bellerophon => "Your Move: Once per game, your Worker may move up two levels.",
+        GodName::Chronus => {
+            "Win Condition: You also win when there are at least five Complete Towers on the board."
+        }
+        GodName::Theseus => {
+            "End of Your Turn: If any of your unmoved Workers is exactly 2 levels below a neighboring opponent Worker, you may remove that opponent Worker from the game."
+        }
+        GodName::Jason => {
+            "Your Turn: Once per game, instead of moving, place a new Worker of your color on an unoccupied ground-level perimeter space, then build with that Worker."
+        }
+        GodName::Achilles => {
+            "Your Turn: Once per game, your Worker may build both before and after moving."
+        }
+        GodName::Stymphalians => {
+            "Setup: Place 3 Workers of your color.\nYour Move: Your Worker may move up to three times, but never back into its starting space.\n* Moves of more than one step must end on a space that doesn't neighbor the starting space"
+        }
+        GodName::Triton => {
+            "Your Move: Each time your Worker moves into a perimeter space, it may immediately move again."
+        }
+    }
+}
+
+/// Lines of `god_description` that describe how this implementation differs from the cards
+pub fn god_implementation_notes(god: GodName) -> impl Iterator<Item = &'static str> {
+    god_description(god)
+        .lines()
+        .filter(|line| line.starts_with("* "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gods::ALL_GODS_BY_ID;
+
+    #[test]
+    fn test_every_god_has_description() {
+        for god in ALL_GODS_BY_ID.iter() {
+            assert!(
+                !god_description(god.god_name).trim().is_empty(),
+                "{:?} has no description",
+                god.god_name
+            );
+        }
+    }
+}
@@ -334,4 +334,5 @@ pub const fn build_eros() -> GodPower {
         6256749897107858133,
     )
     .with_placement_type(PlacementType::PerimeterOpposite)
+    .with_max_workers(2)
 }
@@ -10,7 +10,7 @@ use crate::{
             get_worker_end_move_state_with_custom_worker_helper, get_worker_next_build_state,
             get_worker_next_move_state, get_worker_start_move_state, is_interact_with_key_squares,
             is_mate_only, modify_prelude_for_checking_workers, push_winning_moves,
-        }, FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod
+        }, FullAction, GodDataItem, GodName, GodPower, HistoryIdxHelper, StaticGod
     },
     persephone_check_result,
     player::Player,
@@ -328,17 +328,28 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     }
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
-    match board.god_data[player as usize] {
-        0 => Some("Talus unplaced".to_string()),
-        x => Some(format!("Talus at {:?}", BitBoard(x).lsb())),
-    }
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
+    vec![GodDataItem::squares(
+        "Talus",
+        BitBoard(board.god_data[player as usize]),
+    )]
 }
 
 fn get_frozen_mask(board: &BoardState, player: Player) -> BitBoard {
     BitBoard(board.god_data[player as usize])
 }
 
+/// The talus can't share a square with a dome or a worker
+fn reconcile_god_data(board: &BoardState, player: Player) -> GodData {
+    let talus_mask = BitBoard(board.god_data[player as usize]);
+    let blocked = board.height_map[3] | board.workers[0] | board.workers[1];
+    if (talus_mask & blocked).is_not_empty() {
+        0
+    } else {
+        talus_mask.0 as GodData
+    }
+}
+
 fn flip_horizontal(god_data: GodData) -> GodData {
     BitBoard(god_data).flip_horizontal().0 as GodData
 }
@@ -361,16 +372,22 @@ pub const fn build_europa() -> GodPower {
     )
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
     .with_get_frozen_mask_fn(get_frozen_mask)
     .with_flip_god_data_horizontal_fn(flip_horizontal)
     .with_flip_god_data_vertical_fn(flip_vertical)
     .with_flip_god_data_transpose_fn(flip_transpose)
+    .with_reconcile_god_data_fn(reconcile_god_data)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::fen::{game_state_to_fen, parse_fen};
+    use crate::{
+        bitboard::BitBoard,
+        fen::{game_state_to_fen, parse_fen},
+        player::Player,
+        square::Square,
+    };
 
     #[test]
     fn test_europa_parse_round_trip() {
@@ -380,6 +397,25 @@ mod tests {
         assert_eq!(initial_fen, new_fen);
     }
 
+    #[test]
+    fn test_europa_reconcile_god_data() {
+        let initial_fen = "0000000000000010000000000/2/europa[E2]:A3,D2/persephone:C4,B3";
+        let mut state = parse_fen(initial_fen).unwrap();
+        assert!(!state.reconcile_god_data());
+
+        // A worker can't stand on the talus
+        let mut with_worker = state.clone();
+        with_worker.board.worker_xor(Player::Two, BitBoard::as_mask(Square::E2));
+        assert!(with_worker.reconcile_god_data());
+        assert_eq!(with_worker.board.god_data[0], 0);
+
+        // Neither can a dome
+        state.board.dome_up(Square::E2);
+        assert!(state.reconcile_god_data());
+        assert_eq!(state.board.god_data[0], 0);
+        state.validation_err().unwrap();
+    }
+
     // #[test]
     // fn test_europa_permutations() {
     //     let state =
@@ -1,6 +1,7 @@
 use crate::{
     bitboard::{
-        BitBoard, JUMP_OVER_PUSH_MAPPING, NUM_SQUARES, PUSH_MAPPING, WRAPPING_NEIGHBOR_MAP,
+        BETWEEN_MAPPING, BitBoard, NUM_SQUARES, SLIDE_RAYS, STEP_DIRECTION_MAPPING,
+        WRAPPING_NEIGHBOR_MAP,
     },
     board::{BoardState, FullGameState},
     build_god_power_movers,
@@ -83,47 +84,61 @@ pub fn urania_slide(board: &BoardState, from: Square, to: Square, workers: BitBo
     slide_position_with_custom_blockers(board, to, next_spot, workers)
 }
 
-pub(crate) fn prometheus_slide(
-    prelude: &GeneratorPreludeState,
+/// Where a worker that just stepped from `from` to the neighboring `to` (at `to_height`) ends up
+/// after sliding. It keeps going in the same direction until the next square is in `blockers` or
+/// higher than the one it's on. Returns the final square, its height, and the squares slid over
+/// after `to`
+pub(crate) fn slide_after_step(
+    board: &BoardState,
     from: Square,
     to: Square,
     to_height: usize,
-) -> Square {
-    let Some(next_spot) = PUSH_MAPPING[from as usize][to as usize] else {
-        return to;
+    blockers: BitBoard,
+) -> (Square, usize, BitBoard) {
+    let Some(direction) = STEP_DIRECTION_MAPPING[from as usize][to as usize] else {
+        return (to, to_height, BitBoard::EMPTY);
     };
 
-    let next_height = prelude.board.get_height(next_spot);
-    if next_height > to_height {
-        return to;
-    }
+    let mut current_pos = to;
+    let mut current_height = to_height;
+    let mut slid_over = BitBoard::EMPTY;
+    for &next_spot in SLIDE_RAYS[to as usize][direction as usize].unblocked_squares(blockers) {
+        let next_height = board.get_height(next_spot);
+        if next_height > current_height {
+            break;
+        }
 
-    let next_mask = BitBoard::as_mask(next_spot);
-    if ((prelude.all_workers_and_frozen_mask) & next_mask).is_not_empty() {
-        return to;
+        current_pos = next_spot;
+        current_height = next_height;
+        slid_over |= next_spot.to_board();
     }
 
-    slide_position(prelude, to, next_spot)
+    (current_pos, current_height, slid_over)
 }
 
-pub(crate) fn slide_position(prelude: &GeneratorPreludeState, from: Square, to: Square) -> Square {
-    let Some(next_spot) = PUSH_MAPPING[from as usize][to as usize] else {
-        return to;
-    };
-
-    let to_height = prelude.board.get_height(to);
-    let next_height = prelude.board.get_height(next_spot);
-
-    if next_height > to_height {
-        return to;
-    }
-
-    let next_mask = BitBoard::as_mask(next_spot);
-    if ((prelude.all_workers_and_frozen_mask) & next_mask).is_not_empty() {
-        return to;
-    }
+pub(crate) fn prometheus_slide(
+    prelude: &GeneratorPreludeState,
+    from: Square,
+    to: Square,
+    to_height: usize,
+) -> Square {
+    slide_after_step(
+        prelude.board,
+        from,
+        to,
+        to_height,
+        prelude.all_workers_and_frozen_mask,
+    )
+    .0
+}
 
-    slide_position(prelude, to, next_spot)
+pub(crate) fn slide_position(prelude: &GeneratorPreludeState, from: Square, to: Square) -> Square {
+    slide_position_with_custom_blockers(
+        prelude.board,
+        from,
+        to,
+        prelude.all_workers_and_frozen_mask,
+    )
 }
 
 pub(crate) fn basic_slide_from_unblocked(
@@ -132,30 +147,10 @@ pub(crate) fn basic_slide_from_unblocked(
     from: Square,
     to: Square,
 ) -> (Square, usize) {
-    let mut current_from = from;
-    let mut current_pos = to;
-    let mut current_height = prelude.board.get_height(current_pos);
-
-    loop {
-        let Some(next_spot) = PUSH_MAPPING[current_from as usize][current_pos as usize] else {
-            break;
-        };
-        let next_mask = next_spot.to_board();
-        if (unblocked_squares & next_mask).is_empty() {
-            break;
-        }
-
-        let next_height = prelude.board.get_height(next_spot);
-        if next_height > current_height {
-            break;
-        }
-
-        current_from = current_pos;
-        current_pos = next_spot;
-        current_height = next_height;
-    }
-
-    return (current_pos, current_height);
+    let to_height = prelude.board.get_height(to);
+    let (dest, height, _) =
+        slide_after_step(prelude.board, from, to, to_height, !unblocked_squares);
+    (dest, height)
 }
 
 pub(crate) fn iris_slide_position(
@@ -163,23 +158,9 @@ pub(crate) fn iris_slide_position(
     from: Square,
     to: Square,
 ) -> Square {
-    let Some(next_spot) = JUMP_OVER_PUSH_MAPPING[from as usize][to as usize] else {
-        return to;
-    };
-
-    let to_height = prelude.board.get_height(to);
-    let next_height = prelude.board.get_height(next_spot);
-
-    if next_height > to_height {
-        return to;
-    }
-
-    let next_mask = BitBoard::as_mask(next_spot);
-    if ((prelude.all_workers_and_frozen_mask) & next_mask).is_not_empty() {
-        return to;
-    }
-
-    slide_position(prelude, to, next_spot)
+    // Jumps over a worker keep sliding in the direction of the jump
+    let step_from = BETWEEN_MAPPING[from as usize][to as usize].unwrap_or(from);
+    slide_position(prelude, step_from, to)
 }
 
 pub fn slide_position_with_custom_blockers(
@@ -188,23 +169,7 @@ pub fn slide_position_with_custom_blockers(
     to: Square,
     blockers: BitBoard,
 ) -> Square {
-    let Some(next_spot) = PUSH_MAPPING[from as usize][to as usize] else {
-        return to;
-    };
-
-    let to_height = board.get_height(to);
-    let next_height = board.get_height(next_spot);
-
-    if next_height > to_height {
-        return to;
-    }
-
-    let next_mask = BitBoard::as_mask(next_spot);
-    if (blockers & next_mask).is_not_empty() {
-        return to;
-    }
-
-    slide_position_with_custom_blockers(board, to, next_spot, blockers)
+    slide_after_step(board, from, to, board.get_height(to), blockers).0
 }
 
 // Same as mortal, except for custom key moves logic vs artemis
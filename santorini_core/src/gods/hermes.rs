@@ -551,4 +551,5 @@ pub(super) const fn build_hermes() -> GodPower {
         8064494721607657900,
         8099092864803375172,
     )
+    .with_max_workers(2)
 }
@@ -457,4 +457,5 @@ pub const fn build_hydra() -> GodPower {
         2854659210591727588,
         10142526825370404391,
     )
+    .with_max_workers(11)
 }
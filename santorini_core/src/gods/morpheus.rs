@@ -4,7 +4,8 @@ use crate::{
     build_god_power_movers,
     direction::{Direction, squares_to_direction},
     gods::{
-        FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod, build_god_power_actions,
+        FullAction, GodDataItem, GodName, GodPower, HistoryIdxHelper, StaticGod,
+        build_god_power_actions,
         generic::{
             GenericMove, GodMove, LOWER_POSITION_MASK, MOVE_IS_WINNING_MASK, MoveData,
             MoveGenFlags, NULL_MOVE_DATA, POSITION_WIDTH, ScoredMove,
@@ -585,11 +586,21 @@ pub(super) fn morpheus_move_gen<const F: MoveGenFlags, const MUST_CLIMB: bool>(
     result
 }
 
+/// More build tokens than any game could save up. Move generation adds to the count without
+/// checking for overflow
+const MAX_BUILD_TOKENS: GodData = 10_000;
+
 fn parse_god_data(data: &str) -> Result<GodData, String> {
     match data {
         "" => Ok(0),
         x => {
             let build_count: u32 = x.parse().map_err(|e| format!("{:?}", e))?;
+            if build_count > MAX_BUILD_TOKENS {
+                return Err(format!(
+                    "Morpheus can't have more than {} build tokens",
+                    MAX_BUILD_TOKENS
+                ));
+            }
             Ok(build_count)
         }
     }
@@ -601,16 +612,16 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     }
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
     if board.workers[player as usize].is_empty() {
-        return None;
+        return Vec::new();
     }
 
     let mut god_data = board.god_data[player as usize];
     if board.current_player == player {
         god_data += 1;
     }
-    Some(format!("Builds tokens: {god_data}"))
+    vec![GodDataItem::counter("Build tokens", god_data, None)]
 }
 
 pub const fn build_morpheus() -> GodPower {
@@ -623,7 +634,7 @@ pub const fn build_morpheus() -> GodPower {
     )
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
 }
 
 #[cfg(test)]
@@ -769,4 +769,5 @@ pub const fn build_nemesis() -> GodPower {
         13902774959503976241,
         8706614857531094214,
     )
+    .with_max_workers(2)
 }
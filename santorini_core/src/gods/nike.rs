@@ -3,7 +3,7 @@ use crate::{
     board::{BoardState, FullGameState, GodData},
     build_god_power_movers,
     gods::{
-        GodName, GodPower,
+        GodDataItem, GodName, GodPower,
         athena::AthenaMove,
         build_god_power_actions,
         generic::{MoveGenFlags, ScoredMove, get_default_parse_data_err},
@@ -149,15 +149,14 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     }
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
     if board.current_player == player {
-        return None;
-    }
-    let god_data = board.god_data[player as usize];
-    match god_data {
-        0 => None,
-        _ => Some("Preventing Upward Moves".to_owned()),
+        return Vec::new();
     }
+    vec![GodDataItem::flag(
+        "Preventing upward moves",
+        board.god_data[player as usize] != 0,
+    )]
 }
 
 pub const fn build_nike() -> GodPower {
@@ -172,5 +171,5 @@ pub const fn build_nike() -> GodPower {
     .with_can_opponent_climb_fn(can_opponent_climb)
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
 }
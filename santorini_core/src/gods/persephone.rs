@@ -135,6 +135,7 @@ mod tests {
         search::{
             SearchContext, WINNING_SCORE_BUFFER, get_win_reached_search_terminator, negamax_search,
         },
+        search_observer::NoopSearchObserver,
         search_terminators::DynamicMaxDepthSearchTerminator,
         transposition_table::TranspositionTable,
     };
@@ -156,12 +157,16 @@ mod tests {
             tt: &mut tt,
             new_best_move_callback: Box::new(move |_new_best_move| {}),
             terminator: DynamicMaxDepthSearchTerminator::new(2),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
         };
         let search_state = negamax_search(
             &mut search_context,
             state,
             get_win_reached_search_terminator(),
-        );
+        )
+        .unwrap();
         // Persephone is winning from here
         assert!(search_state.best_move.unwrap().score > WINNING_SCORE_BUFFER);
     }
@@ -852,4 +852,5 @@ pub(crate) const fn build_poseidon() -> GodPower {
         39626542716481940,
         12412485317668298438,
     )
+    .with_max_workers(2)
 }
@@ -3,7 +3,8 @@ use crate::{
     board::{BoardState, FullGameState, GodData},
     build_god_power_movers,
     gods::{
-        FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod, build_god_power_actions,
+        FullAction, GodDataItem, GodName, GodPower, HistoryIdxHelper, StaticGod,
+        build_god_power_actions,
         generic::{
             GenericMove, GodMove, LOWER_POSITION_MASK, MOVE_IS_WINNING_MASK, MoveData,
             MoveGenFlags, NULL_MOVE_DATA, POSITION_WIDTH, ScoredMove,
@@ -341,17 +342,22 @@ fn stringify_god_data(data: GodData) -> Option<String> {
     }
 }
 
-fn pretty_stringify_god_data(board: &BoardState, player: Player) -> Option<String> {
-    match board.god_data[player as usize] {
-        0 => Some("No Female Worker".to_string()),
-        x => Some(format!("Female worker at {:?}", BitBoard(x).lsb())),
-    }
+fn god_data_items(board: &BoardState, player: Player) -> Vec<GodDataItem> {
+    vec![GodDataItem::squares(
+        "Female worker",
+        BitBoard(board.god_data[player as usize]),
+    )]
 }
 
 fn get_female_worker_mask(board: &BoardState, player: Player) -> BitBoard {
     BitBoard(board.god_data[player as usize])
 }
 
+/// The female marker only stays on one of the player's own workers
+fn reconcile_god_data(board: &BoardState, player: Player) -> GodData {
+    board.god_data[player as usize] & board.workers[player as usize].0 as GodData
+}
+
 fn flip_horizontal(god_data: GodData) -> GodData {
     BitBoard(god_data).flip_horizontal().0 as GodData
 }
@@ -374,10 +380,32 @@ pub const fn build_selene() -> GodPower {
     )
     .with_parse_god_data_fn(parse_god_data)
     .with_stringify_god_data_fn(stringify_god_data)
-    .with_pretty_stringify_god_data_fn(pretty_stringify_god_data)
+    .with_god_data_items_fn(god_data_items)
     .with_flip_god_data_horizontal_fn(flip_horizontal)
     .with_flip_god_data_vertical_fn(flip_vertical)
     .with_flip_god_data_transpose_fn(flip_transpose)
     .with_get_female_worker_mask_fn(get_female_worker_mask)
+    .with_reconcile_god_data_fn(reconcile_god_data)
     .with_placement_type(PlacementType::FemaleWorker)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::parse_fen;
+
+    #[test]
+    fn test_selene_reconcile_god_data() {
+        let mut state =
+            parse_fen("0000000000000000000000000/1/selene[A1]:A1,E1/mortal:A5,E5").unwrap();
+        assert!(!state.reconcile_god_data());
+
+        // Removing the female worker takes the marker with her
+        state
+            .board
+            .worker_xor(Player::One, BitBoard::as_mask(Square::A1));
+        assert!(state.reconcile_god_data());
+        assert_eq!(state.board.god_data[0], 0);
+        state.validation_err().unwrap();
+    }
+}
@@ -0,0 +1,550 @@
+use crate::{
+    bitboard::{BitBoard, BitboardMapping, NUM_SQUARES, PERIMETER_SPACES_MASK},
+    board::{BoardState, FullGameState},
+    build_god_power_movers,
+    gods::{
+        FullAction, GodName, GodPower, HistoryIdxHelper, StaticGod, build_god_power_actions,
+        generic::{
+            GenericMove, GodMove, LOWER_POSITION_MASK, MOVE_IS_WINNING_MASK, MoveData,
+            MoveGenFlags, NULL_MOVE_DATA, POSITION_WIDTH, ScoredMove,
+        },
+        god_power,
+        hypnus::hypnus_moveable_worker_filter,
+        move_helpers::{
+            GeneratorPreludeState, WorkerEndMoveState, build_scored_move,
+            get_generator_prelude_state, get_worker_climb_height_raw, get_worker_next_build_state,
+            get_worker_start_move_state, is_include_score, is_mate_only, push_winning_moves,
+            restrict_moves_by_affinity_area,
+        },
+    },
+    persephone_check_result,
+    player::Player,
+    square::Square,
+};
+
+use super::PartialAction;
+
+const MOVE_FROM_POSITION_OFFSET: usize = 0;
+const MOVE_TO_POSITION_OFFSET: usize = MOVE_FROM_POSITION_OFFSET + POSITION_WIDTH;
+const BUILD_POSITION_OFFSET: usize = MOVE_TO_POSITION_OFFSET + POSITION_WIDTH;
+
+// Triton can chain any number of moves through perimeter squares, so moves only store the start
+// and end of the chain. The path between them is recovered by searching the board again.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct TritonMove(pub MoveData);
+
+impl GodMove for TritonMove {
+    fn move_to_actions(
+        self,
+        board: &BoardState,
+        player: Player,
+        other_god: StaticGod,
+    ) -> Vec<FullAction> {
+        let mut gods = [other_god, other_god];
+        gods[player as usize] = GodName::Triton.to_power();
+        let state = FullGameState::new(board.clone(), gods);
+        let prelude = get_generator_prelude_state::<0>(&state, player, BitBoard::EMPTY);
+        let rules = TritonMoveRules::from_prelude(&prelude);
+
+        let from = self.move_from_position();
+        let to = self.move_to_position();
+        let is_winning = self.get_is_winning();
+        let blockers = prelude.all_workers_and_frozen_mask ^ BitBoard::as_mask(from);
+
+        let mut res = vec![PartialAction::SelectWorker(from)];
+        for square in rules.find_path(from, to, is_winning, blockers) {
+            res.push(PartialAction::MoveWorker(square.into()));
+        }
+
+        if !is_winning {
+            res.push(PartialAction::Build(self.build_position()));
+        }
+
+        vec![res]
+    }
+
+    fn make_move(self, board: &mut BoardState, player: Player, _other_god: StaticGod) {
+        let worker_move_mask = self.move_mask();
+        board.worker_xor(player, worker_move_mask);
+
+        if self.get_is_winning() {
+            board.set_winner(player);
+            return;
+        }
+
+        board.build_up(self.build_position());
+    }
+
+    fn get_blocker_board(self, _board: &BoardState) -> BitBoard {
+        // Any perimeter square might be part of the chain that led to the win
+        BitBoard::as_mask(self.move_from_position())
+            | BitBoard::as_mask(self.move_to_position())
+            | PERIMETER_SPACES_MASK
+    }
+
+    fn get_history_idx(self, board: &BoardState) -> usize {
+        let mut helper = HistoryIdxHelper::new();
+        helper.add_square_with_height(board, self.move_from_position());
+        helper.add_square_with_height(board, self.move_to_position());
+        helper.add_square_with_height(board, self.build_position());
+        helper.get()
+    }
+}
+
+impl Into<GenericMove> for TritonMove {
+    fn into(self) -> GenericMove {
+        unsafe { std::mem::transmute(self) }
+    }
+}
+
+impl From<GenericMove> for TritonMove {
+    fn from(value: GenericMove) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl TritonMove {
+    pub fn new_basic_move(
+        move_from_position: Square,
+        move_to_position: Square,
+        build_position: Square,
+    ) -> Self {
+        let data: MoveData = ((move_from_position as MoveData) << MOVE_FROM_POSITION_OFFSET)
+            | ((move_to_position as MoveData) << MOVE_TO_POSITION_OFFSET)
+            | ((build_position as MoveData) << BUILD_POSITION_OFFSET);
+
+        Self(data)
+    }
+
+    pub fn new_winning_move(move_from_position: Square, move_to_position: Square) -> Self {
+        let data: MoveData = ((move_from_position as MoveData) << MOVE_FROM_POSITION_OFFSET)
+            | ((move_to_position as MoveData) << MOVE_TO_POSITION_OFFSET)
+            | MOVE_IS_WINNING_MASK;
+        Self(data)
+    }
+
+    pub fn move_from_position(&self) -> Square {
+        Square::from((self.0 >> MOVE_FROM_POSITION_OFFSET) as u8 & LOWER_POSITION_MASK)
+    }
+
+    pub fn move_to_position(&self) -> Square {
+        Square::from((self.0 >> MOVE_TO_POSITION_OFFSET) as u8 & LOWER_POSITION_MASK)
+    }
+
+    pub fn build_position(self) -> Square {
+        Square::from((self.0 >> BUILD_POSITION_OFFSET) as u8 & LOWER_POSITION_MASK)
+    }
+
+    pub fn move_mask(self) -> BitBoard {
+        BitBoard::as_mask(self.move_from_position()) ^ BitBoard::as_mask(self.move_to_position())
+    }
+
+    pub fn get_is_winning(&self) -> bool {
+        (self.0 & MOVE_IS_WINNING_MASK) != 0
+    }
+}
+
+impl std::fmt::Debug for TritonMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 == NULL_MOVE_DATA {
+            return write!(f, "NULL");
+        }
+
+        let move_from = self.move_from_position();
+        let move_to = self.move_to_position();
+        let build = self.build_position();
+        let is_win = self.get_is_winning();
+
+        if is_win {
+            write!(f, "{}>{}#", move_from, move_to)
+        } else {
+            write!(f, "{}>{}^{}", move_from, move_to, build)
+        }
+    }
+}
+
+struct TritonReach {
+    // Squares the worker may finish its chain on without winning
+    ends: BitBoard,
+    // Level 3 squares the worker may win by climbing onto at some point in the chain
+    wins: BitBoard,
+}
+
+// The subset of prelude state needed to walk a chain of moves. Split out so that check detection
+// can walk chains on the board as it will be after the move is made.
+struct TritonMoveRules<'a> {
+    board: &'a BoardState,
+    neighbor_map: &'static BitboardMapping,
+    win_mask: BitBoard,
+    can_climb: bool,
+    is_down_prevented: bool,
+}
+
+impl<'a> TritonMoveRules<'a> {
+    fn from_prelude(prelude: &GeneratorPreludeState<'a>) -> Self {
+        Self {
+            board: prelude.board,
+            neighbor_map: prelude.standard_neighbor_map,
+            win_mask: prelude.win_mask,
+            can_climb: prelude.can_climb,
+            is_down_prevented: prelude.is_down_prevented,
+        }
+    }
+
+    fn step_moves(&self, from: Square, blockers: BitBoard) -> BitBoard {
+        let height = self.board.get_height(from);
+        let climb_height = get_worker_climb_height_raw(height, self.can_climb);
+        let mut moves =
+            self.neighbor_map[from as usize] & !(self.board.height_map[climb_height] | blockers);
+
+        if self.is_down_prevented && height > 0 {
+            moves &= self.board.height_map[height - 1];
+        }
+
+        moves
+    }
+
+    fn step_wins(&self, from: Square, moves: BitBoard) -> BitBoard {
+        if self.board.get_height(from) == 2 {
+            moves & self.board.exactly_level_3() & self.win_mask
+        } else {
+            BitBoard::EMPTY
+        }
+    }
+
+    fn reach(&self, start: Square, blockers: BitBoard) -> TritonReach {
+        let start_mask = BitBoard::as_mask(start);
+        let mut expanded = start_mask;
+        let mut queue = start_mask;
+        let mut ends = BitBoard::EMPTY;
+        let mut wins = BitBoard::EMPTY;
+
+        while let Some(current) = queue.next() {
+            let mut moves = self.step_moves(current, blockers);
+            let step_wins = self.step_wins(current, moves);
+            wins |= step_wins;
+            moves ^= step_wins;
+
+            ends |= moves;
+            let new_perimeter = moves & PERIMETER_SPACES_MASK & !expanded;
+            expanded |= new_perimeter;
+            queue |= new_perimeter;
+        }
+
+        // Looping back around to the start would be the same as not moving at all
+        let wins = wins & !start_mask;
+        TritonReach {
+            ends: ends & !(wins | start_mask),
+            wins,
+        }
+    }
+
+    // Returns the squares landed on, in order, for a shortest chain from start to end
+    fn find_path(
+        &self,
+        start: Square,
+        end: Square,
+        is_winning: bool,
+        blockers: BitBoard,
+    ) -> Vec<Square> {
+        let end_mask = BitBoard::as_mask(end);
+        let mut parents: [Option<Square>; NUM_SQUARES] = [None; NUM_SQUARES];
+        let mut expanded = BitBoard::as_mask(start);
+        let mut queue = vec![start];
+        let mut queue_idx = 0;
+
+        while queue_idx < queue.len() {
+            let current = queue[queue_idx];
+            queue_idx += 1;
+
+            let mut moves = self.step_moves(current, blockers);
+            let step_wins = self.step_wins(current, moves);
+            moves ^= step_wins;
+
+            let is_found = if is_winning {
+                (step_wins & end_mask).is_not_empty()
+            } else {
+                (moves & end_mask).is_not_empty()
+            };
+
+            if is_found {
+                let mut path = vec![end];
+                let mut backtrack = current;
+                while backtrack != start {
+                    path.push(backtrack);
+                    backtrack = parents[backtrack as usize].unwrap();
+                }
+                path.reverse();
+                return path;
+            }
+
+            for next in moves & PERIMETER_SPACES_MASK & !expanded {
+                expanded |= BitBoard::as_mask(next);
+                parents[next as usize] = Some(current);
+                queue.push(next);
+            }
+        }
+
+        debug_assert!(
+            false,
+            "Could not find triton path from {} to {}",
+            start, end
+        );
+        vec![end]
+    }
+}
+
+fn is_triton_check_after_move(
+    prelude: &GeneratorPreludeState,
+    player: Player,
+    move_mask: BitBoard,
+    build_position: Square,
+) -> bool {
+    let build_mask = BitBoard::as_mask(build_position);
+    let final_level_3 =
+        (prelude.exactly_level_2 & build_mask) | (prelude.exactly_level_3 & !build_mask);
+    if (final_level_3 & prelude.win_mask).is_empty() {
+        return false;
+    }
+
+    let mut check_board = prelude.board.clone();
+    check_board.worker_xor(player, move_mask);
+    check_board.build_up(build_position);
+    check_board.flip_current_player();
+    prelude.other_god.make_passing_move(&mut check_board);
+
+    let rules = TritonMoveRules {
+        board: &check_board,
+        neighbor_map: prelude.standard_neighbor_map,
+        win_mask: prelude.win_mask,
+        can_climb: prelude.other_god.can_opponent_climb(&check_board, !player),
+        is_down_prevented: prelude.is_down_prevented,
+    };
+
+    let own_workers = check_board.workers[player as usize] & BitBoard::MAIN_SECTION_MASK;
+    let all_blockers = own_workers
+        | check_board.workers[!player as usize]
+        | prelude.other_god.get_frozen_mask(&check_board, !player);
+
+    let acting_workers = if prelude.is_against_hypnus {
+        hypnus_moveable_worker_filter(&check_board, own_workers)
+    } else {
+        own_workers
+    };
+
+    for worker in acting_workers {
+        let blockers = all_blockers ^ BitBoard::as_mask(worker);
+        if rules.reach(worker, blockers).wins.is_not_empty() {
+            return true;
+        }
+    }
+
+    false
+}
+
+pub(super) fn triton_move_gen<const F: MoveGenFlags, const MUST_CLIMB: bool>(
+    state: &FullGameState,
+    player: Player,
+    key_squares: BitBoard,
+) -> Vec<ScoredMove> {
+    let mut result = persephone_check_result!(triton_move_gen, state: state, player: player, key_squares: key_squares, MUST_CLIMB: MUST_CLIMB);
+
+    let prelude = get_generator_prelude_state::<F>(state, player, key_squares);
+    let rules = TritonMoveRules::from_prelude(&prelude);
+
+    for worker_start_pos in prelude.acting_workers {
+        let worker_start_state = get_worker_start_move_state(&prelude, worker_start_pos);
+        let blockers = prelude.all_workers_and_frozen_mask ^ worker_start_state.worker_start_mask;
+        let reach = rules.reach(worker_start_pos, blockers);
+
+        let wins = restrict_moves_by_affinity_area(
+            worker_start_state.worker_start_mask,
+            reach.wins,
+            prelude.affinity_area,
+        );
+        if push_winning_moves::<F, TritonMove, _>(
+            &mut result,
+            worker_start_pos,
+            wins,
+            TritonMove::new_winning_move,
+        ) {
+            return result;
+        }
+
+        if is_mate_only::<F>() {
+            continue;
+        }
+
+        let mut ends = restrict_moves_by_affinity_area(
+            worker_start_state.worker_start_mask,
+            reach.ends,
+            prelude.affinity_area,
+        );
+        if MUST_CLIMB {
+            ends &= prelude.board.height_map[worker_start_state.worker_start_height];
+        }
+
+        for worker_end_pos in ends {
+            let worker_end_height = prelude.board.get_height(worker_end_pos);
+            let worker_end_move_state = WorkerEndMoveState {
+                worker_end_pos,
+                worker_end_mask: BitBoard::as_mask(worker_end_pos),
+                worker_end_height,
+                is_improving: worker_end_height > worker_start_state.worker_start_height,
+                is_now_lvl_2: (worker_end_height == 2) as u32,
+            };
+            let worker_next_build_state = get_worker_next_build_state::<F>(
+                &prelude,
+                &worker_start_state,
+                &worker_end_move_state,
+            );
+            let move_mask =
+                worker_start_state.worker_start_mask ^ worker_end_move_state.worker_end_mask;
+
+            for worker_build_pos in worker_next_build_state.narrowed_builds {
+                let new_action =
+                    TritonMove::new_basic_move(worker_start_pos, worker_end_pos, worker_build_pos);
+                let is_check = is_include_score::<F>()
+                    && is_triton_check_after_move(&prelude, player, move_mask, worker_build_pos);
+
+                result.push(build_scored_move::<F, _>(
+                    new_action,
+                    is_check,
+                    worker_end_move_state.is_improving,
+                ))
+            }
+        }
+    }
+
+    result
+}
+
+pub const fn build_triton() -> GodPower {
+    god_power(
+        GodName::Triton,
+        build_god_power_movers!(triton_move_gen),
+        build_god_power_actions::<TritonMove>(),
+        6620784932183418729,
+        11378902611836129507,
+    )
+    .with_nnue_god_name(GodName::Mortal)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{consistency_checker::consistency_check, fen::parse_fen};
+
+    use super::*;
+
+    fn triton_moves(fen: &str) -> Vec<TritonMove> {
+        let state = parse_fen(fen).unwrap();
+        let triton = GodName::Triton.to_power();
+        triton
+            .get_all_moves(&state, Player::One)
+            .into_iter()
+            .map(|m| m.action.into())
+            .collect()
+    }
+
+    #[test]
+    fn test_triton_chains_along_perimeter() {
+        // Walled into the top row, Triton can run the whole row in one turn
+        let moves = triton_moves("0000044444000000000000000/1/triton:A5/mortal:A1,B1");
+        let ends: BitBoard = moves.iter().fold(BitBoard::EMPTY, |acc, m| {
+            acc | m.move_to_position().to_board()
+        });
+
+        assert_eq!(
+            ends,
+            Square::B5.to_board()
+                | Square::C5.to_board()
+                | Square::D5.to_board()
+                | Square::E5.to_board()
+        );
+    }
+
+    #[test]
+    fn test_triton_stops_in_the_middle() {
+        // Moving into the middle ends the chain, so C4 can't be reached through B4 or B3
+        let moves = triton_moves("4444400044004440444400444/1/triton:A4/mortal:A1,B1");
+        let ends: BitBoard = moves.iter().fold(BitBoard::EMPTY, |acc, m| {
+            acc | m.move_to_position().to_board()
+        });
+
+        assert_eq!(
+            ends,
+            Square::B4.to_board()
+                | Square::A3.to_board()
+                | Square::B3.to_board()
+                | Square::A2.to_board()
+        );
+    }
+
+    #[test]
+    fn test_triton_perimeter_loop_back_to_start() {
+        // Triton can run A5 > A4 > A5, but ending where it started doesn't count as a move
+        let moves = triton_moves("0444404444444444444400444/1/triton:A5/mortal:A1,B1");
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].move_to_position(), Square::A4);
+        assert_eq!(moves[0].build_position(), Square::A5);
+    }
+
+    #[test]
+    fn test_triton_wins_mid_chain() {
+        // Climb 1 > 2 > 3 along the perimeter from a ground level start
+        let fen = "0123444444000000000000000/1/triton:A5/mortal:A1,B1";
+        let state = parse_fen(fen).unwrap();
+        let triton = GodName::Triton.to_power();
+        let wins = triton.get_winning_moves(&state, Player::One);
+        assert_eq!(wins.len(), 1);
+
+        let win: TritonMove = wins[0].action.into();
+        assert_eq!(win.move_to_position(), Square::D5);
+
+        let actions = win.move_to_actions(&state.board, Player::One, GodName::Mortal.to_power());
+        assert_eq!(
+            actions,
+            vec![vec![
+                PartialAction::SelectWorker(Square::A5),
+                PartialAction::MoveWorker(Square::B5.into()),
+                PartialAction::MoveWorker(Square::C5.into()),
+                PartialAction::MoveWorker(Square::D5.into()),
+            ]]
+        );
+
+        assert!(consistency_check(&state).is_ok());
+    }
+
+    #[test]
+    fn test_triton_cant_win_through_middle() {
+        // The level 2 square is in the middle, so the chain ends there
+        let fen = "0000012000003000000000000/1/triton:A5/mortal:A1,B1";
+        let state = parse_fen(fen).unwrap();
+        let triton = GodName::Triton.to_power();
+        assert_eq!(triton.get_winning_moves(&state, Player::One).len(), 0);
+    }
+
+    #[test]
+    fn test_triton_detects_chain_checks() {
+        let fen = "0122044444000000000000000/1/triton:A5/mortal:A1,B1";
+        let state = parse_fen(fen).unwrap();
+        let triton = GodName::Triton.to_power();
+
+        let mut checks: Vec<(Square, Square)> = triton
+            .get_moves_for_search(&state, Player::One)
+            .into_iter()
+            .filter(|m| m.action.get_is_check())
+            .map(|m| {
+                let m: TritonMove = m.action.into();
+                (m.move_to_position(), m.build_position())
+            })
+            .collect();
+        checks.sort();
+
+        assert_eq!(
+            checks,
+            vec![(Square::C5, Square::D5), (Square::D5, Square::C5)]
+        );
+        assert!(consistency_check(&state).is_ok());
+    }
+}
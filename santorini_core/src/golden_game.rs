@@ -0,0 +1,184 @@
+use std::fmt;
+
+use crate::{
+    board::FullGameState, fen::game_state_to_fen, hashing::compute_hash_from_scratch,
+    player::Player,
+};
+
+/// A recorded game, replayed by the golden game regression suite under `tests/golden_games/` to
+/// catch changes to move generation. Stored as text, one item per line:
+///
+/// ```text
+/// # Free form comments
+/// fen <starting fen>
+/// move <move, as in MoveDescription::action_str>
+/// winner <1|2>
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoldenGame {
+    pub comments: Vec<String>,
+    pub start_state: FullGameState,
+    /// Every move of the game, as in `MoveDescription::action_str`
+    pub moves: Vec<String>,
+    pub winner: Player,
+}
+
+fn parse_player(s: &str) -> Result<Player, String> {
+    match s {
+        "1" => Ok(Player::One),
+        "2" => Ok(Player::Two),
+        _ => Err(format!("Invalid player: {}", s)),
+    }
+}
+
+fn player_str(player: Player) -> &'static str {
+    match player {
+        Player::One => "1",
+        Player::Two => "2",
+    }
+}
+
+/// Checks that `state` is well formed, that its hash matches one computed from scratch, and that
+/// it survives a round trip through its FEN
+fn check_state(state: &FullGameState) -> Result<(), String> {
+    state.representation_err()?;
+
+    let scratch_hash = compute_hash_from_scratch(state);
+    if state.board.hash != scratch_hash {
+        return Err(format!(
+            "Hash {} doesn't match {} computed from scratch",
+            state.board.hash, scratch_hash
+        ));
+    }
+
+    let fen = game_state_to_fen(state);
+    let parsed = FullGameState::try_from(fen.as_str())
+        .map_err(|err| format!("Couldn't parse own FEN {}: {}", fen, err))?;
+    if &parsed != state || parsed.board.hash != state.board.hash {
+        return Err(format!("FEN {} doesn't round trip: {:?}", fen, parsed));
+    }
+    Ok(())
+}
+
+impl GoldenGame {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut comments = Vec::new();
+        let mut start_state = None;
+        let mut moves = Vec::new();
+        let mut winner = None;
+
+        for (line_idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                comments.push(comment.trim().to_owned());
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            let line_err = |err: String| format!("Line {}: {}", line_idx + 1, err);
+            match key {
+                "fen" => {
+                    let state = FullGameState::try_from(value).map_err(line_err)?;
+                    start_state = Some(state);
+                }
+                "move" => moves.push(value.to_owned()),
+                "winner" => winner = Some(parse_player(value).map_err(line_err)?),
+                _ => return Err(line_err(format!("Unknown key: {}", key))),
+            }
+        }
+
+        Ok(GoldenGame {
+            comments,
+            start_state: start_state.ok_or("Missing fen")?,
+            moves,
+            winner: winner.ok_or("Missing winner")?,
+        })
+    }
+
+    /// Plays every move with the current move generation, checking each position along the way.
+    /// Returns the final position, which must be won by the recorded winner
+    pub fn replay(&self) -> Result<FullGameState, String> {
+        let mut state = self.start_state.clone();
+        check_state(&state).map_err(|err| format!("Starting position: {}", err))?;
+
+        for (move_idx, move_str) in self.moves.iter().enumerate() {
+            let move_err = |err: String| format!("Move {} ({}): {}", move_idx + 1, move_str, err);
+            if let Some(winner) = state.get_winner() {
+                return Err(move_err(format!("{:?} already won", winner)));
+            }
+
+            let descriptions = state.describe_moves_with::<false>();
+            let Some(description) = descriptions
+                .into_iter()
+                .find(|description| &description.action_str == move_str)
+            else {
+                return Err(move_err(format!(
+                    "Not a legal move in {}",
+                    game_state_to_fen(&state)
+                )));
+            };
+
+            state = description.next_state;
+            check_state(&state).map_err(move_err)?;
+        }
+
+        match state.get_winner() {
+            Some(winner) if winner == self.winner => Ok(state),
+            winner => Err(format!(
+                "Expected {:?} to win, but the game ended with winner {:?}",
+                self.winner, winner
+            )),
+        }
+    }
+}
+
+impl fmt::Display for GoldenGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "# {}", comment)?;
+        }
+        writeln!(f, "fen {}", game_state_to_fen(&self.start_state))?;
+        for move_str in &self.moves {
+            writeln!(f, "move {}", move_str)?;
+        }
+        writeln!(f, "winner {}", player_str(self.winner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_game_round_trip() {
+        let text = "# A short game\n\
+            fen 0000000000000000000000000/1/mortal:A1,B2/mortal:C3,D4\n\
+            move A1>A2^A1\n\
+            winner 2\n";
+        let game = GoldenGame::parse(text).unwrap();
+        assert_eq!(game.comments, vec!["A short game"]);
+        assert_eq!(game.moves, vec!["A1>A2^A1"]);
+        assert_eq!(game.winner, Player::Two);
+        assert_eq!(GoldenGame::parse(&game.to_string()).unwrap(), game);
+
+        // The game isn't over, so it can't replay
+        assert!(game.replay().unwrap_err().contains("Expected"));
+    }
+
+    #[test]
+    fn test_golden_game_rejects_illegal_moves() {
+        let text = "fen 0000000000000000000000000/1/mortal:A1,B2/mortal:C3,D4\n\
+            move A1>C3^A1\n\
+            winner 1\n";
+        let err = GoldenGame::parse(text).unwrap().replay().unwrap_err();
+        assert!(
+            err.starts_with("Move 1 (A1>C3^A1): Not a legal move"),
+            "{}",
+            err
+        );
+    }
+}
@@ -325,3 +325,47 @@ pub(crate) fn compute_hash_from_scratch_for_board(
 
     result
 }
+
+/// Used by the `verify_hash` feature to check that an incremental update changed a board's hash
+/// by exactly as much as recomputing it from scratch would.
+/// Only compares deltas, so it works on bare boards that don't know their base hash.
+#[cfg(feature = "verify_hash")]
+pub(crate) struct HashDeltaCheck {
+    hash: HashType,
+    scratch: HashType,
+}
+
+#[cfg(feature = "verify_hash")]
+impl HashDeltaCheck {
+    pub(crate) fn new(board: &BoardState) -> Self {
+        Self {
+            hash: board.hash,
+            scratch: compute_hash_from_scratch_for_board(board, 0),
+        }
+    }
+
+    pub(crate) fn verify(self, board: &BoardState, label: &str) {
+        let incremental_delta = self.hash ^ board.hash;
+        let scratch_delta = self.scratch ^ compute_hash_from_scratch_for_board(board, 0);
+        assert_eq!(
+            incremental_delta,
+            scratch_delta,
+            "{label} updated the hash incorrectly (diff: {:064b}). {:?}",
+            incremental_delta ^ scratch_delta,
+            board
+        );
+    }
+}
+
+/// Used by the `verify_hash` feature to check a full state's hash against one computed from scratch
+#[cfg(feature = "verify_hash")]
+pub(crate) fn verify_full_hash(state: &FullGameState, label: &str) {
+    let expected = compute_hash_from_scratch(state);
+    assert_eq!(
+        state.board.hash,
+        expected,
+        "{label} left an incorrect hash (diff: {:064b}). {:?}",
+        state.board.hash ^ expected,
+        state
+    );
+}
@@ -5,9 +5,13 @@ pub mod board;
 pub mod consistency_checker;
 pub mod direction;
 pub mod engine;
+pub mod eval;
+pub mod explorer;
 pub mod fen;
+pub mod golden_game;
 pub mod gods;
 pub mod hashing;
+pub mod logging;
 pub mod matchup;
 #[cfg(test)]
 pub mod move_verifier;
@@ -15,15 +19,26 @@ pub mod move_container;
 pub mod move_picker;
 pub mod nnue;
 pub mod placement;
+pub mod placement_book;
 pub mod player;
+pub mod prove;
 pub mod pretty_board;
+pub mod quick_verdict;
 pub mod random_utils;
+pub mod refute;
+pub mod report;
+pub mod review;
 pub mod search;
+pub mod search_observer;
 pub mod search_terminators;
 pub mod square;
+pub mod svg;
+pub mod tablebase;
+pub mod time_manager;
 pub mod transposition_table;
 pub mod uci_types;
 pub mod utils;
+pub mod win_reason;
 
 macro_rules! transmute_enum_masked {
     ($x:expr, $mask:expr) => {
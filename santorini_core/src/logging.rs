@@ -0,0 +1,36 @@
+use log::LevelFilter;
+
+/// Logging flags shared by every binary. Logs always go to stderr (or a file), so stdout stays
+/// free for protocol messages and reports.
+#[derive(clap::Args, Clone, Debug)]
+pub struct LogArgs {
+    /// Lowest level to log: off, error, warn, info, debug or trace.
+    /// RUST_LOG overrides this and also accepts per-module filters, e.g.
+    /// RUST_LOG=info,santorini_core::search=trace.
+    /// Trace statements are compiled out of release builds
+    #[arg(long, default_value_t = LevelFilter::Info, global = true)]
+    pub log_level: LevelFilter,
+}
+
+impl Default for LogArgs {
+    fn default() -> Self {
+        Self {
+            log_level: LevelFilter::Info,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LogArgs {
+    /// A logger filtered by `--log-level` and then RUST_LOG, writing to stderr
+    pub fn builder(&self) -> env_logger::Builder {
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(self.log_level).parse_default_env();
+        builder
+    }
+
+    /// Installs `builder` as the global logger
+    pub fn init(&self) {
+        self.builder().init();
+    }
+}
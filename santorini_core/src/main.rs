@@ -4,34 +4,28 @@ use std::{
     time::{Duration, Instant},
 };
 
+use clap::Parser;
 use santorini_core::{
     board::FullGameState,
     engine::EngineThreadWrapper,
-    gods::PartialAction,
+    logging::LogArgs,
     search::BestSearchResult,
-    uci_types::{
-        BestMoveMeta, BestMoveOutput, EngineOutput, NextMovesOutput, NextStateOutput, StartedOutput,
-    },
-    utils::timestamp_string,
+    uci_types::{BestMoveMeta, BestMoveOutput, EngineOutput, NextMovesOutput, StartedOutput},
+    utils::find_action_path,
+    win_reason::get_win_reason,
 };
 
-fn find_action_path(
-    start_state: &FullGameState,
-    destination_state: &FullGameState,
-) -> Option<Vec<PartialAction>> {
-    let all_child_states = start_state.get_next_states_interactive();
-    for full_child in all_child_states {
-        if &full_child.state == destination_state {
-            return Some(full_child.actions);
-        }
-    }
-    None
+/// Runs the json line protocol on stdin/stdout
+#[derive(Debug, Parser)]
+struct EngineArgs {
+    #[command(flatten)]
+    log: LogArgs,
 }
 
 fn try_emit_message(message: &EngineOutput) {
     match serde_json::to_string(message) {
         Ok(json) => println!("{}", json),
-        Err(e) => eprintln!("Error serializing message: {}", e),
+        Err(e) => log::error!("Error serializing message: {}", e),
     }
 }
 
@@ -56,14 +50,15 @@ fn handle_command(
         }
         "ping" => Ok(Some("pong".to_owned())),
         "stop" => {
-            eprintln!("{}, stop", timestamp_string());
+            log::info!("stop");
 
             match engine.stop() {
-                Ok(best_move) => Err(format!(
-                    "Stopping with best move: {:?}",
-                    best_move.child_state
-                )),
-                Err(e) => Err(e),
+                Ok(Some(best_move)) => {
+                    log::info!("Stopped with best move: {:?}", best_move.child_state);
+                    Ok(None)
+                }
+                Ok(None) => Ok(None),
+                Err(e) => Err(e.to_string()),
             }
         }
         "set_position" => {
@@ -72,7 +67,7 @@ fn handle_command(
             }
 
             let fen = parts.remove(0);
-            eprintln!("{}, set_position: {:?}", timestamp_string(), fen);
+            log::info!("set_position: {}", fen);
 
             let state =
                 FullGameState::try_from(&fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
@@ -86,22 +81,19 @@ fn handle_command(
             let state_2 = state.clone();
 
             let callback = Arc::new(move |new_best_move: BestSearchResult| {
-                eprintln!("best move: {:?}", new_best_move);
-                let Some(action_path) = find_action_path(&state_2, &new_best_move.child_state)
-                else {
-                    eprintln!("from move:");
-                    state_2.print_to_console();
-                    eprintln!("to move:");
-                    new_best_move.child_state.print_to_console();
-
-                    panic!(
-                        "Found new best move but couldn't resolve path: {}: {:?} -> {:?}",
-                        new_best_move.action_str,
-                        state_2,
-                        new_best_move.child_state
-                    );
-                };
-
+                log::debug!("best move: {:?}", new_best_move);
+                let action_path = find_action_path(&state_2, &new_best_move.child_state)
+                    .unwrap_or_else(|err| {
+                        log::warn!(
+                            "couldn't resolve actions for best move {}: {}",
+                            new_best_move.action_str, err
+                        );
+                        Vec::new()
+                    });
+
+                let elapsed_seconds = start_time.elapsed().as_secs_f32();
+                let nodes_per_second = new_best_move.nodes_per_second(elapsed_seconds);
+                let tt_hit_rate = new_best_move.tt_hit_rate();
                 let output = EngineOutput::BestMove(BestMoveOutput {
                     original_str: Some(fen.clone()),
                     start_state: state_2.clone(),
@@ -111,15 +103,20 @@ fn handle_command(
                         score: new_best_move.score,
                         calculated_depth: new_best_move.depth,
                         nodes_visited: Some(new_best_move.nodes_visited),
-                        elapsed_seconds: start_time.elapsed().as_secs_f32(),
+                        elapsed_seconds,
+                        nodes_per_second,
+                        seldepth: Some(new_best_move.seldepth),
+                        tt_hit_rate,
                         actions: action_path,
                         action_str: Some(new_best_move.action_str),
+                        win_reason: get_win_reason(&state_2, &new_best_move.child_state),
+                        god_data: new_best_move.child_state.god_data_items(),
                     },
                 });
 
                 try_emit_message(&output);
             });
-            engine.start_search(&state, Some(callback))?;
+            engine.start_search(&state, Some(callback), None)?;
             Ok(None)
         }
         "next_moves" => {
@@ -136,19 +133,7 @@ fn handle_command(
                 return Err("Cannot look for next moves from terminal state".to_owned());
             }
 
-            let child_states = state.get_next_states_interactive();
-
-            let output = EngineOutput::NextMoves(NextMovesOutput {
-                original_str: Some(fen),
-                start_state: state,
-                next_states: child_states
-                    .into_iter()
-                    .map(|full_choice| NextStateOutput {
-                        next_state: full_choice.state,
-                        actions: full_choice.actions,
-                    })
-                    .collect(),
-            });
+            let output = EngineOutput::NextMoves(NextMovesOutput::new(Some(fen), state));
 
             serde_json::to_string(&output)
                 .map(|v| Some(v))
@@ -159,6 +144,10 @@ fn handle_command(
 }
 
 fn main() {
+    let args = EngineArgs::parse();
+    // Logs go to stderr, so stdout only ever carries protocol messages
+    args.log.init();
+
     let (cli_command_sender, cli_command_receiver) = mpsc::channel();
 
     let _io_thread = thread::spawn(move || {
@@ -171,12 +160,12 @@ fn main() {
 
     let mut engine = EngineThreadWrapper::new();
 
-    try_emit_message(&EngineOutput::Started(StartedOutput {}));
+    try_emit_message(&EngineOutput::Started(StartedOutput::current()));
 
     loop {
         let raw_cmd = cli_command_receiver.recv().unwrap();
         if raw_cmd.trim().is_empty() {
-            println!("empty command");
+            log::warn!("empty command");
             thread::sleep(Duration::from_millis(10));
             continue;
         }
@@ -188,7 +177,7 @@ fn main() {
                 // No response to print
             }
             Err(err) => {
-                eprintln!("Error: {}", err.trim());
+                log::error!("{}", err.trim());
             }
         }
     }
@@ -1,14 +1,18 @@
 use std::{
     cell::{LazyCell, OnceCell},
     collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, LazyLock, RwLock},
 };
 
-use rand::seq::IndexedRandom;
+use rand::{Rng, seq::IndexedRandom};
+use serde::Deserialize;
 
 use crate::{
     board::GodPair,
-    gods::{ALL_GODS_BY_ID, GodName, StaticGod, WIP_GODS},
+    gods::{GodName, GodNameParseError, StaticGod, WIP_GODS},
     player::Player,
+    search::Heuristic,
 };
 
 /// A CLI argument value that is either a single god name or "wip" (expands to all WIP gods).
@@ -19,7 +23,7 @@ pub enum GodSelector {
 }
 
 impl std::str::FromStr for GodSelector {
-    type Err = strum::ParseError;
+    type Err = GodNameParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.eq_ignore_ascii_case("wip") {
@@ -59,6 +63,26 @@ impl std::str::FromStr for MatchupPair {
     }
 }
 
+/// A CLI argument value representing a sampling weight for a god, parsed as "god:weight".
+#[derive(Clone, Debug)]
+pub struct GodWeight(pub GodName, pub f32);
+
+impl std::str::FromStr for GodWeight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (a, b) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected god:weight, got '{s}'"))?;
+        let god: GodName = a.parse().map_err(|e| format!("bad god '{a}': {e}"))?;
+        let weight: f32 = b.parse().map_err(|e| format!("bad weight '{b}': {e}"))?;
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(format!("weight must be a non-negative number, got '{b}'"));
+        }
+        Ok(GodWeight(god, weight))
+    }
+}
+
 #[derive(clap::Args, Clone, Debug, Default)]
 pub struct MatchupArgs {
     /// Restrict Player 1 to these gods (accepts god names or "wip")
@@ -81,6 +105,14 @@ pub struct MatchupArgs {
     #[arg(short = 'e', long, num_args = 1..)]
     pub exclude: Vec<GodName>,
 
+    /// Exclude specific matchups in either order as god1:god2 (e.g. --exclude-pair apollo:pan)
+    #[arg(long, num_args = 1..)]
+    pub exclude_pair: Vec<MatchupPair>,
+
+    /// Sampling weights as god:weight (e.g. --weight triton:4). Unlisted gods have weight 1
+    #[arg(short = 'w', long, num_args = 1..)]
+    pub weight: Vec<GodWeight>,
+
     /// Disallow mirror matchups (default: mirrors allowed)
     #[arg(long)]
     pub no_mirror: bool,
@@ -123,7 +155,11 @@ impl MatchupArgs {
             selector = selector.with_extra_matchup(pair.0);
         }
 
+        let excluded: Vec<Matchup> = self.exclude_pair.iter().map(|pair| pair.0).collect();
+        let weights: Vec<(GodName, f32)> = self.weight.iter().map(|w| (w.0, w.1)).collect();
         selector
+            .with_excluded_pairs(&excluded)
+            .with_weights(&weights)
     }
 }
 
@@ -146,6 +182,7 @@ pub const BANNED_MATCHUPS: LazyCell<HashMap<Matchup, BannedReason>> = LazyCell::
     add_matchup(GodName::Aphrodite, GodName::Urania, BannedReason::Game);
 
     add_matchup(GodName::Harpies, GodName::Hermes, BannedReason::Game);
+    add_matchup(GodName::Harpies, GodName::Triton, BannedReason::Game);
 
     // Harpies/Maenads seems fine? Well we implemented it, anyway
 
@@ -170,14 +207,185 @@ pub const BANNED_MATCHUPS: LazyCell<HashMap<Matchup, BannedReason>> = LazyCell::
     set
 });
 
+/// How a lopsided matchup is evened out, instead of being banned
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchupHandicap {
+    /// Score offset in favour of player 1, added to every static eval during search
+    pub komi: Heuristic,
+    /// The player who places their workers last, whatever the gods' own placement rules say
+    pub placement_advantage: Option<Player>,
+}
+
+impl MatchupHandicap {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The same handicap, for the matchup with the players swapped
+    pub fn flip(&self) -> Self {
+        Self {
+            komi: -self.komi,
+            placement_advantage: self.placement_advantage.map(|player| !player),
+        }
+    }
+}
+
+/// Which matchups are banned, and handicaps for matchups that are allowed but lopsided.
+/// The default policy is exactly `BANNED_MATCHUPS`. A different policy can be loaded from yaml at
+/// runtime with `load_matchup_policy` and installed with `set_matchup_policy`, e.g:
+/// ```yaml
+/// # Banned in both orders, on top of BANNED_MATCHUPS
+/// banned:
+///   - nemesis:gaea
+/// # Allowed in both orders, even if banned by default.
+/// # komi is in favour of the first god, and defaults to 0. It used to be called eval_offset.
+/// # placement_advantage is the player (1 or 2) who places last
+/// unbalanced:
+///   - gods: ares:ares
+///   - gods: hades:pan
+///     komi: 300
+///     placement_advantage: 1
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchupPolicy {
+    banned: HashMap<Matchup, BannedReason>,
+    handicaps: HashMap<Matchup, MatchupHandicap>,
+}
+
+impl Default for MatchupPolicy {
+    fn default() -> Self {
+        Self {
+            banned: BANNED_MATCHUPS.clone(),
+            handicaps: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MatchupPolicyFile {
+    #[serde(default)]
+    banned: Vec<String>,
+    #[serde(default)]
+    unbalanced: Vec<UnbalancedMatchupEntry>,
+}
+
+#[derive(Deserialize)]
+struct UnbalancedMatchupEntry {
+    gods: String,
+    #[serde(default, alias = "eval_offset")]
+    komi: Heuristic,
+    #[serde(default)]
+    placement_advantage: Option<usize>,
+}
+
+impl UnbalancedMatchupEntry {
+    fn handicap(&self) -> Result<MatchupHandicap, String> {
+        let placement_advantage = match self.placement_advantage {
+            None => None,
+            Some(1) => Some(Player::One),
+            Some(2) => Some(Player::Two),
+            Some(other) => {
+                return Err(format!(
+                    "Invalid placement_advantage for {}: {}. Expected 1 or 2",
+                    self.gods, other
+                ));
+            }
+        };
+        Ok(MatchupHandicap {
+            komi: self.komi,
+            placement_advantage,
+        })
+    }
+}
+
+impl MatchupPolicy {
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let file: MatchupPolicyFile =
+            serde_yaml::from_str(yaml).map_err(|e| format!("Invalid matchup policy: {e}"))?;
+
+        let mut policy = Self::default();
+        for pair in &file.banned {
+            let matchup = pair.parse::<MatchupPair>()?.0;
+            policy.banned.insert(matchup, BannedReason::Game);
+            policy.banned.insert(matchup.flip(), BannedReason::Game);
+        }
+        for entry in &file.unbalanced {
+            let matchup = entry.gods.parse::<MatchupPair>()?.0;
+            policy.banned.remove(&matchup);
+            policy.banned.remove(&matchup.flip());
+            policy.handicaps.remove(&matchup.flip());
+            let handicap = entry.handicap()?;
+            if !handicap.is_empty() {
+                policy.handicaps.insert(matchup, handicap);
+            }
+        }
+
+        Ok(policy)
+    }
+
+    pub fn banned_reason(&self, matchup: &Matchup) -> Option<BannedReason> {
+        self.banned.get(matchup).copied()
+    }
+
+    pub fn banned_matchups(&self) -> impl Iterator<Item = &Matchup> {
+        self.banned.keys()
+    }
+
+    /// How this matchup is evened out. Empty for balanced matchups
+    pub fn handicap(&self, matchup: &Matchup) -> MatchupHandicap {
+        if let Some(handicap) = self.handicaps.get(matchup) {
+            *handicap
+        } else if let Some(handicap) = self.handicaps.get(&matchup.flip()) {
+            handicap.flip()
+        } else {
+            MatchupHandicap::default()
+        }
+    }
+
+    /// Every matchup with a handicap, in both orders
+    pub fn handicapped_matchups(&self) -> impl Iterator<Item = (Matchup, MatchupHandicap)> + '_ {
+        self.handicaps.iter().flat_map(|(matchup, handicap)| {
+            [(*matchup, *handicap), (matchup.flip(), handicap.flip())]
+        })
+    }
+
+    /// Score offset in favour of player 1 for this matchup. 0 for balanced matchups
+    pub fn komi(&self, matchup: &Matchup) -> Heuristic {
+        self.handicap(matchup).komi
+    }
+}
+
+static MATCHUP_POLICY: LazyLock<RwLock<Arc<MatchupPolicy>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(MatchupPolicy::default())));
+
+pub fn load_matchup_policy(path: impl AsRef<Path>) -> Result<MatchupPolicy, String> {
+    let path = path.as_ref();
+    let yaml = std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading matchup policy {:?}: {e}", path))?;
+    MatchupPolicy::from_yaml(&yaml)
+}
+
+/// The process wide matchup policy
+pub fn matchup_policy() -> Arc<MatchupPolicy> {
+    MATCHUP_POLICY.read().unwrap().clone()
+}
+
+pub fn set_matchup_policy(policy: MatchupPolicy) {
+    *MATCHUP_POLICY.write().unwrap() = Arc::new(policy);
+}
+
 pub fn matchup_banned_reason(matchup: &Matchup) -> Option<BannedReason> {
-    BANNED_MATCHUPS.get(matchup).copied()
+    matchup_policy().banned_reason(matchup)
 }
 
 pub fn is_matchup_banned(matchup: &Matchup) -> bool {
     matchup_banned_reason(matchup).is_some()
 }
 
+pub fn matchup_handicap(matchup: &Matchup) -> MatchupHandicap {
+    matchup_policy().handicap(matchup)
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
 pub struct Matchup {
     pub gods: [GodName; 2],
@@ -237,11 +445,15 @@ pub struct MatchupSelector {
     can_swap: bool,
     // Are mirrir matches allowed (ex: Apollo vs Apollo)?
     can_mirror: bool,
+    // Matchups to skip in either order, on top of BANNED_MATCHUPS
+    excluded_pairs: Vec<Matchup>,
+    // Sampling weight per god. Gods that aren't listed have weight 1
+    god_weights: HashMap<GodName, f32>,
     all_matchups: OnceCell<Vec<Matchup>>,
 }
 
 fn _all_god_names() -> Vec<GodName> {
-    ALL_GODS_BY_ID.iter().map(|g| g.god_name).collect()
+    GodName::all().collect()
 }
 
 impl Default for MatchupSelector {
@@ -251,6 +463,8 @@ impl Default for MatchupSelector {
             extra_matchups: Vec::new(),
             can_swap: false,
             can_mirror: true,
+            excluded_pairs: Vec::new(),
+            god_weights: HashMap::new(),
             all_matchups: OnceCell::new(),
         }
     }
@@ -283,6 +497,8 @@ impl MatchupSelector {
                 res.insert(*m);
             }
 
+            res.retain(|m| !self.excluded_pairs.iter().any(|e| e.is_same_gods(m)));
+
             let mut res_vec: Vec<Matchup> = res.into_iter().collect();
             res_vec.sort();
             res_vec
@@ -290,12 +506,26 @@ impl MatchupSelector {
     }
 
     pub fn get(&self) -> Matchup {
+        self.sample(&mut rand::rng())
+    }
+
+    /// Picks a matchup with probability proportional to `matchup_weight`
+    pub fn sample(&self, rng: &mut impl Rng) -> Matchup {
         *self
             .computed_matchups()
-            .choose(&mut rand::rng())
+            .choose_weighted(rng, |m| self.matchup_weight(m))
             .expect("no valid matchups in selector")
     }
 
+    /// The product of the weights of both gods in the matchup
+    pub fn matchup_weight(&self, matchup: &Matchup) -> f32 {
+        matchup
+            .gods
+            .iter()
+            .map(|g| self.god_weights.get(g).copied().unwrap_or(1.0))
+            .product()
+    }
+
     pub fn get_all(&self) -> Vec<Matchup> {
         self.computed_matchups().to_vec()
     }
@@ -335,6 +565,23 @@ impl MatchupSelector {
         res
     }
 
+    pub fn with_excluded_pairs(mut self, matchups: &[Matchup]) -> Self {
+        self.excluded_pairs.extend_from_slice(matchups);
+        self.all_matchups.take();
+        self
+    }
+
+    pub fn with_weights(mut self, weights: &[(GodName, f32)]) -> Self {
+        for &(god_name, weight) in weights {
+            assert!(
+                weight.is_finite() && weight >= 0.0,
+                "invalid weight {weight} for {god_name}"
+            );
+            self.god_weights.insert(god_name, weight);
+        }
+        self
+    }
+
     pub fn with_can_swap(self) -> Self {
         self.with_can_swap_option(true)
     }
@@ -356,8 +603,74 @@ impl MatchupSelector {
     }
 }
 
+/// Samples matchups from a selector, dividing each matchup's weight by the number of games it has
+/// produced so far. Keeps a long run balanced across matchups even when some are slower to
+/// generate games than others.
+#[derive(Clone, Debug)]
+pub struct BalancedSampler {
+    /// Sorted by matchup
+    entries: Vec<BalancedSamplerEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct BalancedSamplerEntry {
+    matchup: Matchup,
+    weight: f32,
+    games: usize,
+}
+
+impl BalancedSampler {
+    /// Fails if no matchup can ever be sampled, because there are none or they all weigh 0
+    pub fn new(selector: &MatchupSelector) -> Result<Self, String> {
+        let entries: Vec<BalancedSamplerEntry> = selector
+            .get_all()
+            .into_iter()
+            .map(|matchup| BalancedSamplerEntry {
+                matchup,
+                weight: selector.matchup_weight(&matchup),
+                games: 0,
+            })
+            .collect();
+        if entries.is_empty() {
+            return Err("No matchups to sample from".to_owned());
+        }
+        if !entries.iter().any(|entry| entry.weight > 0.0) {
+            return Err("Every matchup has a weight of 0".to_owned());
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Matchup {
+        self.entries
+            .choose_weighted(rng, |entry| entry.weight / (1 + entry.games) as f32)
+            .expect("weights are checked in new")
+            .matchup
+    }
+
+    /// Records that a game was produced for this matchup. Unknown matchups are ignored
+    pub fn record_game(&mut self, matchup: Matchup) {
+        if let Ok(idx) = self.find(&matchup) {
+            self.entries[idx].games += 1;
+        }
+    }
+
+    pub fn games_for(&self, matchup: &Matchup) -> usize {
+        match self.find(matchup) {
+            Ok(idx) => self.entries[idx].games,
+            Err(_) => 0,
+        }
+    }
+
+    fn find(&self, matchup: &Matchup) -> Result<usize, usize> {
+        self.entries
+            .binary_search_by_key(matchup, |entry| entry.matchup)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
     use super::*;
 
     #[test]
@@ -501,4 +814,194 @@ mod tests {
         assert!("apollo".parse::<MatchupPair>().is_err());
         assert!("apollo:notagod".parse::<MatchupPair>().is_err());
     }
+
+    #[test]
+    fn excluded_pairs_removed_in_both_orders() {
+        let selector = MatchupSelector::default()
+            .with_exact_gods_for_player(Player::One, &[GodName::Apollo, GodName::Pan])
+            .with_exact_gods_for_player(Player::Two, &[GodName::Apollo, GodName::Pan])
+            .with_excluded_pairs(&[Matchup::new(GodName::Pan, GodName::Apollo)]);
+
+        let all = selector.get_all();
+        assert_eq!(all.len(), 2);
+        assert!(all.contains(&Matchup::new(GodName::Apollo, GodName::Apollo)));
+        assert!(all.contains(&Matchup::new(GodName::Pan, GodName::Pan)));
+    }
+
+    #[test]
+    fn weighted_sampling_distribution() {
+        let selector = MatchupSelector::default()
+            .with_exact_gods_for_player(Player::One, &[GodName::Apollo, GodName::Pan])
+            .with_exact_gods_for_player(Player::Two, &[GodName::Artemis])
+            .with_weights(&[(GodName::Apollo, 3.0)]);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let samples = 4000;
+        let apollo_count = (0..samples)
+            .filter(|_| selector.sample(&mut rng).gods[0] == GodName::Apollo)
+            .count();
+        let apollo_rate = apollo_count as f32 / samples as f32;
+        assert!(
+            (apollo_rate - 0.75).abs() < 0.03,
+            "expected ~75% apollo, got {apollo_rate}"
+        );
+    }
+
+    #[test]
+    fn zero_weight_is_never_sampled() {
+        let selector = MatchupSelector::default()
+            .with_exact_gods_for_player(Player::One, &[GodName::Apollo, GodName::Pan])
+            .with_exact_gods_for_player(Player::Two, &[GodName::Artemis])
+            .with_weights(&[(GodName::Pan, 0.0)]);
+        let mut rng = StdRng::seed_from_u64(2);
+
+        for _ in 0..1000 {
+            assert_eq!(selector.sample(&mut rng).gods[0], GodName::Apollo);
+        }
+    }
+
+    #[test]
+    fn balanced_sampler_rejects_unsampleable_selectors() {
+        let selector = MatchupSelector::default()
+            .with_exact_gods_for_player(Player::One, &[GodName::Apollo, GodName::Pan])
+            .with_exact_gods_for_player(Player::Two, &[GodName::Artemis]);
+        assert!(BalancedSampler::new(&selector).is_ok());
+
+        let all_zero = selector
+            .clone()
+            .with_weights(&[(GodName::Apollo, 0.0), (GodName::Pan, 0.0)]);
+        assert!(BalancedSampler::new(&all_zero).is_err());
+
+        let excluded = selector.with_excluded_pairs(&[
+            Matchup::new(GodName::Apollo, GodName::Artemis),
+            Matchup::new(GodName::Pan, GodName::Artemis),
+        ]);
+        assert!(BalancedSampler::new(&excluded).is_err());
+    }
+
+    #[test]
+    fn balanced_sampler_evens_out_counts() {
+        let selector = MatchupSelector::default()
+            .with_exact_gods_for_player(
+                Player::One,
+                &[GodName::Apollo, GodName::Pan, GodName::Atlas],
+            )
+            .with_exact_gods_for_player(Player::Two, &[GodName::Artemis]);
+        let mut sampler = BalancedSampler::new(&selector).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        // Start the run heavily skewed towards one matchup
+        let skewed = Matchup::new(GodName::Apollo, GodName::Artemis);
+        for _ in 0..500 {
+            sampler.record_game(skewed);
+        }
+
+        for _ in 0..3000 {
+            let matchup = sampler.sample(&mut rng);
+            sampler.record_game(matchup);
+        }
+
+        // 3500 games total, so a balanced run ends with ~1167 each
+        for matchup in selector.get_all() {
+            let games = sampler.games_for(&matchup);
+            assert!(
+                (1000..=1350).contains(&games),
+                "{matchup} produced {games} games"
+            );
+        }
+    }
+
+    #[test]
+    fn default_policy_matches_banned_matchups() {
+        let policy = MatchupPolicy::default();
+        let banned: HashSet<Matchup> = policy.banned_matchups().copied().collect();
+        let expected: HashSet<Matchup> = BANNED_MATCHUPS.keys().copied().collect();
+        assert_eq!(banned, expected);
+        assert_eq!(MatchupPolicy::from_yaml("{}").unwrap(), policy);
+    }
+
+    #[test]
+    fn policy_yaml_bans_and_unbans() {
+        let yaml = "
+banned:
+  - apollo:pan
+unbalanced:
+  - gods: ares:ares
+  - gods: hades:pan
+    eval_offset: 300
+";
+        let policy = MatchupPolicy::from_yaml(yaml).unwrap();
+
+        let apollo_pan = Matchup::new(GodName::Apollo, GodName::Pan);
+        assert_eq!(policy.banned_reason(&apollo_pan), Some(BannedReason::Game));
+        assert_eq!(
+            policy.banned_reason(&apollo_pan.flip()),
+            Some(BannedReason::Game)
+        );
+
+        let ares_ares = Matchup::new(GodName::Ares, GodName::Ares);
+        assert_eq!(policy.banned_reason(&ares_ares), None);
+        assert_eq!(policy.komi(&ares_ares), 0);
+
+        let hades_pan = Matchup::new(GodName::Hades, GodName::Pan);
+        assert_eq!(policy.banned_reason(&hades_pan), None);
+        assert_eq!(policy.banned_reason(&hades_pan.flip()), None);
+        assert_eq!(policy.komi(&hades_pan), 300);
+        assert_eq!(policy.komi(&hades_pan.flip()), -300);
+    }
+
+    #[test]
+    fn policy_yaml_handicaps() {
+        let yaml = "
+unbalanced:
+  - gods: hades:pan
+    komi: -200
+    placement_advantage: 1
+  - gods: harpies:hermes
+    placement_advantage: 2
+";
+        let policy = MatchupPolicy::from_yaml(yaml).unwrap();
+
+        let hades_pan = Matchup::new(GodName::Hades, GodName::Pan);
+        assert_eq!(
+            policy.handicap(&hades_pan),
+            MatchupHandicap {
+                komi: -200,
+                placement_advantage: Some(Player::One),
+            }
+        );
+        assert_eq!(
+            policy.handicap(&hades_pan.flip()),
+            MatchupHandicap {
+                komi: 200,
+                placement_advantage: Some(Player::Two),
+            }
+        );
+
+        let harpies_hermes = Matchup::new(GodName::Harpies, GodName::Hermes);
+        assert_eq!(policy.komi(&harpies_hermes), 0);
+        assert_eq!(
+            policy.handicap(&harpies_hermes.flip()).placement_advantage,
+            Some(Player::One)
+        );
+        assert_eq!(policy.handicapped_matchups().count(), 4);
+        assert!(
+            policy
+                .handicap(&Matchup::new(GodName::Mortal, GodName::Pan))
+                .is_empty()
+        );
+
+        assert!(
+            MatchupPolicy::from_yaml(
+                "unbalanced:\n  - gods: hades:pan\n    placement_advantage: 3\n"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn policy_yaml_rejects_bad_god() {
+        assert!(MatchupPolicy::from_yaml("banned:\n  - apollo:notagod\n").is_err());
+        assert!(MatchupPolicy::from_yaml("banned: 5").is_err());
+    }
 }
@@ -9,7 +9,7 @@ use crate::{
         },
     },
     player::Player,
-    search::Histories,
+    search::{Histories, RootMoveFilter},
 };
 
 pub const MAX_MOVE_COUNT: usize = 336;
@@ -34,6 +34,7 @@ pub struct MovePicker {
     pub stage: MovePickerStage,
     index: usize,
     key_squares: Option<BitBoard>,
+    root_move_filter: Option<RootMoveFilter>,
 }
 
 impl MovePicker {
@@ -53,11 +54,48 @@ impl MovePicker {
             stage: MovePickerStage::YieldTT,
             index: 0,
             key_squares,
+            root_move_filter: None,
         }
     }
 
+    /// Only yields moves allowed by `root_move_filter`. Meant for the root, where generating
+    /// every move is cheap
+    pub fn with_root_move_filter(mut self, root_move_filter: Option<RootMoveFilter>) -> Self {
+        if let Some(filter) = &root_move_filter {
+            self.tt_move = self.tt_move.filter(|action| filter.allows(*action));
+        }
+        self.root_move_filter = root_move_filter;
+        self
+    }
+
     fn _generate_moves(&mut self, state: &FullGameState) {
-        if let Some(key_squares) = self.key_squares {
+        if let Some(filter) = &self.root_move_filter {
+            // Search move generation stops at the first win and skips moves that don't block a
+            // threat, either of which could leave out every allowed move. So all moves are
+            // generated, and checks are tagged here instead
+            let other_god = state.gods[!self.player as usize];
+            self.move_list = self
+                .active_god
+                .get_all_moves(state, self.player)
+                .into_iter()
+                .filter(|scored| filter.allows(scored.action))
+                .map(|scored| {
+                    if scored.action.get_is_winning() {
+                        return ScoredMove::new_winning_move(scored.action);
+                    }
+                    let child_state = state.next_state(self.active_god, other_god, scored.action);
+                    if self
+                        .active_god
+                        .get_winning_moves(&child_state, self.player)
+                        .is_empty()
+                    {
+                        ScoredMove::new_improving_move(scored.action)
+                    } else {
+                        ScoredMove::new_checking_move(scored.action)
+                    }
+                })
+                .collect();
+        } else if let Some(key_squares) = self.key_squares {
             self.move_list =
                 self.active_god
                     .get_scored_blocker_moves(state, self.player, key_squares);
@@ -98,6 +136,14 @@ impl MovePicker {
             self._generate_moves(state);
         }
 
+        if self.root_move_filter.is_some() {
+            return self
+                .move_list
+                .iter()
+                .find(|scored| scored.action.get_is_winning())
+                .map(|scored| scored.action);
+        }
+
         // get_moves_for_search stops running once it sees a win, so if there is a win it'll be last
         if let Some(last_move) = self.move_list.last() {
             if last_move.get_is_winning() {
@@ -271,6 +271,14 @@ impl LabeledAccumulator {
         res
     }
 
+    /// An accumulator for `state` that evaluates each player's god with the matching entry of
+    /// `model_gods`, rather than its `model_god_name`. For comparing fallback models
+    pub fn new_with_models(state: &FullGameState, model_gods: [GodName; 2]) -> Self {
+        let mut res = Self::new_from_scratch(&state.board, model_gods[0], model_gods[1]);
+        res.calc_eval_delta(state);
+        res
+    }
+
     fn _apply_own_feature_set(&mut self) {
         for feature in &self.feature_set.ordered_features {
             self.accumulator.add_feature(*feature as usize);
@@ -434,6 +442,11 @@ impl Network {
     }
 }
 
+/// Whether the network has features for `god`, so that it can be evaluated as itself
+pub fn has_own_model(god: GodName) -> bool {
+    (god as usize) < NNUE_GOD_COUNT
+}
+
 pub const NNUE_MORPHEUS_MAX_BLOCKS_INCLUSIVE: u32 = 10;
 pub fn emit_god_data_features<Extractor: FnMut(FType)>(
     god: GodName,
@@ -570,7 +583,11 @@ pub fn build_feature_set(board: &BoardState, god1: GodName, god2: GodName) -> Fe
 
 #[cfg(test)]
 mod tests {
-    use crate::{gods::ALL_GODS_BY_ID, nnue::NNUE_GOD_COUNT};
+    use crate::{
+        fen::parse_fen,
+        gods::{ALL_GODS_BY_ID, GodName},
+        nnue::{LabeledAccumulator, NNUE_GOD_COUNT, has_own_model},
+    };
 
     #[test]
     fn test_nnue_id_is_valid() {
@@ -587,7 +604,7 @@ mod tests {
     #[test]
     fn test_nnue_id_should_be_set() {
         for god_power in ALL_GODS_BY_ID.iter() {
-            if (god_power.god_name as usize) < NNUE_GOD_COUNT {
+            if has_own_model(god_power.god_name) {
                 assert_eq!(
                     god_power.god_name, god_power.model_god_name,
                     "God {:?} should use their own NNUE model",
@@ -597,6 +614,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_new_with_models() {
+        let state = parse_fen("0120000100002000000000000/2/pan:A5,C3/triton:B2,E1").unwrap();
+        let mut acc = LabeledAccumulator::new_from_scratch(
+            &state.board,
+            state.gods[0].model_god_name,
+            state.gods[1].model_god_name,
+        );
+        acc.replace_from_state(&state);
+
+        let assigned = LabeledAccumulator::new_with_models(&state, [GodName::Pan, GodName::Mortal]);
+        assert_eq!(assigned.evaluate(), acc.evaluate());
+
+        let forced =
+            LabeledAccumulator::new_with_models(&state, [GodName::Mortal, GodName::Mortal]);
+        assert_ne!(
+            forced.feature_set.ordered_features,
+            acc.feature_set.ordered_features
+        );
+    }
+
     // #[test]
     // fn test_incremental_updates() {
     //     let game_iter = RandomSingleGameStateGenerator::default();
@@ -1,5 +1,6 @@
 use crate::{
     board::{BoardState, GodPair},
+    matchup::{Matchup, matchup_handicap},
     player::Player,
 };
 
@@ -46,6 +47,16 @@ impl PlacementState {
     }
 }
 
+/// Whether player 2 places first. A placement advantage from the matchup policy wins over the
+/// gods' own placement priority
+fn is_placement_flipped(gods: GodPair) -> bool {
+    let matchup = Matchup::new(gods[0].god_name, gods[1].god_name);
+    match matchup_handicap(&matchup).placement_advantage {
+        Some(player) => player == Player::One,
+        None => gods[1].is_placement_priority && !gods[0].is_placement_priority,
+    }
+}
+
 pub fn get_starting_placement_state(
     board: &BoardState,
     gods: GodPair,
@@ -55,12 +66,13 @@ pub fn get_starting_placement_state(
         return Ok(None);
     }
 
-    let is_placement_flipped = gods[1].is_placement_priority && !gods[0].is_placement_priority;
-
     let p1_is_placed = board.workers[0].is_not_empty();
     let p2_is_placed = board.workers[1].is_not_empty();
+    if p1_is_placed && p2_is_placed {
+        return Ok(None);
+    }
 
-    if is_placement_flipped {
+    if is_placement_flipped(gods) {
         match (p1_is_placed, p2_is_placed) {
             (true, true) => Ok(None),
             (false, false) => Ok(Some(PlacementState::new(Player::Two, true))),
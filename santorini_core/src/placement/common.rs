@@ -20,9 +20,23 @@ pub(super) fn compute_unique_placements<W: WorkerPlacementMove>(
     player: Player,
 ) -> Vec<GenericMove> {
     let all_placements = W::get_all_placements(gods, board, player);
+    let base_hash = base_hash_for_god_pair(gods);
+
+    // Placements can only be symmetric to each other under the symmetries of the board they're
+    // made on. Once the board has none, every placement is unique
+    let symmetries: Vec<usize> = board
+        .get_all_permutations::<false>(gods, base_hash)
+        .iter()
+        .enumerate()
+        .filter(|(_, permutation)| *permutation == board)
+        .map(|(idx, _)| idx)
+        .collect();
+    if symmetries.is_empty() {
+        return all_placements;
+    }
+
     let mut res = Vec::<GenericMove>::new();
     let mut all_seen_boards = Vec::new();
-    let base_hash = base_hash_for_god_pair(gods);
 
     for placement in all_placements {
         let mut new_board = board.clone();
@@ -34,10 +48,45 @@ pub(super) fn compute_unique_placements<W: WorkerPlacementMove>(
 
         res.push(placement);
 
-        for permutation in new_board.get_all_permutations::<true>(gods, base_hash) {
-            all_seen_boards.push(permutation);
+        let permutations = new_board.get_all_permutations::<false>(gods, base_hash);
+        for &idx in &symmetries {
+            all_seen_boards.push(permutations[idx].clone());
         }
     }
 
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::FullGameState, gods::GodName, square::Square};
+
+    use super::*;
+
+    fn unique_and_all_placements(state: &FullGameState) -> (usize, usize) {
+        let god = state.gods[0];
+        (
+            god.get_unique_placement_actions(state.gods, &state.board, Player::One)
+                .len(),
+            god.get_all_placement_actions(state.gods, &state.board, Player::One)
+                .len(),
+        )
+    }
+
+    #[test]
+    fn test_empty_board_placements_are_reduced() {
+        let state = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        let (unique, all) = unique_and_all_placements(&state);
+        assert_eq!(all, 300);
+        // Pairs of squares up to the 8 symmetries of the board
+        assert_eq!(unique, 49);
+    }
+
+    #[test]
+    fn test_asymmetric_board_placements_are_unaffected() {
+        let mut state = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        state.board.build_up(Square::B1);
+        let (unique, all) = unique_and_all_placements(&state);
+        assert_eq!(unique, all);
+    }
+}
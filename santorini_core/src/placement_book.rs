@@ -0,0 +1,232 @@
+use std::{collections::HashMap, path::Path, sync::LazyLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::FullGameState, gods::generic::GenericMove, matchup::Matchup,
+    placement::get_starting_placement_state, search::Heuristic,
+};
+
+/// The book shipped with the engine. Regenerate it with the placement_book_gen binary whenever
+/// the eval changes
+const EMBEDDED_BOOK: &str = include_str!("../data/placement_book.yaml");
+
+/// A placement found by a long search, and the position it was played from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BookEntry {
+    pub state: FullGameState,
+    pub next_state: FullGameState,
+    /// Score of the placement from the POV of the player placing
+    pub score: Heuristic,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PlacementBookFile {
+    entries: Vec<BookEntry>,
+}
+
+/// Precomputed placements, per matchup. Positions that are symmetric to a book position use the
+/// matching symmetric placement
+#[derive(Clone, Debug, Default)]
+pub struct PlacementBook {
+    entries: HashMap<Matchup, Vec<BookEntry>>,
+}
+
+fn matchup_for(state: &FullGameState) -> Matchup {
+    Matchup::new(state.gods[0].god_name, state.gods[1].god_name)
+}
+
+impl PlacementBook {
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let file: PlacementBookFile =
+            serde_yaml::from_str(yaml).map_err(|e| format!("Invalid placement book: {e}"))?;
+
+        let mut book = Self::default();
+        for entry in file.entries {
+            book.insert(entry)?;
+        }
+        Ok(book)
+    }
+
+    /// Entries are written sorted by matchup, so that regenerated books diff cleanly
+    pub fn to_yaml(&self) -> Result<String, String> {
+        let mut matchups: Vec<&Matchup> = self.entries.keys().collect();
+        matchups.sort();
+        let file = PlacementBookFile {
+            entries: matchups
+                .into_iter()
+                .flat_map(|matchup| self.entries[matchup].iter().cloned())
+                .collect(),
+        };
+        serde_yaml::to_string(&file).map_err(|e| format!("Error serializing placement book: {e}"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error reading placement book {:?}: {e}", path))?;
+        Self::from_yaml(&yaml)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_yaml()?)
+            .map_err(|e| format!("Error writing placement book {:?}: {e}", path))
+    }
+
+    /// Adds an entry, replacing any existing entry for the same position
+    pub fn insert(&mut self, entry: BookEntry) -> Result<(), String> {
+        if get_starting_placement_state(&entry.state.board, entry.state.gods)?.is_none() {
+            return Err(format!(
+                "Book position isn't in the placement phase: {:?}",
+                entry.state
+            ));
+        }
+
+        let entries = self.entries.entry(matchup_for(&entry.state)).or_default();
+        entries.retain(|existing| existing.state != entry.state);
+        entries.push(entry);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn matchups(&self) -> impl Iterator<Item = &Matchup> {
+        self.entries.keys()
+    }
+
+    /// The book placement for `state`, as the resulting state, the placement and its score
+    pub fn lookup(&self, state: &FullGameState) -> Option<(FullGameState, GenericMove, Heuristic)> {
+        let entries = self.entries.get(&matchup_for(state))?;
+        let permutations = state.get_all_permutations::<true>();
+
+        for entry in entries {
+            let Some(symmetry) = permutations
+                .iter()
+                .position(|permutation| permutation == &entry.state.board)
+            else {
+                continue;
+            };
+
+            // Undo the symmetry by applying the same one to every placement we could make
+            return state
+                .get_all_next_states_with_actions()
+                .into_iter()
+                .find(|(child, _)| {
+                    child.get_all_permutations::<true>()[symmetry] == entry.next_state.board
+                })
+                .map(|(child, action)| (child, action, entry.score));
+        }
+
+        None
+    }
+}
+
+static PLACEMENT_BOOK: LazyLock<PlacementBook> = LazyLock::new(|| {
+    PlacementBook::from_yaml(EMBEDDED_BOOK).expect("Embedded placement book is invalid")
+});
+
+/// The placement book shipped with the engine
+pub fn placement_book() -> &'static PlacementBook {
+    &PLACEMENT_BOOK
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fen::parse_fen,
+        gods::{GodName, WIP_GODS},
+        square::Square,
+    };
+
+    use super::*;
+
+    const CORNER_PLACEMENT: &str = "0000000000000000000000000/2/mortal:A1,B2/mortal";
+
+    fn book_with_corner_placement() -> PlacementBook {
+        let state = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        let next_state = parse_fen(CORNER_PLACEMENT).unwrap();
+        let mut book = PlacementBook::default();
+        book.insert(BookEntry {
+            state,
+            next_state,
+            score: 25,
+        })
+        .unwrap();
+        book
+    }
+
+    #[test]
+    fn test_embedded_book_is_valid() {
+        for matchup in placement_book().matchups() {
+            assert!(!matchup.gods.iter().any(|god| WIP_GODS.contains(god)));
+        }
+    }
+
+    #[test]
+    fn test_lookup_exact_position() {
+        let book = book_with_corner_placement();
+        let state = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        let (child, _, score) = book.lookup(&state).unwrap();
+        assert_eq!(child, parse_fen(CORNER_PLACEMENT).unwrap());
+        assert_eq!(score, 25);
+    }
+
+    #[test]
+    fn test_lookup_uses_symmetry() {
+        let state = parse_fen(CORNER_PLACEMENT).unwrap();
+        let next_state =
+            parse_fen("0000000000000000000000000/1/mortal:A1,B2/mortal:C3,E5").unwrap();
+        let mut book = PlacementBook::default();
+        book.insert(BookEntry {
+            state: state.clone(),
+            next_state: next_state.clone(),
+            score: -10,
+        })
+        .unwrap();
+
+        let permuted_states = state.get_all_permutations::<true>();
+        let permuted_next_states = next_state.get_all_permutations::<true>();
+        for (permuted_state, permuted_next_state) in
+            permuted_states.into_iter().zip(permuted_next_states)
+        {
+            let (child, _, score) = book
+                .lookup(&FullGameState::new(permuted_state, state.gods))
+                .unwrap();
+            assert_eq!(child.board, permuted_next_state);
+            assert_eq!(score, -10);
+        }
+    }
+
+    #[test]
+    fn test_lookup_misses() {
+        let book = book_with_corner_placement();
+        let other_matchup = FullGameState::new_empty_state(GodName::Mortal, GodName::Pan);
+        assert!(book.lookup(&other_matchup).is_none());
+
+        let mut other_position = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        other_position.board.build_up(Square::B1);
+        assert!(book.lookup(&other_position).is_none());
+    }
+
+    #[test]
+    fn test_insert_rejects_positions_after_placement() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B2/mortal:D4,E5").unwrap();
+        let next_state = state.get_next_states()[0].clone();
+        let mut book = PlacementBook::default();
+        assert!(
+            book.insert(BookEntry {
+                state,
+                next_state,
+                score: 0,
+            })
+            .is_err()
+        );
+    }
+}
@@ -36,14 +36,18 @@ pub struct PrettyBoard {
     players: [PrettyPlayer; 2],
 }
 
+fn _token_mask(state: &FullGameState, player: Player) -> BitBoard {
+    let player_god = state.gods[player as usize];
+    player_god.get_frozen_mask(&state.board, player)
+        | player_god.get_female_worker_mask(&state.board, player)
+}
+
 fn _set_pretty_player(state: &FullGameState, player: Player, pretty_player: &mut PrettyPlayer) {
     let player_god = state.gods[player as usize];
 
     pretty_player.god = state.gods[player as usize].god_name;
     pretty_player.workers = state.board.workers[player as usize].all_squares();
-    pretty_player.tokens = (player_god.get_frozen_mask(&state.board, player)
-        | player_god.get_female_worker_mask(&state.board, player))
-    .all_squares();
+    pretty_player.tokens = _token_mask(state, player).all_squares();
     pretty_player.special_text = player_god.pretty_stringify_god_data(&state.board, player);
 }
 
@@ -72,6 +76,112 @@ pub fn state_to_pretty_board(state: &FullGameState) -> PrettyBoard {
     result
 }
 
+/// Bumped whenever the meaning of a `PrettyBoardDelta` field changes
+pub const PRETTY_BOARD_DELTA_VERSION: u32 = 1;
+
+/// Everything a client draws on one square
+#[derive(Clone, PartialEq, Eq, Serialize, Debug)]
+pub struct PrettySquare {
+    square: Square,
+    height: u8,
+    worker: Option<Player>,
+    /// Players with a token on the square
+    tokens: Vec<Player>,
+}
+
+/// The changes that turn one `PrettyBoard` into another. Squares are only listed if something on
+/// them changed, while the fields that aren't tied to a square are always sent in full
+#[derive(Clone, PartialEq, Eq, Serialize, Debug)]
+pub struct PrettyBoardDelta {
+    version: u32,
+    acting_player: Player,
+    winner: Option<Player>,
+    gods: [GodName; 2],
+    special_text: [Option<String>; 2],
+    squares: Vec<PrettySquare>,
+}
+
+/// The delta from the board of `from` to the board of `to`. Changed squares are found with
+/// `diff_states`, along with any square whose tokens changed
+pub fn pretty_board_delta(from: &FullGameState, to: &FullGameState) -> PrettyBoardDelta {
+    let mut changed = BitBoard::EMPTY;
+    for item in diff_states(from, to).items {
+        match item {
+            BoardDiffItem::Height { square, .. }
+            | BoardDiffItem::WorkerAdded { square, .. }
+            | BoardDiffItem::WorkerRemoved { square, .. } => changed |= BitBoard::as_mask(square),
+            _ => (),
+        }
+    }
+    for player in [Player::One, Player::Two] {
+        changed |= _token_mask(from, player) ^ _token_mask(to, player);
+    }
+
+    let to_board = state_to_pretty_board(to);
+    let squares = changed
+        .into_iter()
+        .map(|square| PrettySquare {
+            square,
+            height: to.board.get_height(square) as u8,
+            worker: [Player::One, Player::Two]
+                .into_iter()
+                .find(|&player| to.board.workers[player as usize].contains_square(square)),
+            tokens: [Player::One, Player::Two]
+                .into_iter()
+                .filter(|&player| _token_mask(to, player).contains_square(square))
+                .collect(),
+        })
+        .collect();
+
+    PrettyBoardDelta {
+        version: PRETTY_BOARD_DELTA_VERSION,
+        acting_player: to_board.acting_player,
+        winner: to_board.winner,
+        gods: to_board.players.each_ref().map(|player| player.god),
+        special_text: to_board.players.map(|player| player.special_text),
+        squares,
+    }
+}
+
+impl PrettyBoard {
+    /// Updates the board the same way as a client receiving `delta` would
+    pub fn apply_delta(&mut self, delta: &PrettyBoardDelta) -> Result<(), String> {
+        if delta.version != PRETTY_BOARD_DELTA_VERSION {
+            return Err(format!(
+                "Unsupported pretty board delta version: {}",
+                delta.version
+            ));
+        }
+
+        self.acting_player = delta.acting_player;
+        self.winner = delta.winner;
+        for (idx, pretty_player) in self.players.iter_mut().enumerate() {
+            pretty_player.god = delta.gods[idx];
+            pretty_player.special_text = delta.special_text[idx].clone();
+        }
+
+        for pretty_square in &delta.squares {
+            let square = pretty_square.square;
+            self.heights[square as usize / 5][square as usize % 5] = pretty_square.height;
+            for player in [Player::One, Player::Two] {
+                let pretty_player = &mut self.players[player as usize];
+                pretty_player.workers.retain(|&s| s != square);
+                if pretty_square.worker == Some(player) {
+                    pretty_player.workers.push(square);
+                    pretty_player.workers.sort();
+                }
+                pretty_player.tokens.retain(|&s| s != square);
+                if pretty_square.tokens.contains(&player) {
+                    pretty_player.tokens.push(square);
+                    pretty_player.tokens.sort();
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub fn game_state_with_partial_actions(
     state: &FullGameState,
     actions: &Vec<PartialAction>,
@@ -98,7 +208,6 @@ pub fn game_state_with_partial_actions(
                 board.set_god_data(current_player, square.to_board().0);
             }
             PartialAction::SelectWorker(square) => {
-                assert!(selected_square.is_none());
                 selected_square = Some(square);
             }
             PartialAction::ForceOpponentWorker(from, to) => {
@@ -116,7 +225,10 @@ pub fn game_state_with_partial_actions(
                 }
             }
             PartialAction::MoveWorker(data) => {
-                let selected_square = selected_square.take().unwrap();
+                // The worker stays selected, for gods like Triton that can keep moving
+                let Some(selected_square) = selected_square.replace(data.dest) else {
+                    continue;
+                };
                 let self_mask = BitBoard::as_mask(selected_square) ^ BitBoard::as_mask(data.dest);
                 board.worker_xor(current_player, self_mask);
 
@@ -168,3 +280,348 @@ pub fn game_state_with_partial_actions(
 
     result
 }
+
+/// A single difference between two game states
+#[derive(Clone, PartialEq, Eq, Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BoardDiffItem {
+    God {
+        player: Player,
+        from: GodName,
+        to: GodName,
+    },
+    CurrentPlayer {
+        from: Player,
+        to: Player,
+    },
+    Winner {
+        from: Option<Player>,
+        to: Option<Player>,
+    },
+    Height {
+        square: Square,
+        from: u8,
+        to: u8,
+    },
+    WorkerAdded {
+        player: Player,
+        square: Square,
+    },
+    WorkerRemoved {
+        player: Player,
+        square: Square,
+    },
+    GodData {
+        player: Player,
+        from: GodData,
+        to: GodData,
+    },
+}
+
+impl std::fmt::Display for BoardDiffItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardDiffItem::God { player, from, to } => {
+                write!(f, "{:?} god: {} -> {}", player, from, to)
+            }
+            BoardDiffItem::CurrentPlayer { from, to } => {
+                write!(f, "current player: {:?} -> {:?}", from, to)
+            }
+            BoardDiffItem::Winner { from, to } => write!(f, "winner: {:?} -> {:?}", from, to),
+            BoardDiffItem::Height { square, from, to } => {
+                write!(f, "{} height: {} -> {}", square, from, to)
+            }
+            BoardDiffItem::WorkerAdded { player, square } => {
+                write!(f, "{:?} worker added: {}", player, square)
+            }
+            BoardDiffItem::WorkerRemoved { player, square } => {
+                write!(f, "{:?} worker removed: {}", player, square)
+            }
+            BoardDiffItem::GodData { player, from, to } => {
+                write!(f, "{:?} god data: {:#b} -> {:#b}", player, from, to)
+            }
+        }
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Eq, Serialize, Debug)]
+pub struct BoardDiff {
+    pub items: Vec<BoardDiffItem>,
+}
+
+impl BoardDiff {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn square_annotation(&self, square: Square) -> String {
+        let mut annotation = String::new();
+        for item in &self.items {
+            match item {
+                BoardDiffItem::Height {
+                    square: s,
+                    from,
+                    to,
+                } if *s == square => {
+                    annotation += &format!("{:+}", *to as i32 - *from as i32);
+                }
+                BoardDiffItem::WorkerAdded { player, square: s } if *s == square => {
+                    annotation += &format!("+{}", _worker_char(*player));
+                }
+                BoardDiffItem::WorkerRemoved { player, square: s } if *s == square => {
+                    annotation += &format!("-{}", _worker_char(*player));
+                }
+                _ => (),
+            }
+        }
+        annotation
+    }
+}
+
+fn _worker_char(player: Player) -> char {
+    match player {
+        Player::One => 'X',
+        Player::Two => 'O',
+    }
+}
+
+/// Everything that changed between `a` and `b`. States with different gods are reported as a
+/// diff item, and the rest of the board is still compared
+pub fn diff_states(a: &FullGameState, b: &FullGameState) -> BoardDiff {
+    let mut items = Vec::new();
+
+    for player in [Player::One, Player::Two] {
+        let from = a.gods[player as usize].god_name;
+        let to = b.gods[player as usize].god_name;
+        if from != to {
+            items.push(BoardDiffItem::God { player, from, to });
+        }
+    }
+
+    if a.board.current_player != b.board.current_player {
+        items.push(BoardDiffItem::CurrentPlayer {
+            from: a.board.current_player,
+            to: b.board.current_player,
+        });
+    }
+
+    if a.get_winner() != b.get_winner() {
+        items.push(BoardDiffItem::Winner {
+            from: a.get_winner(),
+            to: b.get_winner(),
+        });
+    }
+
+    for square in Square::ALL {
+        let from = a.board.get_height(square) as u8;
+        let to = b.board.get_height(square) as u8;
+        if from != to {
+            items.push(BoardDiffItem::Height { square, from, to });
+        }
+    }
+
+    for player in [Player::One, Player::Two] {
+        let old_workers = a.board.workers[player as usize];
+        let new_workers = b.board.workers[player as usize];
+        for square in old_workers & !new_workers {
+            items.push(BoardDiffItem::WorkerRemoved { player, square });
+        }
+        for square in new_workers & !old_workers {
+            items.push(BoardDiffItem::WorkerAdded { player, square });
+        }
+    }
+
+    for player in [Player::One, Player::Two] {
+        let from = a.board.god_data[player as usize];
+        let to = b.board.god_data[player as usize];
+        if from != to {
+            items.push(BoardDiffItem::GodData { player, from, to });
+        }
+    }
+
+    BoardDiff { items }
+}
+
+/// Renders `b` as a single board, with every square that changed since `a` annotated with its
+/// height delta and added (+) or removed (-) workers. Changes that aren't tied to a square are
+/// listed underneath
+pub fn render_diff(a: &FullGameState, b: &FullGameState) -> String {
+    const CELL_WIDTH: usize = 8;
+
+    let diff = diff_states(a, b);
+    let mut result = String::from(" ");
+    for col in ["A", "B", "C", "D", "E"] {
+        result += &format!(" {:<CELL_WIDTH$}", col);
+    }
+    result += "\n";
+
+    for row in 0..5 {
+        result += &format!("{}", 5 - row);
+        for col in 0..5 {
+            let square = Square::from_col_row(col, row);
+            let worker = if b.board.workers[0].contains_square(square) {
+                'X'
+            } else if b.board.workers[1].contains_square(square) {
+                'O'
+            } else {
+                '.'
+            };
+            let cell = format!(
+                "{}{}{}",
+                b.board.get_height(square),
+                worker,
+                diff.square_annotation(square)
+            );
+            result += &format!(" {:<CELL_WIDTH$}", cell);
+        }
+        result += "\n";
+    }
+
+    for item in &diff.items {
+        match item {
+            BoardDiffItem::Height { .. }
+            | BoardDiffItem::WorkerAdded { .. }
+            | BoardDiffItem::WorkerRemoved { .. } => (),
+            _ => result += &format!("{}\n", item),
+        }
+    }
+
+    result
+}
+
+pub fn print_diff_to_console(a: &FullGameState, b: &FullGameState) {
+    eprint!("{}", render_diff(a, b));
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    use crate::{
+        fen::parse_fen,
+        gods::ALL_GODS_BY_ID,
+        matchup::{Matchup, is_matchup_banned},
+        random_utils::get_random_god,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_diff_states_move_and_build() {
+        let a = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let b = parse_fen("0000000000000000010000000/2/mortal:A2,B1/mortal:D5,E5").unwrap();
+
+        let diff = diff_states(&a, &b);
+        assert_eq!(
+            diff.items,
+            vec![
+                BoardDiffItem::CurrentPlayer {
+                    from: Player::One,
+                    to: Player::Two
+                },
+                BoardDiffItem::Height {
+                    square: Square::C2,
+                    from: 0,
+                    to: 1
+                },
+                BoardDiffItem::WorkerRemoved {
+                    player: Player::One,
+                    square: Square::A1
+                },
+                BoardDiffItem::WorkerAdded {
+                    player: Player::One,
+                    square: Square::A2
+                },
+            ]
+        );
+
+        let rendered = render_diff(&a, &b);
+        assert!(rendered.contains("1.+1"));
+        assert!(rendered.contains("0X+X"));
+        assert!(rendered.contains("0.-X"));
+        assert!(rendered.contains("current player: One -> Two"));
+    }
+
+    #[test]
+    fn test_diff_states_different_gods() {
+        let a = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let b = parse_fen("0000000000000000000000000/1/pan:A1,B1/mortal:D5,E5").unwrap();
+
+        let diff = diff_states(&a, &b);
+        assert_eq!(
+            diff.items,
+            vec![BoardDiffItem::God {
+                player: Player::One,
+                from: GodName::Mortal,
+                to: GodName::Pan
+            }]
+        );
+        assert!(diff_states(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_pretty_board_deltas_reproduce_boards() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for god in ALL_GODS_BY_ID.iter() {
+            let other_god = get_random_god(&mut rng);
+            for matchup in [
+                Matchup::new(god.god_name, other_god.god_name),
+                Matchup::new(other_god.god_name, god.god_name),
+            ] {
+                if is_matchup_banned(&matchup) {
+                    continue;
+                }
+                let mut state = FullGameState::new_for_matchup(&matchup);
+                let mut client_board = state_to_pretty_board(&state);
+                for _ in 0..30 {
+                    if state.get_winner().is_some() {
+                        break;
+                    }
+                    let Some(next) = state
+                        .get_next_states_interactive()
+                        .choose(&mut rng)
+                        .cloned()
+                    else {
+                        break;
+                    };
+
+                    // Step through the move one action at a time, like a client showing a move
+                    // being made
+                    let mut client_state = state.clone();
+                    for action_count in 1..=next.actions.len() {
+                        let partial = game_state_with_partial_actions(
+                            &state,
+                            &next.actions[..action_count].to_vec(),
+                        );
+                        client_board
+                            .apply_delta(&pretty_board_delta(&client_state, &partial))
+                            .unwrap();
+                        assert_eq!(client_board, state_to_pretty_board(&partial), "{:?}", next);
+                        client_state = partial;
+                    }
+
+                    client_board
+                        .apply_delta(&pretty_board_delta(&client_state, &next.state))
+                        .unwrap();
+                    assert_eq!(
+                        client_board,
+                        state_to_pretty_board(&next.state),
+                        "{:?}",
+                        next
+                    );
+                    state = next.state;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pretty_board_delta_version() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut delta = pretty_board_delta(&state, &state);
+        assert!(delta.squares.is_empty());
+
+        delta.version += 1;
+        assert!(state_to_pretty_board(&state).apply_delta(&delta).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::FullGameState, placement::get_starting_placement_state, player::Player,
+    pretty_board::get_acting_player, refute::next_states_with_strings,
+};
+
+/// One ply of a proven forced win
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProvenStep {
+    pub player: Player,
+    pub action_str: String,
+    pub next_state: FullGameState,
+    /// On the losing side's plies, the number of moves that were examined. All of them lose
+    pub alternatives_examined: Option<usize>,
+}
+
+/// A forced win confirmed by exhaustive search
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProvenLine {
+    pub winner: Player,
+    /// Plies until the win, counting both sides
+    pub plies: usize,
+    pub nodes_visited: usize,
+    /// The winning side's moves, against the losing side's longest defence. Ends early if the
+    /// losing side runs out of moves
+    pub line: Vec<ProvenStep>,
+}
+
+/// Plain AND/OR search with no heuristics, pruning or transposition table, so that every result
+/// is exact
+struct Prover {
+    attacker: Player,
+    nodes_visited: usize,
+}
+
+impl Prover {
+    /// Whether the player to move in `state` can win immediately
+    fn has_immediate_win(&mut self, state: &FullGameState) -> bool {
+        // Win checks assume the placement phase is over
+        if get_starting_placement_state(&state.board, state.gods)
+            .ok()
+            .flatten()
+            .is_some()
+        {
+            return false;
+        }
+
+        let (active_god, other_god) = state.get_active_non_active_gods();
+        active_god
+            .get_winning_moves(state, state.board.current_player)
+            .into_iter()
+            .any(|scored_move| {
+                self.nodes_visited += 1;
+                let child = state.next_state(active_god, other_god, scored_move.action);
+                child.get_winner() == Some(self.attacker)
+            })
+    }
+
+    /// Whether the attacker forces a win from `state` within `depth` plies
+    fn is_forced_win(&mut self, state: &FullGameState, depth: usize) -> bool {
+        self.nodes_visited += 1;
+        if let Some(winner) = state.get_winner() {
+            return winner == self.attacker;
+        }
+        if depth == 0 {
+            return false;
+        }
+        let Ok(player) = get_acting_player(state) else {
+            return false;
+        };
+
+        if player == self.attacker {
+            if self.has_immediate_win(state) {
+                return true;
+            }
+            if depth == 1 {
+                return false;
+            }
+            next_states_with_strings(state)
+                .into_iter()
+                .any(|(child, _, _)| self.is_forced_win(&child, depth - 1))
+        } else {
+            // A player with no moves loses, so an empty list is a win too
+            next_states_with_strings(state)
+                .into_iter()
+                .all(|(child, _, _)| self.is_forced_win(&child, depth - 1))
+        }
+    }
+
+    fn shortest_win(&mut self, state: &FullGameState, max_depth: usize) -> Option<usize> {
+        (0..=max_depth).find(|depth| self.is_forced_win(state, *depth))
+    }
+
+    /// Walks a win of at most `depth` plies that's already been proven
+    fn extract_line(&mut self, state: &FullGameState, mut depth: usize) -> Vec<ProvenStep> {
+        let mut line = Vec::new();
+        let mut current = state.clone();
+
+        while current.get_winner().is_none() && depth > 0 {
+            let Ok(player) = get_acting_player(&current) else {
+                break;
+            };
+            let children = next_states_with_strings(&current);
+
+            let (step, alternatives_examined) = if player == self.attacker {
+                let Some(step) = children
+                    .into_iter()
+                    .find(|(child, _, _)| self.is_forced_win(child, depth - 1))
+                else {
+                    break;
+                };
+                (step, None)
+            } else {
+                let alternatives = children.len();
+                let mut longest: Option<(usize, (FullGameState, _, String))> = None;
+                for child in children {
+                    let child_depth = self.shortest_win(&child.0, depth - 1).unwrap_or(depth);
+                    if longest.as_ref().is_none_or(|(best, _)| child_depth > *best) {
+                        longest = Some((child_depth, child));
+                    }
+                }
+                let Some((_, step)) = longest else {
+                    // Out of moves
+                    break;
+                };
+                (step, Some(alternatives))
+            };
+
+            let (child, _, action_str) = step;
+            line.push(ProvenStep {
+                player,
+                action_str,
+                next_state: child.clone(),
+                alternatives_examined,
+            });
+            current = child;
+            depth -= 1;
+        }
+
+        line
+    }
+}
+
+/// Searches every line from `state`, to confirm that the player to move forces a win within
+/// `max_depth` plies. Slow, but sound as long as move generation is
+pub fn prove_win(state: &FullGameState, max_depth: usize) -> Option<ProvenLine> {
+    if state.get_winner().is_some() || state.validation_err().is_err() {
+        return None;
+    }
+
+    let mut prover = Prover {
+        attacker: get_acting_player(state).ok()?,
+        nodes_visited: 0,
+    };
+    let plies = prover.shortest_win(state, max_depth)?;
+    let line = prover.extract_line(state, plies);
+
+    Some(ProvenLine {
+        winner: prover.attacker,
+        plies,
+        nodes_visited: prover.nodes_visited,
+        line,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen::parse_fen;
+
+    use super::*;
+
+    // Player 1 climbs from A5 to B5
+    const FORCE_WIN_IN_1_STRING: &str = "2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3";
+    // Player 1 steps to B5, builds A5 up to level 3, and Player 2 is too far away to dome it
+    const FORCE_WIN_IN_3_STRING: &str = "2200000000000000000000000/1/mortal:A5,E5/mortal:D1,E1";
+
+    #[test]
+    fn test_prove_win_in_1() {
+        let state = parse_fen(FORCE_WIN_IN_1_STRING).unwrap();
+        let proven = prove_win(&state, 3).unwrap();
+
+        assert_eq!(proven.winner, Player::One);
+        assert_eq!(proven.plies, 1);
+        assert_eq!(proven.line.len(), 1);
+        assert_eq!(proven.line[0].alternatives_examined, None);
+        assert_eq!(proven.line[0].next_state.get_winner(), Some(Player::One));
+    }
+
+    #[test]
+    fn test_prove_win_in_3() {
+        let state = parse_fen(FORCE_WIN_IN_3_STRING).unwrap();
+        assert!(prove_win(&state, 2).is_none());
+
+        let proven = prove_win(&state, 3).unwrap();
+        assert_eq!(proven.winner, Player::One);
+        assert_eq!(proven.plies, 3);
+        assert_eq!(proven.line.len(), 3);
+
+        let defence = &proven.line[1];
+        assert_eq!(defence.player, Player::Two);
+        assert!(defence.alternatives_examined.unwrap() > 0);
+        assert_eq!(
+            proven.line.last().unwrap().next_state.get_winner(),
+            Some(Player::One)
+        );
+    }
+
+    #[test]
+    fn test_no_proof_without_forced_win() {
+        let state = parse_fen(FORCE_WIN_IN_3_STRING).unwrap();
+        assert!(prove_win(&state, 1).is_none());
+
+        let state = parse_fen("0000000000000000000000000/1/mortal:A5,E5/mortal:D1,E1").unwrap();
+        assert!(prove_win(&state, 2).is_none());
+    }
+}
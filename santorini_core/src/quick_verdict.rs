@@ -0,0 +1,408 @@
+use std::{
+    cmp::Reverse,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::{
+    bitboard::BitBoard,
+    board::FullGameState,
+    gods::generic::GenericMove,
+    placement::get_starting_placement_state,
+    player::Player,
+    pretty_board::get_acting_player,
+    review::{score_for_mover, search_fixed_nodes},
+    search::{Heuristic, WINNING_SCORE_BUFFER, win_at_ply},
+    transposition_table::TranspositionTable,
+};
+
+/// Entries in the transposition table used for each root move. Every move gets a fresh table, so
+/// that scores don't depend on which moves happened to be searched first
+const QUICK_VERDICT_TT_SIZE: usize = 1 << 17;
+
+/// Score of the move leading to `child`, from the perspective of `mover`
+fn score_child(
+    tt: &mut TranspositionTable,
+    mover: Player,
+    child: &FullGameState,
+    nodes_per_move: usize,
+) -> Heuristic {
+    // Moves that end the game have exact scores already
+    if let Some(winner) = child.get_winner() {
+        return match winner == mover {
+            true => win_at_ply(1),
+            false => -win_at_ply(1),
+        };
+    }
+
+    tt.reset();
+    match search_fixed_nodes(tt, child, nodes_per_move) {
+        Some(best_move) => score_for_mover(mover, child, best_move.score),
+        None => win_at_ply(1),
+    }
+}
+
+fn sorted_by_score(mut scores: Vec<(GenericMove, Heuristic)>) -> Vec<(GenericMove, Heuristic)> {
+    scores.sort_by_key(|(_, score)| Reverse(*score));
+    scores
+}
+
+/// Scores every legal move from `state` with a separate `nodes_per_move` node search, best first.
+/// Scores are from the perspective of the player to move
+pub fn score_root_moves(
+    state: &FullGameState,
+    nodes_per_move: usize,
+) -> Vec<(GenericMove, Heuristic)> {
+    let Ok(mover) = get_acting_player(state) else {
+        return Vec::new();
+    };
+    if state.get_winner().is_some() {
+        return Vec::new();
+    }
+
+    let mut tt = TranspositionTable::with_size(QUICK_VERDICT_TT_SIZE);
+    sorted_by_score(
+        state
+            .get_all_next_states_with_actions()
+            .into_iter()
+            .map(|(child, action)| (action, score_child(&mut tt, mover, &child, nodes_per_move)))
+            .collect(),
+    )
+}
+
+/// Like `score_root_moves`, but keeps every legal move when one of them wins on the spot. Move
+/// generation for search stops at the first win, which leaves nothing else to choose from
+pub fn score_all_root_moves(
+    state: &FullGameState,
+    nodes_per_move: usize,
+) -> Vec<(GenericMove, Heuristic)> {
+    let Ok(mover) = get_acting_player(state) else {
+        return Vec::new();
+    };
+    if state.get_winner().is_some() {
+        return Vec::new();
+    }
+    // Placements never win on the spot, so there's nothing extra to keep
+    let Ok(None) = get_starting_placement_state(&state.board, state.gods) else {
+        return score_root_moves(state, nodes_per_move);
+    };
+
+    let (active_god, other_god) = state.get_active_non_active_gods();
+    let mut tt = TranspositionTable::with_size(QUICK_VERDICT_TT_SIZE);
+    sorted_by_score(
+        active_god
+            .get_all_moves(state, mover)
+            .into_iter()
+            .map(|scored_move| {
+                let child = state.next_state(active_god, other_god, scored_move.action);
+                let score = score_child(&mut tt, mover, &child, nodes_per_move);
+                (scored_move.action, score)
+            })
+            .collect(),
+    )
+}
+
+/// Same as `score_root_moves`, splitting the root moves across `threads` threads. Each move still
+/// gets its own search and table, so the results match the sequential version. Without threads
+/// (wasm), this is the sequential version
+pub fn score_root_moves_parallel(
+    state: &FullGameState,
+    nodes_per_move: usize,
+    threads: usize,
+) -> Vec<(GenericMove, Heuristic)> {
+    if cfg!(target_arch = "wasm32") || threads <= 1 {
+        return score_root_moves(state, nodes_per_move);
+    }
+
+    let Ok(mover) = get_acting_player(state) else {
+        return Vec::new();
+    };
+    if state.get_winner().is_some() {
+        return Vec::new();
+    }
+
+    let children = state.get_all_next_states_with_actions();
+    let next_child = AtomicUsize::new(0);
+    let threads = threads.min(children.len());
+
+    let mut scores: Vec<(usize, GenericMove, Heuristic)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut tt = TranspositionTable::with_size(QUICK_VERDICT_TT_SIZE);
+                    let mut scores = Vec::new();
+                    loop {
+                        let idx = next_child.fetch_add(1, Ordering::Relaxed);
+                        let Some((child, action)) = children.get(idx) else {
+                            break;
+                        };
+                        let score = score_child(&mut tt, mover, child, nodes_per_move);
+                        scores.push((idx, *action, score));
+                    }
+                    scores
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    // Restore the move generation order, so that ties sort the same way as sequentially
+    scores.sort_by_key(|(idx, _, _)| *idx);
+    sorted_by_score(
+        scores
+            .into_iter()
+            .map(|(_, action, score)| (action, score))
+            .collect(),
+    )
+}
+
+/// Settings for going easy on a beginner, by passing up wins that the engine has found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MercyConfig {
+    /// Moves scoring at least this are avoided while a safe move scoring less exists
+    pub win_threshold: Heuristic,
+    /// Moves that win on the spot are never played before this ply, unless every safe move
+    /// wins on the spot. From this ply on they're always played, so that games still end
+    pub no_direct_wins_before_ply: usize,
+}
+
+impl Default for MercyConfig {
+    fn default() -> Self {
+        MercyConfig {
+            win_threshold: WINNING_SCORE_BUFFER,
+            no_direct_wins_before_ply: 20,
+        }
+    }
+}
+
+/// Picks a move from `scores`, as returned by `score_all_root_moves`, that doesn't punish the
+/// opponent's mistakes. Prefers the best move that neither wins nor loses, then the best winning
+/// move that doesn't end the game, then the best move overall. A lost move is only played when
+/// every move is lost
+pub fn pick_merciful_move(
+    state: &FullGameState,
+    scores: &[(GenericMove, Heuristic)],
+    config: &MercyConfig,
+) -> Option<(GenericMove, Heuristic)> {
+    let direct_wins_allowed = state.ply >= config.no_direct_wins_before_ply;
+    let is_direct_win =
+        |action: &GenericMove, score: Heuristic| action.get_is_winning() || score >= win_at_ply(1);
+    let is_lost = |score: Heuristic| score <= -WINNING_SCORE_BUFFER;
+
+    scores
+        .iter()
+        .find(|(action, score)| match is_direct_win(action, *score) {
+            true => direct_wins_allowed,
+            false => *score < config.win_threshold && !is_lost(*score),
+        })
+        .or_else(|| {
+            scores
+                .iter()
+                .find(|(action, score)| !is_direct_win(action, *score) && !is_lost(*score))
+        })
+        .or_else(|| scores.first())
+        .copied()
+}
+
+/// A worker placement, and the squares it puts workers on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlacementScore {
+    pub action: GenericMove,
+    pub squares: BitBoard,
+    pub score: Heuristic,
+}
+
+/// Scores every placement from `state` like `score_root_moves_parallel`, best first. Empty if
+/// `state` isn't in the placement phase
+pub fn score_placements(
+    state: &FullGameState,
+    nodes_per_move: usize,
+    threads: usize,
+) -> Vec<PlacementScore> {
+    let Ok(Some(placement_mode)) = get_starting_placement_state(&state.board, state.gods) else {
+        return Vec::new();
+    };
+    let placer = placement_mode.next_placement as usize;
+    let children = state.get_all_next_states_with_actions();
+
+    score_root_moves_parallel(state, nodes_per_move, threads)
+        .into_iter()
+        .filter_map(|(action, score)| {
+            let (child, _) = children.iter().find(|(_, a)| *a == action)?;
+            Some(PlacementScore {
+                action,
+                squares: child.board.workers[placer] & !state.board.workers[placer],
+                score,
+            })
+        })
+        .collect()
+}
+
+/// The best score of any placement that covers each square, among placements that cover all of
+/// `required`
+pub fn best_placement_score_per_square(
+    placements: &[PlacementScore],
+    required: BitBoard,
+) -> [Option<Heuristic>; 25] {
+    let mut result = [None; 25];
+    for placement in placements {
+        if (placement.squares & required) != required {
+            continue;
+        }
+        for square in placement.squares {
+            let best = &mut result[square as usize];
+            *best = Some(best.map_or(placement.score, |best: Heuristic| best.max(placement.score)));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fen::parse_fen, search::WINNING_SCORE_BUFFER, square::Square};
+
+    use super::*;
+
+    const NODES_PER_MOVE: usize = 2_000;
+
+    #[test]
+    fn test_winning_move_scores_exactly() {
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let scores = score_root_moves(&state, NODES_PER_MOVE);
+
+        assert_eq!(scores.len(), state.get_all_next_states_with_actions().len());
+        assert_eq!(scores[0].1, win_at_ply(1));
+        assert!(scores.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn test_losing_moves_score_as_losses() {
+        // Player 2 wins from A5 next turn, and Player 1 can't stop it
+        let state = parse_fen("2300000000000000000000000/1/mortal:C1,E3/mortal:A5,E1").unwrap();
+        let scores = score_root_moves(&state, NODES_PER_MOVE);
+        assert!(
+            scores
+                .iter()
+                .all(|(_, score)| *score <= -WINNING_SCORE_BUFFER)
+        );
+    }
+
+    #[test]
+    fn test_mercy_skips_the_only_win() {
+        // A5 can step up to B5 and win, and no other move wins
+        let mut state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let scores = score_all_root_moves(&state, NODES_PER_MOVE);
+        assert_eq!(scores[0].1, win_at_ply(1));
+        assert!(scores[1..].iter().all(|(_, score)| *score < win_at_ply(1)));
+
+        let config = MercyConfig::default();
+        assert_eq!(
+            pick_merciful_move(&state, &scores, &config),
+            Some(scores[1])
+        );
+
+        state.ply = config.no_direct_wins_before_ply;
+        assert_eq!(
+            pick_merciful_move(&state, &scores, &config),
+            Some(scores[0])
+        );
+    }
+
+    #[test]
+    fn test_mercy_fallbacks() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let actions: Vec<GenericMove> = state
+            .get_all_next_states_with_actions()
+            .into_iter()
+            .map(|(_, action)| action)
+            .collect();
+        let config = MercyConfig::default();
+        let pick = |scores: &[(GenericMove, Heuristic)]| {
+            pick_merciful_move(&state, scores, &config).map(|(action, _)| action)
+        };
+
+        // One winning move and several equal ones: the first equal one is played
+        let scores = [
+            (actions[0], win_at_ply(5)),
+            (actions[1], 10),
+            (actions[2], 10),
+            (actions[3], 10),
+        ];
+        assert_eq!(pick(&scores), Some(actions[1]));
+
+        // Every safe move wins, so the best one that doesn't end the game is played
+        let scores = [
+            (actions[0], win_at_ply(1)),
+            (actions[1], win_at_ply(3)),
+            (actions[2], win_at_ply(5)),
+            (actions[3], -win_at_ply(2)),
+        ];
+        assert_eq!(pick(&scores), Some(actions[1]));
+
+        // Winning on the spot beats losing
+        let scores = [
+            (actions[0], win_at_ply(1)),
+            (actions[1], -win_at_ply(2)),
+            (actions[2], -win_at_ply(2)),
+        ];
+        assert_eq!(pick(&scores), Some(actions[0]));
+
+        // Everything loses
+        let scores = [(actions[0], -win_at_ply(4)), (actions[1], -win_at_ply(2))];
+        assert_eq!(pick(&scores), Some(actions[0]));
+        assert_eq!(pick(&[]), None);
+    }
+
+    #[test]
+    fn test_score_placements() {
+        let state = parse_fen("0000000000000000000000000/2/mortal:A1,B2/mortal").unwrap();
+        let placements = score_placements(&state, 200, 4);
+        assert_eq!(
+            placements.len(),
+            state.get_all_next_states_with_actions().len()
+        );
+        assert!(placements.iter().all(|p| p.squares.count_ones() == 2));
+        assert!(
+            placements
+                .iter()
+                .all(|p| (p.squares & state.board.workers[0]).is_empty())
+        );
+
+        let per_square = best_placement_score_per_square(&placements, BitBoard::EMPTY);
+        assert!(per_square[Square::A1 as usize].is_none());
+        assert_eq!(
+            per_square.iter().flatten().max(),
+            Some(&placements[0].score)
+        );
+
+        // Once one worker is down, only placements that include it count
+        let required = BitBoard::as_mask(Square::C3);
+        let per_square = best_placement_score_per_square(&placements, required);
+        for placement in &placements {
+            if !(placement.squares & required).is_empty() {
+                for square in placement.squares {
+                    assert!(per_square[square as usize].unwrap() >= placement.score);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_score_placements_outside_placement_phase() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B2/mortal:D4,E5").unwrap();
+        assert!(score_placements(&state, 200, 1).is_empty());
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let state = parse_fen("0120000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        assert_eq!(
+            score_root_moves_parallel(&state, NODES_PER_MOVE, 4),
+            score_root_moves(&state, NODES_PER_MOVE)
+        );
+    }
+}
@@ -28,6 +28,7 @@ pub fn get_random_starting_state<T: Rng>(matchup: &Matchup, rng: &mut T) -> Full
         let action = placement_actions.choose(rng).unwrap().clone();
 
         active_god.make_placement_move(action, &mut state.board, active_player, other_god);
+        state.ply += 1;
     }
 
     state
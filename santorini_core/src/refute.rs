@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::FullGameState,
+    gods::generic::GenericMove,
+    placement::get_starting_placement_state,
+    pretty_board::get_acting_player,
+    review::score_for_mover,
+    search::{
+        Heuristic, SearchContext, get_past_win_search_terminator, negamax_search, win_at_ply,
+    },
+    search_terminators::DynamicNodesVisitedSearchTerminator,
+    transposition_table::TranspositionTable,
+};
+
+/// Longest continuation read back from the transposition table after the reply
+const MAX_LINE_PLIES: usize = 8;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RefutationStep {
+    pub action_str: String,
+    pub next_state: FullGameState,
+}
+
+/// The opponent's answer to a move, found by searching the position after it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RefutationLine {
+    pub move_str: String,
+    /// The position after the refuted move
+    pub forced_state: FullGameState,
+    /// Score of the refuted move, from the perspective of the player making it
+    pub score: Heuristic,
+    pub depth: usize,
+    pub nodes_visited: usize,
+    /// The opponent's best reply, followed by the expected continuation. Empty if the refuted
+    /// move ends the game
+    pub line: Vec<RefutationStep>,
+}
+
+/// Every legal move from `state`, along with its notation
+pub fn next_states_with_strings(
+    state: &FullGameState,
+) -> Vec<(FullGameState, GenericMove, String)> {
+    with_move_strings(state, state.get_all_next_states_with_actions())
+}
+
+fn with_move_strings(
+    state: &FullGameState,
+    next_states: Vec<(FullGameState, GenericMove)>,
+) -> Vec<(FullGameState, GenericMove, String)> {
+    let placement_state = get_starting_placement_state(&state.board, state.gods)
+        .ok()
+        .flatten();
+    let active_god = match placement_state {
+        Some(placement_state) => state.gods[placement_state.next_placement as usize],
+        None => state.get_active_god(),
+    };
+
+    next_states
+        .into_iter()
+        .map(|(child, action)| {
+            let action_str = match placement_state {
+                Some(_) => active_god.stringify_placement_move(action),
+                None => active_god.stringify_move(action),
+            };
+            (child, action, action_str)
+        })
+        .collect()
+}
+
+fn normalize_move_string(move_str: &str) -> String {
+    move_str
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '#')
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Finds the legal move from `state` written as `move_str`, in the notation used by search
+/// results. Whitespace, case and the trailing `#` of winning moves are ignored
+pub fn parse_move_string(
+    state: &FullGameState,
+    move_str: &str,
+) -> Result<(FullGameState, GenericMove), String> {
+    if state.get_winner().is_some() {
+        return Err("Game is already over".to_owned());
+    }
+    state.validation_err()?;
+
+    let target = normalize_move_string(move_str);
+    // Search move generation stops at the first win, but any legal move can be named
+    with_move_strings(state, state.get_complete_next_states_with_actions())
+        .into_iter()
+        .find(|(_, _, action_str)| normalize_move_string(action_str) == target)
+        .map(|(child, action, _)| (child, action))
+        .ok_or_else(|| format!("Not a legal move: {}", move_str))
+}
+
+/// Follows the best moves stored in `tt` from `state`
+fn read_line_from_tt(
+    tt: &mut TranspositionTable,
+    state: &FullGameState,
+    max_plies: usize,
+) -> Vec<RefutationStep> {
+    let mut result = Vec::new();
+    let mut current = state.clone();
+
+    while result.len() < max_plies && current.get_winner().is_none() {
+        let Some(tt_entry) = tt.fetch(&current, 0) else {
+            break;
+        };
+        // Hash collisions could hand back a move from a different position
+        let Some((child, _, action_str)) = next_states_with_strings(&current)
+            .into_iter()
+            .find(|(_, action, _)| *action == tt_entry.best_action)
+        else {
+            break;
+        };
+
+        result.push(RefutationStep {
+            action_str,
+            next_state: child.clone(),
+        });
+        current = child;
+    }
+
+    result
+}
+
+/// Plays `forced_state` from `state`, then searches the reply for `nodes` nodes
+pub fn refute_child_state(
+    tt: &mut TranspositionTable,
+    state: &FullGameState,
+    forced_state: &FullGameState,
+    nodes: usize,
+) -> Result<RefutationLine, String> {
+    let mover = get_acting_player(state)?;
+    let move_str = next_states_with_strings(state)
+        .into_iter()
+        .find(|(child, _, _)| child == forced_state)
+        .map(|(_, _, action_str)| action_str)
+        .ok_or("Not a legal move from this position")?;
+
+    if let Some(winner) = forced_state.get_winner() {
+        return Ok(RefutationLine {
+            move_str,
+            forced_state: forced_state.clone(),
+            score: match winner == mover {
+                true => win_at_ply(0),
+                false => -win_at_ply(0),
+            },
+            depth: 0,
+            nodes_visited: 0,
+            line: Vec::new(),
+        });
+    }
+
+    let mut search_context =
+        SearchContext::new(tt, DynamicNodesVisitedSearchTerminator::new(nodes));
+    let search_state = negamax_search(
+        &mut search_context,
+        forced_state.clone(),
+        get_past_win_search_terminator(),
+    )
+    .map_err(|err| err.to_string())?;
+    let reply = search_state
+        .best_move
+        .ok_or("Search found no reply to this move")?;
+
+    let mut line = vec![RefutationStep {
+        action_str: reply.action_str.clone(),
+        next_state: reply.child_state.clone(),
+    }];
+    line.extend(read_line_from_tt(
+        search_context.tt,
+        &reply.child_state,
+        MAX_LINE_PLIES,
+    ));
+
+    Ok(RefutationLine {
+        move_str,
+        forced_state: forced_state.clone(),
+        score: score_for_mover(mover, forced_state, reply.score),
+        depth: reply.depth,
+        nodes_visited: search_state.nodes_visited,
+        line,
+    })
+}
+
+/// Forces `action` as the move from `state`, and returns the opponent's best answer to it
+pub fn refute_move(
+    state: &FullGameState,
+    action: &str,
+    nodes: usize,
+) -> Result<RefutationLine, String> {
+    let (forced_state, _) = parse_move_string(state, action)?;
+    let mut tt = TranspositionTable::new();
+    refute_child_state(&mut tt, state, &forced_state, nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fen::parse_fen, player::Player, search::WINNING_SCORE_BUFFER};
+
+    use super::*;
+
+    const REFUTE_NODES: usize = 5_000;
+
+    #[test]
+    fn test_parse_move_string_round_trip() {
+        let state = parse_fen("0120000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        for (child, _, action_str) in next_states_with_strings(&state) {
+            let (parsed_child, _) = parse_move_string(&state, &action_str).unwrap();
+            assert_eq!(parsed_child, child, "{}", action_str);
+
+            let (parsed_child, _) = parse_move_string(&state, &action_str.to_lowercase()).unwrap();
+            assert_eq!(parsed_child, child, "{}", action_str);
+        }
+
+        assert!(parse_move_string(&state, "A1>A1^A1").is_err());
+    }
+
+    #[test]
+    fn test_parse_move_string_beside_a_win() {
+        // Search move generation would stop at A5>B5, leaving out the E1 worker's moves
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let (child, _) = parse_move_string(&state, "E1>D2^D1").unwrap();
+        assert!(child.get_winner().is_none());
+    }
+
+    #[test]
+    fn test_refute_losing_move() {
+        // Player 2 wins from A5 next turn, and Player 1 can't stop it
+        let state = parse_fen("2300000000000000000000000/1/mortal:C1,E3/mortal:A5,E1").unwrap();
+        let (_, _, action_str) = next_states_with_strings(&state).remove(0);
+
+        let refutation = refute_move(&state, &action_str, REFUTE_NODES).unwrap();
+        assert!(refutation.score <= -WINNING_SCORE_BUFFER);
+        assert_eq!(
+            refutation.line[0].next_state.get_winner(),
+            Some(Player::Two)
+        );
+    }
+
+    #[test]
+    fn test_refute_winning_move() {
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let (winning_state, _, action_str) = next_states_with_strings(&state)
+            .into_iter()
+            .find(|(child, _, _)| child.get_winner() == Some(Player::One))
+            .unwrap();
+
+        let refutation = refute_move(&state, &action_str, REFUTE_NODES).unwrap();
+        assert_eq!(refutation.forced_state, winning_state);
+        assert!(refutation.score >= WINNING_SCORE_BUFFER);
+        assert!(refutation.line.is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{
+    board::FullGameState,
+    fen::game_state_to_fen,
+    player::Player,
+    pretty_board::get_acting_player,
+    review::{MoveJudgment, MoveSeverity},
+    search::{Heuristic, WINNING_SCORE, WINNING_SCORE_BUFFER},
+    svg::state_to_svg,
+    uci_types::GameResult,
+};
+
+/// A single move of a reported game, with whatever analysis is known about it
+#[derive(Clone, Debug)]
+pub struct ReportMove {
+    pub parent: FullGameState,
+    pub child: FullGameState,
+    pub comment: String,
+    pub judgment: Option<MoveJudgment>,
+}
+
+/// Markdown summary of a game: a table with every move, the engine's preferred move and any
+/// comments, followed by a board diagram for each blunder
+#[derive(Clone, Debug)]
+pub struct GameReport {
+    pub title: String,
+    pub moves: Vec<ReportMove>,
+    /// None for games that are still going
+    pub result: Option<GameResult>,
+}
+
+impl GameReport {
+    /// A report on the moves between consecutive `states`, without comments. Steps that aren't
+    /// legal moves, like board edits, are left out
+    pub fn from_states(title: impl Into<String>, states: &[FullGameState]) -> Self {
+        let moves: Vec<ReportMove> = states
+            .windows(2)
+            .filter(|pair| move_text(&pair[0], &pair[1]).is_some())
+            .map(|pair| ReportMove {
+                parent: pair[0].clone(),
+                child: pair[1].clone(),
+                comment: String::new(),
+                judgment: None,
+            })
+            .collect();
+        let result = Self::result_after(&moves);
+        Self {
+            title: title.into(),
+            moves,
+            result,
+        }
+    }
+
+    /// The result of the position `moves` end in, by the rules
+    pub fn result_after(moves: &[ReportMove]) -> Option<GameResult> {
+        let last = moves.last()?;
+        GameResult::from_states(Some(&last.parent), &last.child)
+    }
+
+    /// Attaches the results of `review_game` to the moves they judged
+    pub fn with_judgments(mut self, judgments: impl IntoIterator<Item = MoveJudgment>) -> Self {
+        for judgment in judgments {
+            if let Some(report_move) = self
+                .moves
+                .iter_mut()
+                .find(|m| m.judgment.is_none() && m.child == judgment.played_state)
+            {
+                report_move.judgment = Some(judgment);
+            }
+        }
+        self
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut result = format!("# {}\n\n", self.title);
+        if let Some(first) = self.moves.first() {
+            let _ = writeln!(
+                result,
+                "Player One: {}, Player Two: {}\n",
+                first.parent.gods[0].god_name.as_ref(),
+                first.parent.gods[1].god_name.as_ref()
+            );
+        }
+
+        if let Some(game_result) = self.result {
+            let _ = writeln!(result, "Result: {}\n", game_result);
+        }
+
+        result.push_str(
+            "| Ply | Player | Move | Best move | Best score | Verdict | FEN | Comment |\n",
+        );
+        result.push_str("|---|---|---|---|---|---|---|---|\n");
+        for report_move in &self.moves {
+            let (best_move, score, verdict) = match &report_move.judgment {
+                Some(judgment) => (
+                    judgment
+                        .best_move
+                        .as_ref()
+                        .map_or("(played)".to_owned(), |best| {
+                            move_text(&report_move.parent, &best.child_state)
+                                .unwrap_or_else(|| "?".to_owned())
+                        }),
+                    score_text(judgment.best_score),
+                    judgment
+                        .severity
+                        .map_or(String::new(), |severity| severity.to_string()),
+                ),
+                None => (String::new(), String::new(), String::new()),
+            };
+            let _ = writeln!(
+                result,
+                "| {} | {} | {} | {} | {} | {} | `{}` | {} |",
+                report_move.parent.ply,
+                player_text(&report_move.parent),
+                move_text(&report_move.parent, &report_move.child).unwrap_or_default(),
+                best_move,
+                score,
+                verdict,
+                game_state_to_fen(&report_move.parent),
+                table_cell(&report_move.comment)
+            );
+        }
+
+        let blunders: Vec<&ReportMove> = self
+            .moves
+            .iter()
+            .filter(|m| {
+                m.judgment
+                    .as_ref()
+                    .is_some_and(|j| j.severity == Some(MoveSeverity::Blunder))
+            })
+            .collect();
+        if !blunders.is_empty() {
+            result.push_str("\n## Blunders\n");
+        }
+        for report_move in blunders {
+            let judgment = report_move.judgment.as_ref().unwrap();
+            let _ = writeln!(
+                result,
+                "\n### Ply {}: {} by {}\n",
+                report_move.parent.ply,
+                move_text(&report_move.parent, &report_move.child).unwrap_or_default(),
+                player_text(&report_move.parent)
+            );
+            let _ = writeln!(result, "`{}`\n", game_state_to_fen(&report_move.parent));
+            let _ = writeln!(
+                result,
+                "Scored {}, where the engine's choice scored {}\n",
+                score_text(judgment.played_score),
+                score_text(judgment.best_score)
+            );
+            let _ = writeln!(result, "{}", state_to_svg(&report_move.parent));
+            if !report_move.comment.is_empty() {
+                let _ = writeln!(result, "\n{}", report_move.comment);
+            }
+        }
+
+        result
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_markdown()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// The move from `parent` to `child` in engine notation, or None if it isn't a legal move
+fn move_text(parent: &FullGameState, child: &FullGameState) -> Option<String> {
+    parent
+        .describe_moves_with::<false>()
+        .into_iter()
+        .find(|description| &description.next_state == child)
+        .map(|description| description.action_str)
+}
+
+fn player_text(state: &FullGameState) -> &'static str {
+    match get_acting_player(state) {
+        Ok(Player::One) => "P1",
+        Ok(Player::Two) => "P2",
+        Err(_) => "?",
+    }
+}
+
+fn score_text(score: Heuristic) -> String {
+    match score {
+        x @ WINNING_SCORE_BUFFER.. => format!("Win in {}", WINNING_SCORE - x),
+        x if x <= -WINNING_SCORE_BUFFER => format!("Loss in {}", WINNING_SCORE + x),
+        x => format!("{}", x),
+    }
+}
+
+/// Keeps free text from breaking out of its table cell
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fen::parse_fen, review::review_game, uci_types::DrawReason, win_reason::WinReason,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_report_flags_missed_win() {
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let played = state
+            .get_next_states()
+            .into_iter()
+            .find(|child| child.get_winner().is_none())
+            .unwrap();
+        let states = [state, played];
+
+        let mut report = GameReport::from_states("Test game", &states)
+            .with_judgments(review_game(&states, 5_000));
+        report.moves[0].comment = "Missed | it".to_owned();
+        let markdown = report.to_markdown();
+
+        assert!(markdown.starts_with("# Test game\n"));
+        assert!(!markdown.contains("Result:"));
+        assert!(markdown.contains("| blunder |"));
+        assert!(markdown.contains("Missed \\| it"));
+        assert!(markdown.contains("## Blunders"));
+        assert_eq!(markdown.matches("<svg").count(), 1);
+        // Both workers of both players are drawn
+        assert_eq!(markdown.matches("stroke-width=\"2\"").count(), 4);
+    }
+
+    #[test]
+    fn test_report_result() {
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let won = state
+            .get_next_states()
+            .into_iter()
+            .find(|child| child.get_winner().is_some())
+            .unwrap();
+
+        let mut report = GameReport::from_states("Won game", &[state, won]);
+        assert_eq!(
+            report.result,
+            Some(GameResult::Win(Player::One, Some(WinReason::MovedToLevel3)))
+        );
+        assert!(
+            report
+                .to_markdown()
+                .contains("Result: Player One wins (moved to level 3)")
+        );
+
+        report.result = Some(GameResult::Draw(DrawReason::MoveLimit));
+        assert!(report.to_markdown().contains("Result: Draw (move limit)"));
+    }
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::FullGameState,
+    player::Player,
+    pretty_board::get_acting_player,
+    search::{
+        BestSearchResult, Heuristic, SearchContext, WINNING_SCORE_BUFFER,
+        get_past_win_search_terminator, negamax_search, win_at_ply,
+    },
+    search_terminators::DynamicNodesVisitedSearchTerminator,
+    transposition_table::TranspositionTable,
+};
+
+/// Score drops at or above these are flagged, in order of severity
+pub const INACCURACY_THRESHOLD: Heuristic = 100;
+pub const MISTAKE_THRESHOLD: Heuristic = 250;
+pub const BLUNDER_THRESHOLD: Heuristic = 500;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveSeverity {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveSeverity {
+    pub fn from_score_drop(drop: Heuristic) -> Option<Self> {
+        if drop >= BLUNDER_THRESHOLD {
+            Some(MoveSeverity::Blunder)
+        } else if drop >= MISTAKE_THRESHOLD {
+            Some(MoveSeverity::Mistake)
+        } else if drop >= INACCURACY_THRESHOLD {
+            Some(MoveSeverity::Inaccuracy)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for MoveSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            MoveSeverity::Inaccuracy => "inaccuracy",
+            MoveSeverity::Mistake => "mistake",
+            MoveSeverity::Blunder => "blunder",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// Scores of the engine's best move and of a played move, from the perspective of the player
+/// making the move
+#[derive(Clone, Debug)]
+pub struct RootMoveScores {
+    pub best: BestSearchResult,
+    pub played_score: Heuristic,
+}
+
+/// Verdict on a single move of a game
+#[derive(Clone, Debug)]
+pub struct MoveJudgment {
+    /// Index of the position the move was played from
+    pub ply: usize,
+    pub player: Player,
+    pub played_state: FullGameState,
+    pub played_score: Heuristic,
+    pub best_score: Heuristic,
+    /// The engine's preferred move, if it differs from the played one
+    pub best_move: Option<BestSearchResult>,
+    pub severity: Option<MoveSeverity>,
+}
+
+impl MoveJudgment {
+    pub fn score_drop(&self) -> Heuristic {
+        (self.best_score - self.played_score).max(0)
+    }
+}
+
+pub(crate) fn search_fixed_nodes(
+    tt: &mut TranspositionTable,
+    state: &FullGameState,
+    nodes: usize,
+) -> Option<BestSearchResult> {
+    let mut search_context =
+        SearchContext::new(tt, DynamicNodesVisitedSearchTerminator::new(nodes));
+    // Book scores come from much longer searches, and would skew comparisons between moves
+    search_context.params.use_placement_book = false;
+    match negamax_search(
+        &mut search_context,
+        state.clone(),
+        get_past_win_search_terminator(),
+    ) {
+        Ok(search_state) => search_state.best_move,
+        Err(err) => {
+            log::error!("{}", err);
+            None
+        }
+    }
+}
+
+/// Searches `state` and the position after `played` for `nodes` nodes each.
+/// Returns None if `state` is already over, or the search finds no move
+pub fn get_root_move_scores(
+    tt: &mut TranspositionTable,
+    state: &FullGameState,
+    played: &FullGameState,
+    nodes: usize,
+) -> Option<RootMoveScores> {
+    if state.get_winner().is_some() {
+        return None;
+    }
+    let mover = get_acting_player(state).ok()?;
+    let best = search_fixed_nodes(tt, state, nodes)?;
+
+    let played_score = if &best.child_state == played {
+        best.score
+    } else if let Some(winner) = played.get_winner() {
+        match winner == mover {
+            true => win_at_ply(0),
+            false => -win_at_ply(0),
+        }
+    } else {
+        let child_score = search_fixed_nodes(tt, played, nodes)?.score;
+        score_for_mover(mover, played, child_score)
+    };
+
+    Some(RootMoveScores { best, played_score })
+}
+
+/// Converts the search score of `child`, from the perspective of the player to move there, into
+/// the perspective of `mover`, the player who made the move leading to `child`
+pub(crate) fn score_for_mover(
+    mover: Player,
+    child: &FullGameState,
+    child_score: Heuristic,
+) -> Heuristic {
+    if get_acting_player(child) == Ok(mover) {
+        return child_score;
+    }
+
+    // Forced results are one ply further away from the parent
+    let score = -child_score;
+    if score >= WINNING_SCORE_BUFFER {
+        score - 1
+    } else if score <= -WINNING_SCORE_BUFFER {
+        score + 1
+    } else {
+        score
+    }
+}
+
+/// Judges every move in `history` against the engine's choice, searching `nodes_per_position`
+/// nodes per position. Steps that aren't legal moves, like board edits, are skipped
+pub fn review_game(history: &[FullGameState], nodes_per_position: usize) -> Vec<MoveJudgment> {
+    review_game_with_progress(history, nodes_per_position, |_, _| {})
+}
+
+/// Same as `review_game`, reporting `progress(done, total)` as moves are reviewed
+pub fn review_game_with_progress(
+    history: &[FullGameState],
+    nodes_per_position: usize,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<MoveJudgment> {
+    let mut tt = TranspositionTable::new();
+    let mut result = Vec::new();
+    let total = history.len().saturating_sub(1);
+
+    for (ply, pair) in history.windows(2).enumerate() {
+        let (state, played) = (&pair[0], &pair[1]);
+        progress(ply, total);
+
+        let is_legal_move = state.get_winner().is_none()
+            && state.validation_err().is_ok()
+            && state.get_next_states().contains(played);
+        if !is_legal_move {
+            continue;
+        }
+
+        let Ok(player) = get_acting_player(state) else {
+            continue;
+        };
+        let Some(scores) = get_root_move_scores(&mut tt, state, played, nodes_per_position) else {
+            continue;
+        };
+
+        let best_score = scores.best.score.max(scores.played_score);
+        let severity = MoveSeverity::from_score_drop(best_score - scores.played_score);
+        let best_move = (&scores.best.child_state != played).then_some(scores.best);
+        result.push(MoveJudgment {
+            ply,
+            player,
+            played_state: played.clone(),
+            played_score: scores.played_score,
+            best_score,
+            best_move,
+            severity,
+        });
+    }
+
+    progress(total, total);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen::parse_fen;
+
+    use super::*;
+
+    const REVIEW_NODES: usize = 5_000;
+
+    #[test]
+    fn test_review_flags_missed_win() {
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let played = state
+            .get_next_states()
+            .into_iter()
+            .find(|child| child.get_winner().is_none())
+            .unwrap();
+
+        let judgments = review_game(&[state, played], REVIEW_NODES);
+        assert_eq!(judgments.len(), 1);
+        let judgment = &judgments[0];
+        assert_eq!(judgment.player, Player::One);
+        assert_eq!(judgment.severity, Some(MoveSeverity::Blunder));
+        assert!(judgment.best_score >= WINNING_SCORE_BUFFER);
+        assert!(judgment.best_move.is_some());
+    }
+
+    #[test]
+    fn test_review_accepts_winning_move() {
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let played = state
+            .get_next_states()
+            .into_iter()
+            .find(|child| child.get_winner() == Some(Player::One))
+            .unwrap();
+
+        let judgments = review_game(&[state, played], REVIEW_NODES);
+        assert_eq!(judgments.len(), 1);
+        assert_eq!(judgments[0].severity, None);
+    }
+
+    #[test]
+    fn test_review_skips_edits() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        let edited = parse_fen("1000000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+
+        assert!(review_game(&[state, edited], REVIEW_NODES).is_empty());
+    }
+}
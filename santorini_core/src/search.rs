@@ -1,17 +1,26 @@
-use std::{array, fmt::Debug};
+use std::{array, cmp::Reverse, fmt::Debug};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    bitboard::BitBoard,
-    board::FullGameState,
-    gods::generic::{GenericMove, KILLER_MATCH_SCORE, MoveScore},
+    bitboard::{BitBoard, NEIGHBOR_MAP, NUM_SQUARES, apply_mapping_to_mask},
+    board::{ConsoleRenderOptions, FullGameState},
+    eval::evaluate_state,
+    fen::game_state_to_fen,
+    gods::{
+        StaticGod,
+        generic::{GenericMove, KILLER_MATCH_SCORE, MOVE_DATA_MAIN_SECTION, MoveScore},
+    },
+    matchup::matchup_policy,
     move_picker::{MovePicker, MovePickerStage},
     nnue::LabeledAccumulator,
     placement::{PlacementState, get_starting_placement_state},
+    placement_book::placement_book,
+    player::Player,
+    search_observer::{NoopSearchObserver, SearchObserver},
     search_terminators::SearchTerminator,
-    transposition_table::SearchScoreType,
-    utils::{hash_u64, timestamp_string},
+    transposition_table::{SearchScoreType, TTValue},
+    utils::hash_u64,
 };
 
 use super::transposition_table::TranspositionTable;
@@ -23,10 +32,46 @@ pub const WINNING_SCORE: Heuristic = 10_000;
 pub const INFINITY: Heuristic = WINNING_SCORE * 2;
 pub const WINNING_SCORE_BUFFER: Heuristic = 9000;
 
+/// Most plies of the expected line read back from the TT after a new best move
+const MAX_PV_PLIES: usize = 12;
+
 pub const fn win_at_ply(ply: usize) -> Heuristic {
     WINNING_SCORE - ply as Heuristic
 }
 
+/// The game ply on which a forced result lands, given its score from a search of a position on
+/// `root_ply`. Scores count plies from the search root, and the TT stores them relative to each
+/// node, so this is the only place the game's ply comes in. None if `score` isn't forced
+pub fn forced_result_game_ply(score: Heuristic, root_ply: usize) -> Option<usize> {
+    if score.abs() < WINNING_SCORE_BUFFER {
+        return None;
+    }
+    Some(root_ply + (WINNING_SCORE - score.abs()) as usize)
+}
+
+/// `komi`, which is in favour of player 1, from the point of view of `player`
+pub const fn komi_for_player(komi: Heuristic, player: Player) -> Heuristic {
+    match player {
+        Player::One => komi,
+        Player::Two => -komi,
+    }
+}
+
+/// Static eval of `state` for the player to move, including the matchup's komi. Never reaches a
+/// winning score, however large the komi
+fn evaluate_with_komi(
+    state: &FullGameState,
+    nnue_acc: &mut LabeledAccumulator,
+    komi: Heuristic,
+) -> Heuristic {
+    let eval = evaluate_state(state, nnue_acc);
+    if komi == 0 {
+        return eval;
+    }
+    eval.saturating_add(komi_for_player(komi, state.board.current_player))
+        .clamp(-WINNING_SCORE_BUFFER + 1, WINNING_SCORE_BUFFER - 1)
+}
+
 const HALF_USIZE: u32 = size_of::<usize>() as u32 / 2;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -36,7 +81,24 @@ pub enum BestMoveTrigger {
     EndOfLine,
     Improvement,
     Saved,
+    /// The best root move by static eval, reported before any depth is searched
     Seed,
+    Stable,
+    /// There's only one legal move, so the search stopped after a shallow verification search
+    OnlyMove,
+    /// The move came from the placement book, without searching
+    Book,
+}
+
+impl BestMoveTrigger {
+    /// Whether a later result can stand in for this one. Only progress reports qualify: results
+    /// that end a search, or that callers act on, are always delivered
+    pub fn is_coalescable(&self) -> bool {
+        matches!(
+            self,
+            BestMoveTrigger::Improvement | BestMoveTrigger::Seed | BestMoveTrigger::Saved
+        )
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -47,7 +109,16 @@ pub struct BestSearchResult {
     pub score: Heuristic,
     pub depth: usize,
     pub nodes_visited: usize,
+    /// Deepest ply reached by the search, including quiescence
+    pub seldepth: usize,
+    pub tt_probes: usize,
+    pub tt_hits: usize,
+    /// Earlier results of the same search that were coalesced away instead of being reported.
+    /// Filled in by `EngineThreadWrapper`
+    pub suppressed_updates: usize,
     pub trigger: BestMoveTrigger,
+    /// The expected line, starting with `action`. Read back from the TT, so it can be cut short
+    pub pv: Vec<GenericMove>,
 }
 
 impl BestSearchResult {
@@ -73,15 +144,168 @@ impl BestSearchResult {
             score,
             depth,
             nodes_visited,
+            seldepth: 0,
+            tt_probes: 0,
+            tt_hits: 0,
+            suppressed_updates: 0,
             trigger,
+            pv: vec![action],
+        }
+    }
+
+    /// Fills in the seldepth and TT counters from the search that produced this move
+    pub fn with_search_stats(mut self, search_state: &SearchState) -> Self {
+        self.seldepth = search_state.seldepth;
+        self.tt_probes = search_state.tt_probes;
+        self.tt_hits = search_state.tt_hits;
+        self
+    }
+
+    /// Extends the PV with the best moves stored in `tt` after this one
+    fn with_pv_from_tt(mut self, tt: &mut TranspositionTable) -> Self {
+        let mut current = self.child_state.clone();
+        while self.pv.len() < MAX_PV_PLIES && current.get_winner().is_none() {
+            let Some(tt_entry) = tt.fetch(&current, 0) else {
+                break;
+            };
+            // Hash collisions could hand back a move from a different position
+            let Some((child, action)) = current
+                .get_all_next_states_with_actions()
+                .into_iter()
+                .find(|(_, action)| *action == tt_entry.best_action)
+            else {
+                break;
+            };
+            self.pv.push(action);
+            current = child;
+        }
+        self
+    }
+
+    /// Search speed, given how long the search had been running when this move was found
+    pub fn nodes_per_second(&self, elapsed_seconds: f32) -> Option<f32> {
+        (elapsed_seconds > 0.0).then(|| self.nodes_visited as f32 / elapsed_seconds)
+    }
+
+    /// Fraction of TT probes that found an entry, if any were made
+    pub fn tt_hit_rate(&self) -> Option<f32> {
+        (self.tt_probes > 0).then(|| self.tt_hits as f32 / self.tt_probes as f32)
+    }
+}
+
+/// Tunable thresholds for behaviour on top of the core search
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchParams {
+    /// How many consecutive completed depths must agree on the root move before it's reported
+    /// with BestMoveTrigger::Stable
+    pub stable_move_min_depths: usize,
+    /// How far ahead of every other root move the best move must be to be reported as stable
+    pub stable_move_min_score_gap: Heuristic,
+    /// When the root has a single legal move, the depth to search it to before reporting it with
+    /// BestMoveTrigger::OnlyMove. Gives it a real score, and leaves useful TT entries behind
+    pub only_move_verification_depth: usize,
+    /// Skip worker placements that are symmetric to ones already searched
+    pub reduce_placement_symmetry: bool,
+    /// Play placements straight from the placement book when it covers the position
+    pub use_placement_book: bool,
+    /// Order placements by history and the center and adjacency priors. Otherwise only the TT
+    /// placement is moved to the front
+    pub order_placements: bool,
+    /// At the first ply of q-search, also search checks that leave two threats which can't both
+    /// be blocked, instead of standing pat on the static eval
+    pub extend_double_threats: bool,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self {
+            stable_move_min_depths: 8,
+            stable_move_min_score_gap: 200,
+            only_move_verification_depth: 4,
+            reduce_placement_symmetry: true,
+            use_placement_book: true,
+            order_placements: true,
+            extend_double_threats: true,
+        }
+    }
+}
+
+impl SearchParams {
+    /// Names of the params that can be changed with `set`
+    pub const NAMES: [&str; 7] = [
+        "stable_move_min_depths",
+        "stable_move_min_score_gap",
+        "only_move_verification_depth",
+        "reduce_placement_symmetry",
+        "use_placement_book",
+        "order_placements",
+        "extend_double_threats",
+    ];
+
+    /// Sets the param called `name`, parsing `value` as its type
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid value for {}: {}", name, value))
         }
+
+        match name {
+            "stable_move_min_depths" => self.stable_move_min_depths = parse(name, value)?,
+            "stable_move_min_score_gap" => self.stable_move_min_score_gap = parse(name, value)?,
+            "only_move_verification_depth" => {
+                self.only_move_verification_depth = parse(name, value)?
+            }
+            "reduce_placement_symmetry" => self.reduce_placement_symmetry = parse(name, value)?,
+            "use_placement_book" => self.use_placement_book = parse(name, value)?,
+            "order_placements" => self.order_placements = parse(name, value)?,
+            "extend_double_threats" => self.extend_double_threats = parse(name, value)?,
+            _ => {
+                return Err(format!(
+                    "Unknown search param: {}. Expected one of {}",
+                    name,
+                    Self::NAMES.join(", ")
+                ));
+            }
+        }
+        Ok(())
     }
 }
 
-pub struct SearchContext<'a, T: SearchTerminator> {
+/// Restricts which moves are searched from the root position. Moves are matched on their main
+/// data section, like the consistency checker does, so check and win flags don't need to match
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RootMoveFilter {
+    /// Only search these moves
+    Allow(Vec<GenericMove>),
+    /// Search every move except these
+    Deny(Vec<GenericMove>),
+}
+
+impl RootMoveFilter {
+    pub fn allows(&self, action: GenericMove) -> bool {
+        let key = action.0 & MOVE_DATA_MAIN_SECTION;
+        let is_listed = |moves: &Vec<GenericMove>| {
+            moves
+                .iter()
+                .any(|listed| listed.0 & MOVE_DATA_MAIN_SECTION == key)
+        };
+        match self {
+            RootMoveFilter::Allow(moves) => is_listed(moves),
+            RootMoveFilter::Deny(moves) => !is_listed(moves),
+        }
+    }
+}
+
+pub struct SearchContext<'a, T: SearchTerminator, O: SearchObserver = NoopSearchObserver> {
     pub tt: &'a mut TranspositionTable,
     pub new_best_move_callback: Box<dyn FnMut(BestSearchResult)>,
     pub terminator: T,
+    pub params: SearchParams,
+    /// Restricts the root moves searched. If no legal move is allowed, the search returns
+    /// without a best move
+    pub root_move_filter: Option<RootMoveFilter>,
+    pub observer: O,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -148,6 +372,12 @@ const GLOBAL_MOVE_HISTORY_MAX: HistoryDelta = 1024;
 const PER_PLY_HISTORY_MAX: HistoryDelta = 4096;
 const RESPONSE_HISTORY_MAX: HistoryDelta = 8192;
 const FOLLOW_HISTORY_MAX: HistoryDelta = 8192;
+const PLACEMENT_HISTORY_MAX: HistoryDelta = 4096;
+
+/// Placement ordering bonus for each placed worker, per square away from the edge of the board
+const PLACEMENT_CENTER_BONUS: MoveScore = 128;
+/// Placement ordering bonus for each placed worker next to an opponent worker
+const PLACEMENT_ADJACENCY_BONUS: MoveScore = 96;
 
 pub const BASE_MOVE_HISTORY_TABLE_SIZE: usize = 999_983;
 pub const MOVE_HISTORY_BY_DEPTH_SIZE: usize = 200_001;
@@ -283,6 +513,54 @@ impl Histories {
     }
 }
 
+/// History of placements that caused cutoffs, indexed by the lowest and highest squares placed on
+pub type PlacementHistory = [[MoveScore; NUM_SQUARES]; NUM_SQUARES];
+
+const fn distance_from_edge(square: usize) -> MoveScore {
+    let (col, row) = (square % 5, square / 5);
+    let col_distance = if col < 4 - col { col } else { 4 - col };
+    let row_distance = if row < 4 - row { row } else { 4 - row };
+    if col_distance < row_distance {
+        col_distance as MoveScore
+    } else {
+        row_distance as MoveScore
+    }
+}
+
+const PLACEMENT_CENTER_PRIOR: [MoveScore; NUM_SQUARES] = {
+    let mut result = [0; NUM_SQUARES];
+    let mut square = 0;
+    while square < NUM_SQUARES {
+        result[square] = distance_from_edge(square) * PLACEMENT_CENTER_BONUS;
+        square += 1;
+    }
+    result
+};
+
+fn placement_history_idx(placed: BitBoard) -> (usize, usize) {
+    if placed.is_empty() {
+        return (0, 0);
+    }
+    (
+        placed.lsb() as usize,
+        31 - placed.0.leading_zeros() as usize,
+    )
+}
+
+/// Ordering score for a placement onto `placed`, before the TT move is considered
+fn placement_order_score(
+    history: &PlacementHistory,
+    placed: BitBoard,
+    opponent_neighbors: BitBoard,
+) -> MoveScore {
+    let (low, high) = placement_history_idx(placed);
+    let mut score = history[low][high];
+    for square in placed {
+        score += PLACEMENT_CENTER_PRIOR[square as usize];
+    }
+    score + (placed & opponent_neighbors).count_ones() as MoveScore * PLACEMENT_ADJACENCY_BONUS
+}
+
 impl Default for Histories {
     fn default() -> Self {
         Self {
@@ -298,9 +576,20 @@ pub struct SearchState {
     pub last_fully_completed_depth: usize,
     pub best_move: Option<BestSearchResult>,
     pub nodes_visited: usize,
+    /// Deepest ply reached so far, including quiescence
+    pub seldepth: usize,
+    pub tt_probes: usize,
+    pub tt_hits: usize,
+    /// Best score of any root move other than the best move, during the current iteration.
+    /// Non-best moves are only searched with a null window, so this is an upper bound
+    pub root_second_best_score: Heuristic,
     pub killer_move_table: [Option<GenericMove>; MAX_PLY],
     pub search_stack: [SearchStackEntry; MAX_PLY],
     pub history: [Histories; 2],
+    pub placement_history: [PlacementHistory; 2],
+    /// Komi of the root matchup from the matchup policy, in favour of player 1. Added to static
+    /// evals, but never to the scores stored in the TT
+    pub komi: Heuristic,
 }
 
 impl Debug for SearchState {
@@ -312,6 +601,7 @@ impl Debug for SearchState {
             )
             .field("best_move", &self.best_move)
             .field("nodes_visited", &self.nodes_visited)
+            .field("seldepth", &self.seldepth)
             // .field("killer_move_table", &self.killer_move_table)
             // .field("search_stack", &self.search_stack)
             .finish()
@@ -324,18 +614,51 @@ impl Default for SearchState {
             last_fully_completed_depth: 0,
             best_move: None,
             nodes_visited: 0,
+            seldepth: 0,
+            tt_probes: 0,
+            tt_hits: 0,
+            root_second_best_score: -INFINITY,
             killer_move_table: [None; MAX_PLY],
             search_stack: array::from_fn(|_| Default::default()),
             history: Default::default(),
+            placement_history: [[[0; NUM_SQUARES]; NUM_SQUARES]; 2],
+            komi: 0,
         }
     }
 }
 
-impl<'a, T: SearchTerminator> SearchContext<'a, T> {
+impl SearchState {
+    fn record_tt_probe(&mut self, tt_entry: &Option<TTValue>) {
+        self.tt_probes += 1;
+        self.tt_hits += tt_entry.is_some() as usize;
+    }
+}
+
+impl<'a, T: SearchTerminator, O: SearchObserver> SearchContext<'a, T, O> {
     pub fn should_stop(&mut self, state: &SearchState) -> bool {
         self.terminator.should_stop(state)
     }
 
+    /// Same context, reporting the search to `observer`
+    pub fn with_observer<O2: SearchObserver>(self, observer: O2) -> SearchContext<'a, T, O2> {
+        SearchContext {
+            tt: self.tt,
+            new_best_move_callback: self.new_best_move_callback,
+            terminator: self.terminator,
+            params: self.params,
+            root_move_filter: self.root_move_filter,
+            observer,
+        }
+    }
+
+    /// Whether nodes at `ply` are reported to the observer
+    #[inline(always)]
+    fn is_observed(&self, ply: usize) -> bool {
+        O::IS_ACTIVE && ply <= self.observer.max_ply()
+    }
+}
+
+impl<'a, T: SearchTerminator> SearchContext<'a, T> {
     pub fn new(tt: &'a mut TranspositionTable, terminator: T) -> Self {
         let new_best_move_callback = Box::new(|_new_best_move: BestSearchResult| {
             // eprintln!("{:?}", _new_best_move);
@@ -345,32 +668,113 @@ impl<'a, T: SearchTerminator> SearchContext<'a, T> {
             tt,
             new_best_move_callback,
             terminator,
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        }
+    }
+}
+
+/// Why `negamax_search` couldn't search a position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    /// The root position is already won, so there's nothing to search
+    TerminalRoot,
+    /// The root position is invalid, or the search broke one of its own invariants on it. Always
+    /// a bug, either in the caller or in move generation
+    InternalInconsistency { fen: String, detail: String },
+}
+
+impl SearchError {
+    fn inconsistency(state: &FullGameState, detail: String) -> Self {
+        SearchError::InternalInconsistency {
+            fen: game_state_to_fen(state),
+            detail,
+        }
+    }
+}
+
+impl std::fmt::Display for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::TerminalRoot => write!(f, "Cannot search an already terminal state"),
+            SearchError::InternalInconsistency { fen, detail } => {
+                write!(f, "Search inconsistency in {}: {}", fen, detail)
+            }
         }
     }
 }
 
-pub fn negamax_search<T>(
-    search_context: &mut SearchContext<T>,
+impl std::error::Error for SearchError {}
+
+pub fn negamax_search<T, O>(
+    search_context: &mut SearchContext<T, O>,
     mut root_state: FullGameState,
     soft_search_terminator_fn: SoftSearchTerminatorFn,
-) -> SearchState
+) -> Result<SearchState, SearchError>
 where
     T: SearchTerminator,
+    O: SearchObserver,
 {
-    let mut search_state = SearchState::default();
+    let mut search_state = SearchState {
+        komi: matchup_policy().komi(&root_state.get_matchup()),
+        ..Default::default()
+    };
+    search_context.tt.new_search();
+    search_context.tt.set_komi(search_state.komi);
 
-    root_state.validate();
+    root_state
+        .validation_err()
+        .map_err(|detail| SearchError::inconsistency(&root_state, detail))?;
     if root_state.get_winner().is_some() {
-        panic!(
-            "Should not search in an already terminal state: {:?}",
-            root_state
+        return Err(SearchError::TerminalRoot);
+    }
+
+    let starting_mode = get_starting_placement_state(&root_state.board, root_state.gods)
+        .map_err(|detail| SearchError::inconsistency(&root_state, detail))?;
+    if starting_mode.is_some()
+        && search_context.params.use_placement_book
+        && search_context.root_move_filter.is_none()
+        && let Some((child_state, action, score)) = placement_book().lookup(&root_state)
+    {
+        let book_move = BestSearchResult::new(
+            child_state,
+            action,
+            true,
+            score,
+            0,
+            0,
+            BestMoveTrigger::Book,
         );
+        search_state.best_move = Some(book_move.clone());
+        (search_context.new_best_move_callback)(book_move);
+        return Ok(search_state);
     }
 
-    let starting_mode = get_starting_placement_state(&root_state.board, root_state.gods).unwrap();
+    let all_next_states = match &search_context.root_move_filter {
+        Some(filter) => {
+            // Search move generation stops at the first win, which would hide every other move
+            // if that win is excluded
+            let next_states: Vec<_> = root_state
+                .get_complete_next_states_with_actions()
+                .into_iter()
+                .filter(|(_, action)| filter.allows(*action))
+                .collect();
+            if next_states.is_empty() {
+                return Ok(search_state);
+            }
+            next_states
+        }
+        None => root_state.get_all_next_states_with_actions(),
+    };
+    let is_only_move = all_next_states.len() == 1;
 
     if let Some(tt_entry) = search_context.tt.fetch(&root_state, 0)
         && tt_entry.best_action != GenericMove::NULL_MOVE
+        && search_context
+            .root_move_filter
+            .as_ref()
+            .is_none_or(|filter| filter.allows(tt_entry.best_action))
     {
         let mut best_child_state = root_state.clone();
 
@@ -387,6 +791,7 @@ where
             let (active_god, other_god) = root_state.get_active_non_active_gods();
             active_god.make_move(&mut best_child_state.board, other_god, tt_entry.best_action);
         }
+        best_child_state.ply += 1;
 
         let new_best_move = BestSearchResult::new(
             best_child_state,
@@ -400,15 +805,19 @@ where
         search_state.best_move = Some(new_best_move.clone());
         (search_context.new_best_move_callback)(new_best_move);
     } else {
-        // Pick a random move to start with, to make sure we don't fail to find any move
-        let all_next_states = root_state.get_all_next_states_with_actions();
-
-        if let Some((next_state, next_action)) = all_next_states.last() {
+        // Start from the best move by static eval, so that a search that's stopped before
+        // finishing any depth still plays something sensible
+        let mover =
+            starting_mode.map_or(root_state.board.current_player, |mode| mode.next_placement);
+        if let Some((seed_idx, seed_score)) =
+            seed_move(&root_state, &all_next_states, mover, search_state.komi)
+        {
+            let (next_state, next_action) = &all_next_states[seed_idx];
             let new_best_move = BestSearchResult::new(
                 next_state.clone(),
-                next_action.clone(),
+                *next_action,
                 starting_mode.is_some(),
-                -INFINITY,
+                seed_score,
                 0,
                 0,
                 BestMoveTrigger::Seed,
@@ -419,6 +828,9 @@ where
     }
 
     let start_depth = starting_mode.is_none() as usize;
+    let mut stable_action: Option<GenericMove> = None;
+    let mut stable_depth_count = 0;
+    let mut is_stable_reported = false;
 
     let mut nnue_acc = LabeledAccumulator::new_from_scratch(
         &root_state.board,
@@ -444,6 +856,17 @@ where
         );
 
         search_state.last_fully_completed_depth = depth;
+        search_context.terminator.on_depth_completed(&search_state);
+        log::trace!(
+            "completed depth {} seldepth {} nodes {} best {:?}",
+            depth,
+            search_state.seldepth,
+            search_state.nodes_visited,
+            search_state
+                .best_move
+                .as_ref()
+                .map(|best_move| (best_move.action, best_move.score)),
+        );
 
         if search_state.best_move.is_none() && !search_context.should_stop(&search_state) {
             // We didn't find _any_ move. Could be:
@@ -455,15 +878,17 @@ where
                 active_god.get_moves_for_search(&root_state, root_state.board.current_player);
 
             if moves.len() > 0 {
-                root_state.print_to_console();
-                panic!(
-                    "{} Moves were available, but didn't make any: depth: {}, {:?}, {:?}. {:?}",
-                    timestamp_string(),
-                    depth,
-                    root_state.board,
-                    moves,
+                log::error!(
+                    "{}\n{:?}",
+                    root_state.to_console_string(ConsoleRenderOptions::ascii()),
                     search_state
                 );
+                let detail = format!(
+                    "Moves were available, but didn't make any: depth: {}, {:?}, {:?}",
+                    depth, root_state.board, moves
+                );
+                debug_assert!(false, "{}", detail);
+                return Err(SearchError::inconsistency(&root_state, detail));
             }
 
             // There's actually no moves to make. Report the loss
@@ -486,6 +911,37 @@ where
             break;
         }
 
+        if let Some(best_move) = &search_state.best_move {
+            if stable_action == Some(best_move.action) {
+                stable_depth_count += 1;
+            } else {
+                stable_action = Some(best_move.action);
+                stable_depth_count = 1;
+            }
+
+            let score_gap = best_move.score as i32 - search_state.root_second_best_score as i32;
+            if !is_stable_reported
+                && stable_depth_count >= search_context.params.stable_move_min_depths
+                && score_gap >= search_context.params.stable_move_min_score_gap as i32
+                && !search_context.should_stop(&search_state)
+            {
+                is_stable_reported = true;
+                let mut stable_move = best_move.clone();
+                stable_move.trigger = BestMoveTrigger::Stable;
+                (search_context.new_best_move_callback)(stable_move);
+            }
+        }
+
+        if is_only_move
+            && depth >= search_context.params.only_move_verification_depth
+            && !search_context.should_stop(&search_state)
+        {
+            let mut best_move = search_state.best_move.clone().unwrap();
+            best_move.trigger = BestMoveTrigger::OnlyMove;
+            (search_context.new_best_move_callback)(best_move);
+            break;
+        }
+
         if soft_search_terminator_fn(&search_state) && !search_context.should_stop(&search_state) {
             let mut best_move = search_state.best_move.clone().unwrap();
             best_move.trigger = BestMoveTrigger::EndOfLine;
@@ -494,11 +950,51 @@ where
         }
     }
 
-    search_state
+    Ok(search_state)
+}
+
+/// The index and score of the root move with the best static eval for `mover`, preferring an
+/// immediate win over any eval
+fn seed_move(
+    root_state: &FullGameState,
+    all_next_states: &[(FullGameState, GenericMove)],
+    mover: Player,
+    komi: Heuristic,
+) -> Option<(usize, Heuristic)> {
+    let mut nnue_acc = LabeledAccumulator::new_from_scratch(
+        &root_state.board,
+        root_state.gods[0].model_god_name,
+        root_state.gods[1].model_god_name,
+    );
+
+    let mut best: Option<(usize, Heuristic)> = None;
+    for (idx, (child, _)) in all_next_states.iter().enumerate() {
+        let score = match child.get_winner() {
+            Some(winner) if winner == mover => win_at_ply(1),
+            Some(_) => -win_at_ply(1),
+            None => {
+                nnue_acc.replace_from_state(child);
+                let eval = evaluate_with_komi(child, &mut nnue_acc, komi);
+                if child.board.current_player == mover {
+                    eval
+                } else {
+                    -eval
+                }
+            }
+        };
+
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((idx, score));
+        }
+        if score == win_at_ply(1) {
+            break;
+        }
+    }
+    best
 }
 
-fn _root_search<T>(
-    search_context: &mut SearchContext<T>,
+fn _root_search<T, O>(
+    search_context: &mut SearchContext<T, O>,
     search_state: &mut SearchState,
     state: &FullGameState,
     nnue_acc: &mut LabeledAccumulator,
@@ -506,9 +1002,12 @@ fn _root_search<T>(
 ) -> Heuristic
 where
     T: SearchTerminator,
+    O: SearchObserver,
 {
+    search_state.root_second_best_score = -INFINITY;
+
     if let Some(starting_mode) = get_starting_placement_state(&state.board, state.gods).unwrap() {
-        _placement_search::<T, Root>(
+        _placement_search::<T, O, Root>(
             search_context,
             search_state,
             state,
@@ -520,7 +1019,7 @@ where
             INFINITY,
         )
     } else {
-        _start_inner_search::<T, Root>(
+        _start_inner_search::<T, O, Root>(
             search_context,
             search_state,
             state,
@@ -533,8 +1032,17 @@ where
     }
 }
 
-fn _start_inner_search<T, NT>(
-    search_context: &mut SearchContext<T>,
+fn track_root_second_best(search_state: &mut SearchState, score: Heuristic, best_score: Heuristic) {
+    let runner_up = if score > best_score {
+        best_score
+    } else {
+        score
+    };
+    search_state.root_second_best_score = search_state.root_second_best_score.max(runner_up);
+}
+
+fn _start_inner_search<T, O, NT>(
+    search_context: &mut SearchContext<T, O>,
     search_state: &mut SearchState,
     state: &FullGameState,
     nnue_acc: &mut LabeledAccumulator,
@@ -545,6 +1053,7 @@ fn _start_inner_search<T, NT>(
 ) -> Heuristic
 where
     T: SearchTerminator,
+    O: SearchObserver,
     NT: NodeType,
 {
     let (_active_god, other_god) = state.get_active_non_active_gods();
@@ -554,7 +1063,7 @@ where
         .len()
         > 0;
 
-    _inner_search::<T, NT>(
+    _inner_search::<T, O, NT>(
         search_context,
         search_state,
         state,
@@ -569,8 +1078,8 @@ where
     )
 }
 
-fn _placement_search<T, NT>(
-    search_context: &mut SearchContext<T>,
+fn _placement_search<T, O, NT>(
+    search_context: &mut SearchContext<T, O>,
     search_state: &mut SearchState,
     state: &FullGameState,
     nnue_acc: &mut LabeledAccumulator,
@@ -582,15 +1091,23 @@ fn _placement_search<T, NT>(
 ) -> Heuristic
 where
     T: SearchTerminator,
+    O: SearchObserver,
     NT: NodeType,
 {
     debug_assert!(state.validation_err().is_ok());
     // if let Err(err) = state.validation_err() {
     //     panic!("{}", err);
     // }
+    let is_observed = search_context.is_observed(ply);
+    if is_observed {
+        search_context
+            .observer
+            .on_node_enter(ply, state, alpha, beta, remaining_depth as i32);
+    }
 
     search_state.search_stack[ply].eval = -INFINITY;
     search_state.nodes_visited += 1;
+    search_state.seldepth = search_state.seldepth.max(ply);
     let mut best_score = -INFINITY;
 
     let alpha_orig = alpha;
@@ -598,36 +1115,72 @@ where
 
     let active_player = placement_mode.next_placement;
     let active_god = state.gods[active_player as usize];
+    let root_move_filter = search_context
+        .root_move_filter
+        .as_ref()
+        .filter(|_| NT::ROOT);
+    // A filter can name any of a set of symmetric placements, so filtered roots keep them all
     let mut placements =
-        active_god.get_unique_placement_actions(state.gods, &state.board, active_player);
-    let mut best_action = placements[0];
-
-    let tt_entry = search_context.tt.fetch(&state, ply);
-    if let Some(tt_entry) = tt_entry {
-        let tt_move = tt_entry.best_action.into();
-        for i in 1..placements.len() {
-            if placements[i] == tt_move {
-                placements.swap(0, i);
-                break;
-            }
-        }
+        if search_context.params.reduce_placement_symmetry && root_move_filter.is_none() {
+            active_god.get_unique_placement_actions(state.gods, &state.board, active_player)
+        } else {
+            active_god.get_all_placement_actions(state.gods, &state.board, active_player)
+        };
+    if let Some(filter) = root_move_filter {
+        placements.retain(|action| filter.allows(*action));
+    }
+    let is_filtered_root = root_move_filter.is_some();
+
+    let tt_entry = search_context.tt.fetch(state, ply);
+    search_state.record_tt_probe(&tt_entry);
+    let tt_move = tt_entry.map(|tt_entry| tt_entry.best_action);
+
+    // Order placements by the TT move first, then by history and the center and adjacency priors
+    let all_workers = state.board.workers[0] | state.board.workers[1];
+    let opponent_neighbors =
+        apply_mapping_to_mask(state.board.workers[!active_player as usize], &NEIGHBOR_MAP);
+    let history = &search_state.placement_history[active_player as usize];
+    let mut ordered_placements: Vec<(GenericMove, FullGameState, BitBoard, MoveScore)> = placements
+        .into_iter()
+        .map(|action| {
+            let child_state = active_god.make_placement_move_on_clone(action, state, active_player);
+            let placed =
+                (child_state.board.workers[0] | child_state.board.workers[1]) & !all_workers;
+            let order_score = if Some(action) == tt_move {
+                MoveScore::MAX
+            } else if search_context.params.order_placements {
+                placement_order_score(history, placed, opponent_neighbors)
+            } else {
+                0
+            };
+            (action, child_state, placed, order_score)
+        })
+        .collect();
+    if search_context.params.order_placements {
+        ordered_placements.sort_by_key(|(_, _, _, order_score)| Reverse(*order_score));
+    } else if let Some(tt_idx) = ordered_placements
+        .iter()
+        .position(|(_, _, _, order_score)| *order_score == MoveScore::MAX)
+    {
+        ordered_placements.swap(0, tt_idx);
     }
+    let mut best_action = ordered_placements[0].0;
 
     let next_mode = placement_mode.next();
     let turn_switch_score_mult = [-1, 1][placement_mode.is_swapped as usize];
 
     search_state.search_stack[ply].eval = -WINNING_SCORE_BUFFER;
-    for action in placements {
-        let child_state =
-            active_god.make_placement_move_on_clone(action, &state, placement_mode.next_placement);
+    let history_bonus = (75 * (remaining_depth + 1)) as HistoryDelta;
+    for (idx, (action, child_state, placed, _)) in ordered_placements.iter().enumerate() {
+        let action = *action;
         search_state.search_stack[ply].move_hash =
             active_god.get_placement_history_hash(action, &child_state.board);
 
         let score = if let Some(next_mode) = next_mode {
-            -_placement_search::<T, NT::Next>(
+            -_placement_search::<T, O, NT::Next>(
                 search_context,
                 search_state,
-                &child_state,
+                child_state,
                 nnue_acc,
                 next_mode,
                 ply + 1,
@@ -637,10 +1190,10 @@ where
             )
         } else {
             turn_switch_score_mult
-                * _start_inner_search::<T, NT::Next>(
+                * _start_inner_search::<T, O, NT::Next>(
                     search_context,
                     search_state,
-                    &child_state,
+                    child_state,
                     nnue_acc,
                     ply + 1,
                     remaining_depth,
@@ -651,6 +1204,18 @@ where
 
         should_stop = search_context.should_stop(&search_state);
 
+        if NT::ROOT {
+            track_root_second_best(search_state, score, best_score);
+            if O::IS_ACTIVE && !should_stop {
+                search_context.observer.on_root_move_complete(
+                    action,
+                    child_state,
+                    score,
+                    remaining_depth,
+                );
+            }
+        }
+
         if score > best_score {
             best_score = score;
             best_action = action;
@@ -664,7 +1229,9 @@ where
                     remaining_depth,
                     search_state.nodes_visited,
                     BestMoveTrigger::Improvement,
-                );
+                )
+                .with_search_stats(search_state)
+                .with_pv_from_tt(search_context.tt);
 
                 search_state.best_move = Some(new_best_move.clone());
                 (search_context.new_best_move_callback)(new_best_move);
@@ -673,6 +1240,22 @@ where
             if score > alpha {
                 alpha = score;
                 if alpha >= beta {
+                    if is_observed {
+                        search_context.observer.on_beta_cutoff(ply, action, score);
+                    }
+                    let history = &mut search_state.placement_history[active_player as usize];
+                    let (low, high) = placement_history_idx(*placed);
+                    update_history_value::<PLACEMENT_HISTORY_MAX>(
+                        &mut history[low][high],
+                        history_bonus,
+                    );
+                    for (_, _, earlier_placed, _) in &ordered_placements[..idx] {
+                        let (low, high) = placement_history_idx(*earlier_placed);
+                        update_history_value::<PLACEMENT_HISTORY_MAX>(
+                            &mut history[low][high],
+                            -history_bonus,
+                        );
+                    }
                     break;
                 }
             }
@@ -684,7 +1267,8 @@ where
         }
     }
 
-    if !should_stop {
+    // A filtered root's score only covers some of its moves, so it mustn't be reused
+    if !should_stop && !is_filtered_root {
         let tt_score_type = if best_score <= alpha_orig {
             SearchScoreType::UpperBound
         } else if best_score >= beta {
@@ -704,11 +1288,36 @@ where
         );
     }
 
+    if is_observed {
+        search_context.observer.on_node_exit(ply, best_score);
+    }
     best_score
 }
 
-fn _q_extend<T>(
-    search_context: &mut SearchContext<T>,
+/// Whether `player` has two winning moves with disjoint blocker boards, so that no single block on
+/// one of them also stops the other. Blocker boards include the squares the winning workers start
+/// on, which are ignored so that two threats from the same worker count. This only decides what
+/// q-search looks at, so the rare opponent that can block by moving that worker just costs nodes
+fn has_double_threat(state: &FullGameState, god: StaticGod, player: Player) -> bool {
+    let wins = god.get_winning_moves(state, player);
+    if wins.len() < 2 {
+        return false;
+    }
+
+    let own_workers = state.board.workers[player as usize];
+    let blocker_boards: Vec<BitBoard> = wins
+        .iter()
+        .map(|win| god.get_blocker_board(&state.board, win.action) & !own_workers)
+        .collect();
+    blocker_boards.iter().enumerate().any(|(i, first)| {
+        blocker_boards[i + 1..]
+            .iter()
+            .any(|second| (*first & *second).is_empty())
+    })
+}
+
+fn _q_extend<T, O>(
+    search_context: &mut SearchContext<T, O>,
     search_state: &mut SearchState,
     state: &FullGameState,
     nnue_acc: &mut LabeledAccumulator,
@@ -719,10 +1328,15 @@ fn _q_extend<T>(
 ) -> Heuristic
 where
     T: SearchTerminator,
+    O: SearchObserver,
 {
+    // Q-search doesn't touch the search stack, so it can go one ply past the main search's limit
+    debug_assert!(ply <= MAX_PLY, "q-search went past MAX_PLY: {}", ply);
     search_state.nodes_visited += 1;
+    search_state.seldepth = search_state.seldepth.max(ply);
 
     let tt_entry = search_context.tt.fetch(&state, ply);
+    search_state.record_tt_probe(&tt_entry);
     if let Some(tt_value) = &tt_entry {
         match tt_value.score_type {
             SearchScoreType::Exact => {
@@ -757,7 +1371,7 @@ where
     if q_depth > 20 || ply >= MAX_PLY {
         // Give up at some max depth
         nnue_acc.replace_from_state(&state);
-        return nnue_acc.evaluate().min(beta);
+        return evaluate_with_komi(state, nnue_acc, search_state.komi).min(beta);
     }
 
     let eval;
@@ -778,9 +1392,29 @@ where
         );
     } else {
         nnue_acc.replace_from_state(&state);
-        eval = nnue_acc.evaluate();
+        eval = evaluate_with_komi(state, nnue_acc, search_state.komi);
+
+        if eval >= beta || q_depth > 0 || !search_context.params.extend_double_threats {
+            return eval.min(beta);
+        }
 
-        return eval.min(beta);
+        // A quiet position can still have a check that makes two separate threats. Search those
+        // checks, which then only extend through blocks
+        child_moves = active_god
+            .get_moves_for_search(state, state.board.current_player)
+            .into_iter()
+            .filter(|child_move| {
+                child_move.action.get_is_check()
+                    && has_double_threat(
+                        &state.next_state(active_god, other_god, child_move.action),
+                        active_god,
+                        state.board.current_player,
+                    )
+            })
+            .collect();
+        if child_moves.is_empty() {
+            return eval.min(beta);
+        }
     }
 
     if eval >= beta {
@@ -842,8 +1476,53 @@ where
     best_score
 }
 
-fn _inner_search<T, NT>(
-    search_context: &mut SearchContext<T>,
+/// `_search_node`, reported to the observer when `ply` is observed
+#[inline(always)]
+fn _inner_search<T, O, NT>(
+    search_context: &mut SearchContext<T, O>,
+    search_state: &mut SearchState,
+    state: &FullGameState,
+    nnue_acc: &mut LabeledAccumulator,
+    is_in_check: bool,
+    ply: usize,
+    carry_reduction: i32,
+    remaining_depth: i32,
+    alpha: Heuristic,
+    beta: Heuristic,
+    is_cut_node: bool,
+) -> Heuristic
+where
+    T: SearchTerminator,
+    O: SearchObserver,
+    NT: NodeType,
+{
+    let is_observed = search_context.is_observed(ply);
+    if is_observed {
+        search_context
+            .observer
+            .on_node_enter(ply, state, alpha, beta, remaining_depth);
+    }
+    let score = _search_node::<T, O, NT>(
+        search_context,
+        search_state,
+        state,
+        nnue_acc,
+        is_in_check,
+        ply,
+        carry_reduction,
+        remaining_depth,
+        alpha,
+        beta,
+        is_cut_node,
+    );
+    if is_observed {
+        search_context.observer.on_node_exit(ply, score);
+    }
+    score
+}
+
+fn _search_node<T, O, NT>(
+    search_context: &mut SearchContext<T, O>,
     search_state: &mut SearchState,
     state: &FullGameState,
     nnue_acc: &mut LabeledAccumulator,
@@ -857,6 +1536,7 @@ fn _inner_search<T, NT>(
 ) -> Heuristic
 where
     T: SearchTerminator,
+    O: SearchObserver,
     NT: NodeType,
 {
     debug_assert!(state.validation_err().is_ok());
@@ -864,6 +1544,8 @@ where
     //     state.print_to_console();
     //     panic!("{:?}: {}", state, err);
     // }
+    // The killer table and search stack are indexed by ply. Parents stop at MAX_PLY - 1
+    debug_assert!(ply < MAX_PLY, "search went past MAX_PLY: {}", ply);
 
     let current_player_idx = state.board.current_player as usize;
     let other_player_idx = !state.board.current_player;
@@ -903,10 +1585,12 @@ where
     } else {
         search_state.nodes_visited += 1;
     }
+    search_state.seldepth = search_state.seldepth.max(ply);
 
     let mut track_used = false;
     let mut track_unused = false;
     let tt_entry = search_context.tt.fetch(&state, ply);
+    search_state.record_tt_probe(&tt_entry);
 
     if !NT::ROOT {
         if let Some(tt_value) = &tt_entry {
@@ -975,6 +1659,7 @@ where
         None
     };
 
+    let is_filtered_root = NT::ROOT && search_context.root_move_filter.is_some();
     let mut move_picker = MovePicker::new(
         state.board.current_player,
         active_god,
@@ -982,6 +1667,9 @@ where
         search_state.killer_move_table[ply as usize],
         key_squares,
     );
+    if is_filtered_root {
+        move_picker = move_picker.with_root_move_filter(search_context.root_move_filter.clone());
+    }
 
     if !move_picker.has_any_moves(&state) {
         // If this is root, we need to pick a move
@@ -1003,7 +1691,8 @@ where
                     remaining_depth.max(0) as usize,
                     search_state.nodes_visited,
                     BestMoveTrigger::EndOfLine,
-                );
+                )
+                .with_search_stats(search_state);
 
                 search_state.best_move = Some(new_best_move.clone());
                 (search_context.new_best_move_callback)(new_best_move);
@@ -1034,7 +1723,8 @@ where
                 remaining_depth.max(0) as usize,
                 search_state.nodes_visited,
                 BestMoveTrigger::EndOfLine,
-            );
+            )
+            .with_search_stats(search_state);
             search_state.best_move = Some(new_best_move.clone());
             (search_context.new_best_move_callback)(new_best_move);
         }
@@ -1046,7 +1736,7 @@ where
     let eval = if let Some(tt_value) = &tt_entry {
         tt_value.eval
     } else {
-        nnue_acc.evaluate()
+        evaluate_with_komi(state, nnue_acc, search_state.komi)
     };
 
     if ply >= MAX_PLY - 1 {
@@ -1093,7 +1783,7 @@ where
 
             let null_move_child_state = state.next_state_passing(active_god);
 
-            let null_value = -_inner_search::<T, OffPV>(
+            let null_value = -_inner_search::<T, O, OffPV>(
                 search_context,
                 search_state,
                 &null_move_child_state,
@@ -1175,7 +1865,7 @@ where
         if move_idx == 1 {
             child_state = state.next_state(active_god, other_god, child_action);
 
-            score = -_inner_search::<T, NT::Next>(
+            score = -_inner_search::<T, O, NT::Next>(
                 search_context,
                 search_state,
                 &child_state,
@@ -1233,7 +1923,7 @@ where
             child_state = state.next_state(active_god, other_god, child_action);
 
             // Try a 0-window search
-            score = -_inner_search::<T, OffPV>(
+            score = -_inner_search::<T, O, OffPV>(
                 search_context,
                 search_state,
                 &child_state,
@@ -1249,7 +1939,7 @@ where
 
             // If we improve alpha and there was a reduction, try again without that reduction
             if score > alpha && used_reduction >= 1 && next_depth > reduced_depth {
-                score = -_inner_search::<T, OffPV>(
+                score = -_inner_search::<T, O, OffPV>(
                     search_context,
                     search_state,
                     &child_state,
@@ -1266,7 +1956,7 @@ where
 
             // The search failed, try again
             if score > alpha && score < beta {
-                score = -_inner_search::<T, NT::Next>(
+                score = -_inner_search::<T, O, NT::Next>(
                     search_context,
                     search_state,
                     &child_state,
@@ -1284,6 +1974,18 @@ where
 
         should_stop = search_context.should_stop(&search_state);
 
+        if NT::ROOT {
+            track_root_second_best(search_state, score, best_score);
+            if O::IS_ACTIVE && !should_stop {
+                search_context.observer.on_root_move_complete(
+                    child_action,
+                    &child_state,
+                    score,
+                    remaining_depth.max(0) as usize,
+                );
+            }
+        }
+
         if score > best_score {
             best_score = score;
             best_action = child_action;
@@ -1297,7 +1999,9 @@ where
                     remaining_depth.max(0) as usize,
                     search_state.nodes_visited,
                     BestMoveTrigger::Improvement,
-                );
+                )
+                .with_search_stats(search_state)
+                .with_pv_from_tt(search_context.tt);
 
                 search_state.best_move = Some(new_best_move.clone());
                 (search_context.new_best_move_callback)(new_best_move);
@@ -1307,6 +2011,11 @@ where
                 alpha = score;
 
                 if alpha >= beta {
+                    if search_context.is_observed(ply) {
+                        search_context
+                            .observer
+                            .on_beta_cutoff(ply, child_action, score);
+                    }
                     if move_picker.stage == MovePickerStage::YieldNonImprovers {
                         search_state.killer_move_table[ply] = Some(child_action);
                     }
@@ -1342,7 +2051,8 @@ where
         }
     }
 
-    if !should_stop {
+    // A filtered root's score only covers some of its moves, so it mustn't be reused
+    if !should_stop && !is_filtered_root {
         let tt_score_type = if best_score <= alpha_orig {
             SearchScoreType::UpperBound
         } else if best_score >= beta {
@@ -1398,7 +2108,14 @@ mod tests {
     use core::panic;
     use std::{cell::RefCell, rc::Rc};
 
-    use crate::search_terminators::DynamicMaxDepthSearchTerminator;
+    use crate::{
+        gods::GodName,
+        search_terminators::{
+            DynamicMaxDepthSearchTerminator, DynamicNodesVisitedSearchTerminator,
+            DynamicSearchTerminator, SearchLimits,
+        },
+        square::Square,
+    };
 
     use super::*;
 
@@ -1433,16 +2150,620 @@ mod tests {
                 }
             }),
             terminator: DynamicMaxDepthSearchTerminator::new(2),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
         };
 
         let search_state = negamax_search(
             &mut search_context,
             full_state,
             get_win_reached_search_terminator(),
-        );
+        )
+        .unwrap();
 
         let _best_move = search_state.best_move.unwrap();
         // assert!(best_move.score > -WINNING_SCORE_BUFFER);
         // assert!(orig_loss_counter.borrow().clone() <= 1);
     }
+
+    #[test]
+    fn test_set_search_params() {
+        let mut params = SearchParams::default();
+        params.set("stable_move_min_depths", "3").unwrap();
+        params.set("extend_double_threats", "false").unwrap();
+        assert_eq!(params.stable_move_min_depths, 3);
+        assert!(!params.extend_double_threats);
+
+        assert!(params.set("stable_move_min_depths", "-1").is_err());
+        assert!(params.set("not_a_param", "1").is_err());
+        // Every listed name can be set
+        for name in SearchParams::NAMES {
+            let value =
+                if name.ends_with("depths") || name.ends_with("gap") || name.ends_with("depth") {
+                    "1"
+                } else {
+                    "true"
+                };
+            params.set(name, value).unwrap();
+        }
+    }
+
+    fn count_stable_messages(params: SearchParams) -> usize {
+        let full_state =
+            FullGameState::try_from("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5")
+                .unwrap();
+        let stable_counter = Rc::new(RefCell::new(0));
+        let stable_counter_clone = stable_counter.clone();
+        let mut tt = TranspositionTable::new();
+        let mut search_context = SearchContext {
+            tt: &mut tt,
+            new_best_move_callback: Box::new(move |new_best_move| {
+                if new_best_move.trigger == BestMoveTrigger::Stable {
+                    *stable_counter_clone.borrow_mut() += 1;
+                }
+            }),
+            terminator: DynamicMaxDepthSearchTerminator::new(4),
+            params,
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        };
+
+        negamax_search(
+            &mut search_context,
+            full_state,
+            get_past_win_search_terminator(),
+        )
+        .unwrap();
+
+        stable_counter.borrow().clone()
+    }
+
+    #[test]
+    fn test_stable_move_reported_once() {
+        let params = SearchParams {
+            stable_move_min_depths: 1,
+            stable_move_min_score_gap: -INFINITY,
+            ..Default::default()
+        };
+        assert_eq!(count_stable_messages(params), 1);
+    }
+
+    #[test]
+    fn test_stable_move_requires_score_gap() {
+        let params = SearchParams {
+            stable_move_min_depths: 1,
+            stable_move_min_score_gap: INFINITY,
+            ..Default::default()
+        };
+        assert_eq!(count_stable_messages(params), 0);
+    }
+
+    #[test]
+    fn test_forced_result_game_ply() {
+        assert_eq!(forced_result_game_ply(win_at_ply(3), 20), Some(23));
+        assert_eq!(forced_result_game_ply(-win_at_ply(2), 20), Some(22));
+        assert_eq!(forced_result_game_ply(150, 20), None);
+    }
+
+    #[test]
+    fn test_search_stats() {
+        let full_state =
+            FullGameState::try_from("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5")
+                .unwrap();
+        let mut tt = TranspositionTable::new();
+        let mut search_context = SearchContext {
+            tt: &mut tt,
+            new_best_move_callback: Box::new(|_| {}),
+            terminator: DynamicMaxDepthSearchTerminator::new(4),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        };
+
+        let search_state = negamax_search(
+            &mut search_context,
+            full_state,
+            get_past_win_search_terminator(),
+        )
+        .unwrap();
+
+        assert!(search_state.seldepth >= search_state.last_fully_completed_depth);
+        assert!(search_state.tt_hits > 0);
+        assert!(search_state.tt_hits < search_state.tt_probes);
+
+        let best_move = search_state.best_move.unwrap();
+        assert!(best_move.seldepth <= search_state.seldepth);
+        assert!(
+            best_move
+                .tt_hit_rate()
+                .is_some_and(|rate| rate > 0.0 && rate < 1.0)
+        );
+    }
+
+    #[test]
+    fn test_pv_follows_best_move() {
+        let full_state =
+            FullGameState::try_from("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5")
+                .unwrap();
+        let mut tt = TranspositionTable::new();
+        let mut search_context = SearchContext {
+            tt: &mut tt,
+            new_best_move_callback: Box::new(|_| {}),
+            terminator: DynamicMaxDepthSearchTerminator::new(5),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        };
+        let best_move = negamax_search(
+            &mut search_context,
+            full_state.clone(),
+            get_past_win_search_terminator(),
+        )
+        .unwrap()
+        .best_move
+        .unwrap();
+
+        assert_eq!(best_move.pv[0], best_move.action);
+        assert!(best_move.pv.len() > 1);
+        let mut current = full_state;
+        for action in &best_move.pv {
+            current = current
+                .get_all_next_states_with_actions()
+                .into_iter()
+                .find(|(_, a)| a == action)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Deep forced lines: slides and forced responses that keep checks going
+    const PLY_LIMIT_FENS: [&str; 3] = [
+        "0000000000000000000000000/1/maenads:B3,C4/harpies:C3",
+        "1432204411410232444101202/1/stymphalians:D5,E2,B1/harpies:A5,C3",
+        "0422114014424022323422322/1/stymphalians:C2,B1,E1/harpies:C4,D4",
+    ];
+
+    /// Searches `fen` as if it had been reached `ply` plies into a search
+    fn search_from_ply(fen: &str, ply: usize, remaining_depth: i32) -> SearchState {
+        let state = FullGameState::try_from(fen).unwrap();
+        let mut nnue_acc = LabeledAccumulator::new_from_scratch(
+            &state.board,
+            state.gods[0].model_god_name,
+            state.gods[1].model_god_name,
+        );
+        let mut tt = TranspositionTable::with_size(1 << 16);
+        let mut search_context = SearchContext {
+            tt: &mut tt,
+            new_best_move_callback: Box::new(|_| {}),
+            terminator: DynamicMaxDepthSearchTerminator::new(MAX_PLY),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        };
+        let mut search_state = SearchState::default();
+
+        if remaining_depth > 0 {
+            _inner_search::<_, _, OffPV>(
+                &mut search_context,
+                &mut search_state,
+                &state,
+                &mut nnue_acc,
+                false,
+                ply,
+                0,
+                remaining_depth,
+                -INFINITY,
+                INFINITY,
+                false,
+            );
+        } else {
+            _q_extend(
+                &mut search_context,
+                &mut search_state,
+                &state,
+                &mut nnue_acc,
+                ply,
+                0,
+                -INFINITY,
+                INFINITY,
+            );
+        }
+        search_state
+    }
+
+    /// Runs q-search on `fen`, returning its score and node count
+    fn q_search(fen: &str, extend_double_threats: bool) -> (Heuristic, usize) {
+        let state = FullGameState::try_from(fen).unwrap();
+        let mut nnue_acc = LabeledAccumulator::new_from_scratch(
+            &state.board,
+            state.gods[0].model_god_name,
+            state.gods[1].model_god_name,
+        );
+        let mut tt = TranspositionTable::with_size(1 << 16);
+        let mut search_context =
+            SearchContext::new(&mut tt, DynamicMaxDepthSearchTerminator::new(MAX_PLY));
+        search_context.params.extend_double_threats = extend_double_threats;
+        let mut search_state = SearchState::default();
+        let score = _q_extend(
+            &mut search_context,
+            &mut search_state,
+            &state,
+            &mut nnue_acc,
+            0,
+            0,
+            -INFINITY,
+            INFINITY,
+        );
+        (score, search_state.nodes_visited)
+    }
+
+    #[test]
+    fn test_q_search_sees_double_threats() {
+        // Player 2 can step A1 > B2 next to both level 3s on A3 and C3, and player 1 can't reach
+        // either of them
+        let fen = "0000000000303000200020000/2/mortal:E5,E4/mortal:A1,E1";
+        let (score, _) = q_search(fen, false);
+        assert!(score < WINNING_SCORE_BUFFER);
+
+        let (score, nodes) = q_search(fen, true);
+        assert!(score >= WINNING_SCORE_BUFFER);
+        assert!(nodes < 20);
+    }
+
+    /// Positions where a shallow search used to walk into a double threat, with the depth and
+    /// the losing move it played
+    const DOUBLE_THREAT_REGRESSIONS: [(&str, usize, &str); 2] = [
+        (
+            "1011010221121110111112110/1/mortal:E5,B2/mortal:D5,A2",
+            1,
+            "E5>E4^D4",
+        ),
+        (
+            "1120021200010101221100010/2/mortal:E4,B3/mortal:E3,C2",
+            3,
+            "E3>D3^C4",
+        ),
+    ];
+
+    #[test]
+    fn test_double_threat_regressions() {
+        let mut tt = TranspositionTable::with_size(1 << 16);
+        for (fen, depth, losing_move) in DOUBLE_THREAT_REGRESSIONS {
+            let state = FullGameState::try_from(fen).unwrap();
+            let mut best_moves = Vec::new();
+            for extend_double_threats in [false, true] {
+                tt.reset();
+                let mut search_context =
+                    SearchContext::new(&mut tt, DynamicMaxDepthSearchTerminator::new(depth));
+                search_context.params.extend_double_threats = extend_double_threats;
+                let search_state = negamax_search(
+                    &mut search_context,
+                    state.clone(),
+                    get_win_reached_search_terminator(),
+                )
+                .unwrap();
+                best_moves.push(search_state.best_move.unwrap().action_str);
+            }
+            assert_eq!(best_moves[0], losing_move, "{fen}");
+            assert_ne!(best_moves[1], losing_move, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_search_stops_at_max_ply() {
+        for fen in PLY_LIMIT_FENS {
+            for ply in MAX_PLY - 3..MAX_PLY {
+                let search_state = search_from_ply(fen, ply, 3);
+                assert!(search_state.seldepth >= ply);
+                assert!(search_state.seldepth <= MAX_PLY);
+            }
+        }
+    }
+
+    #[test]
+    fn test_q_search_stops_at_max_ply() {
+        for fen in PLY_LIMIT_FENS {
+            for ply in MAX_PLY - 3..=MAX_PLY {
+                let search_state = search_from_ply(fen, ply, 0);
+                assert!(search_state.seldepth <= MAX_PLY);
+            }
+        }
+    }
+
+    fn search_empty_board(params: SearchParams) -> SearchState {
+        let mut tt = TranspositionTable::new();
+        let mut search_context = SearchContext {
+            tt: &mut tt,
+            new_best_move_callback: Box::new(|_| {}),
+            terminator: DynamicMaxDepthSearchTerminator::new(1),
+            params,
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        };
+        negamax_search(
+            &mut search_context,
+            FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal),
+            get_past_win_search_terminator(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_placement_symmetry_reduction() {
+        // Without placement ordering, so that placement history doesn't learn from the symmetric
+        // duplicates that only the full search visits
+        let reduced = search_empty_board(SearchParams {
+            use_placement_book: false,
+            order_placements: false,
+            ..Default::default()
+        });
+        let full = search_empty_board(SearchParams {
+            reduce_placement_symmetry: false,
+            use_placement_book: false,
+            order_placements: false,
+            ..Default::default()
+        });
+
+        let reduced_move = reduced.best_move.unwrap();
+        let full_move = full.best_move.unwrap();
+        assert_eq!(reduced_move.depth, full_move.depth);
+        assert_eq!(reduced_move.score, full_move.score);
+        assert!(reduced.nodes_visited * 4 < full.nodes_visited);
+    }
+
+    #[test]
+    fn test_placement_order_score() {
+        let history: PlacementHistory = [[0; NUM_SQUARES]; NUM_SQUARES];
+        let center = BitBoard::as_mask(Square::C3) | BitBoard::as_mask(Square::B2);
+        let edge = BitBoard::as_mask(Square::A1) | BitBoard::as_mask(Square::E5);
+        assert!(
+            placement_order_score(&history, center, BitBoard::EMPTY)
+                > placement_order_score(&history, edge, BitBoard::EMPTY)
+        );
+
+        let opponent_neighbors = NEIGHBOR_MAP[Square::E5 as usize];
+        let near_opponent = BitBoard::as_mask(Square::D4) | BitBoard::as_mask(Square::B4);
+        let far_from_opponent = BitBoard::as_mask(Square::B2) | BitBoard::as_mask(Square::B4);
+        assert!(
+            placement_order_score(&history, near_opponent, opponent_neighbors)
+                > placement_order_score(&history, far_from_opponent, opponent_neighbors)
+        );
+
+        // History is shared by placements onto the same squares, in either order
+        let mut history = history;
+        let (low, high) = placement_history_idx(edge);
+        assert_eq!((low, high), (Square::E5 as usize, Square::A1 as usize));
+        history[low][high] = 2048;
+        assert!(
+            placement_order_score(&history, edge, BitBoard::EMPTY)
+                > placement_order_score(&history, center, BitBoard::EMPTY)
+        );
+    }
+
+    #[test]
+    fn test_placement_book_skips_search() {
+        // The embedded book always covers Mortal vs Mortal
+        let book_search = search_empty_board(SearchParams::default());
+        let book_move = book_search.best_move.unwrap();
+        assert_eq!(book_move.trigger, BestMoveTrigger::Book);
+        assert_eq!(book_search.nodes_visited, 0);
+
+        let full_search = search_empty_board(SearchParams {
+            use_placement_book: false,
+            ..Default::default()
+        });
+        assert_ne!(
+            full_search.best_move.unwrap().trigger,
+            BestMoveTrigger::Book
+        );
+    }
+
+    #[test]
+    fn test_only_move_stops_early() {
+        // The P1 worker can only move to B4, and then only build back on A5
+        let full_state =
+            FullGameState::try_from("0440040400444000000000000/1/mortal:A5/mortal:D1,E1").unwrap();
+        assert_eq!(full_state.get_all_next_states_with_actions().len(), 1);
+
+        let triggers = Rc::new(RefCell::new(Vec::new()));
+        let triggers_clone = triggers.clone();
+        let mut tt = TranspositionTable::new();
+        let mut search_context = SearchContext {
+            tt: &mut tt,
+            new_best_move_callback: Box::new(move |new_best_move| {
+                triggers_clone.borrow_mut().push(new_best_move.trigger);
+            }),
+            terminator: DynamicMaxDepthSearchTerminator::new(50),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        };
+
+        let search_state = negamax_search(
+            &mut search_context,
+            full_state.clone(),
+            get_past_win_search_terminator(),
+        )
+        .unwrap();
+
+        assert_eq!(triggers.borrow().last(), Some(&BestMoveTrigger::OnlyMove));
+        assert_eq!(
+            search_state.last_fully_completed_depth,
+            SearchParams::default().only_move_verification_depth
+        );
+        assert!(tt.fetch(&full_state, 0).is_some());
+    }
+
+    /// Searches `state` without the placement book, so that placements are always searched
+    fn search_with<T: SearchTerminator>(
+        tt: &mut TranspositionTable,
+        state: &FullGameState,
+        terminator: T,
+        root_move_filter: Option<RootMoveFilter>,
+    ) -> Result<SearchState, SearchError> {
+        let mut search_context = SearchContext {
+            params: SearchParams {
+                use_placement_book: false,
+                ..Default::default()
+            },
+            root_move_filter,
+            ..SearchContext::new(tt, terminator)
+        };
+        negamax_search(
+            &mut search_context,
+            state.clone(),
+            get_past_win_search_terminator(),
+        )
+    }
+
+    #[test]
+    fn test_root_move_filter() {
+        // P1 wins by climbing from A5 to B5, and has nothing else nearly as good
+        let state =
+            FullGameState::try_from("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3")
+                .unwrap();
+        let next_states = state.get_complete_next_states_with_actions();
+        let (_, winning_action) = next_states
+            .iter()
+            .find(|(child, _)| child.get_winner().is_some())
+            .unwrap()
+            .clone();
+
+        let mut tt = TranspositionTable::new();
+        let excluded = search_with(
+            &mut tt,
+            &state,
+            DynamicMaxDepthSearchTerminator::new(4),
+            Some(RootMoveFilter::Deny(vec![winning_action])),
+        )
+        .unwrap();
+        let excluded_move = excluded.best_move.unwrap();
+        assert!(excluded_move.child_state.get_winner().is_none());
+        assert!(excluded_move.score < WINNING_SCORE_BUFFER);
+        // The filtered root isn't stored, so later searches still see the win
+        assert!(tt.fetch(&state, 0).is_none());
+
+        let (allowed_state, allowed_action) = next_states
+            .iter()
+            .find(|(_, action)| *action != winning_action)
+            .unwrap()
+            .clone();
+        let allowed = search_with(
+            &mut tt,
+            &state,
+            DynamicMaxDepthSearchTerminator::new(4),
+            Some(RootMoveFilter::Allow(vec![allowed_action])),
+        )
+        .unwrap();
+        assert_eq!(allowed.best_move.unwrap().child_state, allowed_state);
+
+        let all_moves = next_states.iter().map(|(_, action)| *action).collect();
+        let nothing = search_with(
+            &mut tt,
+            &state,
+            DynamicMaxDepthSearchTerminator::new(4),
+            Some(RootMoveFilter::Deny(all_moves)),
+        )
+        .unwrap();
+        assert!(nothing.best_move.is_none());
+    }
+
+    #[test]
+    fn test_root_move_filter_keeps_symmetric_placements() {
+        let state = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        let (placed_state, placement) = state
+            .get_all_next_states_with_actions()
+            .last()
+            .unwrap()
+            .clone();
+
+        let mut tt = TranspositionTable::new();
+        let search_state = search_with(
+            &mut tt,
+            &state,
+            DynamicMaxDepthSearchTerminator::new(4),
+            Some(RootMoveFilter::Allow(vec![placement])),
+        )
+        .unwrap();
+        assert_eq!(search_state.best_move.unwrap().child_state, placed_state);
+    }
+
+    #[test]
+    fn test_stopped_search_plays_mate_in_one() {
+        // P1 wins by climbing from A5 to B5
+        let state =
+            FullGameState::try_from("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3")
+                .unwrap();
+
+        let timed = search_with(
+            &mut TranspositionTable::new(),
+            &state,
+            DynamicSearchTerminator::new(SearchLimits::new().with_max_millis(1)),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            timed.best_move.unwrap().child_state.get_winner(),
+            Some(Player::One)
+        );
+
+        // Every move, in an order where the win isn't last. Stopping before any search leaves
+        // only the seed
+        let next_states = state.get_complete_next_states_with_actions();
+        assert!(next_states.last().unwrap().0.get_winner().is_none());
+        let all_moves = next_states.into_iter().map(|(_, action)| action).collect();
+        let seeded = search_with(
+            &mut TranspositionTable::new(),
+            &state,
+            DynamicNodesVisitedSearchTerminator::new(0),
+            Some(RootMoveFilter::Allow(all_moves)),
+        )
+        .unwrap();
+        assert_eq!(seeded.last_fully_completed_depth, 0);
+        let seed_move = seeded.best_move.unwrap();
+        assert_eq!(seed_move.child_state.get_winner(), Some(Player::One));
+        assert_eq!(seed_move.score, win_at_ply(1));
+    }
+
+    #[test]
+    fn test_search_terminal_root_is_error() {
+        let state =
+            FullGameState::try_from("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3")
+                .unwrap();
+        let won_state = state
+            .get_next_states()
+            .into_iter()
+            .find(|child| child.get_winner().is_some())
+            .unwrap();
+
+        assert_eq!(
+            search_with(
+                &mut TranspositionTable::new(),
+                &won_state,
+                DynamicMaxDepthSearchTerminator::new(2),
+                None,
+            )
+            .unwrap_err(),
+            SearchError::TerminalRoot
+        );
+    }
+
+    #[test]
+    fn test_search_invalid_root_is_error() {
+        // Player 1 always places first, so it can't be player 2's turn on an empty board
+        let mut state = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
+        state.flip_current_player();
+        assert!(state.validation_err().is_err());
+
+        let err = search_with(
+            &mut TranspositionTable::new(),
+            &state,
+            DynamicMaxDepthSearchTerminator::new(2),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, SearchError::InternalInconsistency { .. }));
+        assert!(err.to_string().contains(&game_state_to_fen(&state)));
+    }
 }
@@ -0,0 +1,460 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::FullGameState, fen::game_state_to_fen, gods::generic::GenericMove, search::Heuristic,
+};
+
+/// Hooks into the tree search, for tracing why the engine prefers a line. Observers are a type
+/// parameter of `SearchContext` rather than a boxed callback, so that hooks for an observer with
+/// `IS_ACTIVE` off are compiled out of the search entirely
+pub trait SearchObserver {
+    const IS_ACTIVE: bool = true;
+
+    /// Nodes deeper than this aren't reported, to bound how much a search produces
+    fn max_ply(&self) -> usize;
+
+    /// A search of `state` started. Nodes are entered and exited in nested order, so every enter
+    /// is matched by an exit at the same ply
+    fn on_node_enter(
+        &mut self,
+        _ply: usize,
+        _state: &FullGameState,
+        _alpha: Heuristic,
+        _beta: Heuristic,
+        _remaining_depth: i32,
+    ) {
+    }
+
+    fn on_node_exit(&mut self, _ply: usize, _score: Heuristic) {}
+
+    /// The node at `ply` stopped searching its moves, because `action` scored at least beta
+    fn on_beta_cutoff(&mut self, _ply: usize, _action: GenericMove, _score: Heuristic) {}
+
+    /// A root move was searched to `depth`. Scores are for the player to move at the root
+    fn on_root_move_complete(
+        &mut self,
+        _action: GenericMove,
+        _child_state: &FullGameState,
+        _score: Heuristic,
+        _depth: usize,
+    ) {
+    }
+}
+
+/// The default observer, which sees nothing
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopSearchObserver;
+
+impl SearchObserver for NoopSearchObserver {
+    const IS_ACTIVE: bool = false;
+
+    fn max_ply(&self) -> usize {
+        0
+    }
+}
+
+pub const SEARCH_TRACE_VERSION: u32 = 1;
+
+/// One line of a search trace. Nodes are identified by the hash of their position, since the
+/// moves leading to them can be recovered from the root by matching child hashes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchTraceRecord {
+    Header {
+        version: u32,
+        fen: String,
+        max_ply: usize,
+    },
+    Enter {
+        ply: usize,
+        hash: u64,
+        alpha: Heuristic,
+        beta: Heuristic,
+        depth: i32,
+    },
+    Exit {
+        ply: usize,
+        score: Heuristic,
+    },
+    Cutoff {
+        ply: usize,
+        score: Heuristic,
+    },
+    RootMove {
+        hash: u64,
+        score: Heuristic,
+        depth: usize,
+    },
+}
+
+/// Writes every hook as a line of gzipped JSONL. Write errors are kept until `finish`, since
+/// hooks can't fail
+pub struct SearchTraceWriter {
+    writer: GzEncoder<BufWriter<File>>,
+    max_ply: usize,
+    error: Option<std::io::Error>,
+}
+
+impl SearchTraceWriter {
+    pub fn create(path: &Path, root: &FullGameState, max_ply: usize) -> std::io::Result<Self> {
+        let file = File::create(path)?;
+        let mut result = SearchTraceWriter {
+            writer: GzEncoder::new(BufWriter::new(file), Compression::default()),
+            max_ply,
+            error: None,
+        };
+        result.write(&SearchTraceRecord::Header {
+            version: SEARCH_TRACE_VERSION,
+            fen: game_state_to_fen(root),
+            max_ply,
+        });
+        Ok(result)
+    }
+
+    fn write(&mut self, record: &SearchTraceRecord) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = serde_json::to_writer(&mut self.writer, record)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writeln!(self.writer));
+        if let Err(err) = result {
+            self.error = Some(err);
+        }
+    }
+
+    /// Flushes the trace, returning the first error hit while writing it
+    pub fn finish(self) -> std::io::Result<()> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.writer.finish()?.flush()
+    }
+}
+
+impl SearchObserver for SearchTraceWriter {
+    fn max_ply(&self) -> usize {
+        self.max_ply
+    }
+
+    fn on_node_enter(
+        &mut self,
+        ply: usize,
+        state: &FullGameState,
+        alpha: Heuristic,
+        beta: Heuristic,
+        remaining_depth: i32,
+    ) {
+        self.write(&SearchTraceRecord::Enter {
+            ply,
+            hash: state.board.hash,
+            alpha,
+            beta,
+            depth: remaining_depth,
+        });
+    }
+
+    fn on_node_exit(&mut self, ply: usize, score: Heuristic) {
+        self.write(&SearchTraceRecord::Exit { ply, score });
+    }
+
+    fn on_beta_cutoff(&mut self, ply: usize, _action: GenericMove, score: Heuristic) {
+        self.write(&SearchTraceRecord::Cutoff { ply, score });
+    }
+
+    fn on_root_move_complete(
+        &mut self,
+        _action: GenericMove,
+        child_state: &FullGameState,
+        score: Heuristic,
+        depth: usize,
+    ) {
+        self.write(&SearchTraceRecord::RootMove {
+            hash: child_state.board.hash,
+            score,
+            depth,
+        });
+    }
+}
+
+pub fn read_search_trace(path: &Path) -> std::io::Result<Vec<SearchTraceRecord>> {
+    let reader = BufReader::new(GzDecoder::new(File::open(path)?));
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line)?);
+    }
+    Ok(records)
+}
+
+/// A node of a traced search. Searching the same position again, as re-searches and later
+/// iterations do, makes a separate node
+#[derive(Clone, Debug, PartialEq)]
+pub struct TracedNode {
+    pub hash: u64,
+    pub alpha: Heuristic,
+    pub beta: Heuristic,
+    pub depth: i32,
+    /// None if the trace ended before the node was exited
+    pub score: Option<Heuristic>,
+    /// Set when one of the children caused a beta cutoff
+    pub cutoff_score: Option<Heuristic>,
+    pub children: Vec<TracedNode>,
+}
+
+/// Rebuilds the searched trees from a trace: one root node per search of the root position, in
+/// the order they were searched
+pub fn build_traced_trees(records: &[SearchTraceRecord]) -> Result<Vec<TracedNode>, String> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<TracedNode> = Vec::new();
+
+    for record in records {
+        match record {
+            SearchTraceRecord::Enter {
+                ply,
+                hash,
+                alpha,
+                beta,
+                depth,
+            } => {
+                if *ply != stack.len() {
+                    return Err(format!(
+                        "Entered a node at ply {} with {} open nodes",
+                        ply,
+                        stack.len()
+                    ));
+                }
+                stack.push(TracedNode {
+                    hash: *hash,
+                    alpha: *alpha,
+                    beta: *beta,
+                    depth: *depth,
+                    score: None,
+                    cutoff_score: None,
+                    children: Vec::new(),
+                });
+            }
+            SearchTraceRecord::Exit { ply, score } => {
+                if *ply + 1 != stack.len() {
+                    return Err(format!(
+                        "Exited a node at ply {} with {} open nodes",
+                        ply,
+                        stack.len()
+                    ));
+                }
+                let mut node = stack.pop().unwrap();
+                node.score = Some(*score);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => roots.push(node),
+                }
+            }
+            SearchTraceRecord::Cutoff { ply, score } => {
+                if let Some(node) = stack.get_mut(*ply) {
+                    node.cutoff_score = Some(*score);
+                }
+            }
+            SearchTraceRecord::Header { .. } | SearchTraceRecord::RootMove { .. } => (),
+        }
+    }
+
+    // Keep whatever was searched before the trace was cut off
+    while let Some(node) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => roots.push(node),
+        }
+    }
+
+    Ok(roots)
+}
+
+/// The move from `state` that reaches a position with `hash`, if there is one
+pub fn find_traced_move(state: &FullGameState, hash: u64) -> Option<(String, FullGameState)> {
+    state
+        .describe_moves_with::<false>()
+        .into_iter()
+        .find(|description| description.next_state.board.hash == hash)
+        .map(|description| (description.action_str, description.next_state))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fen::parse_fen,
+        search::{SearchContext, get_past_win_search_terminator, negamax_search},
+        search_terminators::DynamicMaxDepthSearchTerminator,
+        transposition_table::TranspositionTable,
+    };
+
+    use super::*;
+
+    /// Keeps the records a `SearchTraceWriter` would write
+    struct RecordingObserver {
+        max_ply: usize,
+        records: Vec<SearchTraceRecord>,
+    }
+
+    impl SearchObserver for RecordingObserver {
+        fn max_ply(&self) -> usize {
+            self.max_ply
+        }
+
+        fn on_node_enter(
+            &mut self,
+            ply: usize,
+            state: &FullGameState,
+            alpha: Heuristic,
+            beta: Heuristic,
+            remaining_depth: i32,
+        ) {
+            self.records.push(SearchTraceRecord::Enter {
+                ply,
+                hash: state.board.hash,
+                alpha,
+                beta,
+                depth: remaining_depth,
+            });
+        }
+
+        fn on_node_exit(&mut self, ply: usize, score: Heuristic) {
+            self.records.push(SearchTraceRecord::Exit { ply, score });
+        }
+
+        fn on_beta_cutoff(&mut self, ply: usize, _action: GenericMove, score: Heuristic) {
+            self.records.push(SearchTraceRecord::Cutoff { ply, score });
+        }
+
+        fn on_root_move_complete(
+            &mut self,
+            _action: GenericMove,
+            child_state: &FullGameState,
+            score: Heuristic,
+            depth: usize,
+        ) {
+            self.records.push(SearchTraceRecord::RootMove {
+                hash: child_state.board.hash,
+                score,
+                depth,
+            });
+        }
+    }
+
+    fn traced_search(
+        state: &FullGameState,
+        depth: usize,
+        max_ply: usize,
+    ) -> Vec<SearchTraceRecord> {
+        let mut tt = TranspositionTable::new();
+        let mut search_context =
+            SearchContext::new(&mut tt, DynamicMaxDepthSearchTerminator::new(depth)).with_observer(
+                RecordingObserver {
+                    max_ply,
+                    records: Vec::new(),
+                },
+            );
+        search_context.params.use_placement_book = false;
+        negamax_search(
+            &mut search_context,
+            state.clone(),
+            get_past_win_search_terminator(),
+        )
+        .unwrap();
+        search_context.observer.records
+    }
+
+    #[test]
+    fn test_observer_doesnt_change_search() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:B2,D4/mortal:B4,D2").unwrap();
+        let search = |observed: bool| {
+            let mut tt = TranspositionTable::new();
+            let search_context =
+                SearchContext::new(&mut tt, DynamicMaxDepthSearchTerminator::new(4));
+            let search_state = if observed {
+                let mut search_context = search_context.with_observer(RecordingObserver {
+                    max_ply: 2,
+                    records: Vec::new(),
+                });
+                negamax_search(
+                    &mut search_context,
+                    state.clone(),
+                    get_past_win_search_terminator(),
+                )
+            } else {
+                let mut search_context = search_context;
+                negamax_search(
+                    &mut search_context,
+                    state.clone(),
+                    get_past_win_search_terminator(),
+                )
+            }
+            .unwrap();
+            let best_move = search_state.best_move.unwrap();
+            (
+                best_move.action,
+                best_move.score,
+                search_state.nodes_visited,
+            )
+        };
+
+        assert_eq!(search(true), search(false));
+    }
+
+    #[test]
+    fn test_traced_trees_follow_moves() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:B2,D4/mortal:B4,D2").unwrap();
+        let records = traced_search(&state, 3, 2);
+        let trees = build_traced_trees(&records).unwrap();
+
+        // One search of the root per iteration, none left open
+        let depths: Vec<i32> = trees.iter().map(|tree| tree.depth).collect();
+        assert_eq!(depths, vec![1, 2, 3]);
+        assert!(trees.iter().all(|tree| tree.score.is_some()));
+
+        for tree in &trees {
+            assert_eq!(tree.hash, state.board.hash);
+            for child in &tree.children {
+                let (_, child_state) = find_traced_move(&state, child.hash).unwrap();
+                // Deeper than max_ply isn't traced
+                for grandchild in &child.children {
+                    assert!(grandchild.children.is_empty());
+                    assert!(
+                        find_traced_move(&child_state, grandchild.hash).is_some()
+                            || grandchild.depth < child.depth - 1,
+                        "only null move searches skip a move"
+                    );
+                }
+            }
+        }
+
+        let root_moves = records
+            .iter()
+            .filter(|record| matches!(record, SearchTraceRecord::RootMove { depth: 3, .. }))
+            .count();
+        assert_eq!(root_moves, state.get_next_states().len());
+    }
+
+    #[test]
+    fn test_traced_placement_search() {
+        let state = parse_fen("0000000000000000000000000/1/mortal/mortal").unwrap();
+        let records = traced_search(&state, 2, 1);
+        let trees = build_traced_trees(&records).unwrap();
+
+        let last = trees.last().unwrap();
+        assert!(!last.children.is_empty());
+        for child in &last.children {
+            let (action_str, _) = find_traced_move(&state, child.hash).unwrap();
+            assert!(!action_str.is_empty());
+        }
+    }
+}
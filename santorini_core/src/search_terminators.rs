@@ -1,10 +1,18 @@
-use std::sync::{Arc, atomic::AtomicBool};
+use std::{
+    sync::{Arc, atomic::AtomicBool},
+    time::{Duration, Instant},
+};
 
-use crate::search::SearchState;
+use serde::{Deserialize, Serialize};
+
+use crate::{search::SearchState, time_manager::TimeControl};
 
 /// Trait to check if a search should stop at some static boundary
 pub trait SearchTerminator {
     fn should_stop(&mut self, search_state: &SearchState) -> bool;
+
+    /// Called by the root loop after each fully searched depth
+    fn on_depth_completed(&mut self, _search_state: &SearchState) {}
 }
 
 #[derive(Default)]
@@ -82,6 +90,16 @@ impl<A: SearchTerminator, B: SearchTerminator> SearchTerminator for AndSearchTer
     fn should_stop(&mut self, search_state: &SearchState) -> bool {
         self.a.should_stop(search_state) && self.b.should_stop(search_state)
     }
+
+    fn on_depth_completed(&mut self, search_state: &SearchState) {
+        self.a.on_depth_completed(search_state);
+        self.b.on_depth_completed(search_state);
+    }
+}
+impl<A: SearchTerminator, B: SearchTerminator> AndSearchTerminator<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
 }
 
 impl<A, B> Default for AndSearchTerminator<A, B>
@@ -105,6 +123,16 @@ impl<A: SearchTerminator, B: SearchTerminator> SearchTerminator for OrSearchTerm
     fn should_stop(&mut self, search_state: &SearchState) -> bool {
         self.a.should_stop(search_state) || self.b.should_stop(search_state)
     }
+
+    fn on_depth_completed(&mut self, search_state: &SearchState) {
+        self.a.on_depth_completed(search_state);
+        self.b.on_depth_completed(search_state);
+    }
+}
+impl<A: SearchTerminator, B: SearchTerminator> OrSearchTerminator<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
 }
 
 impl<A, B> Default for OrSearchTerminator<A, B>
@@ -119,3 +147,335 @@ where
         }
     }
 }
+
+/// Search limits that can be configured at runtime. The search stops as soon as any set limit is
+/// reached, except that depth and time limits are ignored until min_nodes have been visited
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchLimits {
+    pub max_depth: Option<usize>,
+    pub max_nodes: Option<usize>,
+    pub max_millis: Option<u64>,
+    pub min_nodes: Option<usize>,
+    /// Clock for the player to move. Handled by `TimeManager` rather than this terminator
+    #[serde(default)]
+    pub time_control: Option<TimeControl>,
+}
+
+impl SearchLimits {
+    pub const fn new() -> Self {
+        SearchLimits {
+            max_depth: None,
+            max_nodes: None,
+            max_millis: None,
+            min_nodes: None,
+            time_control: None,
+        }
+    }
+
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub const fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub const fn with_max_millis(mut self, max_millis: u64) -> Self {
+        self.max_millis = Some(max_millis);
+        self
+    }
+
+    pub const fn with_min_nodes(mut self, min_nodes: usize) -> Self {
+        self.min_nodes = Some(min_nodes);
+        self
+    }
+
+    pub const fn with_time_control(mut self, time_control: TimeControl) -> Self {
+        self.time_control = Some(time_control);
+        self
+    }
+}
+
+const TIME_CHECK_PER_NODES: usize = 1_000;
+
+pub struct DynamicSearchTerminator {
+    limits: SearchLimits,
+    started_at: Option<Instant>,
+    next_time_check: usize,
+    is_out_of_time: bool,
+}
+impl SearchTerminator for DynamicSearchTerminator {
+    fn should_stop(&mut self, search_state: &SearchState) -> bool {
+        if self
+            .limits
+            .max_nodes
+            .is_some_and(|max_nodes| search_state.nodes_visited >= max_nodes)
+        {
+            return true;
+        }
+
+        if self
+            .limits
+            .min_nodes
+            .is_some_and(|min_nodes| search_state.nodes_visited < min_nodes)
+        {
+            return false;
+        }
+
+        if self
+            .limits
+            .max_depth
+            .is_some_and(|max_depth| search_state.last_fully_completed_depth >= max_depth)
+        {
+            return true;
+        }
+
+        if let (Some(max_millis), Some(started_at)) = (self.limits.max_millis, self.started_at) {
+            if !self.is_out_of_time && search_state.nodes_visited >= self.next_time_check {
+                self.next_time_check = search_state.nodes_visited + TIME_CHECK_PER_NODES;
+                self.is_out_of_time = started_at.elapsed().as_millis() >= max_millis as u128;
+            }
+            return self.is_out_of_time;
+        }
+
+        false
+    }
+}
+impl DynamicSearchTerminator {
+    pub fn new(limits: SearchLimits) -> Self {
+        DynamicSearchTerminator {
+            limits,
+            // Only read the clock when needed, so that untimed searches work without it (wasm)
+            started_at: limits.max_millis.map(|_| Instant::now()),
+            next_time_check: 0,
+            is_out_of_time: false,
+        }
+    }
+}
+
+/// Nodes between clock reads while waiting for the next heartbeat
+const HEARTBEAT_CHECK_PER_NODES: usize = 10_000;
+
+/// Progress of a running search, reported periodically so that callers can tell it's still alive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchHeartbeat {
+    /// The depth currently being searched
+    pub depth: usize,
+    pub nodes_visited: usize,
+    pub nodes_per_second: usize,
+    pub elapsed: Duration,
+}
+
+/// Wraps another terminator to call `on_heartbeat` about once per `interval` while the search
+/// runs. Never stops a search by itself. Without a callback, it only defers to the inner
+/// terminator
+pub struct HeartbeatSearchTerminator<T: SearchTerminator, F: FnMut(SearchHeartbeat)> {
+    inner: T,
+    interval: Duration,
+    on_heartbeat: Option<F>,
+    started_at: Option<Instant>,
+    last_heartbeat_at: Option<Instant>,
+    next_heartbeat_check: usize,
+}
+
+impl<T: SearchTerminator, F: FnMut(SearchHeartbeat)> HeartbeatSearchTerminator<T, F> {
+    pub fn new(inner: T, interval: Duration, on_heartbeat: Option<F>) -> Self {
+        // Only read the clock when needed, so that searches without heartbeats work without it (wasm)
+        let started_at = on_heartbeat.as_ref().map(|_| Instant::now());
+        HeartbeatSearchTerminator {
+            inner,
+            interval,
+            on_heartbeat,
+            started_at,
+            last_heartbeat_at: started_at,
+            next_heartbeat_check: HEARTBEAT_CHECK_PER_NODES,
+        }
+    }
+}
+
+impl<T: SearchTerminator, F: FnMut(SearchHeartbeat)> SearchTerminator
+    for HeartbeatSearchTerminator<T, F>
+{
+    fn should_stop(&mut self, search_state: &SearchState) -> bool {
+        if let (Some(on_heartbeat), Some(started_at), Some(last_heartbeat_at)) = (
+            &mut self.on_heartbeat,
+            self.started_at,
+            self.last_heartbeat_at,
+        ) && search_state.nodes_visited >= self.next_heartbeat_check
+        {
+            self.next_heartbeat_check = search_state.nodes_visited + HEARTBEAT_CHECK_PER_NODES;
+
+            if last_heartbeat_at.elapsed() >= self.interval {
+                let elapsed = started_at.elapsed();
+                self.last_heartbeat_at = Some(Instant::now());
+                on_heartbeat(SearchHeartbeat {
+                    depth: search_state.last_fully_completed_depth + 1,
+                    nodes_visited: search_state.nodes_visited,
+                    nodes_per_second: (search_state.nodes_visited as f64
+                        / elapsed.as_secs_f64().max(1e-3))
+                        as usize,
+                    elapsed,
+                });
+            }
+        }
+
+        self.inner.should_stop(search_state)
+    }
+
+    fn on_depth_completed(&mut self, search_state: &SearchState) {
+        self.inner.on_depth_completed(search_state);
+    }
+}
+
+/// Wraps another terminator to call `on_poll` about once per `interval` while the search runs, for
+/// work that has to happen on the search thread at a finer grain than heartbeats. Never stops a
+/// search by itself
+pub struct PollSearchTerminator<T: SearchTerminator, F: FnMut()> {
+    inner: T,
+    interval: Duration,
+    on_poll: Option<F>,
+    last_poll_at: Option<Instant>,
+    next_poll_check: usize,
+}
+
+impl<T: SearchTerminator, F: FnMut()> PollSearchTerminator<T, F> {
+    pub fn new(inner: T, interval: Duration, on_poll: Option<F>) -> Self {
+        PollSearchTerminator {
+            inner,
+            interval,
+            last_poll_at: on_poll.as_ref().map(|_| Instant::now()),
+            on_poll,
+            next_poll_check: HEARTBEAT_CHECK_PER_NODES,
+        }
+    }
+}
+
+impl<T: SearchTerminator, F: FnMut()> SearchTerminator for PollSearchTerminator<T, F> {
+    fn should_stop(&mut self, search_state: &SearchState) -> bool {
+        if let (Some(on_poll), Some(last_poll_at)) = (&mut self.on_poll, self.last_poll_at)
+            && search_state.nodes_visited >= self.next_poll_check
+        {
+            self.next_poll_check = search_state.nodes_visited + HEARTBEAT_CHECK_PER_NODES;
+
+            if last_poll_at.elapsed() >= self.interval {
+                self.last_poll_at = Some(Instant::now());
+                on_poll();
+            }
+        }
+
+        self.inner.should_stop(search_state)
+    }
+
+    fn on_depth_completed(&mut self, search_state: &SearchState) {
+        self.inner.on_depth_completed(search_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_state(depth: usize, nodes: usize) -> SearchState {
+        let mut state = SearchState::default();
+        state.last_fully_completed_depth = depth;
+        state.nodes_visited = nodes;
+        state
+    }
+
+    #[test]
+    fn test_dynamic_terminator_unlimited() {
+        let mut terminator = DynamicSearchTerminator::new(SearchLimits::new());
+        assert!(!terminator.should_stop(&search_state(100, 100_000_000)));
+    }
+
+    #[test]
+    fn test_dynamic_terminator_max_depth() {
+        let mut terminator = DynamicSearchTerminator::new(SearchLimits::new().with_max_depth(5));
+        assert!(!terminator.should_stop(&search_state(4, 1_000_000)));
+        assert!(terminator.should_stop(&search_state(5, 0)));
+    }
+
+    #[test]
+    fn test_dynamic_terminator_max_nodes() {
+        let mut terminator =
+            DynamicSearchTerminator::new(SearchLimits::new().with_max_nodes(1_000));
+        assert!(!terminator.should_stop(&search_state(50, 999)));
+        assert!(terminator.should_stop(&search_state(0, 1_000)));
+    }
+
+    #[test]
+    fn test_dynamic_terminator_max_millis() {
+        let mut terminator = DynamicSearchTerminator::new(SearchLimits::new().with_max_millis(200));
+        assert!(!terminator.should_stop(&search_state(0, 0)));
+        std::thread::sleep(std::time::Duration::from_millis(250));
+        // Time is only checked periodically
+        assert!(!terminator.should_stop(&search_state(0, TIME_CHECK_PER_NODES - 1)));
+        assert!(terminator.should_stop(&search_state(0, TIME_CHECK_PER_NODES)));
+    }
+
+    #[test]
+    fn test_dynamic_terminator_combined() {
+        let limits = SearchLimits::new()
+            .with_max_depth(8)
+            .with_max_nodes(100_000)
+            .with_min_nodes(20_000);
+        let mut terminator = DynamicSearchTerminator::new(limits);
+
+        // Depth is ignored until min nodes are reached
+        assert!(!terminator.should_stop(&search_state(10, 19_999)));
+        assert!(terminator.should_stop(&search_state(8, 20_000)));
+        assert!(!terminator.should_stop(&search_state(7, 99_999)));
+        assert!(terminator.should_stop(&search_state(2, 100_000)));
+
+        // Whichever of depth & time is reached first stops the search
+        let limits = SearchLimits::new()
+            .with_max_depth(8)
+            .with_max_millis(60_000);
+        let mut terminator = DynamicSearchTerminator::new(limits);
+        assert!(!terminator.should_stop(&search_state(7, 0)));
+        assert!(terminator.should_stop(&search_state(8, 0)));
+    }
+
+    #[test]
+    fn test_heartbeat_terminator() {
+        let mut heartbeats = vec![];
+        let mut terminator = HeartbeatSearchTerminator::new(
+            DynamicSearchTerminator::new(SearchLimits::new().with_max_nodes(100_000)),
+            Duration::ZERO,
+            Some(|heartbeat: SearchHeartbeat| heartbeats.push(heartbeat)),
+        );
+
+        // The clock is only checked periodically
+        assert!(!terminator.should_stop(&search_state(2, HEARTBEAT_CHECK_PER_NODES - 1)));
+        assert!(!terminator.should_stop(&search_state(2, HEARTBEAT_CHECK_PER_NODES)));
+        assert!(!terminator.should_stop(&search_state(3, HEARTBEAT_CHECK_PER_NODES + 1)));
+        // Heartbeats never hide the inner terminator's decision
+        assert!(terminator.should_stop(&search_state(3, 100_000)));
+
+        drop(terminator);
+        assert_eq!(heartbeats.len(), 2);
+        assert_eq!(heartbeats[0].depth, 3);
+        assert_eq!(heartbeats[0].nodes_visited, HEARTBEAT_CHECK_PER_NODES);
+        assert_eq!(heartbeats[1].nodes_visited, 100_000);
+    }
+
+    #[test]
+    fn test_poll_terminator() {
+        let mut polls = 0;
+        let mut terminator = PollSearchTerminator::new(
+            DynamicSearchTerminator::new(SearchLimits::new().with_max_nodes(100_000)),
+            Duration::ZERO,
+            Some(|| polls += 1),
+        );
+
+        assert!(!terminator.should_stop(&search_state(2, HEARTBEAT_CHECK_PER_NODES - 1)));
+        assert!(!terminator.should_stop(&search_state(2, HEARTBEAT_CHECK_PER_NODES)));
+        assert!(!terminator.should_stop(&search_state(3, HEARTBEAT_CHECK_PER_NODES + 1)));
+        assert!(terminator.should_stop(&search_state(3, 100_000)));
+
+        drop(terminator);
+        assert_eq!(polls, 2);
+    }
+}
@@ -1,7 +1,10 @@
 use std::{fmt, str::FromStr};
 
 use crate::transmute_enum;
-use crate::{bitboard::BitBoard, direction::ICoord};
+use crate::{
+    bitboard::{BitBoard, NEIGHBOR_MAP},
+    direction::{Direction, ICoord},
+};
 
 #[repr(u8)]
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, EnumIter)]
@@ -35,12 +38,53 @@ impl Square {
     pub const fn const_from_u8(val: u8) -> Self {
         transmute_enum!(val)
     }
+
+    /// Parses a square in algebraic notation, like "C3". Case insensitive
+    pub fn from_algebraic(s: &str) -> Result<Square, String> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 2 {
+            return Err(format!("Could not parse square: '{}'", s));
+        }
+
+        let col = bytes[0].to_ascii_uppercase().wrapping_sub(b'A') as usize;
+        let rank = bytes[1].wrapping_sub(b'1') as usize;
+        if col >= 5 || rank >= 5 {
+            return Err(format!("Could not parse square: '{}'", s));
+        }
+
+        Ok(Square::from_col_row(col, 4 - rank))
+    }
+
+    pub const fn to_algebraic(self) -> &'static str {
+        Self::STR[self as usize]
+    }
+
+    /// All squares adjacent to this one, including diagonals
+    pub const fn neighbors(self) -> BitBoard {
+        NEIGHBOR_MAP[self as usize]
+    }
+
+    /// The direction of a step from this square to an adjacent square.
+    /// Returns None if the squares aren't adjacent
+    pub const fn direction_to(self, other: Square) -> Option<Direction> {
+        let delta = other.to_icoord().sub(self.to_icoord());
+        match (delta.col, delta.row) {
+            (-1, -1) => Some(Direction::NW),
+            (0, -1) => Some(Direction::N),
+            (1, -1) => Some(Direction::NE),
+            (1, 0) => Some(Direction::E),
+            (1, 1) => Some(Direction::SE),
+            (0, 1) => Some(Direction::S),
+            (-1, 1) => Some(Direction::SW),
+            (-1, 0) => Some(Direction::W),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Square {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s: String = String::from(Self::STR[*self as usize]);
-        write!(f, "{s}")
+        write!(f, "{}", self.to_algebraic())
     }
 }
 
@@ -56,16 +100,7 @@ impl FromStr for Square {
             }
         }
 
-        if s.len() != 2 {
-            return Err(format!("Could not parse square: '{}'", s));
-        };
-
-        let index = Self::STR
-            .iter()
-            .position(|&tgt| tgt.to_lowercase() == s.to_lowercase())
-            .ok_or("Invalid square!")?;
-
-        Ok(Square::from(index))
+        Square::from_algebraic(s)
     }
 }
 
@@ -144,6 +179,46 @@ mod tests {
             let parsed_square: Square = serde_json::from_str(&square_str).unwrap();
 
             assert_eq!(square, parsed_square);
+            assert_eq!(square_str, format!("\"{}\"", square.to_algebraic()));
+        }
+    }
+
+    #[test]
+    fn test_algebraic_round_trip() {
+        for square in Square::ALL {
+            assert_eq!(Square::from_algebraic(square.to_algebraic()), Ok(square));
         }
+
+        assert_eq!(Square::from_algebraic("A5"), Ok(Square::A5));
+        assert_eq!(Square::from_algebraic("c3"), Ok(Square::C3));
+        assert_eq!(Square::from_algebraic("E1"), Ok(Square::E1));
+
+        for bad in ["", "A", "A0", "A6", "F1", "C33", "3C"] {
+            assert!(Square::from_algebraic(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_directions_between_squares() {
+        assert_eq!(Square::C3.direction_to(Square::C4), Some(Direction::N));
+        assert_eq!(Square::C3.direction_to(Square::B2), Some(Direction::SW));
+        assert_eq!(Square::C3.direction_to(Square::C3), None);
+        assert_eq!(Square::C3.direction_to(Square::C5), None);
+        // Adjacent by index, but on opposite sides of the board
+        assert_eq!(Square::E4.direction_to(Square::A3), None);
+
+        for square in Square::ALL {
+            let mut neighbors = BitBoard::EMPTY;
+            for other in Square::ALL {
+                if let Some(direction) = square.direction_to(other) {
+                    assert_eq!(direction.apply(square), Some(other));
+                    neighbors |= other.to_board();
+                }
+            }
+            assert_eq!(neighbors, square.neighbors());
+        }
+
+        assert_eq!(Direction::N.apply(Square::C5), None);
+        assert_eq!(Direction::W.apply(Square::A3), None);
     }
 }
@@ -0,0 +1,97 @@
+use std::fmt::Write;
+
+use crate::{board::FullGameState, player::Player, square::Square};
+
+const SQUARE_SIZE: usize = 40;
+const LEGEND_SIZE: usize = 16;
+const BOARD_SIZE: usize = SQUARE_SIZE * 5;
+
+/// Fill colors for heights 0 to 3, matching the UI board
+const HEIGHT_COLORS: [&str; 4] = ["#dcdcdc", "#a0a0a0", "#606060", "#3c3c3c"];
+const DOME_COLOR: &str = "#0e11a1";
+const SKY_COLOR: &str = "#84ceeb";
+
+fn worker_color(player: Player) -> &'static str {
+    match player {
+        Player::One => "#d3d3d3",
+        Player::Two => "#171717",
+    }
+}
+
+/// Renders the heights and workers of `state` as a standalone SVG image, with rank and file
+/// labels. God specific tokens aren't drawn
+pub fn state_to_svg(state: &FullGameState) -> String {
+    let width = BOARD_SIZE + LEGEND_SIZE;
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{width}" viewBox="0 0 {width} {width}" font-family="sans-serif" font-size="10">"#
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="{width}" height="{width}" fill="{SKY_COLOR}"/>"#
+    );
+
+    for r in 0..5 {
+        let y = r * SQUARE_SIZE;
+        let _ = write!(
+            svg,
+            r#"<text x="4" y="{}">{}</text>"#,
+            y + SQUARE_SIZE / 2 + 4,
+            5 - r
+        );
+        for c in 0..5 {
+            let square = Square::from_col_row(c, r);
+            let x = LEGEND_SIZE + c * SQUARE_SIZE;
+            let height = state.board.get_height(square);
+
+            let _ = write!(
+                svg,
+                r#"<rect x="{x}" y="{y}" width="{SQUARE_SIZE}" height="{SQUARE_SIZE}" fill="{}" stroke="black"/>"#,
+                HEIGHT_COLORS[height.min(3)]
+            );
+            if (1..4).contains(&height) {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="{}" fill="white">{}</text>"#,
+                    x + 3,
+                    y + 11,
+                    height
+                );
+            }
+
+            let center_x = x + SQUARE_SIZE / 2;
+            let center_y = y + SQUARE_SIZE / 2;
+            if height >= 4 {
+                let _ = write!(
+                    svg,
+                    r#"<circle cx="{center_x}" cy="{center_y}" r="{}" fill="{DOME_COLOR}"/>"#,
+                    SQUARE_SIZE * 2 / 5
+                );
+            }
+            for player in [Player::One, Player::Two] {
+                if state.board.workers[player as usize].contains_square(square) {
+                    let _ = write!(
+                        svg,
+                        r#"<circle cx="{center_x}" cy="{center_y}" r="{}" fill="{}" stroke="black" stroke-width="2"/>"#,
+                        SQUARE_SIZE * 3 / 10,
+                        worker_color(player)
+                    );
+                }
+            }
+        }
+    }
+
+    for (c, file) in ["A", "B", "C", "D", "E"].iter().enumerate() {
+        let _ = write!(
+            svg,
+            r#"<text x="{}" y="{}">{}</text>"#,
+            LEGEND_SIZE + c * SQUARE_SIZE + SQUARE_SIZE / 2 - 3,
+            BOARD_SIZE + 12,
+            file
+        );
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
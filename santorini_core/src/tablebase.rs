@@ -0,0 +1,421 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    bitboard::BitBoard,
+    board::{BoardState, FullGameState},
+    gods::GodName,
+    hashing::HashType,
+    player::Player,
+    square::Square,
+};
+
+const FILE_MAGIC: &[u8; 4] = b"STB1";
+const WIN_FLAG: u8 = 0x80;
+
+/// Exact result of a tablebase position, for the player to move
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TbScore {
+    /// The player to move wins in this many plies, against any defence
+    Win(u8),
+    /// The player to move loses in this many plies, against their longest defence
+    Loss(u8),
+}
+
+impl TbScore {
+    fn pack(self) -> u8 {
+        match self {
+            TbScore::Win(plies) => WIN_FLAG | plies,
+            TbScore::Loss(plies) => plies,
+        }
+    }
+
+    fn unpack(packed: u8) -> Self {
+        match packed & WIN_FLAG {
+            0 => TbScore::Loss(packed),
+            _ => TbScore::Win(packed & !WIN_FLAG),
+        }
+    }
+}
+
+fn build_count(board: &BoardState) -> usize {
+    (board.height_map[0] & BitBoard::MAIN_SECTION_MASK).count_ones() as usize
+        + board.height_map[1..]
+            .iter()
+            .map(|level| level.count_ones() as usize)
+            .sum::<usize>()
+}
+
+/// Calls `f` with every height map that takes exactly `builds` builds to reach
+fn for_each_height_map(builds: usize, f: &mut impl FnMut(&[u8; 25])) {
+    fn recurse(
+        heights: &mut [u8; 25],
+        idx: usize,
+        remaining: usize,
+        f: &mut impl FnMut(&[u8; 25]),
+    ) {
+        if idx == 25 {
+            if remaining == 0 {
+                f(heights);
+            }
+            return;
+        }
+        for height in 0..=remaining.min(4) {
+            heights[idx] = height as u8;
+            recurse(heights, idx + 1, remaining - height, f);
+        }
+        heights[idx] = 0;
+    }
+
+    recurse(&mut [0; 25], 0, builds, f);
+}
+
+/// Calls `f` with every playable Mortal vs Mortal position on `heights`
+fn for_each_position(heights: &[u8; 25], f: &mut impl FnMut(FullGameState)) {
+    let mut board = BoardState::default();
+    for (square, height) in heights.iter().enumerate() {
+        for level in board.height_map.iter_mut().take(*height as usize) {
+            *level |= BitBoard::as_mask(Square::from(square));
+        }
+    }
+
+    // Workers can't stand on domes, and a worker on level 3 would already have won
+    let standable: Vec<Square> = (0..25)
+        .filter(|square| heights[*square] <= 2)
+        .map(Square::from)
+        .collect();
+    let pairs: Vec<BitBoard> = (0..standable.len())
+        .flat_map(|i| {
+            let standable = &standable;
+            (i + 1..standable.len())
+                .map(move |j| BitBoard::as_mask(standable[i]) | BitBoard::as_mask(standable[j]))
+        })
+        .collect();
+
+    for p1_workers in &pairs {
+        for p2_workers in &pairs {
+            if (*p1_workers & *p2_workers).is_not_empty() {
+                continue;
+            }
+            for current_player in [Player::One, Player::Two] {
+                let mut board = board.clone();
+                board.workers = [*p1_workers, *p2_workers];
+                board.current_player = current_player;
+                f(FullGameState::new(
+                    board,
+                    [GodName::Mortal.to_power(), GodName::Mortal.to_power()],
+                ));
+            }
+        }
+    }
+}
+
+/// Exact results of Mortal vs Mortal positions with few blocks on the board.
+///
+/// Every Mortal turn adds exactly one block, so the positions are solved from the highest build
+/// count down. A position is only stored once its result is forced without the game passing
+/// `max_builds` blocks; anything that depends on later play is left to the search.
+///
+/// Nobody can be boxed in with fewer than 4 blocks on the board, or win with fewer than 5, so
+/// smaller tables are empty.
+///
+/// Tables are built offline with the `tablebase_gen` binary. The search doesn't consult them.
+#[derive(Clone, Debug, Default)]
+pub struct Tablebase {
+    max_builds: usize,
+    keys: Vec<HashType>,
+    scores: Vec<u8>,
+}
+
+impl Tablebase {
+    /// Solves every position with at most `max_builds` blocks. `on_level` is called after each
+    /// build count is finished, with the number of positions solved at that count.
+    ///
+    /// The number of positions grows very quickly with `max_builds`
+    pub fn generate(max_builds: usize, mut on_level: impl FnMut(usize, usize)) -> Self {
+        Self::solve(
+            max_builds,
+            |builds, f| {
+                for_each_height_map(builds, &mut |heights| {
+                    for_each_position(heights, &mut |state| {
+                        // Symmetric positions share a result, so only solve one of them
+                        if state.canonical_hash() == state.board.hash {
+                            f(state)
+                        }
+                    })
+                })
+            },
+            &mut on_level,
+        )
+    }
+
+    fn solve(
+        max_builds: usize,
+        for_each_at_level: impl Fn(usize, &mut dyn FnMut(FullGameState)),
+        on_level: &mut dyn FnMut(usize, usize),
+    ) -> Self {
+        let mut solved: Vec<(HashType, u8)> = Vec::new();
+        let mut next_level: HashMap<HashType, TbScore> = HashMap::new();
+
+        for builds in (0..=max_builds).rev() {
+            let mut current_level: HashMap<HashType, TbScore> = HashMap::new();
+            for_each_at_level(builds, &mut |state| {
+                if state.validation_err().is_err() || state.get_winner().is_some() {
+                    return;
+                }
+                let key = state.canonical_hash();
+                if current_level.contains_key(&key) {
+                    return;
+                }
+                if let Some(score) = Self::solve_position(&state, builds, max_builds, &next_level) {
+                    current_level.insert(key, score);
+                }
+            });
+
+            on_level(builds, current_level.len());
+            solved.extend(
+                current_level
+                    .iter()
+                    .map(|(key, score)| (*key, score.pack())),
+            );
+            next_level = current_level;
+        }
+
+        Self::from_entries(max_builds, solved)
+    }
+
+    /// Result of `state` from the results of the positions one block later
+    fn solve_position(
+        state: &FullGameState,
+        builds: usize,
+        max_builds: usize,
+        next_level: &HashMap<HashType, TbScore>,
+    ) -> Option<TbScore> {
+        let mover = state.board.current_player;
+        let mut fastest_win: Option<u8> = None;
+        let mut slowest_loss: Option<u8> = Some(0);
+
+        for (child, _) in state.get_all_next_states_with_actions() {
+            let child_score = match child.get_winner() {
+                Some(winner) if winner == mover => Some(TbScore::Loss(0)),
+                Some(_) => Some(TbScore::Win(0)),
+                None if builds + 1 > max_builds => None,
+                None => next_level.get(&child.canonical_hash()).copied(),
+            };
+
+            match child_score {
+                Some(TbScore::Loss(plies)) => {
+                    fastest_win = Some(fastest_win.map_or(plies + 1, |best| best.min(plies + 1)));
+                }
+                Some(TbScore::Win(plies)) => {
+                    slowest_loss = slowest_loss.map(|worst| worst.max(plies + 1));
+                }
+                None => slowest_loss = None,
+            }
+        }
+
+        // A player with no moves loses on the spot, which the defaults above cover
+        match (fastest_win, slowest_loss) {
+            (Some(plies), _) => Some(TbScore::Win(plies)),
+            (None, Some(plies)) => Some(TbScore::Loss(plies)),
+            (None, None) => None,
+        }
+    }
+
+    fn from_entries(max_builds: usize, mut entries: Vec<(HashType, u8)>) -> Self {
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        let (keys, scores) = entries.into_iter().unzip();
+        Tablebase {
+            max_builds,
+            keys,
+            scores,
+        }
+    }
+
+    pub fn max_builds(&self) -> usize {
+        self.max_builds
+    }
+
+    /// Number of solved positions, not counting symmetric copies
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether `state` is a position this tablebase covers, solved or not
+    pub fn covers(&self, state: &FullGameState) -> bool {
+        state.gods[0].god_name == GodName::Mortal
+            && state.gods[1].god_name == GodName::Mortal
+            && state.board.workers[0].count_ones() == 2
+            && state.board.workers[1].count_ones() == 2
+            && state.get_winner().is_none()
+            && build_count(&state.board) <= self.max_builds
+    }
+
+    /// Looks up the exact result of `state`. None if this tablebase doesn't cover it, or covers it
+    /// without a forced result
+    pub fn probe(&self, state: &FullGameState) -> Option<TbScore> {
+        if !self.covers(state) {
+            return None;
+        }
+
+        let idx = self.keys.binary_search(&state.canonical_hash()).ok()?;
+        Some(TbScore::unpack(self.scores[idx]))
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<(), String> {
+        let mut write = |bytes: &[u8]| writer.write_all(bytes).map_err(|e| e.to_string());
+        write(FILE_MAGIC)?;
+        write(&(self.max_builds as u32).to_le_bytes())?;
+        write(&(self.keys.len() as u64).to_le_bytes())?;
+        for (key, score) in self.keys.iter().zip(&self.scores) {
+            write(&key.to_le_bytes())?;
+            write(&[*score])?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, String> {
+        let mut read = |bytes: &mut [u8]| reader.read_exact(bytes).map_err(|e| e.to_string());
+
+        let mut magic = [0; 4];
+        read(&mut magic)?;
+        if &magic != FILE_MAGIC {
+            return Err("Not a tablebase file".to_owned());
+        }
+
+        let mut max_builds = [0; 4];
+        read(&mut max_builds)?;
+        let mut len = [0; 8];
+        read(&mut len)?;
+
+        let mut entries = Vec::new();
+        for _ in 0..u64::from_le_bytes(len) {
+            let mut key = [0; 8];
+            read(&mut key)?;
+            let mut score = [0; 1];
+            read(&mut score)?;
+            entries.push((HashType::from_le_bytes(key), score[0]));
+        }
+
+        Ok(Self::from_entries(
+            u32::from_le_bytes(max_builds) as usize,
+            entries,
+        ))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let mut writer = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+        self.write_to(&mut writer)?;
+        writer.flush().map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::read_from(&mut BufReader::new(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen::parse_fen;
+
+    use super::*;
+
+    // Player 1 climbs from A5 to B5
+    const WIN_IN_1_STRING: &str = "2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3";
+    // Player 1's workers are boxed into the corner, with no moves
+    const TRAPPED_STRING: &str = "0000000000220000000000000/1/mortal:A5,A4/mortal:B5,B4";
+
+    fn solve_from(max_builds: usize, states: &[FullGameState]) -> Tablebase {
+        Tablebase::solve(
+            max_builds,
+            |builds, f| {
+                for state in states {
+                    if build_count(&state.board) == builds {
+                        f(state.clone());
+                    }
+                }
+            },
+            &mut |_, _| {},
+        )
+    }
+
+    fn flip_horizontal(state: &FullGameState) -> FullGameState {
+        let mut result = state.clone();
+        result.board = state.get_all_permutations::<false>()[0].clone();
+        result
+    }
+
+    #[test]
+    fn test_solves_immediate_results() {
+        let win = parse_fen(WIN_IN_1_STRING).unwrap();
+        let trapped = parse_fen(TRAPPED_STRING).unwrap();
+        let tablebase = solve_from(5, &[win.clone(), trapped.clone()]);
+
+        assert_eq!(tablebase.len(), 2);
+        assert_eq!(tablebase.probe(&win), Some(TbScore::Win(1)));
+        assert_eq!(tablebase.probe(&trapped), Some(TbScore::Loss(0)));
+
+        // Symmetric positions share an entry
+        let flipped = flip_horizontal(&win);
+        assert_ne!(flipped.board, win.board);
+        assert_eq!(tablebase.probe(&flipped), Some(TbScore::Win(1)));
+    }
+
+    #[test]
+    fn test_solves_through_earlier_levels() {
+        // Player 1 steps to B5 and builds A5 up to level 3, and Player 2 can't stop the climb
+        let start = parse_fen("2200000000000000000000000/1/mortal:A5,E5/mortal:D1,E1").unwrap();
+        let mut states = vec![start.clone()];
+        for (child, _) in start.get_all_next_states_with_actions() {
+            states.extend(
+                child
+                    .get_all_next_states_with_actions()
+                    .into_iter()
+                    .map(|(grandchild, _)| grandchild),
+            );
+            states.push(child);
+        }
+
+        let tablebase = solve_from(6, &states);
+        assert_eq!(tablebase.probe(&start), Some(TbScore::Win(3)));
+    }
+
+    #[test]
+    fn test_ignores_positions_out_of_range() {
+        let win = parse_fen(WIN_IN_1_STRING).unwrap();
+        let tablebase = solve_from(4, &[win.clone()]);
+        assert!(!tablebase.covers(&win));
+        assert_eq!(tablebase.probe(&win), None);
+
+        let state = parse_fen("2300000000000000000000000/1/mortal:A5,E1/pan:C3,E3").unwrap();
+        assert!(!solve_from(5, &[win]).covers(&state));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let win = parse_fen(WIN_IN_1_STRING).unwrap();
+        let trapped = parse_fen(TRAPPED_STRING).unwrap();
+        let tablebase = solve_from(5, &[win.clone(), trapped.clone()]);
+
+        let mut bytes = Vec::new();
+        tablebase.write_to(&mut bytes).unwrap();
+        let loaded = Tablebase::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(loaded.max_builds(), 5);
+        assert_eq!(loaded.keys, tablebase.keys);
+        assert_eq!(loaded.probe(&win), Some(TbScore::Win(1)));
+        assert_eq!(loaded.probe(&trapped), Some(TbScore::Loss(0)));
+
+        assert!(Tablebase::read_from(&mut &b"nope"[..]).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::GamePhase,
+    gods::generic::GenericMove,
+    search::{Heuristic, SearchState},
+    search_terminators::SearchTerminator,
+};
+
+/// Moves we expect to still have to make out of the remaining clock
+const EXPECTED_MOVES_LEFT: u64 = 30;
+/// Held back from every budget for process and pipe latency
+const MOVE_OVERHEAD_MILLIS: u64 = 50;
+/// How far past the soft target a single move may run
+const HARD_LIMIT_FACTOR: u64 = 4;
+/// A drop in the root score this large between depths counts as trouble
+const SCORE_DROP_THRESHOLD: Heuristic = 50;
+/// A lead over the second best root move this large counts as an easy move
+const EASY_MOVE_SCORE_GAP: Heuristic = 300;
+const TIME_CHECK_PER_NODES: usize = 1_000;
+
+/// Share of the usual soft target to spend in each phase. Placements and opening moves are
+/// mostly settled by the book and shallow searches, while the midgame is where most games are
+/// decided
+fn phase_time_scale(phase: GamePhase) -> f32 {
+    match phase {
+        GamePhase::Placement => 0.5,
+        GamePhase::Opening => 0.8,
+        GamePhase::Midgame => 1.3,
+        GamePhase::Endgame => 1.0,
+    }
+}
+
+/// Clock state for the player to move
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub remaining_millis: u64,
+    pub increment_millis: u64,
+}
+
+/// How long to spend on one move
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeBudget {
+    /// Don't start a new depth once this is used up, unless the best move is unstable
+    pub soft_millis: u64,
+    /// Stop searching immediately at this point
+    pub hard_millis: u64,
+}
+
+impl TimeControl {
+    pub fn budget(&self) -> TimeBudget {
+        self.scaled_budget(1.0)
+    }
+
+    /// Same as `budget`, with the soft target scaled for the phase of the game
+    pub fn budget_in_phase(&self, phase: GamePhase) -> TimeBudget {
+        self.scaled_budget(phase_time_scale(phase))
+    }
+
+    fn scaled_budget(&self, scale: f32) -> TimeBudget {
+        let available = self.remaining_millis.saturating_sub(MOVE_OVERHEAD_MILLIS);
+        let soft_millis = available / EXPECTED_MOVES_LEFT + self.increment_millis * 3 / 4;
+        let soft_millis = ((soft_millis as f32 * scale) as u64).min(available);
+        let hard_millis = (soft_millis * HARD_LIMIT_FACTOR)
+            .min(available / 2)
+            .max(soft_millis);
+
+        TimeBudget {
+            soft_millis,
+            hard_millis,
+        }
+    }
+}
+
+/// How much of the soft target to use, based on how the root has behaved across depths
+fn soft_target_scale(stable_depths: usize, score_drop: Heuristic, score_gap: Heuristic) -> f32 {
+    let mut scale = match stable_depths {
+        0 | 1 => 1.6,
+        2 => 1.2,
+        3 | 4 => 1.0,
+        _ => 0.7,
+    };
+    if score_drop >= SCORE_DROP_THRESHOLD {
+        scale *= 1.5;
+    }
+    if stable_depths >= 3 && score_gap >= EASY_MOVE_SCORE_GAP {
+        scale *= 0.5;
+    }
+    scale
+}
+
+/// Wraps another terminator to spend a clock sensibly. Without a time control, it only defers
+/// to the inner terminator
+pub struct TimeManager<T: SearchTerminator> {
+    inner: T,
+    time_control: Option<TimeControl>,
+    budget: Option<TimeBudget>,
+    started_at: Option<Instant>,
+    next_time_check: usize,
+    is_out_of_time: bool,
+    is_soft_stopped: bool,
+    best_action: Option<GenericMove>,
+    best_score: Heuristic,
+    stable_depths: usize,
+}
+
+impl<T: SearchTerminator> TimeManager<T> {
+    pub fn new(inner: T, time_control: Option<TimeControl>) -> Self {
+        TimeManager {
+            inner,
+            time_control,
+            budget: time_control.map(|time_control| time_control.budget()),
+            // Only read the clock when needed, so that untimed searches work without it (wasm)
+            started_at: time_control.map(|_| Instant::now()),
+            next_time_check: 0,
+            is_out_of_time: false,
+            is_soft_stopped: false,
+            best_action: None,
+            best_score: 0,
+            stable_depths: 0,
+        }
+    }
+
+    /// Budgets the clock for a position in `phase`
+    pub fn with_phase(mut self, phase: GamePhase) -> Self {
+        self.budget = self
+            .time_control
+            .map(|time_control| time_control.budget_in_phase(phase));
+        self
+    }
+
+    fn elapsed_millis(&self) -> u64 {
+        self.started_at
+            .map_or(0, |started_at| started_at.elapsed().as_millis() as u64)
+    }
+}
+
+impl<T: SearchTerminator> SearchTerminator for TimeManager<T> {
+    fn should_stop(&mut self, search_state: &SearchState) -> bool {
+        if self.inner.should_stop(search_state) || self.is_soft_stopped {
+            return true;
+        }
+
+        if let Some(budget) = self.budget {
+            if !self.is_out_of_time && search_state.nodes_visited >= self.next_time_check {
+                self.next_time_check = search_state.nodes_visited + TIME_CHECK_PER_NODES;
+                self.is_out_of_time = self.elapsed_millis() >= budget.hard_millis;
+            }
+            return self.is_out_of_time;
+        }
+
+        false
+    }
+
+    fn on_depth_completed(&mut self, search_state: &SearchState) {
+        self.inner.on_depth_completed(search_state);
+
+        let Some(best_move) = &search_state.best_move else {
+            return;
+        };
+        let score_drop = match self.best_action {
+            Some(_) => self.best_score.saturating_sub(best_move.score),
+            None => 0,
+        };
+        if self.best_action == Some(best_move.action) {
+            self.stable_depths += 1;
+        } else {
+            self.best_action = Some(best_move.action);
+            self.stable_depths = 1;
+        }
+        self.best_score = best_move.score;
+
+        if let Some(budget) = self.budget {
+            let score_gap = best_move
+                .score
+                .saturating_sub(search_state.root_second_best_score);
+            let soft_target = budget.soft_millis as f32
+                * soft_target_scale(self.stable_depths, score_drop, score_gap);
+            self.is_soft_stopped = self.elapsed_millis() as f32 >= soft_target;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search_terminators::NoopSearchTerminator;
+
+    use super::*;
+
+    #[test]
+    fn test_budget_splits_remaining_time() {
+        let budget = TimeControl {
+            remaining_millis: 60_050,
+            increment_millis: 0,
+        }
+        .budget();
+        assert_eq!(budget.soft_millis, 2_000);
+        assert_eq!(budget.hard_millis, 8_000);
+    }
+
+    #[test]
+    fn test_budget_uses_increment() {
+        let budget = TimeControl {
+            remaining_millis: 60_050,
+            increment_millis: 1_000,
+        }
+        .budget();
+        assert_eq!(budget.soft_millis, 2_750);
+        assert_eq!(budget.hard_millis, 11_000);
+    }
+
+    #[test]
+    fn test_budget_never_exceeds_clock() {
+        for remaining_millis in [0, 10, 100, 1_000, 10_000] {
+            for increment_millis in [0, 1_000, 100_000] {
+                let budget = TimeControl {
+                    remaining_millis,
+                    increment_millis,
+                }
+                .budget();
+                let available = remaining_millis.saturating_sub(MOVE_OVERHEAD_MILLIS);
+                assert!(budget.soft_millis <= budget.hard_millis);
+                assert!(budget.hard_millis <= available);
+                assert!(budget.soft_millis <= available);
+            }
+        }
+    }
+
+    #[test]
+    fn test_budget_favors_midgame() {
+        let time_control = TimeControl {
+            remaining_millis: 60_050,
+            increment_millis: 0,
+        };
+        let soft_millis = |phase| time_control.budget_in_phase(phase).soft_millis;
+        assert_eq!(
+            soft_millis(GamePhase::Endgame),
+            time_control.budget().soft_millis
+        );
+        assert!(soft_millis(GamePhase::Midgame) > soft_millis(GamePhase::Endgame));
+        assert!(soft_millis(GamePhase::Opening) < soft_millis(GamePhase::Endgame));
+        assert!(soft_millis(GamePhase::Placement) < soft_millis(GamePhase::Opening));
+
+        let nearly_flagged = TimeControl {
+            remaining_millis: 100,
+            increment_millis: 10_000,
+        };
+        let budget = nearly_flagged.budget_in_phase(GamePhase::Midgame);
+        assert!(budget.hard_millis <= 50);
+    }
+
+    #[test]
+    fn test_soft_target_scale() {
+        let changing = soft_target_scale(1, 0, 0);
+        let settled = soft_target_scale(4, 0, 0);
+        let long_stable = soft_target_scale(8, 0, 0);
+        assert!(changing > settled);
+        assert!(settled > long_stable);
+
+        assert!(soft_target_scale(4, SCORE_DROP_THRESHOLD, 0) > settled);
+        assert!(soft_target_scale(4, 0, EASY_MOVE_SCORE_GAP) < settled);
+        // A move that just changed isn't easy, no matter the gap
+        assert_eq!(soft_target_scale(1, 0, EASY_MOVE_SCORE_GAP), changing);
+    }
+
+    #[test]
+    fn test_untimed_defers_to_inner() {
+        let mut time_manager = TimeManager::new(NoopSearchTerminator::default(), None);
+        let mut search_state = SearchState::default();
+        search_state.nodes_visited = 100_000_000;
+        time_manager.on_depth_completed(&search_state);
+        assert!(!time_manager.should_stop(&search_state));
+    }
+
+    #[test]
+    fn test_empty_clock_stops_immediately() {
+        let mut time_manager = TimeManager::new(
+            NoopSearchTerminator::default(),
+            Some(TimeControl {
+                remaining_millis: 0,
+                increment_millis: 0,
+            }),
+        );
+        assert!(time_manager.should_stop(&SearchState::default()));
+    }
+}
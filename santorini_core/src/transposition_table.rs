@@ -7,7 +7,7 @@ use crate::{
     board::FullGameState,
     gods::generic::GenericMove,
     hashing::{HashType, compute_hash_from_scratch},
-    search::{MAX_PLY, WINNING_SCORE_BUFFER},
+    search::{MAX_PLY, WINNING_SCORE_BUFFER, komi_for_player},
 };
 
 use super::search::Heuristic;
@@ -45,6 +45,8 @@ impl Default for TTValue {
 pub struct TTEntry {
     pub hash_code: HashType,
     pub value: TTValue,
+    /// Value of TranspositionTable::generation when this entry was written
+    pub generation: u8,
     // pub board: BoardState,
 }
 
@@ -76,8 +78,17 @@ pub struct TranspositionTable {
     pub lmr_table: LMRTable,
     pub entries: Vec<TTEntry>,
     pub stats: TTStats,
+    generation: u8,
+    /// Komi of the current search, in favour of player 1. Stored scores and evals never include
+    /// it, so entries stay valid when the matchup policy changes
+    komi: Heuristic,
 }
 
+// Within a generation, shallower entries may still replace deeper ones, so that the table doesn't
+// fill up with stale deep entries from early in a long search. Smaller margins keep too many of
+// them: at a fixed depth of 9, a margin of 2 searches 26% more nodes than always replacing
+const REPLACE_DEPTH_MARGIN: u8 = 4;
+
 // const TABLE_SIZE: HashType = 999_983;
 const TABLE_SIZE: HashType = 5_000_011;
 // const TABLE_SIZE: HashType = 10_000_019;
@@ -107,33 +118,106 @@ pub struct TTStats {
     pub read_collision: usize,
     pub used_value: usize,
     pub unused_value: usize,
+    pub replaced_same_position: usize,
+    pub replaced_old_generation: usize,
+    pub replaced_collision: usize,
+    pub skipped_insert: usize,
+    /// Inserts that landed on a slot holding a different position, whether or not it was replaced
+    pub write_collision: usize,
+}
+
+impl TTStats {
+    /// Lookups and inserts that found a different position in their slot
+    pub fn key_collisions(&self) -> usize {
+        self.read_collision + self.write_collision
+    }
 }
 
 impl TranspositionTable {
-    pub const IS_TRACKING_STATS: bool = false;
+    pub const IS_TRACKING_STATS: bool = cfg!(feature = "tt_stats");
 
     pub fn new() -> Self {
+        Self::with_size(TABLE_SIZE as usize)
+    }
+
+    /// A table with `size` entries, for searches too small to make use of the default one
+    pub fn with_size(size: usize) -> Self {
         Self {
             lmr_table: LMRTable::new(),
             entries: vec![
                 TTEntry {
                     hash_code: 0,
                     value: TTValue::default(),
+                    generation: 0,
                 };
-                TABLE_SIZE as usize
+                size.max(1)
             ],
             stats: Default::default(),
+            generation: 0,
+            komi: 0,
+        }
+    }
+
+    /// Start a new root search. Entries from previous searches become preferred for replacement
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn generation(&self) -> u8 {
+        self.generation
+    }
+
+    pub fn set_komi(&mut self, komi: Heuristic) {
+        self.komi = komi;
+    }
+
+    /// Komi for the player to move in `state`, if `value` isn't a win or loss
+    fn komi_for_value(&self, state: &FullGameState, value: Heuristic) -> Heuristic {
+        if value.abs() >= WINNING_SCORE_BUFFER {
+            0
+        } else {
+            komi_for_player(self.komi, state.board.current_player)
+        }
+    }
+
+    /// Replacement policy: always replace the same position or entries from older generations,
+    /// otherwise prefer keeping deeper entries
+    fn should_replace(&mut self, destination: usize, hash_code: HashType, depth: u8) -> bool {
+        let old_entry = &self.entries[destination];
+
+        let is_same_position = old_entry.hash_code == hash_code;
+        let is_old_generation = old_entry.generation != self.generation;
+        let should_replace = is_same_position
+            || is_old_generation
+            || depth.saturating_add(REPLACE_DEPTH_MARGIN) >= old_entry.value.search_depth;
+
+        if TranspositionTable::IS_TRACKING_STATS && old_entry.hash_code != 0 {
+            if !is_same_position {
+                self.stats.write_collision += 1;
+            }
+
+            if !should_replace {
+                self.stats.skipped_insert += 1;
+            } else if is_same_position {
+                self.stats.replaced_same_position += 1;
+            } else if is_old_generation {
+                self.stats.replaced_old_generation += 1;
+            } else {
+                self.stats.replaced_collision += 1;
+            }
         }
+
+        should_replace
     }
 
-    /// Get a key that wraps around the table size, avoiding using Modulo.
+    /// Get a key that wraps around the table size, avoiding using Modulo, which is slow now that
+    /// the size is only known at runtime.
     /// https://lemire.me/blog/2016/06/27/a-fast-alternative-to-the-modulo-reduction/
     fn get_key(&self, hash: u64) -> usize {
-        (hash % TABLE_SIZE) as usize
-        // let key = hash as u128;
-        // let len = TABLE_SIZE as u128;
+        let key = hash as u128;
+        let len = self.entries.len() as u128;
 
-        // ((key * len) >> 64) as usize
+        ((key * len) >> 64) as usize
     }
 
     pub fn insert(
@@ -149,15 +233,20 @@ impl TranspositionTable {
         let hash_code = hash_board(state);
         let destination = self.get_key(hash_code);
 
+        if !self.should_replace(destination, hash_code, depth) {
+            return;
+        }
+
         let new_entry = TTEntry {
             value: TTValue {
                 best_action,
                 search_depth: depth,
                 score_type,
-                score: to_tt(search_score, ply),
-                eval: current_eval,
+                score: to_tt(search_score, ply) - self.komi_for_value(state, search_score),
+                eval: current_eval - self.komi_for_value(state, current_eval),
             },
             hash_code,
+            generation: self.generation,
         };
 
         self.entries[destination] = new_entry;
@@ -189,15 +278,20 @@ impl TranspositionTable {
             best_action = old_entry.value.best_action;
         }
 
+        if !self.should_replace(destination, hash_code, depth) {
+            return;
+        }
+
         let new_entry = TTEntry {
             value: TTValue {
                 best_action,
                 search_depth: depth,
                 score_type,
-                score: to_tt(search_score, ply),
-                eval: current_eval,
+                score: to_tt(search_score, ply) - self.komi_for_value(state, search_score),
+                eval: current_eval - self.komi_for_value(state, current_eval),
             },
             hash_code,
+            generation: self.generation,
         };
 
         self.entries[destination] = new_entry;
@@ -217,12 +311,14 @@ impl TranspositionTable {
                 self.stats.hit += 1;
             }
 
+            let score = to_search(entry.value.score, ply);
+            let eval = entry.value.eval;
             return Some(TTValue {
                 best_action: entry.value.best_action,
                 search_depth: entry.value.search_depth,
                 score_type: entry.value.score_type,
-                score: to_search(entry.value.score, ply),
-                eval: entry.value.eval,
+                score: score + self.komi_for_value(state, score),
+                eval: eval + self.komi_for_value(state, eval),
             });
         } else if TranspositionTable::IS_TRACKING_STATS {
             // eprintln!("TT COLLISION: {}", hash_code);
@@ -246,6 +342,7 @@ impl TranspositionTable {
             .iter_mut()
             .for_each(|entry| *entry = Default::default());
         self.stats = Default::default();
+        self.generation = 0;
     }
 }
 
@@ -292,3 +389,118 @@ impl Default for LMRTable {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{fen::parse_fen, search::INFINITY};
+
+    use super::*;
+
+    #[test]
+    fn test_new_generation_replaces_full_table() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut tt = TranspositionTable::new();
+        tt.new_search();
+
+        // Fill every slot with deep entries for other positions
+        let generation = tt.generation();
+        for (i, entry) in tt.entries.iter_mut().enumerate() {
+            entry.hash_code = i as HashType + 1;
+            entry.value.search_depth = 50;
+            entry.generation = generation;
+        }
+
+        let insert = |tt: &mut TranspositionTable| {
+            tt.insert(
+                &state,
+                GenericMove::NULL_MOVE,
+                1,
+                SearchScoreType::Exact,
+                0,
+                0,
+                0,
+            )
+        };
+
+        // Deeper entries from the same generation are kept
+        insert(&mut tt);
+        assert!(tt.fetch(&state, 0).is_none());
+
+        // But anything from an older generation can be replaced
+        tt.new_search();
+        insert(&mut tt);
+        assert!(tt.fetch(&state, 0).is_some());
+    }
+
+    #[test]
+    fn test_komi_is_not_stored() {
+        // Player 2 to move, so a komi in favour of player 1 counts against them
+        let state = parse_fen("0000000000000000000000000/2/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut tt = TranspositionTable::with_size(16);
+        tt.set_komi(100);
+        tt.insert(
+            &state,
+            GenericMove::NULL_MOVE,
+            1,
+            SearchScoreType::Exact,
+            -40,
+            -60,
+            0,
+        );
+        assert_eq!(tt.entries.iter().map(|e| e.value.score).max(), Some(60));
+
+        let entry = tt.fetch(&state, 0).unwrap();
+        assert_eq!((entry.score, entry.eval), (-40, -60));
+
+        tt.set_komi(0);
+        let entry = tt.fetch(&state, 0).unwrap();
+        assert_eq!((entry.score, entry.eval), (60, 40));
+
+        // Wins and losses are never offset
+        tt.set_komi(100);
+        tt.insert(
+            &state,
+            GenericMove::NULL_MOVE,
+            2,
+            SearchScoreType::Exact,
+            WINNING_SCORE_BUFFER + 5,
+            -INFINITY,
+            0,
+        );
+        let entry = tt.fetch(&state, 0).unwrap();
+        assert_eq!(
+            (entry.score, entry.eval),
+            (WINNING_SCORE_BUFFER + 5, -INFINITY)
+        );
+    }
+
+    #[cfg(feature = "tt_stats")]
+    #[test]
+    fn test_tracks_key_collisions() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        let mut tt = TranspositionTable::new();
+
+        // Put a different position in the slot this state maps to
+        let destination = tt.get_key(state.board.hash);
+        tt.entries[destination].hash_code = state.board.hash ^ 1;
+
+        assert!(tt.fetch(&state, 0).is_none());
+        assert_eq!(tt.stats.read_collision, 1);
+
+        tt.insert(
+            &state,
+            GenericMove::NULL_MOVE,
+            1,
+            SearchScoreType::Exact,
+            0,
+            0,
+            0,
+        );
+        assert_eq!(tt.stats.write_collision, 1);
+        assert_eq!(tt.stats.key_collisions(), 2);
+
+        // Same position again isn't a collision
+        assert!(tt.fetch(&state, 0).is_some());
+        assert_eq!(tt.stats.key_collisions(), 2);
+    }
+}
@@ -1,14 +1,28 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    board::FullGameState,
-    gods::PartialAction,
+    board::{FullGameState, MoveDescription, PositionValidation, TerminalResult},
+    gods::{GodDataItem, GodName, GodSupport, PartialAction, god_support_levels},
+    player::Player,
+    prove::ProvenLine,
+    refute::RefutationLine,
     search::{BestMoveTrigger, Heuristic},
+    win_reason::{WinReason, get_win_reason},
 };
 #[derive(Serialize, Deserialize, Debug)]
 pub struct NextStateOutput {
     pub next_state: FullGameState,
     pub actions: Vec<PartialAction>,
+    /// The move in engine notation, as accepted by `refute`
+    #[serde(default)]
+    pub action_str: Option<String>,
+    #[serde(default)]
+    pub is_win: bool,
+    /// Whether the mover could win next turn if the opponent passed
+    #[serde(default)]
+    pub is_check: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -16,6 +30,45 @@ pub struct NextMovesOutput {
     pub original_str: Option<String>,
     pub start_state: FullGameState,
     pub next_states: Vec<NextStateOutput>,
+    /// Each player's god specific state in `start_state`
+    #[serde(default)]
+    pub god_data: [Vec<GodDataItem>; 2],
+}
+
+impl NextMovesOutput {
+    /// Every legal move from `start_state`, with one entry per series of actions that plays it
+    pub fn new(original_str: Option<String>, start_state: FullGameState) -> Self {
+        let next_states = start_state
+            .describe_moves()
+            .into_iter()
+            .flat_map(|description| {
+                let MoveDescription {
+                    action_str,
+                    action_paths,
+                    is_win,
+                    is_check,
+                    next_state,
+                    ..
+                } = description;
+                action_paths
+                    .into_iter()
+                    .map(move |actions| NextStateOutput {
+                        next_state: next_state.clone(),
+                        actions,
+                        action_str: Some(action_str.clone()),
+                        is_win,
+                        is_check,
+                    })
+            })
+            .collect();
+
+        Self {
+            original_str,
+            god_data: start_state.god_data_items(),
+            start_state,
+            next_states,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -26,6 +79,20 @@ pub struct BestMoveMeta {
     pub elapsed_seconds: f32,
     pub actions: Vec<PartialAction>,
     pub action_str: Option<String>,
+    /// Set when the move immediately ends the game
+    #[serde(default)]
+    pub win_reason: Option<WinReason>,
+    #[serde(default)]
+    pub nodes_per_second: Option<f32>,
+    /// Deepest ply reached, including quiescence
+    #[serde(default)]
+    pub seldepth: Option<usize>,
+    /// Fraction of transposition table probes that found an entry
+    #[serde(default)]
+    pub tt_hit_rate: Option<f32>,
+    /// Each player's god specific state after the move
+    #[serde(default)]
+    pub god_data: [Vec<GodDataItem>; 2],
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -37,8 +104,149 @@ pub struct BestMoveOutput {
     pub meta: BestMoveMeta,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefutationOutput {
+    pub original_str: Option<String>,
+    pub start_state: FullGameState,
+    pub refutation: RefutationLine,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofOutput {
+    pub original_str: Option<String>,
+    pub start_state: FullGameState,
+    pub max_depth: usize,
+    /// None if there's no forced win within max_depth plies
+    pub proven: Option<ProvenLine>,
+}
+
+/// The result of validating a position, sent for the `validate` command and when a position
+/// can't be searched
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValidationOutput {
+    pub original_str: Option<String>,
+    #[serde(flatten)]
+    pub validation: PositionValidation,
+}
+
+/// Periodic progress of a search, so that callers can tell the engine is still alive
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InfoOutput {
+    pub original_str: Option<String>,
+    pub start_state: FullGameState,
+    /// The depth currently being searched
+    pub depth: usize,
+    pub nodes_visited: usize,
+    pub nodes_per_second: usize,
+    pub elapsed_seconds: f32,
+}
+
+/// The commit this build of the engine came from, or `unknown` outside of a git checkout
+pub const ENGINE_GIT_HASH: &str = env!("SANTORINI_GIT_HASH");
+/// The cargo profile this build of the engine came from, like `release`
+pub const ENGINE_BUILD_PROFILE: &str = env!("SANTORINI_BUILD_PROFILE");
+
+/// Names this build of the engine, so that results stay attributable after binaries are renamed
+pub fn engine_version() -> String {
+    format!("{}-{}", ENGINE_GIT_HASH, ENGINE_BUILD_PROFILE)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-pub struct StartedOutput {}
+pub struct StartedOutput {
+    /// How well the engine plays each god. Missing from older engines
+    #[serde(default)]
+    pub god_support: BTreeMap<GodName, GodSupport>,
+    /// `engine_version` of the engine. Missing from older engines
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+impl StartedOutput {
+    /// The message for this build of the engine
+    pub fn current() -> Self {
+        Self {
+            god_support: god_support_levels(),
+            version: Some(engine_version()),
+        }
+    }
+}
+
+/// Why a game ended without a winner. The rules of Santorini have no draws, so these only come
+/// from match runners and tools that stop games early
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawReason {
+    /// A match runner called the game before it finished
+    Adjudicated,
+    /// The same position came up too many times
+    Repetition,
+    /// The game ran past a match runner's move limit
+    MoveLimit,
+}
+
+impl std::fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            DrawReason::Adjudicated => "adjudicated",
+            DrawReason::Repetition => "repetition",
+            DrawReason::MoveLimit => "move limit",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// How a game ended. `FullGameState::get_winner` covers the rules, and this wraps it wherever
+/// games can also end some other way. A win's reason is None when it can't be worked out, like
+/// for a position that was set up already won
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameResult {
+    Win(Player, Option<WinReason>),
+    Draw(DrawReason),
+}
+
+impl GameResult {
+    /// The result of a finished `state`, reached from `prev` if it's known
+    pub fn from_states(prev: Option<&FullGameState>, state: &FullGameState) -> Option<Self> {
+        match state.get_terminal_result()? {
+            TerminalResult::Won(winner) => Some(GameResult::Win(
+                winner,
+                prev.and_then(|prev| get_win_reason(prev, state)),
+            )),
+            TerminalResult::Smothered(loser) => {
+                Some(GameResult::Win(!loser, Some(WinReason::OpponentSmothered)))
+            }
+        }
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameResult::Win(winner, _) => Some(*winner),
+            GameResult::Draw(_) => None,
+        }
+    }
+
+    /// 1 for a win, 0.5 for a draw and 0 for a loss
+    pub fn score_for(&self, player: Player) -> f64 {
+        match self.winner() {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::Win(winner, Some(reason)) => {
+                write!(f, "Player {:?} wins ({})", winner, reason)
+            }
+            GameResult::Win(winner, None) => write!(f, "Player {:?} wins", winner),
+            GameResult::Draw(reason) => write!(f, "Draw ({})", reason),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "type")]
@@ -47,4 +255,56 @@ pub enum EngineOutput {
     Started(StartedOutput),
     BestMove(BestMoveOutput),
     NextMoves(NextMovesOutput),
+    Refutation(RefutationOutput),
+    Proof(ProofOutput),
+    Info(InfoOutput),
+    Validation(ValidationOutput),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_result_from_states() {
+        let prev = FullGameState::try_from("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3")
+            .unwrap();
+        assert_eq!(GameResult::from_states(None, &prev), None);
+
+        let won = prev
+            .describe_moves()
+            .into_iter()
+            .find(|description| description.is_win)
+            .unwrap()
+            .next_state;
+        assert_eq!(
+            GameResult::from_states(Some(&prev), &won),
+            Some(GameResult::Win(Player::One, Some(WinReason::MovedToLevel3)))
+        );
+        assert_eq!(
+            GameResult::from_states(None, &won),
+            Some(GameResult::Win(Player::One, None))
+        );
+    }
+
+    #[test]
+    fn test_game_result_serde_round_trip() {
+        let results = [
+            GameResult::Win(Player::Two, Some(WinReason::PanFall)),
+            GameResult::Win(Player::One, None),
+            GameResult::Draw(DrawReason::Repetition),
+        ];
+        for result in results {
+            let json = serde_json::to_string(&result).unwrap();
+            assert_eq!(serde_json::from_str::<GameResult>(&json).unwrap(), result);
+        }
+        assert_eq!(
+            serde_json::to_string(&GameResult::Draw(DrawReason::MoveLimit)).unwrap(),
+            r#"{"draw":"move_limit"}"#
+        );
+        assert_eq!(
+            GameResult::Draw(DrawReason::Adjudicated).score_for(Player::One),
+            0.5
+        );
+    }
 }
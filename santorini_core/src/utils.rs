@@ -3,23 +3,89 @@
 #![allow(unused)]
 use crate::{
     bitboard::BitBoard,
-    board::{FullGameState, GodPair},
+    board::{BoardState, FullGameState, GodPair, MoveDescription},
     gods::PartialAction,
     hashing::HashType,
 };
 use chrono::Local;
 
+/// Returned when no legal move reaches the destination of `find_action_path`
+#[derive(Clone, Debug)]
+pub struct ActionPathError {
+    pub start_state: FullGameState,
+    pub destination_state: FullGameState,
+    /// The reachable state that differs from the destination on the fewest squares, if any
+    pub nearest_state: Option<FullGameState>,
+    pub nearest_action_str: Option<String>,
+    pub nearest_differences: u32,
+}
+
+impl std::fmt::Display for ActionPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No legal move from {:?} reaches {:?}",
+            self.start_state, self.destination_state
+        )?;
+        if let (Some(nearest_state), Some(action_str)) =
+            (&self.nearest_state, &self.nearest_action_str)
+        {
+            write!(
+                f,
+                ". Nearest: {} -> {:?} ({} differences)",
+                action_str, nearest_state, self.nearest_differences
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ActionPathError {}
+
+/// Number of squares where heights or workers differ, plus one for each other difference
+fn count_differences(a: &BoardState, b: &BoardState) -> u32 {
+    let mut differences = (a.current_player != b.current_player) as u32;
+    for height in 0..4 {
+        differences += (a.height_map[height] ^ b.height_map[height]).count_ones();
+    }
+    for player in 0..2 {
+        differences += (a.workers[player] ^ b.workers[player]).count_ones();
+        differences += (a.god_data[player] != b.god_data[player]) as u32;
+    }
+    differences
+}
+
+/// The interactive actions that take `start_state` to `destination_state`. When several paths
+/// reach it, this is the first path declared for the first move that gets there, so moves that
+/// can be played in either order (like Castor's) always resolve the same way
 pub fn find_action_path(
     start_state: &FullGameState,
     destination_state: &FullGameState,
-) -> Option<Vec<PartialAction>> {
-    let all_child_states = start_state.get_next_states_interactive();
-    for full_child in all_child_states {
-        if &full_child.state == destination_state {
-            return Some(full_child.actions);
+) -> Result<Vec<PartialAction>, Box<ActionPathError>> {
+    let descriptions = start_state.describe_moves_with::<false>();
+
+    let mut nearest: Option<(u32, &MoveDescription)> = None;
+    for description in &descriptions {
+        if &description.next_state == destination_state
+            && let Some(actions) = description.action_paths.first()
+        {
+            return Ok(actions.clone());
+        }
+
+        let differences =
+            count_differences(&description.next_state.board, &destination_state.board);
+        if nearest.is_none_or(|(nearest_differences, _)| differences < nearest_differences) {
+            nearest = Some((differences, description));
         }
     }
-    None
+
+    Err(Box::new(ActionPathError {
+        start_state: start_state.clone(),
+        destination_state: destination_state.clone(),
+        nearest_state: nearest.map(|(_, description)| description.next_state.clone()),
+        nearest_action_str: nearest.map(|(_, description)| description.action_str.clone()),
+        nearest_differences: nearest.map_or(0, |(differences, _)| differences),
+    }))
 }
 
 pub fn sigmoid(x: f32) -> f32 {
@@ -44,10 +110,66 @@ pub const fn grid_position_builder<T: Copy>(
     ]
 }
 
+/// Splits the first argument off a protocol command line, returning it along with the raw rest
+/// of the line. Arguments are separated by whitespace, and can be wrapped in double quotes to
+/// include whitespace, with `\"` and `\\` escaped inside quotes
+pub fn split_first_arg(line: &str) -> Result<Option<(String, &str)>, String> {
+    let line = line.trim_start();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut arg = String::new();
+    let mut is_quoted = false;
+    let mut chars = line.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => is_quoted = !is_quoted,
+            '\\' if is_quoted => match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => arg.push(escaped),
+                Some((_, other)) => {
+                    arg.push('\\');
+                    arg.push(other);
+                }
+                None => break,
+            },
+            c if c.is_whitespace() && !is_quoted => {
+                return Ok(Some((arg, line[idx..].trim_start())));
+            }
+            c => arg.push(c),
+        }
+    }
+
+    if is_quoted {
+        return Err(format!("Unterminated quote in: {}", line));
+    }
+    Ok(Some((arg, "")))
+}
+
+/// Splits a protocol command line into its arguments, as described in `split_first_arg`
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut result = Vec::new();
+    let mut rest = line;
+    while let Some((arg, next_rest)) = split_first_arg(rest)? {
+        result.push(arg);
+        rest = next_rest;
+    }
+    Ok(result)
+}
+
 pub fn timestamp_string() -> String {
     Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
 }
 
+/// The message a caught panic was raised with
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned())
+}
+
 pub(crate) fn base_hash_for_god_pair(gods: GodPair) -> HashType {
     gods[0].hash1 ^ gods[0].hash2
 }
@@ -69,24 +191,24 @@ pub const fn hash_u64(mut x: usize) -> usize {
 }
 
 pub fn print_cpu_arch() {
-    println!("Target arch: {}", std::env::consts::ARCH);
-    println!("Target family: {}", std::env::consts::FAMILY);
-    println!("Target os: {}", std::env::consts::OS);
+    log::info!("Target arch: {}", std::env::consts::ARCH);
+    log::info!("Target family: {}", std::env::consts::FAMILY);
+    log::info!("Target os: {}", std::env::consts::OS);
 
     #[cfg(target_arch = "x86_64")]
     {
-        println!("sse2: {}", std::is_x86_feature_detected!("sse2"));
-        println!("avx: {}", std::is_x86_feature_detected!("avx"));
-        println!("avx512f: {}", std::is_x86_feature_detected!("avx512f"));
-        println!("avx2: {}", std::is_x86_feature_detected!("avx2"));
-        println!("fma: {}", std::is_x86_feature_detected!("fma"));
-        println!("bmi2: {}", std::is_x86_feature_detected!("bmi2"));
+        log::info!("sse2: {}", std::is_x86_feature_detected!("sse2"));
+        log::info!("avx: {}", std::is_x86_feature_detected!("avx"));
+        log::info!("avx512f: {}", std::is_x86_feature_detected!("avx512f"));
+        log::info!("avx2: {}", std::is_x86_feature_detected!("avx2"));
+        log::info!("fma: {}", std::is_x86_feature_detected!("fma"));
+        log::info!("bmi2: {}", std::is_x86_feature_detected!("bmi2"));
     }
 
     #[cfg(target_feature = "avx2")]
     {
         use std::arch::x86_64::*;
-        println!("using avx2");
+        log::info!("using avx2");
     }
 }
 
@@ -152,7 +274,7 @@ pub const SEARCH_TEST_SCENARIOS: [(&'static str, usize); 56] = [
 
 #[cfg(test)]
 mod tests {
-    use crate::{bitboard::NEIGHBOR_MAP, board::FullGameState, square::Square};
+    use crate::{bitboard::NEIGHBOR_MAP, board::FullGameState, gods::GodName, square::Square};
 
     use super::*;
 
@@ -165,4 +287,70 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_split_args_quoted_paths() {
+        assert_eq!(
+            split_args(r#"setoption matchup_policy "/tmp/My Policy.json""#).unwrap(),
+            vec!["setoption", "matchup_policy", "/tmp/My Policy.json"],
+        );
+        assert_eq!(
+            split_args(r#"load "a \"quoted\" name" "back\\slash" C:\path"#).unwrap(),
+            vec!["load", r#"a "quoted" name"#, r"back\slash", r"C:\path"],
+        );
+        assert_eq!(split_args(r#"empty """#).unwrap(), vec!["empty", ""]);
+        assert!(split_args(r#"load "unterminated"#).is_err());
+        assert!(split_args(r#"load "trailing escape\"#).is_err());
+    }
+
+    #[test]
+    fn test_split_args_keeps_fen_case() {
+        let fen = "0000000000000000000000000/1/Mortal:A1,B2/ARTEMIS:C3,D4";
+        let args = split_args(&format!("set_position {}", fen)).unwrap();
+        assert_eq!(args, vec!["set_position", fen]);
+
+        let state = FullGameState::try_from(args[1].as_str()).unwrap();
+        assert_eq!(state.gods[1].god_name, GodName::Artemis);
+    }
+
+    #[test]
+    fn test_split_args_trailing_whitespace() {
+        assert_eq!(split_args("  stop \t\r\n").unwrap(), vec!["stop"]);
+        assert!(split_args(" \n").unwrap().is_empty());
+
+        let (fen, rest) = split_first_arg("fen  A1>B2 C3>D4 \n").unwrap().unwrap();
+        assert_eq!(fen, "fen");
+        assert_eq!(rest, "A1>B2 C3>D4 \n");
+    }
+
+    #[test]
+    fn test_find_action_path_prefers_declared_order() {
+        let state =
+            FullGameState::try_from("0000000000000000000000000/1/castor:B4,D2/mortal:A1,E5")
+                .unwrap();
+
+        let mut checked_transpositions = 0;
+        for description in state.describe_moves() {
+            let path = find_action_path(&state, &description.next_state).unwrap();
+            assert_eq!(path, description.action_paths[0]);
+            checked_transpositions += (description.action_paths.len() > 1) as usize;
+        }
+        assert!(checked_transpositions > 0);
+    }
+
+    #[test]
+    fn test_find_action_path_names_nearest_state() {
+        let state =
+            FullGameState::try_from("0000000000000000000000000/1/mortal:B4,D2/mortal:A1,E5")
+                .unwrap();
+        let reachable = state.describe_moves()[0].next_state.clone();
+
+        // Same move, but without passing the turn
+        let mut destination = reachable.clone();
+        destination.board.current_player = !destination.board.current_player;
+
+        let err = find_action_path(&state, &destination).unwrap_err();
+        assert_eq!(err.nearest_state, Some(reachable));
+        assert_eq!(err.nearest_differences, 1);
+    }
 }
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::{BETWEEN_MAPPING, BitBoard, NEIGHBOR_MAP},
+    board::FullGameState,
+    gods::{
+        GodName, bellerophon::BellerophonMove, castor::CastorMove, generic::GenericMove,
+        harpies::slide_position_with_custom_blockers,
+    },
+};
+
+/// The condition that ended a game
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinReason {
+    /// A worker moved up onto level 3, including god powers that climb more than one level
+    MovedToLevel3,
+    /// Pan moved down 2 or more levels
+    PanFall,
+    /// Eros moved their workers next to each other
+    ErosPair,
+    /// Maenads surrounded an opponent worker
+    MaenadsDance,
+    /// The loser had no legal moves
+    OpponentSmothered,
+    /// Chronus had enough complete towers on the board
+    ChronusTowers,
+    /// The loser's engine stopped responding while on the clock. Only used by match runners
+    Forfeit,
+    /// The loser's engine played a move that isn't legal in the position. Only used by match
+    /// runners
+    IllegalMove,
+}
+
+impl std::fmt::Display for WinReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            WinReason::MovedToLevel3 => "moved to level 3",
+            WinReason::PanFall => "pan fall",
+            WinReason::ErosPair => "eros pair",
+            WinReason::MaenadsDance => "maenads dance",
+            WinReason::OpponentSmothered => "opponent smothered",
+            WinReason::ChronusTowers => "chronus towers",
+            WinReason::Forfeit => "forfeit",
+            WinReason::IllegalMove => "illegal move",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// Returns why `current` is won, given that it was reached from `prev` with a single action.
+/// Returns None if `current` isn't won, or can't be reached from `prev`.
+pub fn get_win_reason(prev: &FullGameState, current: &FullGameState) -> Option<WinReason> {
+    let winner = current.get_winner()?;
+    let current_player = prev.board.current_player;
+    if winner != current_player {
+        return Some(WinReason::OpponentSmothered);
+    }
+
+    let (active_god, oppo_god) = prev.get_active_non_active_gods();
+    let action = active_god
+        .get_winning_moves(prev, current_player)
+        .into_iter()
+        .map(|scored_move| scored_move.action)
+        .find(|&action| &prev.next_state(active_god, oppo_god, action) == current)?;
+
+    explain_win(prev, current, action).ok()
+}
+
+/// Explains why playing `action` from `state` resulted in `won_state` being won by the player to
+/// move in `state`. Returns an error describing the problem if the win isn't explainable by any
+/// known rule.
+pub fn explain_win(
+    state: &FullGameState,
+    won_state: &FullGameState,
+    action: GenericMove,
+) -> Result<WinReason, String> {
+    let current_player = state.board.current_player;
+    let (active_god, oppo_god) = state.get_active_non_active_gods();
+    let stringed_action = active_god.stringify_move(action);
+
+    if won_state.get_winner() != Some(current_player) {
+        return Err(format!(
+            "Winning move did not result in win: {}. {:?} -> {:?} winner: {:?} current_player: {:?}",
+            stringed_action,
+            state,
+            won_state,
+            won_state.get_winner(),
+            current_player
+        ));
+    }
+
+    if active_god.god_name == GodName::Chronus && won_state.board.height_map[3].count_ones() >= 5 {
+        return Ok(WinReason::ChronusTowers);
+    }
+
+    let win_mask = oppo_god.win_mask;
+    let old_workers = state.board.workers[current_player as usize];
+    let new_workers = won_state.board.workers[current_player as usize];
+    let old_only = old_workers & !new_workers;
+    let new_only = new_workers & !old_workers;
+
+    if old_only.count_ones() != 1 || new_only.count_ones() != 1 {
+        if active_god.god_name == GodName::Castor
+            && _is_castor_double_move_win(state, won_state, action, win_mask)
+        {
+            return Ok(WinReason::MovedToLevel3);
+        }
+
+        return Err(format!(
+            "Could not identify moved worker from winning move: {}. {:?} -> {:?}",
+            stringed_action, state, won_state,
+        ));
+    }
+
+    let old_pos = old_only.lsb();
+    let new_pos = new_only.lsb();
+    let old_height = state.board.get_height(old_pos) as i32;
+    let new_height = won_state.board.get_height(new_pos) as i32;
+
+    let is_pan_falling_win = active_god.god_name == GodName::Pan && new_height <= old_height - 2;
+    let can_climb = oppo_god.can_opponent_climb(&state.board, !current_player);
+    let is_plain_climb_win =
+        can_climb && old_height == 2 && new_height == 3 && (win_mask & new_only).is_not_empty();
+
+    if active_god.god_name == GodName::Maenads {
+        return Ok(match is_plain_climb_win {
+            true => WinReason::MovedToLevel3,
+            false => WinReason::MaenadsDance,
+        });
+    }
+
+    if active_god.god_name == GodName::Eros {
+        return Ok(match is_plain_climb_win {
+            true => WinReason::MovedToLevel3,
+            false => WinReason::ErosPair,
+        });
+    }
+
+    if !can_climb && !is_pan_falling_win {
+        return Err(format!(
+            "Win when blocked by athena: {}. {:?} -> {:?}",
+            stringed_action, state, won_state,
+        ));
+    }
+
+    if active_god.god_name == GodName::Bellerophon && old_height == 1 && new_height == 3 {
+        let action: BellerophonMove = action.into();
+        if action.is_use_power() {
+            return Ok(WinReason::MovedToLevel3);
+        }
+    }
+
+    if active_god.god_name == GodName::Stymphalians {
+        // This guy can move a lot...
+        if new_height == 3 {
+            return Ok(WinReason::MovedToLevel3);
+        }
+    }
+
+    if (win_mask & new_only).is_empty() {
+        if active_god.god_name == GodName::Castor
+            && _is_castor_double_move_win(state, won_state, action, win_mask)
+        {
+            return Ok(WinReason::MovedToLevel3);
+        }
+
+        return Err(format!(
+            "Winning move did not move to win mask: {}. {:?} -> {:?}",
+            stringed_action, state, won_state,
+        ));
+    }
+
+    if old_height == 2 && new_height == 3 {
+        return Ok(WinReason::MovedToLevel3);
+    }
+
+    if is_pan_falling_win {
+        return Ok(WinReason::PanFall);
+    }
+
+    if active_god.god_name == GodName::Triton && new_height == 3 {
+        // Triton can climb the rest of the way while chaining through perimeter squares
+        return Ok(WinReason::MovedToLevel3);
+    }
+
+    if active_god.god_name == GodName::Artemis {
+        let old_n = NEIGHBOR_MAP[old_pos as usize];
+        let new_n = NEIGHBOR_MAP[new_pos as usize];
+        let path = old_n & new_n;
+        let path = path & state.board.exactly_level_2();
+        let path = path & !(state.board.workers[0] | state.board.workers[1]);
+
+        if (old_height == 1 || old_height == 3) && new_height == 3 && path.is_not_empty() {
+            return Ok(WinReason::MovedToLevel3);
+        }
+
+        if oppo_god.god_name == GodName::Harpies {
+            for n in old_n {
+                let slide_n = slide_position_with_custom_blockers(
+                    &state.board,
+                    old_pos,
+                    n,
+                    state.board.workers[0] | state.board.workers[1],
+                );
+                if state.board.get_height(slide_n) == 2 {
+                    let final_n = NEIGHBOR_MAP[slide_n as usize];
+
+                    if (new_only & final_n).is_not_empty() {
+                        return Ok(WinReason::MovedToLevel3);
+                    }
+                }
+            }
+        }
+    }
+
+    if active_god.god_name == GodName::Castor
+        && _is_castor_double_move_win(state, won_state, action, win_mask)
+    {
+        return Ok(WinReason::MovedToLevel3);
+    }
+
+    // Iris wins by jumping over a worker to level 2
+    if active_god.god_name == GodName::Iris
+        && let Some(jumped_pos) = BETWEEN_MAPPING[old_pos as usize][new_pos as usize]
+    {
+        let all_workers = won_state.board.workers[0] | won_state.board.workers[1];
+
+        if all_workers.contains_square(jumped_pos) && old_height < 3 && new_height == 3 {
+            return Ok(WinReason::MovedToLevel3);
+        }
+    }
+
+    Err(format!(
+        "Move won with unknown winning condition: {}. {:?} -> {:?}. Old pos: {old_pos} New pos: {new_pos}. Old height: {old_height} New height: {new_height}",
+        stringed_action, state, won_state,
+    ))
+}
+
+fn _is_castor_double_move_win(
+    prev_state: &FullGameState,
+    won_state: &FullGameState,
+    action: GenericMove,
+    win_mask: BitBoard,
+) -> bool {
+    let action: CastorMove = action.into();
+
+    let Some(f1) = action.maybe_move_from_position_1() else {
+        return false;
+    };
+    let Some(f2) = action.maybe_move_from_position_2() else {
+        return false;
+    };
+
+    let t1 = action.move_to_position_1();
+    let t2 = action.move_to_position_2();
+
+    let o1 = prev_state.board.get_height(f1) as i32;
+    let n1 = won_state.board.get_height(t1) as i32;
+    if o1 == 2 && n1 == 3 && (t1.to_board() & win_mask).is_not_empty() {
+        return true;
+    }
+
+    let o2 = prev_state.board.get_height(f2) as i32;
+    let n2 = won_state.board.get_height(t2) as i32;
+    o2 == 2 && n2 == 3 && (t2.to_board() & win_mask).is_not_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen::parse_fen;
+
+    use super::*;
+
+    fn win_reasons_from(fen: &str) -> Vec<WinReason> {
+        let state = parse_fen(fen).unwrap();
+        state
+            .get_next_states_interactive()
+            .into_iter()
+            .filter_map(|child| get_win_reason(&state, &child.state))
+            .collect()
+    }
+
+    #[test]
+    fn test_win_reason_moved_to_level_3() {
+        let reasons = win_reasons_from("2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3");
+        assert_eq!(reasons, vec![WinReason::MovedToLevel3]);
+    }
+
+    #[test]
+    fn test_win_reason_pan_fall() {
+        let reasons = win_reasons_from("2000000000000000000000000/1/pan:A5,E1/mortal:C3,E3");
+        assert!(!reasons.is_empty());
+        assert!(reasons.iter().all(|r| *r == WinReason::PanFall));
+    }
+
+    #[test]
+    fn test_win_reason_chronus_towers() {
+        let reasons = win_reasons_from("4444000000000000000000003/1/chronus:A1,D2/mortal:C4,D4");
+        assert!(reasons.contains(&WinReason::ChronusTowers));
+    }
+
+    #[test]
+    fn test_win_reason_smothered() {
+        let reasons = win_reasons_from("0400044000000000000000000/1/mortal:A5/mortal:E1");
+        assert_eq!(reasons, vec![WinReason::OpponentSmothered]);
+    }
+
+    #[test]
+    fn test_win_reason_non_terminal() {
+        let state = parse_fen("0000000000000000000000000/1/mortal:A5,E1/mortal:C3,E3").unwrap();
+        for child in state.get_next_states() {
+            assert_eq!(get_win_reason(&state, &child), None);
+        }
+    }
+}
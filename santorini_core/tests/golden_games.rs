@@ -0,0 +1,57 @@
+//! Replays every recorded game under `tests/golden_games/`, to catch move generation changes
+//! that single position checks miss. Record new games with `cargo run -p battler --bin
+//! record_golden`
+
+use std::{collections::BTreeSet, fs, path::PathBuf};
+
+use santorini_core::{gods::GodName, golden_game::GoldenGame};
+
+fn golden_game_paths() -> Vec<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden_games");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("Couldn't read {}: {}", dir.display(), err))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn load_golden_games() -> Vec<(PathBuf, GoldenGame)> {
+    golden_game_paths()
+        .into_iter()
+        .map(|path| {
+            let text = fs::read_to_string(&path).unwrap();
+            let game = GoldenGame::parse(&text)
+                .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+            (path, game)
+        })
+        .collect()
+}
+
+#[test]
+fn test_golden_games_replay() {
+    let games = load_golden_games();
+    assert!(!games.is_empty());
+
+    let failures: Vec<String> = games
+        .iter()
+        .filter_map(|(path, game)| {
+            let err = game.replay().err()?;
+            Some(format!("{}: {}", path.display(), err))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_golden_games_cover_every_god() {
+    let covered: BTreeSet<GodName> = load_golden_games()
+        .iter()
+        .flat_map(|(_, game)| game.start_state.gods.map(|god| god.god_name))
+        .collect();
+    let missing: Vec<GodName> = GodName::all_non_wip()
+        .filter(|god| !covered.contains(god))
+        .collect();
+    assert!(missing.is_empty(), "No golden games for {:?}", missing);
+}
@@ -0,0 +1,251 @@
+//! C API for the santorini engine.
+//!
+//! All strings passed in or out are nul terminated UTF-8. Strings returned by the engine are
+//! owned by the caller and must be released with `santorini_free_string`.
+//! Every fallible function returns one of the `SANTORINI_*` status codes below. Panics are caught
+//! at the boundary and reported as `SANTORINI_ERR_PANIC`. This requires an unwinding build, so
+//! build the library with `cargo build -p santorini_ffi --profile ffi` rather than `-r`.
+//! See `tests/ctypes_example.py` for usage from python.
+
+use std::{
+    ffi::{CStr, CString, c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use santorini_core::{
+    board::FullGameState,
+    fen::parse_fen,
+    search::{SearchContext, SearchError, get_past_win_search_terminator, negamax_search},
+    search_observer::NoopSearchObserver,
+    search_terminators::DynamicNodesVisitedSearchTerminator,
+    transposition_table::TranspositionTable,
+    uci_types::{EngineOutput, NextMovesOutput},
+};
+
+pub const SANTORINI_OK: c_int = 0;
+pub const SANTORINI_ERR_NULL_POINTER: c_int = 1;
+pub const SANTORINI_ERR_INVALID_UTF8: c_int = 2;
+pub const SANTORINI_ERR_INVALID_FEN: c_int = 3;
+pub const SANTORINI_ERR_NO_POSITION: c_int = 4;
+pub const SANTORINI_ERR_NO_MOVE: c_int = 5;
+pub const SANTORINI_ERR_SERIALIZE: c_int = 6;
+pub const SANTORINI_ERR_PANIC: c_int = 7;
+/// The search found the position inconsistent. Always an engine bug
+pub const SANTORINI_ERR_SEARCH_FAILED: c_int = 8;
+
+/// Opaque engine handle. Keeps its transposition table between searches
+pub struct SantoriniEngine {
+    tt: TranspositionTable,
+    state: Option<FullGameState>,
+}
+
+type FfiResult<T> = Result<T, c_int>;
+
+fn guard(f: impl FnOnce() -> FfiResult<()>) -> c_int {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => SANTORINI_OK,
+        Ok(Err(code)) => code,
+        Err(_) => SANTORINI_ERR_PANIC,
+    }
+}
+
+unsafe fn engine_mut<'a>(handle: *mut SantoriniEngine) -> FfiResult<&'a mut SantoriniEngine> {
+    unsafe { handle.as_mut() }.ok_or(SANTORINI_ERR_NULL_POINTER)
+}
+
+unsafe fn read_str<'a>(s: *const c_char) -> FfiResult<&'a str> {
+    if s.is_null() {
+        return Err(SANTORINI_ERR_NULL_POINTER);
+    }
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|_| SANTORINI_ERR_INVALID_UTF8)
+}
+
+unsafe fn write_str(out: *mut *mut c_char, s: String) -> FfiResult<()> {
+    if out.is_null() {
+        return Err(SANTORINI_ERR_NULL_POINTER);
+    }
+    let c_string = CString::new(s).map_err(|_| SANTORINI_ERR_SERIALIZE)?;
+    unsafe { *out = c_string.into_raw() };
+    Ok(())
+}
+
+/// Creates a new engine. Returns null on failure
+#[unsafe(no_mangle)]
+pub extern "C" fn santorini_new_engine() -> *mut SantoriniEngine {
+    panic::catch_unwind(|| {
+        Box::into_raw(Box::new(SantoriniEngine {
+            tt: TranspositionTable::new(),
+            state: None,
+        }))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Releases an engine created by `santorini_new_engine`. Null is ignored
+///
+/// # Safety
+/// `handle` must be null or a pointer returned by `santorini_new_engine` that wasn't freed yet
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn santorini_free(handle: *mut SantoriniEngine) {
+    if handle.is_null() {
+        return;
+    }
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(unsafe { Box::from_raw(handle) })));
+}
+
+/// Releases a string returned by the engine. Null is ignored
+///
+/// # Safety
+/// `s` must be null or a string returned by this library that wasn't freed yet
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn santorini_free_string(s: *mut c_char) {
+    if s.is_null() {
+        return;
+    }
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(unsafe { CString::from_raw(s) })));
+}
+
+/// Sets the position to analyze from a fen string
+///
+/// # Safety
+/// `handle` must be a live engine and `fen` a nul terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn santorini_set_position(
+    handle: *mut SantoriniEngine,
+    fen: *const c_char,
+) -> c_int {
+    guard(|| {
+        let engine = unsafe { engine_mut(handle) }?;
+        let fen = unsafe { read_str(fen) }?;
+        let state = parse_fen(fen).map_err(|_| SANTORINI_ERR_INVALID_FEN)?;
+        engine.state = Some(state);
+        Ok(())
+    })
+}
+
+/// Searches the current position for `nodes` nodes.
+/// On success writes the best move string to `out_move` and its score to `out_score`
+///
+/// # Safety
+/// `handle` must be a live engine. `out_move` and `out_score` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn santorini_search_nodes(
+    handle: *mut SantoriniEngine,
+    nodes: u64,
+    out_move: *mut *mut c_char,
+    out_score: *mut c_int,
+) -> c_int {
+    guard(|| {
+        let engine = unsafe { engine_mut(handle) }?;
+        if out_score.is_null() {
+            return Err(SANTORINI_ERR_NULL_POINTER);
+        }
+        let state = engine.state.clone().ok_or(SANTORINI_ERR_NO_POSITION)?;
+        if state.board.get_winner().is_some() {
+            return Err(SANTORINI_ERR_NO_MOVE);
+        }
+
+        let mut search_context = SearchContext {
+            tt: &mut engine.tt,
+            new_best_move_callback: Box::new(|_| {}),
+            terminator: DynamicNodesVisitedSearchTerminator::new(nodes as usize),
+            params: Default::default(),
+            root_move_filter: None,
+            observer: NoopSearchObserver,
+        };
+        let search_result =
+            negamax_search(&mut search_context, state, get_past_win_search_terminator()).map_err(
+                |err| match err {
+                    SearchError::TerminalRoot => SANTORINI_ERR_NO_MOVE,
+                    SearchError::InternalInconsistency { .. } => SANTORINI_ERR_SEARCH_FAILED,
+                },
+            )?;
+        let best_move = search_result.best_move.ok_or(SANTORINI_ERR_NO_MOVE)?;
+
+        unsafe { write_str(out_move, best_move.action_str) }?;
+        unsafe { *out_score = best_move.score as c_int };
+        Ok(())
+    })
+}
+
+/// Writes all legal next states of the current position as json to `out_json`.
+/// The format matches the `next_moves` output of the uci process
+///
+/// # Safety
+/// `handle` must be a live engine. `out_json` must be valid for writes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn santorini_legal_moves(
+    handle: *mut SantoriniEngine,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    guard(|| {
+        let engine = unsafe { engine_mut(handle) }?;
+        let state = engine.state.clone().ok_or(SANTORINI_ERR_NO_POSITION)?;
+
+        let output = EngineOutput::NextMoves(NextMovesOutput::new(None, state));
+        let json = serde_json::to_string(&output).map_err(|_| SANTORINI_ERR_SERIALIZE)?;
+
+        unsafe { write_str(out_json, json) }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_round_trip() {
+        let fen = CString::new("0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5").unwrap();
+        unsafe {
+            let engine = santorini_new_engine();
+            assert!(!engine.is_null());
+
+            let mut out: *mut c_char = ptr::null_mut();
+            let mut score: c_int = 0;
+            assert_eq!(
+                santorini_search_nodes(engine, 1000, &mut out, &mut score),
+                SANTORINI_ERR_NO_POSITION
+            );
+
+            assert_eq!(santorini_set_position(engine, fen.as_ptr()), SANTORINI_OK);
+
+            assert_eq!(santorini_legal_moves(engine, &mut out), SANTORINI_OK);
+            let json = CStr::from_ptr(out).to_str().unwrap().to_owned();
+            santorini_free_string(out);
+            assert!(json.contains("next_states"));
+
+            assert_eq!(
+                santorini_search_nodes(engine, 1000, &mut out, &mut score),
+                SANTORINI_OK
+            );
+            assert!(!CStr::from_ptr(out).to_str().unwrap().is_empty());
+            santorini_free_string(out);
+
+            santorini_free(engine);
+        }
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        let bad_fen = CString::new("not a fen").unwrap();
+        unsafe {
+            let engine = santorini_new_engine();
+            assert_eq!(
+                santorini_set_position(engine, bad_fen.as_ptr()),
+                SANTORINI_ERR_INVALID_FEN
+            );
+            assert_eq!(
+                santorini_set_position(engine, ptr::null()),
+                SANTORINI_ERR_NULL_POINTER
+            );
+            assert_eq!(
+                santorini_set_position(ptr::null_mut(), bad_fen.as_ptr()),
+                SANTORINI_ERR_NULL_POINTER
+            );
+            santorini_free(engine);
+        }
+    }
+}
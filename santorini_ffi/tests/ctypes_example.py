@@ -0,0 +1,82 @@
+"""
+Minimal example of driving the engine through the C API with ctypes.
+
+Build the library first, from the repository root:
+    cargo build -p santorini_ffi --profile ffi
+
+Then run:
+    python santorini_ffi/tests/ctypes_example.py
+"""
+
+import ctypes
+import json
+import pathlib
+import sys
+
+SANTORINI_OK = 0
+
+FEN = "0000000000000000000000000/1/mortal:A1,B1/mortal:D5,E5"
+
+
+def load_library():
+    names = {
+        "linux": "libsantorini_ffi.so",
+        "darwin": "libsantorini_ffi.dylib",
+        "win32": "santorini_ffi.dll",
+    }
+    name = names.get(sys.platform, names["linux"])
+    target_dir = pathlib.Path(__file__).resolve().parents[2] / "target"
+    for profile in ["ffi", "debug"]:
+        path = target_dir / profile / name
+        if path.exists():
+            return ctypes.CDLL(str(path))
+    raise FileNotFoundError(f"Could not find {name} in {target_dir}")
+
+
+def main():
+    lib = load_library()
+
+    lib.santorini_new_engine.restype = ctypes.c_void_p
+    lib.santorini_free.argtypes = [ctypes.c_void_p]
+    lib.santorini_free_string.argtypes = [ctypes.c_void_p]
+    lib.santorini_set_position.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
+    lib.santorini_search_nodes.argtypes = [
+        ctypes.c_void_p,
+        ctypes.c_uint64,
+        ctypes.POINTER(ctypes.c_void_p),
+        ctypes.POINTER(ctypes.c_int),
+    ]
+    lib.santorini_legal_moves.argtypes = [ctypes.c_void_p, ctypes.POINTER(ctypes.c_void_p)]
+
+    def take_string(ptr):
+        value = ctypes.string_at(ptr).decode("utf-8")
+        lib.santorini_free_string(ptr)
+        return value
+
+    engine = lib.santorini_new_engine()
+    if not engine:
+        raise RuntimeError("Failed to create engine")
+
+    try:
+        code = lib.santorini_set_position(engine, FEN.encode("utf-8"))
+        if code != SANTORINI_OK:
+            raise RuntimeError(f"set_position failed: {code}")
+
+        out = ctypes.c_void_p()
+        code = lib.santorini_legal_moves(engine, ctypes.byref(out))
+        if code != SANTORINI_OK:
+            raise RuntimeError(f"legal_moves failed: {code}")
+        legal_moves = json.loads(take_string(out))
+        print("legal moves:", len(legal_moves["next_states"]))
+
+        score = ctypes.c_int()
+        code = lib.santorini_search_nodes(engine, 100_000, ctypes.byref(out), ctypes.byref(score))
+        if code != SANTORINI_OK:
+            raise RuntimeError(f"search failed: {code}")
+        print("best move:", take_string(out), "score:", score.value)
+    finally:
+        lib.santorini_free(engine)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,167 @@
+use std::{
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    time::Instant,
+};
+
+use santorini_core::{
+    board::FullGameState,
+    search::{SearchContext, get_past_win_search_terminator, negamax_search},
+    search_observer::NoopSearchObserver,
+    search_terminators::DynamicNodesVisitedSearchTerminator,
+    transposition_table::TranspositionTable,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct AnalyzeFileArgs {
+    /// File with one FEN per line. Blank lines and lines starting with # are skipped
+    path: PathBuf,
+
+    /// Nodes to search per position
+    #[arg(short = 'n', long, default_value_t = 1_000_000)]
+    nodes: usize,
+
+    /// CSV file to write results to
+    #[arg(short = 'o', long, default_value = "results.csv")]
+    out: PathBuf,
+
+    /// Report progress to stderr after every this many positions
+    #[arg(short = 'p', long, default_value_t = 100)]
+    progress_every: usize,
+}
+
+/// Result of analyzing one line of the input file. Every field but `fen` is empty when `error`
+/// is set
+#[derive(Debug, Default)]
+struct AnalysisRow {
+    fen: String,
+    best_move: String,
+    score: String,
+    depth: String,
+    nodes: String,
+    millis: String,
+    error: String,
+}
+
+impl AnalysisRow {
+    fn error(fen: &str, error: impl Into<String>) -> Self {
+        Self {
+            fen: fen.to_owned(),
+            error: error.into(),
+            ..Default::default()
+        }
+    }
+
+    fn to_record(&self) -> [&str; 7] {
+        [
+            &self.fen,
+            &self.best_move,
+            &self.score,
+            &self.depth,
+            &self.nodes,
+            &self.millis,
+            &self.error,
+        ]
+    }
+}
+
+fn analyze_fen(tt: &mut TranspositionTable, fen: &str, nodes: usize) -> AnalysisRow {
+    let state = match FullGameState::try_from(fen) {
+        Ok(state) => state,
+        Err(e) => return AnalysisRow::error(fen, format!("Error parsing FEN: {}", e)),
+    };
+    if state.board.get_winner().is_some() {
+        return AnalysisRow::error(fen, "Position is already over");
+    }
+
+    let start_time = Instant::now();
+    let mut search_context = SearchContext {
+        tt,
+        new_best_move_callback: Box::new(|_| {}),
+        terminator: DynamicNodesVisitedSearchTerminator::new(nodes),
+        params: Default::default(),
+        root_move_filter: None,
+        observer: NoopSearchObserver,
+    };
+    let search_result =
+        match negamax_search(&mut search_context, state, get_past_win_search_terminator()) {
+            Ok(search_result) => search_result,
+            Err(err) => return AnalysisRow::error(fen, err.to_string()),
+        };
+    let Some(best_move) = search_result.best_move else {
+        return AnalysisRow::error(fen, "Search found no move");
+    };
+
+    AnalysisRow {
+        fen: fen.to_owned(),
+        best_move: best_move.action_str,
+        score: best_move.score.to_string(),
+        depth: best_move.depth.to_string(),
+        nodes: best_move.nodes_visited.to_string(),
+        millis: start_time.elapsed().as_millis().to_string(),
+        error: String::new(),
+    }
+}
+
+/// Searches every position in a file for a fixed number of nodes, sharing one transposition
+/// table, and writes a CSV row per position. Bad positions get a row with an error instead of
+/// stopping the batch
+pub fn run_analyze_file(args: AnalyzeFileArgs) -> Result<(), String> {
+    let file =
+        std::fs::File::open(&args.path).map_err(|e| format!("{}: {}", args.path.display(), e))?;
+    let mut writer =
+        csv::Writer::from_path(&args.out).map_err(|e| format!("{}: {}", args.out.display(), e))?;
+    writer
+        .write_record([
+            "fen",
+            "best_move",
+            "score",
+            "depth",
+            "nodes",
+            "millis",
+            "error",
+        ])
+        .map_err(|e| e.to_string())?;
+
+    let mut tt = TranspositionTable::new();
+    let start_time = Instant::now();
+    let mut analyzed = 0;
+    let mut failed = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("{}: {}", args.path.display(), e))?;
+        let fen = line.trim();
+        if fen.is_empty() || fen.starts_with('#') {
+            continue;
+        }
+
+        let row = analyze_fen(&mut tt, fen, args.nodes);
+        if !row.error.is_empty() {
+            failed += 1;
+        }
+        writer
+            .write_record(row.to_record())
+            .map_err(|e| e.to_string())?;
+
+        analyzed += 1;
+        if args.progress_every > 0 && analyzed % args.progress_every == 0 {
+            // Flushing with the progress report keeps the output usable if the batch is cut short
+            writer.flush().map_err(|e| e.to_string())?;
+            log::info!(
+                "analyzed {} positions ({} failed) in {:.1}s",
+                analyzed,
+                failed,
+                start_time.elapsed().as_secs_f32()
+            );
+        }
+    }
+
+    writer.flush().map_err(|e| e.to_string())?;
+    log::info!(
+        "done: analyzed {} positions ({} failed) in {:.1}s, results in {}",
+        analyzed,
+        failed,
+        start_time.elapsed().as_secs_f32(),
+        args.out.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,458 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex, mpsc},
+    thread,
+    time::Instant,
+};
+
+use clap::Parser;
+use santorini_core::{
+    board::FullGameState,
+    engine::EngineThreadWrapper,
+    eval::evaluate_state,
+    logging::LogArgs,
+    nnue::LabeledAccumulator,
+    search::{BestSearchResult, Heuristic},
+    uci_types::{BestMoveMeta, BestMoveOutput, EngineOutput, NextMovesOutput},
+    utils::find_action_path,
+    win_reason::get_win_reason,
+};
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+#[derive(Debug, Parser)]
+struct ServerArgs {
+    /// Address to listen on.
+    #[arg(short = 'b', long, default_value = "127.0.0.1:8080")]
+    bind: String,
+
+    /// Number of engines available for concurrent analysis requests.
+    #[arg(
+        short = 'p',
+        long,
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+    )]
+    pool_size: usize,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnalyzeRequest {
+    fen: String,
+    movetime_ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegalMovesRequest {
+    fen: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EvalRequest {
+    fens: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EvalResult {
+    fen: String,
+    eval: Option<Heuristic>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct HealthOutput {
+    status: &'static str,
+    pool_size: usize,
+    idle_engines: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorOutput {
+    error: String,
+}
+
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        HttpError {
+            status: 400,
+            message: message.into(),
+        }
+    }
+}
+
+/// Fixed set of engine threads. Engines are checked out for the duration of a request, so that
+/// each one keeps a warm TT across requests
+struct EnginePool {
+    size: usize,
+    idle: Mutex<Vec<EngineThreadWrapper>>,
+}
+
+impl EnginePool {
+    fn new(size: usize) -> Self {
+        EnginePool {
+            size,
+            idle: Mutex::new((0..size).map(|_| EngineThreadWrapper::new()).collect()),
+        }
+    }
+
+    fn try_acquire(self: &Arc<Self>) -> Option<PooledEngine> {
+        let engine = self.idle.lock().unwrap().pop()?;
+        Some(PooledEngine {
+            pool: self.clone(),
+            engine: Some(engine),
+        })
+    }
+
+    fn idle_count(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+}
+
+struct PooledEngine {
+    pool: Arc<EnginePool>,
+    engine: Option<EngineThreadWrapper>,
+}
+
+impl PooledEngine {
+    fn engine(&mut self) -> &mut EngineThreadWrapper {
+        self.engine.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledEngine {
+    fn drop(&mut self) {
+        if let Some(engine) = self.engine.take() {
+            self.pool.idle.lock().unwrap().push(engine);
+        }
+    }
+}
+
+fn json_header() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap()
+}
+
+fn respond_json<T: Serialize>(request: Request, status: u16, body: &T) {
+    let json = match serde_json::to_string(body) {
+        Ok(json) => json,
+        Err(e) => {
+            log::error!("Error serializing response: {}", e);
+            return;
+        }
+    };
+    let response = Response::from_string(json)
+        .with_status_code(status)
+        .with_header(json_header());
+    if let Err(e) = request.respond(response) {
+        log::error!("Error writing response: {}", e);
+    }
+}
+
+fn respond_error(request: Request, err: HttpError) {
+    respond_json(request, err.status, &ErrorOutput { error: err.message });
+}
+
+fn read_body<T: for<'de> Deserialize<'de>>(request: &mut Request) -> Result<T, HttpError> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| HttpError::bad_request(format!("Error reading body: {}", e)))?;
+    serde_json::from_str(&body).map_err(|e| HttpError::bad_request(format!("Invalid body: {}", e)))
+}
+
+fn parse_non_terminal_fen(fen: &str) -> Result<FullGameState, String> {
+    let state = FullGameState::try_from(fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
+    if state.board.get_winner().is_some() {
+        return Err("Position is already terminal".to_owned());
+    }
+    Ok(state)
+}
+
+fn best_move_output(
+    fen: &str,
+    state: &FullGameState,
+    best_move: BestSearchResult,
+    start_time: Instant,
+) -> EngineOutput {
+    let actions = find_action_path(state, &best_move.child_state).unwrap_or_default();
+    let win_reason = get_win_reason(state, &best_move.child_state);
+    let elapsed_seconds = start_time.elapsed().as_secs_f32();
+    let nodes_per_second = best_move.nodes_per_second(elapsed_seconds);
+    let tt_hit_rate = best_move.tt_hit_rate();
+    let god_data = best_move.child_state.god_data_items();
+    EngineOutput::BestMove(BestMoveOutput {
+        original_str: Some(fen.to_owned()),
+        start_state: state.clone(),
+        next_state: best_move.child_state,
+        trigger: best_move.trigger,
+        meta: BestMoveMeta {
+            score: best_move.score,
+            calculated_depth: best_move.depth,
+            nodes_visited: Some(best_move.nodes_visited),
+            elapsed_seconds,
+            nodes_per_second,
+            seldepth: Some(best_move.seldepth),
+            tt_hit_rate,
+            actions,
+            action_str: Some(best_move.action_str),
+            win_reason,
+            god_data,
+        },
+    })
+}
+
+/// Streams one json line per new best move, until movetime_ms has elapsed
+fn handle_analyze(mut request: Request, pool: &Arc<EnginePool>) {
+    let args: AnalyzeRequest = match read_body(&mut request) {
+        Ok(args) => args,
+        Err(err) => return respond_error(request, err),
+    };
+    let state = match parse_non_terminal_fen(&args.fen) {
+        Ok(state) => state,
+        Err(err) => return respond_error(request, HttpError::bad_request(err)),
+    };
+    let Some(mut pooled_engine) = pool.try_acquire() else {
+        return respond_error(
+            request,
+            HttpError {
+                status: 429,
+                message: "All engines are busy".to_owned(),
+            },
+        );
+    };
+
+    // Responses are written by hand as a chunked body, so that each line is flushed as soon as
+    // it's found
+    let mut writer = request.into_writer();
+    let (line_sender, line_receiver) = mpsc::channel::<Option<String>>();
+    let writer_thread = thread::spawn(move || {
+        let headers = "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\n\r\n";
+        if writer.write_all(headers.as_bytes()).is_err() {
+            return;
+        }
+        while let Ok(Some(line)) = line_receiver.recv() {
+            let chunk = format!("{:X}\r\n{}\n\r\n", line.len() + 1, line);
+            if writer
+                .write_all(chunk.as_bytes())
+                .and_then(|_| writer.flush())
+                .is_err()
+            {
+                return;
+            }
+        }
+        let _ = writer.write_all(b"0\r\n\r\n").and_then(|_| writer.flush());
+    });
+
+    let start_time = Instant::now();
+    let callback_sender = line_sender.clone();
+    let callback_state = state.clone();
+    let callback = Arc::new(move |best_move: BestSearchResult| {
+        let output = best_move_output(&args.fen, &callback_state, best_move, start_time);
+        match serde_json::to_string(&output) {
+            Ok(json) => {
+                let _ = callback_sender.send(Some(json));
+            }
+            Err(e) => log::error!("Error serializing message: {}", e),
+        }
+    });
+
+    let result = pooled_engine.engine().search_for_duration_with_callback(
+        &state,
+        args.movetime_ms as f32 / 1000.0,
+        Some(callback),
+    );
+    if let Err(err) = result {
+        if let Ok(json) = serde_json::to_string(&ErrorOutput { error: err }) {
+            let _ = line_sender.send(Some(json));
+        }
+    }
+
+    let _ = line_sender.send(None);
+    let _ = writer_thread.join();
+}
+
+fn handle_legal_moves(mut request: Request) {
+    let args: LegalMovesRequest = match read_body(&mut request) {
+        Ok(args) => args,
+        Err(err) => return respond_error(request, err),
+    };
+    let state = match parse_non_terminal_fen(&args.fen) {
+        Ok(state) => state,
+        Err(err) => return respond_error(request, HttpError::bad_request(err)),
+    };
+
+    let output = EngineOutput::NextMoves(NextMovesOutput::new(Some(args.fen), state));
+
+    respond_json(request, 200, &output);
+}
+
+/// Static evaluation of each position, from the perspective of the player to move
+fn handle_eval(mut request: Request) {
+    let args: EvalRequest = match read_body(&mut request) {
+        Ok(args) => args,
+        Err(err) => return respond_error(request, err),
+    };
+
+    let results: Vec<EvalResult> = args
+        .fens
+        .into_iter()
+        .map(|fen| match parse_non_terminal_fen(&fen) {
+            Ok(state) => {
+                let mut acc = LabeledAccumulator::new_from_scratch(
+                    &state.board,
+                    state.gods[0].model_god_name,
+                    state.gods[1].model_god_name,
+                );
+                acc.replace_from_state(&state);
+                EvalResult {
+                    fen,
+                    eval: Some(evaluate_state(&state, &acc)),
+                    error: None,
+                }
+            }
+            Err(err) => EvalResult {
+                fen,
+                eval: None,
+                error: Some(err),
+            },
+        })
+        .collect();
+
+    respond_json(request, 200, &results);
+}
+
+fn handle_request(request: Request, pool: &Arc<EnginePool>) {
+    log::info!("{} {}", request.method(), request.url());
+
+    match (request.method(), request.url()) {
+        (Method::Get, "/health") => respond_json(
+            request,
+            200,
+            &HealthOutput {
+                status: "ok",
+                pool_size: pool.size,
+                idle_engines: pool.idle_count(),
+            },
+        ),
+        (Method::Post, "/analyze") => handle_analyze(request, pool),
+        (Method::Post, "/legal_moves") => handle_legal_moves(request),
+        (Method::Post, "/eval") => handle_eval(request),
+        _ => {
+            let message = format!("Unknown route: {} {}", request.method(), request.url());
+            respond_error(
+                request,
+                HttpError {
+                    status: 404,
+                    message,
+                },
+            )
+        }
+    }
+}
+
+/// Handles each request on its own thread, forever
+fn serve(server: Server, pool: Arc<EnginePool>) {
+    for request in server.incoming_requests() {
+        let pool = pool.clone();
+        thread::spawn(move || handle_request(request, &pool));
+    }
+}
+
+fn main() {
+    let args = ServerArgs::parse();
+    args.log.init();
+
+    let pool = Arc::new(EnginePool::new(args.pool_size));
+    let server = Server::http(&args.bind).expect("Failed to bind server");
+    log::info!(
+        "Listening on {} with {} engine(s)",
+        args.bind,
+        args.pool_size
+    );
+
+    serve(server, pool);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+    };
+
+    use super::*;
+
+    /// Sends a raw HTTP request to a server started for the test, and reads the whole response
+    /// as a head and a list of decoded chunks
+    fn post_chunked(path: &str, body: &str) -> (String, Vec<String>) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || serve(server, Arc::new(EnginePool::new(1))));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            path,
+            addr,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = Vec::new();
+        let mut buffer = [0; 4096];
+        while !response.ends_with(b"\r\n0\r\n\r\n") {
+            let read = stream.read(&mut buffer).unwrap();
+            assert!(read > 0, "Connection closed before the last chunk");
+            response.extend_from_slice(&buffer[..read]);
+        }
+        let response = String::from_utf8(response).unwrap();
+
+        let (head, mut rest) = response.split_once("\r\n\r\n").unwrap();
+        let mut chunks = Vec::new();
+        loop {
+            let (size, after_size) = rest.split_once("\r\n").unwrap();
+            let size = usize::from_str_radix(size, 16).unwrap();
+            if size == 0 {
+                break;
+            }
+            chunks.push(after_size[..size].to_owned());
+            rest = after_size[size..].strip_prefix("\r\n").unwrap();
+        }
+        (head.to_owned(), chunks)
+    }
+
+    #[test]
+    fn test_analyze_streams_best_moves() {
+        // P1 wins by climbing from A5 to B5
+        let fen = "2300000000000000000000000/1/mortal:A5,E1/mortal:C3,E3";
+        let body = format!(r#"{{"fen": "{}", "movetime_ms": 200}}"#, fen);
+        let (head, chunks) = post_chunked("/analyze", &body);
+
+        assert!(head.starts_with("HTTP/1.1 200"));
+        assert!(head.contains("Transfer-Encoding: chunked"));
+        assert!(!chunks.is_empty());
+
+        // One json line per chunk, each a best move for the requested position
+        let mut last_move = None;
+        for chunk in &chunks {
+            let line = chunk.strip_suffix('\n').unwrap();
+            assert!(!line.contains('\n'));
+            let EngineOutput::BestMove(best_move) = serde_json::from_str(line).unwrap() else {
+                panic!("Expected a best move, got {}", line);
+            };
+            assert_eq!(best_move.original_str.as_deref(), Some(fen));
+            last_move = Some(best_move);
+        }
+        assert!(last_move.unwrap().next_state.board.get_winner().is_some());
+    }
+}
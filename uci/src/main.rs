@@ -4,116 +4,392 @@ use std::{
     time::{Duration, Instant},
 };
 
+use clap::{Parser, Subcommand};
 use santorini_core::{
-    board::FullGameState,
-    engine::EngineThreadWrapper,
-    search::BestSearchResult,
+    board::{FullGameState, PositionValidation},
+    consistency_checker::{consistency_check_catching_panics, consistency_check_deep},
+    engine::{EngineThreadWrapper, HeartbeatCallback},
+    fen::{game_state_to_fen, parse_fen_unvalidated, validate_fen},
+    logging::LogArgs,
+    matchup::{MatchupPolicy, load_matchup_policy, set_matchup_policy},
+    pretty_board::get_acting_player,
+    prove::prove_win,
+    refute::{parse_move_string, refute_move},
+    search::{BestMoveTrigger, BestSearchResult, RootMoveFilter},
+    search_terminators::{SearchHeartbeat, SearchLimits},
+    time_manager::TimeControl,
     uci_types::{
-        BestMoveMeta, BestMoveOutput, EngineOutput, NextMovesOutput, NextStateOutput, StartedOutput,
+        BestMoveMeta, BestMoveOutput, EngineOutput, InfoOutput, NextMovesOutput, ProofOutput,
+        RefutationOutput, StartedOutput, ValidationOutput, engine_version,
     },
-    utils::{find_action_path, timestamp_string},
+    utils::{find_action_path, split_args, split_first_arg},
+    win_reason::get_win_reason,
 };
 
+mod analyze;
+mod play;
+
+/// Runs the json line protocol on stdin/stdout when no subcommand is given
+#[derive(Debug, Parser)]
+struct UciArgs {
+    #[command(subcommand)]
+    command: Option<UciCommand>,
+
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum UciCommand {
+    /// Play against the engine from the terminal
+    Play(play::PlayArgs),
+    /// Search every FEN in a file for a fixed node count, and write the results to a CSV
+    AnalyzeFile(analyze::AnalyzeFileArgs),
+}
+
 fn try_emit_message(message: &EngineOutput) {
     match serde_json::to_string(message) {
         Ok(json) => println!("{}", json),
-        Err(e) => eprintln!("Error serializing message: {}", e),
+        Err(e) => log::error!("Error serializing message: {}", e),
+    }
+}
+
+fn parse_limit<T: std::str::FromStr>(value: &str) -> Result<Option<T>, String> {
+    if value == "none" {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("Invalid limit value: {}", value))
+}
+
+/// Parses exactly N arguments from the rest of a command line, or explains the expected usage
+fn expect_args<const N: usize>(rest: &str, usage: &str) -> Result<[String; N], String> {
+    split_args(rest)?.try_into().map_err(|_| usage.to_owned())
+}
+
+/// Node budget for refute commands, unless max_nodes is set
+const DEFAULT_REFUTE_NODES: usize = 1_000_000;
+
+/// Most positions checkdeep will check, since every ply multiplies the work
+const CHECKDEEP_MAX_POSITIONS: usize = 20_000;
+
+/// Arguments of the go command. Anything else after searchmoves or excludemoves is a move
+const GO_ARGUMENT_NAMES: [&str; 7] = [
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "nodes",
+    "searchmoves",
+    "excludemoves",
+];
+
+fn best_move_output(
+    original_str: Option<String>,
+    start_state: &FullGameState,
+    best_move: BestSearchResult,
+    elapsed: Duration,
+) -> EngineOutput {
+    // An unresolvable path is an engine bug, but the move itself is still legal, so report it
+    // without actions rather than taking the game down
+    let action_path = find_action_path(start_state, &best_move.child_state).unwrap_or_else(|err| {
+        log::warn!(
+            "couldn't resolve actions for best move {}: {}",
+            best_move.action_str,
+            err
+        );
+        Vec::new()
+    });
+
+    let nodes_per_second = best_move.nodes_per_second(elapsed.as_secs_f32());
+    let tt_hit_rate = best_move.tt_hit_rate();
+
+    EngineOutput::BestMove(BestMoveOutput {
+        original_str,
+        start_state: start_state.clone(),
+        next_state: best_move.child_state.clone(),
+        trigger: best_move.trigger,
+        meta: BestMoveMeta {
+            score: best_move.score,
+            calculated_depth: best_move.depth,
+            nodes_visited: Some(best_move.nodes_visited),
+            elapsed_seconds: elapsed.as_secs_f32(),
+            nodes_per_second,
+            seldepth: Some(best_move.seldepth),
+            tt_hit_rate,
+            actions: action_path,
+            action_str: Some(best_move.action_str),
+            win_reason: get_win_reason(start_state, &best_move.child_state),
+            god_data: best_move.child_state.god_data_items(),
+        },
+    })
+}
+
+/// Emits an info message for every search heartbeat. Disabled by `setoption quiet true`
+fn heartbeat_callback() -> HeartbeatCallback {
+    Arc::new(|state: &FullGameState, heartbeat: SearchHeartbeat| {
+        try_emit_message(&EngineOutput::Info(InfoOutput {
+            original_str: Some(game_state_to_fen(state)),
+            start_state: state.clone(),
+            depth: heartbeat.depth,
+            nodes_visited: heartbeat.nodes_visited,
+            nodes_per_second: heartbeat.nodes_per_second,
+            elapsed_seconds: heartbeat.elapsed.as_secs_f32(),
+        }))
+    })
+}
+
+/// Stops any other search and starts searching `fen`, emitting every new best move
+/// Parses a position to search. Positions that can't be searched are also reported with a
+/// `validation` message, so that callers get every issue rather than just an error in the log
+fn parse_search_position(fen: &str) -> Result<FullGameState, String> {
+    let (state, validation) = match parse_fen_unvalidated(fen) {
+        Ok(state) => {
+            let validation = state.position_validation();
+            (Some(state), validation)
+        }
+        Err(err) => (None, PositionValidation::unrepresentable(err)),
+    };
+    match state {
+        Some(state) if validation.playable => Ok(state),
+        _ => {
+            let tier = match validation.representable {
+                true => "not playable",
+                false => "not representable",
+            };
+            let err = format!("Position is {}: {}", tier, validation.issues.join("; "));
+            try_emit_message(&EngineOutput::Validation(ValidationOutput {
+                original_str: Some(fen.to_owned()),
+                validation,
+            }));
+            Err(err)
+        }
     }
 }
 
+fn start_search(
+    engine: &mut EngineThreadWrapper,
+    fen: String,
+    limits: SearchLimits,
+    root_move_filter: Option<RootMoveFilter>,
+) -> Result<(), String> {
+    log::info!("search position: {}", fen);
+
+    let state = parse_search_position(&fen)?;
+
+    if state.board.get_winner().is_some() {
+        return Err("Cannot search for position in terminal state".to_owned());
+    }
+
+    let _ = engine.stop();
+    let start_time = Instant::now();
+    let state_2 = state.clone();
+
+    let callback = Arc::new(move |new_best_move: BestSearchResult| {
+        log::debug!("best move: {:?}", new_best_move);
+        try_emit_message(&best_move_output(
+            Some(fen.clone()),
+            &state_2,
+            new_best_move,
+            start_time.elapsed(),
+        ));
+    });
+    engine.start_filtered_search(&state, Some(callback), Some(limits), root_move_filter)?;
+    Ok(())
+}
+
 fn handle_command(
     engine: &mut EngineThreadWrapper,
+    limits: &mut SearchLimits,
     raw_cmd: &str,
 ) -> Result<Option<String>, String> {
-    let mut parts: Vec<String> = raw_cmd
-        .trim()
-        .split_whitespace()
-        .map(&str::to_owned)
-        .collect();
-    if parts.is_empty() {
+    // Arguments are parsed per command, so that commands taking free text can keep it raw
+    let Some((command, rest)) = split_first_arg(raw_cmd)? else {
         thread::sleep(Duration::from_millis(100));
         return Err("Command was empty".to_owned());
-    }
-    let command = parts.remove(0);
+    };
 
-    match &command as &str {
+    match command.as_str() {
         "quit" => {
             std::process::exit(0);
         }
         "ping" => Ok(Some("pong".to_owned())),
+        "version" => Ok(Some(engine_version())),
         "stop" => {
-            eprintln!("{}, stop", timestamp_string());
+            log::info!("stop");
+
+            let Some((state, elapsed)) = engine
+                .active_search()
+                .map(|(state, elapsed)| (state.clone(), elapsed))
+            else {
+                return Ok(None);
+            };
+            let Some(mut best_move) = engine.stop().map_err(|err| err.to_string())? else {
+                return Ok(None);
+            };
+            best_move.trigger = BestMoveTrigger::StopFlag;
+
+            let output =
+                best_move_output(Some(game_state_to_fen(&state)), &state, best_move, elapsed);
+            serde_json::to_string(&output)
+                .map(Some)
+                .map_err(|e| format!("Error serializing message: {}", e))
+        }
+        "setoption" => {
+            let [name, value] = expect_args(
+                rest,
+                "setoption should be followed by an option name and value",
+            )?;
 
-            match engine.stop() {
-                Ok(best_move) => Err(format!(
-                    "Stopping with best move: {:?}",
-                    best_move.child_state
-                )),
-                Err(e) => Err(e),
+            if name == "matchup_policy" {
+                let policy = match value.as_str() {
+                    "none" => MatchupPolicy::default(),
+                    path => load_matchup_policy(path)?,
+                };
+                set_matchup_policy(policy);
+                log::info!("matchup policy: {}", value);
+                return Ok(None);
+            }
+            if name == "own_book" {
+                let use_placement_book: bool = value
+                    .parse()
+                    .map_err(|_| format!("Invalid own_book value: {}", value))?;
+                let mut params = engine.search_params();
+                params.use_placement_book = use_placement_book;
+                engine.set_search_params(params);
+                log::info!("own book: {}", use_placement_book);
+                return Ok(None);
+            }
+            if name == "param" {
+                let (param_name, param_value) = value
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected param value as name=value: {}", value))?;
+                let mut params = engine.search_params();
+                params.set(param_name, param_value)?;
+                engine.set_search_params(params);
+                log::info!("search params: {:?}", params);
+                return Ok(None);
             }
+            if name == "quiet" {
+                let is_quiet: bool = value
+                    .parse()
+                    .map_err(|_| format!("Invalid quiet value: {}", value))?;
+                engine.set_heartbeat_callback((!is_quiet).then(heartbeat_callback));
+                log::info!("quiet: {}", is_quiet);
+                return Ok(None);
+            }
+            if name == "best_move_interval_millis" {
+                let millis: u64 = value
+                    .parse()
+                    .map_err(|_| format!("Invalid best_move_interval_millis value: {}", value))?;
+                engine.set_best_move_min_interval(Duration::from_millis(millis));
+                log::info!("best move interval: {}ms", millis);
+                return Ok(None);
+            }
+
+            match name.as_str() {
+                "max_depth" => limits.max_depth = parse_limit(&value)?,
+                "max_nodes" => limits.max_nodes = parse_limit(&value)?,
+                "max_millis" => limits.max_millis = parse_limit(&value)?,
+                "min_nodes" => limits.min_nodes = parse_limit(&value)?,
+                name => return Err(format!("Unknown option: {}", name)),
+            }
+            log::info!("search limits: {:?}", limits);
+            Ok(None)
+        }
+        "validate" => {
+            let [fen] = expect_args(rest, "validate should be followed by a single FEN string")?;
+            let output = EngineOutput::Validation(ValidationOutput {
+                validation: validate_fen(&fen),
+                original_str: Some(fen),
+            });
+            serde_json::to_string(&output)
+                .map(Some)
+                .map_err(|e| format!("{:?}", e))
         }
         "set_position" => {
-            if parts.len() != 1 {
-                return Err("set_position should be followed by a single FEN string".to_owned());
-            }
+            let [fen] = expect_args(
+                rest,
+                "set_position should be followed by a single FEN string",
+            )?;
 
-            let fen = parts.remove(0);
-            eprintln!("{}, set_position: {:?}", timestamp_string(), fen);
+            start_search(engine, fen, *limits, None)?;
+            Ok(None)
+        }
+        "go" => {
+            let mut args = split_args(rest)?.into_iter().peekable();
+            let fen = args.next().ok_or(
+                "go should be followed by a FEN string and pairs of clock or node arguments",
+            )?;
+            let state = parse_search_position(&fen)?;
+            let player = get_acting_player(&state)?;
 
-            let state =
-                FullGameState::try_from(&fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
+            let mut clocks = [TimeControl::default(); 2];
+            let mut has_clock = false;
+            let mut go_limits = *limits;
+            let mut root_move_filter = None;
+            while let Some(name) = args.next() {
+                if name == "searchmoves" || name == "excludemoves" {
+                    let mut moves = Vec::new();
+                    while let Some(move_str) =
+                        args.next_if(|arg| !GO_ARGUMENT_NAMES.contains(&arg.as_str()))
+                    {
+                        moves.push(parse_move_string(&state, &move_str)?.1);
+                    }
+                    if moves.is_empty() {
+                        return Err(format!("{} should be followed by at least one move", name));
+                    }
+                    root_move_filter = Some(match name.as_str() {
+                        "searchmoves" => RootMoveFilter::Allow(moves),
+                        _ => RootMoveFilter::Deny(moves),
+                    });
+                    continue;
+                }
 
-            if state.board.get_winner().is_some() {
-                return Err("Cannot search for position in terminal state".to_owned());
-            }
-
-            let _ = engine.stop();
-            let start_time = Instant::now();
-            let state_2 = state.clone();
-
-            let callback = Arc::new(move |new_best_move: BestSearchResult| {
-                eprintln!("best move: {:?}", new_best_move);
-                let Some(action_path) = find_action_path(&state_2, &new_best_move.child_state)
-                else {
-                    eprintln!("from move:");
-                    state_2.print_to_console();
-                    eprintln!("to move:");
-                    new_best_move.child_state.print_to_console();
-
-                    panic!(
-                        "Found new best move but couldn't resolve path: {}: {:?} -> {:?}",
-                        new_best_move.action_str,
-                        state_2,
-                        new_best_move.child_state
-                    );
-                };
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{} should be followed by a value", name))?;
+                if name == "nodes" {
+                    let nodes: usize = value
+                        .parse()
+                        .map_err(|_| format!("Invalid nodes value: {}", value))?;
+                    go_limits = go_limits.with_max_nodes(nodes);
+                    continue;
+                }
 
-                let output = EngineOutput::BestMove(BestMoveOutput {
-                    original_str: Some(fen.clone()),
-                    start_state: state_2.clone(),
-                    next_state: new_best_move.child_state.clone(),
-                    trigger: new_best_move.trigger,
-                    meta: BestMoveMeta {
-                        score: new_best_move.score,
-                        calculated_depth: new_best_move.depth,
-                        nodes_visited: Some(new_best_move.nodes_visited),
-                        elapsed_seconds: start_time.elapsed().as_secs_f32(),
-                        actions: action_path,
-                        action_str: Some(new_best_move.action_str),
-                    },
-                });
-
-                try_emit_message(&output);
-            });
-            engine.start_search(&state, Some(callback))?;
+                has_clock = true;
+                let millis: u64 = value
+                    .parse()
+                    .map_err(|_| format!("Invalid millis value: {}", value))?;
+                match name.as_str() {
+                    "wtime" => clocks[0].remaining_millis = millis,
+                    "btime" => clocks[1].remaining_millis = millis,
+                    "winc" => clocks[0].increment_millis = millis,
+                    "binc" => clocks[1].increment_millis = millis,
+                    name => return Err(format!("Unknown clock argument: {}", name)),
+                }
+            }
+
+            if let Some(filter) = &root_move_filter
+                && !state
+                    .get_complete_next_states_with_actions()
+                    .iter()
+                    .any(|(_, action)| filter.allows(*action))
+            {
+                return Err("Every move is excluded".to_owned());
+            }
+
+            if has_clock {
+                go_limits = go_limits.with_time_control(clocks[player as usize]);
+            }
+
+            start_search(engine, fen, go_limits, root_move_filter)?;
             Ok(None)
         }
         "next_moves" => {
-            if parts.len() != 1 {
-                return Err("next_moves should be followed by a single FEN string".to_owned());
-            }
-
-            let fen = parts.remove(0);
+            let [fen] = expect_args(rest, "next_moves should be followed by a single FEN string")?;
 
             let state: FullGameState =
                 FullGameState::try_from(&fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
@@ -122,29 +398,116 @@ fn handle_command(
                 return Err("Cannot look for next moves from terminal state".to_owned());
             }
 
-            let child_states = state.get_next_states_interactive();
+            let output = EngineOutput::NextMoves(NextMovesOutput::new(Some(fen), state));
 
-            let output = EngineOutput::NextMoves(NextMovesOutput {
+            serde_json::to_string(&output)
+                .map(|v| Some(v))
+                .map_err(|e| format!("{:?}", e))
+        }
+        "refute" => {
+            let usage = "refute should be followed by a FEN string and a move";
+            // Some moves contain spaces, like double moves, so the move is kept raw
+            let (fen, move_str) = split_first_arg(rest)?.ok_or(usage)?;
+            let move_str = move_str.trim_end();
+            if move_str.is_empty() {
+                return Err(usage.to_owned());
+            }
+
+            let state: FullGameState =
+                FullGameState::try_from(&fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
+            let nodes = limits.max_nodes.unwrap_or(DEFAULT_REFUTE_NODES);
+            let refutation = refute_move(&state, move_str, nodes)?;
+
+            let output = EngineOutput::Refutation(RefutationOutput {
                 original_str: Some(fen),
                 start_state: state,
-                next_states: child_states
-                    .into_iter()
-                    .map(|full_choice| NextStateOutput {
-                        next_state: full_choice.state,
-                        actions: full_choice.actions,
-                    })
-                    .collect(),
+                refutation,
             });
 
             serde_json::to_string(&output)
                 .map(|v| Some(v))
                 .map_err(|e| format!("{:?}", e))
         }
+        "prove" => {
+            let [fen, depth] =
+                expect_args(rest, "prove should be followed by a FEN string and a depth")?;
+            let max_depth: usize = depth
+                .parse()
+                .map_err(|_| format!("Invalid depth: {}", depth))?;
+
+            let state: FullGameState =
+                FullGameState::try_from(&fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
+            let proven = prove_win(&state, max_depth);
+
+            let output = EngineOutput::Proof(ProofOutput {
+                original_str: Some(fen),
+                start_state: state,
+                max_depth,
+                proven,
+            });
+
+            serde_json::to_string(&output)
+                .map(|v| Some(v))
+                .map_err(|e| format!("{:?}", e))
+        }
+        "check" => {
+            let [fen] = expect_args(rest, "check should be followed by a single FEN string")?;
+            let state: FullGameState =
+                FullGameState::try_from(&fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
+
+            match consistency_check_catching_panics(&state) {
+                Ok(()) => Ok(Some("ok".to_owned())),
+                Err(errors) => Ok(Some(errors.join("\n"))),
+            }
+        }
+        "checkdeep" => {
+            let [fen, plies] = expect_args(
+                rest,
+                "checkdeep should be followed by a FEN string and a ply count",
+            )?;
+            let plies: usize = plies
+                .parse()
+                .map_err(|_| format!("Invalid ply count: {}", plies))?;
+            let state: FullGameState =
+                FullGameState::try_from(&fen).map_err(|e| format!("Error parsing FEN: {}", e))?;
+
+            let report = consistency_check_deep(&state, plies, CHECKDEEP_MAX_POSITIONS);
+            log::info!(
+                "checkdeep: checked {} positions{}",
+                report.positions_checked,
+                if report.truncated {
+                    ", stopped at the position cap"
+                } else {
+                    ""
+                }
+            );
+
+            if report.errors.is_empty() {
+                Ok(Some("ok".to_owned()))
+            } else {
+                Ok(Some(report.errors.join("\n")))
+            }
+        }
         _ => Err(format!("Skipping unknown command: {}", raw_cmd)),
     }
 }
 
 fn main() {
+    let args = UciArgs::parse();
+    // Logs go to stderr, so stdout only ever carries protocol messages
+    args.log.init();
+    if let Some(command) = args.command {
+        let result = match command {
+            UciCommand::Play(play_args) => play::run_play(play_args),
+            UciCommand::AnalyzeFile(analyze_args) => analyze::run_analyze_file(analyze_args),
+        };
+        if let Err(err) = result {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let (cli_command_sender, cli_command_receiver) = mpsc::channel();
 
     let _io_thread = thread::spawn(move || {
@@ -156,17 +519,19 @@ fn main() {
     });
 
     let mut engine = EngineThreadWrapper::new();
+    engine.set_heartbeat_callback(Some(heartbeat_callback()));
+    let mut limits = SearchLimits::default();
 
-    try_emit_message(&EngineOutput::Started(StartedOutput {}));
+    try_emit_message(&EngineOutput::Started(StartedOutput::current()));
 
     loop {
         let raw_cmd = cli_command_receiver.recv().unwrap();
         if raw_cmd.trim().is_empty() {
-            println!("empty command");
+            log::warn!("empty command");
             thread::sleep(Duration::from_millis(10));
             continue;
         }
-        match handle_command(&mut engine, &raw_cmd) {
+        match handle_command(&mut engine, &mut limits, &raw_cmd) {
             Ok(Some(response)) => {
                 println!("{}", response);
             }
@@ -174,7 +539,7 @@ fn main() {
                 // No response to print
             }
             Err(err) => {
-                eprintln!("Error: {}", err.trim());
+                log::error!("{}", err.trim());
             }
         }
     }
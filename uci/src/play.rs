@@ -0,0 +1,371 @@
+use std::io::{BufRead, Write};
+
+use santorini_core::{
+    board::{FullGameState, TerminalResult},
+    engine::EngineThreadWrapper,
+    fen::{game_state_to_fen, parse_fen},
+    gods::{GameStateWithAction, GodName, MoveWorkerMeta, PartialAction},
+    matchup::{Matchup, matchup_banned_reason},
+    player::Player,
+    pretty_board::get_acting_player,
+    square::Square,
+    uci_types::GameResult,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum HumanSide {
+    One,
+    Two,
+    /// Play both sides, without the engine answering. Handy for manually testing gods
+    Both,
+}
+
+impl HumanSide {
+    fn is_human(self, player: Player) -> bool {
+        match self {
+            HumanSide::One => player == Player::One,
+            HumanSide::Two => player == Player::Two,
+            HumanSide::Both => true,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PlayArgs {
+    /// God for player 1
+    #[arg(long, default_value = "mortal")]
+    p1: GodName,
+
+    /// God for player 2
+    #[arg(long, default_value = "mortal")]
+    p2: GodName,
+
+    /// Which side the human plays
+    #[arg(short = 's', long, value_enum, default_value_t = HumanSide::One)]
+    side: HumanSide,
+
+    /// Engine thinking time per move, in seconds. Also used for hints
+    #[arg(short = 't', long, default_value_t = 3.0)]
+    movetime: f32,
+
+    /// Start from this position instead of an empty board. Overrides the gods
+    #[arg(long)]
+    fen: Option<String>,
+}
+
+const HELP: &str = "Enter the number or square of an action. Other commands:
+  back  - undo the last partial action of this turn
+  end   - end the turn, when optional actions remain
+  undo  - take back your last move
+  hint  - ask the engine for a move
+  fen   - print the current position
+  quit  - exit";
+
+/// Result of narrowing the legal moves by the partial actions chosen so far
+struct NarrowedOptions {
+    next_actions: Vec<PartialAction>,
+    // Set when the chosen actions already make up a complete move
+    completed_state: Option<FullGameState>,
+}
+
+fn narrow_options(
+    next_states: &[GameStateWithAction],
+    current_actions: &[PartialAction],
+) -> NarrowedOptions {
+    let mut result = NarrowedOptions {
+        next_actions: Vec::new(),
+        completed_state: None,
+    };
+
+    for next_state in next_states {
+        if !next_state.actions.starts_with(current_actions) {
+            continue;
+        }
+
+        match next_state.actions.get(current_actions.len()) {
+            Some(next_action) => {
+                if !result.next_actions.contains(next_action) {
+                    result.next_actions.push(*next_action);
+                }
+            }
+            None => result.completed_state = Some(next_state.state.clone()),
+        }
+    }
+
+    result
+}
+
+fn action_square(action: &PartialAction) -> Option<Square> {
+    match action {
+        PartialAction::PlaceWorker(x)
+        | PartialAction::SetFemaleWorker(x)
+        | PartialAction::SelectWorker(x)
+        | PartialAction::Build(x)
+        | PartialAction::SetTalusPosition(x)
+        | PartialAction::Dome(x)
+        | PartialAction::HeroPower(x)
+        | PartialAction::Destroy(x) => Some(*x),
+        PartialAction::MoveWorker(data) => Some(data.dest),
+        PartialAction::ForceOpponentWorker(from, _) => Some(*from),
+        PartialAction::SetWindDirection(_) | PartialAction::NoMoves | PartialAction::EndTurn => {
+            None
+        }
+    }
+}
+
+fn describe_action(action: &PartialAction) -> String {
+    match action {
+        PartialAction::PlaceWorker(x) => format!("place worker {x}"),
+        PartialAction::SetFemaleWorker(x) => format!("pick female worker {x}"),
+        PartialAction::SelectWorker(x) => format!("select worker {x}"),
+        PartialAction::MoveWorker(data) => match data.meta {
+            None => format!("move to {}", data.dest),
+            Some(MoveWorkerMeta::MoveEnemyWorker(push)) => {
+                format!("move to {} (enemy {} -> {})", data.dest, push.from, push.to)
+            }
+            Some(MoveWorkerMeta::KillEnemyWorker(kill)) => {
+                format!("move to {} (kill {})", data.dest, kill.square)
+            }
+            Some(MoveWorkerMeta::IsFWorker) => format!("move female worker to {}", data.dest),
+        },
+        PartialAction::ForceOpponentWorker(from, to) => format!("force enemy {from} -> {to}"),
+        PartialAction::Build(x) => format!("build {x}"),
+        PartialAction::Dome(x) => format!("dome {x}"),
+        PartialAction::Destroy(x) => format!("destroy {x}"),
+        PartialAction::SetTalusPosition(x) => format!("place talus {x}"),
+        PartialAction::HeroPower(x) => format!("use power {x}"),
+        PartialAction::SetWindDirection(Some(direction)) => format!("set wind {direction}"),
+        PartialAction::SetWindDirection(None) => "set no wind".to_owned(),
+        PartialAction::NoMoves => "no moves".to_owned(),
+        PartialAction::EndTurn => "end turn".to_owned(),
+    }
+}
+
+fn prompt(lines: &mut impl Iterator<Item = std::io::Result<String>>) -> Option<String> {
+    eprint!("> ");
+    let _ = std::io::stderr().flush();
+    lines.next()?.ok().map(|line| line.trim().to_lowercase())
+}
+
+struct PlaySession {
+    args: PlayArgs,
+    engine: EngineThreadWrapper,
+    history: Vec<FullGameState>,
+    state: FullGameState,
+}
+
+impl PlaySession {
+    fn is_human_turn(&self) -> bool {
+        let player = get_acting_player(&self.state).unwrap_or(self.state.board.current_player);
+        self.args.side.is_human(player)
+    }
+
+    fn push_state(&mut self, next_state: FullGameState) {
+        let prev_state = std::mem::replace(&mut self.state, next_state);
+        if let Some(TerminalResult::Smothered(loser)) = self.state.get_terminal_result() {
+            eprintln!("Player {:?} has no moves", loser);
+        }
+        if let Some(result) = GameResult::from_states(Some(&prev_state), &self.state) {
+            eprintln!("{}", result);
+        }
+        self.history.push(prev_state);
+    }
+
+    /// Takes back moves until it's a human's turn again, before their last move
+    fn undo(&mut self) {
+        if self.history.is_empty() {
+            eprintln!("Nothing to undo");
+            return;
+        }
+
+        while let Some(prev_state) = self.history.pop() {
+            self.state = prev_state;
+            if self.is_human_turn() {
+                return;
+            }
+        }
+    }
+
+    fn hint(&mut self) {
+        match self
+            .engine
+            .search_for_duration(&self.state, self.args.movetime)
+        {
+            Ok(best_move) => eprintln!(
+                "Hint: {} (score: {}, depth: {})",
+                best_move.action_str, best_move.score, best_move.depth
+            ),
+            Err(err) => eprintln!("Error: {}", err),
+        }
+    }
+
+    fn engine_turn(&mut self) {
+        eprintln!("Engine thinking for {}s...", self.args.movetime);
+        match self
+            .engine
+            .search_for_duration(&self.state, self.args.movetime)
+        {
+            Ok(best_move) => {
+                eprintln!(
+                    "Engine plays: {} (score: {}, depth: {})",
+                    best_move.action_str, best_move.score, best_move.depth
+                );
+                self.push_state(best_move.child_state);
+            }
+            Err(err) => {
+                eprintln!("Engine failed to move: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Returns false once the player asks to quit
+    fn human_turn(&mut self, lines: &mut impl Iterator<Item = std::io::Result<String>>) -> bool {
+        let next_states = self.state.get_next_states_interactive();
+        let mut current_actions: Vec<PartialAction> = Vec::new();
+
+        loop {
+            let options = narrow_options(&next_states, &current_actions);
+            if options.next_actions.is_empty() {
+                if let Some(completed_state) = options.completed_state {
+                    self.push_state(completed_state);
+                    return true;
+                }
+            }
+
+            if !current_actions.is_empty() {
+                let chosen: Vec<String> = current_actions.iter().map(describe_action).collect();
+                eprintln!("Chosen: {}", chosen.join(", "));
+            }
+            for (i, action) in options.next_actions.iter().enumerate() {
+                eprintln!("  {}: {}", i + 1, describe_action(action));
+            }
+            if options.completed_state.is_some() {
+                eprintln!("  end: end turn");
+            }
+
+            let Some(input) = prompt(lines) else {
+                return false;
+            };
+
+            match input.as_str() {
+                "" => continue,
+                "quit" | "exit" => return false,
+                "help" => eprintln!("{}", HELP),
+                "fen" => eprintln!("{}", game_state_to_fen(&self.state)),
+                "hint" => self.hint(),
+                "back" => {
+                    if current_actions.pop().is_none() {
+                        eprintln!("No partial actions to take back");
+                    }
+                }
+                "undo" => {
+                    self.undo();
+                    return true;
+                }
+                "end" => match options.completed_state {
+                    Some(completed_state) => {
+                        self.push_state(completed_state);
+                        return true;
+                    }
+                    None => eprintln!("The turn isn't complete yet"),
+                },
+                _ => {
+                    let action = if let Ok(idx) = input.parse::<usize>() {
+                        options.next_actions.get(idx.wrapping_sub(1)).copied()
+                    } else if let Ok(square) = Square::from_algebraic(&input) {
+                        let mut matches = options
+                            .next_actions
+                            .iter()
+                            .filter(|a| action_square(a) == Some(square));
+                        match (matches.next(), matches.next()) {
+                            (Some(action), None) => Some(*action),
+                            (Some(_), Some(_)) => {
+                                eprintln!("{} is ambiguous, pick by number", square);
+                                continue;
+                            }
+                            _ => None,
+                        }
+                    } else {
+                        None
+                    };
+
+                    match action {
+                        Some(action) => current_actions.push(action),
+                        None => eprintln!("Unknown action: '{}'. Type help for commands", input),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handles commands once the game is over. Returns false once the player asks to quit
+    fn game_over_turn(
+        &mut self,
+        lines: &mut impl Iterator<Item = std::io::Result<String>>,
+    ) -> bool {
+        eprintln!("Game over. Type undo to take back a move, or quit to exit");
+        let Some(input) = prompt(lines) else {
+            return false;
+        };
+        match input.as_str() {
+            "quit" | "exit" => return false,
+            "undo" => self.undo(),
+            "fen" => eprintln!("{}", game_state_to_fen(&self.state)),
+            _ => {}
+        }
+        true
+    }
+}
+
+pub fn run_play(args: PlayArgs) -> Result<(), String> {
+    let state = match &args.fen {
+        Some(fen) => parse_fen(fen)?,
+        None => {
+            let matchup = Matchup::new(args.p1, args.p2);
+            if let Some(reason) = matchup_banned_reason(&matchup) {
+                return Err(format!("Matchup {} is banned ({:?})", matchup, reason));
+            }
+            FullGameState::new_for_matchup(&matchup)
+        }
+    };
+
+    eprintln!(
+        "{} vs {}. You are playing {:?}. Type help for commands",
+        state.gods[0], state.gods[1], args.side
+    );
+
+    let mut session = PlaySession {
+        args,
+        engine: EngineThreadWrapper::new(),
+        history: Vec::new(),
+        state,
+    };
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut last_printed: Option<FullGameState> = None;
+
+    loop {
+        if last_printed.as_ref() != Some(&session.state) {
+            session.state.print_to_console();
+            last_printed = Some(session.state.clone());
+        }
+
+        let should_continue = if session.state.get_terminal_result().is_some() {
+            session.game_over_turn(&mut lines)
+        } else if session.is_human_turn() {
+            session.human_turn(&mut lines)
+        } else {
+            session.engine_turn();
+            true
+        };
+
+        if !should_continue {
+            break;
+        }
+    }
+
+    session.engine.end();
+    Ok(())
+}
@@ -0,0 +1,418 @@
+use std::{
+    fmt,
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use battler::{EngineSubprocess, SpawnConfig, prepare_subprocess};
+use eframe::egui::{Color32, mutex::Mutex};
+use santorini_core::{
+    board::FullGameState,
+    engine::{
+        AnalysisCache, EachMoveCallback, EngineThreadWrapper, SearchFailure, SearchFailureCallback,
+        TimedSearchResult,
+    },
+    fen::game_state_to_fen,
+    gods::generic::{GenericMove, NULL_MOVE_DATA},
+    search::BestSearchResult,
+    search_terminators::SearchLimits,
+    uci_types::{BestMoveOutput, EngineOutput},
+};
+
+/// Storage key for the external engine path, if analysis runs in a subprocess
+pub const ENGINE_PATH_STORAGE_KEY: &str = "engine_subprocess_path";
+
+/// Name of the log file, under battler's log directory, that the engine subprocess writes to
+const SUBPROCESS_LOG_PATH: &str = "ui-engine.log";
+
+/// Minimum time between attempts to restart a crashed engine subprocess
+pub const RESTART_BACKOFF: Duration = Duration::from_secs(2);
+
+/// How the backend analyzing the current position is doing, for the settings panel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackendStatus {
+    /// Searching, or waiting for a position to search
+    Ready,
+    /// Working again, after the engine crashed and was restarted this many times
+    Restarted(usize),
+    /// The engine isn't running, and will be restarted when the backoff allows
+    Failed(String),
+}
+
+impl BackendStatus {
+    pub fn color(&self) -> Color32 {
+        match self {
+            BackendStatus::Ready => Color32::GREEN,
+            BackendStatus::Restarted(_) => Color32::YELLOW,
+            BackendStatus::Failed(_) => Color32::RED,
+        }
+    }
+}
+
+impl fmt::Display for BackendStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendStatus::Ready => write!(f, "Ready"),
+            BackendStatus::Restarted(count) => write!(f, "Restarted ({}x)", count),
+            BackendStatus::Failed(err) => write!(f, "Failed: {}", err),
+        }
+    }
+}
+
+/// Where the main analysis of the current position runs
+pub trait AnalysisBackend {
+    /// Makes sure `state` is being analyzed with `limits`, only restarting the search if either
+    /// changed. See `EngineThreadWrapper::ensure_searching`
+    fn ensure_searching(
+        &mut self,
+        state: &FullGameState,
+        each_move_callback: Option<EachMoveCallback>,
+        limits: Option<SearchLimits>,
+    ) -> Result<(), String>;
+
+    /// Results found so far for `state`, if it was analyzed recently
+    fn cached_analysis(&self, state: &FullGameState) -> Vec<TimedSearchResult>;
+
+    /// Stops the current search, if there is one
+    fn stop(&mut self) -> Result<(), String>;
+
+    /// Checks on the backend, restarting it if it crashed
+    fn status(&mut self) -> BackendStatus;
+
+    /// A search that failed since the last call, if any. The backend keeps working afterwards
+    fn take_search_failure(&mut self) -> Option<SearchFailure>;
+
+    /// Called whenever a failure becomes available from `take_search_failure`
+    fn set_failure_callback(&mut self, failure_callback: Option<SearchFailureCallback>);
+
+    /// Shuts the backend down for good
+    fn end(&mut self);
+}
+
+impl AnalysisBackend for EngineThreadWrapper {
+    fn ensure_searching(
+        &mut self,
+        state: &FullGameState,
+        each_move_callback: Option<EachMoveCallback>,
+        limits: Option<SearchLimits>,
+    ) -> Result<(), String> {
+        EngineThreadWrapper::ensure_searching(self, state, each_move_callback, limits)
+    }
+
+    fn cached_analysis(&self, state: &FullGameState) -> Vec<TimedSearchResult> {
+        EngineThreadWrapper::cached_analysis(self, state)
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        EngineThreadWrapper::stop(self)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+
+    fn status(&mut self) -> BackendStatus {
+        BackendStatus::Ready
+    }
+
+    fn take_search_failure(&mut self) -> Option<SearchFailure> {
+        EngineThreadWrapper::take_search_failure(self)
+    }
+
+    fn set_failure_callback(&mut self, failure_callback: Option<SearchFailureCallback>) {
+        EngineThreadWrapper::set_failure_callback(self, failure_callback);
+    }
+
+    fn end(&mut self) {
+        EngineThreadWrapper::end(self);
+    }
+}
+
+/// The search the subprocess was last asked to run
+struct ActiveSearch {
+    state: FullGameState,
+    limits: SearchLimits,
+    each_move_callback: Option<EachMoveCallback>,
+    /// How long the position had already been analyzed when this search started
+    analyzed_duration: Duration,
+}
+
+#[derive(Default)]
+struct SubprocessShared {
+    active_search: Option<ActiveSearch>,
+    analysis_cache: AnalysisCache,
+    search_failure: Option<SearchFailure>,
+    failure_callback: Option<SearchFailureCallback>,
+}
+
+/// Converts a best move reported over the battler protocol back into a search result
+pub(crate) fn best_move_to_search_result(output: BestMoveOutput) -> BestSearchResult {
+    let action = output
+        .start_state
+        .get_all_next_states_with_actions()
+        .into_iter()
+        .find(|(child_state, _)| child_state == &output.next_state)
+        .map_or(GenericMove(NULL_MOVE_DATA), |(_, action)| action);
+
+    BestSearchResult {
+        child_state: output.next_state,
+        action,
+        action_str: output.meta.action_str.unwrap_or_default(),
+        score: output.meta.score,
+        depth: output.meta.calculated_depth,
+        nodes_visited: output.meta.nodes_visited.unwrap_or_default(),
+        seldepth: output.meta.seldepth.unwrap_or_default(),
+        tt_probes: 0,
+        tt_hits: 0,
+        suppressed_updates: 0,
+        trigger: output.trigger,
+        // The protocol only reports the move itself
+        pv: vec![action],
+    }
+}
+
+/// Records best moves for the active search as the subprocess reports them. Returns once the
+/// subprocess closes its output, reporting a failure if it was searching
+fn subprocess_reader_loop(receiver: mpsc::Receiver<String>, shared: Arc<Mutex<SubprocessShared>>) {
+    for line in receiver {
+        let Ok(EngineOutput::BestMove(output)) = serde_json::from_str(&line) else {
+            continue;
+        };
+
+        let mut shared_guard = shared.lock();
+        let shared = &mut *shared_guard;
+        let Some(active_search) = &shared.active_search else {
+            continue;
+        };
+        if active_search.state != output.start_state {
+            continue;
+        }
+
+        let elapsed = active_search.analyzed_duration
+            + Duration::from_secs_f32(output.meta.elapsed_seconds.max(0.0));
+        let each_move_callback = active_search.each_move_callback.clone();
+        let state = output.start_state.clone();
+        let result = best_move_to_search_result(output);
+        shared
+            .analysis_cache
+            .record(&state, result.clone(), elapsed);
+        drop(shared_guard);
+
+        if let Some(each_move_callback) = each_move_callback {
+            each_move_callback(result);
+        }
+    }
+
+    // Searches are cleared before the subprocess is shut down on purpose, so this one crashed.
+    // It's restarted the next time the backend is used, which the failure callback prompts
+    let mut shared = shared.lock();
+    let Some(active_search) = &shared.active_search else {
+        return;
+    };
+    let search_failure = SearchFailure {
+        message: "Engine exited".to_owned(),
+        state: active_search.state.clone(),
+    };
+    shared.search_failure = Some(search_failure.clone());
+    let failure_callback = shared.failure_callback.clone();
+    drop(shared);
+
+    if let Some(failure_callback) = failure_callback {
+        failure_callback(&search_failure);
+    }
+}
+
+/// Runs analysis in an external engine binary, talking to it over the battler JSON protocol.
+/// The engine is restarted if it crashes, and picks up the search it was running
+pub struct SubprocessBackend {
+    engine_path: PathBuf,
+    subprocess: Option<EngineSubprocess>,
+    shared: Arc<Mutex<SubprocessShared>>,
+    /// Limits the current subprocess was last configured with
+    sent_limits: Option<SearchLimits>,
+    has_started: bool,
+    restart_count: usize,
+    last_error: Option<String>,
+    last_start_attempt: Option<Instant>,
+}
+
+impl SubprocessBackend {
+    pub fn new(engine_path: PathBuf) -> Self {
+        let mut result = SubprocessBackend {
+            engine_path,
+            subprocess: None,
+            shared: Default::default(),
+            sent_limits: None,
+            has_started: false,
+            restart_count: 0,
+            last_error: None,
+            last_start_attempt: None,
+        };
+        result.ensure_running();
+        result
+    }
+
+    fn is_running(&mut self) -> bool {
+        self.subprocess
+            .as_mut()
+            .is_some_and(|subprocess| matches!(subprocess.child.try_wait(), Ok(None)))
+    }
+
+    /// Starts the subprocess if it isn't running, unless a recent attempt failed. Returns whether
+    /// it's running afterwards
+    fn ensure_running(&mut self) -> bool {
+        if self.is_running() {
+            return true;
+        }
+
+        if let Some(mut subprocess) = self.subprocess.take() {
+            subprocess.kill();
+            self.last_error = Some("Engine exited".to_owned());
+        }
+        if self
+            .last_start_attempt
+            .is_some_and(|at| at.elapsed() < RESTART_BACKOFF)
+        {
+            return false;
+        }
+        self.last_start_attempt = Some(Instant::now());
+
+        let mut subprocess =
+            match prepare_subprocess(&self.engine_path, &SpawnConfig::new(SUBPROCESS_LOG_PATH)) {
+                Ok(subprocess) => subprocess,
+                Err(err) => {
+                    self.last_error = Some(err.to_string());
+                    return false;
+                }
+            };
+        if self.has_started {
+            self.restart_count += 1;
+        }
+        self.has_started = true;
+        self.last_error = None;
+        self.sent_limits = None;
+
+        let (_, placeholder_receiver) = mpsc::channel();
+        let receiver = std::mem::replace(&mut subprocess.receiver, placeholder_receiver);
+        let shared = self.shared.clone();
+        thread::spawn(move || subprocess_reader_loop(receiver, shared));
+        self.subprocess = Some(subprocess);
+
+        // Pick up the search that was running when the previous subprocess crashed
+        let resumed_search = self
+            .shared
+            .lock()
+            .active_search
+            .as_ref()
+            .map(|active_search| (active_search.state.clone(), active_search.limits));
+        if let Some((state, limits)) = resumed_search
+            && let Err(err) = self.send_search(&state, limits)
+        {
+            self.last_error = Some(err);
+        }
+        true
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), String> {
+        let subprocess = self
+            .subprocess
+            .as_mut()
+            .ok_or_else(|| "Engine isn't running".to_owned())?;
+        writeln!(subprocess.stdin, "{}", command)
+            .and_then(|_| subprocess.stdin.flush())
+            .map_err(|err| format!("Failed to write to engine: {}", err))
+    }
+
+    fn send_search(&mut self, state: &FullGameState, limits: SearchLimits) -> Result<(), String> {
+        if self.sent_limits != Some(limits) {
+            self.send("setoption quiet true")?;
+            let format_limit = |limit: Option<String>| limit.unwrap_or_else(|| "none".to_owned());
+            for (name, value) in [
+                ("max_depth", limits.max_depth.map(|v| v.to_string())),
+                ("max_nodes", limits.max_nodes.map(|v| v.to_string())),
+                ("max_millis", limits.max_millis.map(|v| v.to_string())),
+                ("min_nodes", limits.min_nodes.map(|v| v.to_string())),
+            ] {
+                self.send(&format!("setoption {} {}", name, format_limit(value)))?;
+            }
+            self.sent_limits = Some(limits);
+        }
+        self.send(&format!("set_position {}", game_state_to_fen(state)))
+    }
+}
+
+impl AnalysisBackend for SubprocessBackend {
+    fn ensure_searching(
+        &mut self,
+        state: &FullGameState,
+        each_move_callback: Option<EachMoveCallback>,
+        limits: Option<SearchLimits>,
+    ) -> Result<(), String> {
+        let limits = limits.unwrap_or_default();
+        {
+            let mut shared = self.shared.lock();
+            if shared.active_search.as_ref().is_some_and(|active_search| {
+                &active_search.state == state && active_search.limits == limits
+            }) {
+                drop(shared);
+                self.ensure_running();
+                return Ok(());
+            }
+
+            let analyzed_duration = shared.analysis_cache.analyzed_duration(state);
+            shared.active_search = Some(ActiveSearch {
+                state: state.clone(),
+                limits,
+                each_move_callback,
+                analyzed_duration,
+            });
+        }
+
+        if !self.ensure_running() {
+            return Err(self
+                .last_error
+                .clone()
+                .unwrap_or_else(|| "Engine isn't running".to_owned()));
+        }
+        self.send_search(state, limits)
+    }
+
+    fn cached_analysis(&self, state: &FullGameState) -> Vec<TimedSearchResult> {
+        self.shared.lock().analysis_cache.results(state)
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        if self.shared.lock().active_search.take().is_none() || !self.is_running() {
+            return Ok(());
+        }
+        self.send("stop")
+    }
+
+    fn status(&mut self) -> BackendStatus {
+        if !self.ensure_running() {
+            let err = self.last_error.clone().unwrap_or_default();
+            return BackendStatus::Failed(err);
+        }
+        match self.restart_count {
+            0 => BackendStatus::Ready,
+            count => BackendStatus::Restarted(count),
+        }
+    }
+
+    fn take_search_failure(&mut self) -> Option<SearchFailure> {
+        // A panicking subprocess crashes instead, which is reported as it exits
+        self.shared.lock().search_failure.take()
+    }
+
+    fn set_failure_callback(&mut self, failure_callback: Option<SearchFailureCallback>) {
+        self.shared.lock().failure_callback = failure_callback;
+    }
+
+    fn end(&mut self) {
+        self.shared.lock().active_search = None;
+        if let Some(mut subprocess) = self.subprocess.take() {
+            subprocess.kill();
+        }
+    }
+}
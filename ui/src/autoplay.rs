@@ -0,0 +1,93 @@
+use eframe::egui::{self, Ui};
+use santorini_core::search::{Heuristic, WINNING_SCORE_BUFFER};
+use serde::{Deserialize, Serialize};
+
+use crate::score_text;
+
+/// Storage key for the autoplay stop conditions in the eframe config
+pub const AUTOPLAY_STOP_STORAGE_KEY: &str = "autoplay_stop_conditions";
+
+/// Reasons for autoplay to turn itself off before the game ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoplayStopConditions {
+    pub is_move_limit_enabled: bool,
+    /// Moves to play after autoplay is turned on
+    pub move_limit: usize,
+    pub is_swing_limit_enabled: bool,
+    /// Largest change in the engine's eval between consecutive moves
+    pub swing_limit: Heuristic,
+    pub stop_on_mate: bool,
+}
+
+impl Default for AutoplayStopConditions {
+    fn default() -> Self {
+        Self {
+            is_move_limit_enabled: false,
+            move_limit: 20,
+            is_swing_limit_enabled: false,
+            swing_limit: 300,
+            stop_on_mate: false,
+        }
+    }
+}
+
+impl AutoplayStopConditions {
+    /// Why autoplay should stop, rather than move from a position the engine currently scores as
+    /// `score`
+    pub fn before_move(&self, score: Option<Heuristic>) -> Option<String> {
+        let score = score?;
+        (self.stop_on_mate && score.abs() >= WINNING_SCORE_BUFFER)
+            .then(|| format!("mate score found ({})", score_text(score)))
+    }
+
+    /// Why autoplay should stop after playing `moves_made` moves, the last of which changed the
+    /// eval for Player One from `prev_eval` to `eval`
+    pub fn after_move(
+        &self,
+        moves_made: usize,
+        prev_eval: Option<Heuristic>,
+        eval: Option<Heuristic>,
+    ) -> Option<String> {
+        if let (Some(prev_eval), Some(eval)) = (prev_eval, eval)
+            && self.is_swing_limit_enabled
+            && (eval as i32 - prev_eval as i32).abs() > self.swing_limit as i32
+        {
+            return Some(format!(
+                "eval swung from {} to {}",
+                score_text(prev_eval),
+                score_text(eval)
+            ));
+        }
+
+        (self.is_move_limit_enabled && moves_made >= self.move_limit)
+            .then(|| format!("played {} moves", moves_made))
+    }
+
+    pub fn show_settings(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.is_move_limit_enabled, "Stop after")
+                .on_hover_text("Turn autoplay off after this many moves");
+            ui.add_enabled(
+                self.is_move_limit_enabled,
+                egui::DragValue::new(&mut self.move_limit).range(1..=1000),
+            );
+            ui.label("moves");
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.is_swing_limit_enabled, "Stop on eval swing over")
+                .on_hover_text(
+                    "Turn autoplay off when the engine's eval changes by more than this between \
+                     consecutive moves. Handy for finding the blunder in a long game",
+                );
+            ui.add_enabled(
+                self.is_swing_limit_enabled,
+                egui::DragValue::new(&mut self.swing_limit)
+                    .speed(10.0)
+                    .range(1..=WINNING_SCORE_BUFFER),
+            );
+        });
+        ui.checkbox(&mut self.stop_on_mate, "Stop on mate score")
+            .on_hover_text("Turn autoplay off as soon as the engine finds a forced win or loss");
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use battler::{
+    BINARY_DIRECTORY, BattleEvent, BattleResult, SpawnConfig, do_battle_observed,
+    prepare_subprocess,
+};
+use eframe::egui;
+use santorini_core::{
+    board::FullGameState, engine::TimedSearchResult, player::Player, uci_types::BestMoveOutput,
+    utils::timestamp_string,
+};
+
+use crate::analysis_backend::best_move_to_search_result;
+
+enum BattleMessage {
+    Thinking(BestMoveOutput),
+    Moved(BestMoveOutput),
+    Finished(BattleResult),
+    Stopped,
+    Failed(String),
+}
+
+/// Lists the engine binaries available to battle
+pub(crate) fn list_engine_binaries() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(BINARY_DIRECTORY) else {
+        return Vec::new();
+    };
+    let mut result: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    result.sort();
+    result
+}
+
+/// A game between two engine subprocesses, played on a background thread
+struct EngineBattle {
+    receiver: mpsc::Receiver<BattleMessage>,
+    stop_flag: Arc<AtomicBool>,
+    engine_names: [String; 2],
+    /// Latest report from each engine, by player
+    latest: [Option<BestMoveOutput>; 2],
+    /// Moves played by the engines that haven't been shown on the board yet, with the engine's
+    /// result for each
+    pending_states: VecDeque<(FullGameState, TimedSearchResult)>,
+    status: String,
+    is_running: bool,
+}
+
+impl EngineBattle {
+    /// Messages wake up `ctx`, so that the battle is shown as it's played
+    fn start(
+        ctx: egui::Context,
+        start_state: FullGameState,
+        engine_names: [String; 2],
+        per_turn: Duration,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop_flag = Arc::new(AtomicBool::new(false));
+
+        let thread_stop_flag = stop_flag.clone();
+        let thread_names = engine_names.clone();
+        thread::spawn(move || {
+            let send = |message: BattleMessage| {
+                let _ = sender.send(message);
+                ctx.request_repaint();
+            };
+            let now_str = timestamp_string();
+            let prepare = |idx: usize| {
+                prepare_subprocess(
+                    &PathBuf::new()
+                        .join(BINARY_DIRECTORY)
+                        .join(&thread_names[idx]),
+                    &SpawnConfig::new(format!(
+                        "ui-battle-{}-{}-{}.log",
+                        idx + 1,
+                        now_str,
+                        thread_names[idx]
+                    )),
+                )
+                .map_err(|err| err.to_string())
+            };
+
+            let mut c1 = match prepare(0) {
+                Ok(c1) => c1,
+                Err(err) => {
+                    send(BattleMessage::Failed(err));
+                    return;
+                }
+            };
+            let mut c2 = match prepare(1) {
+                Ok(c2) => c2,
+                Err(err) => {
+                    c1.kill();
+                    send(BattleMessage::Failed(err));
+                    return;
+                }
+            };
+
+            let result = do_battle_observed(
+                &start_state,
+                &mut c1,
+                &mut c2,
+                per_turn,
+                false,
+                &thread_stop_flag,
+                |event| {
+                    let message = match event {
+                        BattleEvent::Thinking(best_move) => {
+                            BattleMessage::Thinking(best_move.clone())
+                        }
+                        BattleEvent::Moved(best_move) => BattleMessage::Moved(best_move.clone()),
+                    };
+                    send(message);
+                },
+            );
+            c1.kill();
+            c2.kill();
+
+            send(match result {
+                Ok(Some(result)) => BattleMessage::Finished(result),
+                Ok(None) => BattleMessage::Stopped,
+                Err(err) => BattleMessage::Failed(err),
+            });
+        });
+
+        EngineBattle {
+            receiver,
+            stop_flag,
+            engine_names,
+            latest: Default::default(),
+            pending_states: VecDeque::new(),
+            status: "Starting engines...".to_owned(),
+            is_running: true,
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+    }
+
+    fn poll_messages(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                BattleMessage::Thinking(best_move) => {
+                    self.status = "Playing".to_owned();
+                    let player = best_move.start_state.board.current_player;
+                    self.latest[player as usize] = Some(best_move);
+                }
+                BattleMessage::Moved(best_move) => {
+                    let next_state = best_move.next_state.clone();
+                    let elapsed = Duration::from_secs_f32(best_move.meta.elapsed_seconds.max(0.0));
+                    let search = (best_move_to_search_result(best_move), elapsed);
+                    self.pending_states.push_back((next_state, search));
+                }
+                BattleMessage::Finished(result) => {
+                    self.status = result.get_pretty_description();
+                    self.is_running = false;
+                }
+                BattleMessage::Stopped => {
+                    self.status = "Stopped".to_owned();
+                    self.is_running = false;
+                }
+                BattleMessage::Failed(err) => {
+                    self.status = format!("Battle failed: {}", err);
+                    self.is_running = false;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for EngineBattle {
+    fn drop(&mut self) {
+        // The battle thread kills both subprocesses once it sees the flag
+        self.stop();
+    }
+}
+
+fn engine_panel(ui: &mut egui::Ui, player: Player, name: &str, latest: Option<&BestMoveOutput>) {
+    ui.strong(format!("Player {:?}: {}", player, name));
+    match latest {
+        Some(best_move) => {
+            let depth_str = match best_move.meta.seldepth {
+                Some(seldepth) => format!("{}/{}", best_move.meta.calculated_depth, seldepth),
+                // Older engines don't report seldepth
+                None => format!("{}", best_move.meta.calculated_depth),
+            };
+            ui.label(format!(
+                "Depth: {}  Score: {}",
+                depth_str, best_move.meta.score
+            ));
+            if let Some(nodes) = best_move.meta.nodes_visited {
+                ui.label(format!("Nodes: {}", nodes));
+            }
+            if let Some(action_str) = &best_move.meta.action_str {
+                ui.label(format!("Best: {}", action_str));
+            }
+        }
+        None => {
+            ui.label("Waiting...");
+        }
+    }
+}
+
+/// Window for setting up and watching a game between two engine binaries
+pub struct EngineBattleViewer {
+    pub is_open: bool,
+    available_engines: Vec<String>,
+    selected_engines: [String; 2],
+    secs_per_turn: f32,
+    battle: Option<EngineBattle>,
+    last_shown_at: Instant,
+}
+
+impl Default for EngineBattleViewer {
+    fn default() -> Self {
+        EngineBattleViewer {
+            is_open: false,
+            available_engines: Vec::new(),
+            selected_engines: Default::default(),
+            secs_per_turn: 1.0,
+            battle: None,
+            last_shown_at: Instant::now(),
+        }
+    }
+}
+
+impl EngineBattleViewer {
+    pub fn open(&mut self) {
+        self.is_open = true;
+        self.refresh_engines();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.battle.as_ref().is_some_and(|b| b.is_running)
+    }
+
+    /// Whether engine moves are waiting for their turn to be shown on the board
+    pub fn has_unshown_moves(&self) -> bool {
+        self.battle
+            .as_ref()
+            .is_some_and(|b| !b.pending_states.is_empty())
+    }
+
+    fn refresh_engines(&mut self) {
+        self.available_engines = list_engine_binaries();
+        for (idx, selected) in self.selected_engines.iter_mut().enumerate() {
+            if !self.available_engines.contains(selected) {
+                *selected = self
+                    .available_engines
+                    .get(idx.min(self.available_engines.len().saturating_sub(1)))
+                    .cloned()
+                    .unwrap_or_default();
+            }
+        }
+    }
+
+    /// Returns the next engine move to show on the board, with the engine's result for it, at most
+    /// one per `move_delay`
+    pub fn next_state_to_show(
+        &mut self,
+        move_delay: Duration,
+    ) -> Option<(FullGameState, TimedSearchResult)> {
+        let battle = self.battle.as_mut()?;
+        battle.poll_messages();
+
+        if self.last_shown_at.elapsed() < move_delay {
+            return None;
+        }
+        let next_state = battle.pending_states.pop_front()?;
+        self.last_shown_at = Instant::now();
+        Some(next_state)
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, current_state: &FullGameState) {
+        if !self.is_open {
+            return;
+        }
+
+        let mut is_open = self.is_open;
+        egui::Window::new("Engine Battle")
+            .open(&mut is_open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let is_running = self.is_running();
+
+                ui.add_enabled_ui(!is_running, |ui| {
+                    egui::Grid::new("engine_battle_settings").show(ui, |ui| {
+                        for (idx, selected) in self.selected_engines.iter_mut().enumerate() {
+                            ui.label(format!("Player {} engine:", idx + 1));
+                            egui::ComboBox::from_id_salt(("engine_battle_engine", idx))
+                                .selected_text(selected.as_str())
+                                .show_ui(ui, |ui| {
+                                    for engine in &self.available_engines {
+                                        ui.selectable_value(selected, engine.clone(), engine);
+                                    }
+                                });
+                            ui.end_row();
+                        }
+
+                        ui.label("Seconds per turn:");
+                        ui.add(
+                            egui::DragValue::new(&mut self.secs_per_turn)
+                                .speed(0.1)
+                                .range(0.1..=60.0),
+                        );
+                        ui.end_row();
+                    });
+                });
+
+                if self.available_engines.is_empty() {
+                    ui.label(format!("No engines found in {}/", BINARY_DIRECTORY));
+                }
+
+                ui.horizontal(|ui| {
+                    let may_start = !is_running
+                        && self.selected_engines.iter().all(|e| !e.is_empty())
+                        && current_state.validation_err().is_ok()
+                        && current_state.get_terminal_result().is_none();
+                    if ui
+                        .add_enabled(may_start, egui::Button::new("Start"))
+                        .on_hover_text("Play the engines against each other from this position")
+                        .clicked()
+                    {
+                        self.battle = Some(EngineBattle::start(
+                            ctx.clone(),
+                            current_state.clone(),
+                            self.selected_engines.clone(),
+                            Duration::from_secs_f32(self.secs_per_turn),
+                        ));
+                    }
+
+                    if ui
+                        .add_enabled(is_running, egui::Button::new("Stop"))
+                        .clicked()
+                        && let Some(battle) = &mut self.battle
+                    {
+                        battle.stop();
+                    }
+
+                    if ui
+                        .add_enabled(!is_running, egui::Button::new("Refresh Engines"))
+                        .clicked()
+                    {
+                        self.refresh_engines();
+                    }
+                });
+
+                if let Some(battle) = &self.battle {
+                    ui.label(&battle.status);
+                    ui.separator();
+                    ui.columns(2, |columns| {
+                        for player in [Player::One, Player::Two] {
+                            engine_panel(
+                                &mut columns[player as usize],
+                                player,
+                                &battle.engine_names[player as usize],
+                                battle.latest[player as usize].as_ref(),
+                            );
+                        }
+                    });
+                }
+            });
+
+        if !is_open {
+            // Closing the window ends the battle
+            self.battle = None;
+        }
+        self.is_open = is_open;
+    }
+}
@@ -0,0 +1,339 @@
+use std::time::Duration;
+
+use battler::GameLogEntry;
+use santorini_core::{
+    board::FullGameState,
+    engine::TimedSearchResult,
+    fen::{game_state_to_fen, parse_fen},
+    gods::{PartialAction, generic::GenericMove},
+    refute::{next_states_with_strings, parse_move_string},
+    review::{MoveJudgment, MoveSeverity},
+    search::{BestMoveTrigger, BestSearchResult, Heuristic},
+    utils::find_action_path,
+};
+
+use crate::score_graph::score_for_p1;
+
+/// Move quality annotation, shown as a glyph next to the move
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Annotation {
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Annotation {
+    pub const ALL: [Annotation; 4] = [
+        Annotation::Good,
+        Annotation::Inaccuracy,
+        Annotation::Mistake,
+        Annotation::Blunder,
+    ];
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Annotation::Good => "!",
+            Annotation::Inaccuracy => "?!",
+            Annotation::Mistake => "?",
+            Annotation::Blunder => "??",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Annotation::Good => "Good move",
+            Annotation::Inaccuracy => "Inaccuracy",
+            Annotation::Mistake => "Mistake",
+            Annotation::Blunder => "Blunder",
+        }
+    }
+
+    pub fn from_severity(severity: MoveSeverity) -> Self {
+        match severity {
+            MoveSeverity::Inaccuracy => Annotation::Inaccuracy,
+            MoveSeverity::Mistake => Annotation::Mistake,
+            MoveSeverity::Blunder => Annotation::Blunder,
+        }
+    }
+
+    pub fn from_glyph(glyph: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.glyph() == glyph)
+    }
+}
+
+/// How the move that led to a history entry was played, for spotting time management problems
+#[derive(Debug, Clone)]
+pub struct PlayedMove {
+    /// Time spent on the previous position before the move was made
+    pub elapsed: Duration,
+    /// The engine's result for the move, and when it was found, if the engine had chosen it
+    pub search: Option<TimedSearchResult>,
+    /// The engine's latest eval of the previous position when the move was made, from Player
+    /// One's perspective
+    pub eval_for_p1: Option<Heuristic>,
+    /// The engine that chose the move, for games loaded from a battler game log
+    pub engine: Option<String>,
+}
+
+impl PlayedMove {
+    pub fn summary(&self) -> String {
+        let mut result = match &self.engine {
+            Some(engine) => format!(
+                "Played by {} after {:.2}s",
+                engine,
+                self.elapsed.as_secs_f32()
+            ),
+            None => format!("Played after {:.2}s", self.elapsed.as_secs_f32()),
+        };
+        if let Some((search, found_at)) = &self.search {
+            result += &format!(
+                ", depth {}/{}, score {}, {} nodes (found at {:.2}s)",
+                search.depth,
+                search.seldepth,
+                search.score,
+                search.nodes_visited,
+                found_at.as_secs_f32()
+            );
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub state: FullGameState,
+    /// The actions that lead here from the previous entry.
+    /// None for the first entry, or when the position was edited rather than played
+    pub actions_from_prev: Option<Vec<PartialAction>>,
+    pub comment: String,
+    pub nag: Option<Annotation>,
+    /// Best known score of this position, from Player One's perspective
+    pub score_for_p1: Option<Heuristic>,
+    /// Game review verdict on the move that led here
+    pub review: Option<MoveJudgment>,
+    /// Timing and search details of the move that led here, when it was played in this session
+    pub played_move: Option<PlayedMove>,
+}
+
+impl HistoryEntry {
+    pub fn new(state: FullGameState, prev: Option<&FullGameState>) -> Self {
+        let actions_from_prev = prev.and_then(|prev| find_action_path(prev, &state).ok());
+        HistoryEntry {
+            state,
+            actions_from_prev,
+            comment: String::new(),
+            nag: None,
+            score_for_p1: None,
+            review: None,
+            played_move: None,
+        }
+    }
+}
+
+fn escape_comment(comment: &str) -> String {
+    let mut result = String::with_capacity(comment.len());
+    for c in comment.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => {}
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn unescape_comment(escaped: &str) -> String {
+    let mut result = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Serializes a game record, one entry per line: `<fen>\t<annotation glyph>\t<comment>`.
+/// Newlines and tabs in comments are escaped
+pub fn game_record_to_string(history: &[HistoryEntry]) -> String {
+    let mut result = String::new();
+    for entry in history {
+        result += &format!(
+            "{}\t{}\t{}\n",
+            game_state_to_fen(&entry.state),
+            entry.nag.map_or("", Annotation::glyph),
+            escape_comment(&entry.comment),
+        );
+    }
+    result
+}
+
+/// Parses a game record written by `game_record_to_string`.
+/// Lines holding only a fen are accepted, so a plain list of positions also loads
+pub fn parse_game_record(record: &str) -> Result<Vec<HistoryEntry>, String> {
+    let mut result: Vec<HistoryEntry> = Vec::new();
+    for (line_idx, line) in record.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut parts = line.splitn(3, '\t');
+        let fen = parts.next().unwrap_or_default().trim();
+        let glyph = parts.next().unwrap_or_default().trim();
+        let comment = parts.next().unwrap_or_default();
+
+        let state = parse_fen(fen).map_err(|err| format!("Line {}: {}", line_idx + 1, err))?;
+        let nag = match glyph {
+            "" => None,
+            glyph => match Annotation::from_glyph(glyph) {
+                Some(annotation) => Some(annotation),
+                None => {
+                    return Err(format!(
+                        "Line {}: unknown annotation '{}'",
+                        line_idx + 1,
+                        glyph
+                    ));
+                }
+            },
+        };
+
+        let mut entry = HistoryEntry::new(state, result.last().map(|e| &e.state));
+        entry.comment = unescape_comment(comment);
+        entry.nag = nag;
+        result.push(entry);
+    }
+
+    if result.is_empty() {
+        return Err("Game record is empty".to_owned());
+    }
+    Ok(result)
+}
+
+/// The move played from `state` in a game log entry, and its notation. Engines that don't report
+/// their move are checked against the position the log continues from instead
+fn game_log_move(
+    state: &FullGameState,
+    action_str: Option<&str>,
+    next_logged_state: Option<&FullGameState>,
+) -> Result<(FullGameState, GenericMove, String), String> {
+    if let Some(action_str) = action_str {
+        let (child, action) = parse_move_string(state, action_str)?;
+        return Ok((child, action, action_str.to_owned()));
+    }
+
+    let next_logged_state = next_logged_state.ok_or("the last move isn't named")?;
+    // Covers placements. Skipped moves are only possible when a win was, so the complete list
+    // is the fallback
+    if let Some(played) = next_states_with_strings(state)
+        .into_iter()
+        .find(|(child, _, _)| child == next_logged_state)
+    {
+        return Ok(played);
+    }
+    let active_god = state.get_active_god();
+    state
+        .get_complete_next_states_with_actions()
+        .into_iter()
+        .find(|(child, _)| child == next_logged_state)
+        .map(|(child, action)| (child, action, active_god.stringify_move(action)))
+        .ok_or_else(|| "the next position can't be reached with one move".to_owned())
+}
+
+/// Rebuilds the history of a game from its battler game log, with each move's engine, score and
+/// depth. A move that doesn't follow from the position before it ends the history early, with a
+/// warning saying where
+pub fn parse_game_log(
+    entries: &[GameLogEntry],
+) -> Result<(Vec<HistoryEntry>, Option<String>), String> {
+    let Some(GameLogEntry::Header { start_state, .. }) = entries.first() else {
+        return Err("Game log doesn't start with a header".to_owned());
+    };
+    let logged_moves: Vec<&GameLogEntry> = entries
+        .iter()
+        .filter(|entry| matches!(entry, GameLogEntry::Move { .. }))
+        .collect();
+
+    let mut result = vec![HistoryEntry::new(start_state.clone(), None)];
+    for (move_idx, logged_move) in logged_moves.iter().enumerate() {
+        let GameLogEntry::Move {
+            move_number,
+            engine,
+            state,
+            action_str,
+            score,
+            depth,
+            nodes_visited,
+            elapsed_seconds,
+            ..
+        } = logged_move
+        else {
+            unreachable!();
+        };
+
+        let prev_state = &result.last().unwrap().state;
+        if state != prev_state {
+            return Ok((
+                result,
+                Some(format!(
+                    "Stopped at move {}: its position doesn't follow from the previous move",
+                    move_number
+                )),
+            ));
+        }
+        let next_logged_state = logged_moves
+            .get(move_idx + 1)
+            .and_then(|entry| match entry {
+                GameLogEntry::Move { state, .. } => Some(state),
+                _ => None,
+            });
+        let (child, action, action_str) =
+            match game_log_move(state, action_str.as_deref(), next_logged_state) {
+                Ok(played) => played,
+                Err(err) => {
+                    return Ok((
+                        result,
+                        Some(format!("Stopped at move {}: {}", move_number, err)),
+                    ));
+                }
+            };
+
+        let eval_for_p1 = score_for_p1(state, *score);
+        result.last_mut().unwrap().score_for_p1 = eval_for_p1;
+        let elapsed = Duration::from_secs_f32(*elapsed_seconds);
+        let search = BestSearchResult {
+            child_state: child.clone(),
+            action,
+            action_str,
+            score: *score,
+            depth: *depth,
+            nodes_visited: nodes_visited.unwrap_or_default(),
+            seldepth: *depth,
+            tt_probes: 0,
+            tt_hits: 0,
+            suppressed_updates: 0,
+            trigger: BestMoveTrigger::Saved,
+            pv: vec![action],
+        };
+
+        let mut entry = HistoryEntry::new(child, Some(state));
+        entry.played_move = Some(PlayedMove {
+            elapsed,
+            search: Some((search, elapsed)),
+            eval_for_p1,
+            engine: Some(engine.clone()),
+        });
+        result.push(entry);
+    }
+
+    Ok((result, None))
+}
@@ -1,32 +1,78 @@
+mod analysis_backend;
+mod autoplay;
 mod dropdown;
+mod engine_battle;
+mod history;
+mod position_list;
+mod pv_preview;
+mod score_graph;
+mod shortcuts;
 
 use std::{
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
 
+use battler::{BINARY_DIRECTORY, read_game_log};
 use eframe::{
-    egui::{
-        self, Color32, Key, Label, Modifiers, Rangef, Response, RichText, Stroke, Ui, UiBuilder,
-        mutex::Mutex,
-    },
+    egui::{self, Color32, Key, Modifiers, Rangef, Response, Stroke, Ui, UiBuilder, mutex::Mutex},
     epaint::EllipseShape,
 };
 use santorini_core::{
-    bitboard::BitBoard,
-    board::FullGameState,
+    bitboard::{BOARD_WIDTH, BitBoard, NUM_SQUARES},
+    board::{FullGameState, RandomPositionConfig, Symmetry},
     direction::maybe_wind_direction_to_ui_square,
-    engine::EngineThreadWrapper,
+    engine::{EngineThreadWrapper, SearchFailure, TimedSearchResult},
+    explorer::{ExplorerIndex, ExplorerMove},
     fen::{game_state_to_fen, parse_fen},
-    gods::{ALL_GODS_BY_ID, GameStateWithAction, GodName, PartialAction, WIP_GODS},
+    gods::{
+        GameStateWithAction, GodDataItem, GodName, GodSupport, PartialAction, god_description,
+        god_implementation_notes,
+    },
+    logging::LogArgs,
+    placement::get_starting_placement_state,
     player::Player,
     pretty_board::{game_state_with_partial_actions, get_acting_player},
-    search::{BestMoveTrigger, BestSearchResult, WINNING_SCORE, WINNING_SCORE_BUFFER},
+    prove::{ProvenLine, prove_win},
+    quick_verdict::{
+        MercyConfig, PlacementScore, best_placement_score_per_square, pick_merciful_move,
+        score_all_root_moves, score_placements,
+    },
+    refute::{RefutationLine, refute_child_state},
+    report::{GameReport, ReportMove},
+    review::{MoveJudgment, review_game_with_progress},
+    search::{BestMoveTrigger, BestSearchResult, Heuristic, WINNING_SCORE, WINNING_SCORE_BUFFER},
+    search_terminators::SearchLimits,
     square::Square,
+    transposition_table::TranspositionTable,
+    uci_types::GameResult,
     utils::sigmoid,
+    win_reason::WinReason,
+};
+
+use crate::{
+    analysis_backend::{
+        AnalysisBackend, BackendStatus, ENGINE_PATH_STORAGE_KEY, RESTART_BACKOFF, SubprocessBackend,
+    },
+    autoplay::{AUTOPLAY_STOP_STORAGE_KEY, AutoplayStopConditions},
+    engine_battle::{EngineBattleViewer, list_engine_binaries},
+    history::{
+        Annotation, HistoryEntry, PlayedMove, game_record_to_string, parse_game_log,
+        parse_game_record,
+    },
+    position_list::PositionList,
+    pv_preview::PvPreview,
+    score_graph::{QuickEvaluator, score_for_p1, show_score_graph},
+    shortcuts::{
+        SHORTCUTS_STORAGE_KEY, ShortcutSettings, Shortcuts, shortcut_text, shortcut_text_long,
+    },
 };
 
 fn main() -> Result<(), eframe::Error> {
+    // The UI has no flags, so RUST_LOG is the only way to change the level
+    LogArgs::default().init();
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("Santorini Analysis Engine")
@@ -42,7 +88,30 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Santorini Analysis Engine",
         native_options,
-        Box::new(|_cc| Ok(Box::new(MyApp::default()))),
+        Box::new(|cc| {
+            let mut app = MyApp::default();
+            if let Some(shortcuts) = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, SHORTCUTS_STORAGE_KEY))
+            {
+                app.shortcuts = shortcuts;
+            }
+            if let Some(conditions) = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, AUTOPLAY_STOP_STORAGE_KEY))
+            {
+                app.autoplay_stop_conditions = conditions;
+            }
+            if let Some(Some(engine_path)) = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, ENGINE_PATH_STORAGE_KEY))
+            {
+                app.engine_subprocess_path = engine_path;
+                app.is_engine_subprocess = true;
+                app.apply_engine_settings();
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
 
@@ -54,28 +123,21 @@ enum EditMode {
     EditWorkers,
 }
 
-const WORKER_ROTATION: [Option<Player>; 3] = [None, Some(Player::One), Some(Player::Two)];
-
-const SHORTCUT_REDO_TURN: egui::KeyboardShortcut =
-    egui::KeyboardShortcut::new(Modifiers::NONE, Key::ArrowDown);
-const SHORTCUT_ENGINE_MOVE: egui::KeyboardShortcut =
-    egui::KeyboardShortcut::new(Modifiers::NONE, Key::ArrowUp);
-const SHORTCUT_STATE_FORWARD: egui::KeyboardShortcut =
-    egui::KeyboardShortcut::new(Modifiers::NONE, Key::ArrowRight);
-const SHORTCUT_STATE_BACKWARD: egui::KeyboardShortcut =
-    egui::KeyboardShortcut::new(Modifiers::NONE, Key::ArrowLeft);
-const SHORTCUT_CHANGE_MODE: egui::KeyboardShortcut =
-    egui::KeyboardShortcut::new(Modifiers::NONE, Key::M);
-const SHORTCUT_AUTOPLAY_START: egui::KeyboardShortcut =
-    egui::KeyboardShortcut::new(Modifiers::NONE, Key::A);
-
-fn shortcut_text(shortcut: egui::KeyboardShortcut) -> String {
-    shortcut.format(&egui::ModifierNames::SYMBOLS, false)
-}
-
-fn shortcut_text_long(shortcut: egui::KeyboardShortcut) -> String {
-    shortcut.format(&egui::ModifierNames::NAMES, false)
-}
+const FAST_ANALYSIS_LIMITS: SearchLimits = SearchLimits::new()
+    .with_max_depth(10)
+    .with_max_millis(2_000);
+
+const REVIEW_NODES_PER_POSITION: usize = 200_000;
+const MAX_PINNED_POSITIONS: usize = 3;
+const REFUTE_NODES: usize = 500_000;
+const PLACEMENT_SCORE_NODES: usize = 20_000;
+const MERCY_SCORE_NODES: usize = 5_000;
+/// Border drawn around the board while it shows an engine line instead of the game
+const PREVIEW_BORDER_COLOR: Color32 = Color32::from_gray(90);
+/// Outline of the square with keyboard focus
+const BOARD_FOCUS_COLOR: Color32 = Color32::YELLOW;
+/// How often to check on background work that doesn't wake the UI up by itself
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 fn shortcut_button(
     ui: &mut Ui,
@@ -90,16 +152,6 @@ fn shortcut_button(
         ))
 }
 
-fn next_worker_rotation(current: Option<Player>, forward: bool) -> Option<Player> {
-    let current_idx = WORKER_ROTATION.iter().position(|x| *x == current).unwrap() as i32;
-    let new_idx = if forward {
-        (current_idx + 1).rem_euclid(WORKER_ROTATION.len() as i32)
-    } else {
-        (current_idx - 1).rem_euclid(WORKER_ROTATION.len() as i32)
-    };
-    WORKER_ROTATION[new_idx as usize]
-}
-
 fn square_for_interaction(action: &PartialAction) -> Option<Square> {
     match action {
         PartialAction::PlaceWorker(x)
@@ -159,10 +211,51 @@ fn partial_action_label(action: &PartialAction) -> String {
     }
 }
 
+/// What a screen reader says about a square, like "C3, height 2, Player One worker, legal: build"
+fn square_description(
+    state: &FullGameState,
+    square: Square,
+    token: Option<Player>,
+    ui_action: Option<&PartialAction>,
+) -> String {
+    let mut description = match state.board.get_height(square) {
+        4 => format!("{}, dome", square),
+        height => format!("{}, height {}", square, height),
+    };
+    if let Some(worker) = state.board.get_worker_at(square) {
+        description += &format!(", Player {:?} worker", worker);
+    }
+    if let Some(token) = token {
+        description += &format!(", Player {:?} token", token);
+    }
+    if let Some(ui_action) = ui_action {
+        description += &format!(
+            ", legal: {}",
+            partial_action_label(ui_action).to_lowercase()
+        );
+    }
+    description
+}
+
+/// The square `key` moves the board's keyboard cursor to from `square`, if it's an arrow key
+fn square_in_arrow_direction(square: Square, key: Key) -> Option<Square> {
+    let (col, row) = square.to_col_row();
+    let (col, row) = match key {
+        Key::ArrowLeft => (col.checked_sub(1)?, row),
+        Key::ArrowRight => (col + 1, row),
+        Key::ArrowUp => (col, row.checked_sub(1)?),
+        Key::ArrowDown => (col, row + 1),
+        _ => return None,
+    };
+    (col < 5 && row < 5).then(|| Square::from_col_row(col, row))
+}
+
 struct EngineThinkingState {
     state: FullGameState,
     engine_messages: Vec<(BestSearchResult, Duration)>,
     start_time: Instant,
+    /// Woken up when a new message arrives, so that idle windows don't have to poll
+    repaint_ctx: Option<egui::Context>,
 }
 
 impl EngineThinkingState {
@@ -171,52 +264,496 @@ impl EngineThinkingState {
             state,
             engine_messages: Vec::new(),
             start_time: Instant::now(),
+            repaint_ctx: None,
         }
     }
 
-    pub fn reset(&mut self, state: FullGameState) {
+    /// Resets to `state`, keeping any analysis that was already done for it
+    pub fn restore(&mut self, state: FullGameState, cached_analysis: Vec<TimedSearchResult>) {
         self.state = state;
-        self.engine_messages.clear();
-        self.start_time = Instant::now();
+        self.engine_messages = cached_analysis
+            .into_iter()
+            .filter(|(message, _)| message.trigger != BestMoveTrigger::Seed)
+            .collect();
+        let analyzed_duration = self
+            .engine_messages
+            .last()
+            .map_or(Duration::ZERO, |(_, elapsed)| *elapsed);
+        self.start_time = Instant::now() - analyzed_duration;
     }
 
     pub fn add_message(&mut self, state: &FullGameState, message: BestSearchResult) {
-        if state == &self.state && message.trigger != BestMoveTrigger::Seed {
-            self.engine_messages
-                .push((message, self.start_time.elapsed()));
+        if state != &self.state || message.trigger == BestMoveTrigger::Seed {
+            return;
+        }
+
+        // A resumed search starts over from depth 1, and its first results may already have been
+        // restored from the cache
+        if let Some((last, _)) = self.engine_messages.last() {
+            let is_restored =
+                last.depth == message.depth && last.nodes_visited == message.nodes_visited;
+            if message.depth < last.depth || is_restored {
+                return;
+            }
+        }
+
+        self.engine_messages
+            .push((message, self.start_time.elapsed()));
+        if let Some(ctx) = &self.repaint_ctx {
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// A game review running on a background thread
+struct GameReviewJob {
+    states: Vec<FullGameState>,
+    progress: Arc<Mutex<(usize, usize)>>,
+    result: Arc<Mutex<Option<Vec<MoveJudgment>>>>,
+}
+
+impl GameReviewJob {
+    fn start(states: Vec<FullGameState>) -> Self {
+        let progress: Arc<Mutex<(usize, usize)>> = Default::default();
+        let result: Arc<Mutex<Option<Vec<MoveJudgment>>>> = Default::default();
+
+        let thread_states = states.clone();
+        let thread_progress = progress.clone();
+        let thread_result = result.clone();
+        std::thread::spawn(move || {
+            let judgments = review_game_with_progress(
+                &thread_states,
+                REVIEW_NODES_PER_POSITION,
+                |done, total| *thread_progress.lock() = (done, total),
+            );
+            *thread_result.lock() = Some(judgments);
+        });
+
+        GameReviewJob {
+            states,
+            progress,
+            result,
+        }
+    }
+
+    fn progress_fraction(&self) -> f32 {
+        let (done, total) = *self.progress.lock();
+        match total {
+            0 => 0.0,
+            total => done as f32 / total as f32,
+        }
+    }
+}
+
+/// A search for the best answer to a move the user asked about, running on a background thread
+struct RefutationJob {
+    state: FullGameState,
+    result: Arc<Mutex<Option<Result<RefutationLine, String>>>>,
+}
+
+impl RefutationJob {
+    fn start(state: FullGameState, forced_state: FullGameState) -> Self {
+        let result: Arc<Mutex<Option<Result<RefutationLine, String>>>> = Default::default();
+
+        let thread_state = state.clone();
+        let thread_result = result.clone();
+        std::thread::spawn(move || {
+            let mut tt = TranspositionTable::new();
+            let refutation =
+                refute_child_state(&mut tt, &thread_state, &forced_state, REFUTE_NODES);
+            *thread_result.lock() = Some(refutation);
+        });
+
+        RefutationJob { state, result }
+    }
+}
+
+/// An exhaustive search confirming a forced win the engine reported, running on a background
+/// thread
+struct ProofJob {
+    state: FullGameState,
+    max_depth: usize,
+    result: Arc<Mutex<Option<Option<ProvenLine>>>>,
+}
+
+impl ProofJob {
+    fn start(state: FullGameState, max_depth: usize) -> Self {
+        let result: Arc<Mutex<Option<Option<ProvenLine>>>> = Default::default();
+
+        let thread_state = state.clone();
+        let thread_result = result.clone();
+        std::thread::spawn(move || {
+            let proven = prove_win(&thread_state, max_depth);
+            *thread_result.lock() = Some(proven);
+        });
+
+        ProofJob {
+            state,
+            max_depth,
+            result,
+        }
+    }
+}
+
+/// Scores for every placement of a position in the placement phase, running on background
+/// threads
+struct PlacementScoresJob {
+    state: FullGameState,
+    result: Arc<Mutex<Option<Vec<PlacementScore>>>>,
+}
+
+impl PlacementScoresJob {
+    fn start(state: FullGameState) -> Self {
+        let result: Arc<Mutex<Option<Vec<PlacementScore>>>> = Default::default();
+
+        let thread_state = state.clone();
+        let thread_result = result.clone();
+        std::thread::spawn(move || {
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let scores = score_placements(&thread_state, PLACEMENT_SCORE_NODES, threads);
+            *thread_result.lock() = Some(scores);
+        });
+
+        PlacementScoresJob { state, result }
+    }
+}
+
+/// Picks a merciful autoplay move for `state` in the background
+struct MercyJob {
+    state: FullGameState,
+    result: Arc<Mutex<Option<FullGameState>>>,
+}
+
+impl MercyJob {
+    fn start(state: FullGameState) -> Self {
+        let result: Arc<Mutex<Option<FullGameState>>> = Default::default();
+
+        let thread_state = state.clone();
+        let thread_result = result.clone();
+        std::thread::spawn(move || {
+            let scores = score_all_root_moves(&thread_state, MERCY_SCORE_NODES);
+            let Some((action, _)) =
+                pick_merciful_move(&thread_state, &scores, &MercyConfig::default())
+            else {
+                return;
+            };
+            *thread_result.lock() = thread_state
+                .describe_moves()
+                .into_iter()
+                .find(|description| description.action == action)
+                .map(|description| description.next_state);
+        });
+
+        MercyJob { state, result }
+    }
+}
+
+/// Tint for a placement square, from red for the worst score shown to green for the best
+fn placement_tint(score: Heuristic, worst: Heuristic, best: Heuristic) -> Color32 {
+    let t = match best > worst {
+        true => (score - worst) as f32 / (best - worst) as f32,
+        false => 1.0,
+    };
+    Color32::from_rgba_unmultiplied(((1.0 - t) * 255.0) as u8, (t * 255.0) as u8, 0, 90)
+}
+
+/// A snapshot of a position shown in its own window, with optional independent analysis
+struct PinnedPosition {
+    viewport_id: egui::ViewportId,
+    title: String,
+    state: FullGameState,
+    engine: Option<EngineThreadWrapper>,
+    engine_thinking: Arc<Mutex<EngineThinkingState>>,
+    is_open: bool,
+}
+
+impl PinnedPosition {
+    fn new(pin_id: usize, state: FullGameState) -> Self {
+        PinnedPosition {
+            viewport_id: egui::ViewportId::from_hash_of(("pinned_position", pin_id)),
+            title: format!("Pinned: {}", game_state_to_fen(&state)),
+            engine_thinking: Arc::new(Mutex::new(EngineThinkingState::new(state.clone()))),
+            state,
+            engine: None,
+            is_open: true,
+        }
+    }
+
+    fn is_analyzable(&self) -> bool {
+        self.state.validation_err().is_ok() && self.state.get_winner().is_none()
+    }
+
+    fn start_analysis(&mut self) {
+        if self.engine.is_some() || !self.is_analyzable() {
+            return;
+        }
+
+        let engine_thinking = self.engine_thinking.clone();
+        let state = self.state.clone();
+        engine_thinking.lock().restore(state.clone(), Vec::new());
+        let callback = Arc::new(move |new_best_move: BestSearchResult| {
+            engine_thinking.lock().add_message(&state, new_best_move);
+        });
+
+        let mut engine = EngineThreadWrapper::new();
+        match engine.ensure_searching(&self.state, Some(callback), None) {
+            Ok(()) => self.engine = Some(engine),
+            Err(err) => {
+                log::error!("Failed to analyze pinned position: {}", err);
+                engine.end();
+            }
+        }
+    }
+
+    fn stop_analysis(&mut self) {
+        if let Some(mut engine) = self.engine.take() {
+            engine.end();
+        }
+    }
+
+    fn show(&mut self, ctx: &egui::Context) {
+        self.engine_thinking
+            .lock()
+            .repaint_ctx
+            .get_or_insert_with(|| ctx.clone());
+        let builder = egui::ViewportBuilder::default()
+            .with_title(&self.title)
+            .with_inner_size([500.0, 700.0]);
+
+        ctx.show_viewport_immediate(self.viewport_id, builder, |ctx, class| {
+            if ctx.input(|i| i.viewport().close_requested()) {
+                self.is_open = false;
+            }
+
+            match class {
+                egui::ViewportClass::Embedded => {
+                    let mut is_open = self.is_open;
+                    egui::Window::new(&self.title)
+                        .open(&mut is_open)
+                        .show(ctx, |ui| self.contents(ui));
+                    self.is_open &= is_open;
+                }
+                _ => {
+                    egui::CentralPanel::default().show(ctx, |ui| self.contents(ui));
+                }
+            }
+        });
+    }
+
+    fn contents(&mut self, ui: &mut Ui) {
+        ui.label(game_state_to_fen(&self.state));
+
+        if self.is_analyzable() {
+            let mut is_analyzing = self.engine.is_some();
+            if ui
+                .checkbox(&mut is_analyzing, "Analyze")
+                .on_hover_text("Run a separate engine on this position")
+                .changed()
+            {
+                match is_analyzing {
+                    true => self.start_analysis(),
+                    false => self.stop_analysis(),
+                }
+            }
+        }
+
+        if self.engine.is_some() {
+            let rows = self.engine_thinking.lock().engine_messages.clone();
+            egui::ScrollArea::vertical()
+                .max_height(150.0)
+                .show(ui, |ui| {
+                    engine_messages_grid(ui, "PinnedMoves", &rows);
+                });
+        }
+
+        draw_board(ui, &self.state, |_| None);
+    }
+}
+
+impl Drop for PinnedPosition {
+    fn drop(&mut self) {
+        self.stop_analysis();
+    }
+}
+
+/// Opening statistics from an explorer index, with the moves of the last position looked up
+struct OpeningExplorer {
+    index: ExplorerIndex,
+    cached_moves: Option<(FullGameState, Vec<ExplorerMove>)>,
+}
+
+impl OpeningExplorer {
+    fn moves_for(&mut self, state: &FullGameState) -> &[ExplorerMove] {
+        if self
+            .cached_moves
+            .as_ref()
+            .is_none_or(|(cached_state, _)| cached_state != state)
+        {
+            self.cached_moves = Some((state.clone(), self.index.query(state)));
         }
+        &self.cached_moves.as_ref().unwrap().1
     }
 }
 
 struct MyApp {
     state: FullGameState,
-    state_history: Vec<FullGameState>,
+    state_history: Vec<HistoryEntry>,
     state_idx: usize,
+    /// When the current history entry was reached, to time the move played from it
+    state_reached_at: Instant,
+    game_record_path: String,
+    game_record_status: Option<String>,
+    explorer_path: String,
+    explorer_status: Option<String>,
+    report_path: String,
+    report_status: Option<String>,
+    /// Set while a game review runs for a requested report, which is written once it finishes
+    is_report_pending: bool,
+    explorer: Option<OpeningExplorer>,
     editor_fen_string: String,
     editor_fen_error: Option<String>,
     next_states: Vec<GameStateWithAction>,
     current_actions: Vec<PartialAction>,
     available_next_actions: Vec<PartialAction>,
-    engine: EngineThreadWrapper,
+    engine: Box<dyn AnalysisBackend>,
+    /// The last search that failed, shown until dismissed
+    search_failure: Option<SearchFailure>,
     engine_thinking: Arc<Mutex<EngineThinkingState>>,
+    quick_evaluator: QuickEvaluator,
+    review_job: Option<GameReviewJob>,
+    refutation_job: Option<RefutationJob>,
+    proof_job: Option<ProofJob>,
+    placement_scores_job: Option<PlacementScoresJob>,
+    mercy_job: Option<MercyJob>,
 
     // Edit mode
     edit_mode: EditMode,
+    random_build_budget: usize,
     may_show_wip_gods: bool,
+    is_fast_analysis: bool,
+    is_engine_subprocess: bool,
+    engine_subprocess_path: String,
 
     // Autoplay
     is_autoplay_enabled: bool,
     is_autoplay_per_player: [bool; 2],
+    is_autoplay_merciful: bool,
     autoplay_speed_secs: f32,
     autoplay_last_status_change_time: Instant,
+    autoplay_stop_conditions: AutoplayStopConditions,
+    /// Moves played since autoplay was last turned on
+    autoplay_moves_made: usize,
+    autoplay_stop_reason: Option<String>,
 
     // God selector buf
     god_selector_bufs: [String; 2],
+
+    // Shortcuts
+    shortcuts: Shortcuts,
+    shortcut_settings: ShortcutSettings,
+
+    // Pinned positions
+    pinned_positions: Vec<PinnedPosition>,
+    pinned_count: usize,
+
+    // Engine vs engine games
+    engine_battle_viewer: EngineBattleViewer,
+
+    position_list: PositionList,
+
+    /// An engine line shown on the board in place of the current position
+    pv_preview: Option<PvPreview>,
 }
 
 impl MyApp {
+    /// Whether any background job is still running, and will change what's shown once it's done
+    fn has_background_work(&self) -> bool {
+        self.review_job
+            .as_ref()
+            .is_some_and(|job| job.result.lock().is_none())
+            || self
+                .refutation_job
+                .as_ref()
+                .is_some_and(|job| job.result.lock().is_none())
+            || self
+                .proof_job
+                .as_ref()
+                .is_some_and(|job| job.result.lock().is_none())
+            || self
+                .placement_scores_job
+                .as_ref()
+                .is_some_and(|job| job.result.lock().is_none())
+            || self
+                .mercy_job
+                .as_ref()
+                .is_some_and(|job| job.result.lock().is_none())
+            || self.quick_evaluator.is_pending()
+            || self.engine_battle_viewer.has_unshown_moves()
+    }
+
+    /// Reports a search that failed, with the position it failed in so it can be reproduced.
+    /// The engine keeps running, so the banner only needs to be dismissed
+    fn show_search_failure_banner(&mut self, ctx: &egui::Context) {
+        let Some(search_failure) = &self.search_failure else {
+            return;
+        };
+
+        let mut is_dismissed = false;
+        egui::TopBottomPanel::top("search_failure_banner").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                let fen = game_state_to_fen(&search_failure.state);
+                ui.colored_label(
+                    Color32::RED,
+                    format!("Engine search failed: {}", search_failure.message),
+                );
+                ui.label(format!("in {}", fen));
+                if ui.button("Copy FEN").clicked() {
+                    ui.ctx().copy_text(fen);
+                }
+                if ui.button("Dismiss").clicked() {
+                    is_dismissed = true;
+                }
+            });
+        });
+        if is_dismissed {
+            self.search_failure = None;
+        }
+    }
+
+    /// Swaps in the analysis backend chosen in the engine settings, and restarts analysis on it
+    fn apply_engine_settings(&mut self) {
+        self.engine.end();
+        self.engine = if self.is_engine_subprocess {
+            Box::new(SubprocessBackend::new(PathBuf::from(
+                &self.engine_subprocess_path,
+            )))
+        } else {
+            Box::new(EngineThreadWrapper::new())
+        };
+        self.wake_on_search_failure();
+        self.update_state(self.state.clone());
+    }
+
+    /// Repaints when a search fails, so that the failure banner shows up even if nothing else
+    /// is happening
+    fn wake_on_search_failure(&mut self) {
+        let engine_thinking = self.engine_thinking.clone();
+        self.engine.set_failure_callback(Some(Arc::new(move |_| {
+            if let Some(ctx) = &engine_thinking.lock().repaint_ctx {
+                ctx.request_repaint();
+            }
+        })));
+    }
+
+    /// Same as `update_state` for a hand edit of the board, first repairing any god data that the
+    /// edit left pointing at squares it can't be on
+    pub fn update_state_from_edit(&mut self, mut state: FullGameState) {
+        state.reconcile_god_data();
+        self.update_state(state);
+    }
+
     pub fn update_state(&mut self, state: FullGameState) {
-        assert_eq!(self.state, self.state_history[self.state_idx]);
+        assert_eq!(self.state, self.state_history[self.state_idx].state);
 
         let mut is_playable = true;
         self.autoplay_last_status_change_time = Instant::now();
@@ -240,19 +777,34 @@ impl MyApp {
         }
 
         self.state = state.clone();
-        if self.state_history.get(self.state_idx) == Some(&self.state) {
+        let history_state_at = |idx: usize| self.state_history.get(idx).map(|e| &e.state);
+        if history_state_at(self.state_idx) == Some(&self.state) {
             // noop
-        } else if self.state_history.get(self.state_idx + 1) == Some(&self.state) {
+        } else if history_state_at(self.state_idx + 1) == Some(&self.state) {
             self.state_idx = self.state_idx + 1;
+            self.state_reached_at = Instant::now();
         } else {
             self.state_history.truncate(self.state_idx + 1);
-            self.state_history.push(self.state.clone());
+            let prev_state = &self.state_history[self.state_idx].state;
+            let mut entry = HistoryEntry::new(self.state.clone(), Some(prev_state));
+            if entry.actions_from_prev.is_some() {
+                entry.played_move = Some(self.played_move_to(prev_state, &self.state));
+            }
+            self.state_history.push(entry);
             self.state_idx += 1;
+            self.state_reached_at = Instant::now();
+
+            if self.is_autoplay_enabled {
+                self.check_autoplay_after_move();
+            }
         }
 
         self.copy_editor_fen();
         self.compute_next_states(is_playable);
-        self.engine_thinking.lock().reset(state.clone());
+        let cached_analysis = self.engine.cached_analysis(&state);
+        self.engine_thinking
+            .lock()
+            .restore(state.clone(), cached_analysis);
         let engine_thinking_clone = self.engine_thinking.clone();
         let state_clone = state.clone();
 
@@ -262,14 +814,93 @@ impl MyApp {
                 .add_message(&state_clone, new_best_move);
         });
 
-        let _ = self.engine.stop();
+        let is_placing = get_starting_placement_state(&state.board, state.gods)
+            .is_ok_and(|placement_mode| placement_mode.is_some());
+        if !is_playable || !is_placing || self.engine_battle_viewer.is_running() {
+            self.placement_scores_job = None;
+        } else if self
+            .placement_scores_job
+            .as_ref()
+            .is_none_or(|job| job.state != state)
+        {
+            self.placement_scores_job = Some(PlacementScoresJob::start(state.clone()));
+        }
 
-        if is_playable {
-            let res = self.engine.start_search(&state, Some(callback));
+        // Leave the CPU to the battling engines
+        if is_playable && !self.engine_battle_viewer.is_running() {
+            // Only restarts the search if the position or limits changed
+            let limits = self.is_fast_analysis.then_some(FAST_ANALYSIS_LIMITS);
+            let res = self.engine.ensure_searching(&state, Some(callback), limits);
             if let Err(err) = res {
-                panic!("Failed to start search in state {:?}: {}", state, err);
+                log::error!("Failed to start search in state {:?}: {}", state, err);
             }
+        } else if let Err(err) = self.engine.stop() {
+            log::error!("Failed to stop search: {}", err);
+        }
+    }
+
+    /// Times the move from `prev_state` to `state`, attaching the engine's result for it if the
+    /// engine had chosen it
+    fn played_move_to(&self, prev_state: &FullGameState, state: &FullGameState) -> PlayedMove {
+        let engine_thinking = self.engine_thinking.lock();
+        let is_current = &engine_thinking.state == prev_state;
+        let search = if is_current {
+            engine_thinking
+                .engine_messages
+                .iter()
+                .rev()
+                .find(|(message, _)| &message.child_state == state)
+                .cloned()
+        } else {
+            None
+        };
+        let eval_for_p1 = engine_thinking
+            .engine_messages
+            .last()
+            .filter(|_| is_current)
+            .and_then(|(message, _)| score_for_p1(prev_state, message.score));
+        PlayedMove {
+            elapsed: self.state_reached_at.elapsed(),
+            search,
+            eval_for_p1,
+            engine: None,
+        }
+    }
+
+    fn stop_autoplay(&mut self, reason: String) {
+        self.is_autoplay_enabled = false;
+        self.autoplay_stop_reason = Some(reason);
+    }
+
+    /// Stops autoplay if the move that was just added to the history meets a stop condition
+    fn check_autoplay_after_move(&mut self) {
+        self.autoplay_moves_made += 1;
+        let eval_before = |idx: usize| {
+            self.state_history
+                .get(idx)
+                .and_then(|entry| entry.played_move.as_ref())
+                .and_then(|played_move| played_move.eval_for_p1)
+        };
+        let prev_eval = self.state_idx.checked_sub(1).and_then(eval_before);
+        let eval = eval_before(self.state_idx);
+        if let Some(reason) =
+            self.autoplay_stop_conditions
+                .after_move(self.autoplay_moves_made, prev_eval, eval)
+        {
+            self.stop_autoplay(reason);
+        }
+    }
+
+    /// The score of the engine's current best move, if it's searching the current position
+    fn current_engine_score(&self) -> Option<Heuristic> {
+        let engine_thinking = self.engine_thinking.lock();
+        if engine_thinking.state != self.state {
+            return None;
         }
+        engine_thinking
+            .engine_messages
+            .last()
+            .map(|(message, _)| message.score)
     }
 
     pub fn compute_next_states(&mut self, is_playable: bool) {
@@ -352,6 +983,24 @@ impl MyApp {
         }
     }
 
+    /// What a screen reader says after `action` is taken on the board
+    fn action_announcement(&self, action: PartialAction, ply_before: usize) -> String {
+        let mut announcement = partial_action_label(&action);
+        if let Some(square) = square_for_interaction(&action) {
+            announcement += &format!(" {}", square);
+        }
+        if let Some(winner) = self.state.get_winner() {
+            announcement += &format!(". Player {:?} wins", winner);
+        } else if self.state.ply != ply_before {
+            if let Ok(player) = get_acting_player(&self.state) {
+                announcement += &format!(". Player {:?} to move", player);
+            }
+        } else {
+            announcement += &format!(". {} actions available", self.available_next_actions.len());
+        }
+        announcement
+    }
+
     pub fn try_set_editor_fen(&mut self) {
         self.is_autoplay_enabled = false;
         match parse_fen(&self.editor_fen_string) {
@@ -360,6 +1009,12 @@ impl MyApp {
         }
     }
 
+    /// Shows a position picked from the position list
+    fn show_listed_position(&mut self, state: FullGameState) {
+        self.is_autoplay_enabled = false;
+        self.update_state(state);
+    }
+
     pub fn copy_editor_fen(&mut self) {
         self.editor_fen_string = game_state_to_fen(&self.state);
     }
@@ -374,6 +1029,11 @@ impl MyApp {
     }
 
     pub fn try_engine_move(&mut self) {
+        if self.is_autoplay_merciful {
+            self.try_merciful_move();
+            return;
+        }
+
         let engine_state = self.engine_thinking.lock();
         if engine_state.state == self.state {
             if let Some(last_engine_move) = engine_state.engine_messages.last().clone() {
@@ -384,19 +1044,73 @@ impl MyApp {
         }
     }
 
+    /// Plays the move picked by the mercy job for the current state, starting the job if needed
+    fn try_merciful_move(&mut self) {
+        let job = match &self.mercy_job {
+            Some(job) if job.state == self.state => job,
+            _ => self.mercy_job.insert(MercyJob::start(self.state.clone())),
+        };
+        let next_state = job.result.lock().clone();
+        if let Some(next_state) = next_state {
+            self.update_state(next_state);
+        }
+    }
+
     pub fn try_engine_move_if_end_of_line(&mut self) {
+        // Merciful moves don't come from the search, so they wait for the autoplay timeout
+        if self.is_autoplay_merciful {
+            return;
+        }
+
         let engine_state = self.engine_thinking.lock();
         if engine_state.state == self.state {
-            if let Some(last_engine_move) = engine_state.engine_messages.last().clone() {
-                if last_engine_move.0.trigger == BestMoveTrigger::EndOfLine {
-                    let next_state = last_engine_move.0.child_state.clone();
-                    drop(engine_state);
-                    self.update_state(next_state);
-                }
+            // The search keeps going after a stable move, so it may not be the last message.
+            // A saved forced win, as reported straight away for a revisited mate, is settled too
+            let is_settled = engine_state.engine_messages.iter().any(|m| {
+                matches!(
+                    m.0.trigger,
+                    BestMoveTrigger::EndOfLine
+                        | BestMoveTrigger::Stable
+                        | BestMoveTrigger::OnlyMove
+                        | BestMoveTrigger::Book
+                ) || (m.0.trigger == BestMoveTrigger::Saved && m.0.score >= WINNING_SCORE_BUFFER)
+            });
+            if is_settled && let Some(last_engine_move) = engine_state.engine_messages.last() {
+                let next_state = last_engine_move.0.child_state.clone();
+                drop(engine_state);
+                self.update_state(next_state);
             }
         }
     }
 
+    /// Best score of a placement onto each square, given the workers placed so far this turn.
+    /// All None until the placements have been scored
+    fn placement_square_scores(&self, render_state: &FullGameState) -> [Option<Heuristic>; 25] {
+        let Some(job) = &self.placement_scores_job else {
+            return [None; 25];
+        };
+        let Ok(Some(placement_mode)) =
+            get_starting_placement_state(&job.state.board, job.state.gods)
+        else {
+            return [None; 25];
+        };
+        if job.state != self.state {
+            return [None; 25];
+        }
+        let result = job.result.lock();
+        let Some(placements) = result.as_ref() else {
+            return [None; 25];
+        };
+
+        let placer = placement_mode.next_placement as usize;
+        let placed = render_state.board.workers[placer] & !job.state.board.workers[placer];
+        let mut scores = best_placement_score_per_square(placements, placed);
+        for square in placed {
+            scores[square as usize] = None;
+        }
+        scores
+    }
+
     pub fn clear_actions_for_edit(&mut self) {
         self.current_actions.clear();
         self.available_next_actions.clear();
@@ -407,24 +1121,400 @@ impl MyApp {
         self.compute_next_actions();
     }
 
-    pub fn try_forward_state(&mut self) {
-        if let Some(state) = self.state_history.get(self.state_idx + 1) {
+    pub fn try_jump_to_state(&mut self, idx: usize) {
+        if let Some(entry) = self.state_history.get(idx) {
+            let state = entry.state.clone();
             self.is_autoplay_enabled = false;
             self.state = state.clone();
-            self.state_idx += 1;
-            self.update_state(state.clone());
+            self.state_idx = idx;
+            self.state_reached_at = Instant::now();
+            self.update_state(state);
         }
     }
 
+    pub fn try_forward_state(&mut self) {
+        self.try_jump_to_state(self.state_idx + 1);
+    }
+
     pub fn try_back_state(&mut self) {
         if self.state_idx > 0 {
-            if let Some(state) = self.state_history.get(self.state_idx - 1) {
+            self.try_jump_to_state(self.state_idx - 1);
+        }
+    }
+
+    /// Records the latest engine score for the current position, and fills in scores for any
+    /// other positions in the history that were never analyzed
+    pub fn update_history_scores(&mut self) {
+        let engine_score = {
+            let engine = self.engine_thinking.lock();
+            match engine.engine_messages.last() {
+                Some((message, _)) if engine.state == self.state => {
+                    score_for_p1(&engine.state, message.score)
+                }
+                _ => None,
+            }
+        };
+
+        for (idx, entry) in self.state_history.iter_mut().enumerate() {
+            if idx == self.state_idx && engine_score.is_some() {
+                entry.score_for_p1 = engine_score;
+            } else if entry.score_for_p1.is_none() {
+                entry.score_for_p1 = self.quick_evaluator.get_or_request(&entry.state);
+            }
+        }
+    }
+
+    /// Player One's eval of the current position, and of the position before the last move.
+    /// Read from the history scores rather than the engine, so the bar never waits on its lock
+    fn eval_bar_scores(&self) -> (Option<Heuristic>, Option<Heuristic>) {
+        let entry = self
+            .state_history
+            .get(self.state_idx)
+            .filter(|entry| entry.state == self.state);
+
+        let current = match self.state.get_terminal_result() {
+            Some(result) => Some(match result.winner() {
+                Player::One => WINNING_SCORE,
+                Player::Two => -WINNING_SCORE,
+            }),
+            None => entry.and_then(|entry| entry.score_for_p1),
+        };
+
+        // Edited positions didn't come from a move, so there's no swing to show
+        let previous = entry
+            .filter(|entry| entry.actions_from_prev.is_some() && self.state_idx > 0)
+            .and_then(|_| self.state_history[self.state_idx - 1].score_for_p1);
+
+        (current, previous)
+    }
+
+    pub fn clear_old_history(&mut self) {
+        self.state_history = self.state_history.split_off(self.state_idx);
+        self.state_history[0].actions_from_prev = None;
+        self.state_idx = 0;
+    }
+
+    pub fn save_game_record(&mut self) {
+        let record = game_record_to_string(&self.state_history);
+        self.game_record_status = Some(match std::fs::write(&self.game_record_path, record) {
+            Ok(()) => format!("Saved {} positions", self.state_history.len()),
+            Err(err) => format!("Failed to save game record: {}", err),
+        });
+    }
+
+    pub fn load_game_record(&mut self) {
+        let history = std::fs::read_to_string(&self.game_record_path)
+            .map_err(|err| err.to_string())
+            .and_then(|record| parse_game_record(&record));
+        match history {
+            Ok(history) => {
+                self.game_record_status = Some(format!("Loaded {} positions", history.len()));
+                self.is_autoplay_enabled = false;
+                self.state_idx = history.len() - 1;
+                self.state = history[self.state_idx].state.clone();
+                self.state_history = history;
+                self.update_state(self.state.clone());
+            }
+            Err(err) => {
+                self.game_record_status = Some(format!("Failed to load game record: {}", err))
+            }
+        }
+    }
+
+    /// Loads a battler game log from the game record path, with each engine's score and depth
+    /// for its moves
+    pub fn load_game_log(&mut self) {
+        let history = read_game_log(&PathBuf::from(&self.game_record_path))
+            .map_err(|err| err.to_string())
+            .and_then(|entries| parse_game_log(&entries));
+        match history {
+            Ok((history, warning)) => {
+                let mut status = format!("Loaded {} positions from game log", history.len());
+                if let Some(warning) = warning {
+                    status += &format!(". {}", warning);
+                }
+                self.game_record_status = Some(status);
                 self.is_autoplay_enabled = false;
-                self.state = state.clone();
-                self.state_idx -= 1;
-                self.update_state(state.clone());
+                self.state_idx = history.len() - 1;
+                self.state = history[self.state_idx].state.clone();
+                self.state_history = history;
+                self.update_state(self.state.clone());
+            }
+            Err(err) => self.game_record_status = Some(format!("Failed to load game log: {}", err)),
+        }
+    }
+
+    pub fn load_explorer(&mut self) {
+        match ExplorerIndex::load(&self.explorer_path) {
+            Ok(index) => {
+                self.explorer_status = Some(format!(
+                    "Loaded {} opening positions, up to ply {}",
+                    index.len(),
+                    index.max_ply()
+                ));
+                self.explorer = Some(OpeningExplorer {
+                    index,
+                    cached_moves: None,
+                });
+            }
+            Err(err) => {
+                self.explorer_status = Some(format!("Failed to load explorer index: {}", err))
+            }
+        }
+    }
+
+    /// Book-style statistics for the moves played from this position in the explorer's games
+    fn show_explorer(&mut self, ui: &mut Ui) {
+        let Some(explorer) = &mut self.explorer else {
+            return;
+        };
+        let max_ply = explorer.index.max_ply();
+        let moves = explorer.moves_for(&self.state);
+        if moves.is_empty() {
+            ui.label(match self.state.ply >= max_ply {
+                true => "Explorer: past the indexed opening",
+                false => "Explorer: no games from this position",
+            });
+            return;
+        }
+
+        let total_games: u32 = moves.iter().map(|m| m.tally.games).sum();
+        ui.label(format!("Explorer: {} games", total_games));
+        egui::ScrollArea::vertical()
+            .id_salt("explorer_scroll")
+            .max_height(120.0)
+            .show(ui, |ui| {
+                egui::Grid::new("Explorer").striped(true).show(ui, |ui| {
+                    ui.label("Action");
+                    ui.label("Games");
+                    ui.label("Played");
+                    ui.label("Mover wins");
+                    ui.end_row();
+
+                    for explorer_move in moves {
+                        let tally = explorer_move.tally;
+                        ui.label(&explorer_move.action_str);
+                        ui.label(format!("{}", tally.games));
+                        ui.label(format!(
+                            "{:.0}%",
+                            tally.games as f32 / total_games as f32 * 100.0
+                        ));
+                        ui.label(format!("{:.0}%", tally.win_rate() * 100.0));
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    pub fn start_review(&mut self) {
+        let states = self.state_history.iter().map(|e| e.state.clone()).collect();
+        self.review_job = Some(GameReviewJob::start(states));
+    }
+
+    /// Copies the results of a finished review into the history, as long as the reviewed moves
+    /// are still there
+    pub fn apply_finished_review(&mut self) {
+        let Some(job) = &self.review_job else {
+            return;
+        };
+        let Some(judgments) = job.result.lock().take() else {
+            return;
+        };
+
+        for judgment in judgments {
+            let is_same_line = job.states.get(judgment.ply)
+                == self.state_history.get(judgment.ply).map(|e| &e.state);
+            let Some(entry) = self.state_history.get_mut(judgment.ply + 1) else {
+                continue;
+            };
+            if !is_same_line || entry.state != judgment.played_state {
+                continue;
+            }
+
+            if entry.nag.is_none() {
+                entry.nag = judgment.severity.map(Annotation::from_severity);
+            }
+            entry.review = Some(judgment);
+        }
+        self.review_job = None;
+
+        if self.is_report_pending {
+            self.is_report_pending = false;
+            self.write_report();
+        }
+    }
+
+    /// Writes a report on the game in the history, reviewing it first unless every move already
+    /// has a review verdict
+    pub fn export_report(&mut self) {
+        let needs_review = self.state_history.windows(2).any(|pair| {
+            pair[1].actions_from_prev.is_some()
+                && pair[1].review.is_none()
+                && pair[0].state.get_winner().is_none()
+        });
+        if !needs_review {
+            self.write_report();
+            return;
+        }
+
+        if self.review_job.is_none() {
+            self.start_review();
+        }
+        self.is_report_pending = true;
+        self.report_status = Some("Reviewing the game before writing the report".to_owned());
+    }
+
+    fn write_report(&mut self) {
+        let moves = self
+            .state_history
+            .windows(2)
+            .filter(|pair| pair[1].actions_from_prev.is_some())
+            .map(|pair| ReportMove {
+                parent: pair[0].state.clone(),
+                child: pair[1].state.clone(),
+                comment: pair[1].comment.clone(),
+                judgment: pair[1].review.clone(),
+            })
+            .collect::<Vec<_>>();
+        let report = GameReport {
+            title: "Game report".to_owned(),
+            result: GameReport::result_after(&moves),
+            moves,
+        };
+        self.report_status = Some(match report.save(&self.report_path) {
+            Ok(()) => format!("Wrote a report on {} moves", report.moves.len()),
+            Err(err) => format!("Failed to write report: {}", err),
+        });
+    }
+
+    /// Starts searching for the best answer to the move from the current position to
+    /// `forced_state`
+    pub fn start_refutation(&mut self, forced_state: FullGameState) {
+        self.refutation_job = Some(RefutationJob::start(self.state.clone(), forced_state));
+    }
+
+    /// Shows the answer to an "Ask engine about this move" request, while still in that position
+    fn show_refutation(&mut self, ui: &mut Ui) {
+        let Some(job) = &self.refutation_job else {
+            return;
+        };
+        if job.state != self.state {
+            return;
+        }
+
+        let mut is_dismissed = false;
+        ui.horizontal_wrapped(|ui| {
+            match &*job.result.lock() {
+                None => {
+                    ui.spinner();
+                    ui.label("Asking engine about this move...");
+                }
+                Some(Err(err)) => {
+                    ui.label(format!("Couldn't refute move: {}", err));
+                }
+                Some(Ok(refutation)) => {
+                    let line: Vec<&str> = refutation
+                        .line
+                        .iter()
+                        .map(|step| step.action_str.as_str())
+                        .collect();
+                    let answer = match line.is_empty() {
+                        true => "Game over".to_owned(),
+                        false => format!("Best answer: {}", line.join(" ")),
+                    };
+                    ui.label(format!(
+                        "If {}: {} (depth {}). {}",
+                        refutation.move_str,
+                        score_text(refutation.score),
+                        refutation.depth,
+                        answer
+                    ));
+                }
+            }
+            is_dismissed = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+        });
+
+        if is_dismissed {
+            self.refutation_job = None;
+        }
+    }
+
+    /// Offers to prove a forced win reported by the engine, and shows the proven line
+    fn show_proof(&mut self, ui: &mut Ui) {
+        let job = self
+            .proof_job
+            .as_ref()
+            .filter(|job| job.state == self.state);
+
+        let Some(job) = job else {
+            let reported_win = {
+                let engine = self.engine_thinking.lock();
+                match engine.engine_messages.last() {
+                    Some((message, _)) if engine.state == self.state => (message.score
+                        >= WINNING_SCORE_BUFFER)
+                        .then(|| (WINNING_SCORE - message.score) as usize),
+                    _ => None,
+                }
+            };
+
+            if let Some(plies) = reported_win
+                && ui
+                    .button("Prove Win")
+                    .on_hover_text(format!(
+                        "Search every line to confirm the win in {}. Can be slow",
+                        plies
+                    ))
+                    .clicked()
+            {
+                self.proof_job = Some(ProofJob::start(self.state.clone(), plies));
             }
+            return;
+        };
+
+        let mut is_dismissed = false;
+        ui.horizontal_wrapped(|ui| {
+            match &*job.result.lock() {
+                None => {
+                    ui.spinner();
+                    ui.label(format!("Proving win in {}...", job.max_depth));
+                }
+                Some(None) => {
+                    ui.label(format!("No forced win within {} plies", job.max_depth));
+                }
+                Some(Some(proven)) => {
+                    let line: Vec<String> = proven
+                        .line
+                        .iter()
+                        .map(|step| match step.alternatives_examined {
+                            Some(alternatives) => {
+                                format!("{} ({} tried)", step.action_str, alternatives)
+                            }
+                            None => step.action_str.clone(),
+                        })
+                        .collect();
+                    ui.label(format!(
+                        "Proven win for Player {:?} in {}: {}",
+                        proven.winner,
+                        proven.plies,
+                        line.join(", ")
+                    ));
+                }
+            }
+            is_dismissed = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+        });
+
+        if is_dismissed {
+            self.proof_job = None;
+        }
+    }
+
+    pub fn pin_position(&mut self) {
+        if self.pinned_positions.len() >= MAX_PINNED_POSITIONS {
+            return;
         }
+        self.pinned_count += 1;
+        self.pinned_positions
+            .push(PinnedPosition::new(self.pinned_count, self.state.clone()));
     }
 
     pub fn reset_after_mode_change(&mut self) {
@@ -443,6 +1533,59 @@ impl MyApp {
         };
         self.reset_after_mode_change();
     }
+
+    /// Whole-board editing buttons, shown in the edit modes
+    fn edit_tools_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .button("Randomize position")
+                .on_hover_text("Place workers and build randomly, keeping the current gods")
+                .clicked()
+            {
+                let config = RandomPositionConfig::new(self.state.get_matchup())
+                    .with_build_budget(self.random_build_budget);
+                match FullGameState::random_position(&mut rand::rng(), &config) {
+                    Ok(state) => self.update_state(state),
+                    Err(err) => self.editor_fen_error = Some(err),
+                }
+            }
+            ui.add(
+                egui::DragValue::new(&mut self.random_build_budget)
+                    .range(0..=60)
+                    .prefix("Max builds: "),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            for (symmetry, label, hover_text) in [
+                (
+                    Symmetry::MirrorHorizontal,
+                    "Mirror ↔",
+                    "Swap the A and E columns",
+                ),
+                (
+                    Symmetry::MirrorVertical,
+                    "Mirror ↕",
+                    "Swap the top and bottom rows",
+                ),
+                (
+                    Symmetry::Rotate90,
+                    "Rotate 90°",
+                    "Rotate the board clockwise",
+                ),
+            ] {
+                if ui.button(label).on_hover_text(hover_text).clicked() {
+                    self.update_state(self.state.transform(symmetry));
+                }
+            }
+        });
+
+        if self.edit_mode == EditMode::EditWorkers {
+            for issue in self.state.editor_worker_issues() {
+                ui.colored_label(Color32::ORANGE, format!("Incomplete: {}", issue));
+            }
+        }
+    }
 }
 
 impl Default for MyApp {
@@ -450,26 +1593,62 @@ impl Default for MyApp {
         let default_state = FullGameState::new_empty_state(GodName::Mortal, GodName::Mortal);
         let mut result = Self {
             state: default_state.clone(),
-            state_history: vec![default_state.clone()],
+            state_history: vec![HistoryEntry::new(default_state.clone(), None)],
             state_idx: 0,
+            state_reached_at: Instant::now(),
+            game_record_path: "game_record.txt".to_owned(),
+            game_record_status: None,
+            explorer_path: "explorer.idx".to_owned(),
+            explorer_status: None,
+            report_path: "game_report.md".to_owned(),
+            report_status: None,
+            is_report_pending: false,
+            explorer: None,
             editor_fen_string: game_state_to_fen(&default_state),
             editor_fen_error: None,
             next_states: Default::default(),
             current_actions: Default::default(),
             available_next_actions: Default::default(),
-            engine: EngineThreadWrapper::new(),
+            engine: Box::new(EngineThreadWrapper::new()),
+            search_failure: None,
             engine_thinking: Arc::new(Mutex::new(EngineThinkingState::new(default_state.clone()))),
+            quick_evaluator: QuickEvaluator::new(),
+            review_job: None,
+            refutation_job: None,
+            proof_job: None,
+            placement_scores_job: None,
+            mercy_job: None,
             edit_mode: Default::default(),
+            random_build_budget: 20,
             may_show_wip_gods: Default::default(),
+            is_fast_analysis: false,
+            is_engine_subprocess: false,
+            engine_subprocess_path: String::new(),
             // Autoplay
             is_autoplay_enabled: false,
             is_autoplay_per_player: [true; 2],
+            is_autoplay_merciful: false,
             autoplay_speed_secs: 1.0,
             autoplay_last_status_change_time: Instant::now(),
+            autoplay_stop_conditions: Default::default(),
+            autoplay_moves_made: 0,
+            autoplay_stop_reason: None,
 
             god_selector_bufs: Default::default(),
+
+            shortcuts: Default::default(),
+            shortcut_settings: Default::default(),
+
+            pinned_positions: Vec::new(),
+            pinned_count: 0,
+
+            engine_battle_viewer: Default::default(),
+            position_list: Default::default(),
+
+            pv_preview: None,
         };
 
+        result.wake_on_search_failure();
         result.update_state(result.state.clone());
 
         result
@@ -481,199 +1660,396 @@ struct GameGrid<'a> {
 }
 
 impl<'a> GameGrid<'a> {
+    /// Steps `square` through the workers the gods allow there, backwards for a secondary click
     fn _edit_workers(&mut self, square: Square, forward: bool) {
-        let mut new_state = self.app.state.clone();
+        let state = &self.app.state;
+        let options = state.editor_worker_options(square);
+        let current = state.editor_worker_at(square);
+        let current_idx = options.iter().position(|x| *x == current).unwrap_or(0) as i32;
+        let new_idx = if forward {
+            (current_idx + 1).rem_euclid(options.len() as i32)
+        } else {
+            (current_idx - 1).rem_euclid(options.len() as i32)
+        };
 
-        for _ in 0..2 {
-            let current_worker = new_state.board.get_worker_at(square);
-            let new_worker = next_worker_rotation(current_worker, forward);
+        let new_state = state.with_editor_worker(square, options[new_idx as usize]);
+        self.app.update_state_from_edit(new_state);
+    }
+}
 
-            if let Some(current_worker) = current_worker {
-                new_state
-                    .board
-                    .worker_xor(current_worker, BitBoard::as_mask(square));
-            }
+pub(crate) fn score_text(score: Heuristic) -> String {
+    match score {
+        x @ WINNING_SCORE_BUFFER.. => format!("Win in {}", WINNING_SCORE - x),
+        x if x < -WINNING_SCORE_BUFFER => format!("Loss in {}", WINNING_SCORE + x),
+        x => format!("{}", x),
+    }
+}
 
-            if let Some(new_worker) = new_worker {
-                new_state
-                    .board
-                    .worker_xor(new_worker, BitBoard::as_mask(square));
+/// Table of engine results, newest first
+/// Returns the index of the row whose action was clicked, if any
+fn engine_messages_grid(
+    ui: &mut Ui,
+    id_salt: &str,
+    rows: &[(BestSearchResult, Duration)],
+) -> Option<usize> {
+    let mut clicked_idx = None;
+    egui::Grid::new(id_salt).striped(true).show(ui, |ui| {
+        ui.label("Depth/Sel");
+        ui.label("Action");
+        ui.label("Score");
+        ui.label("Secs");
+        ui.label("Nodes");
+        ui.label("NPS");
+        ui.label("TT hits");
+        ui.label("Type");
+        ui.end_row();
+
+        for (idx, (msg, dur)) in rows.iter().enumerate().rev() {
+            ui.label(format!("{}/{}", msg.depth, msg.seldepth));
+            if ui
+                .selectable_label(false, &msg.action_str)
+                .on_hover_text(format!("Step through this line: {} moves", msg.pv.len()))
+                .clicked()
+            {
+                clicked_idx = Some(idx);
             }
-            if new_state.representation_err().is_ok() {
-                break;
+            ui.label(score_text(msg.score));
+            ui.label(format!("{:.2}", dur.as_secs_f32()));
+            ui.label(format!("{}", msg.nodes_visited));
+            ui.label(
+                msg.nodes_per_second(dur.as_secs_f32())
+                    .map_or("-".to_owned(), |nps| format!("{:.0}", nps)),
+            );
+            ui.label(
+                msg.tt_hit_rate()
+                    .map_or("-".to_owned(), |rate| format!("{:.0}%", rate * 100.0)),
+            );
+            ui.label(format!("{:?}", msg.trigger));
+            ui.end_row();
+        }
+    });
+    clicked_idx
+}
+
+/// Sends `text` to screen readers, for changes that no focused widget describes
+fn announce(ui: &Ui, text: String) {
+    ui.ctx().output_mut(|output| {
+        output.events.push(egui::output::OutputEvent::ValueChanged(
+            egui::WidgetInfo::labeled(egui::WidgetType::Label, true, text),
+        ))
+    });
+}
+
+/// Draws `render_state` and the board legend into the available space. `square_action` picks the
+/// interaction offered on each square. Returns the response of each square
+fn draw_board(
+    ui: &mut Ui,
+    render_state: &FullGameState,
+    mut square_action: impl FnMut(Square) -> Option<PartialAction>,
+) -> (Response, Vec<(Square, Response, Option<PartialAction>)>) {
+    let desired_size = ui.available_size();
+    let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
+    let full_width = rect.width();
+    let full_height = rect.height();
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(132, 206, 235));
+
+    let max_dim = full_height.min(full_width) * 0.99;
+    let legend_dim = max_dim * 0.05;
+
+    let full_grid_dim = max_dim - legend_dim;
+
+    let full_float_x = (full_width - max_dim) / 2.0;
+    let full_float_y = (full_height - max_dim) / 2.0;
+
+    let full_float_pos = rect.min + egui::Vec2::new(full_float_x, full_float_y);
+    let grid_float_pos = full_float_pos + egui::Vec2::new(legend_dim, 0.0);
+
+    let bound_dim = full_grid_dim / 5.0;
+    let size = egui::vec2(bound_dim, bound_dim);
+
+    let (p1_tokens, p2_tokens) = render_state.get_token_squares();
+
+    let mut squares = Vec::with_capacity(25);
+    for r in 0..5 {
+        for c in 0..5 {
+            let square = Square::from_col_row(c, r);
+            let ui_action = square_action(square);
+
+            let token = if p1_tokens.contains_square(square) {
+                Some(Player::One)
+            } else if p2_tokens.contains_square(square) {
+                Some(Player::Two)
+            } else {
+                None
+            };
+
+            let square_space = SquareSpace {
+                worker: render_state.board.get_worker_at(square),
+                token,
+                height: render_state.board.get_height(square),
+                dim: bound_dim,
+                ui_action: ui_action.clone(),
+                description: square_description(render_state, square, token, ui_action.as_ref()),
+            };
+
+            let point =
+                grid_float_pos + egui::Vec2::new(c as f32 * bound_dim, r as f32 * bound_dim);
+
+            let mut placed_square = ui.put(egui::Rect::from_min_size(point, size), square_space);
+            if let Some(ui_action) = &ui_action {
+                placed_square = placed_square.on_hover_text(partial_action_label(ui_action));
             }
+            squares.push((square, placed_square, ui_action));
         }
+    }
 
-        self.app.update_state(new_state);
+    let legend_font = egui::FontId::monospace(max_dim / 24.0);
+
+    for r in 0..5 {
+        let text = format!("{}", 5 - r);
+        let text_pos = full_float_pos + egui::vec2(legend_dim / 2.0, (r as f32 + 0.5) * bound_dim);
+        painter.text(
+            text_pos,
+            egui::Align2::CENTER_CENTER,
+            text,
+            legend_font.clone(),
+            egui::Color32::BLACK,
+        );
+    }
+
+    for c in 0..5 {
+        let text = format!("{}", (b'A' + c as u8) as char);
+        let text_pos = full_float_pos
+            + egui::vec2(
+                legend_dim + (c as f32 + 0.5) * bound_dim,
+                max_dim - legend_dim / 2.0,
+            );
+        painter.text(
+            text_pos,
+            egui::Align2::CENTER_CENTER,
+            text,
+            legend_font.clone(),
+            egui::Color32::BLACK,
+        );
     }
+
+    (response, squares)
 }
 
 impl<'a> egui::Widget for GameGrid<'a> {
     fn ui(mut self, ui: &mut egui::Ui) -> egui::Response {
+        if self.app.is_autoplay_enabled {
+            self.app.autoplay_stop_reason = None;
+        } else {
+            self.app.autoplay_moves_made = 0;
+        }
+
         if self.app.is_autoplay_enabled
             && get_acting_player(&self.app.state)
                 .is_ok_and(|p| self.app.is_autoplay_per_player[p as usize])
         {
-            let elapsed_secs = self
+            if let Some(reason) = self
                 .app
-                .autoplay_last_status_change_time
-                .elapsed()
-                .as_secs_f32();
-            if elapsed_secs > self.app.autoplay_speed_secs {
-                self.app.try_engine_move();
-            } else if elapsed_secs > 0.1 {
-                self.app.try_engine_move_if_end_of_line();
-            }
-        }
-
-        let desired_size = ui.available_size();
-        let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
-        let full_width = rect.width();
-        let full_height = rect.height();
-
-        let painter = ui.painter_at(rect);
-        painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(132, 206, 235));
-
-        let max_dim = full_height.min(full_width) * 0.99;
-        let legend_dim = max_dim * 0.05;
-
-        let full_grid_dim = max_dim - legend_dim;
-
-        let full_float_x = (full_width - max_dim) / 2.0;
-        let full_float_y = (full_height - max_dim) / 2.0;
-
-        let full_float_pos = rect.min + egui::Vec2::new(full_float_x, full_float_y);
-        let grid_float_pos = full_float_pos + egui::Vec2::new(legend_dim, 0.0);
+                .autoplay_stop_conditions
+                .before_move(self.app.current_engine_score())
+            {
+                self.app.stop_autoplay(reason);
+            } else {
+                let elapsed_secs = self
+                    .app
+                    .autoplay_last_status_change_time
+                    .elapsed()
+                    .as_secs_f32();
+                if elapsed_secs > self.app.autoplay_speed_secs {
+                    self.app.try_engine_move();
+                } else if elapsed_secs > 0.1 {
+                    self.app.try_engine_move_if_end_of_line();
+                }
+            }
+        }
 
-        let bound_dim = full_grid_dim / 5.0;
-        let size = egui::vec2(bound_dim, bound_dim);
+        if let Some(preview) = &self.app.pv_preview {
+            let (response, _) = draw_board(ui, preview.state(), |_| None);
+            // A dimmed border, so that the preview is never mistaken for the game
+            ui.painter().rect_stroke(
+                response.rect,
+                0.0,
+                Stroke::new(8.0_f32, PREVIEW_BORDER_COLOR),
+                egui::StrokeKind::Inside,
+            );
+            return response;
+        }
 
         let render_state =
             game_state_with_partial_actions(&self.app.state, &self.app.current_actions);
+        let edit_mode = self.app.edit_mode;
+        let placement_scores = match edit_mode {
+            EditMode::Play => self.app.placement_square_scores(&render_state),
+            _ => [None; 25],
+        };
+        let (response, squares) = draw_board(ui, &render_state, |square| {
+            if edit_mode == EditMode::Play {
+                self.app.get_action_for_square(square)
+            } else {
+                None
+            }
+        });
 
-        let (p1_tokens, p2_tokens) = render_state.get_token_squares();
-
-        for r in 0..5 {
-            for c in 0..5 {
-                let square = Square::from_col_row(c, r);
-                let ui_action = if self.app.edit_mode == EditMode::Play {
-                    self.app.get_action_for_square(square)
-                } else {
-                    None
-                };
+        // Outline the squares that can stop the opponent's threatened wins
+        let key_squares = match edit_mode {
+            EditMode::Play if self.app.state.validation_err().is_ok() => {
+                self.app.state.defensive_key_squares()
+            }
+            _ => None,
+        }
+        .unwrap_or(BitBoard::EMPTY);
+
+        // Arrow keys move keyboard focus between squares. Tab and Enter work like on any widget
+        if let Some((focused_square, _, _)) = squares.iter().find(|(_, r, _)| r.has_focus()) {
+            let next_square = ui.input_mut(|i| {
+                [
+                    Key::ArrowLeft,
+                    Key::ArrowRight,
+                    Key::ArrowUp,
+                    Key::ArrowDown,
+                ]
+                .into_iter()
+                .find(|&key| i.consume_key(Modifiers::NONE, key))
+                .and_then(|key| square_in_arrow_direction(*focused_square, key))
+            });
+            if let Some((_, next_response, _)) =
+                squares.iter().find(|(s, _, _)| Some(*s) == next_square)
+            {
+                next_response.request_focus();
+            }
+        }
 
-                let token = if p1_tokens.contains_square(square) {
-                    Some(Player::One)
-                } else if p2_tokens.contains_square(square) {
-                    Some(Player::Two)
-                } else {
-                    None
-                };
+        let best_placement = placement_scores.iter().flatten().max().copied();
+        let worst_placement = placement_scores.iter().flatten().min().copied();
+        for (square, placed_square, ui_action) in squares {
+            let placed_square = match key_squares.contains_square(square) {
+                true => {
+                    ui.painter().rect_stroke(
+                        placed_square.rect,
+                        0.0,
+                        Stroke::new(3.0_f32, Color32::ORANGE),
+                        egui::StrokeKind::Inside,
+                    );
+                    placed_square.on_hover_text(
+                        "Key square: acting here may stop the opponent's threatened win",
+                    )
+                }
+                false => placed_square,
+            };
 
-                let square_space = SquareSpace {
-                    worker: render_state.board.get_worker_at(square),
-                    token,
-                    height: render_state.board.get_height(square),
-                    dim: bound_dim,
-                    ui_action: ui_action.clone(),
-                };
+            let placed_square = match (
+                placement_scores[square as usize],
+                best_placement,
+                worst_placement,
+            ) {
+                (Some(score), Some(best), Some(worst)) => {
+                    ui.painter().rect_filled(
+                        placed_square.rect,
+                        0.0,
+                        placement_tint(score, worst, best),
+                    );
+                    placed_square
+                        .on_hover_text(format!("Best placement here: {}", score_text(score)))
+                }
+                _ => placed_square,
+            };
 
-                let point =
-                    grid_float_pos + egui::Vec2::new(c as f32 * bound_dim, r as f32 * bound_dim);
+            if let Some(action) = ui_action
+                && edit_mode == EditMode::Play
+            {
+                placed_square.context_menu(|ui| {
+                    let mut prefix = self.app.current_actions.clone();
+                    prefix.push(action);
+                    // The same move can be reachable through different action orders
+                    let mut completions: Vec<FullGameState> = Vec::new();
+                    for next_state in &self.app.next_states {
+                        let is_match = (action == PartialAction::EndTurn
+                            && next_state.actions == self.app.current_actions)
+                            || next_state.actions.starts_with(&prefix);
+                        if is_match && !completions.contains(&next_state.state) {
+                            completions.push(next_state.state.clone());
+                        }
+                    }
 
-                let mut placed_square =
-                    ui.put(egui::Rect::from_min_size(point, size), square_space);
-                if let Some(ui_action) = ui_action {
-                    placed_square = placed_square.on_hover_text(partial_action_label(&ui_action));
-                }
+                    let response = ui.add_enabled(
+                        completions.len() == 1,
+                        egui::Button::new("Ask engine about this move"),
+                    );
+                    if response.clicked() {
+                        self.app.start_refutation(completions.remove(0));
+                        ui.close();
+                    }
+                    response.on_disabled_hover_text(format!(
+                        "This action starts {} different moves. Pick more of the move first",
+                        completions.len()
+                    ));
+                });
+            }
 
-                if placed_square.clicked() {
-                    match self.app.edit_mode {
-                        EditMode::Play => {
-                            if let Some(action) = ui_action {
-                                self.app.accept_action(action);
-                            }
+            if placed_square.clicked() {
+                match self.app.edit_mode {
+                    EditMode::Play => {
+                        if let Some(action) = ui_action {
+                            let ply_before = self.app.state.ply;
+                            self.app.accept_action(action);
+                            announce(ui, self.app.action_announcement(action, ply_before));
                         }
-                        EditMode::EditHeights => {
-                            let mut new_state = self.app.state.clone();
-
-                            for _ in 0..2 {
-                                let current_height = new_state.board.get_height(square);
-                                if current_height == 4 {
-                                    new_state.board.undome(square, 0);
-                                } else {
-                                    new_state.board.build_up(square);
-                                }
-                                if new_state.representation_err().is_ok() {
-                                    break;
-                                }
+                    }
+                    EditMode::EditHeights => {
+                        let mut new_state = self.app.state.clone();
+
+                        for _ in 0..2 {
+                            let current_height = new_state.board.get_height(square);
+                            if current_height == 4 {
+                                new_state.board.undome(square, 0);
+                            } else {
+                                new_state.board.build_up(square);
+                            }
+                            if new_state.representation_err().is_ok() {
+                                break;
                             }
-                            self.app.update_state(new_state);
-                        }
-                        EditMode::EditWorkers => {
-                            self._edit_workers(square, true);
                         }
+                        self.app.update_state_from_edit(new_state);
                     }
-                } else if placed_square.secondary_clicked() {
-                    match self.app.edit_mode {
-                        EditMode::EditHeights => {
-                            let mut new_state = self.app.state.clone();
-
-                            for _ in 0..2 {
-                                let current_height = new_state.board.get_height(square);
-                                if current_height == 0 {
-                                    new_state.board.dome_up(square);
-                                } else if current_height == 4 {
-                                    new_state.board.undome(square, 3);
-                                } else {
-                                    new_state.board.unbuild(square);
-                                }
-                                if new_state.representation_err().is_ok() {
-                                    break;
-                                }
+                    EditMode::EditWorkers => {
+                        self._edit_workers(square, true);
+                    }
+                }
+            } else if placed_square.secondary_clicked() {
+                match self.app.edit_mode {
+                    EditMode::EditHeights => {
+                        let mut new_state = self.app.state.clone();
+
+                        for _ in 0..2 {
+                            let current_height = new_state.board.get_height(square);
+                            if current_height == 0 {
+                                new_state.board.dome_up(square);
+                            } else if current_height == 4 {
+                                new_state.board.undome(square, 3);
+                            } else {
+                                new_state.board.unbuild(square);
+                            }
+                            if new_state.representation_err().is_ok() {
+                                break;
                             }
-                            self.app.update_state(new_state);
-                        }
-                        EditMode::EditWorkers => {
-                            self._edit_workers(square, false);
                         }
-                        _ => {}
+                        self.app.update_state_from_edit(new_state);
                     }
+                    EditMode::EditWorkers => {
+                        self._edit_workers(square, false);
+                    }
+                    _ => {}
                 }
             }
         }
 
-        let legend_font = egui::FontId::monospace(max_dim / 24.0);
-
-        for r in 0..5 {
-            let text = format!("{}", 5 - r);
-            let text_pos =
-                full_float_pos + egui::vec2(legend_dim / 2.0, (r as f32 + 0.5) * bound_dim);
-            painter.text(
-                text_pos,
-                egui::Align2::CENTER_CENTER,
-                text,
-                legend_font.clone(),
-                egui::Color32::BLACK,
-            );
-        }
-
-        for c in 0..5 {
-            let text = format!("{}", (b'A' + c as u8) as char);
-            let text_pos = full_float_pos
-                + egui::vec2(
-                    legend_dim + (c as f32 + 0.5) * bound_dim,
-                    max_dim - legend_dim / 2.0,
-                );
-            painter.text(
-                text_pos,
-                egui::Align2::CENTER_CENTER,
-                text,
-                legend_font.clone(),
-                egui::Color32::BLACK,
-            );
-        }
-
         response
     }
 }
@@ -684,6 +2060,8 @@ struct SquareSpace {
     token: Option<Player>,
     height: usize,
     ui_action: Option<PartialAction>,
+    /// Read out by screen readers
+    description: String,
 }
 
 fn unit_color(player: Player) -> egui::Color32 {
@@ -699,6 +2077,9 @@ impl egui::Widget for SquareSpace {
         let height = width;
         let (rect, mut response) =
             ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::click());
+        response.widget_info(|| {
+            egui::WidgetInfo::labeled(egui::WidgetType::Button, ui.is_enabled(), &self.description)
+        });
         let mut painter = ui.painter_at(rect);
 
         painter.rect_filled(rect, 1.0, egui::Color32::LIGHT_GREEN);
@@ -801,6 +2182,15 @@ impl egui::Widget for SquareSpace {
             ));
         }
 
+        if response.has_focus() {
+            painter.rect_stroke(
+                rect,
+                0.0,
+                Stroke::new(width / 20.0, BOARD_FOCUS_COLOR),
+                egui::StrokeKind::Inside,
+            );
+        }
+
         painter.set_opacity(0.4);
         if let Some(ui_action) = self.ui_action {
             let color = partial_action_color(&ui_action);
@@ -818,7 +2208,7 @@ struct GodChanger<'a> {
 }
 
 fn ordered_god_names() -> Vec<GodName> {
-    let mut god_names: Vec<GodName> = ALL_GODS_BY_ID.iter().map(|g| g.god_name).collect();
+    let mut god_names: Vec<GodName> = GodName::all().collect();
     god_names.sort_by_key(|g| {
         (
             if *g == GodName::Mortal { 0 } else { 1 },
@@ -845,21 +2235,33 @@ impl<'a> egui::Widget for GodChanger<'a> {
             let available_gods_iter = ordered_gods
                 .iter()
                 .cloned()
-                .filter(|g| self.app.may_show_wip_gods || !WIP_GODS.contains(&g));
+                .filter(|g| self.app.may_show_wip_gods || g.support_level() != GodSupport::Wip);
 
-            ui.add(dropdown::DropdownComboBox::<GodName, _, _>::new(
+            let combo_response = ui.add(dropdown::DropdownComboBox::<GodName, _, _>::new(
                 text.to_string(),
                 &mut self.app.god_selector_bufs[self.player as usize],
                 available_gods_iter,
                 &mut selected,
                 |god_name| {
-                    if WIP_GODS.contains(god_name) {
+                    if god_name.support_level() == GodSupport::Wip {
                         format!("{:?} (WIP)", god_name)
                     } else {
                         format!("{:?}", god_name)
                     }
                 },
-            ))
+            ));
+
+            let info_response = ui
+                .small_button("ℹ")
+                .on_hover_text(format!("About {:?}", selected));
+            egui::Popup::from_toggle_button_response(&info_response)
+                .width(320.0)
+                .show(|ui| {
+                    ui.strong(format!("{:?}", selected));
+                    ui.label(god_description(selected));
+                });
+
+            combo_response
         });
 
         if selected != before {
@@ -878,54 +2280,137 @@ struct EvalBar<'a> {
     app: &'a MyApp,
 }
 
+/// How long the bar takes to slide to a new eval
+const EVAL_BAR_ANIMATION_SECS: f32 = 0.25;
+const EVAL_BAR_MATE_COLOR: Color32 = Color32::from_rgb(230, 160, 40);
+const EVAL_BAR_DELTA_COLOR: Color32 = Color32::from_rgb(80, 160, 255);
+
+/// Short eval for the bar: "M5" when Player One mates in 5, "-M5" when Player Two does
+fn eval_bar_text(eval_for_p1: Heuristic) -> String {
+    match eval_for_p1 {
+        x @ WINNING_SCORE_BUFFER.. => format!("M{}", WINNING_SCORE - x),
+        x if x < -WINNING_SCORE_BUFFER => format!("-M{}", WINNING_SCORE + x),
+        x => format!("{}", x),
+    }
+}
+
+fn eval_bar_pct(eval_for_p1: Heuristic) -> f32 {
+    sigmoid(eval_for_p1 as f32 / 400.0)
+}
+
 impl<'a> egui::Widget for EvalBar<'a> {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
-        let mut eval_for_p1 = 0;
-        if let Some(winner) = self.app.state.get_winner() {
-            eval_for_p1 = match winner {
-                Player::One => 10_000,
-                Player::Two => -10_000,
-            }
-        } else {
-            let engine = self.app.engine_thinking.lock();
-            if let Ok(active_player) = get_acting_player(&engine.state) {
-                if let Some(message) = engine.engine_messages.last() {
-                    eval_for_p1 = match active_player {
-                        Player::One => message.0.score,
-                        Player::Two => -message.0.score,
-                    }
-                }
-            }
-        }
+        let (eval_for_p1, prev_eval_for_p1) = self.app.eval_bar_scores();
 
         let desired_size = ui.available_size();
         let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
         let full_width = rect.width();
         let full_height = rect.height();
+        let x_range = Rangef::new(rect.min.x, rect.min.x + full_width);
 
-        let painter = ui.painter_at(rect);
-        painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(23, 23, 23));
+        let mate_winner = eval_for_p1.and_then(|eval| match eval {
+            WINNING_SCORE_BUFFER.. => Some(Player::One),
+            x if x < -WINNING_SCORE_BUFFER => Some(Player::Two),
+            _ => None,
+        });
+        let (background_color, fill_color) = match mate_winner {
+            Some(Player::One) => (Color32::from_rgb(23, 23, 23), EVAL_BAR_MATE_COLOR),
+            Some(Player::Two) => (EVAL_BAR_MATE_COLOR, Color32::LIGHT_GRAY),
+            None => (Color32::from_rgb(23, 23, 23), Color32::LIGHT_GRAY),
+        };
+
+        // egui steps the animation by the frame time, and keeps repainting until it settles
+        let pct = ui.ctx().animate_value_with_time(
+            response.id.with("eval"),
+            eval_bar_pct(eval_for_p1.unwrap_or_default()),
+            EVAL_BAR_ANIMATION_SECS,
+        );
 
-        let pct = sigmoid(eval_for_p1 as f32 / 400.0);
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, background_color);
 
         let eval_height = pct * full_height;
         let eval_rect = egui::Rect::from_min_size(rect.min, egui::vec2(full_width, eval_height));
 
-        painter.rect_filled(eval_rect, 0.0, egui::Color32::LIGHT_GRAY);
+        painter.rect_filled(eval_rect, 0.0, fill_color);
 
         painter.hline(
-            Rangef::new(rect.min.x, rect.min.x + full_width),
+            x_range,
             rect.min.y + full_height / 2.0,
-            Stroke::new(1.0, egui::Color32::RED),
+            Stroke::new(1.0_f32, Color32::RED),
         );
 
-        response
+        if let Some(prev_eval) = prev_eval_for_p1 {
+            let prev_y = rect.min.y + eval_bar_pct(prev_eval) * full_height;
+            painter.hline(x_range, prev_y, Stroke::new(2.0_f32, EVAL_BAR_DELTA_COLOR));
+        }
+
+        if let Some(eval) = eval_for_p1 {
+            // Label the side that's ahead, at its end of the bar
+            let font = egui::FontId::proportional((full_width / 3.5).clamp(8.0, 14.0));
+            let (anchor, pos, text_color) = if eval >= 0 {
+                (
+                    egui::Align2::CENTER_TOP,
+                    rect.center_top() + egui::vec2(0.0, 2.0),
+                    background_color,
+                )
+            } else {
+                (
+                    egui::Align2::CENTER_BOTTOM,
+                    rect.center_bottom() - egui::vec2(0.0, 2.0),
+                    fill_color,
+                )
+            };
+            painter.text(pos, anchor, eval_bar_text(eval), font, text_color);
+        }
+
+        let hover_text = match (eval_for_p1, prev_eval_for_p1) {
+            (Some(eval), Some(prev_eval)) => format!(
+                "{} (was {} before the last move)",
+                score_text(eval),
+                score_text(prev_eval)
+            ),
+            (Some(eval), None) => score_text(eval),
+            (None, _) => "Not evaluated yet".to_owned(),
+        };
+        response.on_hover_text(hover_text)
     }
 }
 
+/// A tiny board with `squares` highlighted, for god data that marks squares
+fn mini_board(ui: &mut Ui, squares: BitBoard) -> Response {
+    const CELL_SIZE: f32 = 8.0;
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(
+            CELL_SIZE * BOARD_WIDTH as f32,
+            CELL_SIZE * BOARD_WIDTH as f32,
+        ),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    for idx in 0..NUM_SQUARES {
+        let cell = egui::Rect::from_min_size(
+            rect.min
+                + egui::vec2(
+                    (idx % BOARD_WIDTH) as f32 * CELL_SIZE,
+                    (idx / BOARD_WIDTH) as f32 * CELL_SIZE,
+                ),
+            egui::vec2(CELL_SIZE, CELL_SIZE),
+        );
+        let color = if squares.contains_square(Square::from(idx)) {
+            Color32::YELLOW
+        } else {
+            Color32::DARK_GRAY
+        };
+        painter.rect_filled(cell.shrink(0.5), 0.0, color);
+    }
+    response
+}
+
 struct PlayerInfo<'a> {
     player: Player,
     state: &'a FullGameState,
+    result: Option<GameResult>,
 }
 
 impl<'a> egui::Widget for PlayerInfo<'a> {
@@ -937,9 +2422,19 @@ impl<'a> egui::Widget for PlayerInfo<'a> {
             self.player, self.state.gods[self.player as usize].god_name
         );
 
-        if let Some(winner) = self.state.get_winner() {
-            if winner == self.player {
-                header_text += " (Winner!)";
+        if let Some(result) = self.result {
+            match result {
+                GameResult::Win(winner, Some(reason)) if winner == self.player => {
+                    header_text += &format!(" (Winner! - {reason})")
+                }
+                GameResult::Win(winner, None) if winner == self.player => {
+                    header_text += " (Winner!)"
+                }
+                GameResult::Win(_, Some(WinReason::OpponentSmothered)) => {
+                    header_text += " (No moves)"
+                }
+                GameResult::Win(..) => (),
+                GameResult::Draw(reason) => header_text += &format!(" (Draw - {reason})"),
             }
         } else if get_acting_player(&self.state) == Ok(self.player) {
             header_text += " (To Play)";
@@ -947,17 +2442,35 @@ impl<'a> egui::Widget for PlayerInfo<'a> {
         let resp = ui.heading(header_text);
 
         let god = self.state.gods[self.player as usize];
-        if let Some(text) = god.pretty_stringify_god_data(&self.state.board, self.player) {
-            ui.label(text);
+        for item in god.god_data_items(&self.state.board, self.player) {
+            match &item {
+                GodDataItem::Counter {
+                    name,
+                    value,
+                    max: Some(max),
+                } if *max > 0 => {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{name}:"));
+                        ui.add(
+                            egui::ProgressBar::new(*value as f32 / *max as f32)
+                                .desired_width(100.0)
+                                .text(format!("{value}/{max}")),
+                        );
+                    });
+                }
+                GodDataItem::Squares { name, bitboard } if bitboard.is_not_empty() => {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{name}:"));
+                        mini_board(ui, *bitboard).on_hover_text(item.to_string());
+                    });
+                }
+                _ => {
+                    ui.label(item.to_string());
+                }
+            }
         }
-
-        if god.god_name == GodName::Morpheus {
-            ui.add(Label::new(
-                RichText::new(
-                    "* Multiple Morpheus builds must start in the NW corner and progress clockwise",
-                )
-                .italics(),
-            ));
+        for note in god_implementation_notes(god.god_name) {
+            ui.label(note);
         }
 
         resp
@@ -965,7 +2478,41 @@ impl<'a> egui::Widget for PlayerInfo<'a> {
 }
 
 impl eframe::App for MyApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SHORTCUTS_STORAGE_KEY, &self.shortcuts);
+        eframe::set_value(
+            storage,
+            AUTOPLAY_STOP_STORAGE_KEY,
+            &self.autoplay_stop_conditions,
+        );
+        let engine_path = self
+            .is_engine_subprocess
+            .then(|| self.engine_subprocess_path.clone());
+        eframe::set_value(storage, ENGINE_PATH_STORAGE_KEY, &engine_path);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.engine_thinking
+            .lock()
+            .repaint_ctx
+            .get_or_insert_with(|| ctx.clone());
+
+        // Runs first, so that a key chord being captured doesn't also trigger its old action
+        self.shortcut_settings.show(ctx, &mut self.shortcuts);
+
+        if self
+            .pv_preview
+            .as_ref()
+            .is_some_and(|preview| preview.start_state() != &self.state)
+        {
+            self.pv_preview = None;
+        }
+
+        if let Some(search_failure) = self.engine.take_search_failure() {
+            self.search_failure = Some(search_failure);
+        }
+        self.show_search_failure_banner(ctx);
+
         egui::SidePanel::right("right_panel")
             .resizable(false)
             .exact_width(450.0)
@@ -973,7 +2520,18 @@ impl eframe::App for MyApp {
                 ui.style_mut().spacing.item_spacing = egui::vec2(8.0, 12.0);
                 let available_size = ui.available_size();
 
-                let scroll_area_height = available_size.y * 0.4;
+                self.update_history_scores();
+                self.apply_finished_review();
+                if let Some(clicked_idx) = show_score_graph(
+                    ui,
+                    &self.state_history,
+                    self.state_idx,
+                    available_size.y * 0.15,
+                ) {
+                    self.try_jump_to_state(clicked_idx);
+                }
+
+                let scroll_area_height = available_size.y * 0.3;
 
                 ui.scope_builder(UiBuilder::new(), |ui| {
                     ui.set_min_height(scroll_area_height);
@@ -982,43 +2540,82 @@ impl eframe::App for MyApp {
                         .min_scrolled_height(scroll_area_height)
                         .max_height(scroll_area_height)
                         .show(ui, |ui| {
-                            egui::Grid::new("Moves").striped(true).show(ui, |ui| {
-                                ui.label("Depth");
-                                ui.label("Action");
-                                ui.label("Score");
-                                ui.label("Secs");
-                                ui.label("Nodes");
-                                ui.label("Type");
-                                ui.end_row();
-
-                                let rows = self.engine_thinking.lock().engine_messages.clone();
-                                for row in rows.iter().rev() {
-                                    let (msg, dur) = row;
-                                    let score_str = match msg.score {
-                                        x @ WINNING_SCORE_BUFFER.. => format!("Win in {}", WINNING_SCORE - x),
-                                        x if x < -WINNING_SCORE_BUFFER => format!("Loss in {}", WINNING_SCORE + x),
-                                        x => format!("{}", x),
-                                    };
-
-                                    ui.label(format!("{}", msg.depth));
-                                    ui.label(msg.action_str.to_owned());
-                                    ui.label(score_str);
-                                    ui.label(format!("{:.2}", dur.as_secs_f32()));
-                                    ui.label(format!("{}", msg.nodes_visited));
-                                    ui.label(format!("{:?}", msg.trigger));
-                                    ui.end_row();
-                                }
-                            });
+                            if let Some(played_move) =
+                                &self.state_history[self.state_idx].played_move
+                            {
+                                ui.label(format!("Last move: {}", played_move.summary()));
+                            }
+                            if let Some(description) =
+                                self.pv_preview.as_ref().map(PvPreview::description)
+                            {
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.label(description);
+                                    if ui
+                                        .button("Return")
+                                        .on_hover_text(
+                                            "Back to the live position. Shortcut: Escape",
+                                        )
+                                        .clicked()
+                                    {
+                                        self.pv_preview = None;
+                                    }
+                                });
+                            }
+                            let (start_state, rows) = {
+                                let engine_thinking = self.engine_thinking.lock();
+                                (
+                                    engine_thinking.state.clone(),
+                                    engine_thinking.engine_messages.clone(),
+                                )
+                            };
+                            if let Some(idx) = engine_messages_grid(ui, "Moves", &rows) {
+                                self.pv_preview = Some(PvPreview::new(&start_state, &rows[idx].0.pv));
+                            }
                         });
                 });
 
-                ui.heading("Controls");
+                self.show_explorer(ui);
+                self.show_refutation(ui);
+                self.show_proof(ui);
+
+                let entry = &mut self.state_history[self.state_idx];
+                if let Some(judgment) = &entry.review {
+                    let verdict = match judgment.severity {
+                        Some(severity) => {
+                            format!("Review: {} (-{})", severity, judgment.score_drop())
+                        }
+                        None => "Review: good move".to_owned(),
+                    };
+                    match &judgment.best_move {
+                        Some(best_move) => {
+                            ui.label(format!("{}. Best was {}", verdict, best_move.action_str))
+                        }
+                        None => ui.label(verdict),
+                    };
+                }
+                ui.add(
+                    egui::TextEdit::multiline(&mut entry.comment)
+                        .hint_text("Comment on this position")
+                        .desired_rows(2)
+                        .desired_width(available_size.x),
+                );
+
+                ui.horizontal(|ui| {
+                    ui.heading("Controls");
+                    if ui
+                        .button("Shortcuts")
+                        .on_hover_text("Change keyboard shortcuts")
+                        .clicked()
+                    {
+                        self.shortcut_settings.is_open = true;
+                    }
+                });
                 ui.horizontal(|ui| {
                     if shortcut_button(
                         ui,
                         "Do Engine Move",
                         "Play the engine move.",
-                        SHORTCUT_ENGINE_MOVE,
+                        self.shortcuts.engine_move,
                     )
                     .clicked()
                     {
@@ -1029,24 +2626,82 @@ impl eframe::App for MyApp {
                         ui,
                         "Restart Turn",
                         "Undo any actions taken this turn.",
-                        SHORTCUT_REDO_TURN,
+                        self.shortcuts.redo_turn,
                     )
                     .clicked()
                     {
                         self.clear_actions();
                     }
 
-                    if shortcut_button(ui, "Back", "Go back a turn", SHORTCUT_STATE_BACKWARD)
+                    let played_move_text = |entry: Option<&HistoryEntry>| {
+                        entry
+                            .and_then(|entry| entry.played_move.as_ref())
+                            .map_or_else(String::new, |played_move| {
+                                format!(" {}.", played_move.summary())
+                            })
+                    };
+                    let back_tooltip = format!(
+                        "Go back a turn.{}",
+                        played_move_text(self.state_history.get(self.state_idx))
+                    );
+                    if shortcut_button(ui, "Back", &back_tooltip, self.shortcuts.state_backward)
                         .clicked()
                     {
                         self.try_back_state();
                     }
 
-                    if shortcut_button(ui, "Forward", "Go forward a turn", SHORTCUT_STATE_FORWARD)
+                    let forward_tooltip = format!(
+                        "Go forward a turn.{}",
+                        played_move_text(self.state_history.get(self.state_idx + 1))
+                    );
+                    if shortcut_button(ui, "Forward", &forward_tooltip, self.shortcuts.state_forward)
                         .clicked()
                     {
                         self.try_forward_state();
                     }
+
+                    match &self.review_job {
+                        Some(job) => {
+                            ui.add(egui::ProgressBar::new(job.progress_fraction()).show_percentage());
+                        }
+                        None => {
+                            if ui
+                                .button("Review Game")
+                                .on_hover_text(format!(
+                                    "Compare every move in the history against the engine's choice at {} nodes, and annotate mistakes",
+                                    REVIEW_NODES_PER_POSITION
+                                ))
+                                .clicked()
+                            {
+                                self.start_review();
+                            }
+                        }
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let last_idx = self.state_history.len() - 1;
+                    let entry = &mut self.state_history[self.state_idx];
+                    let glyph = entry.nag.map_or("", Annotation::glyph);
+                    let move_text = match &entry.actions_from_prev {
+                        Some(actions) => format!("Reached by {:?}", actions),
+                        None => "Not reached by a move".to_owned(),
+                    };
+                    ui.label(format!("Position {}/{} {}", self.state_idx, last_idx, glyph))
+                        .on_hover_text(move_text);
+
+                    for annotation in Annotation::ALL {
+                        if ui
+                            .selectable_label(entry.nag == Some(annotation), annotation.glyph())
+                            .on_hover_text(annotation.description())
+                            .clicked()
+                        {
+                            entry.nag = match entry.nag == Some(annotation) {
+                                true => None,
+                                false => Some(annotation),
+                            };
+                        }
+                    }
                 });
 
                 ui.heading("State Settings");
@@ -1070,7 +2725,7 @@ impl eframe::App for MyApp {
                     self.try_set_editor_fen();
                 }
 
-                ui.horizontal(|ui| {
+                ui.horizontal_wrapped(|ui| {
                     if ui
                         .button("Set Position")
                         .on_hover_text("Set current position to the FEN in the editor above")
@@ -1111,13 +2766,46 @@ impl eframe::App for MyApp {
                         self.update_state(new_state);
                     }
 
+                    if ui
+                        .add_enabled(
+                            self.pinned_positions.len() < MAX_PINNED_POSITIONS,
+                            egui::Button::new("Pin Position"),
+                        )
+                        .on_hover_text(format!(
+                            "Open this position in a separate window. Up to {} at a time",
+                            MAX_PINNED_POSITIONS
+                        ))
+                        .clicked()
+                    {
+                        self.pin_position();
+                    }
+
+                    if ui
+                        .button("Engine Battle")
+                        .on_hover_text("Watch two engine binaries play each other")
+                        .clicked()
+                    {
+                        self.engine_battle_viewer.open();
+                    }
+
+                    if ui
+                        .button("Position List")
+                        .on_hover_text(format!(
+                            "Paste or load a list of FENs to click through. Shortcuts: {} and {}",
+                            shortcut_text_long(self.shortcuts.previous_position),
+                            shortcut_text_long(self.shortcuts.next_position)
+                        ))
+                        .clicked()
+                    {
+                        self.position_list.is_open = !self.position_list.is_open;
+                    }
+
                     if ui
                         .button("Clear History")
                         .on_hover_text("Remove old move history")
                         .clicked()
                     {
-                        self.state_history = self.state_history.split_off(self.state_idx);
-                        self.state_idx = 0;
+                        self.clear_old_history();
                     }
                 });
 
@@ -1125,6 +2813,84 @@ impl eframe::App for MyApp {
                     ui.label(fen_error);
                 }
 
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.game_record_path)
+                            .hint_text("Game record path")
+                            .desired_width(200.0),
+                    );
+
+                    if ui
+                        .button("Save Game")
+                        .on_hover_text("Save the move history, with comments, to the game record path")
+                        .clicked()
+                    {
+                        self.save_game_record();
+                    }
+
+                    if ui
+                        .button("Load Game")
+                        .on_hover_text("Load move history from the game record path")
+                        .clicked()
+                    {
+                        self.load_game_record();
+                    }
+
+                    if ui
+                        .button("Open Game Log")
+                        .on_hover_text("Load a battler game log (.jsonl) from the game record path, with each engine's score and depth for its moves")
+                        .clicked()
+                    {
+                        self.load_game_log();
+                    }
+                });
+
+                if let Some(status) = &self.game_record_status {
+                    ui.label(status);
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.explorer_path)
+                            .hint_text("Explorer index path")
+                            .desired_width(200.0),
+                    );
+
+                    if ui
+                        .button("Load Explorer")
+                        .on_hover_text(
+                            "Load opening statistics built by bullet_prep's explorer-index command",
+                        )
+                        .clicked()
+                    {
+                        self.load_explorer();
+                    }
+                });
+
+                if let Some(status) = &self.explorer_status {
+                    ui.label(status);
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.report_path)
+                            .hint_text("Report path")
+                            .desired_width(200.0),
+                    );
+
+                    if ui
+                        .add_enabled(!self.is_report_pending, egui::Button::new("Export Report"))
+                        .on_hover_text("Write a Markdown report of the game to the report path, with the engine's best moves, your comments and diagrams of blunders. Reviews the game first if needed")
+                        .clicked()
+                    {
+                        self.export_report();
+                    }
+                });
+
+                if let Some(status) = &self.report_status {
+                    ui.label(status);
+                }
+
                 ui.horizontal(|ui| {
                     ui.add(GodChanger {
                         app: self,
@@ -1137,8 +2903,11 @@ impl eframe::App for MyApp {
                     });
                 });
 
-                if WIP_GODS.len() > 0 {
-                    let wip_gods_string = WIP_GODS.iter()
+                let wip_gods: Vec<GodName> = GodName::all()
+                    .filter(|g| g.support_level() == GodSupport::Wip)
+                    .collect();
+                if wip_gods.len() > 0 {
+                    let wip_gods_string = wip_gods.iter()
                         .map(|g| format!("{:?}", g))
                         .collect::<Vec<String>>()
                         .join(", ");
@@ -1148,7 +2917,7 @@ impl eframe::App for MyApp {
                 // MODES
                 ui.heading("Modes") .on_hover_text(format!(
                     "Change UI mode between playing a game, edit board heights, or edit worker locations. Shortcut: {}",
-                    shortcut_text_long(SHORTCUT_CHANGE_MODE)
+                    shortcut_text_long(self.shortcuts.change_mode)
                 ));
                 let before = self.edit_mode;
                 ui.horizontal(|ui| {
@@ -1156,11 +2925,70 @@ impl eframe::App for MyApp {
                     ui.radio_value(&mut self.edit_mode, EditMode::EditHeights, "Edit Height")
                         .on_hover_text("Edit square heights on the game board");
                     ui.radio_value(&mut self.edit_mode, EditMode::EditWorkers, "Edit Worker")
-                        .on_hover_text("Edit worker placements on the game board");
+                        .on_hover_text("Edit worker placements on the game board. Click a square to cycle through the workers its gods allow, including female workers, or right click to cycle backwards");
                 });
                 if before != self.edit_mode {
                     self.reset_after_mode_change();
                 }
+                if self.edit_mode != EditMode::Play {
+                    self.edit_tools_ui(ui);
+                }
+
+                // ENGINE
+                ui.horizontal(|ui| {
+                    ui.heading("Engine");
+                    if ui.checkbox(&mut self.is_fast_analysis, "Fast analysis")
+                        .on_hover_text(format!(
+                            "Stop searching after depth {} or {} seconds, instead of searching until the position changes",
+                            FAST_ANALYSIS_LIMITS.max_depth.unwrap(),
+                            FAST_ANALYSIS_LIMITS.max_millis.unwrap() / 1000,
+                        ))
+                        .changed()
+                    {
+                        self.update_state(self.state.clone());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mut is_changed = ui
+                        .checkbox(&mut self.is_engine_subprocess, "External engine")
+                        .on_hover_text("Analyze in a separate engine binary, talking to it over the battler protocol, instead of in the UI's own process. The engine is restarted if it crashes")
+                        .changed();
+                    if self.is_engine_subprocess {
+                        is_changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut self.engine_subprocess_path)
+                                    .hint_text("Engine path")
+                                    .desired_width(160.0),
+                            )
+                            .lost_focus()
+                            && ui.input(|i| i.key_pressed(Key::Enter));
+                        egui::ComboBox::from_id_salt("engine_subprocess_path")
+                            .selected_text("Pick")
+                            .show_ui(ui, |ui| {
+                                for engine in list_engine_binaries() {
+                                    if ui.selectable_label(false, &engine).clicked() {
+                                        self.engine_subprocess_path = PathBuf::from(BINARY_DIRECTORY)
+                                            .join(engine)
+                                            .display()
+                                            .to_string();
+                                        is_changed = true;
+                                    }
+                                }
+                            });
+                        if ui.button("Restart").clicked() {
+                            is_changed = true;
+                        }
+                        let status = self.engine.status();
+                        ui.colored_label(status.color(), status.to_string());
+                        // Restarts are only attempted while the status is being shown
+                        if matches!(status, BackendStatus::Failed(_)) {
+                            ui.ctx().request_repaint_after(RESTART_BACKOFF);
+                        }
+                    }
+                    if is_changed {
+                        self.apply_engine_settings();
+                    }
+                });
 
                 // AUTOPLAY
                 ui.horizontal(|ui| {
@@ -1168,35 +2996,61 @@ impl eframe::App for MyApp {
                     ui.checkbox(&mut self.is_autoplay_enabled, "Enable Autoplay")
                         .on_hover_text(
                             &format!("When enabled, engine moves will automatically be made after the timeout. Shortcut: {}",
-                                shortcut_text_long(SHORTCUT_AUTOPLAY_START)
+                                shortcut_text_long(self.shortcuts.autoplay_start)
                         ));
                 });
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut self.is_autoplay_per_player[0], "Autoplay for Player 1").on_hover_text("Enable autoplay for Player 1");
                     ui.checkbox(&mut self.is_autoplay_per_player[1], "Autoplay for Player 2").on_hover_text("Enable autoplay for Player 2");
                 });
+                ui.checkbox(&mut self.is_autoplay_merciful, "Mercy")
+                    .on_hover_text("Autoplay passes up winning moves, and won't win on the spot early in the game. For teaching beginners");
                 ui.horizontal(|ui| {
                     ui.label("Autoplay Speed (secs):");
                     ui.add(egui::DragValue::new(&mut self.autoplay_speed_secs).speed(0.1).range(0.1..=30.0 * 60.0));
                 });
+                self.autoplay_stop_conditions.show_settings(ui);
+                if let Some(reason) = &self.autoplay_stop_reason {
+                    ui.colored_label(egui::Color32::ORANGE, format!("Autoplay stopped: {}", reason));
+                }
             });
 
         egui::TopBottomPanel::bottom("character_panel").show(ctx, |ui| {
+            let prev_state = self
+                .state_idx
+                .checked_sub(1)
+                .and_then(|prev_idx| self.state_history.get(prev_idx))
+                .map(|prev_entry| &prev_entry.state);
+            let result = GameResult::from_states(prev_state, &self.state);
+
+            ui.label(format!(
+                "{} ({:.0}% developed)",
+                self.state.phase(),
+                self.state.progress() * 100.0
+            ))
+            .on_hover_text("Game phase, judged from the built levels, domes and worker heights");
+
             ui.columns(2, |columns| {
                 let p1 = PlayerInfo {
                     player: Player::One,
                     state: &self.state,
+                    result,
                 };
                 columns[0].add(p1);
 
                 let p2 = PlayerInfo {
                     player: Player::Two,
                     state: &self.state,
+                    result,
                 };
                 columns[1].add(p2);
             });
         });
 
+        if let Some(state) = self.position_list.show(ctx) {
+            self.show_listed_position(state);
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ctx.options_mut(|options| {
                 let central_panel_size = ui.available_size();
@@ -1206,7 +3060,7 @@ impl eframe::App for MyApp {
                 options.input_options.max_click_duration = 1.0;
             });
 
-            let eval_bar_size = 20.0;
+            let eval_bar_size = 36.0;
             let total_size = ui.available_size();
             if total_size.x <= eval_bar_size {
                 let game_grid = GameGrid { app: self };
@@ -1225,7 +3079,30 @@ impl eframe::App for MyApp {
             }
         });
 
-        let is_no_widget_focused = ctx.memory(|mem| mem.focused().is_none());
+        for pinned_position in &mut self.pinned_positions {
+            pinned_position.show(ctx);
+        }
+        // Dropping a closed window ends its engine thread
+        self.pinned_positions.retain(|p| p.is_open);
+
+        self.engine_battle_viewer.show(ctx, &self.state);
+        let move_delay = Duration::from_secs_f32(self.autoplay_speed_secs);
+        if let Some((next_state, search)) = self.engine_battle_viewer.next_state_to_show(move_delay)
+        {
+            self.update_state(next_state);
+            // The battle engines searched in their own processes, so take their word for the timing
+            if let Some(played_move) = &mut self.state_history[self.state_idx].played_move {
+                played_move.elapsed = search.1;
+                played_move.search = Some(search);
+            }
+        }
+
+        let is_capturing_shortcut = self.shortcut_settings.is_capturing();
+        let is_no_widget_focused =
+            ctx.memory(|mem| mem.focused().is_none()) && !is_capturing_shortcut;
+        // Arrow keys move the cursor while editing comments or fens
+        let may_arrow_shortcuts = is_no_widget_focused && !ctx.wants_keyboard_input();
+        let shortcuts = self.shortcuts.clone();
         ctx.input_mut(|i| {
             if i.consume_shortcut(&egui::KeyboardShortcut::new(Modifiers::CTRL, Key::W)) {
                 let ctx = ctx.clone();
@@ -1234,34 +3111,65 @@ impl eframe::App for MyApp {
                 });
             }
 
-            if is_no_widget_focused {
-                if i.consume_shortcut(&SHORTCUT_ENGINE_MOVE) {
+            if may_arrow_shortcuts && let Some(preview) = &mut self.pv_preview {
+                // Stepping moves through the preview instead of the game while it's open
+                if i.consume_shortcut(&shortcuts.state_forward) {
+                    preview.step_forward();
+                }
+                if i.consume_shortcut(&shortcuts.state_backward) {
+                    preview.step_back();
+                }
+                if i.consume_key(Modifiers::NONE, Key::Escape) {
+                    self.pv_preview = None;
+                }
+            }
+
+            if may_arrow_shortcuts {
+                if i.consume_shortcut(&shortcuts.engine_move) {
                     self.try_engine_move();
                 }
 
-                if i.consume_shortcut(&SHORTCUT_REDO_TURN) {
+                if i.consume_shortcut(&shortcuts.redo_turn) {
                     self.clear_actions();
                 }
 
-                if i.consume_shortcut(&SHORTCUT_STATE_FORWARD) {
+                if i.consume_shortcut(&shortcuts.state_forward) {
                     self.try_forward_state();
                 }
 
-                if i.consume_shortcut(&SHORTCUT_STATE_BACKWARD) {
+                if i.consume_shortcut(&shortcuts.state_backward) {
                     self.try_back_state();
                 }
+            }
 
-                if i.consume_shortcut(&SHORTCUT_CHANGE_MODE) {
+            if is_no_widget_focused {
+                if i.consume_shortcut(&shortcuts.change_mode) {
                     self.rotate_through_mode()
                 }
 
-                if i.consume_shortcut(&SHORTCUT_AUTOPLAY_START) {
+                if i.consume_shortcut(&shortcuts.autoplay_start) {
                     self.is_autoplay_enabled = !self.is_autoplay_enabled;
                 }
+
+                if i.consume_shortcut(&shortcuts.next_position)
+                    && let Some(state) = self.position_list.next()
+                {
+                    self.show_listed_position(state);
+                }
+
+                if i.consume_shortcut(&shortcuts.previous_position)
+                    && let Some(state) = self.position_list.previous()
+                {
+                    self.show_listed_position(state);
+                }
             }
         });
 
-        ctx.request_repaint();
+        // Engine results, search failures and engine battle messages wake the UI up themselves,
+        // everything else that changes without input is polled
+        if self.is_autoplay_enabled || self.has_background_work() {
+            ctx.request_repaint_after(BACKGROUND_POLL_INTERVAL);
+        }
     }
 }
 
@@ -0,0 +1,241 @@
+use eframe::egui;
+use santorini_core::{
+    board::FullGameState,
+    fen::{game_state_to_fen, parse_fen},
+};
+
+/// A position in the position list, with the name it's listed under
+#[derive(Clone)]
+pub struct PositionListEntry {
+    pub name: String,
+    pub state: FullGameState,
+}
+
+/// Splits a line into its FEN and its `# name` comment. FENs mark winners with `#` too, so a
+/// comment has to start the line or follow whitespace
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let line = line.trim();
+    let comment_start = line
+        .char_indices()
+        .find(|&(idx, c)| c == '#' && (idx == 0 || line[..idx].ends_with(char::is_whitespace)));
+    match comment_start {
+        Some((idx, _)) => (line[..idx].trim(), Some(line[idx + 1..].trim())),
+        None => (line, None),
+    }
+}
+
+/// Parses one FEN per line. A line can end with `# name` to name its position, and a line with
+/// only `# name` names the position on the next line. Blank lines are skipped.
+/// Returns the positions that parsed, and an error for each line that didn't
+pub fn parse_position_list(text: &str) -> (Vec<PositionListEntry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut pending_name: Option<String> = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let (fen, name) = split_comment(line);
+        let name = name.map(str::to_owned);
+        if fen.is_empty() {
+            if name.is_some() {
+                pending_name = name;
+            }
+            continue;
+        }
+
+        let name = name.or(pending_name.take());
+        let state = parse_fen(fen).and_then(|state| {
+            state.validation_err()?;
+            Ok(state)
+        });
+        match state {
+            Ok(state) => entries.push(PositionListEntry {
+                name: name.unwrap_or_else(|| format!("Position {}", entries.len() + 1)),
+                state,
+            }),
+            Err(err) => errors.push(format!("Line {}: {}", line_idx + 1, err)),
+        }
+    }
+
+    (entries, errors)
+}
+
+pub fn position_list_to_string(entries: &[PositionListEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{} # {}\n", game_state_to_fen(&entry.state), entry.name))
+        .collect()
+}
+
+/// Side panel listing positions to click through
+pub struct PositionList {
+    pub is_open: bool,
+    entries: Vec<PositionListEntry>,
+    /// The last entry shown, which next and previous step from
+    selected_idx: Option<usize>,
+    paste_buf: String,
+    path: String,
+    status: Option<String>,
+    errors: Vec<String>,
+}
+
+impl Default for PositionList {
+    fn default() -> Self {
+        PositionList {
+            is_open: false,
+            entries: Vec::new(),
+            selected_idx: None,
+            paste_buf: String::new(),
+            path: "positions.txt".to_owned(),
+            status: None,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl PositionList {
+    fn select(&mut self, idx: usize) -> FullGameState {
+        self.selected_idx = Some(idx);
+        self.entries[idx].state.clone()
+    }
+
+    /// Selects the entry after the last one shown, wrapping around to the first
+    pub fn next(&mut self) -> Option<FullGameState> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let idx = self
+            .selected_idx
+            .map_or(0, |idx| (idx + 1) % self.entries.len());
+        Some(self.select(idx))
+    }
+
+    /// Selects the entry before the last one shown, wrapping around to the last
+    pub fn previous(&mut self) -> Option<FullGameState> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let len = self.entries.len();
+        let idx = self
+            .selected_idx
+            .map_or(len - 1, |idx| (idx + len - 1) % len);
+        Some(self.select(idx))
+    }
+
+    fn add_pasted(&mut self) {
+        let (entries, errors) = parse_position_list(&self.paste_buf);
+        self.status = Some(format!("Added {} positions", entries.len()));
+        self.entries.extend(entries);
+        if errors.is_empty() {
+            self.paste_buf.clear();
+        }
+        self.errors = errors;
+    }
+
+    fn save(&mut self) {
+        let text = position_list_to_string(&self.entries);
+        self.status = Some(match std::fs::write(&self.path, text) {
+            Ok(()) => format!("Saved {} positions", self.entries.len()),
+            Err(err) => format!("Failed to save positions: {}", err),
+        });
+    }
+
+    fn load(&mut self) {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => {
+                let (entries, errors) = parse_position_list(&text);
+                self.status = Some(format!("Loaded {} positions", entries.len()));
+                self.entries = entries;
+                self.errors = errors;
+                self.selected_idx = None;
+            }
+            Err(err) => self.status = Some(format!("Failed to load positions: {}", err)),
+        }
+    }
+
+    /// Shows the panel, if it's open. Returns the position to show, if one was clicked
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<FullGameState> {
+        if !self.is_open {
+            return None;
+        }
+
+        let mut clicked_state = None;
+        egui::SidePanel::left("position_list_panel")
+            .resizable(true)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Position List");
+                    if ui.button("Close").clicked() {
+                        self.is_open = false;
+                    }
+                });
+
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.paste_buf)
+                        .hint_text("Paste FENs, one per line. End a line with # name to name it")
+                        .desired_rows(4)
+                        .desired_width(f32::INFINITY),
+                );
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Add Positions")
+                        .on_hover_text("Add the pasted positions to the end of the list")
+                        .clicked()
+                    {
+                        self.add_pasted();
+                    }
+                    if ui.button("Clear List").clicked() {
+                        self.entries.clear();
+                        self.selected_idx = None;
+                        self.errors.clear();
+                        self.status = None;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.path)
+                            .hint_text("Position list path")
+                            .desired_width(120.0),
+                    );
+                    if ui
+                        .button("Save")
+                        .on_hover_text("Save the list to the position list path")
+                        .clicked()
+                    {
+                        self.save();
+                    }
+                    if ui
+                        .button("Load")
+                        .on_hover_text("Replace the list with the one at the position list path")
+                        .clicked()
+                    {
+                        self.load();
+                    }
+                });
+
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+                for error in &self.errors {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for idx in 0..self.entries.len() {
+                        let is_selected = self.selected_idx == Some(idx);
+                        if ui
+                            .selectable_label(is_selected, &self.entries[idx].name)
+                            .on_hover_text(game_state_to_fen(&self.entries[idx].state))
+                            .clicked()
+                        {
+                            clicked_state = Some(self.select(idx));
+                        }
+                    }
+                });
+            });
+
+        clicked_state
+    }
+}
@@ -0,0 +1,94 @@
+use santorini_core::{
+    board::FullGameState, gods::generic::GenericMove, placement::get_starting_placement_state,
+    refute::next_states_with_strings,
+};
+
+/// An engine line, stepped through on a ghost board without touching the game history
+pub struct PvPreview {
+    /// The position the line starts from, followed by the position after each of its moves
+    states: Vec<FullGameState>,
+    action_strs: Vec<String>,
+    /// How many moves of the line are shown
+    idx: usize,
+}
+
+impl PvPreview {
+    /// Plays out `pv` from `start`, up to the end of the game or the first move that can't be
+    /// played. Starts out showing the first move
+    pub fn new(start: &FullGameState, pv: &[GenericMove]) -> Self {
+        let mut states = vec![start.clone()];
+        let mut action_strs = Vec::new();
+        for &action in pv {
+            let current = states.last().unwrap();
+            if current.get_winner().is_some() {
+                break;
+            }
+            let Some((next_state, action_str)) = play_move(current, action) else {
+                break;
+            };
+            states.push(next_state);
+            action_strs.push(action_str);
+        }
+
+        Self {
+            idx: action_strs.len().min(1),
+            states,
+            action_strs,
+        }
+    }
+
+    pub fn start_state(&self) -> &FullGameState {
+        &self.states[0]
+    }
+
+    pub fn state(&self) -> &FullGameState {
+        &self.states[self.idx]
+    }
+
+    pub fn step_forward(&mut self) {
+        self.idx = (self.idx + 1).min(self.action_strs.len());
+    }
+
+    pub fn step_back(&mut self) {
+        self.idx = self.idx.saturating_sub(1);
+    }
+
+    /// The line, with the moves shown so far marked
+    pub fn description(&self) -> String {
+        let moves: Vec<String> = self
+            .action_strs
+            .iter()
+            .enumerate()
+            .map(|(i, action_str)| match i + 1 == self.idx {
+                true => format!("[{}]", action_str),
+                false => action_str.clone(),
+            })
+            .collect();
+        format!(
+            "Previewing ply {}/{}: {}",
+            self.idx,
+            self.action_strs.len(),
+            moves.join(" ")
+        )
+    }
+}
+
+/// The position after `action`, along with its notation
+fn play_move(state: &FullGameState, action: GenericMove) -> Option<(FullGameState, String)> {
+    // Placements aren't made by the active god, so they're looked up among the next states
+    if get_starting_placement_state(&state.board, state.gods)
+        .ok()?
+        .is_some()
+    {
+        return next_states_with_strings(state)
+            .into_iter()
+            .find(|(_, next_action, _)| *next_action == action)
+            .map(|(next_state, _, action_str)| (next_state, action_str));
+    }
+
+    let (active_god, other_god) = state.get_active_non_active_gods();
+    Some((
+        state.next_state(active_god, other_god, action),
+        active_god.stringify_move(action),
+    ))
+}
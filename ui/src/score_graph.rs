@@ -0,0 +1,188 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use eframe::egui::{self, Color32, mutex::Mutex};
+use egui_plot::{Line, MarkerShape, Plot, PlotPoints, Points};
+use santorini_core::{
+    board::FullGameState,
+    player::Player,
+    pretty_board::get_acting_player,
+    search::{
+        Heuristic, SearchContext, WINNING_SCORE, WINNING_SCORE_BUFFER,
+        get_past_win_search_terminator, negamax_search,
+    },
+    search_observer::NoopSearchObserver,
+    search_terminators::DynamicNodesVisitedSearchTerminator,
+    transposition_table::TranspositionTable,
+};
+
+use crate::history::HistoryEntry;
+
+/// Node budget for evaluating positions that were never analyzed
+const QUICK_EVAL_NODES: usize = 20_000;
+/// Scores beyond this are drawn at the edge of the graph
+const GRAPH_SCORE_LIMIT: f64 = 1_500.0;
+
+/// Converts a score from the perspective of the player to move into Player One's perspective
+pub fn score_for_p1(state: &FullGameState, score: Heuristic) -> Option<Heuristic> {
+    match get_acting_player(state).ok()? {
+        Player::One => Some(score),
+        Player::Two => Some(-score),
+    }
+}
+
+/// Runs short fixed-node searches on a background thread, so that every position in the history
+/// has a score to graph
+pub struct QuickEvaluator {
+    sender: mpsc::Sender<FullGameState>,
+    requested: HashSet<u64>,
+    scores_for_p1: Arc<Mutex<HashMap<u64, Heuristic>>>,
+    /// How many requested positions have been searched, whether or not they got a score
+    evaluated_count: Arc<AtomicUsize>,
+}
+
+impl QuickEvaluator {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<FullGameState>();
+        let scores_for_p1: Arc<Mutex<HashMap<u64, Heuristic>>> = Default::default();
+
+        let evaluated_count: Arc<AtomicUsize> = Default::default();
+
+        let thread_scores = scores_for_p1.clone();
+        let thread_evaluated_count = evaluated_count.clone();
+        thread::spawn(move || {
+            let mut tt = TranspositionTable::new();
+            while let Ok(state) = receiver.recv() {
+                let mut search_context = SearchContext {
+                    tt: &mut tt,
+                    new_best_move_callback: Box::new(|_| {}),
+                    terminator: DynamicNodesVisitedSearchTerminator::new(QUICK_EVAL_NODES),
+                    params: Default::default(),
+                    root_move_filter: None,
+                    observer: NoopSearchObserver,
+                };
+                let search_result = negamax_search(
+                    &mut search_context,
+                    state.clone(),
+                    get_past_win_search_terminator(),
+                );
+                let score = search_result
+                    .ok()
+                    .and_then(|search_state| search_state.best_move)
+                    .and_then(|best_move| score_for_p1(&state, best_move.score));
+                if let Some(score) = score {
+                    thread_scores.lock().insert(state.board.hash, score);
+                }
+                thread_evaluated_count.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        QuickEvaluator {
+            sender,
+            requested: Default::default(),
+            scores_for_p1,
+            evaluated_count,
+        }
+    }
+
+    /// Whether any requested position hasn't been searched yet
+    pub fn is_pending(&self) -> bool {
+        self.requested.len() > self.evaluated_count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the score for `state` if it's been evaluated, and queues it otherwise
+    pub fn get_or_request(&mut self, state: &FullGameState) -> Option<Heuristic> {
+        if let Some(result) = state.get_terminal_result() {
+            return Some(match result.winner() {
+                Player::One => WINNING_SCORE,
+                Player::Two => -WINNING_SCORE,
+            });
+        }
+
+        if let Some(score) = self.scores_for_p1.lock().get(&state.board.hash) {
+            return Some(*score);
+        }
+
+        if state.validation_err().is_ok() && self.requested.insert(state.board.hash) {
+            let _ = self.sender.send(state.clone());
+        }
+        None
+    }
+}
+
+/// Plots `score_for_p1` of each history entry. Returns the index of a clicked ply
+pub fn show_score_graph(
+    ui: &mut egui::Ui,
+    history: &[HistoryEntry],
+    state_idx: usize,
+    height: f32,
+) -> Option<usize> {
+    let mut line_points: Vec<[f64; 2]> = Vec::new();
+    let mut score_points: Vec<[f64; 2]> = Vec::new();
+    let mut win_points: Vec<[f64; 2]> = Vec::new();
+
+    for (idx, entry) in history.iter().enumerate() {
+        let Some(score) = entry.score_for_p1 else {
+            continue;
+        };
+        let point = [
+            idx as f64,
+            (score as f64).clamp(-GRAPH_SCORE_LIMIT, GRAPH_SCORE_LIMIT),
+        ];
+        line_points.push(point);
+        if score.abs() >= WINNING_SCORE_BUFFER {
+            win_points.push(point);
+        } else {
+            score_points.push(point);
+        }
+    }
+
+    let current_point = line_points
+        .iter()
+        .find(|point| point[0] as usize == state_idx)
+        .copied();
+
+    let plot_response = Plot::new("score_graph")
+        .height(height)
+        .include_x(0.0)
+        .include_x(history.len().saturating_sub(1).max(1) as f64)
+        .include_y(-GRAPH_SCORE_LIMIT)
+        .include_y(GRAPH_SCORE_LIMIT)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .allow_drag(false)
+        .allow_boxed_zoom(false)
+        .allow_double_click_reset(false)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new("Score", PlotPoints::new(line_points)));
+            plot_ui.points(Points::new("Eval", PlotPoints::new(score_points)).radius(2.0_f32));
+            plot_ui.points(
+                Points::new("Forced win", PlotPoints::new(win_points))
+                    .shape(MarkerShape::Diamond)
+                    .color(Color32::RED)
+                    .radius(4.0_f32),
+            );
+            if let Some(current_point) = current_point {
+                plot_ui.points(
+                    Points::new("Current", PlotPoints::new(vec![current_point]))
+                        .color(Color32::LIGHT_BLUE)
+                        .radius(5.0_f32),
+                );
+            }
+
+            plot_ui.pointer_coordinate()
+        });
+
+    if !plot_response.response.clicked() {
+        return None;
+    }
+    let clicked_idx = plot_response.inner?.x.round();
+    (clicked_idx >= 0.0 && (clicked_idx as usize) < history.len()).then_some(clicked_idx as usize)
+}
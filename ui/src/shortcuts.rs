@@ -0,0 +1,223 @@
+use eframe::egui::{self, Event, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+/// Storage key for the shortcuts in the eframe config
+pub const SHORTCUTS_STORAGE_KEY: &str = "shortcuts";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShortcutAction {
+    EngineMove,
+    RedoTurn,
+    StateForward,
+    StateBackward,
+    ChangeMode,
+    AutoplayStart,
+    NextPosition,
+    PreviousPosition,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 8] = [
+        ShortcutAction::EngineMove,
+        ShortcutAction::RedoTurn,
+        ShortcutAction::StateBackward,
+        ShortcutAction::StateForward,
+        ShortcutAction::ChangeMode,
+        ShortcutAction::AutoplayStart,
+        ShortcutAction::PreviousPosition,
+        ShortcutAction::NextPosition,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ShortcutAction::EngineMove => "Do engine move",
+            ShortcutAction::RedoTurn => "Restart turn",
+            ShortcutAction::StateForward => "Go forward a turn",
+            ShortcutAction::StateBackward => "Go back a turn",
+            ShortcutAction::ChangeMode => "Change mode",
+            ShortcutAction::AutoplayStart => "Toggle autoplay",
+            ShortcutAction::NextPosition => "Next listed position",
+            ShortcutAction::PreviousPosition => "Previous listed position",
+        }
+    }
+}
+
+pub fn shortcut_text(shortcut: KeyboardShortcut) -> String {
+    shortcut.format(&egui::ModifierNames::SYMBOLS, false)
+}
+
+pub fn shortcut_text_long(shortcut: KeyboardShortcut) -> String {
+    shortcut.format(&egui::ModifierNames::NAMES, false)
+}
+
+/// Key bindings for every UI action
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shortcuts {
+    pub engine_move: KeyboardShortcut,
+    pub redo_turn: KeyboardShortcut,
+    pub state_forward: KeyboardShortcut,
+    pub state_backward: KeyboardShortcut,
+    pub change_mode: KeyboardShortcut,
+    pub autoplay_start: KeyboardShortcut,
+    pub next_position: KeyboardShortcut,
+    pub previous_position: KeyboardShortcut,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Shortcuts {
+            engine_move: KeyboardShortcut::new(Modifiers::NONE, Key::ArrowUp),
+            redo_turn: KeyboardShortcut::new(Modifiers::NONE, Key::ArrowDown),
+            state_forward: KeyboardShortcut::new(Modifiers::NONE, Key::ArrowRight),
+            state_backward: KeyboardShortcut::new(Modifiers::NONE, Key::ArrowLeft),
+            change_mode: KeyboardShortcut::new(Modifiers::NONE, Key::M),
+            autoplay_start: KeyboardShortcut::new(Modifiers::NONE, Key::A),
+            next_position: KeyboardShortcut::new(Modifiers::NONE, Key::N),
+            previous_position: KeyboardShortcut::new(Modifiers::NONE, Key::P),
+        }
+    }
+}
+
+impl Shortcuts {
+    pub fn get(&self, action: ShortcutAction) -> KeyboardShortcut {
+        match action {
+            ShortcutAction::EngineMove => self.engine_move,
+            ShortcutAction::RedoTurn => self.redo_turn,
+            ShortcutAction::StateForward => self.state_forward,
+            ShortcutAction::StateBackward => self.state_backward,
+            ShortcutAction::ChangeMode => self.change_mode,
+            ShortcutAction::AutoplayStart => self.autoplay_start,
+            ShortcutAction::NextPosition => self.next_position,
+            ShortcutAction::PreviousPosition => self.previous_position,
+        }
+    }
+
+    fn get_mut(&mut self, action: ShortcutAction) -> &mut KeyboardShortcut {
+        match action {
+            ShortcutAction::EngineMove => &mut self.engine_move,
+            ShortcutAction::RedoTurn => &mut self.redo_turn,
+            ShortcutAction::StateForward => &mut self.state_forward,
+            ShortcutAction::StateBackward => &mut self.state_backward,
+            ShortcutAction::ChangeMode => &mut self.change_mode,
+            ShortcutAction::AutoplayStart => &mut self.autoplay_start,
+            ShortcutAction::NextPosition => &mut self.next_position,
+            ShortcutAction::PreviousPosition => &mut self.previous_position,
+        }
+    }
+
+    /// Returns the other action already bound to `shortcut`, if any
+    pub fn find_conflict(
+        &self,
+        action: ShortcutAction,
+        shortcut: KeyboardShortcut,
+    ) -> Option<ShortcutAction> {
+        ShortcutAction::ALL
+            .into_iter()
+            .find(|other| *other != action && self.get(*other) == shortcut)
+    }
+
+    /// Binds `action` to `shortcut`, unless another action already uses it
+    pub fn try_set(
+        &mut self,
+        action: ShortcutAction,
+        shortcut: KeyboardShortcut,
+    ) -> Result<(), String> {
+        if let Some(conflict) = self.find_conflict(action, shortcut) {
+            return Err(format!(
+                "{} is already used by '{}'",
+                shortcut_text_long(shortcut),
+                conflict.description()
+            ));
+        }
+        *self.get_mut(action) = shortcut;
+        Ok(())
+    }
+}
+
+/// State of the shortcut settings window
+#[derive(Default)]
+pub struct ShortcutSettings {
+    pub is_open: bool,
+    /// The action waiting for a new key chord
+    capturing: Option<ShortcutAction>,
+    error: Option<String>,
+}
+
+impl ShortcutSettings {
+    /// While capturing a key chord, other shortcuts should not fire
+    pub fn is_capturing(&self) -> bool {
+        self.is_open && self.capturing.is_some()
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, shortcuts: &mut Shortcuts) {
+        if !self.is_open {
+            self.capturing = None;
+            return;
+        }
+
+        if let Some(action) = self.capturing {
+            let pressed = ctx.input_mut(|i| {
+                let pressed = i.events.iter().find_map(|event| match event {
+                    Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some(KeyboardShortcut::new(*modifiers, *key)),
+                    _ => None,
+                });
+                if pressed.is_some() {
+                    i.events.clear();
+                }
+                pressed
+            });
+
+            match pressed {
+                Some(shortcut) if shortcut.logical_key == Key::Escape => self.capturing = None,
+                Some(shortcut) => {
+                    self.error = shortcuts.try_set(action, shortcut).err();
+                    self.capturing = None;
+                }
+                None => {}
+            }
+        }
+
+        let mut is_open = self.is_open;
+        egui::Window::new("Keyboard Shortcuts")
+            .open(&mut is_open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("Click a shortcut, then press the new key chord. Escape cancels.");
+
+                egui::Grid::new("shortcut_settings")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for action in ShortcutAction::ALL {
+                            ui.label(action.description());
+                            let button_text = match self.capturing == Some(action) {
+                                true => "Press a key...".to_owned(),
+                                false => shortcut_text_long(shortcuts.get(action)),
+                            };
+                            if ui.button(button_text).clicked() {
+                                self.capturing = Some(action);
+                                self.error = None;
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                if ui.button("Reset to Defaults").clicked() {
+                    *shortcuts = Shortcuts::default();
+                    self.capturing = None;
+                    self.error = None;
+                }
+            });
+        self.is_open = is_open;
+    }
+}
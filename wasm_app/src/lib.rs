@@ -1,20 +1,31 @@
 use js_sys;
 use santorini_core::{
     board::FullGameState,
-    fen::parse_fen,
-    gods::PartialAction,
-    matchup::BANNED_MATCHUPS,
+    consistency_checker::consistency_check,
+    fen::{parse_fen, parse_fen_unvalidated, validate_fen},
+    gods::{GodName, GodNameParseError, PartialAction, god_description, god_support_levels},
+    matchup::{Matchup, MatchupPolicy, matchup_policy, set_matchup_policy},
     player::Player,
-    pretty_board::{game_state_with_partial_actions, state_to_pretty_board},
-    search::{SearchContext, get_past_win_search_terminator, negamax_search},
+    pretty_board::{
+        diff_states, game_state_with_partial_actions, pretty_board_delta, state_to_pretty_board,
+    },
+    quick_verdict::score_root_moves_parallel,
+    refute::next_states_with_strings,
+    search::{Heuristic, SearchContext, get_past_win_search_terminator, negamax_search},
+    search_observer::NoopSearchObserver,
     search_terminators::SearchTerminator,
     transposition_table::TranspositionTable,
-    uci_types::{BestMoveMeta, BestMoveOutput, EngineOutput, NextMovesOutput, NextStateOutput},
+    uci_types::{BestMoveMeta, BestMoveOutput, EngineOutput, NextMovesOutput},
     utils::find_action_path,
+    win_reason::get_win_reason,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "threads")]
+mod search_threads;
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
@@ -60,6 +71,9 @@ impl JsTimeSearchTerminator {
 #[wasm_bindgen]
 pub struct WasmApp {
     tt: TranspositionTable,
+    next_search_id: u32,
+    /// Results of searches started with `startNextMove` that ran on this thread
+    finished_searches: HashMap<u32, JsValue>,
 }
 
 fn _parse_js_number(number: &JsValue) -> Result<f64, String> {
@@ -70,6 +84,14 @@ fn _parse_js_number(number: &JsValue) -> Result<f64, String> {
     Ok(number)
 }
 
+/// Parses a position to draw. Unlike `parse_fen`, positions that are representable but not
+/// playable are allowed, the same as the UI shows them
+fn _parse_displayable_fen(fen: &str) -> Result<FullGameState, String> {
+    let state = parse_fen_unvalidated(fen)?;
+    state.representation_err()?;
+    Ok(state)
+}
+
 fn _parse_fen_js_value(fen: &JsValue) -> Result<FullGameState, String> {
     let Some(fen) = fen.as_string() else {
         return Err("fen must be a string".to_owned());
@@ -92,6 +114,8 @@ impl WasmApp {
     pub fn new() -> Self {
         Self {
             tt: TranspositionTable::new(),
+            next_search_id: 0,
+            finished_searches: HashMap::new(),
         }
     }
 
@@ -102,46 +126,10 @@ impl WasmApp {
     ) -> Result<JsValue, String> {
         let timeLimit = _parse_js_number(&duration)?;
         let state = _parse_fen_js_value(&fen)?;
+        let fen = JsValue::as_string(&fen).unwrap();
 
-        let mut search_state = SearchContext {
-            tt: &mut self.tt,
-            new_best_move_callback: Box::new(|_| {}),
-            terminator: JsTimeSearchTerminator::new(timeLimit),
-        };
-
-        let search_result = negamax_search(
-            &mut search_state,
-            state.clone(),
-            get_past_win_search_terminator(),
-        );
-
-        if let Some(action) = search_result.best_move {
-            let actions = find_action_path(&state, &action.child_state).unwrap_or_default();
-
-            let meta = BestMoveMeta {
-                score: action.score,
-                calculated_depth: action.depth,
-                nodes_visited: Some(action.nodes_visited),
-                elapsed_seconds: 0.0,
-                actions: actions,
-                action_str: Some(action.action_str),
-            };
-
-            let output = BestMoveOutput {
-                original_str: Some(JsValue::as_string(&fen).unwrap()),
-                start_state: state.clone(),
-                next_state: action.child_state,
-                trigger: action.trigger,
-                meta: meta,
-            };
-
-            return serde_wasm_bindgen::to_value(&output).map_err(|e| e.to_string());
-        } else {
-            return Err(format!(
-                "no move {} {}",
-                search_result.last_fully_completed_depth, search_result.nodes_visited
-            ));
-        }
+        let output = compute_best_move(&mut self.tt, &state, fen, timeLimit)?;
+        serde_wasm_bindgen::to_value(&output).map_err(|e| e.to_string())
     }
 
     pub fn computeNextMove(&mut self, fen: JsValue, duration: JsValue) -> JsValue {
@@ -150,24 +138,130 @@ impl WasmApp {
             Err(err) => JsValue::from(err),
         }
     }
+
+    /// Starts a search and returns an id to poll it with. Runs on a search thread when threaded
+    /// search is available, and otherwise searches right away, like `computeNextMove`
+    pub fn startNextMove(&mut self, fen: JsValue, duration: JsValue) -> u32 {
+        let search_id = self.next_search_id;
+        self.next_search_id = self.next_search_id.wrapping_add(1);
+
+        #[cfg(feature = "threads")]
+        if search_threads::thread_count() > 0 {
+            let job = _parse_js_number(&duration)
+                .and_then(|time_limit_ms| Ok((_parse_fen_js_value(&fen)?, time_limit_ms)));
+            match job {
+                Ok((state, time_limit_ms)) => search_threads::submit(search_threads::SearchJob {
+                    search_id,
+                    state,
+                    fen: JsValue::as_string(&fen).unwrap(),
+                    time_limit_ms,
+                }),
+                Err(err) => {
+                    self.finished_searches.insert(search_id, JsValue::from(err));
+                }
+            }
+            return search_id;
+        }
+
+        let result = self.computeNextMove(fen, duration);
+        self.finished_searches.insert(search_id, result);
+        search_id
+    }
+
+    /// The result of a search from `startNextMove`, in the same shape as `computeNextMove`.
+    /// Undefined while the search is still running. Each result is only returned once
+    pub fn pollNextMove(&mut self, search_id: u32) -> JsValue {
+        if let Some(result) = self.finished_searches.remove(&search_id) {
+            return result;
+        }
+
+        #[cfg(feature = "threads")]
+        if let Some(result) = search_threads::take_result(search_id) {
+            return match result
+                .and_then(|output| serde_wasm_bindgen::to_value(&output).map_err(|e| e.to_string()))
+            {
+                Ok(result) => result,
+                Err(err) => JsValue::from(err),
+            };
+        }
+
+        JsValue::UNDEFINED
+    }
+}
+
+/// Searches `state` for `time_limit_ms`, shared by the main thread and search threads
+fn compute_best_move(
+    tt: &mut TranspositionTable,
+    state: &FullGameState,
+    fen: String,
+    time_limit_ms: f64,
+) -> Result<BestMoveOutput, String> {
+    let mut search_state = SearchContext {
+        tt,
+        new_best_move_callback: Box::new(|_| {}),
+        terminator: JsTimeSearchTerminator::new(time_limit_ms),
+        params: Default::default(),
+        root_move_filter: None,
+        observer: NoopSearchObserver,
+    };
+
+    let search_result = negamax_search(
+        &mut search_state,
+        state.clone(),
+        get_past_win_search_terminator(),
+    )
+    .map_err(|err| err.to_string())?;
+
+    if let Some(action) = search_result.best_move {
+        let actions = find_action_path(state, &action.child_state).unwrap_or_default();
+
+        let tt_hit_rate = action.tt_hit_rate();
+        let meta = BestMoveMeta {
+            score: action.score,
+            calculated_depth: action.depth,
+            nodes_visited: Some(action.nodes_visited),
+            elapsed_seconds: 0.0,
+            nodes_per_second: None,
+            seldepth: Some(action.seldepth),
+            tt_hit_rate,
+            actions: actions,
+            action_str: Some(action.action_str),
+            win_reason: get_win_reason(state, &action.child_state),
+            god_data: action.child_state.god_data_items(),
+        };
+
+        Ok(BestMoveOutput {
+            original_str: Some(fen),
+            start_state: state.clone(),
+            next_state: action.child_state,
+            trigger: action.trigger,
+            meta: meta,
+        })
+    } else {
+        Err(format!(
+            "no move {} {}",
+            search_result.last_fully_completed_depth, search_result.nodes_visited
+        ))
+    }
+}
+
+fn is_cross_origin_isolated() -> bool {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("crossOriginIsolated"))
+        .is_ok_and(|isolated| isolated.is_truthy())
+}
+
+/// Whether this build can search on wasm threads here. Needs a build with the `threads` feature,
+/// and a cross-origin isolated page so that memory can be shared with workers
+#[wasm_bindgen(js_name = isThreadedSearchAvailable)]
+pub fn is_threaded_search_available() -> bool {
+    cfg!(feature = "threads") && is_cross_origin_isolated()
 }
 
 pub fn _get_next_moves_interactive_result(fen: JsValue) -> Result<JsValue, String> {
     let state = _parse_fen_js_value(&fen)?;
     let fen_string = JsValue::as_string(&fen).ok_or("fen must be a string")?;
 
-    let child_states = state.get_next_states_interactive();
-    let output = EngineOutput::NextMoves(NextMovesOutput {
-        original_str: Some(fen_string),
-        start_state: state,
-        next_states: child_states
-            .into_iter()
-            .map(|full_choice| NextStateOutput {
-                next_state: full_choice.state,
-                actions: full_choice.actions,
-            })
-            .collect(),
-    });
+    let output = EngineOutput::NextMoves(NextMovesOutput::new(Some(fen_string), state));
 
     serde_wasm_bindgen::to_value(&output).map_err(|e| e.to_string())
 }
@@ -180,17 +274,67 @@ pub fn get_next_moves_interactive(fen: JsValue) -> JsValue {
     }
 }
 
+/// How well the engine plays each god, keyed by god name
+#[wasm_bindgen(js_name = getGodSupportLevels)]
+pub fn get_god_support_levels_js() -> JsValue {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    god_support_levels()
+        .serialize(&serializer)
+        .unwrap_or_else(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// A matchup that can't be played as is: either banned outright, or evened out with a handicap
+#[derive(Serialize)]
+struct RestrictedMatchup {
+    /// `<god1>|<god2>`
+    matchup: String,
+    banned: bool,
+    /// In favour of player 1
+    komi: Heuristic,
+    /// The player (1 or 2) who places last, if the policy overrides the placement order
+    placement_advantage: Option<usize>,
+}
+
 #[wasm_bindgen]
 pub fn get_banned_matchups() -> JsValue {
-    let mut res: Vec<String> = Vec::new();
-    for matchup in BANNED_MATCHUPS.keys() {
-        let matchup_str = format!("{}|{}", matchup.gods[0], matchup.gods[1]);
-        res.push(matchup_str);
+    let policy = matchup_policy();
+    let matchup_str = |matchup: &Matchup| format!("{}|{}", matchup.gods[0], matchup.gods[1]);
+
+    let mut res: Vec<RestrictedMatchup> = policy
+        .banned_matchups()
+        .map(|matchup| RestrictedMatchup {
+            matchup: matchup_str(matchup),
+            banned: true,
+            komi: 0,
+            placement_advantage: None,
+        })
+        .collect();
+    for (matchup, handicap) in policy.handicapped_matchups() {
+        res.push(RestrictedMatchup {
+            matchup: matchup_str(&matchup),
+            banned: false,
+            komi: handicap.komi,
+            placement_advantage: handicap
+                .placement_advantage
+                .map(|player| player as usize + 1),
+        });
     }
 
     serde_wasm_bindgen::to_value(&res).unwrap_or_else(|e| JsValue::from_str(&format!("{:?}", e)))
 }
 
+/// Replaces the banned matchups from a yaml matchup policy. An empty string restores the default
+#[wasm_bindgen]
+pub fn set_matchup_policy_yaml(yaml: JsValue) -> Result<(), String> {
+    let yaml = yaml.as_string().ok_or("Matchup policy must be a string")?;
+    let policy = match yaml.trim() {
+        "" => MatchupPolicy::default(),
+        yaml => MatchupPolicy::from_yaml(yaml)?,
+    };
+    set_matchup_policy(policy);
+    Ok(())
+}
+
 fn _get_player_strings_inner(fen: JsValue) -> Result<JsValue, String> {
     let state = _parse_fen_js_value(&fen)?;
 
@@ -206,22 +350,42 @@ pub fn get_player_strings(fen: JsValue) -> JsValue {
     _get_player_strings_inner(fen).unwrap_or_else(|e| JsValue::from_str(&e))
 }
 
+fn _get_player_god_data_inner(fen: JsValue) -> Result<JsValue, String> {
+    let state = _parse_fen_js_value(&fen)?;
+    serde_wasm_bindgen::to_value(&state.god_data_items()).map_err(|e| e.to_string())
+}
+
+/// Each player's god specific state as typed items, so that counters and squares can be drawn
+#[wasm_bindgen(js_name = getPlayerGodData)]
+pub fn get_player_god_data_js(fen: JsValue) -> JsValue {
+    _get_player_god_data_inner(fen).unwrap_or_else(|e| JsValue::from_str(&e))
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 struct PrettyGameStateArgs {
     fen: String,
     actions: Option<Vec<PartialAction>>,
+    /// When set, only the changes since this position are returned, as a `PrettyBoardDelta`
+    #[serde(default)]
+    previous_fen: Option<String>,
 }
 
 fn _get_pretty_game_state_inner(args: JsValue) -> Result<JsValue, String> {
     let args =
         serde_wasm_bindgen::from_value::<PrettyGameStateArgs>(args).map_err(|e| e.to_string())?;
 
-    let mut state = parse_fen(&args.fen)?;
+    let mut state = _parse_displayable_fen(&args.fen)?;
 
     if let Some(actions) = args.actions {
         state = game_state_with_partial_actions(&state, &actions);
     };
 
+    if let Some(previous_fen) = args.previous_fen {
+        let previous_state = _parse_displayable_fen(&previous_fen)?;
+        let delta = pretty_board_delta(&previous_state, &state);
+        return serde_wasm_bindgen::to_value(&delta).map_err(|e| e.to_string());
+    }
+
     let pretty_board = state_to_pretty_board(&state);
 
     serde_wasm_bindgen::to_value(&pretty_board).map_err(|e| e.to_string())
@@ -231,3 +395,142 @@ fn _get_pretty_game_state_inner(args: JsValue) -> Result<JsValue, String> {
 pub fn get_pretty_game_state(args: JsValue) -> JsValue {
     _get_pretty_game_state_inner(args).unwrap_or_else(|e| JsValue::from_str(&e))
 }
+
+fn _apply_actions_delta_inner(fen: JsValue, actions: JsValue) -> Result<JsValue, String> {
+    let state = parse_fen(&fen.as_string().ok_or("fen must be a string")?)?;
+    let actions =
+        serde_wasm_bindgen::from_value::<Vec<PartialAction>>(actions).map_err(|e| e.to_string())?;
+
+    let partial_state = game_state_with_partial_actions(&state, &actions);
+    let delta = pretty_board_delta(&state, &partial_state);
+    serde_wasm_bindgen::to_value(&delta).map_err(|e| e.to_string())
+}
+
+/// The squares that change when `actions` are made from a position, so that a move in progress
+/// can be drawn without resending the whole board
+#[wasm_bindgen(js_name = applyActionsDelta)]
+pub fn apply_actions_delta_js(fen: JsValue, actions: JsValue) -> JsValue {
+    _apply_actions_delta_inner(fen, actions).unwrap_or_else(|e| JsValue::from_str(&e))
+}
+
+fn _diff_states_inner(fen_a: JsValue, fen_b: JsValue) -> Result<JsValue, String> {
+    // Unlike _parse_fen_js_value, finished games are fine here
+    let parse = |fen: JsValue| -> Result<FullGameState, String> {
+        _parse_displayable_fen(&fen.as_string().ok_or("fen must be a string")?)
+    };
+    let state_a = parse(fen_a)?;
+    let state_b = parse(fen_b)?;
+
+    let diff = diff_states(&state_a, &state_b);
+    serde_wasm_bindgen::to_value(&diff).map_err(|e| e.to_string())
+}
+
+/// Lists what changed between two positions, for move previews
+#[wasm_bindgen(js_name = diffStates)]
+pub fn diff_states_js(fen_a: JsValue, fen_b: JsValue) -> JsValue {
+    _diff_states_inner(fen_a, fen_b).unwrap_or_else(|e| JsValue::from_str(&e))
+}
+
+fn _validate_position_inner(fen: JsValue) -> Result<JsValue, String> {
+    let fen = fen.as_string().ok_or("fen must be a string")?;
+    serde_wasm_bindgen::to_value(&validate_fen(&fen)).map_err(|e| e.to_string())
+}
+
+/// Both tiers of validation for a position, as `{representable, playable, issues}`.
+/// Representable positions can be drawn and edited, and only playable ones can be searched
+#[wasm_bindgen(js_name = validatePosition)]
+pub fn validate_position_js(fen: JsValue) -> JsValue {
+    _validate_position_inner(fen).unwrap_or_else(|e| JsValue::from_str(&e))
+}
+
+fn _get_god_description_inner(name: JsValue) -> Result<String, String> {
+    let name = name.as_string().ok_or("God name must be a string")?;
+    let god_name: GodName = name
+        .parse()
+        .map_err(|err: GodNameParseError| err.to_string())?;
+    Ok(god_description(god_name).to_owned())
+}
+
+/// Power text and implementation notes for a god, by name
+#[wasm_bindgen(js_name = getGodDescription)]
+pub fn get_god_description_js(name: JsValue) -> JsValue {
+    match _get_god_description_inner(name) {
+        Ok(description) => JsValue::from_str(&description),
+        Err(err) => JsValue::from_str(&err),
+    }
+}
+
+#[derive(Serialize)]
+struct RootMoveScoreOutput {
+    action_str: String,
+    next_state: FullGameState,
+    score: Heuristic,
+}
+
+fn _score_root_moves_inner(
+    fen: JsValue,
+    nodes_per_move: usize,
+    threads: usize,
+) -> Result<JsValue, String> {
+    let state = _parse_fen_js_value(&fen)?;
+    if state.get_winner().is_some() {
+        return Err("Cannot score moves from a terminal state".to_owned());
+    }
+
+    let mut children = next_states_with_strings(&state);
+    let output: Vec<RootMoveScoreOutput> =
+        score_root_moves_parallel(&state, nodes_per_move, threads)
+            .into_iter()
+            .filter_map(|(action, score)| {
+                let idx = children.iter().position(|(_, a, _)| *a == action)?;
+                let (next_state, _, action_str) = children.swap_remove(idx);
+                Some(RootMoveScoreOutput {
+                    action_str,
+                    next_state,
+                    score,
+                })
+            })
+            .collect();
+    serde_wasm_bindgen::to_value(&output).map_err(|e| e.to_string())
+}
+
+/// Scores every move from a position with the same node budget each, best first. There are no
+/// threads in wasm, so `threads` is only accepted to match the native signature
+#[wasm_bindgen(js_name = scoreRootMoves)]
+pub fn score_root_moves_js(fen: JsValue, nodes_per_move: usize, threads: usize) -> JsValue {
+    _score_root_moves_inner(fen, nodes_per_move, threads).unwrap_or_else(|e| JsValue::from_str(&e))
+}
+
+fn _get_key_squares_inner(fen: JsValue) -> Result<JsValue, String> {
+    let state = _parse_fen_js_value(&fen)?;
+    let key_squares = state
+        .defensive_key_squares()
+        .map(|key_squares| key_squares.all_squares());
+    serde_wasm_bindgen::to_value(&key_squares).map_err(|e| e.to_string())
+}
+
+/// Squares the player to move can use to stop the opponent's immediate wins, or null if they
+/// aren't in check
+#[wasm_bindgen(js_name = getKeySquares)]
+pub fn get_key_squares_js(fen: JsValue) -> JsValue {
+    _get_key_squares_inner(fen).unwrap_or_else(|e| JsValue::from_str(&e))
+}
+
+fn _consistency_check_inner(fen: JsValue) -> Result<String, String> {
+    // Finished games can still be checked
+    let state = parse_fen(&fen.as_string().ok_or("fen must be a string")?)?;
+    match consistency_check(&state) {
+        Ok(()) => Ok("ok".to_owned()),
+        Err(errors) => Ok(errors.join("\n")),
+    }
+}
+
+/// Runs the move generation consistency checker on a position, for attaching to bug reports.
+/// Returns "ok", or each error found on its own line
+#[wasm_bindgen(js_name = consistencyCheck)]
+pub fn consistency_check_js(fen: JsValue) -> JsValue {
+    match _consistency_check_inner(fen) {
+        Ok(output) => JsValue::from_str(&output),
+        Err(err) => JsValue::from_str(&err),
+    }
+}
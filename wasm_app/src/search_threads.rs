@@ -0,0 +1,96 @@
+//! Searches on wasm threads: web workers that share this module's memory. Each thread blocks
+//! waiting for jobs, so a search never holds up the thread that asked for it. Build with
+//! `npm run build:wasm-threads`, and serve the page cross-origin isolated.
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{
+        Condvar, Mutex, MutexGuard, TryLockError,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use santorini_core::{
+    board::FullGameState, transposition_table::TranspositionTable, uci_types::BestMoveOutput,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{compute_best_move, is_threaded_search_available};
+
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+compile_error!("The threads feature needs the atomics and bulk-memory target features");
+
+pub struct SearchJob {
+    pub search_id: u32,
+    pub state: FullGameState,
+    pub fen: String,
+    pub time_limit_ms: f64,
+}
+
+static JOBS: Mutex<VecDeque<SearchJob>> = Mutex::new(VecDeque::new());
+static JOB_ADDED: Condvar = Condvar::new();
+static RESULTS: Mutex<BTreeMap<u32, Result<BestMoveOutput, String>>> = Mutex::new(BTreeMap::new());
+static THREAD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The page's main thread isn't allowed to block, so calls that can come from it spin instead.
+/// Search threads only hold these locks for long enough to push or pop one item
+fn lock_without_blocking<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    loop {
+        match mutex.try_lock() {
+            Ok(guard) => return guard,
+            Err(TryLockError::Poisoned(poisoned)) => return poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => std::hint::spin_loop(),
+        }
+    }
+}
+
+/// How many search threads have joined the pool
+pub fn thread_count() -> usize {
+    THREAD_COUNT.load(Ordering::Acquire)
+}
+
+pub fn submit(job: SearchJob) {
+    lock_without_blocking(&JOBS).push_back(job);
+    JOB_ADDED.notify_one();
+}
+
+pub fn take_result(search_id: u32) -> Option<Result<BestMoveOutput, String>> {
+    lock_without_blocking(&RESULTS).remove(&search_id)
+}
+
+/// The compiled module and shared memory, as `[module, memory]`. Pass them to each search
+/// thread's wasm init, and then call `runSearchThread` there
+#[wasm_bindgen(js_name = initThreadPool)]
+pub fn init_thread_pool() -> Result<js_sys::Array, String> {
+    if !is_threaded_search_available() {
+        return Err("Threaded search needs a cross-origin isolated page".to_owned());
+    }
+    Ok(js_sys::Array::of2(
+        &wasm_bindgen::module(),
+        &wasm_bindgen::memory(),
+    ))
+}
+
+/// Joins the pool and runs searches forever. Only call this from a web worker, since it blocks
+#[wasm_bindgen(js_name = runSearchThread)]
+pub fn run_search_thread() {
+    // Each thread keeps its own table, so that locking never slows down search
+    let mut tt = TranspositionTable::new();
+    THREAD_COUNT.fetch_add(1, Ordering::AcqRel);
+
+    loop {
+        let job = {
+            let mut jobs = JOBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            loop {
+                if let Some(job) = jobs.pop_front() {
+                    break job;
+                }
+                jobs = JOB_ADDED
+                    .wait(jobs)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        };
+
+        let result = compute_best_move(&mut tt, &job.state, job.fen, job.time_limit_ms);
+        lock_without_blocking(&RESULTS).insert(job.search_id, result);
+    }
+}
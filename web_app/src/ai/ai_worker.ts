@@ -6,7 +6,11 @@ export type SearchResultMeta = {
     calculated_depth: number,
     nodes_visited: number,
     action_str: string,
-    actions: Array<PlayerAction>
+    actions: Array<PlayerAction>,
+    win_reason?: string | null,
+    nodes_per_second?: number | null,
+    seldepth?: number | null,
+    tt_hit_rate?: number | null,
 };
 
 export type SearchResult = {
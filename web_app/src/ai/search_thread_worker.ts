@@ -0,0 +1,9 @@
+// A wasm search thread. Joins the memory of the thread that spawned it, then runs searches forever
+self.onmessage = async (e: MessageEvent<[WebAssembly.Module, WebAssembly.Memory]>) => {
+    const [module, memory] = e.data;
+    const wasm = await import('../../pkg/wasm_app');
+    await wasm.default({ module_or_path: module, memory } as Parameters<typeof wasm.default>[0]);
+
+    // Blocks this worker for good
+    (wasm as unknown as { runSearchThread(): void }).runSearchThread();
+};
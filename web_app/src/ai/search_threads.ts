@@ -0,0 +1,47 @@
+// Glue for searching on wasm threads. Threads only start with a wasm build from
+// `npm run build:wasm-threads`, on a cross-origin isolated page. Otherwise WasmApp.startNextMove
+// searches on the calling thread, so callers don't need to care which build they got.
+
+type WasmApp = {
+    startNextMove(fen: string, duration: number): number,
+    pollNextMove(searchId: number): unknown,
+};
+
+type ThreadedWasm = {
+    isThreadedSearchAvailable(): boolean,
+    // Only exported by threaded builds
+    initThreadPool?: () => [WebAssembly.Module, WebAssembly.Memory],
+};
+
+const POLL_INTERVAL_MS = 20;
+
+// Spawns search threads that share the wasm memory of the calling thread. Returns how many were
+// started, which is 0 when threaded search isn't available
+export function startSearchThreads(wasm: ThreadedWasm, threadCount: number): number {
+    if (!wasm.isThreadedSearchAvailable() || !wasm.initThreadPool) {
+        return 0;
+    }
+
+    const [module, memory] = wasm.initThreadPool();
+    for (let i = 0; i < threadCount; i++) {
+        const thread = new Worker(new URL('./search_thread_worker.ts', import.meta.url), { type: 'module' });
+        thread.postMessage([module, memory]);
+    }
+    return threadCount;
+}
+
+// Starts a search and resolves with its result, in the same shape as WasmApp.computeNextMove
+export function searchNextMove(app: WasmApp, fen: string, duration: number): Promise<unknown> {
+    const searchId = app.startNextMove(fen, duration);
+    return new Promise(resolve => {
+        const poll = () => {
+            const result = app.pollNextMove(searchId);
+            if (result === undefined) {
+                setTimeout(poll, POLL_INTERVAL_MS);
+            } else {
+                resolve(result);
+            }
+        };
+        poll();
+    });
+}
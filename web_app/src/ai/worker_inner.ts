@@ -1,12 +1,18 @@
+import { searchNextMove, startSearchThreads } from './search_threads';
+
+// Leave a core for the page itself
+const SEARCH_THREAD_COUNT = Math.max(1, Math.min(4, (navigator.hardwareConcurrency ?? 2) - 1));
+
 async function initializeWasmWorker() {
     self.postMessage('startup');
     const wasm = await import('../../pkg/wasm_app');
     await wasm.default();
 
     const worker = new wasm.WasmApp();
+    startSearchThreads(wasm, SEARCH_THREAD_COUNT);
 
     self.onmessage = async e => {
-        const thinkingResponse = worker.computeNextMove(e.data[0], e.data[1]);
+        const thinkingResponse = await searchNextMove(worker, e.data[0], e.data[1]);
         self.postMessage(thinkingResponse);
     };
 
@@ -1,5 +1,5 @@
-import { get_next_moves_interactive, get_banned_matchups, get_pretty_game_state } from "../../pkg/wasm_app";
-import { type GameState, type DirectionType } from "./game_state";
+import { get_next_moves_interactive, get_banned_matchups, get_pretty_game_state, diffStates as wasmDiffStates, getGodDescription as wasmGetGodDescription, scoreRootMoves as wasmScoreRootMoves, getKeySquares as wasmGetKeySquares, consistencyCheck as wasmConsistencyCheck, validatePosition as wasmValidatePosition } from "../../pkg/wasm_app";
+import { type GameState, type DirectionType, type GodType, type PlayerType } from "./game_state";
 import { assertUnreachable } from "./utils";
 
 export type NextMoves = {
@@ -12,6 +12,9 @@ export type NextMoves = {
 export type NextState = {
     next_state: string,
     actions: Array<PlayerAction>,
+    action_str?: string,
+    is_win?: boolean,
+    is_check?: boolean,
 };
 
 export const PlayerActionTypes = {
@@ -64,12 +67,85 @@ export type PlayerAction =
     | { type: typeof PlayerActionTypes.EndTurn }
     | { type: typeof PlayerActionTypes.NoMoves };
 
+export type BoardDiffItem =
+    | { type: 'god'; player: PlayerType; from: GodType; to: GodType }
+    | { type: 'current_player'; from: PlayerType; to: PlayerType }
+    | { type: 'winner'; from: PlayerType | null; to: PlayerType | null }
+    | { type: 'height'; square: string; from: number; to: number }
+    | { type: 'worker_added'; player: PlayerType; square: string }
+    | { type: 'worker_removed'; player: PlayerType; square: string }
+    | { type: 'god_data'; player: PlayerType; from: number; to: number };
+
+export type BoardDiff = {
+    items: Array<BoardDiffItem>,
+};
+
 export function getNextMoves(fen: string): NextMoves {
     return get_next_moves_interactive(fen);
 }
 
+// A matchup that's either banned, or evened out with a handicap. matchup is `<god1>|<god2>`,
+// komi is in favour of player 1, and placement_advantage is the player who places last
+export type RestrictedMatchup = {
+    matchup: string,
+    banned: boolean,
+    komi: number,
+    placement_advantage: 1 | 2 | null,
+}
+
+function getRestrictedMatchups(): Array<RestrictedMatchup> {
+    return get_banned_matchups();
+}
+
 export function getBannedMatchups(): Set<string> {
-    return new Set(get_banned_matchups());
+    return new Set(getRestrictedMatchups().filter((m) => m.banned).map((m) => m.matchup));
+}
+
+// Matchups that are allowed, but played with a handicap
+export function getUnbalancedMatchups(): Map<string, RestrictedMatchup> {
+    return new Map(getRestrictedMatchups().filter((m) => !m.banned).map((m) => [m.matchup, m]));
+}
+
+export function diffStates(fenA: string, fenB: string): BoardDiff {
+    return wasmDiffStates(fenA, fenB);
+}
+
+export function getGodDescription(godName: string): string {
+    return wasmGetGodDescription(godName);
+}
+
+export type RootMoveScore = {
+    action_str: string,
+    next_state: string,
+    score: number,
+}
+
+// Scores every move from fen with the same node budget each, best first
+export function scoreRootMoves(fen: string, nodesPerMove: number): Array<RootMoveScore> {
+    return wasmScoreRootMoves(fen, nodesPerMove, 1);
+}
+
+// Squares the player to move can use to stop the opponent's immediate wins, or null if they
+// aren't in check
+export function getKeySquares(fen: string): Array<string> | null {
+    return wasmGetKeySquares(fen) ?? null;
+}
+
+// Runs the move generation consistency checker on fen, for attaching to bug reports. Returns
+// "ok", or each error found on its own line
+export function consistencyCheck(fen: string): string {
+    return wasmConsistencyCheck(fen);
+}
+
+export type PositionValidation = {
+    representable: boolean,
+    playable: boolean,
+    issues: Array<string>,
+}
+
+// Representable positions can be drawn and edited, and only playable ones can be searched
+export function validatePosition(fen: string): PositionValidation {
+    return wasmValidatePosition(fen);
 }
 
 export function getPrettyGameStateFromFen(fen: string): GameState {
@@ -53,12 +53,13 @@ export const God = {
     Selene: "selene",
     Stymphalians: "stymphalians",
     Theseus: "theseus",
+    Triton: "triton",
     Urania: "urania",
     Zeus: "zeus",
 } as const;
 export type GodType = (typeof God)[keyof typeof God];
 
-export const WIP_GODS: Set<GodType> = new Set([]);
+export const WIP_GODS: Set<GodType> = new Set([God.Triton]);
 
 export const Square = {
     A5: 0,
@@ -135,7 +136,7 @@ export type Coord = {
 };
 
 export type PlayerGameState = {
-    god: string;
+    god: GodType;
     workers: Array<string>;
     tokens: Array<string>;
     special_text: string;
@@ -5,7 +5,7 @@ import { FullGamePlayer, type AiConfig } from "./FullGamePlayer";
 import type { AiWorker } from "../ai/ai_worker";
 import { assertUnreachable } from "../common/utils";
 import './MenuScreen.css';
-import { getBannedMatchups } from '../common/api'
+import { getBannedMatchups, getUnbalancedMatchups } from '../common/api'
 
 export type MenuScreenProps = {
     aiWorker?: AiWorker,
@@ -104,6 +104,11 @@ export function MenuScreen(props: MenuScreenProps) {
         const matchupStr = `${p1God}|${p2God}`;
         return bannedMatches.has(matchupStr);
     }, [p1God, p2God, bannedMatches]);
+    const unbalancedMatches = useMemo(() => getUnbalancedMatchups(), []);
+    const isMatchupUnbalanced = useMemo(() => {
+        const matchupStr = `${p1God}|${p2God}`;
+        return unbalancedMatches.has(matchupStr);
+    }, [p1God, p2God, unbalancedMatches]);
 
     const startGame = () => {
         if (isGameRunning) {
@@ -139,7 +144,7 @@ export function MenuScreen(props: MenuScreenProps) {
                         disabled={isMatchupBanned}
                     >
                         {
-                            isMatchupBanned ? "Banned Matchup" : "Start Game"
+                            isMatchupBanned ? "Banned Matchup" : isMatchupUnbalanced ? "Start Game (Unbalanced)" : "Start Game"
                         }
                     </button>
 
@@ -3,7 +3,19 @@ import react from '@vitejs/plugin-react'
 import wasm from "vite-plugin-wasm";
 import { VitePWA } from 'vite-plugin-pwa';
 
+// Cross-origin isolation lets a threaded wasm build share its memory with search threads
+const crossOriginIsolationHeaders = {
+    'Cross-Origin-Opener-Policy': 'same-origin',
+    'Cross-Origin-Embedder-Policy': 'require-corp',
+};
+
 export default defineConfig({
+    server: {
+        headers: crossOriginIsolationHeaders,
+    },
+    preview: {
+        headers: crossOriginIsolationHeaders,
+    },
     plugins: [
         react(),
         wasm(),